@@ -2,11 +2,20 @@
 //!
 //! These commands delegate to the service layer - no business logic here.
 
+use axum::http::StatusCode;
+use futures::StreamExt;
 use serde::Serialize;
+use tauri::ipc::{Channel, InvokeBody, Request, Response};
 use tauri::{Manager, WebviewWindow};
+use thaumic_core::sonos::compat::S1_WARNING;
+use thaumic_core::storage::backup::{self, ImportSummary};
 use thaumic_core::{
-    probe_speaker_by_ip, validate_speaker_ip, ErrorCode, ManualSpeakerConfig, NetworkHealth,
-    PlaybackSession, Speaker, ZoneGroup,
+    collect_inventory, estimate_bandwidth_with_progress, probe_speaker_by_host,
+    validate_speaker_host, validate_speaker_ip, BandwidthEstimate, ConnectedClient,
+    DiscoveryDiagnostics, EventBusStats, FirmwareInventory, GenaNotifyStats, ManualSpeakerConfig,
+    NetworkHealth, OperationInfo, OperationKind, OriginApprovalState, PlaybackSessionInfo,
+    ResourceStats, SoapSpeakerStats, Speaker, SpeakerSetup, SpeakerSetupImport, StatsSummary,
+    ThaumicError, ZoneGroup,
 };
 
 use crate::api::AppState;
@@ -28,17 +37,58 @@ pub struct AppStats {
     pub port: u16,
     /// Maximum concurrent streams allowed.
     pub max_streams: usize,
+    /// Latest process CPU/memory and network throughput sample.
+    pub resources: ResourceStats,
+    /// SOAP request statistics per speaker contacted.
+    pub soap: Vec<SoapSpeakerStats>,
+    /// GENA NOTIFY processing lag and event queue depth.
+    pub gena: GenaNotifyStats,
+    /// Events sent and dropped per priority.
+    pub events: EventBusStats,
 }
 
-/// Discovers Sonos speakers on the network.
+/// Discovers Sonos speakers on the network, leaving out ignored ones.
 #[tauri::command]
 pub async fn get_speakers(state: tauri::State<'_, AppState>) -> Result<Vec<Speaker>, CommandError> {
-    state
-        .services
-        .sonos
-        .discover_speakers()
-        .await
-        .map_err(Into::into)
+    let mut speakers = state.services.sonos.discover_speakers().await?;
+    let ignored = &state.services.sonos_state.ignored_speakers;
+    speakers.retain(|s| !ignored.is_ignored(&s.uuid, &s.ip));
+    Ok(speakers)
+}
+
+/// Discovers Sonos speakers, sending each to `on_speaker` as soon as its
+/// metadata resolves so the UI can fill in progressively.
+///
+/// Returns the number of speakers found.
+#[tauri::command]
+pub async fn discover_speakers_progressive(
+    on_speaker: Channel<Speaker>,
+    state: tauri::State<'_, AppState>,
+) -> Result<usize, CommandError> {
+    let mut speakers = state.services.sonos.discover_speakers_stream();
+    let ignored = &state.services.sonos_state.ignored_speakers;
+    let mut count = 0;
+    while let Some(speaker) = speakers.next().await {
+        let speaker = speaker?;
+        if ignored.is_ignored(&speaker.uuid, &speaker.ip) {
+            continue;
+        }
+        if let Err(e) = on_speaker.send(speaker) {
+            log::debug!("[Commands] Speaker channel closed: {}", e);
+            break;
+        }
+        count += 1;
+    }
+    Ok(count)
+}
+
+/// Returns per-method results of the last discovery run, or `None` before
+/// the first one.
+#[tauri::command]
+pub fn get_discovery_diagnostics(
+    state: tauri::State<'_, AppState>,
+) -> Option<DiscoveryDiagnostics> {
+    state.services.sonos.last_discovery_diagnostics()
 }
 
 /// Returns cached zone groups from the discovery service.
@@ -67,9 +117,50 @@ pub async fn get_stats(state: tauri::State<'_, AppState>) -> Result<AppStats, Co
         local_ip: state.services.network.get_local_ip(),
         port: state.services.network.get_port(),
         max_streams: state.config.read().streaming.max_concurrent_streams,
+        resources: state.services.resource_monitor.stats(),
+        soap: state.services.soap_client.stats(),
+        gena: state.services.discovery_service.gena_notify_stats(),
+        events: state.services.event_bridge.stats(),
     })
 }
 
+/// Returns the connected WebSocket clients and what they reported about
+/// themselves.
+#[tauri::command]
+pub fn get_clients(state: tauri::State<'_, AppState>) -> Vec<ConnectedClient> {
+    state.services.ws_manager.clients()
+}
+
+/// Returns approved, pending and denied browser extension origins.
+#[tauri::command]
+pub fn get_origin_approvals(state: tauri::State<'_, AppState>) -> OriginApprovalState {
+    state.services.origin_approvals.state()
+}
+
+/// Trusts a browser extension origin from now on.
+#[tauri::command]
+pub fn approve_origin(
+    state: tauri::State<'_, AppState>,
+    origin: String,
+) -> Result<(), CommandError> {
+    Ok(state.services.origin_approvals.approve(&origin)?)
+}
+
+/// Refuses a browser extension origin until the app restarts.
+#[tauri::command]
+pub fn deny_origin(state: tauri::State<'_, AppState>, origin: String) -> Result<(), CommandError> {
+    Ok(state.services.origin_approvals.deny(&origin)?)
+}
+
+/// Returns persistent casting statistics (most cast-to speakers, formats,
+/// hours per day).
+#[tauri::command]
+pub async fn get_stats_summary(
+    state: tauri::State<'_, AppState>,
+) -> Result<StatsSummary, CommandError> {
+    Ok(state.services.stats.summary(thaumic_core::now_millis()))
+}
+
 /// Returns the current server port.
 #[tauri::command]
 pub async fn get_server_port(state: tauri::State<'_, AppState>) -> Result<u16, CommandError> {
@@ -128,9 +219,10 @@ pub fn get_transport_states(
 /// Returns all active playback sessions.
 ///
 /// A playback session indicates a speaker that is currently casting one of our streams.
+/// Each carries its stream's metadata, including the page being cast.
 #[tauri::command]
-pub fn get_playback_sessions(state: tauri::State<'_, AppState>) -> Vec<PlaybackSession> {
-    state.services.stream_coordinator.get_all_sessions()
+pub fn get_playback_sessions(state: tauri::State<'_, AppState>) -> Vec<PlaybackSessionInfo> {
+    state.services.stream_coordinator.get_session_infos()
 }
 
 /// Clears all active streams and stops all playback.
@@ -173,10 +265,9 @@ pub async fn restart_server(state: tauri::State<'_, AppState>) -> Result<(), Com
 #[tauri::command]
 pub fn get_autostart_enabled(app: tauri::AppHandle) -> Result<bool, CommandError> {
     use tauri_plugin_autostart::ManagerExt;
-    app.autolaunch().is_enabled().map_err(|e| CommandError {
-        code: "autostart_error",
-        message: e.to_string(),
-    })
+    app.autolaunch()
+        .is_enabled()
+        .map_err(|e| CommandError::new("autostart_error", e.to_string()))
 }
 
 /// Sets whether autostart is enabled.
@@ -189,10 +280,7 @@ pub fn set_autostart_enabled(app: tauri::AppHandle, enabled: bool) -> Result<(),
     } else {
         autolaunch.disable()
     };
-    result.map_err(|e| CommandError {
-        code: "autostart_error",
-        message: e.to_string(),
-    })
+    result.map_err(|e| CommandError::new("autostart_error", e.to_string()))
 }
 
 /// Network health status response.
@@ -203,6 +291,10 @@ pub struct NetworkHealthResponse {
     pub health: NetworkHealth,
     /// Reason for the current status (if degraded).
     pub reason: Option<String>,
+    /// Warning shown while S1 speakers limit the stream formats.
+    pub compatibility_warning: Option<&'static str>,
+    /// UUIDs of the speakers running S1 software.
+    pub s1_speakers: Vec<String>,
 }
 
 /// Returns the current network health status.
@@ -226,6 +318,8 @@ pub fn get_network_health(state: tauri::State<'_, AppState>) -> NetworkHealthRes
     NetworkHealthResponse {
         health: health_state.health,
         reason: health_state.reason,
+        compatibility_warning: (!health_state.s1_speakers.is_empty()).then_some(S1_WARNING),
+        s1_speakers: health_state.s1_speakers,
     }
 }
 
@@ -272,49 +366,121 @@ pub fn get_platform() -> &'static str {
 
 /// Helper to get app data directory from AppHandle.
 fn get_app_data_dir(app: &tauri::AppHandle) -> Result<std::path::PathBuf, CommandError> {
-    app.path().app_data_dir().map_err(|e| CommandError {
-        code: "path_error",
-        message: e.to_string(),
-    })
+    app.path()
+        .app_data_dir()
+        .map_err(|e| CommandError::new("path_error", e.to_string()))
 }
 
-/// Probes an IP address to verify it's a Sonos speaker.
+/// Probes an IP address or hostname to verify it's a Sonos speaker.
 ///
-/// Validates the IP format and rejects special addresses before probing.
-/// Accepts bare IPs or URL-like formats (e.g., `http://192.168.1.100/`).
-/// Returns speaker info if valid.
+/// Validates the address and rejects special IPs before probing; hostnames
+/// (e.g., `sonos-kitchen.lan`) are resolved through DNS first.
+/// Accepts bare addresses or URL-like formats (e.g., `http://192.168.1.100/`).
+/// Returns speaker info if valid, with the resolved IP.
 #[tauri::command]
 pub async fn probe_speaker_ip(
     ip: String,
     state: tauri::State<'_, AppState>,
 ) -> Result<Speaker, CommandError> {
+    // Extract the host from URL-like input (e.g., "http://192.168.1.100:1400/")
+    let cleaned_host = extract_ip_from_input(&ip);
+
+    // Validate using shared validation (rejects IPv6, loopback, multicast, etc.)
+    let host = validate_speaker_host(&cleaned_host)?;
+
+    // Use the canonical address for probing
+    let operation = state
+        .services
+        .operations
+        .start(OperationKind::SpeakerProbe, Some(host.clone()));
+    match operation
+        .run(probe_speaker_by_host(state.services.http_client(), &host))
+        .await
+    {
+        Some(result) => result.map_err(|e| {
+            ThaumicError::from(e)
+                .with_last_seen(
+                    state
+                        .services
+                        .sonos_state
+                        .speaker_addresses
+                        .last_seen(&host),
+                )
+                .into()
+        }),
+        None => Err(ThaumicError::Cancelled(operation.id().to_string()).into()),
+    }
+}
+
+/// Measures throughput from this computer to a speaker.
+///
+/// Uploads a short burst to the speaker (a few seconds at most) and returns the
+/// estimated throughput with a recommended codec.
+#[tauri::command]
+pub async fn measure_speaker_bandwidth(
+    ip: String,
+    state: tauri::State<'_, AppState>,
+) -> Result<BandwidthEstimate, CommandError> {
     use std::net::IpAddr;
 
-    // Extract IP from URL-like input (e.g., "http://192.168.1.100:1400/")
-    let cleaned_ip = extract_ip_from_input(&ip);
+    let parsed_ip: IpAddr = ip
+        .parse()
+        .map_err(|_| ThaumicError::InvalidIp("Invalid IP address format".into()))?;
+    let ip = validate_speaker_ip(&parsed_ip)?.to_string();
 
-    // Parse IP address format
-    let parsed_ip: IpAddr = cleaned_ip.parse().map_err(|_| CommandError {
-        code: "invalid_ip",
-        message: "Invalid IP address format".to_string(),
-    })?;
+    let operation = state
+        .services
+        .operations
+        .start(OperationKind::BandwidthTest, Some(ip.clone()));
+    let estimate = estimate_bandwidth_with_progress(&ip, |sent, total| {
+        operation.set_progress(sent, Some(total))
+    });
 
-    // Validate using shared validation (rejects IPv6, loopback, multicast, etc.)
-    let ipv4 = validate_speaker_ip(&parsed_ip).map_err(|e| CommandError {
-        code: e.code(),
-        message: e.message().to_string(),
-    })?;
+    match operation.run(estimate).await {
+        Some(result) => result.map_err(|e| CommandError::from_error(StatusCode::BAD_GATEWAY, &e)),
+        None => Err(ThaumicError::Cancelled(operation.id().to_string()).into()),
+    }
+}
 
-    // Use canonical IP string for probing
-    probe_speaker_by_ip(state.services.http_client(), &ipv4.to_string())
-        .await
-        .map_err(Into::into)
+/// Returns model and software versions of every speaker in the topology,
+/// with firmware known to cause problems flagged.
+#[tauri::command]
+pub async fn get_speaker_inventory(
+    state: tauri::State<'_, AppState>,
+) -> Result<FirmwareInventory, CommandError> {
+    let discovery_service = &state.services.discovery_service;
+    let members: Vec<_> = discovery_service
+        .sonos_state()
+        .groups
+        .read()
+        .iter()
+        .flat_map(|group| group.members.iter().cloned())
+        .collect();
+    Ok(collect_inventory(discovery_service.http_client(), &members).await)
 }
 
-/// Extracts an IP address from user input.
+/// Lists in-flight long-running operations (discovery runs, speaker probes,
+/// bandwidth tests) with their progress.
+#[tauri::command]
+pub fn get_operations(state: tauri::State<'_, AppState>) -> Vec<OperationInfo> {
+    state.services.operations.list()
+}
+
+/// Cancels an in-flight operation. The command that started it fails with
+/// `operation_cancelled`.
+#[tauri::command]
+pub fn cancel_operation(id: String, state: tauri::State<'_, AppState>) -> Result<(), CommandError> {
+    if !state.services.operations.cancel(&id) {
+        return Err(ThaumicError::OperationNotFound(id).into());
+    }
+    Ok(())
+}
+
+/// Extracts an IP address or hostname from user input.
 ///
 /// Handles common formats users might enter:
 /// - Bare IP: `192.168.1.100`
+/// - Hostname: `sonos-kitchen.lan`
 /// - With protocol: `http://192.168.1.100`
 /// - With port: `192.168.1.100:1400`
 /// - Full URL: `http://192.168.1.100:1400/xml/device_description.xml`
@@ -358,9 +524,11 @@ fn extract_ip_from_input(input: &str) -> String {
     s.to_string()
 }
 
-/// Adds a manually configured speaker IP address.
+/// Adds a manually configured speaker IP address or hostname.
 ///
-/// The IP should be pre-validated with `probe_speaker_ip` first.
+/// The address should be pre-validated with `probe_speaker_ip` first.
+/// Hostnames are stored as given and resolved again on every topology
+/// refresh, so the speaker is still found after its IP changes.
 /// Uses atomic file operations to prevent race conditions.
 /// Triggers a topology refresh after adding to ensure groups are updated.
 #[tauri::command]
@@ -370,11 +538,10 @@ pub fn add_manual_speaker_ip(
     ip: String,
 ) -> Result<(), CommandError> {
     let app_data_dir = get_app_data_dir(&app)?;
+    let host = validate_speaker_host(&extract_ip_from_input(&ip))?;
 
-    ManualSpeakerConfig::add_ip_atomic(&app_data_dir, ip).map_err(|e| CommandError {
-        code: "save_error",
-        message: e.to_string(),
-    })?;
+    ManualSpeakerConfig::add_ip_atomic(&app_data_dir, host)
+        .map_err(|e| ThaumicError::SaveFailed(e.to_string()))?;
 
     // Trigger topology refresh so groups update with the new speaker
     state.services.discovery_service.trigger_refresh();
@@ -394,10 +561,8 @@ pub fn remove_manual_speaker_ip(
 ) -> Result<(), CommandError> {
     let app_data_dir = get_app_data_dir(&app)?;
 
-    ManualSpeakerConfig::remove_ip_atomic(&app_data_dir, &ip).map_err(|e| CommandError {
-        code: "save_error",
-        message: e.to_string(),
-    })?;
+    ManualSpeakerConfig::remove_ip_atomic(&app_data_dir, &ip)
+        .map_err(|e| ThaumicError::SaveFailed(e.to_string()))?;
 
     // Trigger topology refresh so groups update without the removed speaker
     state.services.discovery_service.trigger_refresh();
@@ -414,6 +579,126 @@ pub fn get_manual_speaker_ips(app: tauri::AppHandle) -> Result<Vec<String>, Comm
     Ok(config.speaker_ips)
 }
 
+/// Returns the manual and ignored speakers, to import into another
+/// installation with `import_manual_speakers` or the server's
+/// `POST /api/speakers/manual/import`.
+#[tauri::command]
+pub fn export_manual_speakers(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+) -> Result<SpeakerSetup, CommandError> {
+    let app_data_dir = get_app_data_dir(&app)?;
+    Ok(SpeakerSetup::export(
+        &app_data_dir,
+        &state.services.sonos_state.ignored_speakers,
+    ))
+}
+
+/// Adds exported manual and ignored speakers to the existing ones.
+///
+/// Triggers a topology refresh if anything was added.
+#[tauri::command]
+pub fn import_manual_speakers(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+    setup: SpeakerSetup,
+) -> Result<SpeakerSetupImport, CommandError> {
+    let app_data_dir = get_app_data_dir(&app)?;
+    let summary = setup.import(&app_data_dir, &state.services.sonos_state.ignored_speakers)?;
+    if summary.manual_speakers_added + summary.ignored_speakers_added > 0 {
+        state.services.discovery_service.trigger_refresh();
+    }
+    Ok(summary)
+}
+
+/// Ignores a speaker by UUID or IP, leaving it out of discovery, topology
+/// and events.
+///
+/// Returns the entry as stored. Triggers a topology refresh after adding.
+#[tauri::command]
+pub fn add_ignored_speaker(
+    state: tauri::State<'_, AppState>,
+    speaker: String,
+) -> Result<String, CommandError> {
+    let entry = state.services.sonos_state.ignored_speakers.add(&speaker)?;
+    state.services.discovery_service.trigger_refresh();
+    Ok(entry)
+}
+
+/// Stops ignoring a speaker added with `add_ignored_speaker`.
+///
+/// Triggers a topology refresh after removing.
+#[tauri::command]
+pub fn remove_ignored_speaker(
+    state: tauri::State<'_, AppState>,
+    speaker: String,
+) -> Result<(), CommandError> {
+    if state
+        .services
+        .sonos_state
+        .ignored_speakers
+        .remove(&speaker)?
+    {
+        state.services.discovery_service.trigger_refresh();
+    }
+    Ok(())
+}
+
+/// Returns the ignored speaker UUIDs and IPs.
+#[tauri::command]
+pub fn get_ignored_speakers(state: tauri::State<'_, AppState>) -> Vec<String> {
+    state.services.sonos_state.ignored_speakers.entries()
+}
+
+/// Returns the subnets saved with `set_remote_subnets`.
+#[tauri::command]
+pub fn get_remote_subnets(state: tauri::State<'_, AppState>) -> Vec<String> {
+    state.services.remote_subnets.saved()
+}
+
+/// Replaces the subnets on other VLANs searched by unicast during discovery.
+///
+/// Returns the ranges as stored. Triggers a topology refresh after saving.
+#[tauri::command]
+pub fn set_remote_subnets(
+    state: tauri::State<'_, AppState>,
+    subnets: Vec<String>,
+) -> Result<Vec<String>, CommandError> {
+    let saved = state.services.remote_subnets.set_saved(&subnets)?;
+    state.services.discovery_service.trigger_refresh();
+    Ok(saved)
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Backup Commands
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Creates a backup of the app data as a zip archive, returned as raw bytes
+/// for the frontend to save.
+#[tauri::command]
+pub fn export_backup(app: tauri::AppHandle) -> Result<Response, CommandError> {
+    let data_dir = get_app_data_dir(&app)?;
+    Ok(Response::new(backup::export(&data_dir, None)?))
+}
+
+/// Restores a backup made by `export_backup`, sent as the raw request body.
+///
+/// The backup is applied when the app next starts.
+#[tauri::command]
+pub fn import_backup(
+    app: tauri::AppHandle,
+    request: Request<'_>,
+) -> Result<ImportSummary, CommandError> {
+    let InvokeBody::Raw(archive) = request.body() else {
+        return Err(CommandError::new(
+            "invalid_request",
+            "Expected the backup archive as raw bytes",
+        ));
+    };
+    let data_dir = get_app_data_dir(&app)?;
+    Ok(backup::import(&data_dir, None, archive)?)
+}
+
 // ─────────────────────────────────────────────────────────────────────────────
 // Window Visibility Commands
 // ─────────────────────────────────────────────────────────────────────────────
@@ -480,6 +765,10 @@ mod tests {
     #[test]
     fn extract_ip_with_port() {
         assert_eq!(extract_ip_from_input("192.168.1.100:1400"), "192.168.1.100");
+        assert_eq!(
+            extract_ip_from_input("http://sonos-kitchen.lan:1400/"),
+            "sonos-kitchen.lan"
+        );
     }
 
     #[test]
@@ -0,0 +1,129 @@
+//! Lingering mode for tray-only (auto-start) launches.
+//!
+//! When the app starts minimized, the HTTP/WebSocket server comes up so the
+//! extension can connect, but speaker discovery stays paused: no SSDP/mDNS
+//! scans and no GENA subscriptions. Discovery resumes as soon as something
+//! needs it and pauses again after a period of inactivity.
+//!
+//! Activity is any of:
+//! - a connected WebSocket client (the extension)
+//! - an active stream
+//! - the dashboard window being visible
+
+use std::time::{Duration, Instant};
+
+use tauri::Manager;
+
+use super::AppState;
+
+/// How often activity is checked.
+const ACTIVITY_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// How long the app must be inactive before discovery pauses again.
+const IDLE_TIMEOUT: Duration = Duration::from_secs(5 * 60);
+
+/// Transition to apply to the discovery services.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum LingerAction {
+    /// Resume discovery (activity seen while paused).
+    Resume,
+    /// Pause discovery (idle for at least `IDLE_TIMEOUT`).
+    Pause,
+}
+
+/// Decides whether discovery should change state.
+fn decide(has_activity: bool, is_paused: bool, idle_for: Duration) -> Option<LingerAction> {
+    match (has_activity, is_paused) {
+        (true, true) => Some(LingerAction::Resume),
+        (false, false) if idle_for >= IDLE_TIMEOUT => Some(LingerAction::Pause),
+        _ => None,
+    }
+}
+
+/// Returns whether anything currently needs discovery running.
+fn has_activity(state: &AppState) -> bool {
+    if state.services.ws_manager.connection_count() > 0
+        || state.services.stream_coordinator.stream_count() > 0
+    {
+        return true;
+    }
+
+    state
+        .app_handle
+        .read()
+        .as_ref()
+        .and_then(|h| h.get_webview_window("main"))
+        .and_then(|w| w.is_visible().ok())
+        .unwrap_or(false)
+}
+
+/// Spawns the lingering controller.
+///
+/// Discovery must already be paused; the controller only resumes and
+/// re-pauses it. Stops when the services are shut down.
+pub(super) fn spawn(state: AppState) {
+    tauri::async_runtime::spawn(async move {
+        let topology = state.services.discovery_service.topology_monitor().clone();
+        let cancel = state.services.cancel_token.clone();
+        let mut last_activity = Instant::now();
+
+        log::info!("[Lingering] Discovery paused until a client connects");
+
+        loop {
+            tokio::select! {
+                _ = cancel.cancelled() => break,
+                _ = tokio::time::sleep(ACTIVITY_POLL_INTERVAL) => {}
+            }
+
+            let active = has_activity(&state);
+            if active {
+                last_activity = Instant::now();
+            }
+
+            match decide(active, topology.is_paused(), last_activity.elapsed()) {
+                Some(LingerAction::Resume) => {
+                    if topology.resume() {
+                        log::info!("[Lingering] Activity detected, resuming discovery");
+                    }
+                }
+                Some(LingerAction::Pause) => {
+                    if topology.pause() {
+                        log::info!(
+                            "[Lingering] Idle for {}s, pausing discovery",
+                            IDLE_TIMEOUT.as_secs()
+                        );
+                    }
+                }
+                None => {}
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resumes_on_activity_while_paused() {
+        assert_eq!(
+            decide(true, true, Duration::ZERO),
+            Some(LingerAction::Resume)
+        );
+    }
+
+    #[test]
+    fn pauses_only_after_idle_timeout() {
+        assert_eq!(decide(false, false, IDLE_TIMEOUT / 2), None);
+        assert_eq!(
+            decide(false, false, IDLE_TIMEOUT),
+            Some(LingerAction::Pause)
+        );
+    }
+
+    #[test]
+    fn keeps_state_otherwise() {
+        assert_eq!(decide(true, false, IDLE_TIMEOUT), None);
+        assert_eq!(decide(false, true, IDLE_TIMEOUT), None);
+    }
+}
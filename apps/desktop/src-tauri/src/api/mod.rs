@@ -18,6 +18,7 @@ use thaumic_core::{AudioSource, CaptureError, CaptureSourceFactory};
 use crate::tauri_emitter::TauriEventEmitter;
 
 pub mod commands;
+mod lingering;
 
 // ─────────────────────────────────────────────────────────────────────────────
 // CaptureSourceFactory implementation (Windows only)
@@ -85,7 +86,8 @@ pub struct AppState {
     ///
     /// When true, the window should remain hidden on startup (tray-only mode).
     /// The frontend checks this via `show_main_window` command to decide
-    /// whether to show the window after initialization.
+    /// whether to show the window after initialization. Also enables lingering
+    /// mode, which keeps discovery paused until a client connects.
     started_minimized: bool,
     /// Cached artwork source, resolved once at startup.
     ///
@@ -123,7 +125,7 @@ impl AppState {
         let tauri_emitter = Arc::new(TauriEventEmitter::new());
         services
             .event_bridge
-            .set_external_emitter(Arc::clone(&tauri_emitter) as Arc<dyn thaumic_core::EventEmitter>);
+            .add_external_emitter(Arc::clone(&tauri_emitter) as Arc<dyn thaumic_core::EventEmitter>);
 
         Self {
             services,
@@ -197,14 +199,15 @@ impl AppState {
         // Set app handle on TauriEventEmitter for frontend events
         self.tauri_emitter.set_app_handle(handle.clone());
 
-        // Set app data dir for manual speaker configuration
+        // Set app data dir for manual speaker configuration and statistics
         match handle.path().app_data_dir() {
-            Ok(path) => self
-                .services
-                .discovery_service
-                .set_app_data_dir(path.clone()),
+            Ok(path) => {
+                if let Err(e) = self.services.set_app_data_dir(path) {
+                    log::error!("{}; settings changes will not persist until it is fixed", e);
+                }
+            }
             Err(e) => log::warn!(
-                "Failed to get app data dir, manual speakers will not persist: {}",
+                "Failed to get app data dir, manual speakers and stats will not persist: {}",
                 e
             ),
         }
@@ -242,9 +245,18 @@ impl AppState {
 
         log::info!("Starting network services...");
 
+        // In tray-only mode, keep discovery idle until a client needs it
+        if self.started_minimized {
+            self.services.discovery_service.topology_monitor().pause();
+        }
+
         // Start background tasks (GENA renewal, topology monitor) on Tauri's runtime
         self.start_background_tasks();
 
+        if self.started_minimized {
+            lingering::spawn(self.clone());
+        }
+
         // Build the core AppState for the HTTP server
         let core_state = self.build_core_app_state();
 
@@ -3,44 +3,54 @@
 //! This module provides `CommandError` for Tauri command handlers,
 //! with conversions from thaumic-core error types.
 
+use axum::http::StatusCode;
 use serde::Serialize;
-use thaumic_core::ThaumicError;
+use thaumic_core::{ApiErrorResponse, ErrorCode, ThaumicError};
 
 /// Structured error type for Tauri commands.
 ///
-/// Provides machine-readable error codes alongside human-readable messages,
-/// enabling the frontend to handle errors programmatically.
+/// Serializes exactly like an HTTP API error body
+/// (`{ error, message, messageKey, status, details? }`), so the frontend
+/// handles failures the same way whichever transport they came through.
 #[derive(Debug, Serialize)]
-pub struct CommandError {
-    /// Machine-readable error code (e.g., "discovery_failed", "network_error").
-    pub code: &'static str,
-    /// Human-readable error message.
-    pub message: String,
+#[serde(transparent)]
+pub struct CommandError(ApiErrorResponse);
+
+impl CommandError {
+    /// Creates an internal error for failures outside thaumic-core
+    /// (plugins, platform paths).
+    pub fn new(code: &'static str, message: impl Into<String>) -> Self {
+        Self(ApiErrorResponse {
+            error: code,
+            message: message.into(),
+            message_key: format!("error.{}", code),
+            status: StatusCode::INTERNAL_SERVER_ERROR.as_u16(),
+            details: None,
+        })
+    }
+
+    /// Creates a command error from any core error that provides an error code.
+    ///
+    /// `status` should match what the equivalent HTTP endpoint returns.
+    pub fn from_error<E: ErrorCode + std::fmt::Display>(status: StatusCode, err: &E) -> Self {
+        Self(ApiErrorResponse::from_error(status, err))
+    }
 }
 
 impl From<ThaumicError> for CommandError {
     fn from(err: ThaumicError) -> Self {
-        Self {
-            code: err.code(),
-            message: err.to_string(),
-        }
+        Self::from_error(err.status_code(), &err)
     }
 }
 
 impl From<thaumic_core::sonos::discovery::DiscoveryError> for CommandError {
     fn from(err: thaumic_core::sonos::discovery::DiscoveryError) -> Self {
-        use thaumic_core::sonos::discovery::DiscoveryError;
-        let code = match &err {
-            DiscoveryError::SocketBind(_) => "socket_bind_failed",
-            DiscoveryError::NoInterfaces => "no_network_interfaces",
-            DiscoveryError::MdnsDaemon(_) => "mdns_daemon_failed",
-            DiscoveryError::AllMethodsFailed(_) => "all_discovery_methods_failed",
-            DiscoveryError::IpUnreachable(_) => "ip_unreachable",
-            DiscoveryError::NotSonosDevice(_) => "not_sonos_device",
-        };
-        Self {
-            code,
-            message: err.to_string(),
-        }
+        ThaumicError::from(err).into()
+    }
+}
+
+impl From<thaumic_core::IpValidationError> for CommandError {
+    fn from(err: thaumic_core::IpValidationError) -> Self {
+        Self::from_error(StatusCode::BAD_REQUEST, &err)
     }
 }
@@ -21,11 +21,16 @@ use tauri::{Manager, RunEvent};
 use tauri_plugin_log::{Target, TargetKind};
 
 use crate::api::commands::{
-    add_manual_speaker_ip, clear_all_connections, clear_all_streams, get_autostart_enabled,
-    get_capture_capabilities, get_groups, get_manual_speaker_ips, get_network_health, get_platform,
-    get_playback_sessions, get_server_port, get_speakers, get_stats, get_transport_states,
-    probe_speaker_ip, refresh_topology, remove_manual_speaker_ip, restart_server,
-    set_autostart_enabled, show_main_window, start_network_services, start_playback,
+    add_ignored_speaker, add_manual_speaker_ip, approve_origin, cancel_operation,
+    clear_all_connections, clear_all_streams, deny_origin, discover_speakers_progressive,
+    export_backup, export_manual_speakers, get_autostart_enabled, get_capture_capabilities,
+    get_clients, get_discovery_diagnostics, get_groups, get_ignored_speakers,
+    get_manual_speaker_ips, get_network_health, get_operations, get_origin_approvals, get_platform,
+    get_playback_sessions, get_remote_subnets, get_server_port, get_speaker_inventory,
+    get_speakers, get_stats, get_stats_summary, get_transport_states, import_backup,
+    import_manual_speakers, measure_speaker_bandwidth, probe_speaker_ip, refresh_topology,
+    remove_ignored_speaker, remove_manual_speaker_ip, restart_server, set_autostart_enabled,
+    set_remote_subnets, show_main_window, start_network_services, start_playback,
 };
 use crate::api::AppState;
 
@@ -57,8 +62,14 @@ pub fn run() {
         ))
         .invoke_handler(tauri::generate_handler![
             get_speakers,
+            discover_speakers_progressive,
             get_groups,
             get_stats,
+            get_clients,
+            get_origin_approvals,
+            approve_origin,
+            deny_origin,
+            get_stats_summary,
             get_transport_states,
             get_playback_sessions,
             get_network_health,
@@ -73,9 +84,23 @@ pub fn run() {
             get_autostart_enabled,
             set_autostart_enabled,
             probe_speaker_ip,
+            measure_speaker_bandwidth,
+            get_speaker_inventory,
+            get_operations,
+            cancel_operation,
             add_manual_speaker_ip,
             remove_manual_speaker_ip,
             get_manual_speaker_ips,
+            export_manual_speakers,
+            import_manual_speakers,
+            get_discovery_diagnostics,
+            add_ignored_speaker,
+            remove_ignored_speaker,
+            get_ignored_speakers,
+            get_remote_subnets,
+            set_remote_subnets,
+            export_backup,
+            import_backup,
             show_main_window,
             get_capture_capabilities
         ])
@@ -120,6 +145,9 @@ pub fn run() {
             // Initialize system tray
             ui::setup_tray(app)?;
 
+            // Publish now-playing info and listen for OS media keys
+            ui::setup_media_keys(app);
+
             Ok(())
         })
         .on_window_event(|window, event| {
@@ -139,7 +167,7 @@ pub fn run() {
                     }
                 }
                 tauri::WindowEvent::ThemeChanged(theme) => {
-                    // Update tray icon for new theme (Windows only, no-op on other platforms)
+                    // Swap light/dark tray icons for the new theme
                     if let Some(tray_state) = window.app_handle().try_state::<ui::TrayState>() {
                         tray_state.update_for_theme(*theme);
                     }
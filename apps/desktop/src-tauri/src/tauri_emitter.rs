@@ -9,7 +9,8 @@ use std::sync::Arc;
 use parking_lot::RwLock;
 use tauri::{AppHandle, Emitter};
 use thaumic_core::{
-    EventEmitter, LatencyEvent, NetworkEvent, SonosEvent, StreamEvent, TopologyEvent,
+    EventEmitter, LatencyEvent, NetworkEvent, ResourceEvent, SonosEvent, StateEvent, StreamEvent,
+    TopologyEvent,
 };
 
 /// Event emitter that forwards events to the Tauri frontend.
@@ -157,6 +158,77 @@ impl EventEmitter for TauriEventEmitter {
                     },
                 );
             }
+            // The dashboard refreshes on the playback-started that precedes a
+            // resume and on the playback-stopped when a suspension expires
+            StreamEvent::PlaybackSuspended { .. }
+            | StreamEvent::PlaybackStoppedExternally { .. }
+            | StreamEvent::PlaybackResumed { .. } => {}
+            // Always followed by Ended, which the dashboard already handles
+            StreamEvent::ListenersLost { .. } => {}
+            StreamEvent::LockedSpeakerChanged {
+                stream_id,
+                speaker_ip,
+                change,
+                ..
+            } => {
+                #[derive(serde::Serialize, Clone)]
+                #[serde(rename_all = "camelCase")]
+                struct LockedSpeakerChangedPayload {
+                    stream_id: String,
+                    speaker_ip: String,
+                    change: thaumic_core::LockedSpeakerChange,
+                }
+                self.emit_to_tauri(
+                    "locked-speaker-changed",
+                    LockedSpeakerChangedPayload {
+                        stream_id: stream_id.clone(),
+                        speaker_ip: speaker_ip.clone(),
+                        change: *change,
+                    },
+                );
+            }
+            StreamEvent::LabelChanged {
+                stream_id, label, ..
+            } => {
+                #[derive(serde::Serialize, Clone)]
+                #[serde(rename_all = "camelCase")]
+                struct LabelChangedPayload {
+                    stream_id: String,
+                    label: Option<String>,
+                }
+                self.emit_to_tauri(
+                    "stream-label-changed",
+                    LabelChangedPayload {
+                        stream_id: stream_id.clone(),
+                        label: label.clone(),
+                    },
+                );
+            }
+            // Diagnostic only; the dashboard doesn't display buffer sizing
+            StreamEvent::BufferAdapted { .. } => {}
+            // Surfaced by the extension, which owns tab selection
+            StreamEvent::FeedbackLoopSuspected { .. } => {}
+            StreamEvent::MetadataUpdated {
+                stream_id,
+                metadata,
+                ..
+            } => {
+                #[derive(serde::Serialize, Clone)]
+                #[serde(rename_all = "camelCase")]
+                struct MetadataUpdatedPayload {
+                    stream_id: String,
+                    metadata: thaumic_core::StreamMetadata,
+                }
+                self.emit_to_tauri(
+                    "stream-metadata-updated",
+                    MetadataUpdatedPayload {
+                        stream_id: stream_id.clone(),
+                        metadata: metadata.clone(),
+                    },
+                );
+            }
+            // Pushed to the extension's progress UI
+            StreamEvent::Position { .. } => {}
         }
     }
 
@@ -199,6 +271,36 @@ impl EventEmitter for TauriEventEmitter {
                     },
                 );
             }
+            NetworkEvent::OriginApprovalRequested { origin, .. } => {
+                #[derive(serde::Serialize, Clone)]
+                #[serde(rename_all = "camelCase")]
+                struct OriginApprovalPayload {
+                    origin: String,
+                }
+                self.emit_to_tauri(
+                    "origin-approval-requested",
+                    OriginApprovalPayload {
+                        origin: origin.clone(),
+                    },
+                );
+            }
+            NetworkEvent::CrossSubnetSpeakers {
+                speaker_ips, hints, ..
+            } => {
+                #[derive(serde::Serialize, Clone)]
+                #[serde(rename_all = "camelCase")]
+                struct CrossSubnetPayload {
+                    speaker_ips: Vec<String>,
+                    hints: Vec<thaumic_core::CrossSubnetHint>,
+                }
+                self.emit_to_tauri(
+                    "cross-subnet-speakers",
+                    CrossSubnetPayload {
+                        speaker_ips: speaker_ips.clone(),
+                        hints: hints.clone(),
+                    },
+                );
+            }
         }
     }
 
@@ -217,6 +319,15 @@ impl EventEmitter for TauriEventEmitter {
                     },
                 );
             }
+            TopologyEvent::SpeakerAdded { .. } => {
+                self.emit_to_tauri("speaker-added", event.clone());
+            }
+            TopologyEvent::SpeakerRemoved { .. } => {
+                self.emit_to_tauri("speaker-removed", event.clone());
+            }
+            TopologyEvent::SpeakerIpChanged { .. } => {
+                self.emit_to_tauri("speaker-ip-changed", event.clone());
+            }
         }
     }
 
@@ -224,4 +335,51 @@ impl EventEmitter for TauriEventEmitter {
         // Latency events are not forwarded to the Tauri frontend
         // They're only sent to the extension via WebSocket
     }
+
+    fn emit_resource(&self, event: ResourceEvent) {
+        #[derive(serde::Serialize, Clone)]
+        #[serde(rename_all = "camelCase")]
+        struct CpuOverloadPayload {
+            overloaded: bool,
+            cpu_percent: f32,
+        }
+        #[derive(serde::Serialize, Clone)]
+        #[serde(rename_all = "camelCase")]
+        struct StorageHealthPayload {
+            path: String,
+            /// Why the data directory can't be written, or `None` once it can.
+            issue: Option<thaumic_core::StorageIssue>,
+        }
+        match event {
+            ResourceEvent::CpuOverload { cpu_percent, .. } => self.emit_to_tauri(
+                "cpu-overload-changed",
+                CpuOverloadPayload {
+                    overloaded: true,
+                    cpu_percent,
+                },
+            ),
+            ResourceEvent::CpuRecovered { cpu_percent, .. } => self.emit_to_tauri(
+                "cpu-overload-changed",
+                CpuOverloadPayload {
+                    overloaded: false,
+                    cpu_percent,
+                },
+            ),
+            ResourceEvent::StorageDegraded { path, issue, .. } => self.emit_to_tauri(
+                "storage-health-changed",
+                StorageHealthPayload {
+                    path,
+                    issue: Some(issue),
+                },
+            ),
+            ResourceEvent::StorageRecovered { path, .. } => self.emit_to_tauri(
+                "storage-health-changed",
+                StorageHealthPayload { path, issue: None },
+            ),
+        }
+    }
+
+    fn emit_state(&self, event: StateEvent) {
+        self.emit_to_tauri("sonos-state-changed", event);
+    }
 }
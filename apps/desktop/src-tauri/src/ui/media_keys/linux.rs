@@ -0,0 +1,229 @@
+//! Linux media controls via the MPRIS D-Bus interface.
+//!
+//! Registers `org.mpris.MediaPlayer2.thaumic_cast` on the session bus using the
+//! GIO D-Bus bindings already pulled in by GTK. Desktop media applets and
+//! `playerctl` read `PlaybackStatus`/`Metadata` and call Play/Pause/PlayPause/Stop.
+//!
+//! GIO dispatches method calls on the GLib main context, which Tauri runs on the
+//! main thread, so the connection lives in a thread-local like other backends.
+
+use std::cell::RefCell;
+use std::sync::Arc;
+
+use gio::glib::{self, ToVariant, Variant, VariantDict};
+use gio::prelude::*;
+use gio::{BusNameOwnerFlags, BusType, DBusConnection, DBusNodeInfo};
+use parking_lot::Mutex;
+use tokio::sync::mpsc;
+
+use super::{MediaKeyAction, NowPlaying};
+
+const BUS_NAME: &str = "org.mpris.MediaPlayer2.thaumic_cast";
+const OBJECT_PATH: &str = "/org/mpris/MediaPlayer2";
+const ROOT_IFACE: &str = "org.mpris.MediaPlayer2";
+const PLAYER_IFACE: &str = "org.mpris.MediaPlayer2.Player";
+const TRACK_ID: &str = "/org/thaumic_cast/track/live";
+
+/// Introspection data for the two required MPRIS interfaces.
+const MPRIS_XML: &str = r#"
+<node>
+  <interface name="org.mpris.MediaPlayer2">
+    <method name="Raise"/>
+    <method name="Quit"/>
+    <property name="CanQuit" type="b" access="read"/>
+    <property name="CanRaise" type="b" access="read"/>
+    <property name="HasTrackList" type="b" access="read"/>
+    <property name="Identity" type="s" access="read"/>
+    <property name="SupportedUriSchemes" type="as" access="read"/>
+    <property name="SupportedMimeTypes" type="as" access="read"/>
+  </interface>
+  <interface name="org.mpris.MediaPlayer2.Player">
+    <method name="Next"/>
+    <method name="Previous"/>
+    <method name="Pause"/>
+    <method name="PlayPause"/>
+    <method name="Stop"/>
+    <method name="Play"/>
+    <method name="Seek"><arg direction="in" type="x" name="Offset"/></method>
+    <method name="SetPosition">
+      <arg direction="in" type="o" name="TrackId"/>
+      <arg direction="in" type="x" name="Position"/>
+    </method>
+    <method name="OpenUri"><arg direction="in" type="s" name="Uri"/></method>
+    <signal name="Seeked"><arg type="x" name="Position"/></signal>
+    <property name="PlaybackStatus" type="s" access="read"/>
+    <property name="Rate" type="d" access="read"/>
+    <property name="Metadata" type="a{sv}" access="read"/>
+    <property name="Volume" type="d" access="read"/>
+    <property name="Position" type="x" access="read"/>
+    <property name="MinimumRate" type="d" access="read"/>
+    <property name="MaximumRate" type="d" access="read"/>
+    <property name="CanGoNext" type="b" access="read"/>
+    <property name="CanGoPrevious" type="b" access="read"/>
+    <property name="CanPlay" type="b" access="read"/>
+    <property name="CanPause" type="b" access="read"/>
+    <property name="CanSeek" type="b" access="read"/>
+    <property name="CanControl" type="b" access="read"/>
+  </interface>
+</node>
+"#;
+
+/// Registered MPRIS object and the state its properties are read from.
+struct Mpris {
+    connection: DBusConnection,
+    now_playing: Arc<Mutex<Option<NowPlaying>>>,
+}
+
+thread_local! {
+    /// MPRIS registration, owned by the main thread.
+    static MPRIS: RefCell<Option<Mpris>> = const { RefCell::new(None) };
+}
+
+/// Registers the MPRIS object and claims the bus name.
+pub fn init(
+    _app: &tauri::App,
+    actions: mpsc::UnboundedSender<MediaKeyAction>,
+) -> Result<(), String> {
+    let connection =
+        gio::bus_get_sync(BusType::Session, gio::Cancellable::NONE).map_err(|e| e.to_string())?;
+    let node = DBusNodeInfo::for_xml(MPRIS_XML).map_err(|e| e.to_string())?;
+    let now_playing: Arc<Mutex<Option<NowPlaying>>> = Arc::new(Mutex::new(None));
+
+    let root = node
+        .lookup_interface(ROOT_IFACE)
+        .ok_or("missing MPRIS root interface")?;
+    connection
+        .register_object(
+            OBJECT_PATH,
+            &root,
+            |_, _, _, _, _, _, invocation| invocation.return_value(None),
+            |_, _, _, _, property| root_property(property),
+            |_, _, _, _, _, _| false,
+        )
+        .map_err(|e| e.to_string())?;
+
+    let player = node
+        .lookup_interface(PLAYER_IFACE)
+        .ok_or("missing MPRIS player interface")?;
+    let state = Arc::clone(&now_playing);
+    connection
+        .register_object(
+            OBJECT_PATH,
+            &player,
+            move |_, _, _, _, method, _, invocation| {
+                if let Some(action) = map_method(method) {
+                    let _ = actions.send(action);
+                }
+                invocation.return_value(None);
+            },
+            move |_, _, _, _, property| player_property(property, state.lock().as_ref()),
+            |_, _, _, _, _, _| false,
+        )
+        .map_err(|e| e.to_string())?;
+
+    gio::bus_own_name_on_connection(
+        &connection,
+        BUS_NAME,
+        BusNameOwnerFlags::NONE,
+        |_, name| log::debug!("[MediaKeys] Acquired D-Bus name {}", name),
+        |_, name| log::warn!("[MediaKeys] Lost D-Bus name {}", name),
+    );
+
+    MPRIS.with(|cell| {
+        *cell.borrow_mut() = Some(Mpris {
+            connection,
+            now_playing,
+        })
+    });
+    Ok(())
+}
+
+/// Updates the exported state and notifies listeners. Must run on the main thread.
+pub fn update(now_playing: Option<&NowPlaying>) {
+    MPRIS.with(|cell| {
+        let cell = cell.borrow();
+        let Some(mpris) = cell.as_ref() else {
+            return;
+        };
+        *mpris.now_playing.lock() = now_playing.cloned();
+
+        let changed = VariantDict::new(None);
+        changed.insert_value("PlaybackStatus", &playback_status(now_playing).to_variant());
+        changed.insert_value("Metadata", &metadata(now_playing));
+        let args = Variant::tuple_from_iter([
+            PLAYER_IFACE.to_variant(),
+            changed.end(),
+            Vec::<String>::new().to_variant(),
+        ]);
+
+        if let Err(e) = mpris.connection.emit_signal(
+            None,
+            OBJECT_PATH,
+            "org.freedesktop.DBus.Properties",
+            "PropertiesChanged",
+            Some(&args),
+        ) {
+            log::warn!("[MediaKeys] Failed to emit MPRIS PropertiesChanged: {}", e);
+        }
+    });
+}
+
+/// Maps an MPRIS player method to a media key action (others are accepted as no-ops).
+fn map_method(method: &str) -> Option<MediaKeyAction> {
+    match method {
+        "Play" => Some(MediaKeyAction::Play),
+        "Pause" => Some(MediaKeyAction::Pause),
+        "PlayPause" => Some(MediaKeyAction::Toggle),
+        "Stop" => Some(MediaKeyAction::Stop),
+        _ => None,
+    }
+}
+
+fn root_property(property: &str) -> Variant {
+    match property {
+        "Identity" => "Thaumic Cast".to_variant(),
+        "SupportedUriSchemes" | "SupportedMimeTypes" => Vec::<String>::new().to_variant(),
+        // CanQuit, CanRaise, HasTrackList
+        _ => false.to_variant(),
+    }
+}
+
+fn player_property(property: &str, now_playing: Option<&NowPlaying>) -> Variant {
+    match property {
+        "PlaybackStatus" => playback_status(now_playing).to_variant(),
+        "Metadata" => metadata(now_playing),
+        "Rate" | "Volume" | "MinimumRate" | "MaximumRate" => 1.0f64.to_variant(),
+        "Position" => 0i64.to_variant(),
+        "CanPlay" | "CanPause" | "CanControl" => now_playing.is_some().to_variant(),
+        // CanGoNext, CanGoPrevious, CanSeek
+        _ => false.to_variant(),
+    }
+}
+
+fn playback_status(now_playing: Option<&NowPlaying>) -> &'static str {
+    match now_playing {
+        Some(np) if np.playing => "Playing",
+        Some(_) => "Paused",
+        None => "Stopped",
+    }
+}
+
+fn metadata(now_playing: Option<&NowPlaying>) -> Variant {
+    let dict = VariantDict::new(None);
+    if let Some(np) = now_playing {
+        if let Ok(track_id) = glib::variant::ObjectPath::try_from(TRACK_ID) {
+            dict.insert_value("mpris:trackid", &track_id.to_variant());
+        }
+        dict.insert_value("xesam:title", &np.title.to_variant());
+        if let Some(artist) = &np.artist {
+            dict.insert_value("xesam:artist", &vec![artist.clone()].to_variant());
+        }
+        if let Some(album) = &np.album {
+            dict.insert_value("xesam:album", &album.to_variant());
+        }
+        if let Some(url) = &np.artwork_url {
+            dict.insert_value("mpris:artUrl", &url.to_variant());
+        }
+    }
+    dict.end()
+}
@@ -0,0 +1,126 @@
+//! macOS media controls via `MPNowPlayingInfoCenter` and `MPRemoteCommandCenter`.
+//!
+//! The MediaPlayer framework is addressed through the Objective-C runtime.
+//! Both centers are process-wide singletons; `update` must run on the main
+//! thread, which `mod.rs` guarantees via `run_on_main_thread`.
+
+use block2::RcBlock;
+use objc2::msg_send;
+use objc2::rc::Retained;
+use objc2::runtime::{AnyClass, AnyObject};
+use objc2_foundation::{NSMutableDictionary, NSNumber, NSString};
+use tokio::sync::mpsc;
+
+use super::{MediaKeyAction, NowPlaying};
+
+#[link(name = "MediaPlayer", kind = "framework")]
+extern "C" {
+    static MPMediaItemPropertyTitle: &'static NSString;
+    static MPMediaItemPropertyArtist: &'static NSString;
+    static MPMediaItemPropertyAlbumTitle: &'static NSString;
+    static MPNowPlayingInfoPropertyIsLiveStream: &'static NSString;
+    static MPNowPlayingInfoPropertyPlaybackRate: &'static NSString;
+}
+
+/// `MPNowPlayingPlaybackState` values.
+const PLAYBACK_STATE_PLAYING: usize = 1;
+const PLAYBACK_STATE_PAUSED: usize = 2;
+const PLAYBACK_STATE_STOPPED: usize = 3;
+
+/// `MPRemoteCommandHandlerStatusSuccess`.
+const HANDLER_STATUS_SUCCESS: isize = 0;
+
+/// Registers remote command handlers that forward media keys as actions.
+pub fn init(
+    _app: &tauri::App,
+    actions: mpsc::UnboundedSender<MediaKeyAction>,
+) -> Result<(), String> {
+    let cls = AnyClass::get(c"MPRemoteCommandCenter")
+        .ok_or_else(|| "MediaPlayer framework not available".to_string())?;
+
+    // SAFETY: sharedCommandCenter returns the process-wide singleton, and each
+    // command accessor returns a valid MPRemoteCommand owned by that singleton.
+    unsafe {
+        let center: Retained<AnyObject> = msg_send![cls, sharedCommandCenter];
+
+        let play: Retained<AnyObject> = msg_send![&center, playCommand];
+        add_handler(&play, &actions, MediaKeyAction::Play);
+
+        let pause: Retained<AnyObject> = msg_send![&center, pauseCommand];
+        add_handler(&pause, &actions, MediaKeyAction::Pause);
+
+        let toggle: Retained<AnyObject> = msg_send![&center, togglePlayPauseCommand];
+        add_handler(&toggle, &actions, MediaKeyAction::Toggle);
+
+        let stop: Retained<AnyObject> = msg_send![&center, stopCommand];
+        add_handler(&stop, &actions, MediaKeyAction::Stop);
+    }
+
+    Ok(())
+}
+
+/// Enables a remote command and attaches a handler that sends `action`.
+///
+/// # Safety
+///
+/// `command` must be an `MPRemoteCommand` instance.
+unsafe fn add_handler(
+    command: &AnyObject,
+    actions: &mpsc::UnboundedSender<MediaKeyAction>,
+    action: MediaKeyAction,
+) {
+    let actions = actions.clone();
+    let block = RcBlock::new(move |_event: *mut AnyObject| -> isize {
+        let _ = actions.send(action);
+        HANDLER_STATUS_SUCCESS
+    });
+
+    // The command center copies the block; handlers live for the app's lifetime,
+    // so the returned target token is intentionally dropped.
+    let _: () = msg_send![command, setEnabled: true];
+    let _: Retained<AnyObject> = msg_send![command, addTargetWithHandler: &*block];
+}
+
+/// Publishes (or clears) the now-playing info. Must run on the main thread.
+pub fn update(now_playing: Option<&NowPlaying>) {
+    let Some(cls) = AnyClass::get(c"MPNowPlayingInfoCenter") else {
+        return;
+    };
+
+    // SAFETY: defaultCenter returns the process-wide singleton. The dictionary
+    // keys are framework-provided constants and values are Foundation objects.
+    unsafe {
+        let center: Retained<AnyObject> = msg_send![cls, defaultCenter];
+
+        let Some(np) = now_playing else {
+            let _: () = msg_send![&center, setNowPlayingInfo: std::ptr::null::<AnyObject>()];
+            let _: () = msg_send![&center, setPlaybackState: PLAYBACK_STATE_STOPPED];
+            return;
+        };
+
+        let info = NSMutableDictionary::<NSString, AnyObject>::new();
+        info.insert(MPMediaItemPropertyTitle, &NSString::from_str(&np.title));
+        if let Some(artist) = &np.artist {
+            info.insert(MPMediaItemPropertyArtist, &NSString::from_str(artist));
+        }
+        if let Some(album) = &np.album {
+            info.insert(MPMediaItemPropertyAlbumTitle, &NSString::from_str(album));
+        }
+        info.insert(
+            MPNowPlayingInfoPropertyIsLiveStream,
+            &NSNumber::new_bool(true),
+        );
+        info.insert(
+            MPNowPlayingInfoPropertyPlaybackRate,
+            &NSNumber::new_f64(if np.playing { 1.0 } else { 0.0 }),
+        );
+
+        let state = if np.playing {
+            PLAYBACK_STATE_PLAYING
+        } else {
+            PLAYBACK_STATE_PAUSED
+        };
+        let _: () = msg_send![&center, setNowPlayingInfo: &*info];
+        let _: () = msg_send![&center, setPlaybackState: state];
+    }
+}
@@ -0,0 +1,298 @@
+//! OS media-control integration (now-playing info and media keys).
+//!
+//! Publishes the active cast to the platform's now-playing surface and maps
+//! hardware/OS media keys onto the Sonos speakers playing our streams:
+//!
+//! - **Play**: resumes the coordinator's transport (speaker re-fetches the live stream)
+//! - **Pause**: stops the coordinator's transport but keeps the session, so Play resumes it
+//! - **Toggle**: Play or Pause depending on the coordinator's current transport state
+//! - **Stop**: ends the cast on every speaker (same as the dashboard stop button)
+//!
+//! Platform backends own the native controls and live in per-OS submodules.
+//! All native calls happen on the main thread via `run_on_main_thread`.
+
+#[cfg(target_os = "macos")]
+mod macos;
+#[cfg(target_os = "macos")]
+use macos as platform;
+
+#[cfg(target_os = "windows")]
+mod windows;
+#[cfg(target_os = "windows")]
+use self::windows as platform;
+
+#[cfg(target_os = "linux")]
+mod linux;
+#[cfg(target_os = "linux")]
+use linux as platform;
+
+#[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
+mod unsupported;
+#[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
+use unsupported as platform;
+
+use std::time::Duration;
+
+use tauri::{AppHandle, Manager};
+use thaumic_core::{
+    BroadcastEvent, PlaybackSession, SonosPlayback, SpeakerRemovalReason, TransportState,
+};
+use tokio::sync::mpsc;
+
+use crate::api::AppState;
+
+/// How often the now-playing info is re-read while casting.
+///
+/// Metadata updates from the extension don't broadcast events, so we poll
+/// the active stream and only push to the OS when something changed.
+const NOW_PLAYING_REFRESH_INTERVAL: Duration = Duration::from_secs(2);
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Types
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Media key actions forwarded from the platform backend.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MediaKeyAction {
+    Play,
+    Pause,
+    /// Play/pause toggle (platforms without separate play and pause buttons).
+    #[cfg_attr(target_os = "windows", allow(dead_code))]
+    Toggle,
+    Stop,
+}
+
+/// A media key action with any toggle resolved against the transport state.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ResolvedAction {
+    Play,
+    Pause,
+    Stop,
+}
+
+/// Snapshot of the active cast shown in the OS now-playing surface.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NowPlaying {
+    /// Track title (falls back to the app name when the tab has none).
+    pub title: String,
+    /// Track artist, if known.
+    pub artist: Option<String>,
+    /// Source name (e.g., "YouTube"), shown as the album.
+    pub album: Option<String>,
+    /// Artwork URL served by our HTTP server.
+    ///
+    /// Only used by backends that accept a remote URL for artwork.
+    #[cfg_attr(target_os = "macos", allow(dead_code))]
+    pub artwork_url: Option<String>,
+    /// Whether the coordinator is currently playing.
+    pub playing: bool,
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Setup
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Initializes media-key integration.
+///
+/// Must be called from the Tauri `setup` hook (main thread) after `AppState`
+/// is managed. Failures are logged; the app runs without media keys.
+pub fn setup_media_keys(app: &tauri::App) {
+    let (action_tx, action_rx) = mpsc::unbounded_channel();
+
+    if let Err(e) = platform::init(app, action_tx) {
+        log::warn!("[MediaKeys] Media controls unavailable: {}", e);
+        return;
+    }
+
+    let handle = app.handle().clone();
+    tauri::async_runtime::spawn(run_action_loop(handle.clone(), action_rx));
+    tauri::async_runtime::spawn(run_now_playing_loop(handle));
+
+    log::debug!("[MediaKeys] Media controls initialized");
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Now Playing
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Keeps the OS now-playing info in sync with the active cast.
+async fn run_now_playing_loop(app: AppHandle) {
+    let Some(state) = app.try_state::<AppState>() else {
+        log::warn!("[MediaKeys] AppState not available for now-playing listener");
+        return;
+    };
+    let state = state.inner().clone();
+    let mut rx = state.services.event_bridge.subscribe();
+    let mut interval = tokio::time::interval(NOW_PLAYING_REFRESH_INTERVAL);
+    let mut current: Option<NowPlaying> = None;
+
+    loop {
+        tokio::select! {
+            result = rx.recv() => match result {
+                Ok(BroadcastEvent::Stream(_) | BroadcastEvent::Sonos(_)) => {}
+                Ok(_) => continue,
+                Err(tokio::sync::broadcast::error::RecvError::Lagged(_)) => {}
+                Err(tokio::sync::broadcast::error::RecvError::Closed) => break,
+            },
+            _ = interval.tick() => {}
+        }
+
+        let next = build_now_playing(&state);
+        if next != current {
+            current = next.clone();
+            let _ = app.run_on_main_thread(move || platform::update(next.as_ref()));
+        }
+    }
+}
+
+/// Builds the now-playing snapshot for the first active coordinator session.
+fn build_now_playing(state: &AppState) -> Option<NowPlaying> {
+    let sessions = state.services.stream_coordinator.get_all_sessions();
+    let session = active_coordinators(&sessions).into_iter().next()?;
+    let stream = state
+        .services
+        .stream_coordinator
+        .get_stream(&session.stream_id)?;
+    let metadata = stream.metadata.read().clone();
+
+    let transport = state
+        .services
+        .sonos_state
+        .transport_states
+        .get(&session.speaker_ip)
+        .map(|entry| *entry.value());
+
+    Some(NowPlaying {
+        title: metadata
+            .title
+            .unwrap_or_else(|| rust_i18n::t!("tray.app_name").to_string()),
+        artist: metadata.artist,
+        album: metadata.source,
+        artwork_url: Some(state.artwork_metadata_url()),
+        playing: !matches!(
+            transport,
+            Some(TransportState::Stopped | TransportState::Paused)
+        ),
+    })
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Actions
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Dispatches media key actions to the active cast.
+async fn run_action_loop(app: AppHandle, mut rx: mpsc::UnboundedReceiver<MediaKeyAction>) {
+    while let Some(action) = rx.recv().await {
+        let Some(state) = app.try_state::<AppState>() else {
+            continue;
+        };
+        let state = state.inner().clone();
+        handle_action(&state, action).await;
+    }
+}
+
+/// Applies a media key action to every coordinator casting one of our streams.
+async fn handle_action(state: &AppState, action: MediaKeyAction) {
+    let sessions = state.services.stream_coordinator.get_all_sessions();
+    let coordinators = active_coordinators(&sessions);
+    if coordinators.is_empty() {
+        log::debug!("[MediaKeys] {:?} ignored: no active cast", action);
+        return;
+    }
+
+    let action = match action {
+        MediaKeyAction::Play => ResolvedAction::Play,
+        MediaKeyAction::Pause => ResolvedAction::Pause,
+        MediaKeyAction::Stop => ResolvedAction::Stop,
+        MediaKeyAction::Toggle => {
+            let transport = state
+                .services
+                .sonos_state
+                .transport_states
+                .get(&coordinators[0].speaker_ip)
+                .map(|entry| *entry.value());
+            resolve_toggle(transport)
+        }
+    };
+
+    log::info!(
+        "[MediaKeys] {:?} -> {} coordinator(s)",
+        action,
+        coordinators.len()
+    );
+
+    for session in coordinators {
+        let ip = session.speaker_ip.as_str();
+        let result = match action {
+            ResolvedAction::Play => state.services.sonos.play(ip).await,
+            ResolvedAction::Pause => state.services.stream_coordinator.pause_speaker(ip).await,
+            ResolvedAction::Stop => {
+                state
+                    .services
+                    .stream_coordinator
+                    .stop_playback_speaker(
+                        &session.stream_id,
+                        ip,
+                        Some(SpeakerRemovalReason::UserRemoved),
+                    )
+                    .await;
+                Ok(())
+            }
+        };
+        if let Err(e) = result {
+            log::warn!("[MediaKeys] {:?} failed for {}: {}", action, ip, e);
+        }
+    }
+}
+
+/// Returns coordinator sessions (slaves follow their coordinator's transport).
+fn active_coordinators(sessions: &[PlaybackSession]) -> Vec<&PlaybackSession> {
+    sessions
+        .iter()
+        .filter(|s| s.coordinator_ip.is_none())
+        .collect()
+}
+
+/// Resolves a toggle into Play or Pause based on the coordinator's transport state.
+fn resolve_toggle(transport: Option<TransportState>) -> ResolvedAction {
+    match transport {
+        Some(TransportState::Playing | TransportState::Transitioning) | None => {
+            ResolvedAction::Pause
+        }
+        Some(TransportState::Paused | TransportState::Stopped) => ResolvedAction::Play,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn toggle_pauses_when_playing() {
+        assert_eq!(
+            resolve_toggle(Some(TransportState::Playing)),
+            ResolvedAction::Pause
+        );
+        assert_eq!(
+            resolve_toggle(Some(TransportState::Transitioning)),
+            ResolvedAction::Pause
+        );
+    }
+
+    #[test]
+    fn toggle_plays_when_stopped_or_paused() {
+        assert_eq!(
+            resolve_toggle(Some(TransportState::Stopped)),
+            ResolvedAction::Play
+        );
+        assert_eq!(
+            resolve_toggle(Some(TransportState::Paused)),
+            ResolvedAction::Play
+        );
+    }
+
+    #[test]
+    fn toggle_pauses_when_state_unknown() {
+        assert_eq!(resolve_toggle(None), ResolvedAction::Pause);
+    }
+}
@@ -0,0 +1,16 @@
+//! No-op media controls for platforms without a native backend.
+
+use tokio::sync::mpsc;
+
+use super::{MediaKeyAction, NowPlaying};
+
+/// Reports that media controls are not available on this platform.
+pub fn init(
+    _app: &tauri::App,
+    _actions: mpsc::UnboundedSender<MediaKeyAction>,
+) -> Result<(), String> {
+    Err("no media control backend for this platform".into())
+}
+
+/// No-op: there is no now-playing surface to update.
+pub fn update(_now_playing: Option<&NowPlaying>) {}
@@ -0,0 +1,130 @@
+//! Windows media controls via System Media Transport Controls (SMTC).
+//!
+//! Desktop (unpackaged) apps can't call `SystemMediaTransportControls::GetForCurrentView`,
+//! so we borrow the SMTC instance of a `MediaPlayer` with its automatic command
+//! handling disabled. The player never plays anything; it only anchors the
+//! media overlay entry. Controls live in a thread-local on the main thread.
+//!
+//! SMTC has no volume hook, so hardware volume keys keep controlling the PC's
+//! output; speaker volume stays in the dashboard and extension.
+
+use std::cell::RefCell;
+
+use tokio::sync::mpsc;
+use windows::core::HSTRING;
+use windows::Foundation::{TypedEventHandler, Uri};
+use windows::Media::Playback::MediaPlayer;
+use windows::Media::{
+    MediaPlaybackStatus, MediaPlaybackType, SystemMediaTransportControls,
+    SystemMediaTransportControlsButton, SystemMediaTransportControlsButtonPressedEventArgs,
+};
+use windows::Storage::Streams::RandomAccessStreamReference;
+
+use super::{MediaKeyAction, NowPlaying};
+
+/// Native controls; the player must outlive the SMTC registration.
+struct Controls {
+    _player: MediaPlayer,
+    smtc: SystemMediaTransportControls,
+}
+
+thread_local! {
+    /// Native controls, owned by the main thread.
+    static CONTROLS: RefCell<Option<Controls>> = const { RefCell::new(None) };
+}
+
+/// Creates the SMTC registration and forwards button presses as actions.
+pub fn init(
+    _app: &tauri::App,
+    actions: mpsc::UnboundedSender<MediaKeyAction>,
+) -> Result<(), String> {
+    let controls = create_controls(actions).map_err(|e| e.to_string())?;
+    CONTROLS.with(|cell| *cell.borrow_mut() = Some(controls));
+    Ok(())
+}
+
+fn create_controls(
+    actions: mpsc::UnboundedSender<MediaKeyAction>,
+) -> windows::core::Result<Controls> {
+    let player = MediaPlayer::new()?;
+    player.CommandManager()?.SetIsEnabled(false)?;
+
+    let smtc = player.SystemMediaTransportControls()?;
+    smtc.SetIsEnabled(true)?;
+    smtc.SetIsPlayEnabled(true)?;
+    smtc.SetIsPauseEnabled(true)?;
+    smtc.SetIsStopEnabled(true)?;
+    smtc.SetPlaybackStatus(MediaPlaybackStatus::Closed)?;
+
+    smtc.ButtonPressed(&TypedEventHandler::<
+        SystemMediaTransportControls,
+        SystemMediaTransportControlsButtonPressedEventArgs,
+    >::new(move |_, args| {
+        if let Some(action) = map_button(args.ok()?.Button()?) {
+            let _ = actions.send(action);
+        }
+        Ok(())
+    }))?;
+
+    Ok(Controls {
+        _player: player,
+        smtc,
+    })
+}
+
+/// Publishes (or clears) the now-playing info. Must run on the main thread.
+pub fn update(now_playing: Option<&NowPlaying>) {
+    CONTROLS.with(|cell| {
+        let cell = cell.borrow();
+        let Some(controls) = cell.as_ref() else {
+            return;
+        };
+        if let Err(e) = apply(&controls.smtc, now_playing) {
+            log::warn!("[MediaKeys] Failed to update SMTC: {}", e);
+        }
+    });
+}
+
+fn apply(
+    smtc: &SystemMediaTransportControls,
+    now_playing: Option<&NowPlaying>,
+) -> windows::core::Result<()> {
+    let updater = smtc.DisplayUpdater()?;
+
+    let Some(np) = now_playing else {
+        updater.ClearAll()?;
+        updater.Update()?;
+        return smtc.SetPlaybackStatus(MediaPlaybackStatus::Closed);
+    };
+
+    updater.SetType(MediaPlaybackType::Music)?;
+    let music = updater.MusicProperties()?;
+    music.SetTitle(&HSTRING::from(np.title.as_str()))?;
+    music.SetArtist(&HSTRING::from(np.artist.as_deref().unwrap_or_default()))?;
+    music.SetAlbumTitle(&HSTRING::from(np.album.as_deref().unwrap_or_default()))?;
+
+    if let Some(url) = &np.artwork_url {
+        let uri = Uri::CreateUri(&HSTRING::from(url.as_str()))?;
+        updater.SetThumbnail(&RandomAccessStreamReference::CreateFromUri(&uri)?)?;
+    }
+    updater.Update()?;
+
+    smtc.SetPlaybackStatus(if np.playing {
+        MediaPlaybackStatus::Playing
+    } else {
+        MediaPlaybackStatus::Paused
+    })
+}
+
+/// Maps an SMTC button to a media key action (unsupported buttons are ignored).
+///
+/// The overlay shows a single play/pause button and reports whichever one the
+/// current status implies, so no separate toggle mapping is needed.
+fn map_button(button: SystemMediaTransportControlsButton) -> Option<MediaKeyAction> {
+    match button {
+        SystemMediaTransportControlsButton::Play => Some(MediaKeyAction::Play),
+        SystemMediaTransportControlsButton::Pause => Some(MediaKeyAction::Pause),
+        SystemMediaTransportControlsButton::Stop => Some(MediaKeyAction::Stop),
+        _ => None,
+    }
+}
@@ -1,8 +1,10 @@
 //! Native UI components for the desktop application.
 //!
 //! This module handles platform-native UI elements such as the system tray,
-//! notifications, and window management behaviors.
+//! OS media controls, notifications, and window management behaviors.
 
+pub mod media_keys;
 pub mod tray;
 
+pub use media_keys::setup_media_keys;
 pub use tray::{setup_tray, TrayState};
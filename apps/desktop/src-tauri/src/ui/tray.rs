@@ -4,15 +4,24 @@
 //! common actions: viewing status, toggling autostart, and controlling streams.
 //!
 //! The tray menu status line updates dynamically when streams are created or ended.
-//! On macOS, the tray icon uses template images that adapt to light/dark mode.
+//! The icon reflects the app's overall state:
+//!
+//! - **Idle**: idle icon
+//! - **Casting**: active icon, pulsing briefly whenever playback starts on a speaker
+//! - **Error**: red badge after a speaker fails to stop (cleared by the next cast)
+//! - **Degraded**: yellow badge while speakers are discovered but unreachable
+//!
+//! On macOS, badge-free icons are template images that adapt to light/dark mode.
+//! On Windows and Linux, light/dark variants are swapped on theme changes.
 
 use std::future::Future;
-#[cfg(target_os = "windows")]
 use std::sync::{
     atomic::{AtomicBool, Ordering},
     Arc,
 };
+use std::time::Duration;
 
+use parking_lot::Mutex;
 use rust_i18n::t;
 use tauri::{
     image::Image,
@@ -23,14 +32,91 @@ use tauri::{
 use tauri_plugin_autostart::ManagerExt;
 use thiserror::Error;
 
-use thaumic_core::{BroadcastEvent, StreamEvent};
+use thaumic_core::services::StreamCoordinator;
+use thaumic_core::{BroadcastEvent, NetworkEvent, NetworkHealth, StreamEvent};
 
 use crate::api::AppState;
 
+/// Number of icon frames shown when playback starts (alternating active/idle).
+const ACTIVITY_PULSE_FRAMES: u32 = 6;
+
+/// Delay between activity pulse frames.
+const ACTIVITY_PULSE_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Badge diameter as a fraction of the icon's shorter side.
+const BADGE_SIZE_RATIO: f32 = 0.45;
+
+/// Red badge color (RGBA) for the error state.
+const BADGE_COLOR_ERROR: [u8; 4] = [0xE5, 0x39, 0x35, 0xFF];
+
+/// Yellow badge color (RGBA) for the degraded network state.
+const BADGE_COLOR_DEGRADED: [u8; 4] = [0xF9, 0xA8, 0x25, 0xFF];
+
 // ─────────────────────────────────────────────────────────────────────────────
 // Tray State
 // ─────────────────────────────────────────────────────────────────────────────
 
+/// Overall app state shown by the tray icon, highest priority first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TrayStatus {
+    /// A speaker operation failed (red badge).
+    Error,
+    /// Speakers are unreachable (yellow badge).
+    Degraded,
+    /// At least one stream is active.
+    Casting,
+    /// Nothing is happening.
+    Idle,
+}
+
+impl TrayStatus {
+    /// Badge color drawn over the base icon, if this status has one.
+    const fn badge_color(self) -> Option<[u8; 4]> {
+        match self {
+            Self::Error => Some(BADGE_COLOR_ERROR),
+            Self::Degraded => Some(BADGE_COLOR_DEGRADED),
+            Self::Casting | Self::Idle => None,
+        }
+    }
+
+    /// Tooltip text for this status.
+    fn tooltip(self) -> String {
+        match self {
+            Self::Error => t!("tray.tooltip_error").to_string(),
+            Self::Degraded => t!("tray.tooltip_network_degraded").to_string(),
+            Self::Casting | Self::Idle => t!("tray.tooltip").to_string(),
+        }
+    }
+}
+
+/// Inputs that determine which icon is shown.
+#[derive(Debug, Clone, Copy, Default)]
+struct IconState {
+    /// Whether any stream is active.
+    is_streaming: bool,
+    /// Whether the last speaker operation failed.
+    has_error: bool,
+    /// Whether network health is degraded.
+    is_degraded: bool,
+    /// Whether the system theme is dark (Windows/Linux icon selection).
+    is_dark_theme: bool,
+}
+
+impl IconState {
+    /// Resolves the inputs into a single display status.
+    fn status(&self) -> TrayStatus {
+        if self.has_error {
+            TrayStatus::Error
+        } else if self.is_degraded {
+            TrayStatus::Degraded
+        } else if self.is_streaming {
+            TrayStatus::Casting
+        } else {
+            TrayStatus::Idle
+        }
+    }
+}
+
 /// Holds references to tray menu items that need dynamic updates.
 #[derive(Clone)]
 pub struct TrayState {
@@ -38,67 +124,72 @@ pub struct TrayState {
     status_item: MenuItem<tauri::Wry>,
     /// The tray icon for dynamic icon updates.
     tray_icon: TrayIcon<tauri::Wry>,
-    /// Current streaming state (for Windows icon selection).
-    #[cfg(target_os = "windows")]
-    is_streaming: Arc<AtomicBool>,
-    /// Current theme - true if dark (for Windows icon selection).
-    #[cfg(target_os = "windows")]
-    is_dark_theme: Arc<AtomicBool>,
+    /// Current icon inputs (streaming, error, network health, theme).
+    icon_state: Arc<Mutex<IconState>>,
+    /// Whether an activity pulse is running (suppresses overlapping pulses).
+    is_animating: Arc<AtomicBool>,
 }
 
 impl TrayState {
-    /// Updates the status text based on current stream count.
-    fn update_status(&self, stream_count: usize) {
-        let text = format_status_text(stream_count);
+    /// Updates the status text for the current streams.
+    fn update_status(&self, coordinator: &StreamCoordinator) {
+        let text = status_text(coordinator);
         if let Err(e) = self.status_item.set_text(&text) {
             log::warn!("Failed to update tray status: {}", e);
         }
     }
 
-    /// Updates the tray icon based on streaming state.
-    /// On Windows, also considers current theme for icon selection.
-    fn update_icon(&self, is_streaming: bool) {
-        #[cfg(target_os = "windows")]
-        {
-            self.is_streaming.store(is_streaming, Ordering::Relaxed);
-            let is_dark = self.is_dark_theme.load(Ordering::Relaxed);
-            let theme = if is_dark {
-                tauri::Theme::Dark
-            } else {
-                tauri::Theme::Light
-            };
-            self.set_icon_or_warn(load_tray_icon_for_state(theme, is_streaming));
-        }
-
-        #[cfg(not(target_os = "windows"))]
-        {
-            let icon = if is_streaming {
-                load_tray_icon_active()
-            } else {
-                load_tray_icon_idle()
-            };
-            self.set_icon_or_warn(icon);
+    /// Applies a change to the icon inputs and re-renders the icon.
+    fn update_icon(&self, f: impl FnOnce(&mut IconState)) {
+        let state = {
+            let mut state = self.icon_state.lock();
+            f(&mut state);
+            *state
+        };
+        if !self.is_animating.load(Ordering::Relaxed) {
+            self.render(state, state.is_streaming);
         }
     }
 
-    /// Updates the tray icon when system theme changes (Windows only).
-    #[cfg(target_os = "windows")]
+    /// Updates the tray icon when the system theme changes.
+    ///
+    /// macOS template icons adapt on their own; the theme is still recorded so
+    /// badged (non-template) icons stay consistent.
     pub fn update_for_theme(&self, theme: tauri::Theme) {
-        self.is_dark_theme
-            .store(matches!(theme, tauri::Theme::Dark), Ordering::Relaxed);
-        let is_streaming = self.is_streaming.load(Ordering::Relaxed);
-        self.set_icon_or_warn(load_tray_icon_for_state(theme, is_streaming));
+        self.update_icon(|s| s.is_dark_theme = matches!(theme, tauri::Theme::Dark));
     }
 
-    /// Updates the tray icon when system theme changes (no-op on non-Windows).
-    #[cfg(not(target_os = "windows"))]
-    pub fn update_for_theme(&self, _theme: tauri::Theme) {
-        // No-op: macOS uses template icons, Linux has no theme detection
+    /// Briefly alternates the icon between active and idle to signal activity.
+    fn pulse_activity(&self) {
+        if self.is_animating.swap(true, Ordering::Relaxed) {
+            return;
+        }
+
+        let tray = self.clone();
+        tauri::async_runtime::spawn(async move {
+            for frame in 0..ACTIVITY_PULSE_FRAMES {
+                let state = *tray.icon_state.lock();
+                tray.render(state, frame % 2 == 0);
+                tokio::time::sleep(ACTIVITY_PULSE_INTERVAL).await;
+            }
+            tray.is_animating.store(false, Ordering::Relaxed);
+            let state = *tray.icon_state.lock();
+            tray.render(state, state.is_streaming);
+        });
     }
 
-    /// Sets the tray icon, logging warnings on failure.
-    fn set_icon_or_warn(&self, icon: Result<Image<'static>, TrayError>) {
-        match icon {
+    /// Renders the icon and tooltip for `state`, using the active or idle base icon.
+    fn render(&self, state: IconState, show_active: bool) {
+        let status = state.status();
+        let badge = status.badge_color();
+
+        // Template images are drawn monochrome, which would hide the badge color
+        #[cfg(target_os = "macos")]
+        if let Err(e) = self.tray_icon.set_icon_as_template(badge.is_none()) {
+            log::warn!("Failed to update tray template mode: {}", e);
+        }
+
+        match load_tray_icon(state.is_dark_theme, show_active, badge) {
             Ok(img) => {
                 if let Err(e) = self.tray_icon.set_icon(Some(img)) {
                     log::warn!("Failed to update tray icon: {}", e);
@@ -108,6 +199,10 @@ impl TrayState {
                 log::warn!("Failed to load tray icon: {}", e);
             }
         }
+
+        if let Err(e) = self.tray_icon.set_tooltip(Some(status.tooltip())) {
+            log::warn!("Failed to update tray tooltip: {}", e);
+        }
     }
 }
 
@@ -116,82 +211,112 @@ impl TrayState {
 // ─────────────────────────────────────────────────────────────────────────────
 
 /// Icon bytes for active state (embedded at compile time).
-/// Used on macOS (template) and Linux.
-#[cfg(not(target_os = "windows"))]
+/// Used on macOS (template).
+#[cfg(target_os = "macos")]
 const TRAY_ICON_ACTIVE: &[u8] = include_bytes!("../../icons/tray/tray-template.png");
 /// Icon bytes for idle state (embedded at compile time).
-/// Used on macOS and Linux.
-#[cfg(not(target_os = "windows"))]
+/// Used on macOS.
+#[cfg(target_os = "macos")]
 const TRAY_ICON_IDLE: &[u8] = include_bytes!("../../icons/tray/tray-idle.png");
 
-// Windows icons: 4-state matrix (theme × streaming state)
-#[cfg(target_os = "windows")]
+// Windows/Linux icons: 4-state matrix (theme × streaming state)
+#[cfg(not(target_os = "macos"))]
 const TRAY_ICON_LIGHT_IDLE: &[u8] = include_bytes!("../../icons/tray/tray-light-idle.png");
-#[cfg(target_os = "windows")]
+#[cfg(not(target_os = "macos"))]
 const TRAY_ICON_LIGHT_ACTIVE: &[u8] = include_bytes!("../../icons/tray/tray-light-active.png");
-#[cfg(target_os = "windows")]
+#[cfg(not(target_os = "macos"))]
 const TRAY_ICON_DARK_IDLE: &[u8] = include_bytes!("../../icons/tray/tray-dark-idle.png");
-#[cfg(target_os = "windows")]
+#[cfg(not(target_os = "macos"))]
 const TRAY_ICON_DARK_ACTIVE: &[u8] = include_bytes!("../../icons/tray/tray-dark-active.png");
 
-/// Loads the active tray icon (macOS/Linux).
-#[cfg(not(target_os = "windows"))]
-fn load_tray_icon_active() -> Result<Image<'static>, TrayError> {
-    Image::from_bytes(TRAY_ICON_ACTIVE).map_err(|e| TrayError::Build(e.to_string()))
-}
-
-/// Loads the idle tray icon (macOS/Linux).
-#[cfg(not(target_os = "windows"))]
-fn load_tray_icon_idle() -> Result<Image<'static>, TrayError> {
-    Image::from_bytes(TRAY_ICON_IDLE).map_err(|e| TrayError::Build(e.to_string()))
+/// Returns the base icon bytes for the streaming state (macOS - theme via template).
+#[cfg(target_os = "macos")]
+fn tray_icon_bytes(_is_dark: bool, is_streaming: bool) -> &'static [u8] {
+    if is_streaming {
+        TRAY_ICON_ACTIVE
+    } else {
+        TRAY_ICON_IDLE
+    }
 }
 
-/// Detects the current system theme from the main window (Windows).
-#[cfg(target_os = "windows")]
-fn detect_system_theme(app: &tauri::App) -> tauri::Theme {
-    app.get_webview_window("main")
-        .and_then(|w| w.theme().ok())
-        .unwrap_or(tauri::Theme::Light)
+/// Returns the base icon bytes for theme and streaming state (Windows/Linux).
+#[cfg(not(target_os = "macos"))]
+fn tray_icon_bytes(is_dark: bool, is_streaming: bool) -> &'static [u8] {
+    match (is_dark, is_streaming) {
+        (false, false) => TRAY_ICON_LIGHT_IDLE,
+        (false, true) => TRAY_ICON_LIGHT_ACTIVE,
+        (true, false) => TRAY_ICON_DARK_IDLE,
+        (true, true) => TRAY_ICON_DARK_ACTIVE,
+    }
 }
 
-/// Loads the appropriate tray icon based on theme and streaming state (Windows).
-#[cfg(target_os = "windows")]
-fn load_tray_icon_for_state(
-    theme: tauri::Theme,
+/// Loads the tray icon, drawing a status badge over it when requested.
+fn load_tray_icon(
+    is_dark: bool,
     is_streaming: bool,
+    badge: Option<[u8; 4]>,
 ) -> Result<Image<'static>, TrayError> {
-    let bytes = match (theme, is_streaming) {
-        (tauri::Theme::Light, false) => TRAY_ICON_LIGHT_IDLE,
-        (tauri::Theme::Light, true) => TRAY_ICON_LIGHT_ACTIVE,
-        (tauri::Theme::Dark, false) => TRAY_ICON_DARK_IDLE,
-        (tauri::Theme::Dark, true) => TRAY_ICON_DARK_ACTIVE,
-        // tauri::Theme is non_exhaustive, but currently only has Light/Dark
-        _ => TRAY_ICON_LIGHT_IDLE,
+    let icon = Image::from_bytes(tray_icon_bytes(is_dark, is_streaming))
+        .map_err(|e| TrayError::Build(e.to_string()))?;
+
+    let Some(color) = badge else {
+        return Ok(icon);
     };
-    Image::from_bytes(bytes).map_err(|e| TrayError::Build(e.to_string()))
+
+    let (width, height) = (icon.width(), icon.height());
+    let mut rgba = icon.rgba().to_vec();
+    draw_badge(&mut rgba, width, height, color);
+    Ok(Image::new_owned(rgba, width, height))
 }
 
-/// Loads the initial tray icon (Windows - uses theme detection).
-#[cfg(target_os = "windows")]
-fn load_initial_tray_icon(app: &tauri::App) -> Result<Image<'static>, TrayError> {
-    load_tray_icon_for_state(detect_system_theme(app), false)
+/// Draws a filled circle in the bottom-right corner of an RGBA buffer.
+fn draw_badge(rgba: &mut [u8], width: u32, height: u32, color: [u8; 4]) {
+    let diameter = (width.min(height) as f32 * BADGE_SIZE_RATIO).round();
+    let radius = diameter / 2.0;
+    let cx = width as f32 - radius;
+    let cy = height as f32 - radius;
+
+    for y in 0..height {
+        for x in 0..width {
+            let dx = x as f32 + 0.5 - cx;
+            let dy = y as f32 + 0.5 - cy;
+            if dx * dx + dy * dy <= radius * radius {
+                let i = ((y * width + x) * 4) as usize;
+                rgba[i..i + 4].copy_from_slice(&color);
+            }
+        }
+    }
 }
 
-/// Loads the initial tray icon (macOS/Linux - idle state).
-#[cfg(not(target_os = "windows"))]
-fn load_initial_tray_icon(_app: &tauri::App) -> Result<Image<'static>, TrayError> {
-    load_tray_icon_idle()
+/// Detects the current system theme from the main window.
+fn detect_system_theme(app: &tauri::App) -> tauri::Theme {
+    app.get_webview_window("main")
+        .and_then(|w| w.theme().ok())
+        .unwrap_or(tauri::Theme::Light)
 }
 
-/// Formats the status text for a given stream count.
-fn format_status_text(stream_count: usize) -> String {
-    match stream_count {
-        0 => t!("tray.status_idle").to_string(),
-        1 => t!("tray.status_streaming_one").to_string(),
-        n => t!("tray.status_streaming_many", count = n).to_string(),
+/// Formats the status text for a given stream count. A lone stream is
+/// shown by its label when it has one.
+fn format_status_text(stream_count: usize, label: Option<&str>) -> String {
+    match (stream_count, label) {
+        (0, _) => t!("tray.status_idle").to_string(),
+        (1, Some(label)) => t!("tray.status_streaming_labelled", label = label).to_string(),
+        (1, None) => t!("tray.status_streaming_one").to_string(),
+        (n, _) => t!("tray.status_streaming_many", count = n).to_string(),
     }
 }
 
+/// Returns the status text for the coordinator's current streams.
+fn status_text(coordinator: &StreamCoordinator) -> String {
+    let registry = coordinator.stream_registry();
+    let ids = registry.list_stream_ids();
+    let label = match ids.as_slice() {
+        [id] => registry.get_stream(id).and_then(|stream| stream.label()),
+        _ => None,
+    };
+    format_status_text(ids.len(), label.as_deref())
+}
+
 // ─────────────────────────────────────────────────────────────────────────────
 // Menu Item Identifiers
 // ─────────────────────────────────────────────────────────────────────────────
@@ -271,10 +396,10 @@ impl<T, E: std::fmt::Display> MenuResultExt<T> for Result<T, E> {
 /// Gets the current status text based on app state.
 fn get_status_text(app: &tauri::App) -> String {
     let Some(state) = app.try_state::<AppState>() else {
-        return format_status_text(0);
+        return format_status_text(0, None);
     };
 
-    format_status_text(state.services.stream_coordinator.stream_count())
+    status_text(&state.services.stream_coordinator)
 }
 
 /// Checks if autostart is currently enabled.
@@ -307,12 +432,9 @@ where
 ///
 /// Also starts a background task to update the status line when streams change.
 pub fn setup_tray(app: &tauri::App) -> Result<(), TrayError> {
-    // Load initial icon (theme-aware on Windows, idle on other platforms)
-    let icon = load_initial_tray_icon(app)?;
-
-    // Detect initial theme for Windows state tracking
-    #[cfg(target_os = "windows")]
+    // Load initial icon (idle, theme-aware on Windows/Linux)
     let initial_is_dark = matches!(detect_system_theme(app), tauri::Theme::Dark);
+    let icon = load_tray_icon(initial_is_dark, false, None)?;
 
     // Get app version from config
     let version = app.config().version.clone().unwrap_or_default();
@@ -393,10 +515,11 @@ pub fn setup_tray(app: &tauri::App) -> Result<(), TrayError> {
     let tray_state = TrayState {
         status_item: status,
         tray_icon,
-        #[cfg(target_os = "windows")]
-        is_streaming: Arc::new(AtomicBool::new(false)),
-        #[cfg(target_os = "windows")]
-        is_dark_theme: Arc::new(AtomicBool::new(initial_is_dark)),
+        icon_state: Arc::new(Mutex::new(IconState {
+            is_dark_theme: initial_is_dark,
+            ..IconState::default()
+        })),
+        is_animating: Arc::new(AtomicBool::new(false)),
     };
     app.manage(tray_state);
 
@@ -407,7 +530,7 @@ pub fn setup_tray(app: &tauri::App) -> Result<(), TrayError> {
     Ok(())
 }
 
-/// Starts a background task that listens for stream events and updates the tray.
+/// Starts a background task that listens for app events and updates the tray.
 fn start_status_listener(app: AppHandle) {
     let Some(app_state) = app.try_state::<AppState>() else {
         log::warn!("AppState not available for tray status listener");
@@ -417,33 +540,63 @@ fn start_status_listener(app: AppHandle) {
     let mut rx = app_state.services.event_bridge.subscribe();
     let app_state = app_state.inner().clone();
 
+    // Pick up a degraded network reported before the tray existed
+    let initial_health = app_state
+        .services
+        .discovery_service
+        .topology_monitor()
+        .get_network_health()
+        .health;
+    if initial_health == NetworkHealth::Degraded {
+        if let Some(tray_state) = app.try_state::<TrayState>() {
+            tray_state.update_icon(|s| s.is_degraded = true);
+        }
+    }
+
     tauri::async_runtime::spawn(async move {
         loop {
-            match rx.recv().await {
-                Ok(BroadcastEvent::Stream(event)) => {
-                    // Update tray on stream created/ended events
-                    if matches!(
-                        event,
-                        StreamEvent::Created { .. } | StreamEvent::Ended { .. }
-                    ) {
-                        let stream_count = app_state.services.stream_coordinator.stream_count();
-
-                        if let Some(tray_state) = app.try_state::<TrayState>() {
-                            tray_state.update_status(stream_count);
-                            tray_state.update_icon(stream_count > 0);
-                        }
-                    }
-                }
-                Ok(_) => {
-                    // Ignore other event types
-                }
+            let event = match rx.recv().await {
+                Ok(event) => event,
                 Err(tokio::sync::broadcast::error::RecvError::Lagged(n)) => {
                     log::debug!("Tray status listener lagged {} events", n);
+                    continue;
                 }
                 Err(tokio::sync::broadcast::error::RecvError::Closed) => {
                     log::debug!("Broadcast channel closed, stopping tray status listener");
                     break;
                 }
+            };
+
+            let Some(tray_state) = app.try_state::<TrayState>() else {
+                continue;
+            };
+
+            match event {
+                BroadcastEvent::Stream(StreamEvent::Created { .. } | StreamEvent::Ended { .. }) => {
+                    let stream_count = app_state.services.stream_coordinator.stream_count();
+                    tray_state.update_status(&app_state.services.stream_coordinator);
+                    tray_state.update_icon(|s| {
+                        s.is_streaming = stream_count > 0;
+                        // A new cast (or no casts at all) supersedes the last failure
+                        s.has_error = false;
+                    });
+                }
+                BroadcastEvent::Stream(StreamEvent::LabelChanged { .. }) => {
+                    tray_state.update_status(&app_state.services.stream_coordinator);
+                }
+                BroadcastEvent::Stream(StreamEvent::PlaybackStarted { .. }) => {
+                    tray_state.update_icon(|s| s.has_error = false);
+                    tray_state.pulse_activity();
+                }
+                BroadcastEvent::Stream(StreamEvent::PlaybackStopFailed { .. }) => {
+                    tray_state.update_icon(|s| s.has_error = true);
+                }
+                BroadcastEvent::Network(NetworkEvent::HealthChanged { health, .. }) => {
+                    tray_state.update_icon(|s| s.is_degraded = health == NetworkHealth::Degraded);
+                }
+                _ => {
+                    // Ignore other event types
+                }
             }
         }
     });
@@ -569,3 +722,55 @@ fn focus_window(window: &WebviewWindow) {
     let _ = window.unminimize();
     let _ = window.set_focus();
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn status_prefers_error_over_degraded_and_casting() {
+        let state = IconState {
+            is_streaming: true,
+            has_error: true,
+            is_degraded: true,
+            is_dark_theme: false,
+        };
+        assert_eq!(state.status(), TrayStatus::Error);
+    }
+
+    #[test]
+    fn status_prefers_degraded_over_casting() {
+        let state = IconState {
+            is_streaming: true,
+            is_degraded: true,
+            ..IconState::default()
+        };
+        assert_eq!(state.status(), TrayStatus::Degraded);
+    }
+
+    #[test]
+    fn status_reflects_streaming() {
+        assert_eq!(IconState::default().status(), TrayStatus::Idle);
+        let casting = IconState {
+            is_streaming: true,
+            ..IconState::default()
+        };
+        assert_eq!(casting.status(), TrayStatus::Casting);
+        assert_eq!(casting.status().badge_color(), None);
+    }
+
+    #[test]
+    fn badge_fills_bottom_right_corner_only() {
+        let (w, h) = (16, 16);
+        let mut rgba = vec![0u8; (w * h * 4) as usize];
+        draw_badge(&mut rgba, w, h, BADGE_COLOR_ERROR);
+
+        let pixel = |x: u32, y: u32| {
+            let i = ((y * w + x) * 4) as usize;
+            [rgba[i], rgba[i + 1], rgba[i + 2], rgba[i + 3]]
+        };
+        assert_eq!(pixel(w - 2, h - 2), BADGE_COLOR_ERROR);
+        assert_eq!(pixel(0, 0), [0, 0, 0, 0]);
+        assert_eq!(pixel(w - 1, 0), [0, 0, 0, 0]);
+    }
+}
@@ -1,6 +1,7 @@
 import { Route, Switch } from 'wouter-preact';
 import { useEffect } from 'preact/hooks';
 import { Sidebar } from './components/Sidebar';
+import { OriginApprovalPrompt } from './components/OriginApprovalPrompt';
 import { Speakers } from './views/Speakers';
 import { Server } from './views/Server';
 import { Settings } from './views/Settings';
@@ -47,6 +48,7 @@ export function App() {
     <div className={styles.layout}>
       <Sidebar />
       <main className={styles.content}>
+        <OriginApprovalPrompt />
         <Switch>
           <Route path="/" component={Speakers} />
           <Route path="/server" component={Server} />
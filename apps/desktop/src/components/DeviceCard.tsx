@@ -1,4 +1,4 @@
-import type { Speaker } from '../state/store';
+import type { CastSource, Speaker } from '../state/store';
 import { Speaker as SpeakerIcon } from 'lucide-preact';
 import { useTranslation } from 'react-i18next';
 import { Card } from '@thaumic-cast/ui';
@@ -15,6 +15,8 @@ interface DeviceCardProps {
   transportState?: string;
   /** Whether this speaker is casting one of our streams */
   isCasting?: boolean;
+  /** What the speaker is casting (source page and track) */
+  castSource?: CastSource;
 }
 
 /**
@@ -27,6 +29,7 @@ interface DeviceCardProps {
  * @param props.memberCount - Number of members in the group
  * @param props.transportState - Current transport state
  * @param props.isCasting - Whether this speaker is casting one of our streams
+ * @param props.castSource - What the speaker is casting
  * @returns The rendered DeviceCard component
  */
 export function DeviceCard({
@@ -35,6 +38,7 @@ export function DeviceCard({
   memberCount,
   transportState,
   isCasting,
+  castSource,
 }: DeviceCardProps) {
   const { t } = useTranslation();
 
@@ -43,6 +47,8 @@ export function DeviceCard({
   const displayState = isCasting && isPlaying ? t('device.streaming') : transportState;
   const statusClass =
     isCasting && isPlaying ? styles.statusCasting : isPlaying ? styles.statusPlaying : '';
+  const sourceLabel =
+    castSource?.label || castSource?.sourceTitle || castSource?.title || castSource?.source;
 
   return (
     <Card noPadding className={styles.container}>
@@ -68,6 +74,14 @@ export function DeviceCard({
             </span>
           )}
         </div>
+        {isCasting && sourceLabel && (
+          <div className={styles.source} title={castSource?.sourceUrl}>
+            {castSource?.faviconUrl && (
+              <img className={styles.favicon} src={castSource.faviconUrl} alt="" />
+            )}
+            <span className={styles.sourceTitle}>{sourceLabel}</span>
+          </div>
+        )}
       </div>
     </Card>
   );
@@ -1,7 +1,8 @@
 import { useState, useRef } from 'preact/hooks';
 import { useTranslation } from 'react-i18next';
 import { Input, Button } from '@thaumic-cast/ui';
-import { useAddManualSpeaker, getSpeakerErrorMessage } from '../hooks/useAddManualSpeaker';
+import { useAddManualSpeaker } from '../hooks/useAddManualSpeaker';
+import { getErrorMessage } from '../lib/errors';
 import styles from './ManualSpeakerForm.module.css';
 
 interface ManualSpeakerFormProps {
@@ -74,7 +75,11 @@ export function ManualSpeakerForm({
             : t('onboarding.speakers.manual_add')}
         </Button>
       </div>
-      {error && <div className={styles.error}>{getSpeakerErrorMessage(error, t)}</div>}
+      {error !== null && (
+        <div className={styles.error}>
+          {getErrorMessage(error, t, 'onboarding.speakers.manual_error_generic')}
+        </div>
+      )}
     </>
   );
 }
@@ -0,0 +1,52 @@
+import { useEffect } from 'preact/hooks';
+import { listen, type UnlistenFn } from '@tauri-apps/api/event';
+import { useTranslation } from 'react-i18next';
+import { Button, ButtonGroup } from '@thaumic-cast/ui';
+import type { OriginApprovalPayload } from '../lib/events';
+import {
+  pendingOrigins,
+  fetchOriginApprovals,
+  approveOrigin,
+  denyOrigin,
+} from '../state/store';
+import styles from './OriginApprovalPrompt.module.css';
+
+/**
+ * Asks the user to approve browser extensions the server doesn't trust yet.
+ *
+ * Shows the oldest pending extension; the rest follow once it is decided.
+ * @returns The rendered prompt, or null when nothing is pending
+ */
+export function OriginApprovalPrompt(): preact.JSX.Element | null {
+  const { t } = useTranslation();
+
+  useEffect(() => {
+    let unlisten: UnlistenFn | undefined;
+    fetchOriginApprovals();
+    listen<OriginApprovalPayload>('origin-approval-requested', () => {
+      fetchOriginApprovals();
+    }).then((fn) => (unlisten = fn));
+    return () => unlisten?.();
+  }, []);
+
+  const pending = pendingOrigins.value[0];
+  if (!pending) {
+    return null;
+  }
+
+  return (
+    <div className={styles.prompt} role="alertdialog" aria-labelledby="origin-approval-title">
+      <h2 id="origin-approval-title" className={styles.title}>
+        {t('origins.prompt_title')}
+      </h2>
+      <p>{t(`origins.prompt_body_${pending.browser}`)}</p>
+      <code className={styles.origin}>{pending.origin}</code>
+      <ButtonGroup>
+        <Button onClick={() => approveOrigin(pending.origin)}>{t('origins.approve')}</Button>
+        <Button variant="secondary" onClick={() => denyOrigin(pending.origin)}>
+          {t('origins.deny')}
+        </Button>
+      </ButtonGroup>
+    </div>
+  );
+}
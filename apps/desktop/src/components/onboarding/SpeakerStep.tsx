@@ -77,6 +77,7 @@ export function SpeakerStep({ onSpeakersFound }: SpeakerStepProps): preact.JSX.E
     listen<NetworkHealthPayload>('network-health-changed', (event) => {
       log.debug('Network health changed:', event.payload);
       networkHealth.value = {
+        ...networkHealth.value,
         health: event.payload.health,
         reason: event.payload.reason,
       };
@@ -172,8 +173,8 @@ export function SpeakerStep({ onSpeakersFound }: SpeakerStepProps): preact.JSX.E
             <div key={group.coordinatorUuid} className={styles.speakerItem}>
               <Speaker size={16} />
               <span>{coordinator.zoneName}</span>
-              {group.members.length > 1 && (
-                <span className={styles.memberCount}>+{group.members.length - 1}</span>
+              {group.roomCount > 1 && (
+                <span className={styles.memberCount}>+{group.roomCount - 1}</span>
               )}
             </div>
           );
@@ -9,8 +9,8 @@ interface UseAddManualSpeakerOptions {
 interface UseAddManualSpeakerResult {
   /** Whether a probe/add operation is in progress */
   isTesting: boolean;
-  /** Error message from last failed attempt, or null */
-  error: string | null;
+  /** Caught error from the last failed attempt, or null */
+  error: unknown;
   /** Add a speaker by IP address */
   addSpeaker: (ip: string) => Promise<boolean>;
   /** Clear any existing error */
@@ -29,7 +29,7 @@ export function useAddManualSpeaker(
 ): UseAddManualSpeakerResult {
   const { onSuccess } = options;
   const [isTesting, setIsTesting] = useState(false);
-  const [error, setError] = useState<string | null>(null);
+  const [error, setError] = useState<unknown>(null);
 
   // Track mounted state to prevent state updates after unmount
   const mountedRef = useRef(true);
@@ -57,22 +57,27 @@ export function useAddManualSpeaker(
       const [result] = await Promise.all([
         probeSpeakerIp(trimmedIp)
           .then((speaker) => ({ success: true as const, ip: speaker.ip }))
-          .catch((e) => ({ success: false as const, ip: '', error: String(e) })),
+          .catch((e: unknown) => ({ success: false as const, ip: '', error: e })),
         new Promise((resolve) => setTimeout(resolve, 400)),
       ]);
 
       if (!mountedRef.current) return false;
 
-      if (result.success) {
+      if (!result.success) {
+        setError(result.error);
+        return false;
+      }
+
+      try {
         // Use the cleaned IP from the probe result, not the original user input
         await addManualSpeakerIp(result.ip);
-        // Backend automatically triggers topology refresh; views update via event listener
-        onSuccessRef.current?.(result.ip);
-        return true;
-      } else {
-        setError(result.error);
+      } catch (e) {
+        if (mountedRef.current) setError(e);
         return false;
       }
+      // Backend automatically triggers topology refresh; views update via event listener
+      onSuccessRef.current?.(result.ip);
+      return true;
     } finally {
       if (mountedRef.current) {
         setIsTesting(false);
@@ -91,24 +96,3 @@ export function useAddManualSpeaker(
     clearError,
   };
 }
-
-/**
- * Get a localized error message for speaker probe errors.
- *
- * @param error - The error string from probe attempt
- * @param t - Translation function from useTranslation
- * @returns Localized error message
- */
-export function getSpeakerErrorMessage(error: string, t: (key: string) => string): string {
-  if (error.includes('ip_unreachable')) {
-    return t('onboarding.speakers.manual_error_unreachable');
-  }
-  if (error.includes('not_sonos_device')) {
-    return t('onboarding.speakers.manual_error_not_sonos');
-  }
-  if (error.includes('invalid_ip')) {
-    return t('onboarding.speakers.manual_error_invalid');
-  }
-  // Generic fallback for unexpected errors (timeout, network issues, etc.)
-  return t('onboarding.speakers.manual_error_generic');
-}
@@ -0,0 +1,71 @@
+/**
+ * Errors returned by Tauri commands.
+ *
+ * Failed commands reject with the same body the HTTP API returns on error,
+ * so both can be handled by code and localized via `messageKey`.
+ */
+
+/**
+ * Machine-readable hint describing how the user can recover.
+ */
+export interface Remediation {
+  /** Suggested action identifier (e.g., "check_speaker_power") */
+  action: string;
+  /** Short human-readable suggestion */
+  hint: string;
+  /** Whether retrying may succeed without user action */
+  retryable: boolean;
+  /** When the speaker concerned was last discovered (ms since epoch) */
+  lastSeen?: number;
+}
+
+/**
+ * Rejection value of a failed Tauri command.
+ */
+export interface CommandError {
+  /** Machine-readable error code (e.g., "ip_unreachable") */
+  error: string;
+  /** Human-readable message (English) */
+  message: string;
+  /** i18n key for localizing the message (e.g., "error.ip_unreachable") */
+  messageKey: string;
+  /** HTTP status the same failure has on the API */
+  status: number;
+  /** Remediation hint, when one applies */
+  details?: Remediation;
+}
+
+/**
+ * Checks whether a caught value is a structured command error.
+ * @param value - The caught value
+ * @returns True if the value has the command error shape
+ */
+export function isCommandError(value: unknown): value is CommandError {
+  return (
+    typeof value === 'object' &&
+    value !== null &&
+    typeof (value as CommandError).error === 'string' &&
+    typeof (value as CommandError).messageKey === 'string'
+  );
+}
+
+/**
+ * Translation function shape used to localize errors.
+ */
+type Translate = (key: string, options?: { defaultValue?: string }) => string;
+
+/**
+ * Returns a localized message for a caught value.
+ *
+ * Command errors are looked up by `messageKey`, falling back to their English
+ * `message` when no translation exists.
+ * @param value - The caught value
+ * @param t - Translation function from useTranslation
+ * @param fallbackKey - Key shown for unstructured errors
+ * @returns Localized error message
+ */
+export function getErrorMessage(value: unknown, t: Translate, fallbackKey: string): string {
+  return isCommandError(value)
+    ? t(value.messageKey, { defaultValue: value.message })
+    : t(fallbackKey);
+}
@@ -26,6 +26,14 @@ export interface TransportStatePayload {
   state: string;
 }
 
+/**
+ * Payload from the origin-approval-requested Tauri event.
+ * Emitted when an unknown browser extension calls the API.
+ */
+export interface OriginApprovalPayload {
+  origin: string;
+}
+
 /**
  * Listens for a Tauri event once, with a timeout fallback.
  * The listener is registered before returning, ensuring no race conditions
@@ -1,6 +1,7 @@
 import { signal } from '@preact/signals';
-import { invoke } from '@tauri-apps/api/core';
+import { Channel, invoke } from '@tauri-apps/api/core';
 import { createLogger } from '@thaumic-cast/shared';
+import type { Remediation } from '../lib/errors';
 
 const log = createLogger('Store');
 
@@ -12,16 +13,27 @@ export interface Speaker {
   ip: string;
 }
 
+/** Only players can be cast to; the other roles play through another speaker or not at all. */
+export type DeviceRole = 'player' | 'satellite' | 'subwoofer' | 'bridge';
+
 export interface ZoneGroupMember {
   uuid: string;
   ip: string;
   zoneName: string;
   model: string;
+  role: DeviceRole;
+  /** Bonded set (stereo pair, home theater) the member belongs to */
+  bond?: { kind: 'stereoPair' | 'homeTheater' | 'withSubwoofer'; primaryUuid: string };
 }
 
 export interface ZoneGroup {
   id: string;
+  /** Every room in the group, coordinator first ("Kitchen, Office") */
   name: string;
+  /** Short label in the Sonos app's style ("Kitchen + 1") */
+  label: string;
+  /** Rooms in the group, counting bonded sets (stereo pairs, home theaters) once */
+  roomCount: number;
   coordinatorUuid: string;
   coordinatorIp: string;
   members: ZoneGroupMember[];
@@ -30,11 +42,26 @@ export interface ZoneGroup {
 /** Map of speaker IP to transport state (Playing, Stopped, etc.). */
 export type TransportStates = Record<string, string>;
 
+/** What a stream is casting: its label, track details and the source page. */
+export interface CastSource {
+  label?: string;
+  title?: string | null;
+  artist?: string | null;
+  source?: string | null;
+  sourceUrl?: string;
+  sourceTitle?: string;
+  faviconUrl?: string;
+}
+
 /** Active playback session linking a stream to a speaker. */
 export interface PlaybackSession {
   streamId: string;
   speakerIp: string;
   streamUrl: string;
+  /** User-given name for the stream. */
+  label?: string;
+  /** The stream's current metadata, if the stream still exists. */
+  metadata?: Omit<CastSource, 'label'>;
 }
 
 /** Set of speaker IPs that are currently casting our streams. */
@@ -47,6 +74,10 @@ export type NetworkHealthStatus = 'ok' | 'degraded';
 export interface NetworkHealth {
   health: NetworkHealthStatus;
   reason: string | null;
+  /** Set while S1 speakers limit the stream formats. */
+  compatibilityWarning: string | null;
+  /** UUIDs of the speakers running S1 software. */
+  s1Speakers: string[];
 }
 
 // Global State
@@ -54,10 +85,18 @@ export const speakers = signal<Speaker[]>([]);
 export const groups = signal<ZoneGroup[]>([]);
 export const transportStates = signal<TransportStates>({});
 export const castingSpeakers = signal<CastingSpeakers>(new Set());
+/** What each casting speaker is playing, keyed by speaker IP. */
+export const castSources = signal<Record<string, CastSource>>({});
 export const serverPort = signal<number>(0);
 export const isLoading = signal<boolean>(false);
 export const stats = signal<AppStats | null>(null);
-export const networkHealth = signal<NetworkHealth>({ health: 'ok', reason: null });
+export const statsSummary = signal<StatsSummary | null>(null);
+export const networkHealth = signal<NetworkHealth>({
+  health: 'ok',
+  reason: null,
+  compatibilityWarning: null,
+  s1Speakers: [],
+});
 
 export interface AppStats {
   connectionCount: number;
@@ -66,6 +105,40 @@ export interface AppStats {
   localIp: string;
   port: number;
   maxStreams: number;
+  resources: ResourceStats;
+  soap: SoapSpeakerStats[];
+}
+
+/** Latest process resource sample (CPU relative to total machine capacity). */
+export interface ResourceStats {
+  cpuPercent: number;
+  memoryBytes: number;
+  networkRxBytesPerSec: number;
+  networkTxBytesPerSec: number;
+}
+
+/** SOAP request statistics for one speaker. */
+export interface SoapSpeakerStats {
+  ip: string;
+  requests: number;
+  failures: number;
+  /** TCP connections opened; requests beyond this reused a pooled connection. */
+  connectionsOpened: number;
+  avgLatencyMs: number;
+  maxLatencyMs: number;
+  inFlight: number;
+}
+
+/** Persistent casting statistics (local-only counters). */
+export interface StatsSummary {
+  totalPlays: number;
+  totalHours: number;
+  /** Speakers, most cast to first. */
+  speakers: { ip: string; name: string | null; plays: number; hours: number }[];
+  /** Stream formats, most used first. */
+  presets: { preset: string; streams: number }[];
+  /** The last 30 days (UTC), oldest first. */
+  daily: { date: string; hours: number }[];
 }
 
 // ─────────────────────────────────────────────────────────────────────────────
@@ -92,6 +165,14 @@ export const fetchStats = async (): Promise<void> => {
   serverPort.value = fetchedStats.port;
 };
 
+/**
+ * Fetches persistent casting statistics from the backend.
+ * Updates the statsSummary signal.
+ */
+export const fetchStatsSummary = async (): Promise<void> => {
+  statsSummary.value = await invoke<StatsSummary>('get_stats_summary');
+};
+
 /**
  * Fetches transport states from the backend.
  * Updates the transportStates signal.
@@ -130,7 +211,7 @@ export const updateTransportState = (speakerIp: string, state: string): void =>
  * @param reason - Optional reason for degraded health
  */
 export const updateNetworkHealth = (health: NetworkHealthStatus, reason: string | null): void => {
-  networkHealth.value = { health, reason };
+  networkHealth.value = { ...networkHealth.value, health, reason };
 };
 
 /**
@@ -152,6 +233,9 @@ export const fetchGroups = async (): Promise<void> => {
     groups.value = [...fetchedGroups].sort((a, b) => a.name.localeCompare(b.name));
     transportStates.value = states;
     castingSpeakers.value = new Set(sessions.map((s) => s.speakerIp));
+    castSources.value = Object.fromEntries(
+      sessions.map((s) => [s.speakerIp, { ...s.metadata, label: s.label }]),
+    );
 
     // Debug: log if health changed
     if (networkHealth.value.health !== health.health) {
@@ -277,6 +361,17 @@ export interface ProbeResult {
   name: string;
   uuid: string;
   modelName?: string;
+  /** Bridges (Boost, Bridge) and subwoofers can't be cast to */
+  role: DeviceRole;
+  softwareVersion?: string;
+  displayVersion?: string;
+  hardwareVersion?: string;
+  /** Software generation: 1 for S1, 2 for S2 */
+  swGen?: number;
+  /** Sonos household, when the speaker answered SSDP */
+  householdId?: string;
+  /** Set for S1 speakers, whose stream formats are limited */
+  compatibilityWarning?: string;
 }
 
 /**
@@ -289,6 +384,110 @@ export const probeSpeakerIp = async (ip: string): Promise<ProbeResult> => {
   return invoke<ProbeResult>('probe_speaker_ip', { ip });
 };
 
+/**
+ * Scans the network for speakers, reporting each as soon as it resolves.
+ * @param onSpeaker - Called once per speaker, in the order they resolve
+ * @returns Number of speakers found
+ * @throws CommandError if every discovery method fails
+ */
+export const discoverSpeakers = async (
+  onSpeaker: (speaker: ProbeResult) => void,
+): Promise<number> => {
+  const channel = new Channel<ProbeResult>();
+  channel.onmessage = onSpeaker;
+  return invoke<number>('discover_speakers_progressive', { onSpeaker: channel });
+};
+
+/** Estimated throughput from this computer to a speaker. */
+export interface BandwidthEstimate {
+  speakerIp: string;
+  bytesSent: number;
+  durationMs: number;
+  throughputKbps: number;
+  recommendedCodec: 'pcm' | 'aac' | 'mp3' | 'flac';
+}
+
+/**
+ * Measures throughput to a speaker with a short upload burst.
+ * @param ip - The speaker IP address
+ * @returns Estimated throughput and recommended codec
+ * @throws Error if the speaker is unreachable
+ */
+export const measureSpeakerBandwidth = async (ip: string): Promise<BandwidthEstimate> => {
+  return invoke<BandwidthEstimate>('measure_speaker_bandwidth', { ip });
+};
+
+/** A firmware problem detected on a speaker. */
+export interface FirmwareIssue {
+  id: string;
+  summary: string;
+  remediation: Remediation;
+}
+
+/** Model and software versions of a speaker. */
+export interface SpeakerFirmware {
+  ip: string;
+  uuid: string;
+  name: string;
+  modelName?: string;
+  modelNumber?: string;
+  seriesId?: string;
+  hardwareVersion?: string;
+  softwareVersion?: string;
+  displayVersion?: string;
+  swGen?: number;
+  reachable: boolean;
+  issues: FirmwareIssue[];
+}
+
+/** Firmware inventory of the household. */
+export interface FirmwareInventory {
+  speakers: SpeakerFirmware[];
+  latestSoftwareVersion?: string;
+  /** Number of speakers with at least one issue. */
+  flagged: number;
+}
+
+/**
+ * Queries every speaker in the topology for its firmware versions.
+ * @returns The inventory, with known firmware issues flagged
+ */
+export const getSpeakerInventory = async (): Promise<FirmwareInventory> => {
+  return invoke<FirmwareInventory>('get_speaker_inventory');
+};
+
+/** A long-running operation in progress on the server. */
+export interface OperationInfo {
+  id: string;
+  kind: 'discovery' | 'speakerProbe' | 'bandwidthTest';
+  /** What the operation acts on (e.g., a speaker IP) */
+  target?: string;
+  /** Current step, for operations that report steps */
+  stage?: string;
+  completed: number;
+  total?: number;
+  /** Whether cancellation has been requested */
+  cancelling: boolean;
+  startedAt: number;
+}
+
+/**
+ * Lists in-flight discovery runs, speaker probes and bandwidth tests.
+ * @returns Operations with their progress, oldest first
+ */
+export const getOperations = async (): Promise<OperationInfo[]> => {
+  return invoke<OperationInfo[]>('get_operations');
+};
+
+/**
+ * Cancels an in-flight operation.
+ * @param id - The operation ID from getOperations
+ * @throws CommandError with code "operation_not_found" if it already finished
+ */
+export const cancelOperation = async (id: string): Promise<void> => {
+  await invoke('cancel_operation', { id });
+};
+
 /**
  * Adds a manually configured speaker IP address.
  * The IP should be pre-validated with probeSpeakerIp first.
@@ -313,3 +512,87 @@ export const removeManualSpeakerIp = async (ip: string): Promise<void> => {
 export const getManualSpeakerIps = async (): Promise<string[]> => {
   return invoke<string[]>('get_manual_speaker_ips');
 };
+
+/** Manual and ignored speakers, as moved between installations. */
+export interface SpeakerSetup {
+  manualSpeakers: string[];
+  ignoredSpeakers: string[];
+}
+
+/** Outcome of importing a speaker setup. */
+export interface SpeakerSetupImport {
+  manualSpeakersAdded: number;
+  ignoredSpeakersAdded: number;
+  /** Entries left out because they aren't valid addresses or UUIDs */
+  skipped: string[];
+}
+
+/**
+ * Exports the manual and ignored speakers, for the desktop app or server.
+ * @returns The speaker setup
+ */
+export const exportManualSpeakers = async (): Promise<SpeakerSetup> => {
+  return invoke<SpeakerSetup>('export_manual_speakers');
+};
+
+/**
+ * Adds an exported speaker setup to the existing speakers.
+ * @param setup - Setup exported by the desktop app or server
+ * @returns What was added
+ */
+export const importManualSpeakers = async (setup: SpeakerSetup): Promise<SpeakerSetupImport> => {
+  return invoke<SpeakerSetupImport>('import_manual_speakers', { setup });
+};
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Extension Approval
+// ─────────────────────────────────────────────────────────────────────────────
+
+/** A browser extension waiting for the user to approve it. */
+export interface PendingOrigin {
+  /** Extension origin (e.g. "chrome-extension://<id>", "moz-extension://<uuid>") */
+  origin: string;
+  /** Browser the extension runs in; Chromium covers Chrome and Edge */
+  browser: 'chromium' | 'firefox';
+  firstSeen: number;
+  lastSeen: number;
+  /** Requests refused so far */
+  attempts: number;
+}
+
+/** Approved, pending and denied extension origins. */
+export interface OriginApprovalState {
+  approved: string[];
+  pending: PendingOrigin[];
+  denied: string[];
+}
+
+/** Extensions waiting for approval, oldest first. */
+export const pendingOrigins = signal<PendingOrigin[]>([]);
+
+/**
+ * Fetches extension approvals from the backend.
+ * Updates the pendingOrigins signal.
+ */
+export const fetchOriginApprovals = async (): Promise<void> => {
+  const state = await invoke<OriginApprovalState>('get_origin_approvals');
+  pendingOrigins.value = state.pending;
+};
+
+/**
+ * Trusts a browser extension from now on.
+ * @param origin - The extension origin
+ */
+export const approveOrigin = async (origin: string): Promise<void> => {
+  await invoke('approve_origin', { origin });
+  await fetchOriginApprovals();
+};
+
+/**
+ * Refuses a browser extension until the app restarts.
+ * @param origin - The extension origin
+ */
+export const denyOrigin = async (origin: string): Promise<void> => {
+  await invoke('deny_origin', { origin });
+  await fetchOriginApprovals();
+};
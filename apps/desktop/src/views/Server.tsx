@@ -1,17 +1,28 @@
 import { useEffect, useState } from 'preact/hooks';
-import { stats, fetchStats, clearAllConnections, restartServer, stopAll } from '../state/store';
+import {
+  stats,
+  statsSummary,
+  fetchStats,
+  fetchStatsSummary,
+  clearAllConnections,
+  restartServer,
+  stopAll,
+} from '../state/store';
 import { ActionButton, Button, Card } from '@thaumic-cast/ui';
 import { useTranslation } from 'react-i18next';
-import { Copy, Check, RefreshCcw, Unplug, Square, Circle } from 'lucide-preact';
+import { Copy, Check, RefreshCcw, Unplug, Square, Circle, History } from 'lucide-preact';
 import styles from './Server.module.css';
 
 /** Duration to show "copied" feedback before reverting to copy icon (ms). */
 const COPIED_FEEDBACK_DURATION_MS = 2000;
 
+/** Days counted for the "this week" total. */
+const WEEK_DAYS = 7;
+
 /**
  * Server management page.
  *
- * Displays server status, connection info, and provides controls for:
+ * Displays server status, connection info, casting history, and provides controls for:
  * - Restarting the server
  * - Disconnecting all clients
  * - Stopping all streams
@@ -23,10 +34,16 @@ export function Server() {
 
   useEffect(() => {
     fetchStats();
+    fetchStatsSummary();
     const interval = setInterval(fetchStats, 5000);
     return () => clearInterval(interval);
   }, []);
 
+  const summary = statsSummary.value;
+  const topSpeaker = summary?.speakers[0];
+  const topPreset = summary?.presets[0];
+  const weekHours = summary?.daily.slice(-WEEK_DAYS).reduce((sum, day) => sum + day.hours, 0) ?? 0;
+
   const copyAddress = () => {
     if (stats.value) {
       const url = `http://${stats.value.localIp}:${stats.value.port}`;
@@ -77,6 +94,42 @@ export function Server() {
         </dl>
       </Card>
 
+      {/* History Section */}
+      <Card title={t('server.history')} icon={History} titleLevel="h3" className={styles.section}>
+        <dl className={styles.statusGrid}>
+          <div className={styles.statusItem}>
+            <dt className={styles.statusLabel}>{t('server.most_cast_to')}</dt>
+            <dd className={styles.statusValue}>
+              {topSpeaker
+                ? t('server.speaker_plays', {
+                    name: topSpeaker.name ?? topSpeaker.ip,
+                    count: topSpeaker.plays,
+                  })
+                : '---'}
+            </dd>
+          </div>
+
+          <div className={styles.statusItem}>
+            <dt className={styles.statusLabel}>{t('server.hours_this_week')}</dt>
+            <dd className={styles.statusValue}>
+              {t('server.hours', { count: Math.round(weekHours * 10) / 10 })}
+            </dd>
+          </div>
+
+          <div className={styles.statusItem}>
+            <dt className={styles.statusLabel}>{t('server.hours_total')}</dt>
+            <dd className={styles.statusValue}>
+              {t('server.hours', { count: summary?.totalHours ?? 0 })}
+            </dd>
+          </div>
+
+          <div className={styles.statusItem}>
+            <dt className={styles.statusLabel}>{t('server.favourite_format')}</dt>
+            <dd className={styles.statusValue}>{topPreset?.preset ?? '---'}</dd>
+          </div>
+        </dl>
+      </Card>
+
       {/* Actions Section */}
       <Card title={t('server.actions')} titleLevel="h3" className={styles.section}>
         <div className={styles.actionList}>
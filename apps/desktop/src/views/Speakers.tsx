@@ -6,6 +6,7 @@ import {
   groups,
   transportStates,
   castingSpeakers,
+  castSources,
   networkHealth,
   fetchGroups,
   debouncedFetchGroups,
@@ -74,6 +75,8 @@ export function Speakers() {
     listen('stream-ended', debouncedFetchGroups).then((fn) => unlisteners.push(fn));
     listen('playback-started', debouncedFetchGroups).then((fn) => unlisteners.push(fn));
     listen('playback-stopped', debouncedFetchGroups).then((fn) => unlisteners.push(fn));
+    listen('stream-metadata-updated', debouncedFetchGroups).then((fn) => unlisteners.push(fn));
+    listen('stream-label-changed', debouncedFetchGroups).then((fn) => unlisteners.push(fn));
 
     // Listen for transport state changes (direct state update, no fetch needed)
     listen<TransportStatePayload>('transport-state-changed', (event) => {
@@ -148,9 +151,10 @@ export function Speakers() {
               key={coordinator.uuid}
               speaker={coordinator}
               isCoordinator={true}
-              memberCount={group.members.length}
+              memberCount={group.roomCount}
               transportState={transportStates.value[group.coordinatorIp]}
               isCasting={castingSpeakers.value.has(group.coordinatorIp)}
+              castSource={castSources.value[group.coordinatorIp]}
             />
           ))}
         </div>
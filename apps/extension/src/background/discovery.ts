@@ -17,6 +17,7 @@
 import { DEFAULT_MAX_CONCURRENT_STREAMS } from '@thaumic-cast/protocol';
 import { createLogger } from '@thaumic-cast/shared';
 import { loadExtensionSettings } from '../lib/settings';
+import { isApprovalPending } from '../lib/serverTest';
 import { getConnectionState, setDesktopApp } from './connection-state';
 
 const log = createLogger('Discovery');
@@ -34,6 +35,8 @@ export interface DiscoveredApp {
   url: string;
   /** Maximum concurrent streams allowed by the server. */
   maxStreams: number;
+  /** Whether the app is waiting for the user to approve this extension. */
+  awaitingApproval?: boolean;
 }
 
 /**
@@ -46,7 +49,7 @@ const CACHE_TTL = 5 * 60 * 1000;
  * @param url - The URL to probe
  * @returns Discovered app info or null if not valid
  */
-async function probeUrl(url: string): Promise<DiscoveredApp | null> {
+export async function probeUrl(url: string): Promise<DiscoveredApp | null> {
   try {
     const response = await fetch(`${url}/health`, {
       signal: AbortSignal.timeout(500),
@@ -61,6 +64,8 @@ async function probeUrl(url: string): Promise<DiscoveredApp | null> {
           maxStreams: data.limits?.maxStreams || DEFAULT_MAX_CONCURRENT_STREAMS,
         };
       }
+    } else if (await isApprovalPending(response)) {
+      return { url, maxStreams: DEFAULT_MAX_CONCURRENT_STREAMS, awaitingApproval: true };
     }
   } catch {
     // URL is unreachable or timed out
@@ -92,7 +97,7 @@ export async function discoverDesktopApp(force = false): Promise<DiscoveredApp |
     const app = await probeUrl(settings.serverUrl);
 
     if (app) {
-      setDesktopApp(app.url, app.maxStreams);
+      if (!app.awaitingApproval) setDesktopApp(app.url, app.maxStreams);
       return app;
     }
 
@@ -131,7 +136,14 @@ export async function discoverDesktopApp(force = false): Promise<DiscoveredApp |
 
   const scanPromises = ports.map((port) => probeUrl(`http://localhost:${port}`));
   const results = await Promise.all(scanPromises);
-  const foundApp = results.find((app) => app !== null);
+  const foundApp =
+    results.find((app) => app !== null && !app.awaitingApproval) ??
+    results.find((app) => app !== null);
+
+  if (foundApp?.awaitingApproval) {
+    log.info(`Desktop App at ${foundApp.url} is waiting for this extension to be approved`);
+    return foundApp;
+  }
 
   if (foundApp) {
     log.info(`Desktop App discovered at: ${foundApp.url} (Limit: ${foundApp.maxStreams})`);
@@ -42,7 +42,7 @@ import { stopCastForTab } from '../sonos-event-handlers';
 import { ensureOffscreen } from '../offscreen-manager';
 import { offscreenBroker } from '../offscreen-broker';
 import { detectAndCacheCodecSupport } from '../codec-support';
-import { discoverAndCache, connectWebSocket } from './connection';
+import { discoverAndCache, connectWebSocket, APPROVAL_PENDING_ERROR } from './connection';
 
 const log = createLogger('Background');
 
@@ -70,6 +70,7 @@ export async function handleStartCast(msg: StartCastMessage): Promise<ExtensionR
       clearConnectionState();
       throw new Error('error_desktop_not_found');
     }
+    if (app.awaitingApproval) throw new Error(APPROVAL_PENDING_ERROR);
 
     // 2. Check session limits
     if (getSessionCount() >= app.maxStreams) {
@@ -163,15 +164,14 @@ export async function handleStartCast(msg: StartCastMessage): Promise<ExtensionR
 
     if (captureResponse.success && captureResponse.streamId) {
       const cachedState = getCachedState(tabId);
-      const initialMetadata: StreamMetadata | undefined = cachedState?.metadata
-        ? {
-            title: cachedState.metadata.title,
-            artist: cachedState.metadata.artist,
-            source: cachedState.source,
-          }
-        : cachedState?.source
-          ? { source: cachedState.source }
-          : undefined;
+      const initialMetadata: StreamMetadata = {
+        title: cachedState?.metadata?.title,
+        artist: cachedState?.metadata?.artist,
+        source: cachedState?.source ?? getSourceFromUrl(tab.url),
+        sourceUrl: tab.url,
+        sourceTitle: tab.title,
+        faviconUrl: tab.favIconUrl,
+      };
 
       const playbackResponse = await offscreenBroker.startPlayback(
         tabId,
@@ -186,7 +186,7 @@ export async function handleStartCast(msg: StartCastMessage): Promise<ExtensionR
       if (successfulResults.length === 0) {
         log.error('All playback attempts failed, cleaning up capture');
         await cleanupCapture(tabId);
-        throw new Error('error_playback_failed');
+        throw new Error(playbackResponse.error ?? 'error_playback_failed');
       }
 
       for (const failed of playbackResponse.results.filter((r) => !r.success)) {
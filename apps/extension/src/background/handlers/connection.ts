@@ -21,7 +21,7 @@ import type {
   NetworkEventMessage,
   TopologyEventMessage,
 } from '../../lib/messages';
-import { discoverDesktopApp } from '../discovery';
+import { discoverDesktopApp, probeUrl } from '../discovery';
 import {
   getConnectionState,
   setConnected,
@@ -35,6 +35,7 @@ import { ensureOffscreen } from '../offscreen-manager';
 import { offscreenBroker } from '../offscreen-broker';
 import { notifyPopup } from '../notification-service';
 import { clearAllSessions } from '../session-manager';
+import { buildWsUrl } from '../../lib/ws-url';
 
 const log = createLogger('Background');
 
@@ -44,15 +45,20 @@ export interface DiscoverResult {
   url: string;
   /** Maximum concurrent streams allowed */
   maxStreams: number;
+  /** Whether the app is waiting for the user to approve this extension */
+  awaitingApproval?: boolean;
 }
 
+/** Error key shown while the desktop app waits for this extension to be approved. */
+export const APPROVAL_PENDING_ERROR = 'error_origin_approval_pending';
+
 /**
  * Connects to the desktop app WebSocket via offscreen document.
  * @param serverUrl - The desktop app HTTP URL
  */
 export async function connectWebSocket(serverUrl: string): Promise<void> {
   await ensureOffscreen();
-  const wsUrl = serverUrl.replace(/^http/, 'ws') + '/ws';
+  const wsUrl = buildWsUrl(serverUrl, 'events');
   log.info(`Connecting WebSocket to: ${wsUrl}`);
   await offscreenBroker.connectWebSocket(wsUrl);
 }
@@ -84,6 +90,11 @@ export async function handleWsConnectRequest(url: string, maxStreams?: number):
 export async function discoverAndCache(force = false): Promise<DiscoverResult | null> {
   const app = await discoverDesktopApp(force);
   if (!app) return null;
+  if (app.awaitingApproval) {
+    // The app has queued us for approval; connecting would only be refused
+    setConnectionError(APPROVAL_PENDING_ERROR);
+    return { url: app.url, maxStreams: app.maxStreams, awaitingApproval: true };
+  }
   // Note: setDesktopApp is now called inside discoverDesktopApp
   return { url: app.url, maxStreams: app.maxStreams };
 }
@@ -137,6 +148,37 @@ export function handleWsPermanentlyDisconnected(): void {
   setConnectionError('error_connection_lost');
   log.warn('WebSocket permanently disconnected');
   notifyPopup({ type: 'WS_CONNECTION_LOST', reason: 'max_retries_exceeded' });
+
+  // The WebSocket can't see why it was refused; ask /health whether the app
+  // stopped trusting us rather than reporting a plain connection loss.
+  const { desktopAppUrl } = getConnectionState();
+  if (desktopAppUrl) {
+    probeUrl(desktopAppUrl)
+      .then((app) => {
+        if (app?.awaitingApproval) reportAwaitingApproval();
+      })
+      .catch(() => {});
+  }
+}
+
+/**
+ * Records that the desktop app is waiting for the user to approve this extension.
+ * @returns The response for the popup, which offers a retry once approved
+ */
+function reportAwaitingApproval(): EnsureConnectionResponse {
+  log.info('Desktop App is waiting for this extension to be approved');
+  setConnectionError(APPROVAL_PENDING_ERROR);
+  notifyPopup({
+    type: 'CONNECTION_ATTEMPT_FAILED',
+    error: APPROVAL_PENDING_ERROR,
+    canRetry: true,
+  });
+  return {
+    connected: false,
+    desktopAppUrl: null,
+    maxStreams: null,
+    error: APPROVAL_PENDING_ERROR,
+  };
 }
 
 /**
@@ -199,6 +241,10 @@ export async function ensureConnection(): Promise<EnsureConnectionResponse> {
       };
     }
 
+    if (app.awaitingApproval) {
+      return reportAwaitingApproval();
+    }
+
     // Connect WebSocket
     await connectWebSocket(app.url);
 
@@ -177,11 +177,15 @@ export async function handleTabMetadataUpdate(
   // If this tab is casting, notify popup and forward to offscreen
   if (hasSession(tabId)) {
     onMetadataUpdate(tabId);
-    // Enrich metadata with source for Sonos display
+    // Enrich metadata with source for Sonos display, and the page itself
+    // for the desktop app's activity view
     const streamMeta: StreamMetadata = {
       title: metadata?.title,
       artist: metadata?.artist,
       source,
+      sourceUrl: sender.tab?.url,
+      sourceTitle: sender.tab?.title,
+      faviconUrl: sender.tab?.favIconUrl,
     };
     forwardMetadataToOffscreen(tabId, streamMeta);
 
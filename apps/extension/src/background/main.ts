@@ -181,7 +181,7 @@ chrome.storage.local.onChanged.addListener(async (changes) => {
 
     // Trigger fresh discovery and connection
     const app = await discoverAndCache(true);
-    if (app) {
+    if (app && !app.awaitingApproval) {
       await connectWebSocket(app.url);
     }
 
@@ -52,6 +52,12 @@ const TRANSPORT_DEBOUNCE_MS = 500;
 const recentlyRemovedSpeakers = new Map<string, number>();
 const REMOVAL_DEDUPE_MS = 2000;
 
+/** Speakers whose playback the server has suspended while another source plays */
+const suspendedSpeakers = new Set<string>();
+
+/** Speakers stopped from the Sonos app that the server resumes or holds for confirmation */
+const heldSpeakers = new Set<string>();
+
 /**
  * Handles a broadcast event from the desktop app.
  * Routes to appropriate handler based on event category and type.
@@ -100,6 +106,8 @@ export async function handleSonosEvent(event: BroadcastEvent): Promise<void> {
           eventData.speakerIp as string,
           eventData.reason as SpeakerRemovalReason | undefined,
         );
+        suspendedSpeakers.delete(eventData.speakerIp as string);
+        heldSpeakers.delete(eventData.speakerIp as string);
         break;
 
       case 'playbackStopFailed':
@@ -109,6 +117,33 @@ export async function handleSonosEvent(event: BroadcastEvent): Promise<void> {
           eventData.error as string,
         );
         break;
+
+      case 'feedbackLoopSuspected':
+        handleFeedbackLoopSuspected(eventData.streamId as string);
+        break;
+
+      case 'playbackSuspended':
+        log.info(`Playback on ${eventData.speakerIp} suspended by another source`);
+        suspendedSpeakers.add(eventData.speakerIp as string);
+        break;
+
+      case 'playbackStoppedExternally':
+        log.info(
+          `Playback on ${eventData.speakerIp} stopped from the Sonos app` +
+            (eventData.autoResume ? ', resuming' : ', awaiting confirmation'),
+        );
+        heldSpeakers.add(eventData.speakerIp as string);
+        break;
+
+      case 'lockedSpeakerChanged':
+        log.warn(`Locked speaker ${eventData.speakerIp} changed outside the lock: ${eventData.change}`);
+        break;
+
+      case 'playbackResumed':
+        log.info(`Playback on ${eventData.speakerIp} resumed`);
+        suspendedSpeakers.delete(eventData.speakerIp as string);
+        heldSpeakers.delete(eventData.speakerIp as string);
+        break;
     }
   } else if (event.category === 'latency') {
     await handleLatencyEvent(event as LatencyBroadcastEvent);
@@ -157,8 +192,10 @@ function handleTransportState(speakerIp: string, state: TransportState): void {
 
     log.info(`Transport state: ${speakerIp} → ${state}`);
 
-    // If Sonos stopped (e.g., stream killed due to underflow), remove speaker from session
-    if (state === 'Stopped') {
+    // If Sonos stopped (e.g., stream killed due to underflow), remove speaker from session.
+    // Speakers the server resumes or holds after a stop in the Sonos app are kept;
+    // it reports playbackStopped if they end up dropped.
+    if (state === 'Stopped' && !heldSpeakers.has(speakerIp)) {
       const session = getSessionBySpeakerIp(speakerIp);
       if (session) {
         log.warn(`Speaker ${speakerIp} stopped while casting - removing from session`);
@@ -286,10 +323,16 @@ function syncStateToOffscreen(): void {
  * Handles source changed events.
  * Auto-removes speaker from cast when user switches to another source (Spotify, AirPlay, etc.).
  * If no speakers remain, stops the entire cast.
+ * Suspended speakers are kept: the server resumes them once the other source
+ * stops, or reports playbackStopped when the resume window lapses.
  * @param speakerIp - The speaker IP address
  * @param currentUri - The current playback URI
  */
 async function handleSourceChanged(speakerIp: string, currentUri: string): Promise<void> {
+  if (suspendedSpeakers.has(speakerIp)) {
+    log.info(`Source changed on suspended speaker ${speakerIp}: ${currentUri}`);
+    return;
+  }
   log.warn(`Source changed on ${speakerIp}: ${currentUri}`);
   await handleSpeakerRemoval(speakerIp, 'source_changed');
 }
@@ -530,6 +573,27 @@ function handlePlaybackStopFailed(streamId: string, speakerIp: string, error: st
   });
 }
 
+/**
+ * Handles feedback loop warnings.
+ * The captured tab is playing our own stream back into itself, so ask the
+ * user to cast a different tab.
+ * @param streamId - The stream ID being fed back into itself
+ */
+function handleFeedbackLoopSuspected(streamId: string): void {
+  const session = getSessionByStreamId(streamId);
+  if (!session) {
+    log.debug(`FeedbackLoopSuspected: stream ${streamId} not found, ignoring`);
+    return;
+  }
+
+  log.warn(`Tab ${session.tabId} appears to be capturing its own stream`);
+
+  notifyPopup({
+    type: 'FEEDBACK_LOOP_SUSPECTED',
+    tabId: session.tabId,
+  });
+}
+
 /**
  * Handles latency measurement events.
  * Routes to:
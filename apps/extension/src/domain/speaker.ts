@@ -11,7 +11,7 @@
  * - Factory functions: easy conversion from protocol types
  */
 
-import type { ZoneGroup, ZoneGroupMember } from '@thaumic-cast/protocol';
+import { isCastableMember, type ZoneGroup, type ZoneGroupMember } from '@thaumic-cast/protocol';
 
 /**
  * Represents a single Sonos speaker.
@@ -170,12 +170,13 @@ export class SpeakerGroup {
    * @returns A new SpeakerGroup instance
    */
   static fromZoneGroup(group: ZoneGroup): SpeakerGroup {
-    const members = group.members.map(Speaker.fromMember);
+    // Bonded satellites play through their primary, so they aren't separate speakers
+    const members = group.members.filter(isCastableMember).map(Speaker.fromMember);
     const coordinator =
       members.find((m) => m.ip === group.coordinatorIp) ??
       new Speaker(group.coordinatorIp, group.name);
 
-    return new SpeakerGroup(group.id, group.name, coordinator, members);
+    return new SpeakerGroup(group.id, group.label ?? group.name, coordinator, members);
   }
 }
 
@@ -19,6 +19,7 @@ import {
   PlaybackResultSchema,
   LatencyBroadcastEvent,
   SpeakerRemovalReasonSchema,
+  TopologyEventSchema,
 } from '@thaumic-cast/protocol';
 
 // Re-export SpeakerRemovalReason from protocol for convenience
@@ -428,6 +429,12 @@ export const SpeakerStopFailedMessageSchema = z.object({
 });
 export type SpeakerStopFailedMessage = z.infer<typeof SpeakerStopFailedMessageSchema>;
 
+export const FeedbackLoopSuspectedMessageSchema = z.object({
+  type: z.literal('FEEDBACK_LOOP_SUSPECTED'),
+  tabId: TabIdSchema,
+});
+export type FeedbackLoopSuspectedMessage = z.infer<typeof FeedbackLoopSuspectedMessageSchema>;
+
 export const WsConnectionLostMessageSchema = z.object({
   type: z.literal('WS_CONNECTION_LOST'),
   reason: z.string(),
@@ -496,11 +503,7 @@ export type LatencyEventMessage = z.infer<typeof LatencyEventMessageSchema>;
 
 export const TopologyEventMessageSchema = z.object({
   type: z.literal('TOPOLOGY_EVENT'),
-  payload: z.object({
-    type: z.literal('groupsDiscovered'),
-    groups: SonosStateSnapshotSchema.shape.groups,
-    timestamp: z.number(),
-  }),
+  payload: TopologyEventSchema,
 });
 export type TopologyEventMessage = z.infer<typeof TopologyEventMessageSchema>;
 
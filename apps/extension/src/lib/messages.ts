@@ -125,6 +125,8 @@ export {
   type SpeakerRemovedMessage,
   SpeakerStopFailedMessageSchema,
   type SpeakerStopFailedMessage,
+  FeedbackLoopSuspectedMessageSchema,
+  type FeedbackLoopSuspectedMessage,
   WsConnectionLostMessageSchema,
   type WsConnectionLostMessage,
   ConnectionAttemptFailedMessageSchema,
@@ -204,6 +206,7 @@ import type {
   CastAutoStoppedMessage,
   SpeakerRemovedMessage,
   SpeakerStopFailedMessage,
+  FeedbackLoopSuspectedMessage,
   WsStateChangedMessage,
   VolumeUpdateMessage,
   MuteUpdateMessage,
@@ -272,6 +275,7 @@ export type BackgroundToPopupType =
   | 'CAST_AUTO_STOPPED'
   | 'SPEAKER_REMOVED'
   | 'SPEAKER_STOP_FAILED'
+  | 'FEEDBACK_LOOP_SUSPECTED'
   | 'WS_STATE_CHANGED'
   | 'VOLUME_UPDATE'
   | 'MUTE_UPDATE'
@@ -366,6 +370,7 @@ export type BackgroundToPopupMessage =
   | CastAutoStoppedMessage
   | SpeakerRemovedMessage
   | SpeakerStopFailedMessage
+  | FeedbackLoopSuspectedMessage
   | WsStateChangedMessage
   | VolumeUpdateMessage
   | MuteUpdateMessage
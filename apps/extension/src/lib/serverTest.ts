@@ -15,7 +15,27 @@ export interface ServerTestResult {
 /**
  * Error types for connection test failures.
  */
-export type ServerTestErrorType = 'network_failed' | 'server_error' | 'wrong_server';
+export type ServerTestErrorType =
+  | 'network_failed'
+  | 'server_error'
+  | 'wrong_server'
+  | 'approval_pending';
+
+/**
+ * Checks whether a response is the server's 403 for an extension it hasn't approved yet.
+ * The server queues the extension and asks the user, so this is a wait, not a failure.
+ * @param response - The response to check
+ * @returns True if the server is waiting for this extension to be approved
+ */
+export async function isApprovalPending(response: Response): Promise<boolean> {
+  if (response.status !== 403) return false;
+  try {
+    const data = await response.json();
+    return data.error === 'origin_approval_required';
+  } catch {
+    return false;
+  }
+}
 
 /**
  * Maps a server test result to its corresponding i18n error key.
@@ -32,6 +52,8 @@ export function getServerTestErrorKey(result: ServerTestResult): string | null {
       return 'error_server_error';
     case 'wrong_server':
       return 'error_wrong_server';
+    case 'approval_pending':
+      return 'error_origin_approval_pending';
     default:
       return 'server_test_failed';
   }
@@ -57,6 +79,9 @@ export async function testServerConnection(
     });
 
     if (!res.ok) {
+      if (await isApprovalPending(res)) {
+        return { success: false, error: 'approval_pending' };
+      }
       return { success: false, error: 'server_error' };
     }
 
@@ -0,0 +1,27 @@
+/**
+ * WebSocket URL construction for desktop app connections.
+ *
+ * Every connection negotiates the event protocol version and identifies the
+ * extension, so the desktop app can list what is connected and why.
+ */
+
+import { EVENT_PROTOCOL_VERSION } from '@thaumic-cast/protocol';
+
+/** What a connection is used for, as reported to the desktop app. */
+export type WsPurpose = 'ingest' | 'events';
+
+/**
+ * Builds the WebSocket URL for a desktop app.
+ * @param baseUrl - The desktop app HTTP URL
+ * @param purpose - What the connection is used for
+ * @returns The `/ws` URL with protocol and client identification parameters
+ */
+export function buildWsUrl(baseUrl: string, purpose: WsPurpose): string {
+  const params = new URLSearchParams({
+    protocolVersion: String(EVENT_PROTOCOL_VERSION),
+    client: 'extension',
+    clientVersion: chrome.runtime.getManifest().version,
+    purpose,
+  });
+  return `${baseUrl.replace(/^http/, 'ws')}/ws?${params}`;
+}
@@ -135,6 +135,18 @@ const WS_CONNECT_TIMEOUT_MS = 5000;
 /** Handshake timeout (ms). */
 const HANDSHAKE_TIMEOUT_MS = 5000;
 
+/**
+ * Error the server sent during the handshake, keeping its i18n key.
+ */
+class ServerError extends Error {
+  constructor(
+    message: string,
+    readonly messageKey?: string,
+  ) {
+    super(message);
+  }
+}
+
 // ─────────────────────────────────────────────────────────────────────────────
 // Worker State
 // ─────────────────────────────────────────────────────────────────────────────
@@ -809,7 +821,7 @@ async function connectWebSocket(wsUrl: string, handshake: HandshakeMessage): Pro
           } else if (message.type === 'ERROR') {
             clearTimeout(handshakeTimeout);
             ws.removeEventListener('message', handshakeHandler);
-            reject(new Error(message.payload.message));
+            reject(new ServerError(message.payload.message, message.payload.messageKey));
           }
         } catch {
           // Ignore parse errors during handshake
@@ -882,6 +894,7 @@ function handleWsMessage(event: MessageEvent): void {
         postToMain({
           type: 'PLAYBACK_ERROR',
           message: message.payload.message,
+          messageKey: message.payload.messageKey,
         });
         break;
 
@@ -890,6 +903,7 @@ function handleWsMessage(event: MessageEvent): void {
         postToMain({
           type: 'ERROR',
           message: message.payload.message,
+          messageKey: message.payload.messageKey,
         });
         break;
 
@@ -1394,8 +1408,9 @@ self.onmessage = async (event: MessageEvent<WorkerInboundMessage>) => {
       });
     } catch (err) {
       const message = err instanceof Error ? err.message : String(err);
+      const messageKey = err instanceof ServerError ? err.messageKey : undefined;
       log.error('Initialization failed:', message);
-      postToMain({ type: 'ERROR', message });
+      postToMain({ type: 'ERROR', message, messageKey });
       cleanup();
     }
   }
@@ -1417,8 +1432,9 @@ self.onmessage = async (event: MessageEvent<WorkerInboundMessage>) => {
       // Worker is now idle — WS message handler + heartbeat do all the work
     } catch (err) {
       const message = err instanceof Error ? err.message : String(err);
+      const messageKey = err instanceof ServerError ? err.messageKey : undefined;
       log.error('Browser capture init failed:', message);
-      postToMain({ type: 'ERROR', message });
+      postToMain({ type: 'ERROR', message, messageKey });
       cleanup();
     }
   }
@@ -26,7 +26,9 @@ import { createAudioRingBuffer, HEADER_SIZE } from './ring-buffer';
 import type { EncoderConfig, StreamMetadata } from '@thaumic-cast/protocol';
 import { isSupportedSampleRate } from '@thaumic-cast/protocol';
 import { noop } from '../lib/noop';
+import { buildWsUrl } from '../lib/ws-url';
 import type { WorkerOutboundMessage } from './worker-messages';
+import en from '../locales/en.json';
 
 const log = createLogger('Offscreen');
 
@@ -48,6 +50,20 @@ const HEALTHY_STATS_LOG_INTERVAL = 30000;
 /** Minimum gain value to keep Chrome's audio detection active without audible sound. */
 const KEEP_AUDIBLE_GAIN = 0.0001;
 
+/**
+ * Builds the error for a server-reported failure.
+ *
+ * Errors crossing to the popup carry an i18n key as their message, so the
+ * server's `messageKey` is used when the extension translates it; otherwise
+ * the English message passes through as-is.
+ * @param message - The server's English message
+ * @param messageKey - The server's i18n key, if any
+ * @returns An error whose message the popup can hand to `t()`
+ */
+function serverError(message: string, messageKey?: string): Error {
+  return new Error(messageKey && messageKey in en ? messageKey : message);
+}
+
 /**
  * Manages an active capture session.
  *
@@ -478,7 +494,7 @@ export class StreamSession {
    * In browser capture mode, the Worker only manages WS lifecycle (no audio encoding).
    */
   private async startWorker(): Promise<void> {
-    const wsUrl = this.baseUrl.replace(/^http/, 'ws') + '/ws';
+    const wsUrl = buildWsUrl(this.baseUrl, 'ingest');
 
     this.consumerWorker = new Worker(new URL('./audio-consumer.worker.ts', import.meta.url), {
       type: 'module',
@@ -509,9 +525,9 @@ export class StreamSession {
 
         case 'ERROR':
           log.error(`Worker error: ${msg.message}`);
-          this.connectionResolver?.reject(new Error(msg.message));
+          this.connectionResolver?.reject(serverError(msg.message, msg.messageKey));
           this.connectionResolver = null;
-          this.playbackResultsResolver?.reject(new Error(msg.message));
+          this.playbackResultsResolver?.reject(serverError(msg.message, msg.messageKey));
           this.playbackResultsResolver = null;
           break;
 
@@ -545,7 +561,7 @@ export class StreamSession {
 
         case 'PLAYBACK_ERROR':
           log.error(`Playback error: ${msg.message}`);
-          this.playbackResultsResolver?.reject(new Error(msg.message));
+          this.playbackResultsResolver?.reject(serverError(msg.message, msg.messageKey));
           this.playbackResultsResolver = null;
           break;
 
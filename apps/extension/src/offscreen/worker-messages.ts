@@ -84,6 +84,8 @@ export interface WorkerDisconnectedMessage {
 export interface WorkerErrorMessage {
   type: 'ERROR';
   message: string;
+  /** i18n key when the error came from the server (e.g., 'error.capture_timeout') */
+  messageKey?: string;
 }
 
 /** Stream is ready for playback (buffer threshold reached). */
@@ -114,6 +116,8 @@ export interface WorkerPlaybackResultsMessage {
 export interface WorkerPlaybackErrorMessage {
   type: 'PLAYBACK_ERROR';
   message: string;
+  /** i18n key for the server's message (e.g., 'error.no_speaker_ips') */
+  messageKey?: string;
 }
 
 /** Server-side browser capture error (process exit, device disconnect). */
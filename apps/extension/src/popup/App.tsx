@@ -124,7 +124,7 @@ function MainPopup(): JSX.Element {
     }
   }, [autoStopNotification, autoStopMessage]);
 
-  // Handle speaker stop failure notifications
+  // Handle speaker stop failure and feedback loop notifications
   useChromeMessage((message) => {
     const msg = message as { type: string };
     if (msg.type === 'SPEAKER_STOP_FAILED') {
@@ -132,6 +132,9 @@ function MainPopup(): JSX.Element {
       const name = speakerGroups.getGroupName(failedMsg.speakerIp) || failedMsg.speakerIp;
       setError(t('error_speaker_stop_failed', { name }));
     }
+    if (msg.type === 'FEEDBACK_LOOP_SUSPECTED') {
+      setError(t('error_feedback_loop'));
+    }
   });
 
   /**
@@ -45,9 +45,6 @@ export function SpeakerStep({ onSpeakersFound }: SpeakerStepProps): preact.JSX.E
               <div key={group.id} className={styles.speakerItem}>
                 <Speaker size={16} />
                 <span>{group.name}</span>
-                {group.isMultiSpeaker && (
-                  <span className={styles.memberCount}>+{group.size - 1}</span>
-                )}
               </div>
             ))}
           </div>
@@ -16,16 +16,87 @@ pub struct ServerConfig {
     /// Override: `THAUMIC_BIND_PORT`
     pub bind_port: u16,
 
+    /// Port for a dedicated GENA (UPnP event) listener.
+    /// Unset or 0 receives speaker NOTIFY requests on the main port.
+    /// Override: `THAUMIC_GENA_PORT`
+    pub gena_port: Option<u16>,
+
+    /// Serve the remote control web UI at `/ui`.
+    /// Override: `THAUMIC_WEB_UI`
+    pub web_ui: bool,
+
+    /// Extra origins allowed to call the API from a browser. The extension
+    /// and pages on localhost are always allowed. Entries may use `*`
+    /// wildcards (`https://*.example.com`) or `re:` regular expressions.
+    /// Override: `THAUMIC_ALLOWED_ORIGINS` (comma-separated)
+    pub allowed_origins: Vec<String>,
+
+    /// Accept audio pushed by other apps at `/api/ingest`.
+    /// Override: `THAUMIC_INGEST_ENABLED`
+    pub ingest_enabled: bool,
+
+    /// Bearer key required by the ingest API. Must be set to enable ingest.
+    /// Override: `THAUMIC_INGEST_API_KEY`
+    pub ingest_api_key: Option<String>,
+
+    /// Port for Icecast source clients (BUTT, Mixxx). Needs ingest enabled;
+    /// sources use the ingest API key as password.
+    /// Override: `THAUMIC_ICECAST_PORT`
+    pub icecast_port: Option<u16>,
+
+    /// UDP port to receive RTP audio (L16) on. Unset disables RTP ingest.
+    /// Override: `THAUMIC_RTP_PORT`
+    pub rtp_port: Option<u16>,
+
+    /// Sample rate of RTP streams with a dynamic payload type.
+    /// Override: `THAUMIC_RTP_SAMPLE_RATE`
+    pub rtp_sample_rate: u32,
+
+    /// Channel count (1 or 2) of RTP streams with a dynamic payload type.
+    /// Override: `THAUMIC_RTP_CHANNELS`
+    pub rtp_channels: u16,
+
     /// IP address to advertise to Sonos speakers.
     /// This should be the IP that Sonos speakers can reach.
     /// If not specified, auto-detection will be attempted.
     /// Override: `THAUMIC_ADVERTISE_IP`
     pub advertise_ip: Option<IpAddr>,
 
+    /// Our addresses on other subnets, as `address/prefix` (e.g.
+    /// `10.0.20.5/24`), for hosts with an interface on several VLANs.
+    /// Speakers on one of these subnets are given that address for event
+    /// callbacks and streams instead of `advertise_ip`.
+    /// Override: `THAUMIC_SUBNET_ADDRESSES` (comma-separated)
+    pub subnet_addresses: Vec<String>,
+
     /// Interval in seconds between topology refresh checks.
     /// Override: `THAUMIC_TOPOLOGY_REFRESH_INTERVAL`
     pub topology_refresh_interval: u64,
 
+    /// Skip SSDP and mDNS entirely and only use manually added speakers, for
+    /// networks where multicast doesn't reach the speakers.
+    /// Override: `THAUMIC_UNICAST_ONLY`
+    pub unicast_only: bool,
+
+    /// Probe every address of the local subnets when SSDP and mDNS find
+    /// no speakers.
+    /// Override: `THAUMIC_SUBNET_SCAN`
+    pub subnet_scan: bool,
+
+    /// IPv4 ranges for the subnet scan (empty: the /24 of each interface).
+    /// Override: `THAUMIC_SUBNET_SCAN_CIDRS` (comma-separated)
+    pub subnet_scan_cidrs: Vec<String>,
+
+    /// IPv4 ranges on other VLANs or routed subnets, searched by unicast
+    /// M-SEARCH and description probes on every discovery.
+    /// Override: `THAUMIC_REMOTE_SUBNETS` (comma-separated)
+    pub remote_subnets: Vec<String>,
+
+    /// Speakers to leave out of discovery, topology and events, by UUID
+    /// (`RINCON_...`) or IPv4 address.
+    /// Override: `THAUMIC_IGNORED_SPEAKERS` (comma-separated)
+    pub ignored_speakers: Vec<String>,
+
     /// Directory for persistent data (manual speakers config).
     /// Override: `THAUMIC_DATA_DIR`
     pub data_dir: Option<PathBuf>,
@@ -35,16 +106,142 @@ pub struct ServerConfig {
     /// If not set, checks for `artwork.jpg` in data_dir, then uses embedded default.
     /// Override: `THAUMIC_ARTWORK_URL`
     pub artwork_url: Option<String>,
+
+    /// Disable Nagle's algorithm on HTTP connections (recommended).
+    /// Override: `THAUMIC_TCP_NODELAY`
+    pub tcp_nodelay: bool,
+
+    /// Idle seconds before TCP keepalive probes start (0 disables keepalive).
+    /// Raise or lower this if your router drops quiet connections.
+    /// Override: `THAUMIC_TCP_KEEPALIVE_SECS`
+    pub tcp_keepalive_secs: u64,
+
+    /// Seconds between TCP keepalive probes.
+    /// Override: `THAUMIC_TCP_KEEPALIVE_INTERVAL_SECS`
+    pub tcp_keepalive_interval_secs: u64,
+
+    /// Socket send buffer size in bytes (0 keeps the OS default).
+    /// Override: `THAUMIC_SEND_BUFFER_BYTES`
+    pub send_buffer_bytes: usize,
+
+    /// Largest PCM buffer in milliseconds that ingest jitter may grow a
+    /// speaker's queue to (100-1000).
+    /// Override: `THAUMIC_MAX_JITTER_BUFFER_MS`
+    pub max_jitter_buffer_ms: u64,
+
+    /// Seconds to wait for another source (Spotify, AirPlay) to stop before
+    /// a taken-over cast is ended instead of resumed (0 ends it at once).
+    /// Override: `THAUMIC_TAKEOVER_RESUME_SECS`
+    pub takeover_resume_secs: u64,
+
+    /// Seconds a stream may go without any speaker pulling it before it is
+    /// stopped (0 keeps it until its source ends).
+    /// Override: `THAUMIC_IDLE_LISTENER_SECS`
+    pub idle_listener_secs: u64,
+
+    /// What to do when a speaker is stopped from the Sonos app while its
+    /// stream keeps running: `stop`, `resume` or `ask`.
+    /// Override: `THAUMIC_EXTERNAL_STOP`
+    pub external_stop: thaumic_core::ExternalStopPolicy,
+
+    /// Seconds clients have to confirm resuming under `external_stop: ask`.
+    /// Override: `THAUMIC_EXTERNAL_STOP_CONFIRM_SECS`
+    pub external_stop_confirm_secs: u64,
+
+    /// Prefer speakers on Ethernet (then those with the strongest Wi-Fi
+    /// signal) as the coordinator of a synchronized cast.
+    /// Override: `THAUMIC_COORDINATOR_PREFER_WIRED`
+    pub coordinator_prefer_wired: bool,
+
+    /// Speaker models to prefer as coordinator, best first (e.g. `arc`).
+    /// Override: `THAUMIC_COORDINATOR_MODEL_PRIORITY` (comma-separated)
+    pub coordinator_model_priority: Vec<String>,
+
+    /// Fixed coordinators for particular sets of speakers.
+    pub coordinator_pins: Vec<thaumic_core::CoordinatorPin>,
+
+    /// Look up missing artist/album for title-only tabs via MusicBrainz.
+    /// Override: `THAUMIC_METADATA_ENRICHMENT`
+    pub metadata_enrichment: bool,
+
+    /// last.fm API key, adds last.fm as a second lookup provider and is
+    /// used for last.fm scrobbling.
+    /// Override: `THAUMIC_LASTFM_API_KEY`
+    pub lastfm_api_key: Option<String>,
+
+    /// last.fm API shared secret, required for scrobbling.
+    /// Override: `THAUMIC_LASTFM_API_SECRET`
+    pub lastfm_api_secret: Option<String>,
+
+    /// last.fm session key for the account to scrobble to.
+    /// Override: `THAUMIC_LASTFM_SESSION_KEY`
+    pub lastfm_session_key: Option<String>,
+
+    /// ListenBrainz user token; enables ListenBrainz scrobbling.
+    /// Override: `THAUMIC_LISTENBRAINZ_TOKEN`
+    pub listenbrainz_token: Option<String>,
+
+    /// Seconds a track must play before it's scrobbled (30-240).
+    /// Override: `THAUMIC_SCROBBLE_AFTER_SECS`
+    pub scrobble_after_secs: u64,
+
+    /// File to append JSON events to (rotated at 10 MB, 5 files kept).
+    /// Override: `THAUMIC_EVENT_LOG_FILE`
+    pub event_log_file: Option<PathBuf>,
+
+    /// Forward JSON events to syslog/journald.
+    /// Override: `THAUMIC_EVENT_SYSLOG`
+    pub event_syslog: bool,
+
+    /// Remote syslog server (`host:port`, UDP) instead of the local socket.
+    /// Override: `THAUMIC_EVENT_SYSLOG_ADDRESS`
+    pub event_syslog_address: Option<String>,
 }
 
 impl Default for ServerConfig {
     fn default() -> Self {
         Self {
             bind_port: 49400,
+            gena_port: None,
+            web_ui: true,
+            allowed_origins: Vec::new(),
+            ingest_enabled: false,
+            ingest_api_key: None,
+            icecast_port: None,
+            rtp_port: None,
+            rtp_sample_rate: 48_000,
+            rtp_channels: 2,
             advertise_ip: None,
+            subnet_addresses: Vec::new(),
             topology_refresh_interval: 30,
+            unicast_only: false,
+            subnet_scan: true,
+            subnet_scan_cidrs: Vec::new(),
+            remote_subnets: Vec::new(),
+            ignored_speakers: Vec::new(),
             data_dir: None,
             artwork_url: None,
+            tcp_nodelay: true,
+            tcp_keepalive_secs: 30,
+            tcp_keepalive_interval_secs: 10,
+            send_buffer_bytes: 0,
+            max_jitter_buffer_ms: 1000,
+            takeover_resume_secs: 60,
+            idle_listener_secs: 600,
+            external_stop: thaumic_core::ExternalStopPolicy::default(),
+            external_stop_confirm_secs: 60,
+            coordinator_prefer_wired: false,
+            coordinator_model_priority: Vec::new(),
+            coordinator_pins: Vec::new(),
+            metadata_enrichment: false,
+            lastfm_api_key: None,
+            lastfm_api_secret: None,
+            lastfm_session_key: None,
+            listenbrainz_token: None,
+            scrobble_after_secs: 120,
+            event_log_file: None,
+            event_syslog: false,
+            event_syslog_address: None,
         }
     }
 }
@@ -73,24 +270,252 @@ impl ServerConfig {
             }
         }
 
+        if let Ok(val) = std::env::var("THAUMIC_GENA_PORT") {
+            if let Ok(port) = val.parse() {
+                self.gena_port = Some(port);
+            }
+        }
+
+        if let Ok(val) = std::env::var("THAUMIC_WEB_UI") {
+            if let Ok(enabled) = val.parse() {
+                self.web_ui = enabled;
+            }
+        }
+
+        if let Ok(val) = std::env::var("THAUMIC_ALLOWED_ORIGINS") {
+            self.allowed_origins = val
+                .split(',')
+                .map(str::trim)
+                .filter(|origin| !origin.is_empty())
+                .map(str::to_string)
+                .collect();
+        }
+
+        if let Ok(val) = std::env::var("THAUMIC_INGEST_ENABLED") {
+            if let Ok(enabled) = val.parse() {
+                self.ingest_enabled = enabled;
+            }
+        }
+
+        if let Ok(val) = std::env::var("THAUMIC_INGEST_API_KEY") {
+            if !val.is_empty() {
+                self.ingest_api_key = Some(val);
+            }
+        }
+
+        if let Ok(val) = std::env::var("THAUMIC_ICECAST_PORT") {
+            if let Ok(port) = val.parse() {
+                self.icecast_port = Some(port);
+            }
+        }
+
+        if let Ok(val) = std::env::var("THAUMIC_RTP_PORT") {
+            if let Ok(port) = val.parse() {
+                self.rtp_port = Some(port);
+            }
+        }
+
+        if let Ok(val) = std::env::var("THAUMIC_RTP_SAMPLE_RATE") {
+            if let Ok(rate) = val.parse() {
+                self.rtp_sample_rate = rate;
+            }
+        }
+
+        if let Ok(val) = std::env::var("THAUMIC_RTP_CHANNELS") {
+            if let Ok(channels) = val.parse() {
+                self.rtp_channels = channels;
+            }
+        }
+
         if let Ok(val) = std::env::var("THAUMIC_ADVERTISE_IP") {
             if let Ok(ip) = val.parse() {
                 self.advertise_ip = Some(ip);
             }
         }
 
+        if let Ok(val) = std::env::var("THAUMIC_SUBNET_ADDRESSES") {
+            self.subnet_addresses = val
+                .split(',')
+                .map(str::trim)
+                .filter(|address| !address.is_empty())
+                .map(str::to_string)
+                .collect();
+        }
+
         if let Ok(val) = std::env::var("THAUMIC_TOPOLOGY_REFRESH_INTERVAL") {
             if let Ok(interval) = val.parse() {
                 self.topology_refresh_interval = interval;
             }
         }
 
+        if let Ok(val) = std::env::var("THAUMIC_UNICAST_ONLY") {
+            if let Ok(unicast_only) = val.parse() {
+                self.unicast_only = unicast_only;
+            }
+        }
+
+        if let Ok(val) = std::env::var("THAUMIC_SUBNET_SCAN") {
+            if let Ok(subnet_scan) = val.parse() {
+                self.subnet_scan = subnet_scan;
+            }
+        }
+
+        if let Ok(val) = std::env::var("THAUMIC_SUBNET_SCAN_CIDRS") {
+            self.subnet_scan_cidrs = val
+                .split(',')
+                .map(str::trim)
+                .filter(|cidr| !cidr.is_empty())
+                .map(str::to_string)
+                .collect();
+        }
+
+        if let Ok(val) = std::env::var("THAUMIC_REMOTE_SUBNETS") {
+            self.remote_subnets = val
+                .split(',')
+                .map(str::trim)
+                .filter(|cidr| !cidr.is_empty())
+                .map(str::to_string)
+                .collect();
+        }
+
+        if let Ok(val) = std::env::var("THAUMIC_IGNORED_SPEAKERS") {
+            self.ignored_speakers = val
+                .split(',')
+                .map(str::trim)
+                .filter(|speaker| !speaker.is_empty())
+                .map(str::to_string)
+                .collect();
+        }
+
         if let Ok(val) = std::env::var("THAUMIC_ARTWORK_URL") {
             if !val.is_empty() {
                 self.artwork_url = Some(val);
             }
         }
 
+        if let Ok(val) = std::env::var("THAUMIC_TCP_NODELAY") {
+            if let Ok(nodelay) = val.parse() {
+                self.tcp_nodelay = nodelay;
+            }
+        }
+
+        if let Ok(val) = std::env::var("THAUMIC_TCP_KEEPALIVE_SECS") {
+            if let Ok(secs) = val.parse() {
+                self.tcp_keepalive_secs = secs;
+            }
+        }
+
+        if let Ok(val) = std::env::var("THAUMIC_TCP_KEEPALIVE_INTERVAL_SECS") {
+            if let Ok(secs) = val.parse() {
+                self.tcp_keepalive_interval_secs = secs;
+            }
+        }
+
+        if let Ok(val) = std::env::var("THAUMIC_SEND_BUFFER_BYTES") {
+            if let Ok(bytes) = val.parse() {
+                self.send_buffer_bytes = bytes;
+            }
+        }
+
+        if let Ok(val) = std::env::var("THAUMIC_MAX_JITTER_BUFFER_MS") {
+            if let Ok(ms) = val.parse() {
+                self.max_jitter_buffer_ms = ms;
+            }
+        }
+
+        if let Ok(val) = std::env::var("THAUMIC_TAKEOVER_RESUME_SECS") {
+            if let Ok(secs) = val.parse() {
+                self.takeover_resume_secs = secs;
+            }
+        }
+
+        if let Ok(val) = std::env::var("THAUMIC_IDLE_LISTENER_SECS") {
+            if let Ok(secs) = val.parse() {
+                self.idle_listener_secs = secs;
+            }
+        }
+
+        if let Ok(val) = std::env::var("THAUMIC_EXTERNAL_STOP") {
+            if let Ok(policy) = serde_yaml::from_str(&val) {
+                self.external_stop = policy;
+            }
+        }
+
+        if let Ok(val) = std::env::var("THAUMIC_EXTERNAL_STOP_CONFIRM_SECS") {
+            if let Ok(secs) = val.parse() {
+                self.external_stop_confirm_secs = secs;
+            }
+        }
+
+        if let Ok(val) = std::env::var("THAUMIC_COORDINATOR_PREFER_WIRED") {
+            if let Ok(prefer) = val.parse() {
+                self.coordinator_prefer_wired = prefer;
+            }
+        }
+
+        if let Ok(val) = std::env::var("THAUMIC_COORDINATOR_MODEL_PRIORITY") {
+            self.coordinator_model_priority = val
+                .split(',')
+                .map(str::trim)
+                .filter(|model| !model.is_empty())
+                .map(str::to_string)
+                .collect();
+        }
+
+        if let Ok(val) = std::env::var("THAUMIC_METADATA_ENRICHMENT") {
+            if let Ok(enabled) = val.parse() {
+                self.metadata_enrichment = enabled;
+            }
+        }
+
+        if let Ok(val) = std::env::var("THAUMIC_LASTFM_API_KEY") {
+            if !val.is_empty() {
+                self.lastfm_api_key = Some(val);
+            }
+        }
+
+        if let Ok(val) = std::env::var("THAUMIC_LASTFM_API_SECRET") {
+            if !val.is_empty() {
+                self.lastfm_api_secret = Some(val);
+            }
+        }
+
+        if let Ok(val) = std::env::var("THAUMIC_LASTFM_SESSION_KEY") {
+            if !val.is_empty() {
+                self.lastfm_session_key = Some(val);
+            }
+        }
+
+        if let Ok(val) = std::env::var("THAUMIC_LISTENBRAINZ_TOKEN") {
+            if !val.is_empty() {
+                self.listenbrainz_token = Some(val);
+            }
+        }
+
+        if let Ok(val) = std::env::var("THAUMIC_SCROBBLE_AFTER_SECS") {
+            if let Ok(secs) = val.parse() {
+                self.scrobble_after_secs = secs;
+            }
+        }
+
+        if let Ok(val) = std::env::var("THAUMIC_EVENT_LOG_FILE") {
+            if !val.is_empty() {
+                self.event_log_file = Some(PathBuf::from(val));
+            }
+        }
+
+        if let Ok(val) = std::env::var("THAUMIC_EVENT_SYSLOG") {
+            if let Ok(enabled) = val.parse() {
+                self.event_syslog = enabled;
+            }
+        }
+
+        if let Ok(val) = std::env::var("THAUMIC_EVENT_SYSLOG_ADDRESS") {
+            if !val.is_empty() {
+                self.event_syslog_address = Some(val);
+            }
+        }
+
         // Note: THAUMIC_DATA_DIR is handled by clap via #[arg(env = ...)] in main.rs
     }
 
@@ -98,8 +523,67 @@ impl ServerConfig {
     pub fn to_core_config(&self) -> thaumic_core::Config {
         thaumic_core::Config {
             preferred_port: self.bind_port,
+            gena_listener: match self.gena_port {
+                Some(port) if port > 0 => thaumic_core::GenaListener::Dedicated(port),
+                _ => thaumic_core::GenaListener::Shared,
+            },
+            web_ui: self.web_ui,
+            allowed_origins: self.allowed_origins.clone(),
+            ingest: thaumic_core::IngestConfig {
+                enabled: self.ingest_enabled,
+                api_key: self.ingest_api_key.clone(),
+                icecast_port: self.icecast_port.filter(|&port| port > 0),
+                rtp: thaumic_core::RtpIngestConfig {
+                    port: self.rtp_port.filter(|&port| port > 0),
+                    sample_rate: self.rtp_sample_rate,
+                    channels: self.rtp_channels,
+                    ..Default::default()
+                },
+            },
             topology_refresh_interval: self.topology_refresh_interval,
-            ..Default::default()
+            unicast_only: self.unicast_only,
+            subnet_scan: self.subnet_scan,
+            subnet_scan_cidrs: self.subnet_scan_cidrs.clone(),
+            remote_subnets: self.remote_subnets.clone(),
+            ignored_speakers: self.ignored_speakers.clone(),
+            streaming: thaumic_core::StreamingConfig {
+                tcp_nodelay: self.tcp_nodelay,
+                tcp_keepalive_secs: (self.tcp_keepalive_secs > 0)
+                    .then_some(self.tcp_keepalive_secs),
+                tcp_keepalive_interval_secs: self.tcp_keepalive_interval_secs.max(1),
+                send_buffer_bytes: (self.send_buffer_bytes > 0).then_some(self.send_buffer_bytes),
+                max_jitter_buffer_ms: self.max_jitter_buffer_ms,
+                takeover_resume_secs: (self.takeover_resume_secs > 0)
+                    .then_some(self.takeover_resume_secs),
+                idle_listener_secs: (self.idle_listener_secs > 0)
+                    .then_some(self.idle_listener_secs),
+                external_stop: self.external_stop,
+                external_stop_confirm_secs: self.external_stop_confirm_secs,
+                coordinator: thaumic_core::CoordinatorPreferences {
+                    prefer_wired: self.coordinator_prefer_wired,
+                    model_priority: self.coordinator_model_priority.clone(),
+                    pins: self.coordinator_pins.clone(),
+                },
+                ..Default::default()
+            },
+            enrichment: thaumic_core::EnrichmentConfig {
+                enabled: self.metadata_enrichment,
+                lastfm_api_key: self.lastfm_api_key.clone(),
+                ..Default::default()
+            },
+            scrobbling: thaumic_core::ScrobbleConfig {
+                listenbrainz_token: self.listenbrainz_token.clone(),
+                lastfm_api_key: self.lastfm_api_key.clone(),
+                lastfm_api_secret: self.lastfm_api_secret.clone(),
+                lastfm_session_key: self.lastfm_session_key.clone(),
+                scrobble_after_secs: self.scrobble_after_secs,
+            },
+            event_log: thaumic_core::EventLogConfig {
+                file: self.event_log_file.clone(),
+                syslog: self.event_syslog,
+                syslog_address: self.event_syslog_address.clone(),
+                ..Default::default()
+            },
         }
     }
 
@@ -5,19 +5,23 @@
 //! Thaumic Cast service runs as a background daemon.
 
 mod config;
+mod soak;
 
 use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::Duration;
 
 use anyhow::{Context, Result};
 use clap::Parser;
 use parking_lot::RwLock;
 use thaumic_core::{
     bootstrap_services_with_network, start_server, AppState, LocalIpDetector, NetworkContext,
+    SubnetAddress,
 };
 use tokio::signal;
 
 use crate::config::ServerConfig;
+use crate::soak::{SoakOptions, SoakTarget};
 
 /// Thaumic Server - Headless browser-to-Sonos audio streaming server.
 #[derive(Parser, Debug)]
@@ -43,6 +47,33 @@ struct Args {
     /// Data directory for persistent state (manual speakers, etc.).
     #[arg(short = 'd', long, env = "THAUMIC_DATA_DIR")]
     data_dir: Option<PathBuf>,
+
+    /// Soak test: cast a generated tone and check stability until stopped.
+    #[arg(long)]
+    soak: bool,
+
+    /// Soak test target: a speaker IP, or `local` for a built-in listener.
+    #[arg(
+        long,
+        value_name = "TARGET",
+        default_value = "local",
+        requires = "soak"
+    )]
+    soak_target: SoakTarget,
+
+    /// Ends the soak test after this many minutes instead of on Ctrl+C.
+    #[arg(long, value_name = "MINUTES", requires = "soak")]
+    soak_minutes: Option<u64>,
+
+    /// Seconds between soak test reports.
+    #[arg(
+        long,
+        value_name = "SECONDS",
+        default_value_t = 60,
+        value_parser = clap::value_parser!(u64).range(1..),
+        requires = "soak"
+    )]
+    soak_report_secs: u64,
 }
 
 #[tokio::main]
@@ -93,6 +124,20 @@ async fn main() -> Result<()> {
         )?
     };
 
+    if !config.subnet_addresses.is_empty() {
+        let addresses = config
+            .subnet_addresses
+            .iter()
+            .map(|address| address.parse::<SubnetAddress>())
+            .collect::<Result<Vec<_>, _>>()
+            .context("Invalid subnet_addresses entry")?;
+        log::info!(
+            "Configuration: subnet_addresses={:?}",
+            config.subnet_addresses
+        );
+        network.set_subnet_addresses(addresses);
+    }
+
     // Bootstrap services with explicit network configuration
     let core_config = config.to_core_config();
     let handle = tokio::runtime::Handle::current();
@@ -105,9 +150,11 @@ async fn main() -> Result<()> {
     // refresh includes manual speakers. This must happen before start_background_tasks().
     if let Some(ref data_dir) = config.data_dir {
         log::info!("Using data directory: {}", data_dir.display());
-        services.discovery_service.set_app_data_dir(data_dir);
+        if let Err(e) = services.set_app_data_dir(data_dir) {
+            log::error!("{}; settings changes will not persist until it is fixed", e);
+        }
     } else {
-        log::info!("No data directory configured - manual speakers will not persist");
+        log::info!("No data directory configured - manual speakers and stats will not persist");
     }
 
     // Start background tasks (topology monitor will load manual speakers if data_dir set)
@@ -116,11 +163,12 @@ async fn main() -> Result<()> {
     log::info!("Background tasks started");
 
     // Build app state for the HTTP server
-    let app_state = AppState::new(
+    let mut app_state = AppState::new(
         &services,
         Arc::new(RwLock::new(core_config)),
         config.to_artwork_config(),
     );
+    app_state.config_file = args.config.clone();
 
     // Spawn HTTP server on the main tokio runtime.
     // Unlike the desktop app (which uses a dedicated high-priority streaming runtime
@@ -134,10 +182,23 @@ async fn main() -> Result<()> {
 
     log::info!("HTTP server started on port {}", config.bind_port);
 
-    // Wait for shutdown signal
-    shutdown_signal().await;
+    // Soak mode ends on its own when given a duration; otherwise wait for
+    // the shutdown signal
+    let soak_result = if args.soak {
+        let options = SoakOptions {
+            target: args.soak_target,
+            duration: args
+                .soak_minutes
+                .map(|minutes| Duration::from_secs(minutes.saturating_mul(60))),
+            report_interval: Duration::from_secs(args.soak_report_secs),
+        };
+        soak::run(&services, options, shutdown_signal()).await
+    } else {
+        shutdown_signal().await;
+        Ok(())
+    };
 
-    log::info!("Shutdown signal received, cleaning up...");
+    log::info!("Shutting down, cleaning up...");
 
     // Graceful shutdown
     services.shutdown().await;
@@ -146,7 +207,7 @@ async fn main() -> Result<()> {
     server_handle.abort();
 
     log::info!("Shutdown complete");
-    Ok(())
+    soak_result
 }
 
 /// Waits for a shutdown signal (Ctrl+C or SIGTERM).
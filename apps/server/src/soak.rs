@@ -0,0 +1,381 @@
+//! Soak-test mode (`--soak`).
+//!
+//! Casts a generated tone to one target for as long as the server runs and
+//! logs stability metrics at an interval, checking invariants that must hold
+//! throughout:
+//!
+//! - the stream stays registered and keeps at least one listener
+//! - a speaker target keeps playing the stream; the built-in `local`
+//!   listener keeps receiving audio
+//! - no critical event is dropped on the event bus
+//! - resident memory stays within [`MEMORY_GROWTH_LIMIT`] of the first sample
+//!
+//! Violations are logged as errors, and the soak fails once it ends.
+
+use std::f32::consts::TAU;
+use std::future::Future;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use anyhow::{bail, Context, Result};
+use bytes::Bytes;
+use thaumic_core::protocol_constants::DEFAULT_STREAMING_BUFFER_MS;
+use thaumic_core::services::StreamCoordinator;
+use thaumic_core::{
+    validate_speaker_ip, AudioCodec, AudioFormat, BootstrappedServices, StreamMetadata,
+    TransportState,
+};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio::task::JoinHandle;
+
+/// Duration of each generated audio frame.
+const FRAME_DURATION_MS: u32 = 20;
+
+/// Frequency of the generated tone.
+const TONE_HZ: f32 = 440.0;
+
+/// Tone amplitude relative to full scale (-20 dBFS), so a soak on real
+/// speakers stays in the background.
+const TONE_AMPLITUDE: f32 = 0.1;
+
+/// How far resident memory may grow past the first sample.
+const MEMORY_GROWTH_LIMIT: u64 = 128 * 1024 * 1024;
+
+/// Delay before the local listener reconnects after losing the stream.
+const RECONNECT_DELAY: Duration = Duration::from_secs(1);
+
+/// What the soak test casts to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SoakTarget {
+    /// A listener inside the server fetching the stream over HTTP, as a
+    /// speaker would.
+    Local,
+    /// A Sonos speaker, by IP.
+    Speaker(String),
+}
+
+impl FromStr for SoakTarget {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.eq_ignore_ascii_case("local") {
+            return Ok(Self::Local);
+        }
+        let ip = s
+            .parse()
+            .map_err(|_| format!("expected a speaker IP or `local`, got `{}`", s))?;
+        validate_speaker_ip(&ip)
+            .map(|ip| Self::Speaker(ip.to_string()))
+            .map_err(|e| e.to_string())
+    }
+}
+
+/// Soak test settings.
+#[derive(Debug, Clone)]
+pub struct SoakOptions {
+    pub target: SoakTarget,
+    /// How long to run; until interrupted if `None`.
+    pub duration: Option<Duration>,
+    /// Interval between metric reports and invariant checks.
+    pub report_interval: Duration,
+}
+
+/// Metrics taken at one report.
+#[derive(Debug)]
+struct Sample {
+    elapsed: Duration,
+    stream_alive: bool,
+    listeners: usize,
+    frames_pushed: u64,
+    /// Bytes the local listener received; `None` for speaker targets.
+    bytes_received: Option<u64>,
+    /// Transport state of a speaker target; `None` for the local listener.
+    speaker_state: Option<Option<TransportState>>,
+    critical_dropped: u64,
+    memory_bytes: u64,
+    cpu_percent: f32,
+}
+
+/// Compares each sample with the ones before it.
+#[derive(Debug, Default)]
+struct InvariantChecker {
+    first_memory_bytes: Option<u64>,
+    last_bytes_received: u64,
+    last_critical_dropped: u64,
+    violations: u64,
+}
+
+impl InvariantChecker {
+    /// Returns the invariants `sample` violates.
+    fn check(&mut self, sample: &Sample) -> Vec<String> {
+        let mut violations = Vec::new();
+
+        if !sample.stream_alive {
+            violations.push("the stream was removed".to_string());
+        } else if sample.listeners == 0 {
+            violations.push("the stream has no listeners".to_string());
+        }
+
+        if let Some(received) = sample.bytes_received {
+            if received <= self.last_bytes_received {
+                violations.push("the local listener received nothing since the last report".into());
+            }
+            self.last_bytes_received = received;
+        }
+
+        if let Some(state) = sample.speaker_state {
+            if !matches!(
+                state,
+                Some(TransportState::Playing | TransportState::Transitioning)
+            ) {
+                violations.push(format!("the speaker is not playing ({:?})", state));
+            }
+        }
+
+        if sample.critical_dropped > self.last_critical_dropped {
+            violations.push(format!(
+                "{} critical event(s) were dropped",
+                sample.critical_dropped - self.last_critical_dropped
+            ));
+            self.last_critical_dropped = sample.critical_dropped;
+        }
+
+        // The resource monitor reports 0 until its first sample
+        if sample.memory_bytes > 0 {
+            let first = *self.first_memory_bytes.get_or_insert(sample.memory_bytes);
+            if sample.memory_bytes > first + MEMORY_GROWTH_LIMIT {
+                violations.push(format!(
+                    "resident memory grew from {} MiB to {} MiB",
+                    first / (1024 * 1024),
+                    sample.memory_bytes / (1024 * 1024)
+                ));
+            }
+        }
+
+        self.violations += violations.len() as u64;
+        violations
+    }
+}
+
+/// Runs a soak test until `options.duration` passes or `shutdown` resolves.
+///
+/// # Errors
+///
+/// Returns an error if the stream or cast couldn't be started, or if any
+/// invariant was violated during the run.
+pub async fn run(
+    services: &BootstrappedServices,
+    options: SoakOptions,
+    shutdown: impl Future<Output = ()>,
+) -> Result<()> {
+    let coordinator = &services.stream_coordinator;
+    let format = AudioFormat::new(48_000, 2, 16);
+    let stream_id = coordinator
+        .create_stream(
+            AudioCodec::Pcm,
+            format,
+            DEFAULT_STREAMING_BUFFER_MS,
+            FRAME_DURATION_MS,
+        )
+        .map_err(anyhow::Error::msg)
+        .context("Failed to create soak stream")?;
+    coordinator.update_metadata(
+        &stream_id,
+        StreamMetadata {
+            title: Some("Thaumic Cast soak test".to_string()),
+            ..Default::default()
+        },
+    );
+
+    let frames_pushed = Arc::new(AtomicU64::new(0));
+    let bytes_received = Arc::new(AtomicU64::new(0));
+    let mut tasks: Vec<JoinHandle<()>> = vec![tokio::spawn(push_tone(
+        Arc::clone(coordinator),
+        stream_id.clone(),
+        format,
+        Arc::clone(&frames_pushed),
+    ))];
+
+    match &options.target {
+        SoakTarget::Local => {
+            tasks.push(tokio::spawn(listen_locally(
+                services.network.get_port(),
+                stream_id.clone(),
+                Arc::clone(&bytes_received),
+            )));
+        }
+        SoakTarget::Speaker(ip) => {
+            let artwork_url = services.network.url_builder().artwork_url();
+            if let Err(e) = coordinator
+                .start_playback(ip, &stream_id, None, &artwork_url)
+                .await
+            {
+                tasks.iter().for_each(JoinHandle::abort);
+                coordinator.remove_stream(&stream_id);
+                return Err(e).with_context(|| format!("Failed to cast to {}", ip));
+            }
+        }
+    }
+
+    log::info!(
+        "[Soak] Casting stream {} to {:?} for {}",
+        stream_id,
+        options.target,
+        options
+            .duration
+            .map_or("until interrupted".to_string(), |d| format!(
+                "{} min",
+                d.as_secs() / 60
+            ))
+    );
+
+    let started = Instant::now();
+    let deadline = options.duration.map(|d| started + d);
+    let mut interval = tokio::time::interval(options.report_interval);
+    interval.tick().await;
+    let mut checker = InvariantChecker::default();
+    tokio::pin!(shutdown);
+
+    loop {
+        tokio::select! {
+            _ = &mut shutdown => break,
+            _ = sleep_until(deadline) => break,
+            _ = interval.tick() => {}
+        }
+
+        let stream = coordinator.get_stream(&stream_id);
+        let resources = services.resource_monitor.stats();
+        let sample = Sample {
+            elapsed: started.elapsed(),
+            stream_alive: stream.is_some(),
+            listeners: stream.map_or(0, |stream| stream.listener_count()),
+            frames_pushed: frames_pushed.load(Ordering::Relaxed),
+            bytes_received: (options.target == SoakTarget::Local)
+                .then(|| bytes_received.load(Ordering::Relaxed)),
+            speaker_state: match &options.target {
+                SoakTarget::Local => None,
+                SoakTarget::Speaker(ip) => Some(
+                    services
+                        .sonos_state
+                        .transport_states
+                        .get(ip)
+                        .map(|state| *state),
+                ),
+            },
+            critical_dropped: services.event_bridge.stats().critical.dropped,
+            memory_bytes: resources.memory_bytes,
+            cpu_percent: resources.cpu_percent,
+        };
+
+        log::info!(
+            "[Soak] {}m: listeners={}, frames={}, received={}, speaker={:?}, cpu={:.1}%, rss={} MiB, critical_dropped={}",
+            sample.elapsed.as_secs() / 60,
+            sample.listeners,
+            sample.frames_pushed,
+            sample
+                .bytes_received
+                .map_or("-".to_string(), |bytes| bytes.to_string()),
+            sample.speaker_state.flatten(),
+            sample.cpu_percent,
+            sample.memory_bytes / (1024 * 1024),
+            sample.critical_dropped
+        );
+        for violation in checker.check(&sample) {
+            log::error!("[Soak] Invariant violated: {}", violation);
+        }
+    }
+
+    tasks.iter().for_each(JoinHandle::abort);
+    coordinator.remove_stream_async(&stream_id).await;
+
+    let minutes = started.elapsed().as_secs() / 60;
+    if checker.violations > 0 {
+        bail!(
+            "Soak test failed: {} invariant violation(s) in {} min",
+            checker.violations,
+            minutes
+        );
+    }
+    log::info!("[Soak] Passed after {} min", minutes);
+    Ok(())
+}
+
+/// Sleeps until `deadline`, or forever without one.
+async fn sleep_until(deadline: Option<Instant>) {
+    match deadline {
+        Some(deadline) => tokio::time::sleep_until(deadline.into()).await,
+        None => std::future::pending().await,
+    }
+}
+
+/// Pushes a sine tone into the stream at real-time pace.
+async fn push_tone(
+    coordinator: Arc<StreamCoordinator>,
+    stream_id: String,
+    format: AudioFormat,
+    frames_pushed: Arc<AtomicU64>,
+) {
+    let samples_per_frame = (format.sample_rate * FRAME_DURATION_MS / 1000) as usize;
+    let channels = usize::from(format.channels);
+    let step = TAU * TONE_HZ / format.sample_rate as f32;
+    let mut phase = 0.0f32;
+    let mut interval = tokio::time::interval(Duration::from_millis(u64::from(FRAME_DURATION_MS)));
+
+    loop {
+        interval.tick().await;
+        let mut frame = Vec::with_capacity(samples_per_frame * channels * 2);
+        for _ in 0..samples_per_frame {
+            let sample = (phase.sin() * TONE_AMPLITUDE * f32::from(i16::MAX)) as i16;
+            for _ in 0..channels {
+                frame.extend_from_slice(&sample.to_le_bytes());
+            }
+            phase = (phase + step) % TAU;
+        }
+
+        if coordinator
+            .push_frame(&stream_id, Bytes::from(frame))
+            .is_none()
+        {
+            log::warn!(
+                "[Soak] Stream {} is gone, no longer pushing audio",
+                stream_id
+            );
+            return;
+        }
+        frames_pushed.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// Fetches the stream from the server like a speaker, reconnecting whenever
+/// the connection ends, and counts the bytes received.
+async fn listen_locally(port: u16, stream_id: String, bytes_received: Arc<AtomicU64>) {
+    let request = format!(
+        "GET /stream/{}/live.wav HTTP/1.1\r\nHost: 127.0.0.1:{}\r\nUser-Agent: thaumic-soak\r\nConnection: close\r\n\r\n",
+        stream_id, port
+    );
+    let mut buf = vec![0u8; 16 * 1024];
+
+    loop {
+        let result: std::io::Result<()> = async {
+            let mut socket = TcpStream::connect(("127.0.0.1", port)).await?;
+            socket.write_all(request.as_bytes()).await?;
+            loop {
+                let n = socket.read(&mut buf).await?;
+                if n == 0 {
+                    return Ok(());
+                }
+                bytes_received.fetch_add(n as u64, Ordering::Relaxed);
+            }
+        }
+        .await;
+
+        match result {
+            Ok(()) => log::warn!("[Soak] Local listener: stream connection closed"),
+            Err(e) => log::warn!("[Soak] Local listener: {}", e),
+        }
+        tokio::time::sleep(RECONNECT_DELAY).await;
+    }
+}
@@ -26,6 +26,9 @@ export * from './src/sonos.js';
 // Event types (sonos, stream, latency, broadcast)
 export * from './src/events.js';
 
+// Batch control actions (scenes)
+export * from './src/actions.js';
+
 // Media metadata and tab state types
 export * from './src/media.js';
 
@@ -0,0 +1,69 @@
+import { z } from 'zod';
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Batch Actions (POST /api/actions/batch)
+// ─────────────────────────────────────────────────────────────────────────────
+
+/**
+ * One control action in a batch, tagged by `action`.
+ * Volume and mute are routed like the per-speaker endpoints.
+ */
+export const BatchActionSchema = z.discriminatedUnion('action', [
+  z.object({
+    action: z.literal('setVolume'),
+    ip: z.string(),
+    volume: z.number().int().min(0).max(100),
+  }),
+  z.object({ action: z.literal('setMute'), ip: z.string(), mute: z.boolean() }),
+  z.object({ action: z.literal('joinGroup'), ip: z.string(), coordinatorIp: z.string() }),
+  z.object({ action: z.literal('leaveGroup'), ip: z.string() }),
+  z.object({ action: z.literal('play'), ip: z.string(), streamId: z.string() }),
+  z.object({ action: z.literal('stop'), ip: z.string(), streamId: z.string() }),
+]);
+export type BatchAction = z.infer<typeof BatchActionSchema>;
+
+/** Maximum number of actions the server accepts in one batch. */
+export const MAX_BATCH_ACTIONS = 32;
+
+/**
+ * Body of a batch request. Actions run in order; unless `continueOnError`
+ * is set, the steps after the first failure are skipped.
+ */
+export const BatchRequestSchema = z.object({
+  actions: z.array(BatchActionSchema).min(1).max(MAX_BATCH_ACTIONS),
+  continueOnError: z.boolean().optional(),
+});
+export type BatchRequest = z.infer<typeof BatchRequestSchema>;
+
+export const BatchStepStatusSchema = z.enum(['applied', 'failed', 'skipped']);
+export type BatchStepStatus = z.infer<typeof BatchStepStatusSchema>;
+
+/** Error body of a failed step, shaped like an HTTP API error. */
+export const BatchStepErrorSchema = z.object({
+  error: z.string(),
+  message: z.string(),
+  messageKey: z.string(),
+  status: z.number(),
+});
+export type BatchStepError = z.infer<typeof BatchStepErrorSchema>;
+
+export const BatchStepResultSchema = z.object({
+  action: BatchActionSchema,
+  status: BatchStepStatusSchema,
+  error: BatchStepErrorSchema.optional(),
+  /** Action that undoes this step, when it was applied and is reversible. */
+  undo: BatchActionSchema.optional(),
+});
+export type BatchStepResult = z.infer<typeof BatchStepResultSchema>;
+
+/**
+ * Result of a batch. Batches are not atomic: `rollback` lists the undo
+ * actions of the applied steps, last step first, ready to post back.
+ */
+export const BatchResultSchema = z.object({
+  applied: z.number().int(),
+  failed: z.number().int(),
+  steps: z.array(BatchStepResultSchema),
+  rollback: z.array(BatchActionSchema),
+});
+export type BatchResult = z.infer<typeof BatchResultSchema>;
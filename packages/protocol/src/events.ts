@@ -1,6 +1,19 @@
 import { z } from 'zod';
 
-import { TransportStateSchema, ZoneGroupSchema } from './sonos.js';
+import {
+  NetworkHealthSchema,
+  SonosStateSnapshotSchema,
+  TransportStateSchema,
+  ZoneGroupMemberSchema,
+  ZoneGroupSchema,
+} from './sonos.js';
+
+/**
+ * Event protocol version this client understands.
+ * Sent as `?protocolVersion=` on the WebSocket URL so the server withholds
+ * or translates events introduced in later versions.
+ */
+export const EVENT_PROTOCOL_VERSION = 13;
 
 /**
  * Reasons for removing a speaker from an active cast session.
@@ -17,6 +30,12 @@ export const SpeakerRemovalReasonSchema = z.enum([
 ]);
 export type SpeakerRemovalReason = z.infer<typeof SpeakerRemovalReasonSchema>;
 
+/**
+ * What changed on a speaker playing a PIN-locked stream.
+ */
+export const LockedSpeakerChangeSchema = z.enum(['volume', 'mute', 'stopped', 'source']);
+export type LockedSpeakerChange = z.infer<typeof LockedSpeakerChangeSchema>;
+
 /**
  * Sonos event types broadcast by desktop app.
  */
@@ -53,6 +72,13 @@ export const SonosEventSchema = z.discriminatedUnion('type', [
     groups: z.array(ZoneGroupSchema),
     timestamp: z.number(),
   }),
+  z.object({
+    type: z.literal('subscriptionLost'),
+    speakerIp: z.string(),
+    /** UPnP service whose event subscription was lost */
+    service: z.string(),
+    reason: z.string(),
+  }),
 ]);
 export type SonosEvent = z.infer<typeof SonosEventSchema>;
 
@@ -80,6 +106,36 @@ export const StreamEventSchema = z.discriminatedUnion('type', [
     streamId: z.string(),
     timestamp: z.number(),
   }),
+  z.object({
+    type: z.literal('listenersLost'),
+    streamId: z.string(),
+    /** Seconds without a speaker pulling the stream; 'ended' follows */
+    idleSecs: z.number(),
+    timestamp: z.number(),
+  }),
+  z.object({
+    type: z.literal('labelChanged'),
+    streamId: z.string(),
+    /** User-given stream name, null when cleared */
+    label: z.string().nullable(),
+    timestamp: z.number(),
+  }),
+  z.object({
+    type: z.literal('metadataUpdated'),
+    streamId: z.string(),
+    /** Current track metadata, including enrichment results */
+    metadata: z.object({
+      title: z.string().nullish(),
+      artist: z.string().nullish(),
+      album: z.string().optional(),
+      artworkUrl: z.string().optional(),
+      source: z.string().nullish(),
+      sourceUrl: z.string().optional(),
+      sourceTitle: z.string().optional(),
+      faviconUrl: z.string().optional(),
+    }),
+    timestamp: z.number(),
+  }),
   z.object({
     type: z.literal('playbackStarted'),
     streamId: z.string(),
@@ -95,6 +151,40 @@ export const StreamEventSchema = z.discriminatedUnion('type', [
     reason: SpeakerRemovalReasonSchema.optional(),
     timestamp: z.number(),
   }),
+  z.object({
+    type: z.literal('playbackSuspended'),
+    streamId: z.string(),
+    speakerIp: z.string(),
+    /** URI of the source that took over the speaker */
+    currentUri: z.string(),
+    /** Seconds the session waits for the other source to stop before ending */
+    resumeWindowSecs: z.number().int().positive(),
+    timestamp: z.number(),
+  }),
+  z.object({
+    type: z.literal('playbackStoppedExternally'),
+    streamId: z.string(),
+    speakerIp: z.string(),
+    /** Whether the server is resuming the stream without asking */
+    autoResume: z.boolean(),
+    /** Seconds left to confirm resuming via POST /api/playback/resume */
+    confirmWindowSecs: z.number().int().positive().optional(),
+    timestamp: z.number(),
+  }),
+  z.object({
+    type: z.literal('lockedSpeakerChanged'),
+    streamId: z.string(),
+    speakerIp: z.string(),
+    /** What was changed without the stream's PIN, e.g. from the Sonos app */
+    change: LockedSpeakerChangeSchema,
+    timestamp: z.number(),
+  }),
+  z.object({
+    type: z.literal('playbackResumed'),
+    streamId: z.string(),
+    speakerIp: z.string(),
+    timestamp: z.number(),
+  }),
   z.object({
     type: z.literal('playbackStopFailed'),
     streamId: z.string(),
@@ -104,9 +194,119 @@ export const StreamEventSchema = z.discriminatedUnion('type', [
     reason: SpeakerRemovalReasonSchema.optional(),
     timestamp: z.number(),
   }),
+  z.object({
+    type: z.literal('bufferAdapted'),
+    streamId: z.string(),
+    speakerIp: z.string(),
+    /** Previous cadence buffer in milliseconds of audio */
+    previousBufferMs: z.number().int().nonnegative(),
+    /** New cadence buffer in milliseconds of audio */
+    bufferMs: z.number().int().nonnegative(),
+    /** Smoothed ingest jitter in milliseconds */
+    jitterMs: z.number().int().nonnegative(),
+    timestamp: z.number(),
+  }),
+  z.object({
+    type: z.literal('feedbackLoopSuspected'),
+    streamId: z.string(),
+    /** Which signal triggered the warning */
+    cause: z.enum(['audio', 'metadata']),
+    /** Measured loop delay in milliseconds (audio detection only) */
+    loopDelayMs: z.number().int().nonnegative().optional(),
+    timestamp: z.number(),
+  }),
+  z.object({
+    type: z.literal('position'),
+    streamId: z.string(),
+    speakerIp: z.string(),
+    /** Playback epoch ID (increments on Sonos reconnect) */
+    epochId: z.number().int().nonnegative(),
+    /** Position reported by the speaker (second precision) */
+    relTimeMs: z.number().int().nonnegative(),
+    /** Track duration, if the speaker reports one */
+    trackDurationMs: z.number().int().nonnegative().optional(),
+    /** Stream time since the epoch's first audio frame when the position was sampled */
+    streamElapsedMs: z.number().int().nonnegative(),
+    timestamp: z.number(),
+  }),
 ]);
 export type StreamEvent = z.infer<typeof StreamEventSchema>;
 
+/**
+ * Network changes that can let speakers on another subnet work.
+ */
+export const CrossSubnetHintSchema = z.enum([
+  'mdns_reflector',
+  'igmp_snooping',
+  'unicast_only',
+  'allow_inbound',
+]);
+export type CrossSubnetHint = z.infer<typeof CrossSubnetHintSchema>;
+
+/**
+ * Network health event types broadcast by desktop app.
+ */
+export const NetworkEventSchema = z.discriminatedUnion('type', [
+  z.object({
+    type: z.literal('healthChanged'),
+    health: NetworkHealthSchema,
+    /** Human-readable reason for the status (if degraded) */
+    reason: z.string().optional(),
+    timestamp: z.number(),
+  }),
+  z.object({
+    type: z.literal('originApprovalRequested'),
+    /** Origin of the browser extension awaiting approval */
+    origin: z.string(),
+    timestamp: z.number(),
+  }),
+  z.object({
+    type: z.literal('crossSubnetSpeakers'),
+    /** IP the speakers are told to reach the server at */
+    localIp: z.string(),
+    /** Speakers on subnets the server has no address on (empty once resolved) */
+    speakerIps: z.array(z.string()),
+    /** Network changes worth checking, most likely first */
+    hints: z.array(CrossSubnetHintSchema),
+    timestamp: z.number(),
+  }),
+]);
+export type NetworkEvent = z.infer<typeof NetworkEventSchema>;
+
+/**
+ * Topology discovery event types broadcast by desktop app.
+ */
+export const TopologyEventSchema = z.discriminatedUnion('type', [
+  z.object({
+    type: z.literal('groupsDiscovered'),
+    groups: z.array(ZoneGroupSchema),
+    timestamp: z.number(),
+  }),
+  /** Follows the `groupsDiscovered` that includes the speaker */
+  z.object({
+    type: z.literal('speakerAdded'),
+    speaker: ZoneGroupMemberSchema,
+    timestamp: z.number(),
+  }),
+  z.object({
+    type: z.literal('speakerRemoved'),
+    uuid: z.string(),
+    /** Address the speaker was last seen at */
+    speakerIp: z.string(),
+    zoneName: z.string(),
+    timestamp: z.number(),
+  }),
+  z.object({
+    type: z.literal('speakerIpChanged'),
+    uuid: z.string(),
+    zoneName: z.string(),
+    oldIp: z.string(),
+    newIp: z.string(),
+    timestamp: z.number(),
+  }),
+]);
+export type TopologyEvent = z.infer<typeof TopologyEventSchema>;
+
 /**
  * Latency event types broadcast by desktop app.
  * Used for measuring audio playback delay from source to Sonos speaker.
@@ -147,6 +347,79 @@ export const LatencyEventSchema = z.discriminatedUnion('type', [
 ]);
 export type LatencyEvent = z.infer<typeof LatencyEventSchema>;
 
+/**
+ * Resource event types broadcast by desktop app.
+ * Warns when sustained CPU usage during streaming is likely to cause stutter,
+ * and when the data directory is full or read-only so settings changes can't
+ * be saved.
+ */
+export const ResourceEventSchema = z.discriminatedUnion('type', [
+  z.object({
+    type: z.literal('cpuOverload'),
+    /** Process CPU usage as a percentage of total machine capacity */
+    cpuPercent: z.number().nonnegative(),
+    /** How long CPU has been above the threshold, in seconds */
+    sustainedSecs: z.number().int().nonnegative(),
+    /** Number of streams active when the overload was detected */
+    activeStreams: z.number().int().nonnegative(),
+    /** Unix timestamp in milliseconds */
+    timestamp: z.number(),
+  }),
+  z.object({
+    type: z.literal('cpuRecovered'),
+    /** Process CPU usage as a percentage of total machine capacity */
+    cpuPercent: z.number().nonnegative(),
+    /** Unix timestamp in milliseconds */
+    timestamp: z.number(),
+  }),
+  z.object({
+    type: z.literal('storageDegraded'),
+    /** The data directory */
+    path: z.string(),
+    issue: z.enum(['full', 'readOnly']),
+    /** Unix timestamp in milliseconds */
+    timestamp: z.number(),
+  }),
+  z.object({
+    type: z.literal('storageRecovered'),
+    /** The data directory */
+    path: z.string(),
+    /** Unix timestamp in milliseconds */
+    timestamp: z.number(),
+  }),
+]);
+export type ResourceEvent = z.infer<typeof ResourceEventSchema>;
+
+/**
+ * Coalesced Sonos state updates, sent once a burst of changes settles.
+ * Revisions increase by one per event; after a gap, fetch `/api/state` again.
+ */
+export const StateEventSchema = z.discriminatedUnion('type', [
+  z.object({
+    type: z.literal('snapshot'),
+    revision: z.number().int().nonnegative(),
+    /** The full state; sent first and whenever the topology changed */
+    state: SonosStateSnapshotSchema,
+    /** Unix timestamp in milliseconds */
+    timestamp: z.number(),
+  }),
+  z.object({
+    type: z.literal('patch'),
+    /** Revision after applying the patch */
+    revision: z.number().int().nonnegative(),
+    /** Changed speakers per map; `null` removes the speaker's entry */
+    changes: z.object({
+      transportStates: z.record(z.string(), TransportStateSchema.nullable()).optional(),
+      groupVolumes: z.record(z.string(), z.number().nullable()).optional(),
+      groupMutes: z.record(z.string(), z.boolean().nullable()).optional(),
+      groupVolumeFixed: z.record(z.string(), z.boolean().nullable()).optional(),
+    }),
+    /** Unix timestamp in milliseconds */
+    timestamp: z.number(),
+  }),
+]);
+export type StateEvent = z.infer<typeof StateEventSchema>;
+
 /**
  * Broadcast event wrapper from desktop app.
  * Uses passthrough to allow the nested event fields.
@@ -32,6 +32,24 @@ export const TRANSPORT_STATE_ICONS: Record<TransportState, string> = {
   Transitioning: 'loader',
 } as const;
 
+/**
+ * What a Sonos device can do with a stream.
+ * Only players can be cast to; satellites and subwoofers play through their
+ * room's primary speaker, and bridges (Boost, Bridge) have no audio output.
+ */
+export const DeviceRoleSchema = z.enum(['player', 'satellite', 'subwoofer', 'bridge']);
+export type DeviceRole = z.infer<typeof DeviceRoleSchema>;
+
+/**
+ * Membership in a bonded set (stereo pair, home theater), which Sonos treats
+ * as one logical speaker. Only the set's primary can be cast to.
+ */
+export const BondSchema = z.object({
+  kind: z.enum(['stereoPair', 'homeTheater', 'withSubwoofer']),
+  primaryUuid: z.string(),
+});
+export type Bond = z.infer<typeof BondSchema>;
+
 /**
  * A member of a Sonos zone group.
  */
@@ -40,15 +58,35 @@ export const ZoneGroupMemberSchema = z.object({
   ip: z.string(),
   zoneName: z.string(),
   model: z.string().optional(),
+  /** Absent from servers that predate role detection */
+  role: DeviceRoleSchema.optional(),
+  bond: BondSchema.optional(),
+  /** Whether the speaker is on Ethernet; absent when its firmware doesn't say */
+  wired: z.boolean().optional(),
 });
 export type ZoneGroupMember = z.infer<typeof ZoneGroupMemberSchema>;
 
+/**
+ * Whether a member can be cast to on its own. Bonded satellites (the second
+ * half of a stereo pair, surrounds, subs) play through their set's primary.
+ * @param member - The zone group member
+ * @returns True unless the server marked the member as non-playable
+ */
+export function isCastableMember(member: ZoneGroupMember): boolean {
+  return member.role === undefined || member.role === 'player';
+}
+
 /**
  * A Sonos zone group (one or more speakers playing in sync).
  */
 export const ZoneGroupSchema = z.object({
   id: z.string(),
+  /** Every room in the group, coordinator first ("Kitchen, Office") */
   name: z.string(),
+  /** Short label in the Sonos app's style ("Kitchen + 1"); absent from older servers */
+  label: z.string().optional(),
+  /** Rooms in the group, counting bonded sets once; absent from older servers */
+  roomCount: z.number().optional(),
   coordinatorUuid: z.string(),
   coordinatorIp: z.string(),
   members: z.array(ZoneGroupMemberSchema),
@@ -93,6 +131,12 @@ export function createEmptySonosState(): SonosStateSnapshot {
   };
 }
 
+/**
+ * Network health: whether discovered speakers are responding.
+ */
+export const NetworkHealthSchema = z.enum(['ok', 'degraded']);
+export type NetworkHealth = z.infer<typeof NetworkHealthSchema>;
+
 /**
  * Initial state message sent by desktop on WebSocket connect.
  * Includes Sonos state.
@@ -104,6 +148,11 @@ export const InitialStatePayloadSchema = z.object({
   groupMutes: z.record(z.string(), z.boolean()),
   groupVolumeFixed: z.record(z.string(), z.boolean()),
   sessions: z.array(PlaybackSessionSchema).optional(),
+  networkHealth: NetworkHealthSchema.optional(),
+  /** Why the network is degraded, if it is. */
+  networkHealthReason: z.string().optional(),
+  /** Event protocol version negotiated for this connection. */
+  protocolVersion: z.number().int().optional(),
 });
 export type InitialStatePayload = z.infer<typeof InitialStatePayloadSchema>;
 
@@ -11,6 +11,12 @@ export const StreamMetadataSchema = z.object({
   artist: z.string().optional(),
   /** Source name derived from tab URL (e.g., "YouTube", "Spotify") */
   source: z.string().optional(),
+  /** URL of the page being cast (shown in the desktop app, not on Sonos) */
+  sourceUrl: z.string().optional(),
+  /** Title of the page being cast */
+  sourceTitle: z.string().optional(),
+  /** Favicon URL of the page being cast */
+  faviconUrl: z.string().optional(),
 });
 export type StreamMetadata = z.infer<typeof StreamMetadataSchema>;
 
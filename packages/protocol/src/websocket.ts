@@ -10,16 +10,22 @@ import { StreamMetadataSchema } from './stream.js';
  */
 export const WsHandshakePayloadSchema = z.object({
   encoderConfig: EncoderConfigSchema,
+  /** User-given name for the stream (at most 64 characters). */
+  label: z.string().max(64).optional(),
 });
 export type WsHandshakePayload = z.infer<typeof WsHandshakePayloadSchema>;
 
 export const WsHandshakeAckPayloadSchema = z.object({
   streamId: z.string(),
+  /** Frame duration the server settled on (ms). Absent from older servers. */
+  frameDurationMs: z.number().int().positive().optional(),
 });
 export type WsHandshakeAckPayload = z.infer<typeof WsHandshakeAckPayloadSchema>;
 
 export const WsErrorPayloadSchema = z.object({
   message: z.string(),
+  /** i18n key for localizing the message (e.g., 'error.capture_timeout'). */
+  messageKey: z.string().optional(),
 });
 export type WsErrorPayload = z.infer<typeof WsErrorPayloadSchema>;
 
@@ -115,6 +121,8 @@ export const WsStartPlaybackPayloadSchema = z.object({
   speakerIp: z.string(),
   /** Whether the client has video sync enabled (gates server-side latency monitoring). */
   videoSyncEnabled: z.boolean().default(false),
+  /** Name of uploaded artwork to show for this cast (see `POST /api/artwork`). */
+  artwork: z.string().optional(),
 });
 export type WsStartPlaybackPayload = z.infer<typeof WsStartPlaybackPayloadSchema>;
 
@@ -147,6 +155,8 @@ export type WsPlaybackStartedMessage = z.infer<typeof WsPlaybackStartedMessageSc
 export const WsPlaybackErrorPayloadSchema = z.object({
   /** Error message describing the failure. */
   message: z.string(),
+  /** i18n key for localizing the message (e.g., 'error.no_speaker_ips'). */
+  messageKey: z.string().optional(),
 });
 export type WsPlaybackErrorPayload = z.infer<typeof WsPlaybackErrorPayloadSchema>;
 
@@ -244,6 +254,8 @@ export const WsControlCommandSchema = z.discriminatedUnion('type', [
       volume: z.number().int().min(0).max(100),
       /** When true, sets volume for the entire sync group via the coordinator. */
       group: z.boolean().optional(),
+      /** PIN of the stream the speaker plays, if another connection locked it. */
+      pin: z.string().optional(),
     }),
   }),
   z.object({
@@ -253,6 +265,8 @@ export const WsControlCommandSchema = z.discriminatedUnion('type', [
       mute: z.boolean(),
       /** When true, sets mute for the entire sync group via the coordinator. */
       group: z.boolean().optional(),
+      /** PIN of the stream the speaker plays, if another connection locked it. */
+      pin: z.string().optional(),
     }),
   }),
   z.object({
@@ -270,7 +284,38 @@ export const WsControlCommandSchema = z.discriminatedUnion('type', [
       ip: z.string(),
       /** Reason for stopping (optional for backward compat) */
       reason: SpeakerRemovalReasonSchema.optional(),
+      /** PIN of the stream, if another connection locked it. */
+      pin: z.string().optional(),
     }),
   }),
+  z.object({
+    type: z.literal('LOCK_STREAM'),
+    /** Locks this connection's stream with a 4 to 8 digit PIN. */
+    payload: z.object({ pin: z.string() }),
+  }),
 ]);
 export type WsControlCommand = z.infer<typeof WsControlCommandSchema>;
+
+/**
+ * Sent by server in reply to SET_VOLUME / GET_VOLUME.
+ */
+export const WsVolumeStateMessageSchema = z.object({
+  type: z.literal('VOLUME_STATE'),
+  payload: z.object({
+    ip: z.string(),
+    volume: z.number().int().min(0).max(100),
+  }),
+});
+export type WsVolumeStateMessage = z.infer<typeof WsVolumeStateMessageSchema>;
+
+/**
+ * Sent by server in reply to SET_MUTE / GET_MUTE.
+ */
+export const WsMuteStateMessageSchema = z.object({
+  type: z.literal('MUTE_STATE'),
+  payload: z.object({
+    ip: z.string(),
+    mute: z.boolean(),
+  }),
+});
+export type WsMuteStateMessage = z.infer<typeof WsMuteStateMessageSchema>;
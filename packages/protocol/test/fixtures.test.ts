@@ -0,0 +1,91 @@
+/**
+ * Protocol conformance tests.
+ *
+ * The JSON files in `fixtures/` are golden samples of what the Rust server
+ * sends and accepts; thaumic-core checks its serde output against the same
+ * files. Server → client fixtures must parse without losing any field, so a
+ * field added on one side only fails here instead of being silently dropped
+ * by the extension at runtime.
+ */
+
+import { describe, expect, test } from 'bun:test';
+import type { ZodType } from 'zod';
+
+import {
+  LatencyEventSchema,
+  NetworkEventSchema,
+  ResourceEventSchema,
+  SonosEventSchema,
+  StateEventSchema,
+  StreamEventSchema,
+  TopologyEventSchema,
+  WsControlCommandSchema,
+  WsInitialStateMessageSchema,
+  WsMessageSchema,
+  WsMuteStateMessageSchema,
+  WsVolumeStateMessageSchema,
+} from '../index.js';
+import events from '../fixtures/events.json';
+import wsClient from '../fixtures/ws-client.json';
+import wsServer from '../fixtures/ws-server.json';
+
+const EVENT_SCHEMAS: Record<string, ZodType> = {
+  sonos: SonosEventSchema,
+  stream: StreamEventSchema,
+  network: NetworkEventSchema,
+  topology: TopologyEventSchema,
+  latency: LatencyEventSchema,
+  resource: ResourceEventSchema,
+  state: StateEventSchema,
+};
+
+/**
+ * Picks the schema for a server → client message.
+ * @param type - The message type tag
+ * @returns The schema that validates the message
+ */
+function serverMessageSchema(type: string): ZodType {
+  switch (type) {
+    case 'INITIAL_STATE':
+      return WsInitialStateMessageSchema;
+    case 'VOLUME_STATE':
+      return WsVolumeStateMessageSchema;
+    case 'MUTE_STATE':
+      return WsMuteStateMessageSchema;
+    default:
+      return WsMessageSchema;
+  }
+}
+
+describe('broadcast events', () => {
+  for (const [name, fixture] of Object.entries(events)) {
+    test(name, () => {
+      const { category, ...event } = fixture as { category: string; type: string };
+      expect(`${category}.${event.type}`).toBe(name);
+
+      const schema = EVENT_SCHEMAS[category];
+      expect(schema).toBeDefined();
+      expect(schema.parse(event)).toEqual(event);
+    });
+  }
+});
+
+describe('server messages', () => {
+  for (const [name, fixture] of Object.entries(wsServer)) {
+    test(name, () => {
+      expect(fixture.type).toBe(name);
+      expect(serverMessageSchema(name).parse(fixture)).toEqual(fixture);
+    });
+  }
+});
+
+describe('client messages', () => {
+  for (const [name, fixture] of Object.entries(wsClient)) {
+    test(name, () => {
+      expect(fixture.type).toBe(name);
+      const result = WsMessageSchema.safeParse(fixture);
+      const parsed = result.success ? result.data : WsControlCommandSchema.parse(fixture);
+      expect(parsed.type).toBe(name);
+    });
+  }
+});
@@ -0,0 +1,440 @@
+//! Cross-origin policy for the HTTP API.
+//!
+//! Browsers only let a page read API responses from another origin when the
+//! server allows that origin. An [`OriginPolicy`] is a list of rules, each
+//! written as an origin string:
+//!
+//! - `https://app.example.com` matches that exact origin (scheme, host and
+//!   port must all match, so `http://` and `https://` are distinct)
+//! - `*` in the host or port matches any host label(s) or port:
+//!   `chrome-extension://*`, `https://*.example.com`, `http://localhost:*`
+//! - `re:<pattern>` matches origins against a regular expression, which is
+//!   anchored to the whole origin
+//! - a lone `*` allows every origin
+//!
+//! Every rule must name its scheme; an entry such as `example.com` is
+//! rejected rather than guessed at. The same policy is used by the desktop
+//! app and the headless server, and [`create_router`](super::http::create_router)
+//! applies it to the JSON API while media routes allow any origin.
+//!
+//! Browser extensions aren't trusted by default: each build has its own ID,
+//! and unknown ones go through
+//! [`OriginApprovals`](crate::services::origin_approvals::OriginApprovals)
+//! unless configured here. The exception is the store-published extension,
+//! whose IDs release builds bake in (see [`published_extension_origins`]). Chrome and Edge extensions use
+//! `chrome-extension://<32 letters a-p>`, Firefox ones
+//! `moz-extension://<UUID>`; exact entries with those schemes are checked
+//! against the browser's ID format so a mistyped ID fails at startup.
+
+use axum::http::{header, HeaderValue, Method};
+use regex::Regex;
+use serde::Serialize;
+use tower_http::cors::{AllowOrigin, CorsLayer};
+
+use crate::error::{ThaumicError, ThaumicResult};
+
+/// How long browsers may cache a preflight response (seconds).
+const PREFLIGHT_MAX_AGE_SECS: u64 = 600;
+
+/// Origins trusted without configuration: pages served from this machine.
+pub const DEFAULT_TRUSTED_ORIGINS: &[&str] = &[
+    "http://localhost",
+    "http://localhost:*",
+    "http://127.0.0.1",
+    "http://127.0.0.1:*",
+];
+
+/// Chromium IDs of the store-published extension, comma-separated.
+///
+/// Store IDs are fixed by the listing's signing key, so release builds set
+/// `THAUMIC_PUBLISHED_EXTENSION_IDS` when compiling and installs from the
+/// store work without an approval prompt. Unset in development builds.
+const PUBLISHED_EXTENSION_IDS: Option<&str> = option_env!("THAUMIC_PUBLISHED_EXTENSION_IDS");
+
+/// Origins of the store-published extension, trusted alongside
+/// [`DEFAULT_TRUSTED_ORIGINS`].
+pub fn published_extension_origins() -> Vec<String> {
+    extension_origins(PUBLISHED_EXTENSION_IDS.unwrap_or_default())
+}
+
+fn extension_origins(ids: &str) -> Vec<String> {
+    ids.split(',')
+        .map(str::trim)
+        .filter(|id| !id.is_empty())
+        .map(|id| format!("chrome-extension://{}", id))
+        .collect()
+}
+
+/// Browser family of an extension origin, told apart by its scheme.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ExtensionBrowser {
+    /// Chrome, Edge and other Chromium browsers (`chrome-extension://`).
+    Chromium,
+    /// Firefox (`moz-extension://`).
+    Firefox,
+}
+
+impl ExtensionBrowser {
+    /// Returns the browser an origin's scheme belongs to, if it is an
+    /// extension scheme.
+    pub fn from_origin(origin: &str) -> Option<Self> {
+        let (scheme, _) = origin.split_once("://")?;
+        if scheme.eq_ignore_ascii_case("chrome-extension") {
+            Some(Self::Chromium)
+        } else if scheme.eq_ignore_ascii_case("moz-extension") {
+            Some(Self::Firefox)
+        } else {
+            None
+        }
+    }
+
+    /// Whether `id` has the shape this browser gives extension IDs.
+    ///
+    /// Chromium IDs are 32 letters `a`-`p`; Firefox uses a random UUID per
+    /// installation.
+    pub fn is_valid_id(self, id: &str) -> bool {
+        match self {
+            Self::Chromium => {
+                id.len() == 32
+                    && id
+                        .bytes()
+                        .all(|b| matches!(b.to_ascii_lowercase(), b'a'..=b'p'))
+            }
+            Self::Firefox => {
+                let groups: Vec<&str> = id.split('-').collect();
+                groups.iter().map(|g| g.len()).eq([8, 4, 4, 4, 12])
+                    && groups
+                        .iter()
+                        .all(|g| g.bytes().all(|b| b.is_ascii_hexdigit()))
+            }
+        }
+    }
+
+    /// Whether `origin` is a well-formed origin for this browser's
+    /// extensions.
+    pub fn is_valid_origin(self, origin: &str) -> bool {
+        origin
+            .split_once("://")
+            .is_some_and(|(_, id)| self.is_valid_id(id))
+    }
+}
+
+/// A single origin rule.
+#[derive(Debug, Clone)]
+enum OriginRule {
+    /// Any origin.
+    Any,
+    /// Exact origin, lowercased.
+    Exact(String),
+    /// Wildcard or regular expression, anchored.
+    Pattern(Regex),
+}
+
+impl OriginRule {
+    fn parse(entry: &str) -> ThaumicResult<Self> {
+        let entry = entry.trim();
+        if entry == "*" {
+            return Ok(Self::Any);
+        }
+        if let Some(pattern) = entry.strip_prefix("re:") {
+            return Regex::new(&format!("^(?:{})$", pattern))
+                .map(Self::Pattern)
+                .map_err(|e| invalid(entry, &e.to_string()));
+        }
+
+        let (scheme, authority) = entry
+            .split_once("://")
+            .ok_or_else(|| invalid(entry, "origins must include a scheme (e.g. https://)"))?;
+        let scheme_valid = scheme
+            .chars()
+            .next()
+            .is_some_and(|c| c.is_ascii_alphabetic())
+            && scheme
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'));
+        if !scheme_valid {
+            return Err(invalid(entry, "invalid scheme"));
+        }
+        if authority.is_empty() || authority.contains(['/', '?', '#']) {
+            return Err(invalid(entry, "origins have no path, query or fragment"));
+        }
+
+        let origin = format!("{}://{}", scheme, authority).to_ascii_lowercase();
+        if !authority.contains('*') {
+            if let Some(browser) = ExtensionBrowser::from_origin(&origin) {
+                if !browser.is_valid_origin(&origin) {
+                    return Err(invalid(entry, extension_id_hint(browser)));
+                }
+            }
+            return Ok(Self::Exact(origin));
+        }
+
+        let (host, port) = match authority.rsplit_once(':') {
+            Some((host, port)) => (host, Some(port)),
+            None => (authority, None),
+        };
+        let mut pattern = format!("^{}://", regex::escape(&scheme.to_ascii_lowercase()));
+        pattern.push_str(&glob(
+            &host.to_ascii_lowercase(),
+            "[a-z0-9-]+(?:\\.[a-z0-9-]+)*",
+        ));
+        if let Some(port) = port {
+            pattern.push(':');
+            pattern.push_str(&glob(port, "[0-9]+"));
+        }
+        pattern.push('$');
+        Regex::new(&pattern)
+            .map(Self::Pattern)
+            .map_err(|e| invalid(entry, &e.to_string()))
+    }
+
+    fn matches(&self, origin: &str) -> bool {
+        match self {
+            Self::Any => true,
+            Self::Exact(exact) => origin.eq_ignore_ascii_case(exact),
+            Self::Pattern(regex) => regex.is_match(&origin.to_ascii_lowercase()),
+        }
+    }
+}
+
+/// Escapes `text` for a regex, with `*` standing for `wildcard`.
+fn glob(text: &str, wildcard: &str) -> String {
+    text.split('*')
+        .map(regex::escape)
+        .collect::<Vec<_>>()
+        .join(wildcard)
+}
+
+fn extension_id_hint(browser: ExtensionBrowser) -> &'static str {
+    match browser {
+        ExtensionBrowser::Chromium => "Chrome and Edge extension IDs are 32 letters a-p",
+        ExtensionBrowser::Firefox => "Firefox extension IDs are UUIDs",
+    }
+}
+
+fn invalid(entry: &str, reason: &str) -> ThaumicError {
+    ThaumicError::InvalidRequest(format!("Invalid allowed origin '{}': {}", entry, reason))
+}
+
+/// Which origins may call the API from a browser.
+#[derive(Debug, Clone, Default)]
+pub struct OriginPolicy {
+    rules: Vec<OriginRule>,
+}
+
+impl OriginPolicy {
+    /// Starts an empty policy, which allows no cross-origin requests.
+    pub fn builder() -> OriginPolicyBuilder {
+        OriginPolicyBuilder::default()
+    }
+
+    /// A policy allowing every origin.
+    pub fn any() -> Self {
+        Self {
+            rules: vec![OriginRule::Any],
+        }
+    }
+
+    /// The default trusted origins and published extension, plus `extra`
+    /// configured entries.
+    pub fn trusted_with(extra: &[String]) -> ThaumicResult<Self> {
+        Self::trusted_with_published(extra, &published_extension_origins())
+    }
+
+    fn trusted_with_published(extra: &[String], published: &[String]) -> ThaumicResult<Self> {
+        let mut builder = Self::builder();
+        for entry in DEFAULT_TRUSTED_ORIGINS {
+            builder = builder.allow(entry)?;
+        }
+        for entry in published.iter().chain(extra) {
+            builder = builder.allow(entry)?;
+        }
+        Ok(builder.build())
+    }
+
+    /// Whether a request from `origin` is allowed.
+    pub fn allows(&self, origin: &str) -> bool {
+        self.rules.iter().any(|rule| rule.matches(origin))
+    }
+
+    /// A CORS layer enforcing this policy for `methods`.
+    pub fn layer(&self, methods: &[Method]) -> CorsLayer {
+        if self.rules.iter().any(|r| matches!(r, OriginRule::Any)) {
+            return cors_layer(AllowOrigin::any(), methods);
+        }
+        let policy = self.clone();
+        cors_layer(
+            AllowOrigin::predicate(move |origin: &HeaderValue, _| {
+                origin.to_str().is_ok_and(|origin| policy.allows(origin))
+            }),
+            methods,
+        )
+    }
+}
+
+/// A CORS layer allowing `allow_origin` to use `methods`.
+pub(crate) fn cors_layer(allow_origin: AllowOrigin, methods: &[Method]) -> CorsLayer {
+    CorsLayer::new()
+        .allow_origin(allow_origin)
+        .allow_methods(methods.to_vec())
+        .allow_headers([
+            header::CONTENT_TYPE,
+            header::IF_NONE_MATCH,
+            header::IF_MODIFIED_SINCE,
+            header::RANGE,
+        ])
+        .expose_headers([header::ETAG, header::LAST_MODIFIED])
+        .max_age(std::time::Duration::from_secs(PREFLIGHT_MAX_AGE_SECS))
+}
+
+/// Builds an [`OriginPolicy`] rule by rule.
+#[derive(Debug, Default)]
+pub struct OriginPolicyBuilder {
+    rules: Vec<OriginRule>,
+}
+
+impl OriginPolicyBuilder {
+    /// Allows origins matching `entry` (see the module docs for the syntax).
+    pub fn allow(mut self, entry: &str) -> ThaumicResult<Self> {
+        self.rules.push(OriginRule::parse(entry)?);
+        Ok(self)
+    }
+
+    /// Allows exactly `origin`.
+    pub fn exact(mut self, origin: &str) -> Self {
+        self.rules
+            .push(OriginRule::Exact(origin.trim().to_ascii_lowercase()));
+        self
+    }
+
+    /// Allows origins matching a regular expression, anchored to the whole
+    /// origin.
+    pub fn regex(self, pattern: &str) -> ThaumicResult<Self> {
+        self.allow(&format!("re:{}", pattern))
+    }
+
+    pub fn build(self) -> OriginPolicy {
+        OriginPolicy { rules: self.rules }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy(entries: &[&str]) -> OriginPolicy {
+        let mut builder = OriginPolicy::builder();
+        for entry in entries {
+            builder = builder.allow(entry).unwrap();
+        }
+        builder.build()
+    }
+
+    #[test]
+    fn exact_origins_do_not_match_prefixes() {
+        let policy = policy(&["http://localhost:3000"]);
+        assert!(policy.allows("http://localhost:3000"));
+        assert!(policy.allows("HTTP://LOCALHOST:3000"));
+        assert!(!policy.allows("http://localhost:30000"));
+        assert!(!policy.allows("http://localhost:3000.evil.com"));
+        assert!(!policy.allows("https://localhost:3000"));
+    }
+
+    #[test]
+    fn wildcards_match_whole_labels_and_ports() {
+        let policy = policy(&["https://*.example.com", "http://localhost:*"]);
+        assert!(policy.allows("https://app.example.com"));
+        assert!(policy.allows("https://a.b.example.com"));
+        assert!(!policy.allows("https://example.com"));
+        assert!(!policy.allows("https://evil.com/.example.com"));
+        assert!(!policy.allows("http://app.example.com"));
+        assert!(policy.allows("http://localhost:5173"));
+        assert!(!policy.allows("http://localhost:5173.evil.com"));
+    }
+
+    #[test]
+    fn regex_entries_are_anchored() {
+        let policy = policy(&["re:https://(dev|staging)\\.example\\.com"]);
+        assert!(policy.allows("https://dev.example.com"));
+        assert!(!policy.allows("https://dev.example.com.evil.com"));
+        assert!(!policy.allows("https://prod.example.com"));
+    }
+
+    #[test]
+    fn rejects_entries_without_scheme_or_with_paths() {
+        assert!(OriginPolicy::builder().allow("example.com").is_err());
+        assert!(OriginPolicy::builder()
+            .allow("https://example.com/")
+            .is_err());
+        assert!(OriginPolicy::builder().allow("re:(").is_err());
+    }
+
+    #[test]
+    fn extension_entries_are_checked_per_browser() {
+        let chrome = "chrome-extension://abcdefghijklmnopabcdefghijklmnop";
+        let firefox = "moz-extension://0b4f7a52-6c8e-4d7a-9a8e-3f1c2d5e6b7a";
+        let extensions = policy(&[chrome, firefox]);
+        assert!(extensions.allows(chrome));
+        assert!(extensions.allows(&firefox.to_ascii_uppercase()));
+        assert!(!extensions.allows("moz-extension://abcdefghijklmnopabcdefghijklmnop"));
+
+        // Each scheme's ID format is enforced, not just "some ID"
+        for entry in [
+            "chrome-extension://abcdefghijklmnopabcdefghijklmnoq",
+            "chrome-extension://abc",
+            "chrome-extension://0b4f7a52-6c8e-4d7a-9a8e-3f1c2d5e6b7a",
+            "moz-extension://abcdefghijklmnopabcdefghijklmnop",
+            "moz-extension://0b4f7a52-6c8e-4d7a-9a8e-3f1c2d5e6b7",
+        ] {
+            assert!(OriginPolicy::builder().allow(entry).is_err(), "{}", entry);
+        }
+
+        // Wildcards still allow a whole browser
+        let any_firefox = policy(&["moz-extension://*"]);
+        assert!(any_firefox.allows(firefox));
+        assert!(!any_firefox.allows(chrome));
+    }
+
+    #[test]
+    fn recognizes_extension_browsers() {
+        assert_eq!(
+            ExtensionBrowser::from_origin("chrome-extension://x"),
+            Some(ExtensionBrowser::Chromium)
+        );
+        assert_eq!(
+            ExtensionBrowser::from_origin("MOZ-EXTENSION://x"),
+            Some(ExtensionBrowser::Firefox)
+        );
+        assert_eq!(ExtensionBrowser::from_origin("https://example.com"), None);
+        assert_eq!(ExtensionBrowser::from_origin("null"), None);
+    }
+
+    #[test]
+    fn default_policy_trusts_only_loopback() {
+        let policy = OriginPolicy::trusted_with_published(&[], &[]).unwrap();
+        assert!(!policy.allows("chrome-extension://abcdefghijklmnopabcdefghijklmnop"));
+        assert!(!policy.allows("moz-extension://0b4f7a52-6c8e-4d7a-9a8e-3f1c2d5e6b7a"));
+        assert!(policy.allows("http://127.0.0.1:49400"));
+        assert!(!policy.allows("http://192.168.1.50"));
+        assert!(!policy.allows("null"));
+        assert!(OriginPolicy::any().allows("https://anywhere.example"));
+    }
+
+    #[test]
+    fn published_extension_ids_are_trusted() {
+        let published = extension_origins(" abcdefghijklmnopabcdefghijklmnop, ,");
+        assert_eq!(
+            published,
+            vec!["chrome-extension://abcdefghijklmnopabcdefghijklmnop".to_string()]
+        );
+
+        let policy = OriginPolicy::trusted_with_published(&[], &published).unwrap();
+        assert!(policy.allows("chrome-extension://abcdefghijklmnopabcdefghijklmnop"));
+        assert!(!policy.allows("chrome-extension://ponmlkjihgfedcbaponmlkjihgfedcba"));
+
+        // A mistyped ID in a release build fails at startup.
+        let typo = extension_origins("abc");
+        assert!(OriginPolicy::trusted_with_published(&[], &typo).is_err());
+    }
+}
@@ -3,29 +3,57 @@
 //! All handlers are thin - they delegate to services for business logic.
 //! The latency-critical streaming handler lives in [`super::stream`].
 
+use std::collections::HashMap;
 use std::net::{IpAddr, SocketAddr};
 use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Instant;
 
 use axum::{
     body::Body,
-    extract::{connect_info::ConnectInfo, Path, State},
-    http::{header, HeaderMap, Request, StatusCode},
+    extract::{connect_info::ConnectInfo, DefaultBodyLimit, Path, Query, State},
+    http::{header, HeaderMap, Method, Request, StatusCode},
+    middleware::{from_fn_with_state, Next},
     response::{IntoResponse, Response},
-    routing::{any, get, post},
+    routing::{any, get, patch, post},
     Json, Router,
 };
+use bytes::Bytes;
+use futures::stream::{self, StreamExt};
 use serde::Deserialize;
-use serde_json::json;
+use serde_json::{json, Value};
+use tower_http::compression::CompressionLayer;
 
-use super::stream::stream_audio;
-use crate::api::response::{api_error, api_ok, api_success};
+use super::cors::OriginPolicy;
+use super::ingest::{
+    create_ingest_stream, list_ingest_mounts, rtp_ingest_sdp, update_ingest_metadata,
+    upload_ingest_stream,
+};
+use super::stream::{stream_audio, stream_probe};
+use super::ui::serve_ui;
+use crate::api::response::{
+    api_error, api_list, api_ok, api_success, conditional_json, etag_for, etag_matches, list_body,
+    not_modified, not_modified_since, ListQuery,
+};
 use crate::api::ws::ws_handler;
 use crate::api::AppState;
-use crate::error::{ErrorCode, ThaumicError, ThaumicResult};
+use crate::artwork::{DEFAULT_ARTWORK_NAME, MAX_ARTWORK_UPLOAD_BYTES};
+use crate::error::{ThaumicError, ThaumicResult};
+use crate::events::EVENT_PROTOCOL_VERSION;
+use crate::operations::{Operation, OperationKind};
+use crate::outputs::OutputPlayRequest;
 use crate::protocol_constants::{MAX_GENA_BODY_SIZE, SERVICE_ID};
-use crate::sonos::discovery::probe_speaker_by_ip;
-use crate::state::ManualSpeakerConfig;
-use crate::utils::validate_speaker_ip;
+use crate::services::action_batch::{ActionBatch, BatchRequest};
+use crate::services::handoff::{build_handoff, render_qr_png};
+use crate::sonos::bandwidth::estimate_bandwidth_with_progress;
+use crate::sonos::discovery::{probe_speaker_by_host, DiscoveryError};
+use crate::sonos::inventory::collect_inventory;
+use crate::sonos::network_status::collect_network_status;
+use crate::sonos::reachability::{check_reachability, probe_clip};
+use crate::sonos::SonosClient;
+use crate::state::{ManualSpeakerConfig, SonosState, SpeakerSetup};
+use crate::storage::backup::{self, MAX_BACKUP_BYTES};
+use crate::utils::{now_millis, validate_speaker_host, validate_speaker_ip};
 
 // ─────────────────────────────────────────────────────────────────────────────
 // GENA Helpers
@@ -79,43 +107,162 @@ struct PlaybackRequest {
     ip: String,
     #[serde(rename = "streamId")]
     stream_id: String,
+    /// Name of uploaded artwork to show instead of the default.
+    #[serde(default)]
+    artwork: Option<String>,
+    /// PIN of a locked stream the speaker plays now, which this replaces.
+    #[serde(default)]
+    pin: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct ResumeRequest {
+    ip: String,
+}
+
+#[derive(Deserialize)]
+struct OutputPlaybackRequest {
+    #[serde(rename = "streamId")]
+    stream_id: String,
+}
+
+#[derive(Deserialize)]
+struct StreamUpdateRequest {
+    /// New label; `null` or blank clears it.
+    label: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct StreamLockRequest {
+    pin: String,
+}
+
+#[derive(Deserialize)]
+struct ArtworkUploadQuery {
+    #[serde(default)]
+    name: Option<String>,
+    /// Preset label (e.g. `"AAC 48kHz"`) to show the artwork for.
+    #[serde(default)]
+    preset: Option<String>,
 }
 
 #[derive(Deserialize)]
 struct VolumeRequest {
     volume: u8,
+    /// PIN of the stream the speaker plays, if it is locked.
+    #[serde(default)]
+    pin: Option<String>,
 }
 
 #[derive(Deserialize)]
 struct MuteRequest {
     mute: bool,
+    /// PIN of the stream the speaker plays, if it is locked.
+    #[serde(default)]
+    pin: Option<String>,
 }
 
 #[derive(Deserialize)]
 struct ManualSpeakerRequest {
+    /// IPv4 address or hostname (e.g. `sonos-kitchen.lan`).
     ip: String,
 }
 
+#[derive(Deserialize)]
+struct IgnoredSpeakerRequest {
+    /// Speaker UUID (`RINCON_...`) or IPv4 address.
+    speaker: String,
+}
+
+#[derive(Deserialize)]
+struct RemoteSubnetsRequest {
+    /// IPv4 CIDR ranges, replacing the saved ones.
+    subnets: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct OriginRequest {
+    origin: String,
+}
+
 // ─────────────────────────────────────────────────────────────────────────────
 // Router
 // ─────────────────────────────────────────────────────────────────────────────
 
 /// Creates the Axum router with all routes.
+///
+/// JSON API responses are gzip/deflate compressed when the client accepts it.
+/// Audio streams, artwork, GENA callbacks and the WebSocket are routed
+/// outside the compression layer: audio is already compressed or must reach
+/// speakers without buffering, and the others gain nothing from it.
+///
+/// Cross-origin access to the JSON API follows the configured
+/// [`OriginPolicy`] plus extensions the user approved; unknown extensions are
+/// refused from the API and WebSocket until approved. Streams and artwork are
+/// plain media any page may load.
 pub fn create_router(state: AppState) -> Router {
-    Router::new()
+    let api = Router::new()
         .route("/health", get(health_check))
         .route("/ready", get(readiness_check))
         .route("/api/speakers", get(list_speakers))
         .route("/api/groups", get(list_groups))
+        .route("/api/outputs", get(list_outputs))
+        .route("/api/outputs/{target}/{id}/play", post(play_on_output))
+        .route("/api/outputs/{target}/{id}/stop", post(stop_output))
+        .route(
+            "/api/artwork",
+            get(list_artwork)
+                .post(upload_artwork)
+                .layer(DefaultBodyLimit::max(MAX_ARTWORK_UPLOAD_BYTES)),
+        )
+        .route("/api/artwork/{name}", axum::routing::delete(delete_artwork))
         .route("/api/state", get(get_current_state))
         .route("/api/refresh", post(handle_refresh))
         .route("/api/playback/start", post(handle_start_playback))
+        .route("/api/playback/resume", post(handle_resume_playback))
+        .route("/api/actions/batch", post(run_action_batch))
+        .route("/api/streams/{id}", patch(update_stream))
+        .route("/api/streams/{id}/handoff", get(stream_handoff))
+        .route("/api/streams/{id}/qr.png", get(stream_qr))
+        .route("/api/streams/{id}/lock", post(lock_stream))
+        .route("/api/streams/{id}/unlock", post(unlock_stream))
         .route(
             "/api/speakers/{ip}/volume",
             get(get_volume).post(set_volume),
         )
         .route("/api/speakers/{ip}/mute", get(get_mute).post(set_mute))
+        .route("/api/speakers/{ip}/bandwidth", post(measure_bandwidth))
+        .route(
+            "/api/speakers/{ip}/reachability",
+            post(check_speaker_reachability),
+        )
+        .route("/api/speakers/inventory", get(speaker_inventory))
+        .route("/api/speakers/network", get(speaker_network))
+        .route("/api/stats/summary", get(stats_summary))
+        .route("/api/stats/soap", get(soap_stats))
+        .route("/api/stats/startup", get(startup_stats))
+        .route("/api/stats/gena", get(gena_stats))
+        .route("/api/stats/events", get(event_stats))
+        .route("/api/diagnostics/discovery", get(discovery_diagnostics))
+        .route(
+            "/api/discovery/subnets",
+            get(list_remote_subnets).put(set_remote_subnets),
+        )
+        .route("/api/operations", get(list_operations))
+        .route("/api/operations/{id}/cancel", post(cancel_operation))
+        .route("/api/clients", get(list_clients))
+        .route("/api/ingest", post(create_ingest_stream))
+        .route("/api/ingest/mounts", get(list_ingest_mounts))
+        .route("/api/ingest/rtp.sdp", get(rtp_ingest_sdp))
+        .route("/api/ingest/{id}", axum::routing::put(upload_ingest_stream))
+        .route("/api/ingest/{id}/metadata", post(update_ingest_metadata))
+        .route("/api/origins", get(list_origins))
+        .route("/api/origins/approve", post(approve_origin))
+        .route("/api/origins/deny", post(deny_origin))
+        .route("/ui", get(serve_ui))
         .route("/api/speakers/manual/probe", post(probe_manual_speaker))
+        .route("/api/speakers/manual/export", get(export_manual_speakers))
+        .route("/api/speakers/manual/import", post(import_manual_speakers))
         .route(
             "/api/speakers/manual",
             get(list_manual_speakers).post(add_manual_speaker),
@@ -124,12 +271,54 @@ pub fn create_router(state: AppState) -> Router {
             "/api/speakers/manual/{ip}",
             axum::routing::delete(remove_manual_speaker),
         )
-        .route("/sonos/gena", any(handle_gena_notify))
+        .route(
+            "/api/speakers/ignored",
+            get(list_ignored_speakers).post(add_ignored_speaker),
+        )
+        .route(
+            "/api/speakers/ignored/{entry}",
+            axum::routing::delete(remove_ignored_speaker),
+        )
+        .route("/api/backup/export", get(export_backup))
+        .route(
+            "/api/backup/import",
+            post(import_backup).layer(DefaultBodyLimit::max(MAX_BACKUP_BYTES)),
+        )
+        .layer(from_fn_with_state(state.clone(), require_approved_origin))
+        .layer(CompressionLayer::new().gzip(true).deflate(true))
+        .layer(state.origin_approvals.cors_layer(&[
+            Method::GET,
+            Method::POST,
+            Method::PUT,
+            Method::PATCH,
+            Method::DELETE,
+        ]));
+
+    let media = Router::new()
         .route("/stream/{id}/live", get(stream_audio))
         .route("/stream/{id}/live.wav", get(stream_audio))
         .route("/stream/{id}/live.flac", get(stream_audio))
+        .route("/stream/{id}/live.ogg", get(stream_audio))
+        .route("/stream/{id}/probe", get(stream_probe))
+        .route("/reachability/{token}/clip.wav", get(reachability_clip))
         .route("/artwork.jpg", get(serve_artwork))
-        .route("/ws", get(ws_handler))
+        .route("/artwork/{file}", get(serve_uploaded_artwork))
+        .layer(OriginPolicy::any().layer(&[Method::GET, Method::HEAD]));
+
+    api.merge(media)
+        .route("/sonos/gena", any(handle_gena_notify))
+        .route(
+            "/ws",
+            get(ws_handler).route_layer(from_fn_with_state(state.clone(), require_approved_origin)),
+        )
+        .with_state(state)
+}
+
+/// Creates the router for a dedicated GENA listener, which only accepts
+/// speaker NOTIFY requests.
+pub fn create_gena_router(state: AppState) -> Router {
+    Router::new()
+        .route("/sonos/gena", any(handle_gena_notify))
         .with_state(state)
 }
 
@@ -146,6 +335,7 @@ async fn health_check(State(state): State<AppState>) -> impl IntoResponse {
     api_success(json!({
         "status": "ok",
         "service": SERVICE_ID,
+        "eventProtocolVersion": EVENT_PROTOCOL_VERSION,
         "limits": {
             "maxStreams": max_streams
         }
@@ -159,15 +349,36 @@ async fn health_check(State(state): State<AppState>) -> impl IntoResponse {
 async fn serve_artwork(
     State(state): State<AppState>,
     ConnectInfo(remote_addr): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
 ) -> Response {
     match state.artwork.as_bytes() {
         Some(bytes) => {
+            let etag = etag_for(bytes);
+            if etag_matches(&headers, &etag)
+                || not_modified_since(&headers, state.artwork_loaded_at)
+            {
+                log::debug!("[Artwork] Album art not modified for {}", remote_addr.ip());
+                return not_modified(&etag);
+            }
+
             log::info!(
                 "[Artwork] Album art requested by {} ({} bytes)",
                 remote_addr.ip(),
                 bytes.len()
             );
-            ([(header::CONTENT_TYPE, "image/jpeg")], bytes.clone()).into_response()
+            (
+                [
+                    (header::CONTENT_TYPE, "image/jpeg".to_string()),
+                    (header::ETAG, etag),
+                    (header::CACHE_CONTROL, "no-cache".to_string()),
+                    (
+                        header::LAST_MODIFIED,
+                        httpdate::fmt_http_date(state.artwork_loaded_at),
+                    ),
+                ],
+                bytes.clone(),
+            )
+                .into_response()
         }
         None => {
             // Artwork is configured as external URL; Sonos fetches directly
@@ -177,6 +388,91 @@ async fn serve_artwork(
     }
 }
 
+/// Serves artwork uploaded through `POST /api/artwork`.
+async fn serve_uploaded_artwork(
+    State(state): State<AppState>,
+    Path(file): Path<String>,
+    headers: HeaderMap,
+) -> Response {
+    let Some(image) = file
+        .strip_suffix(".jpg")
+        .and_then(|name| state.artwork_library.get(name))
+    else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+
+    let etag = etag_for(&image);
+    if etag_matches(&headers, &etag) {
+        return not_modified(&etag);
+    }
+    (
+        [
+            (header::CONTENT_TYPE, "image/jpeg".to_string()),
+            (header::ETAG, etag),
+            (header::CACHE_CONTROL, "no-cache".to_string()),
+        ],
+        image,
+    )
+        .into_response()
+}
+
+/// GET /api/artwork
+///
+/// Lists uploaded artwork with the URLs speakers fetch it from and the
+/// quality presets each image is assigned to.
+async fn list_artwork(State(state): State<AppState>) -> impl IntoResponse {
+    let presets = state.artwork_library.presets();
+    let artwork: Vec<Value> = state
+        .artwork_library
+        .names()
+        .into_iter()
+        .map(|name| {
+            let mut assigned: Vec<&String> = presets
+                .iter()
+                .filter(|(_, n)| **n == name)
+                .map(|(preset, _)| preset)
+                .collect();
+            assigned.sort();
+            json!({
+                "name": name,
+                "url": state.uploaded_artwork_url(&name),
+                "presets": assigned,
+            })
+        })
+        .collect();
+    api_success(json!({ "artwork": artwork }))
+}
+
+/// POST /api/artwork?name=<name>&preset=<preset>
+///
+/// Stores the JPEG or PNG request body as artwork, resized for speakers.
+/// Without `name` it replaces the default artwork shown for every cast;
+/// with `preset` it's shown for casts using that quality preset.
+async fn upload_artwork(
+    State(state): State<AppState>,
+    Query(query): Query<ArtworkUploadQuery>,
+    body: Bytes,
+) -> ThaumicResult<impl IntoResponse> {
+    let name = query.name.as_deref().unwrap_or(DEFAULT_ARTWORK_NAME);
+    state
+        .artwork_library
+        .save(name, &body, query.preset.as_deref())?;
+    Ok(api_success(json!({
+        "name": name,
+        "url": state.uploaded_artwork_url(name),
+        "preset": query.preset,
+    })))
+}
+
+/// DELETE /api/artwork/{name}
+async fn delete_artwork(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+) -> ThaumicResult<impl IntoResponse> {
+    state.artwork_library.remove(&name)?;
+    Ok(api_ok())
+}
+
 /// Readiness probe: "Can the service handle requests?"
 ///
 /// Returns 200 OK only when:
@@ -213,23 +509,244 @@ async fn readiness_check(State(state): State<AppState>) -> Response {
     }
 }
 
-async fn list_speakers(State(state): State<AppState>) -> Response {
-    match state.sonos.discover_speakers().await {
-        Ok(speakers) => api_success(json!({ "speakers": speakers })).into_response(),
-        Err(e) => {
-            api_error(StatusCode::INTERNAL_SERVER_ERROR, "discovery_failed", e).into_response()
-        }
+/// GET /api/stats/summary
+///
+/// Casting statistics. Only served to clients on this machine.
+async fn stats_summary(
+    State(state): State<AppState>,
+    ConnectInfo(remote_addr): ConnectInfo<SocketAddr>,
+) -> ThaumicResult<impl IntoResponse> {
+    require_local(remote_addr)?;
+    Ok(api_success(state.stats.summary(now_millis())))
+}
+
+/// GET /api/stats/soap
+///
+/// Per-speaker SOAP request counts, latencies and connections opened.
+/// Only served to clients on this machine.
+async fn soap_stats(
+    State(state): State<AppState>,
+    ConnectInfo(remote_addr): ConnectInfo<SocketAddr>,
+) -> ThaumicResult<impl IntoResponse> {
+    require_local(remote_addr)?;
+    Ok(api_success(state.soap_client.stats()))
+}
+
+/// GET /api/stats/startup
+///
+/// Cold-start timeline of every speaker of every active stream, from stream
+/// creation to first audio. Only served to clients on this machine.
+async fn startup_stats(
+    State(state): State<AppState>,
+    ConnectInfo(remote_addr): ConnectInfo<SocketAddr>,
+) -> ThaumicResult<impl IntoResponse> {
+    require_local(remote_addr)?;
+    let registry = state.stream_coordinator.stream_registry();
+    let reports: Vec<_> = registry
+        .list_stream_ids()
+        .iter()
+        .filter_map(|id| registry.get_stream(id))
+        .flat_map(|stream| stream.startup_reports())
+        .collect();
+    Ok(api_success(reports))
+}
+
+/// GET /api/stats/gena
+///
+/// GENA NOTIFY processing lag (arrival to state applied and to events
+/// emitted) and event queue depth, to tell a slow speaker from our own
+/// backlog. Only served to clients on this machine.
+async fn gena_stats(
+    State(state): State<AppState>,
+    ConnectInfo(remote_addr): ConnectInfo<SocketAddr>,
+) -> ThaumicResult<impl IntoResponse> {
+    require_local(remote_addr)?;
+    Ok(api_success(state.discovery_service.gena_notify_stats()))
+}
+
+/// GET /api/stats/events
+///
+/// Events sent and dropped by lagging receivers, per priority. Only served
+/// to clients on this machine.
+async fn event_stats(
+    State(state): State<AppState>,
+    ConnectInfo(remote_addr): ConnectInfo<SocketAddr>,
+) -> ThaumicResult<impl IntoResponse> {
+    require_local(remote_addr)?;
+    Ok(api_success(state.event_bridge.stats()))
+}
+
+/// GET /api/diagnostics/discovery
+///
+/// Per-method results of the last discovery run: status, responses and
+/// errors per interface, timings, and merged/dropped counts (`null` before
+/// the first run). Only served to clients on this machine.
+async fn discovery_diagnostics(
+    State(state): State<AppState>,
+    ConnectInfo(remote_addr): ConnectInfo<SocketAddr>,
+) -> ThaumicResult<impl IntoResponse> {
+    require_local(remote_addr)?;
+    Ok(api_success(
+        json!({ "diagnostics": state.sonos.last_discovery_diagnostics() }),
+    ))
+}
+
+/// GET /api/speakers
+///
+/// Runs discovery and returns the speakers found, leaving out ignored ones,
+/// as a [`ListQuery`] list tagged with an ETag.
+/// With `?stream=true`, responds with newline-delimited JSON instead, one
+/// speaker per line as soon as its device description resolves.
+async fn list_speakers(
+    State(state): State<AppState>,
+    Query(mut params): Query<HashMap<String, String>>,
+    headers: HeaderMap,
+) -> ThaumicResult<Response> {
+    if params
+        .remove("stream")
+        .is_some_and(|stream| stream == "true")
+    {
+        return Ok(stream_speakers(Arc::clone(&state.sonos), Arc::clone(&state.sonos_state)).await);
     }
+
+    let query = ListQuery::from_params(params)?;
+    let mut speakers = state.sonos.discover_speakers().await?;
+    let ignored = &state.sonos_state.ignored_speakers;
+    speakers.retain(|s| !ignored.is_ignored(&s.uuid, &s.ip));
+    let body = list_body("speakers", query.apply(speakers)?);
+    Ok(conditional_json(&headers, &body))
 }
 
-async fn list_groups(State(state): State<AppState>) -> impl IntoResponse {
+/// Streams discovered speakers as NDJSON.
+///
+/// Waits for the first result so a failed scan still gets a regular error
+/// response rather than a 200 with an error line.
+async fn stream_speakers(sonos: Arc<dyn SonosClient>, sonos_state: Arc<SonosState>) -> Response {
+    let mut speakers = Box::pin(async_stream::stream! {
+        let mut speakers = sonos.discover_speakers_stream();
+        while let Some(result) = speakers.next().await {
+            yield result;
+        }
+    });
+
+    let first = match speakers.next().await {
+        Some(Err(e)) => return ThaumicError::from(e).into_response(),
+        first => first,
+    };
+
+    let lines = stream::iter(first)
+        .chain(speakers)
+        .filter_map(|result| async move { result.ok() })
+        .filter(move |speaker| {
+            let ignored = sonos_state
+                .ignored_speakers
+                .is_ignored(&speaker.uuid, &speaker.ip);
+            async move { !ignored }
+        })
+        .map(|speaker| serde_json::to_string(&speaker).map(|json| json + "\n"));
+
+    (
+        [(header::CONTENT_TYPE, "application/x-ndjson")],
+        Body::from_stream(lines),
+    )
+        .into_response()
+}
+
+/// GET /api/groups
+///
+/// Lists the current zone groups, as a [`ListQuery`] list tagged with an ETag.
+async fn list_groups(
+    State(state): State<AppState>,
+    Query(params): Query<HashMap<String, String>>,
+    headers: HeaderMap,
+) -> ThaumicResult<Response> {
+    let query = ListQuery::from_params(params)?;
     let groups = state.sonos_state.groups.read().clone();
-    api_success(json!({ "groups": groups }))
+    let body = list_body("groups", query.apply(groups)?);
+    Ok(conditional_json(&headers, &body))
+}
+
+/// GET /api/outputs
+///
+/// Discovers devices of the registered non-Sonos output targets, as a
+/// [`ListQuery`] list.
+async fn list_outputs(
+    State(state): State<AppState>,
+    Query(params): Query<HashMap<String, String>>,
+) -> ThaumicResult<impl IntoResponse> {
+    let query = ListQuery::from_params(params)?;
+    let devices = state.output_targets.discover().await;
+    Ok(api_list("outputs", query.apply(devices)?))
+}
+
+/// POST /api/outputs/{target}/{id}/play
+///
+/// Plays a stream on a device found by the last `/api/outputs` discovery.
+async fn play_on_output(
+    Path((target, id)): Path<(String, String)>,
+    State(state): State<AppState>,
+    Json(payload): Json<OutputPlaybackRequest>,
+) -> ThaumicResult<impl IntoResponse> {
+    let stream = state
+        .stream_coordinator
+        .get_stream(&payload.stream_id)
+        .ok_or_else(|| ThaumicError::StreamNotFound(payload.stream_id.clone()))?;
+    let device = state.output_targets.device(&target, &id)?;
+    let request = OutputPlayRequest {
+        stream_url: state
+            .network
+            .url_builder_for(&device.address)
+            .stream_url(&stream.id),
+        codec: stream.codec,
+        audio_format: stream.audio_format,
+        metadata: Some(stream.metadata.read().clone()),
+        artwork_url: state.artwork_url_for(&stream.id, None),
+    };
+    state.output_targets.play(&target, &id, &request).await?;
+    Ok(api_success(json!({
+        "target": target,
+        "id": id,
+        "streamId": payload.stream_id,
+    })))
+}
+
+/// POST /api/outputs/{target}/{id}/stop
+async fn stop_output(
+    Path((target, id)): Path<(String, String)>,
+    State(state): State<AppState>,
+) -> ThaumicResult<impl IntoResponse> {
+    state.output_targets.stop(&target, &id).await?;
+    Ok(api_ok())
 }
 
-/// Returns current system state (groups, transport states, volumes, mute states).
+/// Returns current system state (groups, transport states, volumes, mute
+/// states) with the active streams and playback sessions.
 async fn get_current_state(State(state): State<AppState>) -> impl IntoResponse {
-    api_success(state.sonos_state.to_json())
+    let mut body = state.sonos_state.to_json();
+    if let Value::Object(map) = &mut body {
+        let coordinator = &state.stream_coordinator;
+        let streams: Vec<Value> = coordinator
+            .stream_registry()
+            .list_stream_ids()
+            .into_iter()
+            .filter_map(|id| coordinator.get_stream(&id))
+            .map(|stream| {
+                json!({
+                    "id": stream.id,
+                    "codec": stream.codec,
+                    "label": stream.label(),
+                    "locked": stream.is_locked(),
+                    "metadata": *stream.metadata.read(),
+                })
+            })
+            .collect();
+        map.insert("streams".to_string(), Value::Array(streams));
+        map.insert(
+            "sessions".to_string(),
+            json!(coordinator.get_session_infos()),
+        );
+    }
+    api_success(body)
 }
 
 /// Triggers a manual topology refresh.
@@ -242,7 +759,12 @@ async fn handle_start_playback(
     State(state): State<AppState>,
     Json(payload): Json<PlaybackRequest>,
 ) -> ThaumicResult<impl IntoResponse> {
-    let artwork_url = state.artwork_metadata_url();
+    state.stream_coordinator.authorize_speaker(
+        &payload.ip,
+        payload.pin.as_deref(),
+        Some(&payload.stream_id),
+    )?;
+    let artwork_url = state.artwork_url_for(&payload.stream_id, payload.artwork.as_deref());
     state
         .stream_coordinator
         .start_playback(&payload.ip, &payload.stream_id, None, &artwork_url)
@@ -251,6 +773,131 @@ async fn handle_start_playback(
     Ok(api_ok())
 }
 
+/// POST /api/playback/resume
+///
+/// Resumes a speaker stopped from the Sonos app while the server waits for
+/// confirmation (`externalStop: ask`).
+async fn handle_resume_playback(
+    State(state): State<AppState>,
+    Json(payload): Json<ResumeRequest>,
+) -> ThaumicResult<impl IntoResponse> {
+    if !state.stream_coordinator.confirm_resume(&payload.ip).await {
+        return Err(ThaumicError::InvalidRequest(format!(
+            "{} is not waiting to resume",
+            payload.ip
+        )));
+    }
+    Ok(api_ok())
+}
+
+/// POST /api/actions/batch
+///
+/// Runs an ordered list of control actions (a "scene") in one round trip.
+/// Responds 200 with per-step results even when a step failed; only a
+/// malformed batch is rejected outright.
+async fn run_action_batch(
+    State(state): State<AppState>,
+    Json(request): Json<BatchRequest>,
+) -> ThaumicResult<impl IntoResponse> {
+    let artwork_url = state.artwork_metadata_url();
+    let batch = ActionBatch {
+        sonos: &*state.sonos,
+        stream_coordinator: &state.stream_coordinator,
+        sonos_state: &state.sonos_state,
+        artwork_url: &artwork_url,
+    };
+    Ok(api_success(batch.run(request).await?))
+}
+
+/// PATCH /api/streams/{id}
+///
+/// Sets or clears the stream's label with `{"label": "..."}`.
+async fn update_stream(
+    State(state): State<AppState>,
+    Path(stream_id): Path<String>,
+    Json(request): Json<StreamUpdateRequest>,
+) -> ThaumicResult<impl IntoResponse> {
+    let label = state
+        .stream_coordinator
+        .set_stream_label(&stream_id, request.label.as_deref())?;
+    Ok(api_success(json!({
+        "streamId": stream_id,
+        "label": label,
+    })))
+}
+
+/// POST /api/streams/{id}/lock
+///
+/// Locks the stream with `{"pin": "1234"}` so volume, mute and stop requests
+/// for its speakers need the PIN. Only clients on this machine may lock
+/// streams here; the connection casting a stream locks it with `LOCK_STREAM`.
+async fn lock_stream(
+    State(state): State<AppState>,
+    ConnectInfo(remote_addr): ConnectInfo<SocketAddr>,
+    Path(stream_id): Path<String>,
+    Json(request): Json<StreamLockRequest>,
+) -> ThaumicResult<impl IntoResponse> {
+    require_local(remote_addr)?;
+    state
+        .stream_coordinator
+        .lock_stream(&stream_id, &request.pin)?;
+    Ok(api_ok())
+}
+
+/// POST /api/streams/{id}/unlock
+///
+/// Unlocks the stream with the PIN it was locked with.
+async fn unlock_stream(
+    State(state): State<AppState>,
+    Path(stream_id): Path<String>,
+    Json(request): Json<StreamLockRequest>,
+) -> ThaumicResult<impl IntoResponse> {
+    state
+        .stream_coordinator
+        .unlock_stream(&stream_id, &request.pin)?;
+    Ok(api_ok())
+}
+
+/// GET /api/streams/{id}/handoff
+///
+/// Returns what another device needs to listen along to a stream: its URL,
+/// metadata and elapsed time, plus a short text to show as a QR code.
+async fn stream_handoff(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> ThaumicResult<impl IntoResponse> {
+    let handoff = build_handoff(&state.stream_coordinator, &state.network, &id)?;
+    Ok(api_success(handoff))
+}
+
+/// GET /api/streams/{id}/qr.png
+///
+/// Serves the handoff text of a stream as a QR code, for a phone to scan and
+/// listen along. The code only changes with the server's address, so it is
+/// cached and revalidated by ETag.
+async fn stream_qr(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    headers: HeaderMap,
+) -> ThaumicResult<Response> {
+    let handoff = build_handoff(&state.stream_coordinator, &state.network, &id)?;
+    let png = render_qr_png(&handoff.qr_text)?;
+
+    let etag = etag_for(&png);
+    if etag_matches(&headers, &etag) {
+        return Ok(not_modified(&etag));
+    }
+    Ok((
+        [
+            (header::CONTENT_TYPE, "image/png".to_string()),
+            (header::ETAG, etag),
+            (header::CACHE_CONTROL, "private, max-age=300".to_string()),
+        ],
+        png,
+    )
+        .into_response())
+}
+
 // ─────────────────────────────────────────────────────────────────────────────
 // Manual Speaker Handlers
 // ─────────────────────────────────────────────────────────────────────────────
@@ -278,33 +925,51 @@ fn parse_and_validate_ip(ip: &str) -> ThaumicResult<String> {
     Ok(ipv4.to_string())
 }
 
+/// Parses and validates a manual speaker address: an IP or a hostname.
+///
+/// Returns the canonical IPv4 string or lowercased hostname on success.
+fn parse_and_validate_host(host: &str) -> ThaumicResult<String> {
+    validate_speaker_host(host.trim()).map_err(|e| ThaumicError::InvalidIp(e.message().into()))
+}
+
 /// POST /api/speakers/manual/probe
 ///
-/// Validates an IP address and probes it to confirm it's a Sonos speaker.
-/// Does not persist the IP - use POST /api/speakers/manual to add after probing.
+/// Validates an IP address or hostname and probes it to confirm it's a
+/// Sonos speaker, resolving hostnames through DNS first.
+/// Does not persist the address - use POST /api/speakers/manual to add after probing.
 async fn probe_manual_speaker(
     State(state): State<AppState>,
     Json(payload): Json<ManualSpeakerRequest>,
 ) -> Response {
-    let canonical_ip = match parse_and_validate_ip(&payload.ip) {
-        Ok(ip) => ip,
+    let canonical_host = match parse_and_validate_host(&payload.ip) {
+        Ok(host) => host,
         Err(e) => return e.into_response(),
     };
 
-    match probe_speaker_by_ip(state.discovery_service.http_client(), &canonical_ip).await {
-        Ok(speaker) => api_success(json!({ "speaker": speaker })).into_response(),
-        Err(e) => {
-            // DiscoveryError implements ErrorCode trait with specific codes
-            // like "ip_unreachable" and "not_sonos_device"
-            api_error(StatusCode::BAD_REQUEST, e.code(), e).into_response()
-        }
+    let operation = state
+        .operations
+        .start(OperationKind::SpeakerProbe, Some(canonical_host.clone()));
+    let probe = probe_speaker_by_host(state.discovery_service.http_client(), &canonical_host);
+
+    match operation.run(probe).await {
+        Some(Ok(speaker)) => api_success(json!({ "speaker": speaker })).into_response(),
+        Some(Err(e)) => probe_failed(&state, &canonical_host, e).into_response(),
+        None => cancelled(&operation).into_response(),
     }
 }
 
+/// Turns a failed probe into an error that keeps its discovery code, like
+/// "ip_unreachable", and says when the speaker was last discovered.
+fn probe_failed(state: &AppState, host: &str, err: DiscoveryError) -> ThaumicError {
+    ThaumicError::from(err).with_last_seen(state.sonos_state.speaker_addresses.last_seen(host))
+}
+
 /// POST /api/speakers/manual
 ///
-/// Adds a manually configured speaker IP address.
-/// Probes the IP first to verify it's a Sonos speaker before persisting.
+/// Adds a manually configured speaker IP address or hostname.
+/// Probes the address first to verify it's a Sonos speaker before persisting.
+/// Hostnames are stored as given and resolved again on every topology
+/// refresh, so the speaker is still found after its IP changes.
 /// Triggers a topology refresh after adding.
 async fn add_manual_speaker(
     State(state): State<AppState>,
@@ -315,19 +980,24 @@ async fn add_manual_speaker(
         Err(e) => return e.into_response(),
     };
 
-    let canonical_ip = match parse_and_validate_ip(&payload.ip) {
-        Ok(ip) => ip,
+    let canonical_host = match parse_and_validate_host(&payload.ip) {
+        Ok(host) => host,
         Err(e) => return e.into_response(),
     };
 
     // Probe to verify it's actually a Sonos speaker before persisting
-    if let Err(e) = probe_speaker_by_ip(state.discovery_service.http_client(), &canonical_ip).await
-    {
-        return api_error(StatusCode::BAD_REQUEST, e.code(), e).into_response();
+    let operation = state
+        .operations
+        .start(OperationKind::SpeakerProbe, Some(canonical_host.clone()));
+    let probe = probe_speaker_by_host(state.discovery_service.http_client(), &canonical_host);
+    match operation.run(probe).await {
+        Some(Ok(_)) => {}
+        Some(Err(e)) => return probe_failed(&state, &canonical_host, e).into_response(),
+        None => return cancelled(&operation).into_response(),
     }
 
-    if let Err(e) = ManualSpeakerConfig::add_ip_atomic(&data_dir, canonical_ip) {
-        return api_error(StatusCode::INTERNAL_SERVER_ERROR, "save_failed", e).into_response();
+    if let Err(e) = ManualSpeakerConfig::add_ip_atomic(&data_dir, canonical_host) {
+        return ThaumicError::SaveFailed(e.to_string()).into_response();
     }
 
     state.discovery_service.trigger_refresh();
@@ -336,10 +1006,10 @@ async fn add_manual_speaker(
 
 /// DELETE /api/speakers/manual/:ip
 ///
-/// Removes a manually configured speaker IP address.
+/// Removes a manually configured speaker IP address or hostname.
 /// Triggers a topology refresh after removing.
 ///
-/// If the IP can be parsed and validated, uses canonical form for storage matching.
+/// If the address can be parsed and validated, uses canonical form for storage matching.
 /// If parsing fails (e.g., IPv6, malformed input), falls back to exact string
 /// matching to allow removal of legacy/invalid entries from the config file.
 async fn remove_manual_speaker(Path(ip): Path<String>, State(state): State<AppState>) -> Response {
@@ -349,10 +1019,10 @@ async fn remove_manual_speaker(Path(ip): Path<String>, State(state): State<AppSt
     };
 
     // Try canonical matching first, fall back to exact string for invalid/legacy entries
-    let ip_to_remove = parse_and_validate_ip(&ip).unwrap_or_else(|_| ip.clone());
+    let ip_to_remove = parse_and_validate_host(&ip).unwrap_or_else(|_| ip.clone());
 
     if let Err(e) = ManualSpeakerConfig::remove_ip_atomic(&data_dir, &ip_to_remove) {
-        return api_error(StatusCode::INTERNAL_SERVER_ERROR, "save_failed", e).into_response();
+        return ThaumicError::SaveFailed(e.to_string()).into_response();
     }
 
     state.discovery_service.trigger_refresh();
@@ -361,15 +1031,202 @@ async fn remove_manual_speaker(Path(ip): Path<String>, State(state): State<AppSt
 
 /// GET /api/speakers/manual
 ///
-/// Lists manually configured speaker IP addresses.
-async fn list_manual_speakers(State(state): State<AppState>) -> Response {
-    let data_dir = match require_data_dir(&state) {
-        Ok(dir) => dir,
-        Err(e) => return e.into_response(),
+/// Lists manually configured speaker IP addresses, as a [`ListQuery`] list.
+async fn list_manual_speakers(
+    State(state): State<AppState>,
+    Query(params): Query<HashMap<String, String>>,
+) -> ThaumicResult<impl IntoResponse> {
+    let query = ListQuery::from_params(params)?;
+    let data_dir = require_data_dir(&state)?;
+    let config = ManualSpeakerConfig::load(&data_dir);
+    Ok(api_list("ips", query.apply(config.speaker_ips)?))
+}
+
+/// GET /api/speakers/manual/export
+///
+/// Returns the manual and ignored speakers as a [`SpeakerSetup`], for
+/// `POST /api/speakers/manual/import` on another installation.
+async fn export_manual_speakers(State(state): State<AppState>) -> ThaumicResult<impl IntoResponse> {
+    let data_dir = require_data_dir(&state)?;
+    Ok(api_success(SpeakerSetup::export(
+        &data_dir,
+        &state.sonos_state.ignored_speakers,
+    )))
+}
+
+/// POST /api/speakers/manual/import
+///
+/// Adds the manual and ignored speakers of an exported [`SpeakerSetup`] to
+/// the existing ones. Triggers a topology refresh if anything was added.
+async fn import_manual_speakers(
+    State(state): State<AppState>,
+    Json(setup): Json<SpeakerSetup>,
+) -> ThaumicResult<impl IntoResponse> {
+    let data_dir = require_data_dir(&state)?;
+    let summary = setup.import(&data_dir, &state.sonos_state.ignored_speakers)?;
+    if summary.manual_speakers_added + summary.ignored_speakers_added > 0 {
+        state.discovery_service.trigger_refresh();
+    }
+    Ok(api_success(summary))
+}
+
+/// GET /api/speakers/ignored
+///
+/// Lists ignored speaker UUIDs and IPs, as a [`ListQuery`] list.
+async fn list_ignored_speakers(
+    State(state): State<AppState>,
+    Query(params): Query<HashMap<String, String>>,
+) -> ThaumicResult<impl IntoResponse> {
+    let query = ListQuery::from_params(params)?;
+    let entries = state.sonos_state.ignored_speakers.entries();
+    Ok(api_list("speakers", query.apply(entries)?))
+}
+
+/// POST /api/speakers/ignored
+///
+/// Ignores a speaker by UUID or IP, leaving it out of discovery, topology
+/// and events. Triggers a topology refresh after adding.
+async fn add_ignored_speaker(
+    State(state): State<AppState>,
+    Json(payload): Json<IgnoredSpeakerRequest>,
+) -> ThaumicResult<impl IntoResponse> {
+    let entry = state.sonos_state.ignored_speakers.add(&payload.speaker)?;
+    state.discovery_service.trigger_refresh();
+    Ok(api_success(json!({ "speaker": entry })))
+}
+
+/// DELETE /api/speakers/ignored/:entry
+///
+/// Stops ignoring a speaker added through the API. Entries from the
+/// configuration file can't be removed here. Triggers a topology refresh
+/// after removing.
+async fn remove_ignored_speaker(
+    Path(entry): Path<String>,
+    State(state): State<AppState>,
+) -> ThaumicResult<impl IntoResponse> {
+    if state.sonos_state.ignored_speakers.remove(&entry)? {
+        state.discovery_service.trigger_refresh();
+    }
+    Ok(api_ok())
+}
+
+/// GET /api/discovery/subnets
+///
+/// Lists the subnets searched by unicast during discovery: `subnets` holds
+/// every range, `saved` those set through the API.
+async fn list_remote_subnets(State(state): State<AppState>) -> impl IntoResponse {
+    api_success(json!({
+        "subnets": state.remote_subnets.entries(),
+        "saved": state.remote_subnets.saved(),
+    }))
+}
+
+/// PUT /api/discovery/subnets
+///
+/// Replaces the subnets saved through the API. Ranges from the configuration
+/// file are kept. Triggers a topology refresh after saving.
+async fn set_remote_subnets(
+    State(state): State<AppState>,
+    Json(payload): Json<RemoteSubnetsRequest>,
+) -> ThaumicResult<impl IntoResponse> {
+    let saved = state.remote_subnets.set_saved(&payload.subnets)?;
+    state.discovery_service.trigger_refresh();
+    Ok(api_success(json!({ "saved": saved })))
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Backup Handlers
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Returns the data directory backups are made of and restored to.
+fn require_backup_dir(state: &AppState) -> ThaumicResult<PathBuf> {
+    state.discovery_service.get_app_data_dir().ok_or_else(|| {
+        ThaumicError::DataDirNotConfigured(
+            "Backups require --data-dir or THAUMIC_DATA_DIR to be set".into(),
+        )
+    })
+}
+
+/// Refuses backup requests sent by a web page the policy doesn't trust.
+///
+/// Any page the user visits can make their browser send a simple request to
+/// localhost, and CORS only hides the response, so the loopback check alone
+/// doesn't keep other sites out. Requests without an `Origin` (curl, the
+/// desktop app) pass.
+fn require_trusted_origin(state: &AppState, headers: &HeaderMap) -> ThaumicResult<()> {
+    let Some(origin) = headers.get(header::ORIGIN) else {
+        return Ok(());
     };
+    match origin.to_str() {
+        Ok(origin) if state.origin_approvals.allows(origin) => Ok(()),
+        _ => Err(ThaumicError::Forbidden(
+            "Backups can't be requested from this origin".into(),
+        )),
+    }
+}
 
-    let config = ManualSpeakerConfig::load(&data_dir);
-    api_success(json!({ "ips": config.speaker_ips })).into_response()
+/// Requires an `application/zip` body, which browsers can't send across
+/// origins without a CORS preflight.
+fn require_zip(headers: &HeaderMap) -> ThaumicResult<()> {
+    let is_zip = headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.split(';').next())
+        .is_some_and(|mime| mime.trim().eq_ignore_ascii_case("application/zip"));
+    if is_zip {
+        Ok(())
+    } else {
+        Err(ThaumicError::InvalidRequest(
+            "Backups must be sent with Content-Type: application/zip".into(),
+        ))
+    }
+}
+
+/// GET /api/backup/export
+///
+/// Downloads a zip of the data directory and configuration file. Only
+/// served to clients on this machine, since it includes API keys.
+async fn export_backup(
+    State(state): State<AppState>,
+    ConnectInfo(remote_addr): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
+) -> ThaumicResult<Response> {
+    require_local(remote_addr)?;
+    require_trusted_origin(&state, &headers)?;
+    let data_dir = require_backup_dir(&state)?;
+    let archive = backup::export(&data_dir, state.config_file.as_deref())?;
+    let disposition = format!(
+        "attachment; filename=\"thaumic-cast-backup-{}.zip\"",
+        crate::stats::format_date(now_millis())
+    );
+    Ok((
+        [
+            (header::CONTENT_TYPE, "application/zip".to_string()),
+            (header::CONTENT_DISPOSITION, disposition),
+        ],
+        archive,
+    )
+        .into_response())
+}
+
+/// POST /api/backup/import
+///
+/// Restores a backup made by `/api/backup/export`, sent as the request body.
+/// Data files are restored on the next start; the configuration file is
+/// replaced right away. Only accepted from clients on this machine, as
+/// `application/zip`.
+async fn import_backup(
+    State(state): State<AppState>,
+    ConnectInfo(remote_addr): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> ThaumicResult<impl IntoResponse> {
+    require_local(remote_addr)?;
+    require_trusted_origin(&state, &headers)?;
+    require_zip(&headers)?;
+    let data_dir = require_backup_dir(&state)?;
+    let summary = backup::import(&data_dir, state.config_file.as_deref(), &body)?;
+    Ok(api_success(summary))
 }
 
 // ─────────────────────────────────────────────────────────────────────────────
@@ -401,6 +1258,9 @@ async fn set_volume(
     State(state): State<AppState>,
     Json(payload): Json<VolumeRequest>,
 ) -> ThaumicResult<impl IntoResponse> {
+    state
+        .stream_coordinator
+        .authorize_speaker(&ip, payload.pin.as_deref(), None)?;
     state
         .stream_coordinator
         .set_volume_routed(&*state.sonos, &ip, payload.volume)
@@ -432,6 +1292,9 @@ async fn set_mute(
     State(state): State<AppState>,
     Json(payload): Json<MuteRequest>,
 ) -> ThaumicResult<impl IntoResponse> {
+    state
+        .stream_coordinator
+        .authorize_speaker(&ip, payload.pin.as_deref(), None)?;
     state
         .stream_coordinator
         .set_mute_routed(&*state.sonos, &ip, payload.mute)
@@ -439,10 +1302,266 @@ async fn set_mute(
     Ok(api_success(json!({ "ip": ip, "mute": payload.mute })))
 }
 
+/// POST /api/speakers/{ip}/bandwidth
+///
+/// Runs a short upload burst to the speaker and reports the estimated
+/// throughput with a codec recommendation. Takes a few seconds; progress is
+/// listed under `/api/operations`.
+async fn measure_bandwidth(Path(ip): Path<String>, State(state): State<AppState>) -> Response {
+    let canonical_ip = match parse_and_validate_ip(&ip) {
+        Ok(ip) => ip,
+        Err(e) => return e.into_response(),
+    };
+
+    let operation = state
+        .operations
+        .start(OperationKind::BandwidthTest, Some(canonical_ip.clone()));
+    let estimate = estimate_bandwidth_with_progress(&canonical_ip, |sent, total| {
+        operation.set_progress(sent, Some(total))
+    });
+
+    match operation.run(estimate).await {
+        Some(Ok(estimate)) => api_success(json!({ "bandwidth": estimate })).into_response(),
+        Some(Err(e)) => api_error(StatusCode::BAD_GATEWAY, &e).into_response(),
+        None => cancelled(&operation).into_response(),
+    }
+}
+
+/// POST /api/speakers/{ip}/reachability
+///
+/// Checks that the speaker can fetch audio from this server by having it play
+/// a short silent clip. Interrupts the speaker's playback, so speakers being
+/// cast to are refused. Takes up to ten seconds; progress is listed under
+/// `/api/operations`.
+async fn check_speaker_reachability(
+    Path(ip): Path<String>,
+    State(state): State<AppState>,
+) -> Response {
+    let canonical_ip = match parse_and_validate_ip(&ip) {
+        Ok(ip) => ip,
+        Err(e) => return e.into_response(),
+    };
+    if state
+        .stream_coordinator
+        .is_speaker_in_sync_session(&canonical_ip)
+        .is_some()
+    {
+        return ThaumicError::InvalidRequest(
+            "Speaker is being cast to; stop the cast before checking reachability".into(),
+        )
+        .into_response();
+    }
+
+    let operation = state
+        .operations
+        .start(OperationKind::ReachabilityCheck, Some(canonical_ip.clone()));
+    let url_builder = state.network.url_builder();
+    let artwork_url = state.artwork_metadata_url();
+    let check = check_reachability(
+        state.discovery_service.http_client(),
+        &*state.sonos,
+        &state.reachability,
+        &url_builder,
+        &artwork_url,
+        &canonical_ip,
+    );
+
+    match operation.run(check).await {
+        Some(report) => api_success(json!({ "reachability": report })).into_response(),
+        None => cancelled(&operation).into_response(),
+    }
+}
+
+/// GET /reachability/{token}/clip.wav
+///
+/// Serves the silent clip of a running reachability check, recording that
+/// the speaker's request arrived.
+async fn reachability_clip(
+    Path(token): Path<String>,
+    State(state): State<AppState>,
+    ConnectInfo(remote_addr): ConnectInfo<SocketAddr>,
+) -> Response {
+    if !state.reachability.request_arrived(&token, remote_addr.ip()) {
+        return StatusCode::NOT_FOUND.into_response();
+    }
+    ([(header::CONTENT_TYPE, "audio/wav")], probe_clip()).into_response()
+}
+
+/// GET /api/speakers/inventory
+///
+/// Model and software versions of every speaker in the current topology,
+/// with firmware known to cause problems flagged.
+async fn speaker_inventory(State(state): State<AppState>) -> impl IntoResponse {
+    let members: Vec<_> = state
+        .sonos_state
+        .groups
+        .read()
+        .iter()
+        .flat_map(|group| group.members.iter().cloned())
+        .collect();
+    let inventory = collect_inventory(state.discovery_service.http_client(), &members).await;
+    api_success(json!({ "inventory": inventory }))
+}
+
+/// GET /api/speakers/network
+///
+/// Whether each speaker in the current topology is wired, and the Wi-Fi
+/// signal of wireless ones, with weak signals flagged.
+async fn speaker_network(State(state): State<AppState>) -> impl IntoResponse {
+    let members: Vec<_> = state
+        .sonos_state
+        .groups
+        .read()
+        .iter()
+        .flat_map(|group| group.members.iter().cloned())
+        .collect();
+    let speakers = collect_network_status(state.discovery_service.http_client(), &members).await;
+    for speaker in &speakers {
+        if let Some(rssi) = speaker.signal_strength {
+            state
+                .sonos_state
+                .signal_strengths
+                .insert(speaker.ip.clone(), rssi);
+        }
+    }
+    api_success(json!({ "speakers": speakers }))
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Operation Handlers
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// GET /api/operations
+///
+/// Lists in-flight long-running operations (discovery runs, speaker probes,
+/// bandwidth tests) with their progress, as a [`ListQuery`] list.
+async fn list_operations(
+    State(state): State<AppState>,
+    Query(params): Query<HashMap<String, String>>,
+) -> ThaumicResult<impl IntoResponse> {
+    let query = ListQuery::from_params(params)?;
+    Ok(api_list(
+        "operations",
+        query.apply(state.operations.list())?,
+    ))
+}
+
+/// GET /api/clients
+///
+/// Lists connected WebSocket clients with what they reported about
+/// themselves, as a [`ListQuery`] list.
+async fn list_clients(
+    State(state): State<AppState>,
+    Query(params): Query<HashMap<String, String>>,
+) -> ThaumicResult<impl IntoResponse> {
+    let query = ListQuery::from_params(params)?;
+    Ok(api_list(
+        "clients",
+        query.apply(state.ws_manager.clients())?,
+    ))
+}
+
+/// POST /api/operations/{id}/cancel
+///
+/// Requests cancellation of an in-flight operation. The request that started
+/// it fails with `operation_cancelled`.
+async fn cancel_operation(
+    Path(id): Path<String>,
+    State(state): State<AppState>,
+) -> ThaumicResult<impl IntoResponse> {
+    if !state.operations.cancel(&id) {
+        return Err(ThaumicError::OperationNotFound(id));
+    }
+    Ok(api_ok())
+}
+
+/// Error returned by a request whose operation was cancelled.
+fn cancelled(operation: &Operation) -> ThaumicError {
+    ThaumicError::Cancelled(operation.id().to_string())
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Origin Approval Handlers
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Refuses requests from browser extensions the user hasn't approved.
+///
+/// Requests without an `Origin` header (speakers, scripts, same-origin page
+/// loads) pass through.
+async fn require_approved_origin(
+    State(state): State<AppState>,
+    request: Request<Body>,
+    next: Next,
+) -> Response {
+    let origin = request
+        .headers()
+        .get(header::ORIGIN)
+        .and_then(|v| v.to_str().ok());
+    if let Some(origin) = origin {
+        if let Err(e) = state.origin_approvals.check(origin) {
+            return e.into_response();
+        }
+    }
+    next.run(request).await
+}
+
+/// GET /api/origins
+///
+/// Approved, pending and denied extension origins. Only served to clients on
+/// this machine.
+async fn list_origins(
+    State(state): State<AppState>,
+    ConnectInfo(remote_addr): ConnectInfo<SocketAddr>,
+) -> ThaumicResult<impl IntoResponse> {
+    require_local(remote_addr)?;
+    Ok(api_success(state.origin_approvals.state()))
+}
+
+/// POST /api/origins/approve
+///
+/// Trusts an extension origin from now on. Only served to clients on this
+/// machine.
+async fn approve_origin(
+    State(state): State<AppState>,
+    ConnectInfo(remote_addr): ConnectInfo<SocketAddr>,
+    Json(payload): Json<OriginRequest>,
+) -> ThaumicResult<impl IntoResponse> {
+    require_local(remote_addr)?;
+    state.origin_approvals.approve(&payload.origin)?;
+    Ok(api_ok())
+}
+
+/// POST /api/origins/deny
+///
+/// Refuses an extension origin until restart. Only served to clients on this
+/// machine.
+async fn deny_origin(
+    State(state): State<AppState>,
+    ConnectInfo(remote_addr): ConnectInfo<SocketAddr>,
+    Json(payload): Json<OriginRequest>,
+) -> ThaumicResult<impl IntoResponse> {
+    require_local(remote_addr)?;
+    state.origin_approvals.deny(&payload.origin)?;
+    Ok(api_ok())
+}
+
+/// Refuses requests from other machines, for endpoints that expose or
+/// change local state.
+fn require_local(remote_addr: SocketAddr) -> ThaumicResult<()> {
+    if remote_addr.ip().is_loopback() {
+        Ok(())
+    } else {
+        Err(ThaumicError::Forbidden(
+            "Only available to clients on this machine".into(),
+        ))
+    }
+}
+
 async fn handle_gena_notify(
     State(state): State<AppState>,
     req: Request<Body>,
 ) -> ThaumicResult<impl IntoResponse> {
+    let received = Instant::now();
     let (parts, body) = req.into_parts();
 
     // Only accept NOTIFY method (used by UPnP/GENA)
@@ -462,9 +1581,11 @@ async fn handle_gena_notify(
             ThaumicError::InvalidRequest("Failed to read body".into())
         })?;
 
-    let events = state
-        .discovery_service
-        .handle_gena_notify(&sid, &String::from_utf8_lossy(&body_bytes));
+    let events = state.discovery_service.handle_gena_notify(
+        &sid,
+        &String::from_utf8_lossy(&body_bytes),
+        received,
+    );
 
     if events.is_empty() {
         log::trace!(
@@ -556,4 +1677,65 @@ mod tests {
             assert!(result.is_err());
         }
     }
+
+    #[test]
+    fn backup_uploads_must_be_zip() {
+        let with_type = |value: &str| {
+            let mut headers = HeaderMap::new();
+            headers.insert(
+                header::CONTENT_TYPE,
+                axum::http::HeaderValue::from_str(value).unwrap(),
+            );
+            headers
+        };
+        assert!(require_zip(&with_type("application/zip")).is_ok());
+        assert!(require_zip(&with_type("Application/ZIP; charset=binary")).is_ok());
+        assert!(require_zip(&with_type("text/plain")).is_err());
+        assert!(require_zip(&with_type("application/x-www-form-urlencoded")).is_err());
+        assert!(require_zip(&HeaderMap::new()).is_err());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn only_local_clients_lock_streams_over_http() {
+        let (_, state) = crate::test_support::bootstrap_app(
+            crate::state::Config::default(),
+            crate::context::NetworkContext::for_test(),
+        );
+        let stream_id = state
+            .stream_coordinator
+            .create_stream(
+                crate::stream::AudioCodec::Aac,
+                crate::stream::AudioFormat::default(),
+                200,
+                20,
+            )
+            .unwrap();
+        let lock = |remote: &str| {
+            lock_stream(
+                State(state.clone()),
+                ConnectInfo(remote.parse().unwrap()),
+                Path(stream_id.clone()),
+                Json(StreamLockRequest {
+                    pin: "2468".to_string(),
+                }),
+            )
+        };
+
+        assert!(matches!(
+            lock("192.168.1.50:50000").await,
+            Err(ThaumicError::Forbidden(_))
+        ));
+        assert!(!state
+            .stream_coordinator
+            .get_stream(&stream_id)
+            .unwrap()
+            .is_locked());
+
+        assert!(lock("127.0.0.1:50000").await.is_ok());
+        assert!(state
+            .stream_coordinator
+            .get_stream(&stream_id)
+            .unwrap()
+            .is_locked());
+    }
 }
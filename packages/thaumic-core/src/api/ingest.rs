@@ -0,0 +1,417 @@
+//! Generic audio ingest for apps other than the browser extension.
+//!
+//! With [`IngestConfig::enabled`], any LAN app holding the API key (OBS, a
+//! Python script, a line-in recorder) can push audio to a stream and cast it
+//! like a browser tab:
+//!
+//! 1. `POST /api/ingest` with the same fields as the WebSocket handshake's
+//!    `encoderConfig` (plus optional `metadata`) creates a stream and returns
+//!    its ID and upload URL.
+//! 2. `PUT /api/ingest/{id}` with a chunked body streams the audio. The
+//!    stream ends when the upload does.
+//! 3. `POST /api/playback/start` plays the stream on speakers as usual.
+//!
+//! Every request carries `Authorization: Bearer <api key>`. Raw PCM is cut
+//! into frames of the configured duration, so it can be written in any chunk
+//! size; MP3 and AAC (ADTS) are passed through as received. FLAC is not
+//! accepted here because its stream header must arrive as its own frame; use
+//! the WebSocket protocol for it. Opus isn't accepted either: Sonos can't
+//! play it and the server doesn't decode it, so senders must decode it to
+//! PCM first.
+//!
+//! Devices that send RTP rather than HTTP are served by the separate
+//! [`RtpReceiver`](crate::services::RtpReceiver), configured under
+//! [`IngestConfig::rtp`]; `GET /api/ingest/rtp.sdp` describes what it expects.
+//! Icecast source clients connect to [`IngestConfig::icecast_port`] instead
+//! (see the `icecast` module).
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use axum::{
+    body::Body,
+    extract::{Path, State},
+    http::{header, HeaderMap},
+    response::{IntoResponse, Response},
+    Json,
+};
+use bytes::BytesMut;
+use futures::StreamExt;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use super::response::{api_ok, api_success};
+use super::ws::{parse_stream_config, EncoderConfig, HandshakeRequest};
+use super::AppState;
+use crate::error::{ThaumicError, ThaumicResult};
+use crate::protocol_constants::{INGEST_UPLOAD_TIMEOUT_SECS, MAX_SAMPLE_RATE, MIN_SAMPLE_RATE};
+use crate::services::stream_coordinator::normalize_label;
+use crate::services::{RtpIngestConfig, StreamCoordinator};
+use crate::stream::{AudioCodec, StreamMetadata};
+use crate::utils::constant_time_eq;
+
+/// Configuration for generic audio ingest.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct IngestConfig {
+    /// Accept audio at `/api/ingest`.
+    pub enabled: bool,
+    /// Key clients send as `Authorization: Bearer <key>`. Required when
+    /// ingest is enabled.
+    pub api_key: Option<String>,
+    /// RTP listener for devices that push audio over UDP. Independent of
+    /// `enabled`, since RTP carries no API key.
+    pub rtp: RtpIngestConfig,
+    /// Port for Icecast source clients (BUTT, Mixxx), which log in with the
+    /// API key as password. `None` disables the source listener.
+    pub icecast_port: Option<u16>,
+}
+
+impl IngestConfig {
+    /// Checks that an enabled ingest endpoint has a usable key.
+    pub fn validate(&self) -> ThaumicResult<()> {
+        let has_key = self
+            .api_key
+            .as_deref()
+            .is_some_and(|k| !k.trim().is_empty());
+        if self.enabled && !has_key {
+            return Err(ThaumicError::InvalidRequest(
+                "Ingest is enabled but no ingest API key is set".into(),
+            ));
+        }
+        if let Some(port) = self.icecast_port {
+            if port == 0 || !self.enabled {
+                return Err(ThaumicError::InvalidRequest(
+                    "The Icecast source listener needs a fixed port and ingest enabled".into(),
+                ));
+            }
+        }
+        self.rtp.validate()
+    }
+
+    /// The API key requests must carry, without surrounding whitespace, or
+    /// `None` while ingest is disabled.
+    pub fn active_key(&self) -> Option<&str> {
+        self.api_key
+            .as_deref()
+            .map(str::trim)
+            .filter(|_| self.enabled)
+    }
+}
+
+/// Body of `POST /api/ingest`.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(super) struct IngestRequest {
+    #[serde(flatten)]
+    encoder: EncoderConfig,
+    #[serde(default)]
+    metadata: Option<StreamMetadata>,
+    /// User-given name for the stream.
+    #[serde(default)]
+    label: Option<String>,
+}
+
+/// Streams created through the ingest API.
+#[derive(Default)]
+pub(crate) struct IngestSessions {
+    /// Stream ID to PCM frame size in bytes (`None` for encoded codecs),
+    /// for streams whose upload hasn't started.
+    waiting: Mutex<HashMap<String, Option<usize>>>,
+    /// Icecast mount point to the stream its connected source feeds.
+    mounts: Mutex<HashMap<String, String>>,
+}
+
+impl IngestSessions {
+    /// Registers a new stream and removes it if no upload starts in time.
+    fn add(
+        self: &Arc<Self>,
+        stream_id: &str,
+        frame_bytes: Option<usize>,
+        coordinator: Arc<StreamCoordinator>,
+    ) {
+        self.waiting
+            .lock()
+            .insert(stream_id.to_string(), frame_bytes);
+
+        let sessions = Arc::clone(self);
+        let stream_id = stream_id.to_string();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_secs(INGEST_UPLOAD_TIMEOUT_SECS)).await;
+            if sessions.waiting.lock().remove(&stream_id).is_some() {
+                log::info!("[Ingest] No upload for stream {}, removing it", stream_id);
+                coordinator.remove_stream(&stream_id);
+            }
+        });
+    }
+
+    /// Claims a waiting stream for upload. Each stream takes one upload.
+    fn claim(&self, stream_id: &str) -> Option<Option<usize>> {
+        self.waiting.lock().remove(stream_id)
+    }
+
+    /// Assigns a mount point to a stream. Fails if a source already has it.
+    pub(super) fn claim_mount(&self, mount: &str, stream_id: &str) -> bool {
+        let mut mounts = self.mounts.lock();
+        if mounts.contains_key(mount) {
+            return false;
+        }
+        mounts.insert(mount.to_string(), stream_id.to_string());
+        true
+    }
+
+    /// Frees a mount point once its source disconnects.
+    pub(super) fn release_mount(&self, mount: &str) {
+        self.mounts.lock().remove(mount);
+    }
+
+    /// Returns the stream fed by the source on a mount point.
+    pub(super) fn mount_stream(&self, mount: &str) -> Option<String> {
+        self.mounts.lock().get(mount).cloned()
+    }
+}
+
+/// Removes an ingest stream when its upload ends or the client goes away.
+struct UploadGuard {
+    stream_id: String,
+    coordinator: Arc<StreamCoordinator>,
+}
+
+impl Drop for UploadGuard {
+    fn drop(&mut self) {
+        self.coordinator.remove_stream(&self.stream_id);
+        log::info!("[Ingest] Upload ended, removed stream {}", self.stream_id);
+    }
+}
+
+/// Checks that ingest is enabled and the request carries its API key.
+fn authorize(state: &AppState, headers: &HeaderMap) -> ThaumicResult<()> {
+    let config = state.config.read().ingest.clone();
+    let Some(expected) = config.active_key() else {
+        return Err(ThaumicError::Forbidden("Ingest is disabled".into()));
+    };
+    let provided = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .map(str::trim);
+    match provided {
+        Some(key) if constant_time_eq(key.as_bytes(), expected.as_bytes()) => Ok(()),
+        Some(_) => Err(ThaumicError::Unauthorized("Wrong ingest API key".into())),
+        None => Err(ThaumicError::Unauthorized("Missing ingest API key".into())),
+    }
+}
+
+/// POST /api/ingest
+///
+/// Creates a stream for an external app to upload audio to.
+pub(super) async fn create_ingest_stream(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(request): Json<IngestRequest>,
+) -> ThaumicResult<impl IntoResponse> {
+    authorize(&state, &headers)?;
+
+    let codec_name = request.encoder.codec.to_ascii_lowercase();
+    if codec_name == "opus" {
+        return Err(ThaumicError::InvalidRequest(
+            "Opus can't be ingested; decode it to PCM first".into(),
+        ));
+    }
+    if !matches!(
+        codec_name.as_str(),
+        "pcm" | "mp3" | "aac" | "aac-lc" | "he-aac" | "he-aac-v2"
+    ) {
+        return Err(ThaumicError::InvalidRequest(format!(
+            "Unsupported ingest codec '{}'. Use pcm, mp3 or aac.",
+            request.encoder.codec
+        )));
+    }
+
+    let config = parse_stream_config(&HandshakeRequest {
+        codec: None,
+        encoder_config: Some(request.encoder),
+        label: None,
+    })
+    .map_err(ThaumicError::InvalidRequest)?;
+    let sample_rate = config.audio_format.sample_rate;
+    if !(MIN_SAMPLE_RATE..=MAX_SAMPLE_RATE).contains(&sample_rate) {
+        return Err(ThaumicError::InvalidRequest(format!(
+            "Invalid sample_rate: {}. Must be {}-{} Hz.",
+            sample_rate, MIN_SAMPLE_RATE, MAX_SAMPLE_RATE
+        )));
+    }
+    let label = normalize_label(request.label.as_deref()).map_err(ThaumicError::InvalidRequest)?;
+    let stream_id = state
+        .stream_coordinator
+        .create_stream(
+            config.codec,
+            config.audio_format,
+            config.streaming_buffer_ms,
+            config.frame_duration_ms,
+        )
+        .map_err(ThaumicError::InvalidRequest)?;
+    if let Some(metadata) = request.metadata {
+        state
+            .stream_coordinator
+            .update_metadata(&stream_id, metadata);
+    }
+    if label.is_some() {
+        state
+            .stream_coordinator
+            .set_stream_label(&stream_id, label.as_deref())?;
+    }
+
+    let frame_bytes = (config.codec == AudioCodec::Pcm)
+        .then(|| config.audio_format.frame_bytes(config.frame_duration_ms));
+    state.ingest.add(
+        &stream_id,
+        frame_bytes,
+        Arc::clone(&state.stream_coordinator),
+    );
+    log::info!(
+        "[Ingest] Created stream {}: codec={:?}, format={:?}",
+        stream_id,
+        config.codec,
+        config.audio_format
+    );
+
+    Ok(api_success(json!({
+        "streamId": stream_id,
+        "frameDurationMs": config.frame_duration_ms,
+        "uploadUrl": format!("/api/ingest/{}", stream_id),
+        "streamUrl": state.network.stream_url(&stream_id),
+    })))
+}
+
+/// PUT /api/ingest/{id}
+///
+/// Streams the request body into the stream until the upload ends, then
+/// removes the stream.
+pub(super) async fn upload_ingest_stream(
+    State(state): State<AppState>,
+    Path(stream_id): Path<String>,
+    headers: HeaderMap,
+    body: Body,
+) -> ThaumicResult<impl IntoResponse> {
+    authorize(&state, &headers)?;
+    let frame_bytes = state
+        .ingest
+        .claim(&stream_id)
+        .ok_or_else(|| ThaumicError::StreamNotFound(stream_id.clone()))?;
+    let _guard = UploadGuard {
+        stream_id: stream_id.clone(),
+        coordinator: Arc::clone(&state.stream_coordinator),
+    };
+
+    let mut received: u64 = 0;
+    let mut pending = BytesMut::new();
+    let mut chunks = body.into_data_stream();
+    while let Some(chunk) = chunks.next().await {
+        let chunk = chunk.map_err(|e| ThaumicError::InvalidRequest(e.to_string()))?;
+        received += chunk.len() as u64;
+
+        let pushed = match frame_bytes {
+            Some(frame_bytes) => {
+                pending.extend_from_slice(&chunk);
+                let mut pushed = Some(false);
+                while pending.len() >= frame_bytes && pushed.is_some() {
+                    let frame = pending.split_to(frame_bytes).freeze();
+                    pushed = state.stream_coordinator.push_frame(&stream_id, frame);
+                }
+                pushed
+            }
+            None => state.stream_coordinator.push_frame(&stream_id, chunk),
+        };
+        if pushed.is_none() {
+            // Removed elsewhere (e.g. "stop all"): end the upload
+            break;
+        }
+    }
+
+    Ok(api_success(json!({
+        "streamId": stream_id,
+        "bytesReceived": received,
+    })))
+}
+
+/// POST /api/ingest/{id}/metadata
+///
+/// Updates the track metadata shown for an ingest stream.
+pub(super) async fn update_ingest_metadata(
+    State(state): State<AppState>,
+    Path(stream_id): Path<String>,
+    headers: HeaderMap,
+    Json(metadata): Json<StreamMetadata>,
+) -> ThaumicResult<impl IntoResponse> {
+    authorize(&state, &headers)?;
+    if state.stream_coordinator.get_stream(&stream_id).is_none() {
+        return Err(ThaumicError::StreamNotFound(stream_id));
+    }
+    state
+        .stream_coordinator
+        .update_metadata(&stream_id, metadata);
+    Ok(api_ok())
+}
+
+/// GET /api/ingest/mounts
+///
+/// Lists Icecast mount points with a connected source and their streams.
+pub(super) async fn list_ingest_mounts(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> ThaumicResult<impl IntoResponse> {
+    authorize(&state, &headers)?;
+    let mut mounts: Vec<_> = state
+        .ingest
+        .mounts
+        .lock()
+        .iter()
+        .map(|(mount, stream_id)| json!({ "mount": mount, "streamId": stream_id }))
+        .collect();
+    mounts.sort_by(|a, b| a["mount"].as_str().cmp(&b["mount"].as_str()));
+    Ok(api_success(json!({ "mounts": mounts })))
+}
+
+/// GET /api/ingest/rtp.sdp
+///
+/// SDP description of the RTP listener, for senders that take one.
+pub(super) async fn rtp_ingest_sdp(State(state): State<AppState>) -> ThaumicResult<Response> {
+    let rtp = state.config.read().ingest.rtp.clone();
+    let sdp = rtp
+        .sdp(&state.network.get_local_ip())
+        .ok_or_else(|| ThaumicError::Forbidden("RTP ingest is disabled".into()))?;
+    Ok(([(header::CONTENT_TYPE, "application/sdp")], sdp).into_response())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn enabled_ingest_needs_a_key() {
+        assert!(IngestConfig::default().validate().is_ok());
+        let mut config = IngestConfig {
+            enabled: true,
+            api_key: Some("  ".into()),
+            ..Default::default()
+        };
+        assert!(config.validate().is_err());
+        config.api_key = Some("s3cret".into());
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn keys_are_compared_without_surrounding_whitespace() {
+        let mut config = IngestConfig {
+            enabled: true,
+            api_key: Some(" s3cret\n".into()),
+            ..Default::default()
+        };
+        assert_eq!(config.active_key(), Some("s3cret"));
+        config.enabled = false;
+        assert_eq!(config.active_key(), None);
+    }
+}
@@ -3,29 +3,46 @@
 //! This module contains thin handlers that delegate to services.
 //! It provides the router construction and server startup functionality.
 
+use std::future::IntoFuture;
 use std::net::IpAddr;
+use std::path::PathBuf;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
+use std::time::SystemTime;
 
+use axum::serve::ListenerExt;
 use parking_lot::RwLock;
 use thiserror::Error;
 
-use crate::artwork::{ArtworkConfig, ArtworkSource};
+use crate::artwork::{ArtworkConfig, ArtworkLibrary, ArtworkSource, DEFAULT_ARTWORK_NAME};
 use crate::capture::CaptureSourceFactory;
 use crate::context::NetworkContext;
 use crate::events::BroadcastEventBridge;
 use crate::mdns_advertise::MdnsAdvertiser;
-use crate::services::{DiscoveryService, LatencyMonitor, StreamCoordinator};
+use crate::operations::OperationRegistry;
+use crate::outputs::OutputTargetRegistry;
+use crate::services::{DiscoveryService, LatencyMonitor, OriginApprovals, StreamCoordinator};
+use crate::sonos::discovery::RemoteSubnets;
+use crate::sonos::reachability::ReachabilityProbes;
+use crate::sonos::soap::SoapClient;
 use crate::sonos::SonosClient;
 use crate::state::{Config, SonosState};
+use crate::stats::StatsStore;
 
+pub mod cors;
 pub mod http;
+mod icecast;
+pub mod ingest;
 pub mod response;
+mod socket;
 mod stream;
+mod ui;
 pub mod ws;
 pub mod ws_connection;
 
-pub use ws_connection::WsConnectionManager;
+pub use cors::{ExtensionBrowser, OriginPolicy, OriginPolicyBuilder};
+pub use ingest::IngestConfig;
+pub use ws_connection::{ClientInfo, ClientPurpose, ConnectedClient, WsConnectionManager};
 
 /// Errors that can occur when starting or running the server.
 #[derive(Debug, Error)]
@@ -61,12 +78,36 @@ pub struct AppState {
     pub ws_manager: Arc<WsConnectionManager>,
     /// Latency monitoring service.
     pub latency_monitor: Arc<LatencyMonitor>,
+    /// Persistent casting statistics.
+    pub stats: Arc<StatsStore>,
+    /// In-flight long-running operations.
+    pub operations: Arc<OperationRegistry>,
+    /// Pooled SOAP client, for its per-speaker request statistics.
+    pub soap_client: SoapClient,
+    /// Origins allowed to call the API from a browser, including approved
+    /// extensions.
+    pub origin_approvals: Arc<OriginApprovals>,
+    /// Playback backends for non-Sonos devices.
+    pub output_targets: Arc<OutputTargetRegistry>,
+    /// Subnets searched by unicast during discovery.
+    pub remote_subnets: Arc<RemoteSubnets>,
+    /// Streams created through the ingest API that await their upload.
+    pub(crate) ingest: Arc<ingest::IngestSessions>,
+    /// Reachability checks waiting for their speaker to fetch the clip.
+    pub(crate) reachability: Arc<ReachabilityProbes>,
     /// Application configuration.
     pub config: Arc<RwLock<Config>>,
+    /// Configuration file the server was started with, included in backups.
+    /// `None` on the desktop app and for servers configured by environment.
+    pub config_file: Option<PathBuf>,
     /// Whether network services have been started.
     services_started: Arc<AtomicBool>,
     /// Artwork source for Sonos album art display.
     pub artwork: ArtworkSource,
+    /// When the artwork was resolved, served as its `Last-Modified`.
+    pub artwork_loaded_at: SystemTime,
+    /// Artwork uploaded through the API.
+    pub artwork_library: Arc<ArtworkLibrary>,
     /// mDNS advertiser for network discovery (optional, may fail on some systems).
     /// Kept alive for its Drop impl to unregister the service on shutdown.
     /// Created after server binds to get the actual port.
@@ -98,9 +139,20 @@ impl AppState {
             network: services.network.clone(),
             ws_manager: Arc::clone(&services.ws_manager),
             latency_monitor: Arc::clone(&services.latency_monitor),
+            stats: Arc::clone(&services.stats),
+            operations: Arc::clone(&services.operations),
+            soap_client: services.soap_client.clone(),
+            origin_approvals: Arc::clone(&services.origin_approvals),
+            output_targets: Arc::clone(&services.output_targets),
+            remote_subnets: Arc::clone(&services.remote_subnets),
+            ingest: Arc::default(),
+            reachability: Arc::default(),
             config,
+            config_file: None,
             services_started: Arc::new(AtomicBool::new(false)),
             artwork: artwork_config.resolve(),
+            artwork_loaded_at: SystemTime::now(),
+            artwork_library: Arc::clone(&services.artwork_library),
             mdns_advertiser: Arc::new(RwLock::new(None)),
             capture_factory: None,
         }
@@ -108,14 +160,44 @@ impl AppState {
 
     /// Returns the artwork URL to use in Sonos DIDL-Lite metadata.
     ///
-    /// For external URLs, returns that URL directly.
-    /// For local bytes, returns the local `/artwork.jpg` endpoint URL.
+    /// Uploaded artwork named [`DEFAULT_ARTWORK_NAME`] takes precedence.
+    /// Otherwise, for external URLs, returns that URL directly, and for
+    /// local bytes the local `/artwork.jpg` endpoint URL.
     #[must_use]
     pub fn artwork_metadata_url(&self) -> String {
+        if let Some(url) = self.uploaded_artwork_url(DEFAULT_ARTWORK_NAME) {
+            return url;
+        }
         let local_url = self.network.url_builder().artwork_url();
         self.artwork.metadata_url(&local_url)
     }
 
+    /// Returns the artwork URL for a cast of `stream_id`: the uploaded
+    /// artwork named `requested`, else the one assigned to the stream's
+    /// quality preset, else the default.
+    #[must_use]
+    pub fn artwork_url_for(&self, stream_id: &str, requested: Option<&str>) -> String {
+        let preset = || {
+            let stream = self.stream_coordinator.get_stream(stream_id)?;
+            let preset = crate::stats::preset_label(stream.codec, &stream.audio_format);
+            self.artwork_library.name_for_preset(&preset)
+        };
+        requested
+            .and_then(|name| self.uploaded_artwork_url(name))
+            .or_else(|| preset().and_then(|name| self.uploaded_artwork_url(&name)))
+            .unwrap_or_else(|| self.artwork_metadata_url())
+    }
+
+    fn uploaded_artwork_url(&self, name: &str) -> Option<String> {
+        let image = self.artwork_library.get(name)?;
+        let version = format!("{:x}", md5::compute(&image));
+        Some(
+            self.network
+                .url_builder()
+                .uploaded_artwork_url(name, &version[..8]),
+        )
+    }
+
     /// Marks services as started.
     ///
     /// Returns `true` if this was the first call to mark started,
@@ -156,11 +238,35 @@ pub async fn start_server(state: AppState) -> Result<(), ServerError> {
         find_available_port(49400, 49410).await?
     };
 
+    // Bind the dedicated GENA listener, if configured, before announcing the
+    // port: subscriptions start as soon as it is set
+    let gena_listener = match state.network.get_gena_port() {
+        Some(gena_port) => {
+            let addr = std::net::SocketAddr::from(([0, 0, 0, 0], gena_port));
+            let listener = tokio::net::TcpListener::bind(&addr).await?;
+            log::info!("GENA listener on http://0.0.0.0:{}", gena_port);
+            Some(listener)
+        }
+        None => None,
+    };
+
+    // Icecast source clients get their own port: their requests have no
+    // length, so they can't go through the HTTP router
+    let icecast_port = state.config.read().ingest.icecast_port;
+    if let Some(icecast_port) = icecast_port {
+        let addr = std::net::SocketAddr::from(([0, 0, 0, 0], icecast_port));
+        let listener = tokio::net::TcpListener::bind(&addr).await?;
+        log::info!("Icecast source listener on 0.0.0.0:{}", icecast_port);
+        tokio::spawn(icecast::serve_sources(listener, state.clone()));
+    }
+
     // Set port and signal waiters
     state.network.set_port(port);
 
     // Start mDNS advertisement now that we know the actual port (best-effort, non-fatal)
-    if let Ok(ip) = state.network.get_local_ip().parse::<IpAddr>() {
+    if state.config.read().unicast_only {
+        log::info!("[Server] Unicast-only mode, not advertising over mDNS");
+    } else if let Ok(ip) = state.network.get_local_ip().parse::<IpAddr>() {
         match MdnsAdvertiser::new(ip, port) {
             Ok(advertiser) => {
                 *state.mdns_advertiser.write() = Some(advertiser);
@@ -172,13 +278,24 @@ pub async fn start_server(state: AppState) -> Result<(), ServerError> {
     }
 
     log::info!("Server listening on http://0.0.0.0:{}", port);
+    let socket_options = socket::SocketOptions::from(&state.config.read().streaming);
+    let listener = listener.tap_io(move |tcp| socket_options.apply(tcp));
+    let gena_app = gena_listener.map(|gena| (gena, http::create_gena_router(state.clone())));
     let app = http::create_router(state);
 
     // Use into_make_service_with_connect_info to enable ConnectInfo<SocketAddr> extraction
-    axum::serve(
+    let server = axum::serve(
         listener,
         app.into_make_service_with_connect_info::<std::net::SocketAddr>(),
-    )
-    .await?;
+    );
+    match gena_app {
+        Some((gena_listener, gena_app)) => {
+            tokio::try_join!(
+                server.into_future(),
+                axum::serve(gena_listener, gena_app).into_future()
+            )?;
+        }
+        None => server.await?,
+    }
     Ok(())
 }
@@ -1,8 +1,25 @@
 //! HTTP response helper functions for consistent API responses.
+//!
+//! List endpoints share [`ListQuery`] for pagination, filtering and field
+//! selection, and respond through [`api_list`].
+//!
+//! Responses that clients poll (topology, artwork) carry an ETag derived from
+//! their content, and answer `304 Not Modified` when the client already has
+//! that version (see [`conditional_json`]).
 
-use axum::{http::StatusCode, response::IntoResponse, Json};
+use std::collections::HashMap;
+
+use std::time::SystemTime;
+
+use axum::{
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
 use serde::Serialize;
-use serde_json::json;
+use serde_json::{json, Map, Value};
+
+use crate::error::{ApiErrorResponse, ErrorCode, ThaumicError, ThaumicResult};
 
 /// Standard API success response with JSON data.
 pub fn api_success<T: Serialize>(data: T) -> impl IntoResponse {
@@ -14,17 +31,349 @@ pub fn api_ok() -> impl IntoResponse {
     api_success(json!({ "success": true }))
 }
 
-/// Standard API error response with code and message.
-pub fn api_error(
+/// Standard API error response built from a structured error.
+///
+/// Produces the same body as `ThaumicError` responses, including the
+/// remediation `details` when the error provides them.
+pub fn api_error<E: ErrorCode + std::fmt::Display>(
     status: StatusCode,
-    code: &str,
-    message: impl std::fmt::Display,
+    err: &E,
 ) -> impl IntoResponse {
-    (
-        status,
-        Json(json!({
-            "error": code,
-            "message": message.to_string()
-        })),
+    (status, Json(ApiErrorResponse::from_error(status, err)))
+}
+
+/// Largest page a list endpoint returns.
+pub const MAX_PAGE_LIMIT: usize = 500;
+
+/// Pagination, filtering and field selection for list endpoints.
+///
+/// - `limit=N`: at most `N` items per page (1-500, default all)
+/// - `cursor=...`: continue after the page that returned this `nextCursor`
+/// - `fields=a,b`: keep only these top-level fields of each item
+/// - any other `name=value`: keep items whose top-level field `name` equals
+///   `value` (numbers and booleans compare by their JSON text)
+///
+/// Cursors are opaque to clients. They are positions in the (filtered) list,
+/// so items added or removed between requests can shift a page by a few items.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct ListQuery {
+    pub limit: Option<usize>,
+    pub cursor: Option<String>,
+    pub fields: Option<Vec<String>>,
+    pub filters: Vec<(String, String)>,
+}
+
+/// One page of a list.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Page {
+    pub items: Vec<Value>,
+    /// Number of items matching the filters, across all pages.
+    pub total: usize,
+    /// Cursor for the next page, if there is one.
+    pub next_cursor: Option<String>,
+}
+
+impl ListQuery {
+    /// Parses list parameters from a request's query string.
+    ///
+    /// Handlers with their own parameters remove them first, so they aren't
+    /// taken for filters.
+    pub fn from_params(params: HashMap<String, String>) -> ThaumicResult<Self> {
+        let mut query = Self::default();
+        for (name, value) in params {
+            match name.as_str() {
+                "limit" => {
+                    let limit = value
+                        .parse::<usize>()
+                        .ok()
+                        .filter(|limit| (1..=MAX_PAGE_LIMIT).contains(limit))
+                        .ok_or_else(|| {
+                            ThaumicError::InvalidRequest(format!(
+                                "limit must be between 1 and {}",
+                                MAX_PAGE_LIMIT
+                            ))
+                        })?;
+                    query.limit = Some(limit);
+                }
+                "cursor" => query.cursor = Some(value),
+                "fields" => {
+                    query.fields = Some(
+                        value
+                            .split(',')
+                            .map(str::trim)
+                            .filter(|field| !field.is_empty())
+                            .map(String::from)
+                            .collect(),
+                    );
+                }
+                _ => query.filters.push((name, value)),
+            }
+        }
+        // Deterministic order for callers and tests
+        query.filters.sort();
+        Ok(query)
+    }
+
+    /// Filters, pages and projects `items`, which must already be in a
+    /// stable order.
+    pub fn apply<T: Serialize>(&self, items: impl IntoIterator<Item = T>) -> ThaumicResult<Page> {
+        let offset = match &self.cursor {
+            Some(cursor) => decode_cursor(cursor)?,
+            None => 0,
+        };
+
+        let matching: Vec<Value> = items
+            .into_iter()
+            .map(|item| serde_json::to_value(item).unwrap_or(Value::Null))
+            .filter(|item| self.matches(item))
+            .collect();
+        let total = matching.len();
+        let limit = self.limit.unwrap_or(total);
+
+        let items: Vec<Value> = matching
+            .into_iter()
+            .skip(offset)
+            .take(limit)
+            .map(|item| self.project(item))
+            .collect();
+        let end = offset + items.len();
+        let next_cursor = (end < total).then(|| encode_cursor(end));
+
+        Ok(Page {
+            items,
+            total,
+            next_cursor,
+        })
+    }
+
+    fn matches(&self, item: &Value) -> bool {
+        self.filters
+            .iter()
+            .all(|(name, expected)| match item.get(name) {
+                Some(Value::String(value)) => value == expected,
+                Some(value) => serde_json::to_string(value).is_ok_and(|text| text == *expected),
+                None => false,
+            })
+    }
+
+    fn project(&self, item: Value) -> Value {
+        match (&self.fields, item) {
+            (Some(fields), Value::Object(mut object)) => {
+                object.retain(|key, _| fields.iter().any(|field| field == key));
+                Value::Object(object)
+            }
+            (_, item) => item,
+        }
+    }
+}
+
+fn encode_cursor(offset: usize) -> String {
+    format!("o{}", offset)
+}
+
+fn decode_cursor(cursor: &str) -> ThaumicResult<usize> {
+    cursor
+        .strip_prefix('o')
+        .and_then(|offset| offset.parse().ok())
+        .ok_or_else(|| ThaumicError::InvalidRequest("Invalid cursor".into()))
+}
+
+/// List response: the page's items under `key`, the filtered `total`, and
+/// `nextCursor` when more items follow.
+pub fn api_list(key: &str, page: Page) -> impl IntoResponse {
+    api_success(list_body(key, page))
+}
+
+/// Body of a list response, for handlers that respond some other way.
+pub fn list_body(key: &str, page: Page) -> Value {
+    let mut body = Map::new();
+    body.insert(key.to_string(), Value::Array(page.items));
+    body.insert("total".to_string(), json!(page.total));
+    if let Some(cursor) = page.next_cursor {
+        body.insert("nextCursor".to_string(), Value::String(cursor));
+    }
+    Value::Object(body)
+}
+
+/// Strong ETag for a response body.
+pub fn etag_for(body: &[u8]) -> String {
+    format!("\"{:x}\"", md5::compute(body))
+}
+
+/// Whether the request's `If-None-Match` lists `etag` (or is `*`).
+///
+/// Uses the weak comparison GET requires, so `W/"x"` matches `"x"`.
+pub fn etag_matches(headers: &HeaderMap, etag: &str) -> bool {
+    let Some(value) = headers
+        .get(header::IF_NONE_MATCH)
+        .and_then(|value| value.to_str().ok())
+    else {
+        return false;
+    };
+    value.split(',').map(str::trim).any(|candidate| {
+        candidate == "*" || candidate.strip_prefix("W/").unwrap_or(candidate) == etag
+    })
+}
+
+/// Whether the request's `If-Modified-Since` is at or after `modified`.
+///
+/// Only consulted when the request has no `If-None-Match`.
+pub fn not_modified_since(headers: &HeaderMap, modified: SystemTime) -> bool {
+    if headers.contains_key(header::IF_NONE_MATCH) {
+        return false;
+    }
+    headers
+        .get(header::IF_MODIFIED_SINCE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| httpdate::parse_http_date(value).ok())
+        // HTTP dates have second precision
+        .is_some_and(|since| {
+            httpdate::parse_http_date(&httpdate::fmt_http_date(modified))
+                .is_ok_and(|modified| modified <= since)
+        })
+}
+
+/// Empty `304 Not Modified` response carrying `etag`.
+pub fn not_modified(etag: &str) -> Response {
+    let mut response = StatusCode::NOT_MODIFIED.into_response();
+    set_validators(response.headers_mut(), etag);
+    response
+}
+
+/// Responds with `data` as JSON, tagged with an ETag of its content.
+///
+/// Answers `304 Not Modified` without a body when the client's
+/// `If-None-Match` already names this version.
+pub fn conditional_json<T: Serialize>(headers: &HeaderMap, data: &T) -> Response {
+    let body = match serde_json::to_vec(data) {
+        Ok(body) => body,
+        Err(e) => {
+            return ThaumicError::Internal(format!("Failed to serialize response: {}", e))
+                .into_response()
+        }
+    };
+    let etag = etag_for(&body);
+    if etag_matches(headers, &etag) {
+        return not_modified(&etag);
+    }
+
+    let mut response = (
+        [(
+            header::CONTENT_TYPE,
+            HeaderValue::from_static("application/json"),
+        )],
+        body,
     )
+        .into_response();
+    set_validators(response.headers_mut(), &etag);
+    response
+}
+
+/// Sets the ETag, and asks caches to revalidate before reusing the response.
+fn set_validators(headers: &mut HeaderMap, etag: &str) {
+    if let Ok(value) = HeaderValue::from_str(etag) {
+        headers.insert(header::ETAG, value);
+    }
+    headers.insert(header::CACHE_CONTROL, HeaderValue::from_static("no-cache"));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn query(params: &[(&str, &str)]) -> ListQuery {
+        let params = params
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect();
+        ListQuery::from_params(params).unwrap()
+    }
+
+    fn speakers() -> Vec<Value> {
+        (1..=5)
+            .map(|i| json!({ "ip": format!("192.168.1.{}", i), "model": if i % 2 == 0 { "one" } else { "five" }, "online": i != 3 }))
+            .collect()
+    }
+
+    #[test]
+    fn pages_with_cursor() {
+        let first = query(&[("limit", "2")]).apply(speakers()).unwrap();
+        assert_eq!(first.items.len(), 2);
+        assert_eq!(first.total, 5);
+        let cursor = first.next_cursor.unwrap();
+
+        let rest = query(&[("limit", "10"), ("cursor", &cursor)])
+            .apply(speakers())
+            .unwrap();
+        assert_eq!(rest.items.len(), 3);
+        assert_eq!(rest.items[0]["ip"], "192.168.1.3");
+        assert_eq!(rest.next_cursor, None);
+    }
+
+    #[test]
+    fn filters_and_selects_fields() {
+        let page = query(&[("model", "five"), ("online", "true"), ("fields", "ip")])
+            .apply(speakers())
+            .unwrap();
+        assert_eq!(page.total, 2);
+        assert_eq!(
+            page.items,
+            vec![
+                json!({ "ip": "192.168.1.1" }),
+                json!({ "ip": "192.168.1.5" })
+            ]
+        );
+    }
+
+    #[test]
+    fn defaults_to_everything() {
+        let page = ListQuery::default().apply(speakers()).unwrap();
+        assert_eq!(page.items, speakers());
+        assert_eq!(page.next_cursor, None);
+    }
+
+    fn if_none_match(value: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::IF_NONE_MATCH, HeaderValue::from_str(value).unwrap());
+        headers
+    }
+
+    #[test]
+    fn conditional_json_answers_not_modified() {
+        let data = json!({ "groups": [] });
+        let first = conditional_json(&HeaderMap::new(), &data);
+        assert_eq!(first.status(), StatusCode::OK);
+        let etag = first.headers()[header::ETAG].to_str().unwrap().to_string();
+
+        let repeat = conditional_json(&if_none_match(&format!("\"other\", W/{}", etag)), &data);
+        assert_eq!(repeat.status(), StatusCode::NOT_MODIFIED);
+        assert_eq!(repeat.headers()[header::ETAG], etag.as_str());
+
+        let changed = conditional_json(&if_none_match(&etag), &json!({ "groups": [1] }));
+        assert_eq!(changed.status(), StatusCode::OK);
+    }
+
+    #[test]
+    fn if_modified_since_uses_second_precision() {
+        let modified = SystemTime::UNIX_EPOCH + std::time::Duration::from_millis(1_700_000_000_500);
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            header::IF_MODIFIED_SINCE,
+            HeaderValue::from_str(&httpdate::fmt_http_date(modified)).unwrap(),
+        );
+        assert!(not_modified_since(&headers, modified));
+        assert!(!not_modified_since(
+            &headers,
+            modified + std::time::Duration::from_secs(2)
+        ));
+    }
+
+    #[test]
+    fn rejects_bad_limit_and_cursor() {
+        let params =
+            |name: &str, value: &str| HashMap::from([(name.to_string(), value.to_string())]);
+        assert!(ListQuery::from_params(params("limit", "0")).is_err());
+        assert!(ListQuery::from_params(params("limit", "501")).is_err());
+        assert!(query(&[("cursor", "bogus")]).apply(speakers()).is_err());
+    }
 }
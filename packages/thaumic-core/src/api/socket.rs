@@ -0,0 +1,110 @@
+//! Socket tuning for accepted HTTP/WebSocket connections.
+//!
+//! Sonos pulls audio over long-lived connections that can sit quiet for
+//! minutes. The options here (from [`StreamingConfig`]) keep those
+//! connections alive through NAT/firewall idle timers and keep small audio
+//! writes from being delayed by Nagle's algorithm.
+
+use std::time::Duration;
+
+use socket2::{SockRef, TcpKeepalive};
+use tokio::net::TcpStream;
+
+use crate::state::StreamingConfig;
+
+/// Socket options applied to every accepted connection.
+#[derive(Debug, Clone)]
+pub(crate) struct SocketOptions {
+    nodelay: bool,
+    keepalive: Option<TcpKeepalive>,
+    send_buffer_bytes: Option<usize>,
+}
+
+impl From<&StreamingConfig> for SocketOptions {
+    fn from(config: &StreamingConfig) -> Self {
+        let keepalive = config.tcp_keepalive_secs.map(|secs| {
+            TcpKeepalive::new()
+                .with_time(Duration::from_secs(secs))
+                .with_interval(Duration::from_secs(config.tcp_keepalive_interval_secs))
+        });
+        Self {
+            nodelay: config.tcp_nodelay,
+            keepalive,
+            send_buffer_bytes: config.send_buffer_bytes,
+        }
+    }
+}
+
+impl SocketOptions {
+    /// Applies the options to an accepted connection.
+    ///
+    /// Failures are logged and ignored: a connection with default socket
+    /// options still works, it's just less resilient.
+    pub(crate) fn apply(&self, tcp: &TcpStream) {
+        if let Err(e) = tcp.set_nodelay(self.nodelay) {
+            log::debug!("[Server] Failed to set TCP_NODELAY: {}", e);
+        }
+
+        let sock = SockRef::from(tcp);
+        if let Some(keepalive) = &self.keepalive {
+            if let Err(e) = sock.set_tcp_keepalive(keepalive) {
+                log::debug!("[Server] Failed to enable TCP keepalive: {}", e);
+            }
+        }
+        if let Some(size) = self.send_buffer_bytes {
+            if let Err(e) = sock.set_send_buffer_size(size) {
+                log::debug!("[Server] Failed to set send buffer size: {}", e);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::net::TcpListener;
+
+    async fn accepted_connection() -> (TcpStream, TcpStream) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let client = TcpStream::connect(listener.local_addr().unwrap())
+            .await
+            .unwrap();
+        let (server, _) = listener.accept().await.unwrap();
+        (server, client)
+    }
+
+    #[tokio::test]
+    async fn applies_configured_options() {
+        let (server, _client) = accepted_connection().await;
+        let config = StreamingConfig {
+            tcp_keepalive_secs: Some(45),
+            send_buffer_bytes: Some(128 * 1024),
+            ..Default::default()
+        };
+
+        SocketOptions::from(&config).apply(&server);
+
+        let sock = SockRef::from(&server);
+        assert!(server.nodelay().unwrap());
+        assert!(sock.keepalive().unwrap());
+        #[cfg(not(windows))]
+        assert_eq!(sock.tcp_keepalive_time().unwrap(), Duration::from_secs(45));
+        // Kernels may round or double the requested size
+        assert!(sock.send_buffer_size().unwrap() >= 128 * 1024);
+    }
+
+    #[tokio::test]
+    async fn disabled_keepalive_leaves_socket_untouched() {
+        let (server, _client) = accepted_connection().await;
+        let config = StreamingConfig {
+            tcp_nodelay: false,
+            tcp_keepalive_secs: None,
+            ..Default::default()
+        };
+
+        SocketOptions::from(&config).apply(&server);
+
+        assert!(!server.nodelay().unwrap());
+        assert!(!SockRef::from(&server).keepalive().unwrap());
+    }
+}
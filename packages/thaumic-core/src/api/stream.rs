@@ -2,11 +2,29 @@
 //!
 //! Separated from REST handlers due to its distinct concerns:
 //! codec-specific pipeline construction, prefill delays, epoch
-//! tracking, ICY metadata injection, and WAV header generation.
+//! tracking, ICY metadata injection, and WAV/FLAC header generation.
 //!
-//! Runtime context: In the desktop app, this handler (and its cadence metronome)
-//! runs on the dedicated `StreamingRuntime` high-priority threads — inherited
-//! via `streaming_runtime.spawn()` in the Tauri API layer.
+//! Sonos probes a stream URL (HEAD, or a throwaway GET) before the real
+//! playback request. HEAD and `/stream/{id}/probe` answer with the same
+//! headers as the live stream but no body, without prefill delay or
+//! subscribing, so probing doesn't hold up playback start.
+//!
+//! HTTP/ICY semantics:
+//! - `Range` is ignored (RFC 9110 §14.2): a live stream can't seek, so every
+//!   GET gets `200` with the stream from "now" and `Accept-Ranges: none`.
+//!   Rejecting with `416` makes some Sonos models give up on resume.
+//! - `Icy-MetaData: <n>` with `n > 0` enables in-band metadata (MP3/AAC
+//!   only); `0`, garbage, or absence gets a plain stream.
+//! - HTTP/1.0 clients (Shoutcast-era players) get no `Connection:
+//!   keep-alive`, since compressed streams are close-delimited.
+//!
+//! FLAC streams are also served as Ogg FLAC at `live.ogg` for players and
+//! renderers that expect container framing.
+//!
+//! Runtime context: In the desktop app, this handler runs on the dedicated
+//! `StreamingRuntime` high-priority threads — inherited via
+//! `streaming_runtime.spawn()` in the Tauri API layer. The PCM cadence
+//! metronome runs on its own per-connection OS thread (`spawn_cadence_thread`).
 
 use std::net::SocketAddr;
 use std::pin::Pin;
@@ -16,7 +34,7 @@ use std::time::{Duration, Instant};
 use axum::{
     body::Body,
     extract::{connect_info::ConnectInfo, Path, State},
-    http::{header, HeaderMap},
+    http::{header, response::Builder, HeaderMap, Method, Uri, Version},
     response::Response,
 };
 use bytes::Bytes;
@@ -26,18 +44,108 @@ use tokio_stream::wrappers::BroadcastStream;
 
 use crate::api::AppState;
 use crate::error::{ThaumicError, ThaumicResult};
+use crate::events::{EventEmitter, StreamEvent};
 use crate::protocol_constants::{
     APP_NAME, ICY_METAINT, MAX_CADENCE_QUEUE_SIZE, WAV_STREAM_SIZE_MAX,
 };
 use crate::stream::{
-    create_wav_header, create_wav_stream_with_cadence, lagged_error, AudioCodec, CadenceConfig,
-    IcyMetadataInjector, LoggingStreamGuard,
+    create_wav_header, flac, lagged_error, spawn_cadence_thread, AudioCodec, CadenceConfig,
+    Coalesce, IcyMetadataInjector, LoggingStreamGuard, OggFlacMuxer, QueueAdaptation, StartupStage,
+    DEFAULT_COALESCE_MAX_BYTES, OGG_MIME_TYPE,
 };
+use crate::utils::now_millis;
 
 /// Boxed stream type for audio data.
 type AudioStream = Pin<Box<dyn Stream<Item = Result<Bytes, std::io::Error>> + Send>>;
 
+/// Returns whether the client asked for ICY metadata and the codec supports it.
+///
+/// ICY metadata is only supported for MP3/AAC streams (not PCM/FLAC).
+/// Clients send `Icy-MetaData: 1`; any positive integer is accepted.
+fn wants_icy(codec: AudioCodec, headers: &HeaderMap) -> bool {
+    let supports_icy = matches!(codec, AudioCodec::Mp3 | AudioCodec::Aac);
+    let requested = headers
+        .get("icy-metadata")
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.trim().parse::<u32>().ok())
+        .is_some_and(|n| n > 0);
+    supports_icy && requested
+}
+
+/// Builds the response headers shared by the live stream, HEAD, and probe.
+fn stream_response(content_type: &'static str, wants_icy: bool, version: Version) -> Builder {
+    let mut builder = Response::builder()
+        .header(header::CONTENT_TYPE, content_type)
+        .header(header::CACHE_CONTROL, "no-cache")
+        // Live audio can't be seeked; discourages range probing
+        .header(header::ACCEPT_RANGES, "none")
+        // DLNA streaming header: indicates real-time playback vs download-first
+        .header("TransferMode.dlna.org", "Streaming")
+        // Stream identification for renderers that display station name
+        .header("icy-name", APP_NAME);
+
+    // HTTP/1.0 has no chunked encoding, so a stream without Content-Length
+    // ends on close; advertising keep-alive there would be a lie.
+    if version >= Version::HTTP_11 {
+        builder = builder.header(header::CONNECTION, "keep-alive");
+    }
+
+    if wants_icy {
+        builder = builder
+            .header("icy-metaint", ICY_METAINT.to_string())
+            // Private LAN stream: don't list in Shoutcast directories
+            .header("icy-pub", "0");
+    }
+    builder
+}
+
+/// Lightweight probe for a stream: headers only, no subscription.
+///
+/// Lets renderers (and the extension) check a stream URL without paying the
+/// prefill delay or opening a cadence pipeline.
+pub(super) async fn stream_probe(
+    version: Version,
+    Path(id): Path<String>,
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> ThaumicResult<Response> {
+    let stream_state = state
+        .stream_coordinator
+        .get_stream(&id)
+        .ok_or_else(|| ThaumicError::StreamNotFound(id.clone()))?;
+
+    let wants_icy = wants_icy(stream_state.codec, &headers);
+    stream_response(stream_state.codec.mime_type(), wants_icy, version)
+        .header(header::CONTENT_LENGTH, "0")
+        .body(Body::empty())
+        .map_err(|e| ThaumicError::Internal(e.to_string()))
+}
+
+/// Cadence queue size for a streaming buffer: `ceil(buffer_ms / frame_ms)`,
+/// clamped to `[1, MAX_CADENCE_QUEUE_SIZE]`.
+fn cadence_queue_size(streaming_buffer_ms: u64, frame_duration_ms: u32) -> usize {
+    let queue_size = streaming_buffer_ms.div_ceil(frame_duration_ms as u64) as usize;
+    queue_size.clamp(1, MAX_CADENCE_QUEUE_SIZE)
+}
+
+/// Milliseconds of prefill still missing once `buffered_frames` are already
+/// in the ring buffer.
+///
+/// Frames keep arriving while the SOAP calls run, so by the time the speaker
+/// connects part (often all) of the prefill is already buffered.
+fn prefill_remaining_ms(
+    streaming_buffer_ms: u64,
+    buffered_frames: usize,
+    frame_duration_ms: u32,
+) -> u64 {
+    let buffered_ms = (buffered_frames as u64).saturating_mul(frame_duration_ms as u64);
+    streaming_buffer_ms.saturating_sub(buffered_ms)
+}
+
 pub(super) async fn stream_audio(
+    method: Method,
+    version: Version,
+    uri: Uri,
     Path(id): Path<String>,
     State(state): State<AppState>,
     ConnectInfo(remote_addr): ConnectInfo<SocketAddr>,
@@ -50,14 +158,51 @@ pub(super) async fn stream_audio(
 
     let remote_ip = remote_addr.ip();
 
+    // Ogg container output is only defined for FLAC
+    let ogg = uri.path().ends_with(".ogg");
+    if ogg && stream_state.codec != AudioCodec::Flac {
+        return Err(ThaumicError::InvalidRequest(format!(
+            "Ogg output requires a FLAC stream, not {}",
+            stream_state.codec.as_str()
+        )));
+    }
+    let content_type = if ogg {
+        OGG_MIME_TYPE
+    } else {
+        stream_state.codec.mime_type()
+    };
+
+    let wants_icy = wants_icy(stream_state.codec, &headers);
+    let mut builder = stream_response(content_type, wants_icy, version);
+
+    // PCM: Use fixed Content-Length to avoid chunked transfer encoding.
+    // Some renderers (including Sonos) stutter or disconnect with chunked encoding.
+    // The stream will end before reaching this length, but it signals "file-like"
+    // behavior to the renderer.
+    if stream_state.codec == AudioCodec::Pcm {
+        builder = builder.header(header::CONTENT_LENGTH, WAV_STREAM_SIZE_MAX.to_string());
+    }
+
+    // HEAD: answer immediately with the live headers. Must not count as a
+    // connection (no prefill delay, resume detection, or epoch).
+    if method == Method::HEAD {
+        log::debug!("[Stream] HEAD probe: client={}, stream={}", remote_ip, id);
+        return builder
+            .body(Body::empty())
+            .map_err(|e| ThaumicError::Internal(e.to_string()));
+    }
+
+    stream_state.record_startup(remote_ip, StartupStage::FirstRequest);
+
     let range_header = headers
         .get(header::RANGE)
         .and_then(|v| v.to_str().ok())
         .map(|s| s.to_string());
 
+    // Range is deliberately ignored: the response is 200 with the live stream
     if let Some(ref range) = range_header {
         log::debug!(
-            "[Stream] Range request: client={}, stream={}, codec={:?}, range='{}'",
+            "[Stream] Range request (serving live stream): client={}, stream={}, codec={:?}, range='{}'",
             remote_ip,
             id,
             stream_state.codec,
@@ -80,15 +225,21 @@ pub(super) async fn stream_audio(
     // Upfront buffering delay for PCM streams BEFORE subscribing.
     // This lets the ring buffer accumulate more frames. Subscribing after
     // ensures the broadcast receiver doesn't fill up during the delay.
-    // Delay matches streaming_buffer_ms so cadence queue starts full.
+    // The delay only tops the buffer up to streaming_buffer_ms so the cadence
+    // queue starts full: audio buffered during the SOAP calls counts towards it.
     //
     // SKIP on resume: Sonos closes the connection within milliseconds if we delay.
     // The buffer already has frames from before the pause, so no delay is needed.
-    let prefill_delay_ms = stream_state.streaming_buffer_ms;
+    let prefill_delay_ms = prefill_remaining_ms(
+        stream_state.streaming_buffer_ms,
+        stream_state.buffer_len(),
+        stream_state.frame_duration_ms,
+    );
     if stream_state.codec == AudioCodec::Pcm && prefill_delay_ms > 0 && !is_resume {
         log::debug!(
-            "[Stream] Applying {}ms prefill delay for PCM stream",
-            prefill_delay_ms
+            "[Stream] Applying {}ms prefill delay for PCM stream ({}ms already buffered)",
+            prefill_delay_ms,
+            stream_state.streaming_buffer_ms - prefill_delay_ms
         );
         tokio::time::sleep(Duration::from_millis(prefill_delay_ms)).await;
     } else if is_resume && stream_state.codec == AudioCodec::Pcm {
@@ -111,8 +262,20 @@ pub(super) async fn stream_audio(
     // reflect actual transport latency, not intentional buffering.
     let connected_at = Instant::now();
 
+    // PCM prefill seeds the cadence queue: anything beyond its size would be
+    // dropped once live frames arrive, after the oldest frame already played
+    let queue_size = cadence_queue_size(
+        stream_state.streaming_buffer_ms,
+        stream_state.frame_duration_ms,
+    );
+    let max_prefill = if stream_state.codec == AudioCodec::Pcm {
+        queue_size
+    } else {
+        usize::MAX
+    };
+
     // Subscribe AFTER delay to get fresh prefill snapshot and avoid rx backlog
-    let (epoch_candidate, prefill_frames, rx) = stream_state.subscribe();
+    let (epoch_candidate, prefill_frames, rx) = stream_state.subscribe(max_prefill);
 
     log::debug!(
         "[Stream] Client {} connected to stream {}, sending {} prefill frames",
@@ -140,22 +303,37 @@ pub(super) async fn stream_audio(
         let frame_duration_ms = stream_state.frame_duration_ms;
         let silence_frame = stream_state.audio_format.silence_frame(frame_duration_ms);
 
-        // Calculate queue size from streaming buffer (ceil division)
-        // queue_size = ceil(buffer_ms / frame_ms), clamped to [1, MAX_CADENCE_QUEUE_SIZE]
-        let queue_size = stream_state
-            .streaming_buffer_ms
-            .div_ceil(frame_duration_ms as u64) as usize;
-        let queue_size = queue_size.clamp(1, MAX_CADENCE_QUEUE_SIZE);
+        // Under ingest jitter the queue may grow up to the configured maximum
+        let max_jitter_buffer_ms = state.config.read().streaming.max_jitter_buffer_ms;
+        let max_queue_size =
+            cadence_queue_size(max_jitter_buffer_ms, frame_duration_ms).max(queue_size);
+
+        let emitter = Arc::clone(&state.event_bridge);
+        let stream_id = id.clone();
+        let speaker_ip = remote_ip.to_string();
+        let frame_ms = frame_duration_ms as u64;
+        let on_adapt = Box::new(move |adaptation: QueueAdaptation| {
+            emitter.emit_stream(StreamEvent::BufferAdapted {
+                stream_id: stream_id.clone(),
+                speaker_ip: speaker_ip.clone(),
+                previous_buffer_ms: adaptation.previous_size as u64 * frame_ms,
+                buffer_ms: adaptation.queue_size as u64 * frame_ms,
+                jitter_ms: adaptation.jitter_ms.round() as u64,
+                timestamp: now_millis(),
+            });
+        });
 
-        Box::pin(create_wav_stream_with_cadence(
+        let cadence = spawn_cadence_thread(
             rx,
             Arc::clone(&guard),
             CadenceConfig {
                 silence_frame,
                 queue_size,
+                max_queue_size,
                 frame_duration_ms,
                 audio_format: stream_state.audio_format,
                 prefill_frames,
+                on_adapt: Some(on_adapt),
             },
             Some((
                 Arc::clone(&stream_state),
@@ -163,9 +341,21 @@ pub(super) async fn stream_audio(
                 connected_at,
                 remote_ip,
             )),
-        ))
+        )
+        .map_err(|e| ThaumicError::Internal(format!("Failed to start cadence thread: {}", e)))?;
+        Box::pin(cadence)
     } else {
-        // Compressed codecs: no silence injection, chain prefill before live
+        // Compressed codecs: no silence injection, chain prefill before live.
+        // FLAC gets its stream header prepended below, so the first audio
+        // must start on a frame boundary for the decoder to lock on at once.
+        let prefill_frames: Vec<Bytes> = if stream_state.codec == AudioCodec::Flac {
+            prefill_frames
+                .into_iter()
+                .skip_while(|frame| !flac::is_frame_start(frame))
+                .collect()
+        } else {
+            prefill_frames
+        };
         let prefill_stream = futures::stream::iter(prefill_frames.into_iter().map(Ok));
         let live_stream = BroadcastStream::new(rx).map(|res| match res {
             Ok(frame) => Ok(frame),
@@ -191,6 +381,7 @@ pub(super) async fn stream_audio(
                                 connected_at,
                                 remote_ip,
                             );
+                            stream_state.record_startup(remote_ip, StartupStage::FirstAudio);
                         } else {
                             *hook = Some((stream_state, epoch_candidate, connected_at, remote_ip));
                         }
@@ -203,35 +394,6 @@ pub(super) async fn stream_audio(
         )
     };
 
-    // Content-Type based on output codec
-    let content_type = stream_state.codec.mime_type();
-
-    // ICY metadata only supported for MP3/AAC streams (not PCM/FLAC)
-    let supports_icy = matches!(stream_state.codec, AudioCodec::Mp3 | AudioCodec::Aac);
-    let wants_icy =
-        supports_icy && headers.get("icy-metadata").and_then(|v| v.to_str().ok()) == Some("1");
-
-    let mut builder = Response::builder()
-        .header(header::CONTENT_TYPE, content_type)
-        .header(header::CACHE_CONTROL, "no-cache")
-        .header(header::CONNECTION, "keep-alive")
-        // DLNA streaming header: indicates real-time playback vs download-first
-        .header("TransferMode.dlna.org", "Streaming")
-        // Stream identification for renderers that display station name
-        .header("icy-name", APP_NAME);
-
-    if wants_icy {
-        builder = builder.header("icy-metaint", ICY_METAINT.to_string());
-    }
-
-    // PCM: Use fixed Content-Length to avoid chunked transfer encoding.
-    // Some renderers (including Sonos) stutter or disconnect with chunked encoding.
-    // The stream will end before reaching this length, but it signals "file-like"
-    // behavior to the renderer.
-    if stream_state.codec == AudioCodec::Pcm {
-        builder = builder.header(header::CONTENT_LENGTH, WAV_STREAM_SIZE_MAX.to_string());
-    }
-
     // Apply ICY injection or PCM/WAV header
     let inner_stream: AudioStream = if wants_icy {
         let stream_ref = Arc::clone(&stream_state);
@@ -254,6 +416,28 @@ pub(super) async fn stream_audio(
             futures::stream::once(async move { Ok(wav_header) }),
             combined_stream,
         ))
+    } else if ogg {
+        // Ogg FLAC: header pages, then one page per frame. Serial only needs
+        // to be unique per connection.
+        let mut muxer = OggFlacMuxer::new(uuid::Uuid::new_v4().as_u128() as u32);
+        let header_pages = muxer.header_pages(&stream_state.flac_stream_header());
+        let pages = combined_stream.filter_map(move |res| {
+            futures::future::ready(match res {
+                Ok(frame) => muxer.frame_pages(&frame).map(Ok),
+                Err(e) => Some(Err(e)),
+            })
+        });
+        Box::pin(futures::StreamExt::chain(
+            futures::stream::once(async move { Ok(header_pages) }),
+            pages,
+        ))
+    } else if stream_state.codec == AudioCodec::Flac {
+        // FLAC streams need the stream header per-connection, like WAV
+        let flac_header = stream_state.flac_stream_header();
+        Box::pin(futures::StreamExt::chain(
+            futures::stream::once(async move { Ok(flac_header) }),
+            combined_stream,
+        ))
     } else {
         Box::pin(combined_stream)
     };
@@ -261,16 +445,400 @@ pub(super) async fn stream_audio(
     // Wrap stream with logging guard to track delivery timing and errors.
     // The guard logs summary stats on drop when the stream ends.
     let guard_for_frames = Arc::clone(&guard);
+    let tracked_stream = inner_stream.map(move |res: Result<Bytes, std::io::Error>| {
+        match &res {
+            Ok(_) => guard_for_frames.record_frame(),
+            Err(e) => guard_for_frames.record_error(&e.to_string()),
+        }
+        res
+    });
+
+    // Merge frames that are ready together (prefill, catch-up bursts) into
+    // larger writes. Steady-state frames still go out one per tick.
     let final_stream: AudioStream =
-        Box::pin(inner_stream.map(move |res: Result<Bytes, std::io::Error>| {
-            match &res {
-                Ok(_) => guard_for_frames.record_frame(),
-                Err(e) => guard_for_frames.record_error(&e.to_string()),
-            }
-            res
-        }));
+        Box::pin(Coalesce::new(tracked_stream, DEFAULT_COALESCE_MAX_BYTES));
 
     builder
         .body(Body::from_stream(final_stream))
         .map_err(|e| ThaumicError::Internal(e.to_string()))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::context::NetworkContext;
+    use crate::state::Config;
+    use crate::stream::AudioFormat;
+    use crate::test_support::bootstrap_app;
+    use crate::BootstrappedServices;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::{TcpListener, TcpStream};
+
+    fn icy_request(value: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert("icy-metadata", value.parse().unwrap());
+        headers
+    }
+
+    #[test]
+    fn icy_only_for_mp3_and_aac() {
+        let headers = icy_request("1");
+        assert!(wants_icy(AudioCodec::Aac, &headers));
+        assert!(wants_icy(AudioCodec::Mp3, &headers));
+        assert!(!wants_icy(AudioCodec::Pcm, &headers));
+        assert!(!wants_icy(AudioCodec::Flac, &headers));
+        assert!(!wants_icy(AudioCodec::Aac, &HeaderMap::new()));
+    }
+
+    #[test]
+    fn icy_metadata_value_parsing() {
+        assert!(!wants_icy(AudioCodec::Aac, &icy_request("0")));
+        assert!(!wants_icy(AudioCodec::Aac, &icy_request("yes")));
+        assert!(wants_icy(AudioCodec::Aac, &icy_request(" 1 ")));
+        assert!(wants_icy(AudioCodec::Aac, &icy_request("2")));
+    }
+
+    #[test]
+    fn prefill_delay_only_covers_audio_not_yet_buffered() {
+        assert_eq!(prefill_remaining_ms(200, 0, 10), 200);
+        assert_eq!(prefill_remaining_ms(200, 15, 10), 50);
+        assert_eq!(prefill_remaining_ms(200, 20, 10), 0);
+        assert_eq!(prefill_remaining_ms(200, 500, 10), 0);
+        assert_eq!(prefill_remaining_ms(0, 0, 10), 0);
+    }
+
+    #[test]
+    fn response_headers_describe_live_stream() {
+        let response = stream_response(AudioCodec::Aac.mime_type(), true, Version::HTTP_11)
+            .body(Body::empty())
+            .unwrap();
+        let headers = response.headers();
+        assert_eq!(headers[header::CONTENT_TYPE], AudioCodec::Aac.mime_type());
+        assert_eq!(headers[header::ACCEPT_RANGES], "none");
+        assert_eq!(headers[header::CONNECTION], "keep-alive");
+        assert_eq!(headers["icy-name"], APP_NAME);
+        assert_eq!(headers["icy-metaint"], ICY_METAINT.to_string());
+        assert_eq!(headers["icy-pub"], "0");
+
+        let response = stream_response(AudioCodec::Pcm.mime_type(), false, Version::HTTP_10)
+            .body(Body::empty())
+            .unwrap();
+        assert!(!response.headers().contains_key("icy-metaint"));
+        assert!(!response.headers().contains_key(header::CONNECTION));
+    }
+
+    // ─────────────────────────────────────────────────────────────────────
+    // Loopback tests replaying request shapes captured from Sonos players
+    // ─────────────────────────────────────────────────────────────────────
+
+    const SONOS_UA: &str = "Linux UPnP/1.0 Sonos/80.1-55240 (ZPS1)";
+
+    /// Serves the real router on loopback; returns the address and services.
+    async fn serve() -> (SocketAddr, &'static BootstrappedServices) {
+        serve_with(Config::default()).await
+    }
+
+    async fn serve_with(config: Config) -> (SocketAddr, &'static BootstrappedServices) {
+        let (services, state) = bootstrap_app(config, NetworkContext::for_test());
+        let app = crate::api::http::create_router(state);
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(
+                listener,
+                app.into_make_service_with_connect_info::<SocketAddr>(),
+            )
+            .await
+            .unwrap();
+        });
+        (addr, services)
+    }
+
+    fn create_stream(services: &BootstrappedServices, codec: AudioCodec) -> String {
+        services
+            .stream_coordinator
+            .create_stream(codec, AudioFormat::default(), 100, 20)
+            .unwrap()
+    }
+
+    /// Sends a raw request and returns the status line, lowercased headers,
+    /// and whatever body bytes arrived with them.
+    async fn request(addr: SocketAddr, raw: String) -> (String, HeaderMap, Vec<u8>) {
+        let mut conn = TcpStream::connect(addr).await.unwrap();
+        conn.write_all(raw.as_bytes()).await.unwrap();
+
+        let mut buf = Vec::new();
+        let mut chunk = [0u8; 4096];
+        let head_end = loop {
+            let n = tokio::time::timeout(Duration::from_secs(5), conn.read(&mut chunk))
+                .await
+                .expect("response timed out")
+                .unwrap();
+            assert!(n > 0, "connection closed before headers");
+            buf.extend_from_slice(&chunk[..n]);
+            if let Some(pos) = buf.windows(4).position(|w| w == b"\r\n\r\n") {
+                break pos + 4;
+            }
+        };
+
+        let head = String::from_utf8_lossy(&buf[..head_end]).into_owned();
+        let mut lines = head.split("\r\n");
+        let status = lines.next().unwrap().to_string();
+        let mut headers = HeaderMap::new();
+        for line in lines.filter(|l| !l.is_empty()) {
+            let (name, value) = line.split_once(':').unwrap();
+            headers.append(
+                header::HeaderName::from_bytes(name.trim().as_bytes()).unwrap(),
+                value.trim().parse().unwrap(),
+            );
+        }
+        (status, headers, buf[head_end..].to_vec())
+    }
+
+    /// Sends a request on a `Connection: close` connection and returns the
+    /// status line and the full body.
+    async fn request_to_end(addr: SocketAddr, raw: String) -> (String, Vec<u8>) {
+        let mut conn = TcpStream::connect(addr).await.unwrap();
+        conn.write_all(raw.as_bytes()).await.unwrap();
+        let mut buf = Vec::new();
+        tokio::time::timeout(Duration::from_secs(5), conn.read_to_end(&mut buf))
+            .await
+            .expect("response timed out")
+            .unwrap();
+        let head_end = buf.windows(4).position(|w| w == b"\r\n\r\n").unwrap() + 4;
+        let head = String::from_utf8_lossy(&buf[..head_end]);
+        let status = head.split("\r\n").next().unwrap().to_string();
+        (status, buf[head_end..].to_vec())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn ingest_upload_feeds_and_ends_the_stream() {
+        let config = Config {
+            ingest: crate::api::IngestConfig {
+                enabled: true,
+                api_key: Some("s3cret".into()),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let (addr, services) = serve_with(config).await;
+        let create = |key: &str| {
+            let body = r#"{"codec":"pcm","sampleRate":48000,"channels":2,"bitsPerSample":16}"#;
+            format!(
+                "POST /api/ingest HTTP/1.1\r\nHost: {addr}\r\nAuthorization: Bearer {key}\r\n\
+                 Content-Type: application/json\r\nContent-Length: {}\r\n\
+                 Connection: close\r\n\r\n{body}",
+                body.len()
+            )
+        };
+
+        let (status, _) = request_to_end(addr, create("wrong")).await;
+        assert_eq!(status, "HTTP/1.1 401 Unauthorized");
+
+        let (status, body) = request_to_end(addr, create("s3cret")).await;
+        assert_eq!(status, "HTTP/1.1 200 OK");
+        let created: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        let id = created["streamId"].as_str().unwrap().to_string();
+        assert_eq!(created["uploadUrl"], format!("/api/ingest/{id}"));
+        assert!(services.stream_coordinator.get_stream(&id).is_some());
+
+        // Two chunks that don't line up with frame boundaries
+        let chunk = "\0".repeat(2500);
+        let (status, body) = request_to_end(
+            addr,
+            format!(
+                "PUT /api/ingest/{id} HTTP/1.1\r\nHost: {addr}\r\nAuthorization: Bearer s3cret\r\n\
+                 Transfer-Encoding: chunked\r\nConnection: close\r\n\r\n\
+                 9c4\r\n{0}\r\n9c4\r\n{0}\r\n0\r\n\r\n",
+                chunk
+            ),
+        )
+        .await;
+        assert_eq!(status, "HTTP/1.1 200 OK");
+        let uploaded: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(uploaded["bytesReceived"], 5000);
+        assert!(services.stream_coordinator.get_stream(&id).is_none());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn sonos_head_gets_live_headers_without_body() {
+        let (addr, services) = serve().await;
+        let id = create_stream(services, AudioCodec::Pcm);
+
+        let (status, headers, body) = request(
+            addr,
+            format!(
+                "HEAD /stream/{id}/live.wav HTTP/1.1\r\nHost: {addr}\r\nUser-Agent: {SONOS_UA}\r\n\
+                 Icy-MetaData: 1\r\nConnection: close\r\n\r\n"
+            ),
+        )
+        .await;
+
+        assert_eq!(status, "HTTP/1.1 200 OK");
+        assert_eq!(headers[header::CONTENT_TYPE], AudioCodec::Pcm.mime_type());
+        assert_eq!(
+            headers[header::CONTENT_LENGTH],
+            WAV_STREAM_SIZE_MAX.to_string()
+        );
+        assert!(!headers.contains_key("icy-metaint"), "no ICY for PCM");
+        assert!(body.is_empty());
+        // HEAD must not register as a listener
+        let stream = services.stream_coordinator.get_stream(&id).unwrap();
+        assert!(stream.timing.current_epoch_for(addr.ip()).is_none());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn sonos_range_get_gets_full_stream() {
+        let (addr, services) = serve().await;
+        let id = create_stream(services, AudioCodec::Pcm);
+
+        let (status, headers, body) = request(
+            addr,
+            format!(
+                "GET /stream/{id}/live.wav HTTP/1.1\r\nHost: {addr}\r\nUser-Agent: {SONOS_UA}\r\n\
+                 Icy-MetaData: 1\r\nRange: bytes=0-\r\nConnection: close\r\n\r\n"
+            ),
+        )
+        .await;
+
+        assert_eq!(status, "HTTP/1.1 200 OK");
+        assert!(!headers.contains_key(header::CONTENT_RANGE));
+        assert_eq!(headers[header::ACCEPT_RANGES], "none");
+        assert!(body.is_empty() || body.starts_with(b"RIFF"));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn mid_stream_range_is_not_rejected() {
+        let (addr, services) = serve().await;
+        let id = create_stream(services, AudioCodec::Aac);
+
+        let (status, headers, _) = request(
+            addr,
+            format!(
+                "GET /stream/{id}/live HTTP/1.1\r\nHost: {addr}\r\nUser-Agent: {SONOS_UA}\r\n\
+                 Range: bytes=81920-\r\n\r\n"
+            ),
+        )
+        .await;
+
+        assert_eq!(status, "HTTP/1.1 200 OK");
+        assert!(!headers.contains_key(header::CONTENT_RANGE));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn icy_metadata_zero_gets_plain_stream() {
+        let (addr, services) = serve().await;
+        let id = create_stream(services, AudioCodec::Aac);
+
+        let (_, with_icy, _) = request(
+            addr,
+            format!("GET /stream/{id}/live HTTP/1.1\r\nHost: {addr}\r\nIcy-MetaData: 1\r\n\r\n"),
+        )
+        .await;
+        assert_eq!(with_icy["icy-metaint"], ICY_METAINT.to_string());
+
+        let (_, without_icy, _) = request(
+            addr,
+            format!("GET /stream/{id}/live HTTP/1.1\r\nHost: {addr}\r\nIcy-MetaData: 0\r\n\r\n"),
+        )
+        .await;
+        assert!(!without_icy.contains_key("icy-metaint"));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn http10_shoutcast_client_gets_close_delimited_stream() {
+        let (addr, services) = serve().await;
+        let id = create_stream(services, AudioCodec::Mp3);
+
+        let (status, headers, _) = request(
+            addr,
+            format!("GET /stream/{id}/live HTTP/1.0\r\nIcy-MetaData: 1\r\nUser-Agent: WinampMPEG/5.66\r\n\r\n"),
+        )
+        .await;
+
+        assert!(status.starts_with("HTTP/1.0 200"), "{status}");
+        assert!(headers
+            .get(header::CONNECTION)
+            .is_none_or(|v| v != "keep-alive"));
+        assert!(!headers.contains_key(header::TRANSFER_ENCODING));
+        assert_eq!(headers["icy-metaint"], ICY_METAINT.to_string());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn only_api_responses_are_compressed() {
+        let (addr, services) = serve().await;
+        let id = create_stream(services, AudioCodec::Flac);
+
+        let (status, headers, _) = request(
+            addr,
+            format!(
+                "GET /api/state HTTP/1.1\r\nHost: {addr}\r\nAccept-Encoding: gzip\r\n\
+                 Connection: close\r\n\r\n"
+            ),
+        )
+        .await;
+        assert_eq!(status, "HTTP/1.1 200 OK");
+        assert_eq!(headers[header::CONTENT_ENCODING], "gzip");
+
+        let (status, headers, _) = request(
+            addr,
+            format!(
+                "GET /stream/{id}/live.flac HTTP/1.1\r\nHost: {addr}\r\n\
+                 Accept-Encoding: gzip, deflate\r\nConnection: close\r\n\r\n"
+            ),
+        )
+        .await;
+        assert_eq!(status, "HTTP/1.1 200 OK");
+        assert!(!headers.contains_key(header::CONTENT_ENCODING));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn ogg_output_only_for_flac_streams() {
+        let (addr, services) = serve().await;
+        let flac_id = create_stream(services, AudioCodec::Flac);
+        let aac_id = create_stream(services, AudioCodec::Aac);
+
+        let (status, headers, body) = request(
+            addr,
+            format!("GET /stream/{flac_id}/live.ogg HTTP/1.1\r\nHost: {addr}\r\n\r\n"),
+        )
+        .await;
+        assert_eq!(status, "HTTP/1.1 200 OK");
+        assert_eq!(headers[header::CONTENT_TYPE], OGG_MIME_TYPE);
+        // Chunked body: the first chunk carries the Ogg header pages
+        assert!(body.is_empty() || body.windows(4).any(|w| w == b"OggS"));
+
+        let (status, _, _) = request(
+            addr,
+            format!("GET /stream/{aac_id}/live.ogg HTTP/1.1\r\nHost: {addr}\r\n\r\n"),
+        )
+        .await;
+        assert!(status.starts_with("HTTP/1.1 400"), "{status}");
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn probe_returns_headers_for_known_stream_only() {
+        let (addr, services) = serve().await;
+        let id = create_stream(services, AudioCodec::Flac);
+
+        let (status, headers, body) = request(
+            addr,
+            format!("GET /stream/{id}/probe HTTP/1.1\r\nHost: {addr}\r\nConnection: close\r\n\r\n"),
+        )
+        .await;
+        assert_eq!(status, "HTTP/1.1 200 OK");
+        assert_eq!(headers[header::CONTENT_TYPE], AudioCodec::Flac.mime_type());
+        assert_eq!(headers[header::CONTENT_LENGTH], "0");
+        assert!(body.is_empty());
+
+        let (status, _, _) = request(
+            addr,
+            format!(
+                "GET /stream/missing/probe HTTP/1.1\r\nHost: {addr}\r\nConnection: close\r\n\r\n"
+            ),
+        )
+        .await;
+        assert!(status.starts_with("HTTP/1.1 404"), "{status}");
+    }
+}
@@ -0,0 +1,32 @@
+//! Remote control web UI.
+//!
+//! A single static page embedded in the binary, for installs without the
+//! desktop app. It polls `/api/state` for groups, volumes and active casts,
+//! and controls playback and volume through the regular API, so any phone
+//! browser on the LAN can drive the server. Served at `/ui` when
+//! [`Config::web_ui`](crate::Config::web_ui) is set.
+
+use axum::{
+    extract::State,
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+};
+
+use crate::api::AppState;
+
+const INDEX_HTML: &str = include_str!("ui/index.html");
+
+/// GET /ui
+pub(super) async fn serve_ui(State(state): State<AppState>) -> Response {
+    if !state.config.read().web_ui {
+        return StatusCode::NOT_FOUND.into_response();
+    }
+    (
+        [
+            (header::CONTENT_TYPE, "text/html; charset=utf-8"),
+            (header::CACHE_CONTROL, "no-cache"),
+        ],
+        INDEX_HTML,
+    )
+        .into_response()
+}
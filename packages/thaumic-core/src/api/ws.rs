@@ -1,23 +1,30 @@
 //! WebSocket handler for real-time client communication.
 
 use axum::extract::ws::{Message, WebSocket};
-use axum::extract::{State, WebSocketUpgrade};
+use axum::extract::{ConnectInfo, Query, State, WebSocketUpgrade};
+use axum::http::{header, HeaderMap};
 use axum::response::IntoResponse;
 use bytes::Bytes;
 use futures::sink::SinkExt;
 use futures::stream::StreamExt;
 use serde::{Deserialize, Serialize};
+use std::net::SocketAddr;
 use std::sync::Arc;
-use std::time::{Duration, Instant};
+use std::time::Duration;
 
+use crate::api::ws_connection::{ClientInfo, ClientPurpose};
 use crate::api::AppState;
+use crate::error::{ErrorCode, ThaumicError};
+use crate::events::compat::{encode_for_version, negotiate_version};
 use crate::events::SpeakerRemovalReason;
 use crate::protocol_constants::{
     DEFAULT_STREAMING_BUFFER_MS, MAX_FRAME_DURATION_MS, MAX_STREAMING_BUFFER_MS,
     MIN_FRAME_DURATION_MS, MIN_STREAMING_BUFFER_MS, SILENCE_FRAME_DURATION_MS,
-    WS_HEARTBEAT_CHECK_INTERVAL_SECS, WS_HEARTBEAT_TIMEOUT_SECS,
+    WS_PING_INTERVAL_SECS, WS_PING_SEND_TIMEOUT_SECS,
 };
-use crate::services::StreamCoordinator;
+use crate::services::topology_monitor::NetworkHealthState;
+use crate::services::{PlaybackSession, StreamCoordinator};
+use crate::state::SonosState;
 use crate::stream::{AudioCodec, AudioFormat, StreamMetadata};
 
 // ─────────────────────────────────────────────────────────────────────────────
@@ -71,10 +78,30 @@ enum WsIncoming {
     GetMute { payload: WsSpeakerRequest },
     StartPlayback { payload: StartPlaybackRequest },
     StopPlaybackSpeaker { payload: StopPlaybackSpeakerPayload },
+    LockStream { payload: WsLockRequest },
     StartBrowserCapture { payload: StartBrowserCaptureRequest },
     StopBrowserCapture,
 }
 
+impl WsIncoming {
+    /// Returns whether the message changes anything (streams, playback or
+    /// speakers), which read-only connections may not do.
+    fn mutates(&self) -> bool {
+        match self {
+            Self::Heartbeat | Self::GetVolume { .. } | Self::GetMute { .. } => false,
+            Self::Handshake { .. }
+            | Self::MetadataUpdate { .. }
+            | Self::SetVolume { .. }
+            | Self::SetMute { .. }
+            | Self::StartPlayback { .. }
+            | Self::StopPlaybackSpeaker { .. }
+            | Self::LockStream { .. }
+            | Self::StartBrowserCapture { .. }
+            | Self::StopBrowserCapture => true,
+        }
+    }
+}
+
 /// Request payload for starting playback via WebSocket.
 /// Supports both single speaker (legacy) and multi-speaker (new).
 #[derive(Deserialize)]
@@ -97,6 +124,13 @@ struct StartPlaybackRequest {
     /// Whether the client has video sync enabled (gates latency monitoring).
     #[serde(default)]
     video_sync_enabled: bool,
+    /// Name of uploaded artwork to show for this cast, overriding the
+    /// preset's and the default artwork.
+    #[serde(default)]
+    artwork: Option<String>,
+    /// PIN of a locked stream the speakers play now, which this replaces.
+    #[serde(default)]
+    pin: Option<String>,
 }
 
 impl StartPlaybackRequest {
@@ -132,6 +166,9 @@ struct WsVolumeRequest {
     /// on the coordinator. When false (default), uses sync-aware per-speaker routing.
     #[serde(default)]
     group: bool,
+    /// PIN of the stream the speaker plays, if another connection locked it.
+    #[serde(default)]
+    pin: Option<String>,
 }
 
 /// Request payload for mute control via WebSocket.
@@ -144,6 +181,9 @@ struct WsMuteRequest {
     /// on the coordinator. When false (default), uses sync-aware per-speaker routing.
     #[serde(default)]
     group: bool,
+    /// PIN of the stream the speaker plays, if another connection locked it.
+    #[serde(default)]
+    pin: Option<String>,
 }
 
 /// Request payload for speaker queries via WebSocket.
@@ -153,6 +193,13 @@ struct WsSpeakerRequest {
     ip: String,
 }
 
+/// Request payload for locking the connection's own stream.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct WsLockRequest {
+    pin: String,
+}
+
 /// Request payload for stopping playback on a single speaker.
 #[derive(Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -162,13 +209,16 @@ struct StopPlaybackSpeakerPayload {
     /// Reason for stopping (optional for backward compat).
     #[serde(default)]
     reason: Option<SpeakerRemovalReason>,
+    /// PIN of the stream, if another connection locked it.
+    #[serde(default)]
+    pin: Option<String>,
 }
 
 /// Encoder configuration from extension.
 #[derive(Deserialize)]
 #[serde(rename_all = "camelCase")]
-struct EncoderConfig {
-    codec: String,
+pub(super) struct EncoderConfig {
+    pub(super) codec: String,
     #[allow(dead_code)]
     bitrate: Option<u32>,
     sample_rate: Option<u32>,
@@ -177,6 +227,9 @@ struct EncoderConfig {
     bits_per_sample: Option<u16>,
     /// Streaming buffer size in milliseconds (100-1000). Only affects PCM codec.
     streaming_buffer_ms: Option<u64>,
+    /// Frame duration in milliseconds, which is also the cadence granularity.
+    /// Only honoured for PCM; other codecs have fixed frame sizes.
+    frame_duration_ms: Option<u32>,
     /// Frame size in samples per channel.
     /// Server derives exact duration: duration_ms = samples * 1000 / sample_rate
     frame_size_samples: Option<u32>,
@@ -185,13 +238,16 @@ struct EncoderConfig {
 /// Handshake request payload from client.
 #[derive(Deserialize)]
 #[serde(rename_all = "camelCase")]
-struct HandshakeRequest {
+pub(super) struct HandshakeRequest {
     /// Legacy codec field (deprecated).
     #[serde(default)]
-    codec: Option<String>,
+    pub(super) codec: Option<String>,
     /// New encoder config from extension.
     #[serde(default)]
-    encoder_config: Option<EncoderConfig>,
+    pub(super) encoder_config: Option<EncoderConfig>,
+    /// User-given name for the stream.
+    #[serde(default)]
+    pub(super) label: Option<String>,
 }
 
 /// Outgoing WebSocket messages.
@@ -203,7 +259,7 @@ enum WsOutgoing {
     },
     HeartbeatAck,
     Error {
-        message: String,
+        payload: ErrorPayload,
     },
     InitialState {
         payload: serde_json::Value,
@@ -249,6 +305,16 @@ enum CaptureErrorReason {
     CaptureError,
 }
 
+/// Payload for error messages.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ErrorPayload {
+    message: String,
+    /// i18n key for the message (`error.<code>`), so the UI can localize it.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    message_key: Option<String>,
+}
+
 /// Payload for stream ready notification.
 #[derive(Serialize)]
 #[serde(rename_all = "camelCase")]
@@ -261,6 +327,9 @@ struct StreamReadyPayload {
 #[serde(rename_all = "camelCase")]
 struct PlaybackErrorPayload {
     message: String,
+    /// i18n key for the message (`error.<code>`), so the UI can localize it.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    message_key: Option<String>,
 }
 
 /// Payload for multi-group playback results.
@@ -288,6 +357,47 @@ struct WsMutePayload {
 }
 
 impl WsOutgoing {
+    /// Creates an error message keyed `error.<code>` for UI localization,
+    /// the same scheme HTTP and Tauri errors use.
+    fn error(code: &'static str, message: impl Into<String>) -> Self {
+        Self::Error {
+            payload: ErrorPayload {
+                message: message.into(),
+                message_key: Some(message_key(code)),
+            },
+        }
+    }
+
+    /// Creates an error message from any error that provides an error code.
+    fn from_error<E: ErrorCode + std::fmt::Display>(err: &E) -> Self {
+        Self::Error {
+            payload: ErrorPayload {
+                message: err.to_string(),
+                message_key: Some(err.message_key()),
+            },
+        }
+    }
+
+    /// Creates a playback error message keyed `error.<code>`.
+    fn playback_error(code: &'static str, message: impl Into<String>) -> Self {
+        Self::PlaybackError {
+            payload: PlaybackErrorPayload {
+                message: message.into(),
+                message_key: Some(message_key(code)),
+            },
+        }
+    }
+
+    /// Creates an error message without an i18n key (e.g., pass-through errors).
+    fn error_unkeyed(message: impl Into<String>) -> Self {
+        Self::Error {
+            payload: ErrorPayload {
+                message: message.into(),
+                message_key: None,
+            },
+        }
+    }
+
     /// Serializes the message to a WebSocket text message.
     fn to_message(&self) -> Option<Message> {
         serde_json::to_string(self)
@@ -296,11 +406,18 @@ impl WsOutgoing {
     }
 }
 
+/// Returns the i18n key for a WebSocket-only error code.
+fn message_key(code: &str) -> String {
+    format!("error.{}", code)
+}
+
 /// Handshake acknowledgment payload.
 #[derive(Serialize)]
 #[serde(rename_all = "camelCase")]
 struct HandshakePayload {
     stream_id: String,
+    /// Negotiated frame duration, which the cadence ticks at.
+    frame_duration_ms: u32,
 }
 
 /// Sends a response based on an already-resolved result.
@@ -311,7 +428,7 @@ async fn send_result<T, E, R>(
     result: Result<T, E>,
     response_fn: R,
 ) where
-    E: std::fmt::Display,
+    E: Into<ThaumicError>,
     R: FnOnce(T) -> WsOutgoing,
 {
     match result {
@@ -321,9 +438,7 @@ async fn send_result<T, E, R>(
             }
         }
         Err(e) => {
-            let err = WsOutgoing::Error {
-                message: e.to_string(),
-            };
+            let err = WsOutgoing::from_error(&e.into());
             if let Some(msg) = err.to_message() {
                 let _ = sender.send(msg).await;
             }
@@ -340,7 +455,7 @@ async fn send_command_response<F, T, E, R>(
     response_fn: R,
 ) where
     F: std::future::Future<Output = Result<T, E>>,
-    E: std::fmt::Display,
+    E: Into<ThaumicError>,
     R: FnOnce(T) -> WsOutgoing,
 {
     send_result(sender, fut.await, response_fn).await;
@@ -354,13 +469,36 @@ async fn send_command_response<F, T, E, R>(
 ///
 /// Includes Sonos state (groups, transport, volume, mute), active playback sessions,
 /// and current network health status.
-fn build_initial_state(state: &AppState) -> Option<Message> {
-    let mut payload = state.sonos_state.to_json();
+fn build_initial_state(state: &AppState, protocol_version: u32) -> Option<Message> {
+    let payload = initial_state_payload(
+        &state.sonos_state,
+        &state.stream_coordinator.get_all_sessions(),
+        &state
+            .discovery_service
+            .topology_monitor()
+            .get_network_health(),
+        protocol_version,
+    );
+    WsOutgoing::InitialState { payload }.to_message()
+}
+
+/// Builds the INITIAL_STATE payload: Sonos state plus sessions and network health.
+fn initial_state_payload(
+    sonos_state: &SonosState,
+    sessions: &[PlaybackSession],
+    health_state: &NetworkHealthState,
+    protocol_version: u32,
+) -> serde_json::Value {
+    let mut payload = sonos_state.to_json();
 
-    // Add sessions to the initial state
     if let serde_json::Value::Object(ref mut map) = payload {
-        let sessions = state.stream_coordinator.get_all_sessions();
-        let sessions_json = match serde_json::to_value(&sessions) {
+        map.insert(
+            "protocolVersion".to_string(),
+            serde_json::Value::from(protocol_version),
+        );
+
+        // Add sessions to the initial state
+        let sessions_json = match serde_json::to_value(sessions) {
             Ok(v) => v,
             Err(e) => {
                 log::warn!("[WS] Failed to serialize sessions: {}", e);
@@ -370,10 +508,6 @@ fn build_initial_state(state: &AppState) -> Option<Message> {
         map.insert("sessions".to_string(), sessions_json);
 
         // Add network health to the initial state
-        let health_state = state
-            .discovery_service
-            .topology_monitor()
-            .get_network_health();
         let health_json = match serde_json::to_value(health_state.health) {
             Ok(v) => v,
             Err(e) => {
@@ -390,13 +524,13 @@ fn build_initial_state(state: &AppState) -> Option<Message> {
         }
     }
 
-    WsOutgoing::InitialState { payload }.to_message()
+    payload
 }
 
 /// Result of handling a handshake request.
 enum HandshakeResult {
-    /// Successfully created stream with this ID.
-    Success(String),
+    /// Successfully created stream with this ID and frame duration.
+    Success(String, u32),
     /// Failed to create stream, connection should close.
     Error(String),
 }
@@ -423,18 +557,18 @@ fn resolve_codec(codec_str: Option<&str>) -> AudioCodec {
 }
 
 /// Parsed and validated stream configuration from a handshake request.
-struct StreamConfig {
-    codec: AudioCodec,
-    audio_format: AudioFormat,
-    streaming_buffer_ms: u64,
-    frame_duration_ms: u32,
+pub(super) struct StreamConfig {
+    pub(super) codec: AudioCodec,
+    pub(super) audio_format: AudioFormat,
+    pub(super) streaming_buffer_ms: u64,
+    pub(super) frame_duration_ms: u32,
 }
 
 /// Parses and validates stream configuration from a handshake request.
 ///
 /// Extracts codec, sample rate, channels, bit depth, buffer size, and frame duration
 /// from the encoder config (or legacy fields), applying defaults and validation.
-fn parse_stream_config(payload: &HandshakeRequest) -> Result<StreamConfig, String> {
+pub(super) fn parse_stream_config(payload: &HandshakeRequest) -> Result<StreamConfig, String> {
     let codec_str = payload
         .encoder_config
         .as_ref()
@@ -477,16 +611,18 @@ fn parse_stream_config(payload: &HandshakeRequest) -> Result<StreamConfig, Strin
         .unwrap_or(DEFAULT_STREAMING_BUFFER_MS)
         .clamp(MIN_STREAMING_BUFFER_MS, MAX_STREAMING_BUFFER_MS);
 
-    // Derive frame duration from frame_size_samples.
-    // Using samples avoids floating-point rounding errors in the extension.
-    // Formula: duration_ms = samples * 1000 / sample_rate
-    let frame_duration_ms = payload
-        .encoder_config
-        .as_ref()
-        .and_then(|c| c.frame_size_samples)
-        .map(|samples| (samples as u64 * 1000 / sample_rate as u64) as u32)
-        .unwrap_or(SILENCE_FRAME_DURATION_MS)
-        .clamp(MIN_FRAME_DURATION_MS, MAX_FRAME_DURATION_MS);
+    let frame_duration_ms = resolve_frame_duration(
+        codec,
+        sample_rate,
+        payload
+            .encoder_config
+            .as_ref()
+            .and_then(|c| c.frame_duration_ms),
+        payload
+            .encoder_config
+            .as_ref()
+            .and_then(|c| c.frame_size_samples),
+    )?;
 
     // Validate bit depth (16 or 24), defaulting to 16.
     // 24-bit is only supported for FLAC codec on Sonos S2 speakers.
@@ -522,6 +658,56 @@ fn parse_stream_config(payload: &HandshakeRequest) -> Result<StreamConfig, Strin
     })
 }
 
+/// Resolves the frame duration (and cadence granularity) for a stream.
+///
+/// `frame_size_samples` sets the duration exactly (`samples * 1000 /
+/// sample_rate`), clamped to the supported range. For PCM an explicit
+/// `frame_duration_ms` may be given as well or instead; it must be in range
+/// and round to `frame_size_samples` when both are sent. Given alone it must
+/// cover a whole number of samples, so cadence ticks don't drift from the
+/// audio. Without either, the default is used.
+fn resolve_frame_duration(
+    codec: AudioCodec,
+    sample_rate: u32,
+    frame_duration_ms: Option<u32>,
+    frame_size_samples: Option<u32>,
+) -> Result<u32, String> {
+    // Using samples avoids floating-point rounding errors in the extension
+    let from_samples = frame_size_samples.map(|samples| {
+        ((samples as u64 * 1000 / sample_rate as u64) as u32)
+            .clamp(MIN_FRAME_DURATION_MS, MAX_FRAME_DURATION_MS)
+    });
+
+    let requested = match frame_duration_ms {
+        Some(ms) if codec == AudioCodec::Pcm => ms,
+        _ => return Ok(from_samples.unwrap_or(SILENCE_FRAME_DURATION_MS)),
+    };
+    if !(MIN_FRAME_DURATION_MS..=MAX_FRAME_DURATION_MS).contains(&requested) {
+        return Err(format!(
+            "Invalid frame_duration_ms: {}. Must be {}-{} ms.",
+            requested, MIN_FRAME_DURATION_MS, MAX_FRAME_DURATION_MS
+        ));
+    }
+
+    let exact_samples = sample_rate as u64 * requested as u64;
+    match (frame_size_samples, from_samples) {
+        (Some(samples), Some(derived)) => {
+            if (exact_samples + 500) / 1000 != samples as u64 {
+                return Err(format!(
+                    "frame_duration_ms {} does not match frame_size_samples {} at {} Hz.",
+                    requested, samples, sample_rate
+                ));
+            }
+            Ok(derived)
+        }
+        _ if exact_samples % 1000 != 0 => Err(format!(
+            "Invalid frame_duration_ms: {} ms is not a whole number of samples at {} Hz.",
+            requested, sample_rate
+        )),
+        _ => Ok(requested),
+    }
+}
+
 /// Handles a HANDSHAKE message: creates a stream and returns ack or error.
 fn handle_handshake(state: &AppState, payload: HandshakeRequest) -> HandshakeResult {
     let config = match parse_stream_config(&payload) {
@@ -543,7 +729,17 @@ fn handle_handshake(state: &AppState, payload: HandshakeRequest) -> HandshakeRes
         config.streaming_buffer_ms,
         config.frame_duration_ms,
     ) {
-        Ok(id) => HandshakeResult::Success(id),
+        Ok(id) => {
+            if payload.label.is_some() {
+                if let Err(e) = state
+                    .stream_coordinator
+                    .set_stream_label(&id, payload.label.as_deref())
+                {
+                    log::warn!("[WS] Ignoring label for stream {}: {}", id, e);
+                }
+            }
+            HandshakeResult::Success(id, config.frame_duration_ms)
+        }
         Err(e) => HandshakeResult::Error(e),
     }
 }
@@ -604,9 +800,10 @@ async fn handle_start_browser_capture(
 ) {
     // Reject if already capturing
     if capture.session.is_some() {
-        let msg = WsOutgoing::Error {
-            message: "Browser capture already active on this connection".into(),
-        };
+        let msg = WsOutgoing::error(
+            "capture_already_active",
+            "Browser capture already active on this connection",
+        );
         if let Some(msg) = msg.to_message() {
             let _ = sender.send(msg).await;
         }
@@ -617,9 +814,10 @@ async fn handle_start_browser_capture(
     let factory = match &state.capture_factory {
         Some(f) if f.available() => f,
         _ => {
-            let msg = WsOutgoing::Error {
-                message: "Browser capture is not available on this platform".into(),
-            };
+            let msg = WsOutgoing::error(
+                "capture_unavailable",
+                "Browser capture is not available on this platform",
+            );
             if let Some(msg) = msg.to_message() {
                 let _ = sender.send(msg).await;
             }
@@ -631,9 +829,10 @@ async fn handle_start_browser_capture(
     let source = match factory.create_source(payload.browser_name.as_deref()) {
         Ok(s) => s,
         Err(e) => {
-            let msg = WsOutgoing::Error {
-                message: format!("Failed to create capture source: {}", e),
-            };
+            let msg = WsOutgoing::error(
+                "capture_source_failed",
+                format!("Failed to create capture source: {}", e),
+            );
             if let Some(msg) = msg.to_message() {
                 let _ = sender.send(msg).await;
             }
@@ -645,12 +844,14 @@ async fn handle_start_browser_capture(
     let stream_config = match parse_stream_config(&HandshakeRequest {
         codec: None,
         encoder_config: payload.encoder_config,
+        label: None,
     }) {
         Ok(c) => c,
         Err(e) => {
-            let msg = WsOutgoing::Error {
-                message: format!("Invalid encoder config: {}", e),
-            };
+            let msg = WsOutgoing::error(
+                "invalid_encoder_config",
+                format!("Invalid encoder config: {}", e),
+            );
             if let Some(msg) = msg.to_message() {
                 let _ = sender.send(msg).await;
             }
@@ -683,6 +884,7 @@ async fn handle_start_browser_capture(
             let ack = WsOutgoing::HandshakeAck {
                 payload: HandshakePayload {
                     stream_id: stream_id.clone(),
+                    frame_duration_ms: stream_config.frame_duration_ms,
                 },
             };
             if let Some(msg) = ack.to_message() {
@@ -711,16 +913,17 @@ async fn handle_start_browser_capture(
                 }
             } else {
                 log::warn!("[WS] Browser capture: timeout waiting for first audio frame");
-                let msg = WsOutgoing::Error {
-                    message: "Timeout waiting for audio from browser. Is audio playing?".into(),
-                };
+                let msg = WsOutgoing::error(
+                    "capture_timeout",
+                    "Timeout waiting for audio from browser. Is audio playing?",
+                );
                 if let Some(msg) = msg.to_message() {
                     let _ = sender.send(msg).await;
                 }
             }
         }
         Err(e) => {
-            let msg = WsOutgoing::Error { message: e };
+            let msg = WsOutgoing::error_unkeyed(e);
             if let Some(msg) = msg.to_message() {
                 let _ = sender.send(msg).await;
             }
@@ -744,9 +947,7 @@ async fn handle_stop_browser_capture(
         // StreamGuard drop will clean up the stream
         stream_guard.take();
     } else {
-        let msg = WsOutgoing::Error {
-            message: "No active browser capture to stop".into(),
-        };
+        let msg = WsOutgoing::error("no_active_capture", "No active browser capture to stop");
         if let Some(msg) = msg.to_message() {
             let _ = sender.send(msg).await;
         }
@@ -773,11 +974,10 @@ async fn handle_start_playback(
     let guard = match stream_guard {
         Some(g) => g,
         None => {
-            let msg = WsOutgoing::PlaybackError {
-                payload: PlaybackErrorPayload {
-                    message: "No active stream on this connection".into(),
-                },
-            };
+            let msg = WsOutgoing::playback_error(
+                "no_active_stream",
+                "No active stream on this connection",
+            );
             if let Some(msg) = msg.to_message() {
                 let _ = sender.send(msg).await;
             }
@@ -789,9 +989,25 @@ async fn handle_start_playback(
     let speaker_ips = payload.get_speaker_ips();
 
     if speaker_ips.is_empty() {
+        let msg = WsOutgoing::playback_error("no_speaker_ips", "No speaker IPs provided");
+        if let Some(msg) = msg.to_message() {
+            let _ = sender.send(msg).await;
+        }
+        return;
+    }
+
+    // Replacing a locked stream on any of the speakers needs its PIN
+    let locked = speaker_ips.iter().find_map(|ip| {
+        state
+            .stream_coordinator
+            .authorize_speaker(ip, payload.pin.as_deref(), Some(&stream_id))
+            .err()
+    });
+    if let Some(e) = locked {
         let msg = WsOutgoing::PlaybackError {
             payload: PlaybackErrorPayload {
-                message: "No speaker IPs provided".into(),
+                message: e.to_string(),
+                message_key: Some(e.message_key()),
             },
         };
         if let Some(msg) = msg.to_message() {
@@ -810,7 +1026,7 @@ async fn handle_start_playback(
     }
 
     // Start playback on all speakers (multi-group support)
-    let artwork_url = state.artwork_metadata_url();
+    let artwork_url = state.artwork_url_for(&stream_id, payload.artwork.as_deref());
     let results = state
         .stream_coordinator
         .start_playback_multi(
@@ -822,15 +1038,19 @@ async fn handle_start_playback(
         )
         .await;
 
-    // Only start latency monitoring if video sync is enabled
-    if latency_monitoring {
-        for result in &results {
-            if result.success {
-                state
-                    .latency_monitor
-                    .start_monitoring(&stream_id, &result.speaker_ip)
-                    .await;
-            }
+    // Push position updates for every playing speaker; measure latency only
+    // if video sync is enabled
+    for result in results.iter().filter(|result| result.success) {
+        if latency_monitoring {
+            state
+                .latency_monitor
+                .start_monitoring(&stream_id, &result.speaker_ip)
+                .await;
+        } else {
+            state
+                .latency_monitor
+                .start_position_updates(&stream_id, &result.speaker_ip)
+                .await;
         }
     }
 
@@ -843,29 +1063,101 @@ async fn handle_start_playback(
     }
 }
 
+/// Handles a STOP_PLAYBACK_SPEAKER message: stops the stream on a speaker,
+/// checking the PIN if another connection's stream is locked.
+async fn handle_stop_playback_speaker(
+    state: &AppState,
+    sender: &mut futures::stream::SplitSink<WebSocket, Message>,
+    stream_guard: &Option<StreamGuard>,
+    payload: StopPlaybackSpeakerPayload,
+) {
+    let own_stream = stream_guard.as_ref().map(StreamGuard::id);
+    if let Err(e) = state.stream_coordinator.authorize_stream(
+        &payload.stream_id,
+        payload.pin.as_deref(),
+        own_stream,
+    ) {
+        if let Some(msg) = WsOutgoing::from_error(&e).to_message() {
+            let _ = sender.send(msg).await;
+        }
+        return;
+    }
+
+    // Stop playback; stop latency monitoring for all stopped speakers
+    // (when stopping a coordinator, this includes all its slaves)
+    let stopped_ips = state
+        .stream_coordinator
+        .stop_playback_speaker(&payload.stream_id, &payload.ip, payload.reason)
+        .await;
+    for ip in stopped_ips {
+        state
+            .latency_monitor
+            .stop_speaker(&payload.stream_id, &ip)
+            .await;
+    }
+}
+
 /// WebSocket upgrade handler.
-pub async fn ws_handler(ws: WebSocketUpgrade, State(state): State<AppState>) -> impl IntoResponse {
-    ws.on_upgrade(move |socket| handle_ws(socket, state))
+pub async fn ws_handler(
+    ws: WebSocketUpgrade,
+    Query(params): Query<WsParams>,
+    State(state): State<AppState>,
+    ConnectInfo(remote_addr): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
+) -> impl IntoResponse {
+    let client = ClientInfo {
+        name: ClientInfo::label(params.client),
+        version: ClientInfo::label(params.client_version),
+        purpose: params.purpose,
+        user_agent: ClientInfo::label(
+            headers
+                .get(header::USER_AGENT)
+                .and_then(|value| value.to_str().ok())
+                .map(str::to_string),
+        ),
+        remote_ip: Some(remote_addr.ip()),
+        protocol_version: negotiate_version(params.protocol_version),
+    };
+    ws.on_upgrade(move |socket| handle_ws(socket, state, client))
+}
+
+/// Query parameters accepted on the WebSocket upgrade request.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WsParams {
+    /// Event protocol version the client understands (1 if omitted).
+    protocol_version: Option<u32>,
+    /// Client name, shown in client listings.
+    client: Option<String>,
+    /// Client version, shown in client listings.
+    client_version: Option<String>,
+    /// What the client uses the connection for.
+    purpose: Option<ClientPurpose>,
 }
 
 /// Main WebSocket connection handler.
-async fn handle_ws(socket: WebSocket, state: AppState) {
+async fn handle_ws(socket: WebSocket, state: AppState, client: ClientInfo) {
+    let protocol_version = client.protocol_version;
     let (mut sender, mut receiver) = socket.split();
     let mut stream_guard: Option<StreamGuard> = None;
     let mut capture = BrowserCaptureState::new();
     let mut broadcast_rx = state.event_bridge.subscribe();
-    let mut last_activity = Instant::now();
     let mut latency_monitoring = false;
+    let read_only = client.purpose == Some(ClientPurpose::Observe);
 
     // Register connection for tracking and force-close capability
-    let conn_guard = state.ws_manager.register();
+    let conn_guard = state.ws_manager.register(client);
     let cancel_token = conn_guard.cancel_token().clone();
 
-    log::info!("[WS] New connection established: {}", conn_guard.id());
+    log::info!(
+        "[WS] New connection established: {} (event protocol v{})",
+        conn_guard.id(),
+        protocol_version
+    );
 
     // Send initial state immediately on connect (before any handshake)
     // This allows clients to monitor speaker state without creating a stream
-    if let Some(msg) = build_initial_state(&state) {
+    if let Some(msg) = build_initial_state(&state, protocol_version) {
         if sender.send(msg).await.is_err() {
             log::warn!("[WS] Failed to send initial state, client disconnected");
             return;
@@ -874,9 +1166,8 @@ async fn handle_ws(socket: WebSocket, state: AppState) {
 
     // Use interval instead of sleep to reduce timer allocations and prevent drift.
     // Delay mode skips missed ticks rather than bursting to catch up.
-    let mut heartbeat_interval =
-        tokio::time::interval(Duration::from_secs(WS_HEARTBEAT_CHECK_INTERVAL_SECS));
-    heartbeat_interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    let mut ping_interval = tokio::time::interval(Duration::from_secs(WS_PING_INTERVAL_SECS));
+    ping_interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
 
     loop {
         tokio::select! {
@@ -887,29 +1178,43 @@ async fn handle_ws(socket: WebSocket, state: AppState) {
             }
             // Handle incoming messages from the client
             msg = receiver.next() => {
-                last_activity = Instant::now();
+                conn_guard.mark_alive();
                 match msg {
                     Some(Ok(Message::Text(text))) => {
                         let parsed = serde_json::from_str::<WsIncoming>(&text);
                         match parsed {
+                            Ok(message) if read_only && message.mutates() => {
+                                log::debug!("[WS] Rejected control message on read-only connection {}", conn_guard.id());
+                                let err = WsOutgoing::error(
+                                    "read_only_connection",
+                                    "This connection is read-only (purpose=observe)",
+                                );
+                                if let Some(msg) = err.to_message() {
+                                    let _ = sender.send(msg).await;
+                                }
+                            }
                             Ok(WsIncoming::Handshake { payload }) => {
                                 match handle_handshake(&state, payload) {
-                                    HandshakeResult::Success(id) => {
+                                    HandshakeResult::Success(id, frame_duration_ms) => {
                                         // Create guard immediately - cleanup happens on drop
                                         let guard = StreamGuard::new(
                                             id.clone(),
                                             Arc::clone(&state.stream_coordinator),
                                         );
                                         let ack = WsOutgoing::HandshakeAck {
-                                            payload: HandshakePayload { stream_id: id },
+                                            payload: HandshakePayload {
+                                                stream_id: id,
+                                                frame_duration_ms,
+                                            },
                                         };
                                         stream_guard = Some(guard);
+                                        conn_guard.set_stream_id(stream_guard.as_ref().map(StreamGuard::id));
                                         if let Some(msg) = ack.to_message() {
                                             let _ = sender.send(msg).await;
                                         }
                                     }
                                     HandshakeResult::Error(e) => {
-                                        let err = WsOutgoing::Error { message: e };
+                                        let err = WsOutgoing::error_unkeyed(e);
                                         if let Some(msg) = err.to_message() {
                                             let _ = sender.send(msg).await;
                                         }
@@ -931,12 +1236,18 @@ async fn handle_ws(socket: WebSocket, state: AppState) {
                                 let ip = payload.ip.clone();
                                 let volume = payload.volume;
                                 let sc = &state.stream_coordinator;
+                                let own_stream = stream_guard.as_ref().map(StreamGuard::id);
 
-                                let result = if payload.group {
-                                    sc.set_sync_group_volume(&*state.sonos, &ip, volume)
+                                let result = match sc.authorize_speaker(&ip, payload.pin.as_deref(), own_stream) {
+                                    Err(e) => Err(e),
+                                    Ok(()) if payload.group => sc
+                                        .set_sync_group_volume(&*state.sonos, &ip, volume)
                                         .await
-                                } else {
-                                    sc.set_volume_routed(&*state.sonos, &ip, volume).await
+                                        .map_err(ThaumicError::from),
+                                    Ok(()) => sc
+                                        .set_volume_routed(&*state.sonos, &ip, volume)
+                                        .await
+                                        .map_err(ThaumicError::from),
                                 };
                                 send_result(&mut sender, result, |()| {
                                     WsOutgoing::VolumeState {
@@ -949,12 +1260,18 @@ async fn handle_ws(socket: WebSocket, state: AppState) {
                                 let ip = payload.ip.clone();
                                 let mute = payload.mute;
                                 let sc = &state.stream_coordinator;
+                                let own_stream = stream_guard.as_ref().map(StreamGuard::id);
 
-                                let result = if payload.group {
-                                    sc.set_sync_group_mute(&*state.sonos, &ip, mute)
+                                let result = match sc.authorize_speaker(&ip, payload.pin.as_deref(), own_stream) {
+                                    Err(e) => Err(e),
+                                    Ok(()) if payload.group => sc
+                                        .set_sync_group_mute(&*state.sonos, &ip, mute)
+                                        .await
+                                        .map_err(ThaumicError::from),
+                                    Ok(()) => sc
+                                        .set_mute_routed(&*state.sonos, &ip, mute)
                                         .await
-                                } else {
-                                    sc.set_mute_routed(&*state.sonos, &ip, mute).await
+                                        .map_err(ThaumicError::from),
                                 };
                                 send_result(&mut sender, result, |()| {
                                     WsOutgoing::MuteState {
@@ -999,21 +1316,23 @@ async fn handle_ws(socket: WebSocket, state: AppState) {
                                 ).await;
                             }
                             Ok(WsIncoming::StopPlaybackSpeaker { payload }) => {
-                                // Stop playback; stop latency monitoring for all stopped speakers
-                                // (when stopping a coordinator, this includes all its slaves)
-                                let stopped_ips = state
-                                    .stream_coordinator
-                                    .stop_playback_speaker(
-                                        &payload.stream_id,
-                                        &payload.ip,
-                                        payload.reason,
-                                    )
-                                    .await;
-                                for ip in stopped_ips {
-                                    state
-                                        .latency_monitor
-                                        .stop_speaker(&payload.stream_id, &ip)
-                                        .await;
+                                handle_stop_playback_speaker(
+                                    &state, &mut sender, &stream_guard, payload,
+                                ).await;
+                            }
+                            Ok(WsIncoming::LockStream { payload }) => {
+                                let result = match stream_guard {
+                                    Some(ref guard) => state
+                                        .stream_coordinator
+                                        .lock_stream(guard.id(), &payload.pin),
+                                    None => Err(ThaumicError::InvalidRequest(
+                                        "No stream to lock; send HANDSHAKE first".into(),
+                                    )),
+                                };
+                                if let Err(e) = result {
+                                    if let Some(msg) = WsOutgoing::from_error(&e).to_message() {
+                                        let _ = sender.send(msg).await;
+                                    }
                                 }
                             }
                             Ok(WsIncoming::StartBrowserCapture { payload }) => {
@@ -1024,6 +1343,7 @@ async fn handle_ws(socket: WebSocket, state: AppState) {
                                     &mut capture,
                                     payload,
                                 ).await;
+                                conn_guard.set_stream_id(stream_guard.as_ref().map(StreamGuard::id));
                             }
                             Ok(WsIncoming::StopBrowserCapture) => {
                                 handle_stop_browser_capture(
@@ -1031,6 +1351,7 @@ async fn handle_ws(socket: WebSocket, state: AppState) {
                                     &mut stream_guard,
                                     &mut capture,
                                 ).await;
+                                conn_guard.set_stream_id(stream_guard.as_ref().map(StreamGuard::id));
                             }
                             Err(_) => {} // Unknown message type, ignore
                         }
@@ -1058,9 +1379,9 @@ async fn handle_ws(socket: WebSocket, state: AppState) {
                     _ => {}
                 }
             }
-            // Handle broadcasted events (GENA, etc.)
+            // Handle broadcasted events (GENA, etc.), in the client's negotiated shape
             Ok(event) = broadcast_rx.recv() => {
-                if let Ok(json) = serde_json::to_string(&event) {
+                if let Some(json) = encode_for_version(&event, protocol_version) {
                     if sender.send(Message::Text(json.into())).await.is_err() {
                         break;
                     }
@@ -1101,10 +1422,13 @@ async fn handle_ws(socket: WebSocket, state: AppState) {
                     break;
                 }
             }
-            // Heartbeat timeout check
-            _ = heartbeat_interval.tick() => {
-                if last_activity.elapsed() > Duration::from_secs(WS_HEARTBEAT_TIMEOUT_SECS) {
-                    log::warn!("[WS] Heartbeat timeout");
+            // Keepalive ping. Clients answer automatically; the pong marks the
+            // connection alive, and the manager reaps connections that stay silent.
+            _ = ping_interval.tick() => {
+                let ping = sender.send(Message::Ping(Bytes::new()));
+                let sent = tokio::time::timeout(Duration::from_secs(WS_PING_SEND_TIMEOUT_SECS), ping).await;
+                if !matches!(sent, Ok(Ok(()))) {
+                    log::warn!("[WS] Ping failed, closing {}", conn_guard.id());
                     break;
                 }
             }
@@ -1130,3 +1454,232 @@ async fn handle_ws(socket: WebSocket, state: AppState) {
 
     // StreamGuard and ConnectionGuard Drop impls handle any remaining cleanup
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::conformance::{
+        assert_serializes_to_fixtures, fixtures, sample_group, WS_CLIENT, WS_SERVER,
+    };
+    use crate::events::NetworkHealth;
+    use crate::services::PlaybackResult;
+    use crate::sonos::types::TransportState;
+
+    fn to_value(message: WsOutgoing) -> serde_json::Value {
+        serde_json::to_value(message).unwrap()
+    }
+
+    #[test]
+    fn server_messages_match_fixtures() {
+        let sonos_state = SonosState::default();
+        *sonos_state.groups.write() = vec![sample_group()];
+        let ip = "192.168.1.20".to_string();
+        sonos_state
+            .transport_states
+            .insert(ip.clone(), TransportState::Paused);
+        sonos_state.group_volumes.insert(ip.clone(), 35);
+        sonos_state.group_mutes.insert(ip.clone(), false);
+        sonos_state.group_volume_fixed.insert(ip.clone(), false);
+        let health = NetworkHealthState {
+            health: NetworkHealth::Degraded,
+            reason: Some("Speakers discovered but not responding".to_string()),
+            s1_speakers: Vec::new(),
+        };
+
+        assert_serializes_to_fixtures(
+            WS_SERVER,
+            vec![
+                (
+                    "HANDSHAKE_ACK",
+                    to_value(WsOutgoing::HandshakeAck {
+                        payload: HandshakePayload {
+                            stream_id: "7d3c".to_string(),
+                            frame_duration_ms: 85,
+                        },
+                    }),
+                ),
+                ("HEARTBEAT_ACK", to_value(WsOutgoing::HeartbeatAck)),
+                (
+                    "ERROR",
+                    to_value(WsOutgoing::error(
+                        "capture_already_active",
+                        "Browser capture already active on this connection",
+                    )),
+                ),
+                (
+                    "INITIAL_STATE",
+                    to_value(WsOutgoing::InitialState {
+                        payload: initial_state_payload(&sonos_state, &[], &health, 2),
+                    }),
+                ),
+                (
+                    "VOLUME_STATE",
+                    to_value(WsOutgoing::VolumeState {
+                        payload: WsVolumePayload {
+                            ip: ip.clone(),
+                            volume: 35,
+                        },
+                    }),
+                ),
+                (
+                    "MUTE_STATE",
+                    to_value(WsOutgoing::MuteState {
+                        payload: WsMutePayload {
+                            ip: ip.clone(),
+                            mute: true,
+                        },
+                    }),
+                ),
+                (
+                    "STREAM_READY",
+                    to_value(WsOutgoing::StreamReady {
+                        payload: StreamReadyPayload { buffer_size: 12 },
+                    }),
+                ),
+                (
+                    "PLAYBACK_ERROR",
+                    to_value(WsOutgoing::playback_error(
+                        "no_speaker_ips",
+                        "No speaker IPs provided",
+                    )),
+                ),
+                (
+                    "PLAYBACK_RESULTS",
+                    to_value(WsOutgoing::PlaybackResults {
+                        payload: PlaybackResultsPayload {
+                            results: vec![
+                                PlaybackResult {
+                                    speaker_ip: ip.clone(),
+                                    success: true,
+                                    stream_url: Some(
+                                        "http://192.168.1.10:49400/stream/7d3c/live.flac"
+                                            .to_string(),
+                                    ),
+                                    error: None,
+                                },
+                                PlaybackResult {
+                                    speaker_ip: "192.168.1.21".to_string(),
+                                    success: false,
+                                    stream_url: None,
+                                    error: Some("Speaker not found".to_string()),
+                                },
+                            ],
+                        },
+                    }),
+                ),
+                (
+                    "BROWSER_CAPTURE_ERROR",
+                    to_value(WsOutgoing::BrowserCaptureError {
+                        payload: BrowserCaptureErrorPayload {
+                            stream_id: "7d3c".to_string(),
+                            error: "Browser process exited".to_string(),
+                            reason: CaptureErrorReason::ProcessExited,
+                        },
+                    }),
+                ),
+            ],
+        );
+    }
+
+    #[test]
+    fn client_messages_parse() {
+        for (name, fixture) in fixtures(WS_CLIENT) {
+            let message: WsIncoming = serde_json::from_value(fixture)
+                .unwrap_or_else(|e| panic!("{} does not parse: {}", name, e));
+
+            match (name.as_str(), message) {
+                ("HANDSHAKE", WsIncoming::Handshake { payload }) => {
+                    let config = parse_stream_config(&payload).unwrap();
+                    assert_eq!(config.codec, AudioCodec::Flac);
+                    assert_eq!(config.audio_format.bits_per_sample, 24);
+                    assert_eq!(config.streaming_buffer_ms, 200);
+                    assert_eq!(config.frame_duration_ms, 85);
+                }
+                ("HEARTBEAT", WsIncoming::Heartbeat) => {}
+                ("METADATA_UPDATE", WsIncoming::MetadataUpdate { payload }) => {
+                    assert_eq!(payload.title.as_deref(), Some("Song"));
+                    assert_eq!(payload.source.as_deref(), Some("YouTube"));
+                }
+                ("START_PLAYBACK", WsIncoming::StartPlayback { payload }) => {
+                    assert_eq!(payload.get_speaker_ips(), vec!["192.168.1.20"]);
+                    assert!(payload.video_sync_enabled);
+                }
+                ("SET_VOLUME", WsIncoming::SetVolume { payload }) => {
+                    assert_eq!(payload.volume, 35);
+                    assert!(payload.group);
+                }
+                ("SET_MUTE", WsIncoming::SetMute { payload }) => assert!(payload.mute),
+                ("GET_VOLUME", WsIncoming::GetVolume { payload })
+                | ("GET_MUTE", WsIncoming::GetMute { payload }) => {
+                    assert_eq!(payload.ip, "192.168.1.20");
+                }
+                ("STOP_PLAYBACK_SPEAKER", WsIncoming::StopPlaybackSpeaker { payload }) => {
+                    assert_eq!(payload.stream_id, "7d3c");
+                    assert_eq!(payload.reason, Some(SpeakerRemovalReason::UserRemoved));
+                }
+                ("LOCK_STREAM", WsIncoming::LockStream { payload }) => {
+                    assert_eq!(payload.pin, "2468");
+                }
+                (name, _) => panic!("{} parsed as a different message", name),
+            }
+        }
+    }
+
+    #[test]
+    fn only_queries_are_allowed_read_only() {
+        let parse = |text: &str| serde_json::from_str::<WsIncoming>(text).unwrap();
+        assert!(!parse(r#"{"type":"HEARTBEAT"}"#).mutates());
+        assert!(!parse(r#"{"type":"GET_VOLUME","payload":{"ip":"192.168.1.20"}}"#).mutates());
+        assert!(
+            parse(r#"{"type":"SET_MUTE","payload":{"ip":"192.168.1.20","mute":true}}"#).mutates()
+        );
+        assert!(parse(r#"{"type":"STOP_BROWSER_CAPTURE"}"#).mutates());
+    }
+
+    #[test]
+    fn errors_use_the_api_message_keys() {
+        let err = ThaumicError::StreamLocked("7d3c".into());
+        let body = crate::error::ApiErrorResponse::from_error(err.status_code(), &err);
+        let ws = to_value(WsOutgoing::from_error(&err));
+        assert_eq!(ws["payload"]["messageKey"], body.message_key.as_str());
+        assert_eq!(ws["payload"]["messageKey"], "error.stream_locked");
+
+        let ws = to_value(WsOutgoing::error("capture_timeout", "No audio"));
+        assert_eq!(ws["payload"]["messageKey"], "error.capture_timeout");
+    }
+
+    #[test]
+    fn pcm_frame_duration_is_negotiable() {
+        let pcm = AudioCodec::Pcm;
+        assert_eq!(resolve_frame_duration(pcm, 48000, None, None), Ok(10));
+        assert_eq!(resolve_frame_duration(pcm, 48000, Some(50), None), Ok(50));
+        assert_eq!(
+            resolve_frame_duration(pcm, 48000, Some(20), Some(960)),
+            Ok(20)
+        );
+        // Rounded sample counts, as the extension computes them
+        assert_eq!(
+            resolve_frame_duration(pcm, 22050, Some(10), Some(221)),
+            Ok(10)
+        );
+
+        assert!(resolve_frame_duration(pcm, 48000, Some(2), None).is_err());
+        assert!(resolve_frame_duration(pcm, 48000, Some(500), None).is_err());
+        assert!(resolve_frame_duration(pcm, 48000, Some(20), Some(480)).is_err());
+        // 5ms at 44.1kHz is 220.5 samples
+        assert!(resolve_frame_duration(pcm, 44100, Some(5), None).is_err());
+    }
+
+    #[test]
+    fn compressed_frame_duration_follows_frame_size() {
+        // AAC frames are 1024 samples whatever duration the client asks for
+        assert_eq!(
+            resolve_frame_duration(AudioCodec::Aac, 48000, Some(10), Some(1024)),
+            Ok(21)
+        );
+        assert_eq!(
+            resolve_frame_duration(AudioCodec::Mp3, 48000, Some(500), None),
+            Ok(10)
+        );
+    }
+}
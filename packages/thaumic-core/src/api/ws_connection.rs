@@ -4,16 +4,105 @@
 //!
 //! - `WsConnectionManager`: Tracks all active WebSocket connections
 //! - `ConnectionGuard`: RAII guard for automatic cleanup on disconnect
+//!
+//! Connection handlers ping their clients periodically and report any
+//! traffic (including pongs) via [`ConnectionGuard::mark_alive`]. The
+//! manager's reaper closes connections that have gone silent, so a client
+//! that vanished without closing its socket (laptop lid shut, Wi-Fi dropped)
+//! stops counting as connected within seconds rather than when TCP gives up.
+//!
+//! Clients may identify themselves on the upgrade request
+//! (`/ws?client=extension&clientVersion=1.2.0&purpose=ingest`); what they
+//! report is kept per connection and listed by [`WsConnectionManager::clients`].
+//! `purpose=observe` makes the connection read-only.
 
+use std::net::IpAddr;
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 use dashmap::DashMap;
 use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
 use tokio_util::sync::CancellationToken;
 
-/// Internal connection state (placeholder for future metadata).
-struct ConnectionState {}
+use crate::protocol_constants::{WS_HEARTBEAT_TIMEOUT_SECS, WS_PING_INTERVAL_SECS};
+use crate::runtime::TokioSpawner;
+use crate::utils::now_millis;
+
+/// Longest client-supplied label kept (characters).
+const MAX_LABEL_LEN: usize = 64;
+
+/// What a client uses its connection for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ClientPurpose {
+    /// Sends audio for a stream.
+    Ingest,
+    /// Follows events and controls speakers, without sending audio.
+    Events,
+    /// Follows events and state only, e.g. a wall-mounted dashboard. Every
+    /// message that would change anything is rejected.
+    Observe,
+}
+
+/// How a client identified itself when connecting.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClientInfo {
+    /// Client name (e.g., "extension").
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// Client version (e.g., the extension version).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub purpose: Option<ClientPurpose>,
+    /// `User-Agent` of the upgrade request, which names the browser.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user_agent: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub remote_ip: Option<IpAddr>,
+    /// Event protocol version negotiated for the connection.
+    pub protocol_version: u32,
+}
+
+impl ClientInfo {
+    /// Keeps a client-supplied label if it is non-empty, truncated to a
+    /// sane length.
+    pub fn label(value: Option<String>) -> Option<String> {
+        let value = value?;
+        let value = value.trim();
+        (!value.is_empty()).then(|| value.chars().take(MAX_LABEL_LEN).collect())
+    }
+}
+
+/// A connected client, as listed by `/api/clients`.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConnectedClient {
+    pub id: String,
+    #[serde(flatten)]
+    pub info: ClientInfo,
+    /// Unix timestamp (ms) of the connection.
+    pub connected_at: u64,
+    /// Time since the client last sent anything, in milliseconds.
+    pub idle_ms: u64,
+    /// Stream the connection owns, if it started one.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stream_id: Option<String>,
+}
+
+/// Internal connection state.
+struct ConnectionState {
+    /// Token closing this connection only.
+    cancel_token: CancellationToken,
+    /// When the client last sent anything, pongs included.
+    last_seen: Instant,
+    info: ClientInfo,
+    connected_at: u64,
+    stream_id: Option<String>,
+}
 
 /// Manages all active WebSocket connections.
 ///
@@ -44,12 +133,18 @@ impl WsConnectionManager {
     ///
     /// The returned `ConnectionGuard` will automatically unregister the
     /// connection when dropped.
-    pub fn register(self: &Arc<Self>) -> ConnectionGuard {
+    pub fn register(self: &Arc<Self>, info: ClientInfo) -> ConnectionGuard {
         let id = self.next_id.fetch_add(1, Ordering::Relaxed);
         let conn_id = format!("ws-{}", id);
         let cancel_token = self.global_cancel.read().child_token();
 
-        let state = ConnectionState {};
+        let state = ConnectionState {
+            cancel_token: cancel_token.clone(),
+            last_seen: Instant::now(),
+            info,
+            connected_at: now_millis(),
+            stream_id: None,
+        };
 
         self.connections.insert(conn_id.clone(), state);
         log::info!(
@@ -76,6 +171,88 @@ impl WsConnectionManager {
         }
     }
 
+    /// Records that the client sent something.
+    fn mark_alive(&self, id: &str) {
+        if let Some(mut state) = self.connections.get_mut(id) {
+            state.last_seen = Instant::now();
+        }
+    }
+
+    fn set_stream_id(&self, id: &str, stream_id: Option<&str>) {
+        if let Some(mut state) = self.connections.get_mut(id) {
+            state.stream_id = stream_id.map(str::to_string);
+        }
+    }
+
+    /// Lists the connected clients, oldest connection first.
+    #[must_use]
+    pub fn clients(&self) -> Vec<ConnectedClient> {
+        let mut clients: Vec<ConnectedClient> = self
+            .connections
+            .iter()
+            .map(|entry| ConnectedClient {
+                id: entry.key().clone(),
+                info: entry.info.clone(),
+                connected_at: entry.connected_at,
+                idle_ms: entry.last_seen.elapsed().as_millis() as u64,
+                stream_id: entry.stream_id.clone(),
+            })
+            .collect();
+        clients.sort_by(|a, b| (a.connected_at, &a.id).cmp(&(b.connected_at, &b.id)));
+        clients
+    }
+
+    /// Closes connections whose client has been silent for longer than
+    /// `timeout`.
+    ///
+    /// Reaped connections are removed immediately, so the connection count
+    /// drops even if their handler is stuck writing to a dead socket.
+    ///
+    /// Returns the number of connections reaped.
+    pub fn reap_unresponsive(&self, timeout: Duration) -> usize {
+        let mut reaped = 0;
+        self.connections.retain(|id, state| {
+            let silent_for = state.last_seen.elapsed();
+            if silent_for <= timeout {
+                return true;
+            }
+            log::warn!(
+                "[WS] Connection {} unresponsive for {:.1}s, closing",
+                id,
+                silent_for.as_secs_f64()
+            );
+            state.cancel_token.cancel();
+            reaped += 1;
+            false
+        });
+        if reaped > 0 {
+            log::info!(
+                "[WS] Reaped {} connection(s) (remaining: {})",
+                reaped,
+                self.connections.len()
+            );
+        }
+        reaped
+    }
+
+    /// Starts the background task reaping unresponsive connections.
+    pub fn start_reaper(self: &Arc<Self>, spawner: &TokioSpawner, cancel: CancellationToken) {
+        let manager = Arc::clone(self);
+        spawner.spawn(async move {
+            let mut interval =
+                tokio::time::interval(Duration::from_secs(WS_PING_INTERVAL_SECS / 2));
+            interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            loop {
+                tokio::select! {
+                    _ = cancel.cancelled() => break,
+                    _ = interval.tick() => {
+                        manager.reap_unresponsive(Duration::from_secs(WS_HEARTBEAT_TIMEOUT_SECS));
+                    }
+                }
+            }
+        });
+    }
+
     /// Returns the number of active connections.
     #[must_use]
     pub fn connection_count(&self) -> usize {
@@ -139,6 +316,17 @@ impl ConnectionGuard {
     pub fn cancel_token(&self) -> &CancellationToken {
         &self.cancel_token
     }
+
+    /// Records that the client sent something, keeping the connection from
+    /// being reaped.
+    pub fn mark_alive(&self) {
+        self.manager.mark_alive(&self.id);
+    }
+
+    /// Records the stream this connection owns, shown in client listings.
+    pub fn set_stream_id(&self, stream_id: Option<&str>) {
+        self.manager.set_stream_id(&self.id, stream_id);
+    }
 }
 
 impl Drop for ConnectionGuard {
@@ -146,3 +334,52 @@ impl Drop for ConnectionGuard {
         self.manager.unregister(&self.id);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reaps_only_silent_connections() {
+        let manager = Arc::new(WsConnectionManager::new());
+        let silent = manager.register(ClientInfo::default());
+        let active = manager.register(ClientInfo::default());
+
+        std::thread::sleep(Duration::from_millis(20));
+        active.mark_alive();
+
+        assert_eq!(manager.reap_unresponsive(Duration::from_millis(10)), 1);
+        assert!(silent.cancel_token().is_cancelled());
+        assert!(!active.cancel_token().is_cancelled());
+        assert_eq!(manager.connection_count(), 1);
+
+        // The reaped handler exiting later doesn't disturb the count
+        drop(silent);
+        assert_eq!(manager.connection_count(), 1);
+    }
+
+    #[test]
+    fn lists_client_details() {
+        let manager = Arc::new(WsConnectionManager::new());
+        let guard = manager.register(ClientInfo {
+            name: ClientInfo::label(Some(" extension ".to_string())),
+            version: ClientInfo::label(Some(String::new())),
+            purpose: Some(ClientPurpose::Ingest),
+            protocol_version: 3,
+            ..Default::default()
+        });
+        guard.set_stream_id(Some("7d3c"));
+
+        let clients = manager.clients();
+        assert_eq!(clients.len(), 1);
+        assert_eq!(clients[0].id, guard.id());
+        assert_eq!(clients[0].info.name.as_deref(), Some("extension"));
+        assert_eq!(clients[0].info.version, None);
+        assert_eq!(clients[0].stream_id.as_deref(), Some("7d3c"));
+
+        let json = serde_json::to_value(&clients[0]).unwrap();
+        assert_eq!(json["purpose"], "ingest");
+        assert_eq!(json["protocolVersion"], 3);
+        assert_eq!(json["streamId"], "7d3c");
+    }
+}
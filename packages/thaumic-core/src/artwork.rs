@@ -7,6 +7,10 @@
 //! 2. **Local file** (`data_dir/artwork.jpg`): User-provided file in the data directory
 //! 3. **Embedded default**: Compile-time embedded artwork as fallback
 //!
+//! Artwork can also be uploaded through the API into an [`ArtworkLibrary`].
+//! An upload named [`DEFAULT_ARTWORK_NAME`] replaces the configured artwork,
+//! and others can be picked per cast or assigned to a quality preset.
+//!
 //! # Example
 //!
 //! ```ignore
@@ -21,10 +25,15 @@
 //! // Returns ArtworkSource::Url("https://cdn.example.com/artwork.jpg")
 //! ```
 
-use std::path::PathBuf;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 
 use bytes::Bytes;
+use image::codecs::jpeg::JpegEncoder;
+use image::imageops::FilterType;
+use parking_lot::RwLock;
 
+use crate::storage;
 use crate::DEFAULT_ARTWORK;
 
 /// The resolved artwork source for Sonos album art display.
@@ -157,6 +166,219 @@ impl ArtworkConfig {
     }
 }
 
+/// Longest side of uploaded artwork after resizing, in pixels. Sonos apps
+/// show album art no larger than this.
+const MAX_ARTWORK_SIZE: u32 = 600;
+
+/// Largest artwork upload accepted, in bytes.
+pub const MAX_ARTWORK_UPLOAD_BYTES: usize = 10 * 1024 * 1024;
+
+/// Folder in the data dir holding uploaded artwork.
+const ARTWORK_DIR: &str = "artwork";
+
+/// File in [`ARTWORK_DIR`] mapping quality presets to artwork names.
+const PRESETS_FILE: &str = "presets.json";
+
+/// Name of the uploaded artwork that replaces the configured default.
+pub const DEFAULT_ARTWORK_NAME: &str = "default";
+
+/// Errors from uploading and storing artwork.
+#[derive(Debug, thiserror::Error)]
+pub enum ArtworkError {
+    /// The upload isn't a JPEG or PNG image.
+    #[error("Unsupported image: {0}")]
+    Decode(String),
+
+    /// Artwork names may only use letters, digits, `-` and `_`.
+    #[error("Invalid artwork name: {0}")]
+    InvalidName(String),
+
+    /// No artwork with this name was uploaded.
+    #[error("No artwork named {0}")]
+    NotFound(String),
+
+    /// Uploads are stored in the data dir, which isn't set.
+    #[error("Artwork uploads require a data directory")]
+    NoDataDir,
+
+    /// Writing the image or the preset assignments failed.
+    #[error("Failed to store artwork: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+/// Decodes an uploaded image, shrinks it to fit [`MAX_ARTWORK_SIZE`] and
+/// re-encodes it as JPEG, the format every Sonos app displays.
+///
+/// # Errors
+///
+/// Returns [`ArtworkError::Decode`] if the bytes aren't a supported image.
+pub fn prepare_artwork(upload: &[u8]) -> Result<Bytes, ArtworkError> {
+    let image = image::load_from_memory(upload).map_err(|e| ArtworkError::Decode(e.to_string()))?;
+    let image = if image.width() > MAX_ARTWORK_SIZE || image.height() > MAX_ARTWORK_SIZE {
+        image.resize(MAX_ARTWORK_SIZE, MAX_ARTWORK_SIZE, FilterType::Lanczos3)
+    } else {
+        image
+    };
+
+    let mut jpeg = Vec::new();
+    image
+        .to_rgb8()
+        .write_with_encoder(JpegEncoder::new_with_quality(&mut jpeg, 85))
+        .map_err(|e| ArtworkError::Decode(e.to_string()))?;
+    Ok(Bytes::from(jpeg))
+}
+
+/// Uploaded artwork, stored as `data_dir/artwork/<name>.jpg`, and which
+/// quality preset each is shown for.
+#[derive(Default)]
+pub struct ArtworkLibrary {
+    dir: RwLock<Option<PathBuf>>,
+    images: RwLock<HashMap<String, Bytes>>,
+    /// Quality preset label (e.g. "aac 48kHz") to artwork name.
+    presets: RwLock<HashMap<String, String>>,
+}
+
+impl ArtworkLibrary {
+    /// Creates an empty library.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the data dir and loads the artwork stored there.
+    pub fn set_data_dir(&self, data_dir: impl AsRef<Path>) {
+        let dir = data_dir.as_ref().join(ARTWORK_DIR);
+        let mut images = HashMap::new();
+        if let Ok(entries) = std::fs::read_dir(&dir) {
+            for path in entries.filter_map(|entry| entry.ok().map(|entry| entry.path())) {
+                let name = path
+                    .file_stem()
+                    .and_then(|stem| stem.to_str())
+                    .filter(|stem| valid_name(stem));
+                let (Some(name), Some("jpg")) = (name, path.extension().and_then(|e| e.to_str()))
+                else {
+                    continue;
+                };
+                match std::fs::read(&path) {
+                    Ok(bytes) => {
+                        images.insert(name.to_string(), Bytes::from(bytes));
+                    }
+                    Err(e) => log::warn!("[Artwork] Failed to read {}: {}", path.display(), e),
+                }
+            }
+        }
+        let presets: HashMap<String, String> = std::fs::read(dir.join(PRESETS_FILE))
+            .ok()
+            .and_then(|json| serde_json::from_slice(&json).ok())
+            .unwrap_or_default();
+        if !images.is_empty() {
+            log::info!("[Artwork] Loaded {} uploaded image(s)", images.len());
+        }
+
+        *self.images.write() = images;
+        *self.presets.write() = presets;
+        *self.dir.write() = Some(dir);
+    }
+
+    /// Returns an uploaded image.
+    #[must_use]
+    pub fn get(&self, name: &str) -> Option<Bytes> {
+        self.images.read().get(name).cloned()
+    }
+
+    /// Returns the names of the uploaded images, sorted.
+    #[must_use]
+    pub fn names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.images.read().keys().cloned().collect();
+        names.sort();
+        names
+    }
+
+    /// Returns the preset labels assigned to each artwork name.
+    #[must_use]
+    pub fn presets(&self) -> HashMap<String, String> {
+        self.presets.read().clone()
+    }
+
+    /// Returns the artwork name assigned to a quality preset, if it exists.
+    #[must_use]
+    pub fn name_for_preset(&self, preset: &str) -> Option<String> {
+        let name = self.presets.read().get(preset).cloned()?;
+        self.images.read().contains_key(&name).then_some(name)
+    }
+
+    /// Resizes and stores an upload under `name`, replacing any image of
+    /// that name, and optionally shows it for casts using `preset`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the name or image is invalid, no data dir is set,
+    /// or writing fails.
+    pub fn save(
+        &self,
+        name: &str,
+        upload: &[u8],
+        preset: Option<&str>,
+    ) -> Result<(), ArtworkError> {
+        if !valid_name(name) {
+            return Err(ArtworkError::InvalidName(name.to_string()));
+        }
+        let dir = self.dir.read().clone().ok_or(ArtworkError::NoDataDir)?;
+        let jpeg = prepare_artwork(upload)?;
+
+        // Kept in memory only while the data directory is full or read-only
+        storage::tolerate(storage::write_atomic(&dir, &format!("{}.jpg", name), &jpeg))?;
+        log::info!("[Artwork] Stored '{}' ({} bytes)", name, jpeg.len());
+        self.images.write().insert(name.to_string(), jpeg);
+
+        if let Some(preset) = preset {
+            let mut presets = self.presets.write();
+            presets.insert(preset.to_string(), name.to_string());
+            save_presets(&dir, &presets)?;
+        }
+        Ok(())
+    }
+
+    /// Deletes an uploaded image and its preset assignments.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ArtworkError::NotFound`] if there's no such image, or an
+    /// I/O error if deleting fails.
+    pub fn remove(&self, name: &str) -> Result<(), ArtworkError> {
+        if self.images.write().remove(name).is_none() {
+            return Err(ArtworkError::NotFound(name.to_string()));
+        }
+        let Some(dir) = self.dir.read().clone() else {
+            return Ok(());
+        };
+        match std::fs::remove_file(dir.join(format!("{}.jpg", name))) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(e.into()),
+            _ => {}
+        }
+
+        let mut presets = self.presets.write();
+        let before = presets.len();
+        presets.retain(|_, assigned| assigned != name);
+        if presets.len() != before {
+            save_presets(&dir, &presets)?;
+        }
+        Ok(())
+    }
+}
+
+fn valid_name(name: &str) -> bool {
+    (1..=64).contains(&name.len())
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+fn save_presets(dir: &Path, presets: &HashMap<String, String>) -> std::io::Result<()> {
+    let json = serde_json::to_vec_pretty(presets).map_err(std::io::Error::other)?;
+    storage::tolerate(storage::write_atomic(dir, PRESETS_FILE, json))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -248,4 +470,64 @@ mod tests {
         // Should fall through to default, not use empty URL
         assert!(matches!(source, ArtworkSource::Bytes(_)));
     }
+
+    fn png(width: u32, height: u32) -> Vec<u8> {
+        let mut png = Vec::new();
+        image::RgbImage::new(width, height)
+            .write_to(&mut std::io::Cursor::new(&mut png), image::ImageFormat::Png)
+            .unwrap();
+        png
+    }
+
+    #[test]
+    fn uploads_are_resized_to_jpeg() {
+        let jpeg = prepare_artwork(&png(1200, 800)).unwrap();
+        let image = image::load_from_memory(&jpeg).unwrap();
+        assert_eq!((image.width(), image.height()), (600, 400));
+        assert_eq!(
+            image::guess_format(&jpeg).unwrap(),
+            image::ImageFormat::Jpeg
+        );
+
+        assert!(matches!(
+            prepare_artwork(b"not an image"),
+            Err(ArtworkError::Decode(_))
+        ));
+    }
+
+    #[test]
+    fn library_persists_images_and_preset_assignments() {
+        let temp_dir = TempDir::new().unwrap();
+        let library = ArtworkLibrary::new();
+        assert!(matches!(
+            library.save("podcast", &png(10, 10), None),
+            Err(ArtworkError::NoDataDir)
+        ));
+
+        library.set_data_dir(temp_dir.path());
+        assert!(matches!(
+            library.save("../escape", &png(10, 10), None),
+            Err(ArtworkError::InvalidName(_))
+        ));
+        library
+            .save("podcast", &png(10, 10), Some("aac 48kHz"))
+            .unwrap();
+        library.save("vinyl", &png(10, 10), None).unwrap();
+
+        let reloaded = ArtworkLibrary::new();
+        reloaded.set_data_dir(temp_dir.path());
+        assert_eq!(reloaded.names(), vec!["podcast", "vinyl"]);
+        assert_eq!(
+            reloaded.name_for_preset("aac 48kHz").as_deref(),
+            Some("podcast")
+        );
+
+        reloaded.remove("podcast").unwrap();
+        assert_eq!(reloaded.name_for_preset("aac 48kHz"), None);
+        assert!(reloaded.presets().is_empty());
+        assert!(matches!(
+            reloaded.remove("podcast"),
+            Err(ArtworkError::NotFound(_))
+        ));
+    }
 }
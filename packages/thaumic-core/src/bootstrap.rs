@@ -11,20 +11,31 @@ use std::sync::Arc;
 use std::time::Duration;
 
 use reqwest::Client;
-use tokio::sync::broadcast;
 use tokio_util::sync::CancellationToken;
 
-use crate::api::WsConnectionManager;
+use crate::api::{OriginPolicy, WsConnectionManager};
+use crate::artwork::ArtworkLibrary;
+use crate::clock::SystemClock;
 use crate::context::{LocalIpDetector, NetworkContext};
+use crate::enrichment::MetadataEnricher;
 use crate::error::{ThaumicError, ThaumicResult};
-use crate::events::{BroadcastEvent, BroadcastEventBridge, EventEmitter};
-use crate::protocol_constants::{EVENT_CHANNEL_CAPACITY, SOAP_TIMEOUT_SECS};
+use crate::events::{BroadcastEventBridge, EventEmitter};
+use crate::operations::OperationRegistry;
+use crate::outputs::OutputTargetRegistry;
+use crate::protocol_constants::SOAP_TIMEOUT_SECS;
 use crate::runtime::TokioSpawner;
-use crate::services::{DiscoveryService, LatencyMonitor, StreamCoordinator};
+use crate::services::{
+    DiscoveryService, LatencyMonitor, ListenerWatchdog, OriginApprovals, ResourceMonitor,
+    ResourceReaper, RtpReceiver, ScriptHooks, ScrobbleService, StateSnapshotter, StatsRecorder,
+    StorageMonitor, StreamCoordinator,
+};
+use crate::sonos::discovery::{DeviceDescriptionCache, DiscoveryConfig, RemoteSubnets};
 use crate::sonos::gena::GenaSubscriptionManager;
+use crate::sonos::soap::SoapClient;
 use crate::sonos::subscription_arbiter::SubscriptionArbiter;
 use crate::sonos::{SonosClient, SonosClientImpl, SonosPlayback, SonosTopologyClient};
-use crate::state::{Config, SonosState};
+use crate::state::{Config, GenaListener, SonosState};
+use crate::stats::StatsStore;
 use crate::streaming_runtime::StreamingRuntime;
 
 /// Container for all bootstrapped services.
@@ -41,8 +52,6 @@ pub struct BootstrappedServices {
     pub discovery_service: Arc<DiscoveryService>,
     /// Runtime state for discovered Sonos groups.
     pub sonos_state: Arc<SonosState>,
-    /// Broadcast channel sender for real-time events.
-    pub broadcast_tx: broadcast::Sender<BroadcastEvent>,
     /// Event bridge for emitting events to WebSocket and optional external consumers.
     pub event_bridge: Arc<BroadcastEventBridge>,
     /// Network configuration (port, local IP).
@@ -51,6 +60,32 @@ pub struct BootstrappedServices {
     pub ws_manager: Arc<WsConnectionManager>,
     /// Latency monitoring service.
     pub latency_monitor: Arc<LatencyMonitor>,
+    /// Process CPU/memory/network sampling service.
+    pub resource_monitor: Arc<ResourceMonitor>,
+    /// Reports a full or read-only data directory.
+    pub storage_monitor: Arc<StorageMonitor>,
+    /// Periodically cleans up leaked streams, sessions and subscriptions.
+    pub resource_reaper: Arc<ResourceReaper>,
+    /// Stops streams without listeners, unless idle streams are kept.
+    pub listener_watchdog: Option<Arc<ListenerWatchdog>>,
+    /// Scrobble submission service, when any scrobble service is configured.
+    pub scrobble_service: Option<Arc<ScrobbleService>>,
+    /// Persistent casting statistics.
+    pub stats: Arc<StatsStore>,
+    /// Records casting statistics from stream events.
+    pub stats_recorder: Arc<StatsRecorder>,
+    /// Runs user scripts from the data dir on events.
+    pub script_hooks: Arc<ScriptHooks>,
+    /// Sends debounced Sonos state snapshots and patches.
+    pub state_snapshotter: Arc<StateSnapshotter>,
+    /// In-flight long-running operations (discovery, probes, bandwidth tests).
+    pub operations: Arc<OperationRegistry>,
+    /// Speaker device descriptions kept across discovery runs and restarts.
+    pub description_cache: Arc<DeviceDescriptionCache>,
+    /// Ranges on other VLANs that discovery searches by unicast.
+    pub remote_subnets: Arc<RemoteSubnets>,
+    /// Pooled SOAP client used for all speaker control requests.
+    pub soap_client: SoapClient,
     /// Dedicated high-priority runtime for HTTP streaming.
     pub streaming_runtime: Arc<StreamingRuntime>,
     /// Shared HTTP client for connection pooling.
@@ -59,6 +94,16 @@ pub struct BootstrappedServices {
     pub spawner: TokioSpawner,
     /// Cancellation token for graceful shutdown.
     pub cancel_token: CancellationToken,
+    /// Origins allowed to call the API from a browser, including extensions
+    /// the user approved.
+    pub origin_approvals: Arc<OriginApprovals>,
+    /// RTP ingest listener, when an RTP port is configured.
+    pub rtp_receiver: Option<Arc<RtpReceiver>>,
+    /// Playback backends for non-Sonos devices. Empty until the app
+    /// registers some.
+    pub output_targets: Arc<OutputTargetRegistry>,
+    /// Artwork uploaded through the API, stored in the data dir.
+    pub artwork_library: Arc<ArtworkLibrary>,
 }
 
 impl BootstrappedServices {
@@ -67,16 +112,72 @@ impl BootstrappedServices {
         &self.http_client
     }
 
+    /// Sets the directory for persisted data (manual and ignored speakers,
+    /// remote subnets, statistics, cached device descriptions, approved extensions, scripts,
+    /// uploaded artwork), first restoring a backup imported since the last
+    /// start and upgrading files saved by older versions.
+    ///
+    /// Call before `start_background_tasks()` so the initial topology refresh
+    /// includes manual speakers.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ThaumicError::StorageUnavailable`] if the directory is full
+    /// or read-only. Everything is still set up; changes are kept in memory
+    /// until the directory can be written again.
+    pub fn set_app_data_dir(&self, path: impl AsRef<std::path::Path>) -> ThaumicResult<()> {
+        let storage = self.storage_monitor.set_data_dir(path.as_ref());
+        crate::storage::backup::apply_pending(path.as_ref());
+        if let Err(e) = crate::storage::migrations::run(path.as_ref()) {
+            log::error!("[Bootstrap] Failed to record data format versions: {}", e);
+        }
+        self.discovery_service.set_app_data_dir(path.as_ref());
+        self.stats.set_data_dir(path.as_ref());
+        self.origin_approvals.set_data_dir(path.as_ref());
+        self.script_hooks.set_data_dir(path.as_ref());
+        self.artwork_library.set_data_dir(path.as_ref());
+        self.remote_subnets.set_data_dir(path.as_ref());
+        self.description_cache.set_data_dir(path);
+        storage
+    }
+
     /// Starts all background services.
     ///
     /// This includes:
     /// - GENA subscription renewal task
     /// - Sonos topology monitor
     /// - Latency monitor
+    /// - Resource monitor
+    /// - Storage monitor
+    /// - Resource reaper
+    /// - Listener watchdog (if enabled)
+    /// - Scrobble service (if configured)
+    /// - Statistics recorder
+    /// - Script hooks (if the data dir has scripts)
+    /// - State snapshotter
+    /// - WebSocket connection reaper
+    /// - RTP ingest listener (if configured)
     pub fn start_background_tasks(&self) {
         self.discovery_service.start_renewal_task();
         Arc::clone(&self.discovery_service).start_topology_monitor();
         self.latency_monitor.start();
+        self.resource_monitor.start();
+        self.storage_monitor.start();
+        self.resource_reaper.start();
+        if let Some(listener_watchdog) = &self.listener_watchdog {
+            listener_watchdog.start();
+        }
+        if let Some(scrobble_service) = &self.scrobble_service {
+            scrobble_service.start();
+        }
+        self.stats_recorder.start();
+        self.script_hooks.start();
+        self.state_snapshotter.start();
+        self.ws_manager
+            .start_reaper(&self.spawner, self.cancel_token.clone());
+        if let Some(rtp_receiver) = &self.rtp_receiver {
+            rtp_receiver.start();
+        }
     }
 
     /// Initiates graceful shutdown of all services.
@@ -93,6 +194,13 @@ impl BootstrappedServices {
         // Unsubscribe from all GENA events
         self.discovery_service.shutdown().await;
 
+        if let Err(e) = self.stats.flush() {
+            log::warn!("[Bootstrap] Failed to save stats: {}", e);
+        }
+        if let Err(e) = self.description_cache.flush() {
+            log::warn!("[Bootstrap] Failed to save device description cache: {}", e);
+        }
+
         log::info!("[Bootstrap] Shutdown complete");
     }
 
@@ -186,24 +294,65 @@ pub fn bootstrap_services_with_network(
     // Create task spawner with explicit handle
     let spawner = TokioSpawner::new(runtime_handle);
 
+    // Callback URLs must point at a dedicated GENA listener from the first
+    // subscription on, so its port has to be fixed up front
+    if let GenaListener::Dedicated(port) = config.gena_listener {
+        if port == 0 {
+            return Err(ThaumicError::InvalidRequest(
+                "A dedicated GENA listener needs a fixed port".into(),
+            ));
+        }
+        network.set_gena_port(Some(port));
+    }
+    let origin_policy = OriginPolicy::trusted_with(&config.allowed_origins)?;
+    config.ingest.validate()?;
+
     // Create shared HTTP client for connection pooling
     let http_client = create_http_client();
 
-    // Create broadcast channel for real-time events to WebSocket clients
-    let (broadcast_tx, _) = broadcast::channel::<BroadcastEvent>(EVENT_CHANNEL_CAPACITY);
-
-    // Create the event bridge that maps domain events to broadcast transport
-    let event_bridge = Arc::new(BroadcastEventBridge::with_sender(broadcast_tx.clone()));
+    // Create the event bridge that maps domain events to the prioritized
+    // broadcast channels WebSocket clients subscribe to
+    let event_bridge = Arc::new(BroadcastEventBridge::new());
+    for sink in config.event_log.sinks() {
+        event_bridge.add_external_emitter(sink);
+    }
+    let origin_approvals = Arc::new(OriginApprovals::new(
+        origin_policy,
+        Arc::clone(&event_bridge) as Arc<dyn EventEmitter>,
+    ));
 
     // Create cancellation token for graceful shutdown
     let cancel_token = CancellationToken::new();
 
     // Shared mutable state
     let sonos_state = Arc::new(SonosState::default());
+    sonos_state
+        .ignored_speakers
+        .set_configured(&config.ignored_speakers);
     let ws_manager = Arc::new(WsConnectionManager::new());
 
-    // Create the Sonos client (implements multiple traits)
-    let sonos_impl = Arc::new(SonosClientImpl::new(http_client.clone()));
+    // Create the Sonos client (implements multiple traits). Device descriptions
+    // are cached across runs once a data dir is set.
+    let description_cache = Arc::new(DeviceDescriptionCache::new());
+    let soap_client = SoapClient::new();
+    let remote_subnets = Arc::new(RemoteSubnets::default());
+    remote_subnets.set_configured(&config.remote_subnets);
+    let mut discovery_config = if config.unicast_only {
+        DiscoveryConfig::unicast_only()
+    } else {
+        let mut discovery_config = DiscoveryConfig {
+            subnet_scan_enabled: config.subnet_scan,
+            ..DiscoveryConfig::default()
+        };
+        discovery_config.subnet_scan.cidrs = config.subnet_scan_cidrs.clone();
+        discovery_config
+    };
+    discovery_config.remote_subnets = Arc::clone(&remote_subnets);
+    let sonos_impl = Arc::new(
+        SonosClientImpl::new(soap_client.clone())
+            .with_description_cache(Arc::clone(&description_cache))
+            .with_discovery_config(discovery_config),
+    );
 
     // Validate streaming config (panics early if invalid)
     config
@@ -211,9 +360,13 @@ pub fn bootstrap_services_with_network(
         .validate()
         .expect("Invalid streaming configuration");
 
+    // Time source for renewal, polling and refresh schedules
+    let clock = SystemClock::arc();
+
     // Create gena_manager first (shared between StreamCoordinator and DiscoveryService)
-    let (gena_manager, gena_event_rx) = GenaSubscriptionManager::new(http_client.clone());
-    let gena_manager = Arc::new(gena_manager);
+    let (gena_manager, gena_event_rx) =
+        GenaSubscriptionManager::new(http_client.clone(), Arc::clone(&clock));
+    let gena_manager = Arc::new(gena_manager.with_network(network.clone()));
 
     // Topology refresh notifier — shared between StreamCoordinator, GenaEventProcessor,
     // and TopologyMonitor. Any party can signal it to trigger an immediate topology fetch.
@@ -232,6 +385,11 @@ pub fn bootstrap_services_with_network(
         Arc::clone(&arbiter),
     );
     stream_coordinator.set_topology_refresh(Arc::clone(&refresh_notify));
+    if let Some(enricher) =
+        MetadataEnricher::from_config(&config.enrichment, http_client.clone(), spawner.clone())
+    {
+        stream_coordinator.set_metadata_enricher(Arc::new(enricher));
+    }
     let stream_coordinator = Arc::new(stream_coordinator);
 
     // Wire up latency monitor with its dependencies
@@ -241,8 +399,98 @@ pub fn bootstrap_services_with_network(
         Arc::clone(&event_bridge) as Arc<dyn EventEmitter>,
         cancel_token.clone(),
         spawner.clone(),
+        Arc::clone(&clock),
+    ));
+
+    // Wire up resource monitor (samples CPU/memory/network, warns on overload)
+    let resource_monitor = Arc::new(ResourceMonitor::new(
+        stream_coordinator.stream_registry(),
+        Arc::clone(&event_bridge) as Arc<dyn EventEmitter>,
+        cancel_token.clone(),
+        spawner.clone(),
+    ));
+
+    // Wire up the storage monitor (reports a full or read-only data dir)
+    let storage_monitor = Arc::new(StorageMonitor::new(
+        Arc::clone(&event_bridge) as Arc<dyn EventEmitter>,
+        cancel_token.clone(),
+        spawner.clone(),
+    ));
+
+    // Wire up the resource reaper (audits for leaked resources)
+    let resource_reaper = Arc::new(ResourceReaper::new(
+        Arc::clone(&stream_coordinator),
+        Arc::clone(&latency_monitor),
+        Arc::clone(&gena_manager),
+        Arc::clone(&sonos_state),
+        cancel_token.clone(),
+        spawner.clone(),
+    ));
+
+    // Wire up the listener watchdog (stops streams speakers stopped pulling)
+    let listener_watchdog = ListenerWatchdog::from_config(
+        &config.streaming,
+        Arc::clone(&stream_coordinator),
+        cancel_token.clone(),
+        spawner.clone(),
+    )
+    .map(Arc::new);
+
+    // Wire up scrobbling (subscribes now so no early playback events are missed)
+    let scrobble_sinks = config.scrobbling.sinks(&http_client);
+    let scrobble_service = (!scrobble_sinks.is_empty()).then(|| {
+        Arc::new(ScrobbleService::new(
+            scrobble_sinks,
+            Duration::from_secs(config.scrobbling.scrobble_after_secs()),
+            event_bridge.subscribe(),
+            cancel_token.clone(),
+            spawner.clone(),
+        ))
+    });
+
+    // Wire up statistics (local-only counters, persisted once a data dir is set)
+    let stats = Arc::new(StatsStore::new());
+    let stats_recorder = Arc::new(StatsRecorder::new(
+        Arc::clone(&stats),
+        stream_coordinator.stream_registry(),
+        Arc::clone(&sonos_state),
+        event_bridge.subscribe(),
+        cancel_token.clone(),
+        spawner.clone(),
+    ));
+
+    // Wire up script hooks (scripts are loaded from the data dir on start)
+    let script_hooks = Arc::new(ScriptHooks::new(
+        Arc::clone(&stream_coordinator),
+        Arc::clone(&sonos_impl) as Arc<dyn SonosClient>,
+        Arc::clone(&sonos_state),
+        http_client.clone(),
+        event_bridge.subscribe(),
+        cancel_token.clone(),
+        spawner.clone(),
+    ));
+
+    // Wire up coalesced state updates for clients with large households
+    let state_snapshotter = Arc::new(StateSnapshotter::new(
+        Arc::clone(&sonos_state),
+        Arc::clone(&event_bridge) as Arc<dyn EventEmitter>,
+        event_bridge.subscribe(),
+        cancel_token.clone(),
+        spawner.clone(),
     ));
 
+    // Wire up RTP ingest (binds its port once background tasks start)
+    let rtp_receiver = RtpReceiver::from_config(
+        &config.ingest.rtp,
+        Arc::clone(&stream_coordinator),
+        cancel_token.clone(),
+        spawner.clone(),
+    )
+    .map(Arc::new);
+
+    // Long-running operations register here so clients can list and cancel them
+    let operations = Arc::new(OperationRegistry::new());
+
     // Wire up discovery service with its dependencies (gena_manager is passed in, not created internally)
     let discovery_service = Arc::new(DiscoveryService::new(
         Arc::clone(&sonos_impl) as Arc<dyn SonosTopologyClient>,
@@ -252,11 +500,14 @@ pub fn bootstrap_services_with_network(
         network.clone(),
         http_client.clone(),
         config.topology_refresh_interval,
+        config.unicast_only,
         spawner.clone(),
         gena_manager,
         gena_event_rx,
         refresh_notify,
         arbiter,
+        Arc::clone(&operations),
+        clock,
     ));
 
     // Coerce to the general SonosClient trait for storage
@@ -267,15 +518,31 @@ pub fn bootstrap_services_with_network(
         stream_coordinator,
         discovery_service,
         sonos_state,
-        broadcast_tx,
         event_bridge,
         network,
         ws_manager,
         latency_monitor,
+        resource_monitor,
+        storage_monitor,
+        resource_reaper,
+        listener_watchdog,
+        scrobble_service,
+        stats,
+        stats_recorder,
+        script_hooks,
+        state_snapshotter,
+        operations,
+        description_cache,
+        remote_subnets,
+        soap_client,
         streaming_runtime,
         http_client,
         spawner,
         cancel_token,
+        origin_approvals,
+        rtp_receiver,
+        output_targets: Arc::new(OutputTargetRegistry::new()),
+        artwork_library: Arc::new(ArtworkLibrary::new()),
     })
 }
 
@@ -0,0 +1,175 @@
+//! Time source for background services.
+//!
+//! Renewal, polling and refresh loops read the time and sleep through a
+//! [`Clock`] instead of calling `Instant::now()` or `tokio::time` directly,
+//! so tests can drive them with a [`MockClock`] and step through hours of
+//! timeouts without waiting.
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use futures::future::BoxFuture;
+use parking_lot::Mutex;
+use tokio::sync::oneshot;
+
+use crate::utils::now_millis;
+
+/// Source of monotonic time, wall-clock time and sleeps.
+pub trait Clock: Send + Sync {
+    /// Returns the current monotonic time.
+    fn now(&self) -> Instant;
+
+    /// Returns the current wall-clock time in milliseconds since the Unix epoch.
+    fn now_millis(&self) -> u64;
+
+    /// Returns a future that completes once `duration` has passed on this clock.
+    fn sleep(&self, duration: Duration) -> BoxFuture<'static, ()>;
+}
+
+/// The real clock, backed by `Instant`, `SystemTime` and `tokio::time`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl SystemClock {
+    /// Returns a shared system clock.
+    #[must_use]
+    pub fn arc() -> Arc<dyn Clock> {
+        Arc::new(Self)
+    }
+}
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+
+    fn now_millis(&self) -> u64 {
+        now_millis()
+    }
+
+    fn sleep(&self, duration: Duration) -> BoxFuture<'static, ()> {
+        Box::pin(tokio::time::sleep(duration))
+    }
+}
+
+/// A clock that only moves when told to.
+///
+/// Starts at the moment it is created. Sleeps complete when [`advance`]
+/// moves the clock past their deadline.
+///
+/// [`advance`]: MockClock::advance
+pub struct MockClock {
+    start: Instant,
+    start_millis: u64,
+    state: Mutex<MockState>,
+}
+
+struct MockState {
+    elapsed: Duration,
+    sleepers: Vec<(Duration, oneshot::Sender<()>)>,
+}
+
+impl MockClock {
+    /// Creates a mock clock stopped at the current time.
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            start: Instant::now(),
+            start_millis: now_millis(),
+            state: Mutex::new(MockState {
+                elapsed: Duration::ZERO,
+                sleepers: Vec::new(),
+            }),
+        }
+    }
+
+    /// Moves the clock forward, waking every sleep whose deadline has passed.
+    pub fn advance(&self, duration: Duration) {
+        let mut state = self.state.lock();
+        state.elapsed += duration;
+        let now = state.elapsed;
+        // Dropping a sleeper's sender wakes it
+        state.sleepers.retain(|(deadline, _)| *deadline > now);
+    }
+
+    /// Returns how many sleeps are waiting for the clock to advance.
+    #[must_use]
+    pub fn pending_sleeps(&self) -> usize {
+        self.state.lock().sleepers.len()
+    }
+}
+
+impl Default for MockClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clock for MockClock {
+    fn now(&self) -> Instant {
+        self.start + self.state.lock().elapsed
+    }
+
+    fn now_millis(&self) -> u64 {
+        self.start_millis + self.state.lock().elapsed.as_millis() as u64
+    }
+
+    fn sleep(&self, duration: Duration) -> BoxFuture<'static, ()> {
+        let (tx, rx) = oneshot::channel();
+        {
+            let mut state = self.state.lock();
+            if duration.is_zero() {
+                drop(tx);
+            } else {
+                let deadline = state.elapsed + duration;
+                state.sleepers.push((deadline, tx));
+            }
+        }
+        Box::pin(async move {
+            // The sender is dropped once the deadline passes
+            let _ = rx.await;
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::FutureExt;
+
+    #[test]
+    fn mock_clock_only_moves_when_advanced() {
+        let clock = MockClock::new();
+        let start = clock.now();
+        let start_millis = clock.now_millis();
+
+        assert_eq!(clock.now(), start);
+        clock.advance(Duration::from_secs(90));
+        assert_eq!(clock.now() - start, Duration::from_secs(90));
+        assert_eq!(clock.now_millis() - start_millis, 90_000);
+    }
+
+    #[test]
+    fn mock_sleep_completes_once_deadline_passes() {
+        let clock = MockClock::new();
+        let mut short = clock.sleep(Duration::from_secs(10));
+        let mut long = clock.sleep(Duration::from_secs(60));
+        assert_eq!(clock.pending_sleeps(), 2);
+        assert!((&mut short).now_or_never().is_none());
+
+        clock.advance(Duration::from_secs(10));
+        assert!((&mut short).now_or_never().is_some());
+        assert!((&mut long).now_or_never().is_none());
+        assert_eq!(clock.pending_sleeps(), 1);
+
+        clock.advance(Duration::from_secs(50));
+        assert!(long.now_or_never().is_some());
+        assert_eq!(clock.pending_sleeps(), 0);
+    }
+
+    #[test]
+    fn zero_sleep_is_immediately_ready() {
+        let clock = MockClock::new();
+        assert!(clock.sleep(Duration::ZERO).now_or_never().is_some());
+    }
+}
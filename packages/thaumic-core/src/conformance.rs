@@ -0,0 +1,473 @@
+//! Protocol conformance tests against the shared golden fixtures.
+//!
+//! `packages/protocol/fixtures` holds JSON samples of every message the
+//! server exchanges with clients. The TypeScript package parses the same files
+//! with its zod schemas, so a serde change that isn't mirrored on the client
+//! (or the other way round) fails one of the two test suites instead of
+//! surfacing as a runtime error in the extension.
+//!
+//! Each fixture file is an object keyed by message name. Tests build the
+//! matching Rust value for every key and compare the serialized output.
+
+use std::collections::BTreeSet;
+
+use serde_json::{Map, Value};
+
+use crate::events::{
+    BroadcastEvent, CrossSubnetHint, LatencyEvent, LockedSpeakerChange, NetworkEvent,
+    NetworkHealth, ResourceEvent, SonosEvent, SpeakerRemovalReason, StateEvent, StreamEvent,
+    TopologyEvent,
+};
+use crate::sonos::services::SonosService;
+use crate::sonos::types::{DeviceRole, TransportState, ZoneGroup, ZoneGroupMember};
+use crate::state::SonosState;
+use crate::storage::StorageIssue;
+use crate::stream::{FeedbackCause, StreamMetadata};
+
+/// Broadcast events, keyed `category.type`.
+pub(crate) const EVENTS: &str = include_str!("../../protocol/fixtures/events.json");
+
+/// Server → client WebSocket messages, keyed by type.
+pub(crate) const WS_SERVER: &str = include_str!("../../protocol/fixtures/ws-server.json");
+
+/// Client → server WebSocket messages, keyed by type.
+pub(crate) const WS_CLIENT: &str = include_str!("../../protocol/fixtures/ws-client.json");
+
+const STREAM_ID: &str = "7d3c";
+const SPEAKER_IP: &str = "192.168.1.20";
+const STREAM_URL: &str = "http://192.168.1.10:49400/stream/7d3c/live.flac";
+const TIMESTAMP: u64 = 1_700_000_000_000;
+
+/// Parses a fixture file into its named samples.
+pub(crate) fn fixtures(json: &str) -> Map<String, Value> {
+    match serde_json::from_str(json) {
+        Ok(Value::Object(map)) => map,
+        other => panic!("fixture file is not a JSON object: {:?}", other),
+    }
+}
+
+/// Asserts `samples` cover exactly the fixtures in `json` and serialize to them.
+pub(crate) fn assert_serializes_to_fixtures(json: &str, samples: Vec<(&str, Value)>) {
+    let fixtures = fixtures(json);
+
+    let fixture_names: BTreeSet<&str> = fixtures.keys().map(String::as_str).collect();
+    let sample_names: BTreeSet<&str> = samples.iter().map(|(name, _)| *name).collect();
+    assert_eq!(
+        fixture_names, sample_names,
+        "fixtures and Rust samples cover different messages"
+    );
+
+    for (name, value) in samples {
+        assert_eq!(value, fixtures[name], "{} does not match its fixture", name);
+    }
+}
+
+/// A one-speaker group, as used by the fixtures.
+pub(crate) fn sample_group() -> ZoneGroup {
+    ZoneGroup {
+        id: "RINCON_000E58A0B1C201400:1".to_string(),
+        name: "Living Room".to_string(),
+        label: "Living Room".to_string(),
+        room_count: 1,
+        coordinator_uuid: "RINCON_000E58A0B1C201400".to_string(),
+        coordinator_ip: SPEAKER_IP.to_string(),
+        members: vec![ZoneGroupMember {
+            uuid: "RINCON_000E58A0B1C201400".to_string(),
+            ip: SPEAKER_IP.to_string(),
+            zone_name: "Living Room".to_string(),
+            model: "one".to_string(),
+            role: DeviceRole::Player,
+            bond: None,
+            wired: None,
+        }],
+    }
+}
+
+fn sample_events() -> Vec<(&'static str, BroadcastEvent)> {
+    let s = str::to_string;
+    let sonos_state = SonosState::default();
+    sonos_state
+        .transport_states
+        .insert(s(SPEAKER_IP), TransportState::Playing);
+    sonos_state.group_volumes.insert(s(SPEAKER_IP), 35);
+    sonos_state.group_mutes.insert(s(SPEAKER_IP), false);
+    sonos_state.group_volume_fixed.insert(s(SPEAKER_IP), false);
+
+    vec![
+        (
+            "sonos.transportState",
+            SonosEvent::TransportState {
+                speaker_ip: s(SPEAKER_IP),
+                state: TransportState::Playing,
+                current_uri: Some(s(
+                    "x-rincon-mp3radio://192.168.1.10:49400/stream/7d3c/live.flac",
+                )),
+                timestamp: TIMESTAMP,
+            }
+            .into(),
+        ),
+        (
+            "sonos.groupVolume",
+            SonosEvent::GroupVolume {
+                speaker_ip: s(SPEAKER_IP),
+                volume: 35,
+                fixed: Some(false),
+                timestamp: TIMESTAMP,
+            }
+            .into(),
+        ),
+        (
+            "sonos.groupMute",
+            SonosEvent::GroupMute {
+                speaker_ip: s(SPEAKER_IP),
+                muted: true,
+                timestamp: TIMESTAMP,
+            }
+            .into(),
+        ),
+        (
+            "sonos.sourceChanged",
+            SonosEvent::SourceChanged {
+                speaker_ip: s(SPEAKER_IP),
+                current_uri: s("x-sonos-spotify:spotify%3atrack%3a4uLU6hMCjMI75M1A2tKUQC"),
+                expected_uri: Some(s(
+                    "x-rincon-mp3radio://192.168.1.10:49400/stream/7d3c/live.flac",
+                )),
+                timestamp: TIMESTAMP,
+            }
+            .into(),
+        ),
+        (
+            "sonos.zoneGroupsUpdated",
+            SonosEvent::ZoneGroupsUpdated {
+                groups: vec![sample_group()],
+                timestamp: TIMESTAMP,
+            }
+            .into(),
+        ),
+        (
+            "sonos.subscriptionLost",
+            SonosEvent::SubscriptionLost {
+                speaker_ip: s(SPEAKER_IP),
+                service: SonosService::AVTransport,
+                reason: s("Renewal failed: speaker unreachable"),
+            }
+            .into(),
+        ),
+        (
+            "stream.created",
+            StreamEvent::Created {
+                stream_id: s(STREAM_ID),
+                timestamp: TIMESTAMP,
+            }
+            .into(),
+        ),
+        (
+            "stream.ended",
+            StreamEvent::Ended {
+                stream_id: s(STREAM_ID),
+                timestamp: TIMESTAMP,
+            }
+            .into(),
+        ),
+        (
+            "stream.metadataUpdated",
+            StreamEvent::MetadataUpdated {
+                stream_id: s(STREAM_ID),
+                metadata: StreamMetadata {
+                    title: Some(s("Song")),
+                    artist: Some(s("Band")),
+                    album: Some(s("Record")),
+                    artwork_url: Some(s("https://coverartarchive.org/release/1/front-500")),
+                    source: Some(s("YouTube")),
+                    source_url: Some(s("https://www.youtube.com/watch?v=dQw4w9WgXcQ")),
+                    source_title: Some(s("Song - YouTube")),
+                    favicon_url: Some(s("https://www.youtube.com/favicon.ico")),
+                },
+                timestamp: TIMESTAMP,
+            }
+            .into(),
+        ),
+        (
+            "stream.playbackStarted",
+            StreamEvent::PlaybackStarted {
+                stream_id: s(STREAM_ID),
+                speaker_ip: s(SPEAKER_IP),
+                stream_url: s(STREAM_URL),
+                timestamp: TIMESTAMP,
+            }
+            .into(),
+        ),
+        (
+            "stream.playbackStopped",
+            StreamEvent::PlaybackStopped {
+                stream_id: s(STREAM_ID),
+                speaker_ip: s(SPEAKER_IP),
+                reason: Some(SpeakerRemovalReason::SourceChanged),
+                timestamp: TIMESTAMP,
+            }
+            .into(),
+        ),
+        (
+            "stream.playbackStopFailed",
+            StreamEvent::PlaybackStopFailed {
+                stream_id: s(STREAM_ID),
+                speaker_ip: s(SPEAKER_IP),
+                error: s("SOAP request timed out"),
+                reason: Some(SpeakerRemovalReason::UserRemoved),
+                timestamp: TIMESTAMP,
+            }
+            .into(),
+        ),
+        (
+            "stream.listenersLost",
+            StreamEvent::ListenersLost {
+                stream_id: s(STREAM_ID),
+                idle_secs: 600,
+                timestamp: TIMESTAMP,
+            }
+            .into(),
+        ),
+        (
+            "stream.labelChanged",
+            StreamEvent::LabelChanged {
+                stream_id: s(STREAM_ID),
+                label: Some(s("Vinyl rip")),
+                timestamp: TIMESTAMP,
+            }
+            .into(),
+        ),
+        (
+            "stream.playbackSuspended",
+            StreamEvent::PlaybackSuspended {
+                stream_id: s(STREAM_ID),
+                speaker_ip: s(SPEAKER_IP),
+                current_uri: s("x-sonos-spotify:spotify%3atrack%3a4uLU6hMCjMI75M1A2tKUQC"),
+                resume_window_secs: 60,
+                timestamp: TIMESTAMP,
+            }
+            .into(),
+        ),
+        (
+            "stream.playbackStoppedExternally",
+            StreamEvent::PlaybackStoppedExternally {
+                stream_id: s(STREAM_ID),
+                speaker_ip: s(SPEAKER_IP),
+                auto_resume: false,
+                confirm_window_secs: Some(60),
+                timestamp: TIMESTAMP,
+            }
+            .into(),
+        ),
+        (
+            "stream.lockedSpeakerChanged",
+            StreamEvent::LockedSpeakerChanged {
+                stream_id: s(STREAM_ID),
+                speaker_ip: s(SPEAKER_IP),
+                change: LockedSpeakerChange::Volume,
+                timestamp: TIMESTAMP,
+            }
+            .into(),
+        ),
+        (
+            "stream.playbackResumed",
+            StreamEvent::PlaybackResumed {
+                stream_id: s(STREAM_ID),
+                speaker_ip: s(SPEAKER_IP),
+                timestamp: TIMESTAMP,
+            }
+            .into(),
+        ),
+        (
+            "stream.bufferAdapted",
+            StreamEvent::BufferAdapted {
+                stream_id: s(STREAM_ID),
+                speaker_ip: s(SPEAKER_IP),
+                previous_buffer_ms: 200,
+                buffer_ms: 400,
+                jitter_ms: 35,
+                timestamp: TIMESTAMP,
+            }
+            .into(),
+        ),
+        (
+            "stream.feedbackLoopSuspected",
+            StreamEvent::FeedbackLoopSuspected {
+                stream_id: s(STREAM_ID),
+                cause: FeedbackCause::Audio,
+                loop_delay_ms: Some(1850),
+                timestamp: TIMESTAMP,
+            }
+            .into(),
+        ),
+        (
+            "stream.position",
+            StreamEvent::Position {
+                stream_id: s(STREAM_ID),
+                speaker_ip: s(SPEAKER_IP),
+                epoch_id: 2,
+                rel_time_ms: 63_000,
+                track_duration_ms: None,
+                stream_elapsed_ms: 64_450,
+                timestamp: TIMESTAMP,
+            }
+            .into(),
+        ),
+        (
+            "network.healthChanged",
+            NetworkEvent::HealthChanged {
+                health: NetworkHealth::Degraded,
+                reason: Some(s("Speakers discovered but not responding")),
+                timestamp: TIMESTAMP,
+            }
+            .into(),
+        ),
+        (
+            "network.originApprovalRequested",
+            NetworkEvent::OriginApprovalRequested {
+                origin: s("chrome-extension://abcdefghijklmnopabcdefghijklmnop"),
+                timestamp: TIMESTAMP,
+            }
+            .into(),
+        ),
+        (
+            "network.crossSubnetSpeakers",
+            NetworkEvent::CrossSubnetSpeakers {
+                local_ip: s("192.168.1.100"),
+                speaker_ips: vec![s("10.0.20.30")],
+                hints: vec![
+                    CrossSubnetHint::MdnsReflector,
+                    CrossSubnetHint::IgmpSnooping,
+                    CrossSubnetHint::UnicastOnly,
+                    CrossSubnetHint::AllowInbound,
+                ],
+                timestamp: TIMESTAMP,
+            }
+            .into(),
+        ),
+        (
+            "topology.groupsDiscovered",
+            TopologyEvent::GroupsDiscovered {
+                groups: vec![],
+                timestamp: TIMESTAMP,
+            }
+            .into(),
+        ),
+        (
+            "topology.speakerAdded",
+            TopologyEvent::SpeakerAdded {
+                speaker: sample_group().members.remove(0),
+                timestamp: TIMESTAMP,
+            }
+            .into(),
+        ),
+        (
+            "topology.speakerRemoved",
+            TopologyEvent::SpeakerRemoved {
+                uuid: s("RINCON_000E58A0B1C201400"),
+                speaker_ip: s(SPEAKER_IP),
+                zone_name: s("Living Room"),
+                timestamp: TIMESTAMP,
+            }
+            .into(),
+        ),
+        (
+            "topology.speakerIpChanged",
+            TopologyEvent::SpeakerIpChanged {
+                uuid: s("RINCON_000E58A0B1C201400"),
+                zone_name: s("Living Room"),
+                old_ip: s("192.168.1.21"),
+                new_ip: s(SPEAKER_IP),
+                timestamp: TIMESTAMP,
+            }
+            .into(),
+        ),
+        (
+            "latency.updated",
+            LatencyEvent::Updated {
+                stream_id: s(STREAM_ID),
+                speaker_ip: s(SPEAKER_IP),
+                epoch_id: 2,
+                latency_ms: 1450,
+                jitter_ms: 12,
+                confidence: 0.5,
+                timestamp: TIMESTAMP,
+            }
+            .into(),
+        ),
+        (
+            "latency.stale",
+            LatencyEvent::Stale {
+                stream_id: s(STREAM_ID),
+                speaker_ip: s(SPEAKER_IP),
+                epoch_id: 2,
+                timestamp: TIMESTAMP,
+            }
+            .into(),
+        ),
+        (
+            "resource.cpuOverload",
+            ResourceEvent::CpuOverload {
+                cpu_percent: 92.5,
+                sustained_secs: 15,
+                active_streams: 2,
+                timestamp: TIMESTAMP,
+            }
+            .into(),
+        ),
+        (
+            "resource.cpuRecovered",
+            ResourceEvent::CpuRecovered {
+                cpu_percent: 40.0,
+                timestamp: TIMESTAMP,
+            }
+            .into(),
+        ),
+        (
+            "resource.storageDegraded",
+            ResourceEvent::StorageDegraded {
+                path: s("/var/lib/thaumic-cast"),
+                issue: StorageIssue::Full,
+                timestamp: TIMESTAMP,
+            }
+            .into(),
+        ),
+        (
+            "resource.storageRecovered",
+            ResourceEvent::StorageRecovered {
+                path: s("/var/lib/thaumic-cast"),
+                timestamp: TIMESTAMP,
+            }
+            .into(),
+        ),
+        (
+            "state.snapshot",
+            StateEvent::Snapshot {
+                revision: 1,
+                state: sonos_state.to_json(),
+                timestamp: TIMESTAMP,
+            }
+            .into(),
+        ),
+        (
+            "state.patch",
+            StateEvent::Patch {
+                revision: 2,
+                changes: serde_json::json!({
+                    "groupVolumes": { "192.168.1.20": 40, "192.168.1.21": null }
+                }),
+                timestamp: TIMESTAMP,
+            }
+            .into(),
+        ),
+    ]
+}
+
+#[test]
+fn broadcast_events_match_fixtures() {
+    let samples = sample_events()
+        .into_iter()
+        .map(|(name, event)| (name, serde_json::to_value(event).unwrap()))
+        .collect();
+    assert_serializes_to_fixtures(EVENTS, samples);
+}
@@ -3,10 +3,15 @@
 //! This module provides [`NetworkContext`] which bundles network configuration
 //! used across services. It supports both explicit configuration (for server
 //! deployment) and auto-detection (for desktop app).
-
-use std::net::IpAddr;
-#[cfg(test)]
-use std::net::Ipv4Addr;
+//!
+//! On multi-homed hosts, speakers on different subnets may only be able to
+//! reach us at the address on their own subnet. [`SubnetAddress`]es let the
+//! callback and stream URLs handed to each speaker use that address.
+
+use std::fmt;
+use std::net::{IpAddr, Ipv4Addr};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 
 use parking_lot::RwLock;
@@ -29,8 +34,16 @@ pub struct NetworkContext {
     pub port: Arc<RwLock<u16>>,
     /// Notifier signaled when port is assigned.
     pub port_notify: Arc<Notify>,
+    /// Port of the dedicated GENA listener, if NOTIFY requests don't share
+    /// the server port.
+    gena_port: Arc<RwLock<Option<u16>>>,
     /// IP address that Sonos speakers can reach us at.
     pub local_ip: Arc<RwLock<String>>,
+    /// Local addresses on other subnets, used instead of `local_ip` for
+    /// speakers on those subnets.
+    subnet_addresses: Arc<RwLock<Vec<SubnetAddress>>>,
+    /// Whether the subnet addresses were configured rather than detected.
+    subnets_configured: Arc<AtomicBool>,
     /// IP detector for checking network changes (auto-detect mode only).
     ip_detector: Option<Arc<dyn IpDetector>>,
 }
@@ -50,7 +63,10 @@ impl NetworkContext {
         Self {
             port: Arc::new(RwLock::new(bind_port)),
             port_notify: Arc::new(Notify::new()),
+            gena_port: Arc::new(RwLock::new(None)),
             local_ip: Arc::new(RwLock::new(advertise_ip.to_string())),
+            subnet_addresses: Arc::default(),
+            subnets_configured: Arc::default(),
             ip_detector: None,
         }
     }
@@ -73,10 +89,14 @@ impl NetworkContext {
         ip_detector: Arc<dyn IpDetector>,
     ) -> Result<Self, NetworkError> {
         let local_ip = ip_detector.detect()?;
+        let subnet_addresses = ip_detector.detect_subnets();
         Ok(Self {
             port: Arc::new(RwLock::new(preferred_port)),
             port_notify: Arc::new(Notify::new()),
+            gena_port: Arc::new(RwLock::new(None)),
             local_ip: Arc::new(RwLock::new(local_ip)),
+            subnet_addresses: Arc::new(RwLock::new(subnet_addresses)),
+            subnets_configured: Arc::default(),
             ip_detector: Some(ip_detector),
         })
     }
@@ -116,21 +136,175 @@ impl NetworkContext {
         self.port_notify.notify_waiters();
     }
 
+    /// Returns the dedicated GENA listener port, if one is configured.
+    #[must_use]
+    pub fn get_gena_port(&self) -> Option<u16> {
+        *self.gena_port.read()
+    }
+
+    /// Sets the dedicated GENA listener port (`None` to share the server port).
+    pub fn set_gena_port(&self, port: Option<u16>) {
+        *self.gena_port.write() = port;
+    }
+
     /// Updates the local IP address.
     pub fn set_local_ip(&self, ip: String) {
         *self.local_ip.write() = ip;
     }
 
+    /// Returns the addresses used for speakers on specific subnets.
+    #[must_use]
+    pub fn subnet_addresses(&self) -> Vec<SubnetAddress> {
+        self.subnet_addresses.read().clone()
+    }
+
+    /// Sets the addresses used for speakers on specific subnets.
+    ///
+    /// Configured addresses replace detected ones and are no longer
+    /// re-detected.
+    pub fn set_subnet_addresses(&self, addresses: Vec<SubnetAddress>) {
+        *self.subnet_addresses.write() = addresses;
+        self.subnets_configured.store(true, Ordering::Relaxed);
+    }
+
+    /// Re-reads the subnet addresses from the IP detector.
+    ///
+    /// Returns `true` if they changed. Does nothing in explicit mode or once
+    /// addresses have been configured.
+    pub fn refresh_subnet_addresses(&self) -> bool {
+        let Some(detector) = &self.ip_detector else {
+            return false;
+        };
+        if self.subnets_configured.load(Ordering::Relaxed) {
+            return false;
+        }
+        let detected = detector.detect_subnets();
+        let mut current = self.subnet_addresses.write();
+        if *current == detected {
+            return false;
+        }
+        *current = detected;
+        true
+    }
+
+    /// Returns our address on the speaker's subnet, if we have one there.
+    ///
+    /// When several subnets match, the most specific wins.
+    #[must_use]
+    pub fn subnet_ip_for(&self, speaker_ip: &str) -> Option<String> {
+        let IpAddr::V4(speaker) = speaker_ip.parse().ok()? else {
+            return None;
+        };
+        self.subnet_addresses
+            .read()
+            .iter()
+            .filter(|address| address.contains(speaker))
+            .max_by_key(|address| address.prefix_len)
+            .map(|address| address.ip.to_string())
+    }
+
+    /// Returns our address the OS routes traffic to the speaker from, for
+    /// speakers on subnets we have no address on (routed VLANs, site-to-site
+    /// VPNs). Only addresses of ours on known subnets are returned, so a
+    /// default route through the local IP's interface gives `None`.
+    #[must_use]
+    pub fn routed_ip_for(&self, speaker_ip: &str) -> Option<String> {
+        let IpAddr::V4(speaker) = speaker_ip.parse().ok()? else {
+            return None;
+        };
+        // Connecting a UDP socket only picks the route; nothing is sent
+        let socket = std::net::UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0)).ok()?;
+        socket.connect((speaker, 1400)).ok()?;
+        let IpAddr::V4(source) = socket.local_addr().ok()?.ip() else {
+            return None;
+        };
+        let local_ip = self.get_local_ip();
+        self.subnet_addresses
+            .read()
+            .iter()
+            .any(|address| address.ip == source)
+            .then(|| source.to_string())
+            .filter(|source| *source != local_ip)
+    }
+
+    /// Returns the address a speaker should reach us at when it isn't the
+    /// local IP: ours on its subnet, or the one traffic to it is routed from.
+    #[must_use]
+    pub fn speaker_facing_ip(&self, speaker_ip: &str) -> Option<String> {
+        self.subnet_ip_for(speaker_ip)
+            .or_else(|| self.routed_ip_for(speaker_ip))
+    }
+
+    /// Returns the subnet of the local IP, if it is one of this host's
+    /// addresses.
+    #[must_use]
+    pub fn local_subnet(&self) -> Option<SubnetAddress> {
+        let IpAddr::V4(local) = self.get_local_ip().parse().ok()? else {
+            return None;
+        };
+        let known = self
+            .subnet_addresses
+            .read()
+            .iter()
+            .find(|address| address.ip == local)
+            .copied();
+        known.or_else(|| {
+            interface_subnets()
+                .into_iter()
+                .find(|address| address.ip == local)
+        })
+    }
+
+    /// Returns whether a speaker is on a subnet we have no address on.
+    ///
+    /// `None` if that can't be told, e.g. when the local IP is a forwarded
+    /// address rather than one of this host's.
+    #[must_use]
+    pub fn is_cross_subnet(&self, speaker_ip: &str) -> Option<bool> {
+        let IpAddr::V4(speaker) = speaker_ip.parse().ok()? else {
+            return None;
+        };
+        if self.subnet_ip_for(speaker_ip).is_some() {
+            return Some(false);
+        }
+        Some(!self.local_subnet()?.contains(speaker))
+    }
+
+    /// Returns the IP a speaker should use to reach us: our address on its
+    /// subnet or route to it, or the local IP.
+    #[must_use]
+    pub fn local_ip_for(&self, speaker_ip: &str) -> String {
+        self.speaker_facing_ip(speaker_ip)
+            .unwrap_or_else(|| self.get_local_ip())
+    }
+
     /// Returns a `UrlBuilder` for the current network configuration.
     #[must_use]
     pub fn url_builder(&self) -> UrlBuilder {
         UrlBuilder::new(self.get_local_ip(), self.get_port())
     }
 
+    /// Returns a `UrlBuilder` for URLs handed to a specific speaker.
+    #[must_use]
+    pub fn url_builder_for(&self, speaker_ip: &str) -> UrlBuilder {
+        UrlBuilder::new(self.local_ip_for(speaker_ip), self.get_port())
+    }
+
     /// Returns the GENA callback URL for receiving Sonos event notifications.
+    ///
+    /// Points at the dedicated GENA listener when one is configured, and at
+    /// the main server port otherwise.
     #[must_use]
     pub fn gena_callback_url(&self) -> String {
-        self.url_builder().gena_callback_url()
+        let port = self.get_gena_port().unwrap_or_else(|| self.get_port());
+        UrlBuilder::new(self.get_local_ip(), port).gena_callback_url()
+    }
+
+    /// Returns the GENA callback URL to give a specific speaker.
+    #[must_use]
+    pub fn gena_callback_url_for(&self, speaker_ip: &str) -> String {
+        let port = self.get_gena_port().unwrap_or_else(|| self.get_port());
+        UrlBuilder::new(self.local_ip_for(speaker_ip), port).gena_callback_url()
     }
 
     /// Returns the stream URL for a given stream ID.
@@ -147,6 +321,11 @@ impl NetworkContext {
 pub trait IpDetector: Send + Sync {
     /// Detects the local IP address.
     fn detect(&self) -> Result<String, NetworkError>;
+
+    /// Detects the local addresses on each subnet, for multi-homed hosts.
+    fn detect_subnets(&self) -> Vec<SubnetAddress> {
+        Vec::new()
+    }
 }
 
 /// Default IP detector using the system's network interfaces.
@@ -173,6 +352,36 @@ impl IpDetector for LocalIpDetector {
             .map(|ip| ip.to_string())
             .map_err(|e| NetworkError::Detection(e.to_string()))
     }
+
+    /// Lists private IPv4 interface addresses. A single address is no
+    /// different from the detected local IP, so only multi-homed hosts
+    /// get any.
+    fn detect_subnets(&self) -> Vec<SubnetAddress> {
+        let mut addresses: Vec<SubnetAddress> = interface_subnets()
+            .into_iter()
+            .filter(|address| address.ip.is_private())
+            .collect();
+        if addresses.len() < 2 {
+            addresses.clear();
+        }
+        addresses
+    }
+}
+
+/// Lists the IPv4 addresses of this host's interfaces, loopback excluded.
+fn interface_subnets() -> Vec<SubnetAddress> {
+    let networks = sysinfo::Networks::new_with_refreshed_list();
+    let mut addresses: Vec<SubnetAddress> = networks
+        .values()
+        .flat_map(|data| data.ip_networks())
+        .filter_map(|network| match network.addr {
+            IpAddr::V4(ip) if !ip.is_loopback() => SubnetAddress::new(ip, network.prefix),
+            _ => None,
+        })
+        .collect();
+    addresses.sort_by_key(|address| address.ip);
+    addresses.dedup();
+    addresses
 }
 
 /// Errors that can occur during network operations.
@@ -185,6 +394,55 @@ pub enum NetworkError {
     /// No IP detector configured (explicit mode).
     #[error("No IP detector configured (using explicit mode)")]
     NoDetector,
+
+    /// A subnet address was not in `address/prefix` form.
+    #[error("Invalid subnet address: {0}")]
+    InvalidSubnet(String),
+}
+
+/// A local IPv4 address and the length of its subnet prefix.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SubnetAddress {
+    /// Our address on the subnet.
+    pub ip: Ipv4Addr,
+    /// Subnet prefix length (e.g. 24 for a /24).
+    pub prefix_len: u8,
+}
+
+impl SubnetAddress {
+    /// Creates a subnet address, or `None` if the prefix is longer than 32.
+    #[must_use]
+    pub fn new(ip: Ipv4Addr, prefix_len: u8) -> Option<Self> {
+        (prefix_len <= 32).then_some(Self { ip, prefix_len })
+    }
+
+    /// Returns whether `ip` is on this subnet.
+    #[must_use]
+    pub fn contains(&self, ip: Ipv4Addr) -> bool {
+        let mask = u32::MAX
+            .checked_shl(32 - u32::from(self.prefix_len))
+            .unwrap_or(0);
+        u32::from(self.ip) & mask == u32::from(ip) & mask
+    }
+}
+
+impl FromStr for SubnetAddress {
+    type Err = NetworkError;
+
+    /// Parses `address/prefix`, e.g. `10.0.20.5/24`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || NetworkError::InvalidSubnet(s.to_string());
+        let (ip, prefix_len) = s.trim().split_once('/').ok_or_else(invalid)?;
+        let ip = ip.parse().map_err(|_| invalid())?;
+        let prefix_len = prefix_len.parse().map_err(|_| invalid())?;
+        Self::new(ip, prefix_len).ok_or_else(invalid)
+    }
+}
+
+impl fmt::Display for SubnetAddress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.ip, self.prefix_len)
+    }
 }
 
 /// Builder for constructing URLs for the streaming server.
@@ -217,6 +475,15 @@ impl UrlBuilder {
         format!("{}/stream/{}/live", self.base_url(), stream_id)
     }
 
+    /// Returns the URL of a reachability check's clip.
+    ///
+    /// Like [`stream_url`](Self::stream_url), returned without the `.wav`
+    /// extension, which `build_sonos_stream_uri` appends.
+    #[must_use]
+    pub fn reachability_clip_url(&self, token: &str) -> String {
+        format!("{}/reachability/{}/clip.wav", self.base_url(), token)
+    }
+
     /// Returns the GENA callback URL for receiving Sonos notifications.
     #[must_use]
     pub fn gena_callback_url(&self) -> String {
@@ -228,12 +495,18 @@ impl UrlBuilder {
     pub fn artwork_url(&self) -> String {
         format!("{}/artwork.jpg", self.base_url())
     }
+
+    /// Returns the URL of uploaded artwork. `version` changes with the
+    /// image so speakers don't keep showing a cached copy.
+    #[must_use]
+    pub fn uploaded_artwork_url(&self, name: &str, version: &str) -> String {
+        format!("{}/artwork/{}.jpg?v={}", self.base_url(), name, version)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::net::Ipv4Addr;
 
     struct MockIpDetector {
         ip: String,
@@ -267,6 +540,26 @@ mod tests {
         assert!(matches!(ctx.detect_ip(), Err(NetworkError::NoDetector)));
     }
 
+    #[test]
+    fn gena_callback_url_follows_listener_port() {
+        let ctx = NetworkContext::explicit(8080, IpAddr::V4(Ipv4Addr::new(192, 168, 1, 100)));
+        assert_eq!(
+            ctx.gena_callback_url(),
+            "http://192.168.1.100:8080/sonos/gena"
+        );
+
+        ctx.set_gena_port(Some(3400));
+        assert_eq!(
+            ctx.gena_callback_url(),
+            "http://192.168.1.100:3400/sonos/gena"
+        );
+        // Streams stay on the server port
+        assert_eq!(
+            ctx.stream_url("abc123"),
+            "http://192.168.1.100:8080/stream/abc123/live"
+        );
+    }
+
     #[test]
     fn url_builder_generates_correct_urls() {
         let builder = UrlBuilder::new("192.168.1.100", 8080);
@@ -275,9 +568,75 @@ mod tests {
             builder.stream_url("abc123"),
             "http://192.168.1.100:8080/stream/abc123/live"
         );
+        assert_eq!(
+            builder.reachability_clip_url("t1"),
+            "http://192.168.1.100:8080/reachability/t1/clip.wav"
+        );
+        assert_eq!(
+            builder.uploaded_artwork_url("podcast", "1a2b3c4d"),
+            "http://192.168.1.100:8080/artwork/podcast.jpg?v=1a2b3c4d"
+        );
         assert_eq!(
             builder.gena_callback_url(),
             "http://192.168.1.100:8080/sonos/gena"
         );
     }
+
+    #[test]
+    fn subnet_address_parsing_and_matching() {
+        let address: SubnetAddress = "10.0.20.5/24".parse().unwrap();
+        assert_eq!(address.to_string(), "10.0.20.5/24");
+        assert!(address.contains(Ipv4Addr::new(10, 0, 20, 99)));
+        assert!(!address.contains(Ipv4Addr::new(10, 0, 21, 99)));
+
+        let everything: SubnetAddress = "10.0.20.5/0".parse().unwrap();
+        assert!(everything.contains(Ipv4Addr::new(192, 168, 1, 1)));
+
+        assert!("10.0.20.5".parse::<SubnetAddress>().is_err());
+        assert!("10.0.20.5/33".parse::<SubnetAddress>().is_err());
+        assert!("fe80::1/64".parse::<SubnetAddress>().is_err());
+    }
+
+    #[test]
+    fn speakers_get_the_address_on_their_subnet() {
+        let ctx = NetworkContext::explicit(8080, IpAddr::V4(Ipv4Addr::new(192, 168, 1, 100)));
+        ctx.set_gena_port(Some(3400));
+        ctx.set_subnet_addresses(vec![
+            "10.0.0.5/16".parse().unwrap(),
+            "10.0.20.5/24".parse().unwrap(),
+        ]);
+
+        // Most specific subnet wins
+        assert_eq!(ctx.local_ip_for("10.0.20.30"), "10.0.20.5");
+        assert_eq!(ctx.local_ip_for("10.0.30.30"), "10.0.0.5");
+        // Other speakers use the local IP
+        assert_eq!(ctx.local_ip_for("192.168.1.50"), "192.168.1.100");
+        assert_eq!(ctx.subnet_ip_for("192.168.1.50"), None);
+
+        assert_eq!(
+            ctx.gena_callback_url_for("10.0.20.30"),
+            "http://10.0.20.5:3400/sonos/gena"
+        );
+        assert_eq!(
+            ctx.url_builder_for("10.0.20.30").stream_url("abc123"),
+            "http://10.0.20.5:8080/stream/abc123/live"
+        );
+    }
+
+    #[test]
+    fn speakers_outside_our_subnets_are_cross_subnet() {
+        let ctx = NetworkContext::explicit(8080, IpAddr::V4(Ipv4Addr::new(192, 168, 1, 100)));
+        ctx.set_subnet_addresses(vec![
+            "192.168.1.100/24".parse().unwrap(),
+            "10.0.20.5/24".parse().unwrap(),
+        ]);
+
+        assert_eq!(ctx.is_cross_subnet("192.168.1.50"), Some(false));
+        assert_eq!(ctx.is_cross_subnet("10.0.20.30"), Some(false));
+        assert_eq!(ctx.is_cross_subnet("10.0.30.30"), Some(true));
+
+        // A forwarded address isn't ours, so its subnet is unknown
+        let forwarded = NetworkContext::explicit(8080, IpAddr::V4(Ipv4Addr::new(203, 0, 113, 7)));
+        assert_eq!(forwarded.is_cross_subnet("10.0.30.30"), None);
+    }
 }
@@ -0,0 +1,113 @@
+//! Bounded TTL cache for enrichment lookups.
+
+use std::collections::{HashMap, VecDeque};
+use std::time::{Duration, Instant};
+
+use super::TrackInfo;
+
+/// Caches lookup results, including misses, by normalized query key.
+///
+/// Caching misses matters as much as hits: a generic tab title that no
+/// provider recognizes would otherwise be looked up on every metadata update.
+/// Eviction is oldest-inserted first once `capacity` is reached.
+#[derive(Debug)]
+pub(crate) struct LookupCache {
+    entries: HashMap<String, (Instant, Option<TrackInfo>)>,
+    order: VecDeque<String>,
+    capacity: usize,
+    ttl: Duration,
+}
+
+impl LookupCache {
+    pub(crate) fn new(capacity: usize, ttl: Duration) -> Self {
+        Self {
+            entries: HashMap::with_capacity(capacity),
+            order: VecDeque::with_capacity(capacity),
+            capacity,
+            ttl,
+        }
+    }
+
+    /// Returns the cached result for `key`.
+    ///
+    /// The outer `Option` is whether the key is cached at all; the inner one
+    /// is the cached result (`None` for a cached miss).
+    pub(crate) fn get(&mut self, key: &str, now: Instant) -> Option<Option<TrackInfo>> {
+        let (stored_at, info) = self.entries.get(key)?;
+        if now.duration_since(*stored_at) < self.ttl {
+            return Some(info.clone());
+        }
+        self.entries.remove(key);
+        self.order.retain(|k| k != key);
+        None
+    }
+
+    /// Stores a lookup result (or miss) for `key`.
+    pub(crate) fn insert(&mut self, key: String, info: Option<TrackInfo>, now: Instant) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.entries.insert(key.clone(), (now, info)).is_some() {
+            self.order.retain(|k| *k != key);
+        }
+        self.order.push_back(key);
+
+        while self.order.len() > self.capacity {
+            if let Some(oldest) = self.order.pop_front() {
+                self.entries.remove(&oldest);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn info(title: &str) -> Option<TrackInfo> {
+        Some(TrackInfo {
+            title: title.to_string(),
+            artist: "Artist".to_string(),
+            album: None,
+            artwork_url: None,
+        })
+    }
+
+    #[test]
+    fn caches_hits_and_misses() {
+        let mut cache = LookupCache::new(4, Duration::from_secs(60));
+        let now = Instant::now();
+        cache.insert("a".into(), info("A"), now);
+        cache.insert("b".into(), None, now);
+
+        assert_eq!(cache.get("a", now), Some(info("A")));
+        assert_eq!(cache.get("b", now), Some(None));
+        assert_eq!(cache.get("c", now), None);
+    }
+
+    #[test]
+    fn expires_after_ttl() {
+        let mut cache = LookupCache::new(4, Duration::from_secs(60));
+        let now = Instant::now();
+        cache.insert("a".into(), info("A"), now);
+
+        assert!(cache.get("a", now + Duration::from_secs(59)).is_some());
+        assert!(cache.get("a", now + Duration::from_secs(60)).is_none());
+        assert!(cache.order.is_empty());
+    }
+
+    #[test]
+    fn evicts_oldest_at_capacity() {
+        let mut cache = LookupCache::new(2, Duration::from_secs(60));
+        let now = Instant::now();
+        cache.insert("a".into(), info("A"), now);
+        cache.insert("b".into(), info("B"), now);
+        // Re-inserting refreshes position
+        cache.insert("a".into(), info("A2"), now);
+        cache.insert("c".into(), info("C"), now);
+
+        assert_eq!(cache.get("b", now), None);
+        assert_eq!(cache.get("a", now), Some(info("A2")));
+        assert_eq!(cache.get("c", now), Some(info("C")));
+    }
+}
@@ -0,0 +1,171 @@
+//! last.fm track search provider.
+//!
+//! Requires an API key. Search results carry no relevance score, so a result
+//! is only accepted when its title matches the query's; otherwise a generic
+//! tab title would pick up whatever popular track happens to contain it.
+//! Search results don't include the album.
+
+use std::time::Duration;
+
+use async_trait::async_trait;
+use reqwest::{Client, Url};
+use serde::Deserialize;
+
+use super::{normalize, EnrichmentError, EnrichmentQuery, MetadataProvider, TrackInfo, USER_AGENT};
+
+/// API root.
+const API_URL: &str = "https://ws.audioscrobbler.com/2.0/";
+
+/// Stay well below last.fm's documented 5 requests/second.
+const MIN_INTERVAL: Duration = Duration::from_millis(250);
+
+#[derive(Deserialize)]
+struct SearchResponse {
+    results: Results,
+}
+
+#[derive(Deserialize)]
+struct Results {
+    trackmatches: TrackMatches,
+}
+
+#[derive(Deserialize)]
+struct TrackMatches {
+    #[serde(default)]
+    track: Vec<Track>,
+}
+
+#[derive(Deserialize)]
+struct Track {
+    name: String,
+    artist: String,
+    #[serde(default)]
+    image: Vec<Image>,
+}
+
+#[derive(Deserialize)]
+struct Image {
+    #[serde(rename = "#text")]
+    url: String,
+    size: String,
+}
+
+/// Looks up tracks on last.fm.
+pub struct LastFmProvider {
+    client: Client,
+    api_key: String,
+}
+
+impl LastFmProvider {
+    pub fn new(client: Client, api_key: String) -> Self {
+        Self { client, api_key }
+    }
+}
+
+#[async_trait]
+impl MetadataProvider for LastFmProvider {
+    fn name(&self) -> &'static str {
+        "lastfm"
+    }
+
+    fn min_interval(&self) -> Duration {
+        MIN_INTERVAL
+    }
+
+    async fn lookup(&self, query: &EnrichmentQuery) -> Result<Option<TrackInfo>, EnrichmentError> {
+        let mut params = vec![
+            ("method", "track.search"),
+            ("track", query.title.as_str()),
+            ("api_key", self.api_key.as_str()),
+            ("format", "json"),
+            ("limit", "5"),
+        ];
+        if let Some(artist) = &query.artist {
+            params.push(("artist", artist.as_str()));
+        }
+
+        let url = Url::parse_with_params(API_URL, &params)
+            .map_err(|e| EnrichmentError::Parse(e.to_string()))?;
+        let response = self
+            .client
+            .get(url)
+            .header(reqwest::header::USER_AGENT, USER_AGENT)
+            .send()
+            .await?
+            .error_for_status()?;
+
+        let body = response.text().await?;
+        parse_response(&body, query)
+    }
+}
+
+/// Parses a search response, accepting only a result whose title matches.
+fn parse_response(
+    body: &str,
+    query: &EnrichmentQuery,
+) -> Result<Option<TrackInfo>, EnrichmentError> {
+    let response: SearchResponse =
+        serde_json::from_str(body).map_err(|e| EnrichmentError::Parse(e.to_string()))?;
+
+    let wanted = normalize(&query.title);
+    let Some(track) = response
+        .results
+        .trackmatches
+        .track
+        .into_iter()
+        .find(|t| normalize(&t.name) == wanted)
+    else {
+        return Ok(None);
+    };
+
+    // Prefer the large image; last.fm returns empty URLs for missing art
+    let artwork_url = ["extralarge", "large", "medium"].iter().find_map(|size| {
+        track
+            .image
+            .iter()
+            .find(|i| i.size == *size && !i.url.is_empty())
+            .map(|i| i.url.clone())
+    });
+
+    Ok(Some(TrackInfo {
+        title: track.name,
+        artist: track.artist,
+        album: None,
+        artwork_url,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn query(title: &str) -> EnrichmentQuery {
+        EnrichmentQuery {
+            title: title.to_string(),
+            artist: None,
+        }
+    }
+
+    const BODY: &str = r##"{"results": {"trackmatches": {"track": [
+        {"name": "Believe It", "artist": "Someone Else", "image": []},
+        {"name": "Believe", "artist": "Cher", "image": [
+            {"#text": "https://img/s.png", "size": "small"},
+            {"#text": "https://img/l.png", "size": "large"},
+            {"#text": "", "size": "extralarge"}
+        ]}
+    ]}}}"##;
+
+    #[test]
+    fn picks_result_with_matching_title() {
+        let info = parse_response(BODY, &query("believe")).unwrap().unwrap();
+        assert_eq!(info.artist, "Cher");
+        assert_eq!(info.title, "Believe");
+        assert_eq!(info.artwork_url.as_deref(), Some("https://img/l.png"));
+    }
+
+    #[test]
+    fn ignores_results_with_other_titles() {
+        assert_eq!(parse_response(BODY, &query("Strong Enough")).unwrap(), None);
+        assert!(parse_response(r#"{"error": 10}"#, &query("x")).is_err());
+    }
+}
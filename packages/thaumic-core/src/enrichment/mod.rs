@@ -0,0 +1,594 @@
+//! Metadata enrichment for sparse tab metadata.
+//!
+//! Many tabs only expose a page title ("Song Name (Official Video)"), so
+//! Sonos shows a bare title with no artist. When enabled, the
+//! [`MetadataEnricher`] looks the title up with pluggable
+//! [`MetadataProvider`]s and fills in artist, album, and artwork.
+//!
+//! - Providers are tried in order; the first confident match wins.
+//! - Results, including misses, are cached by normalized query.
+//! - Each provider is rate limited to its service's published limit.
+//! - Metadata supplied by the tab is never overwritten, and results that
+//!   arrive after the tab has moved on to another track are dropped.
+
+mod cache;
+pub mod lastfm;
+pub mod musicbrainz;
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::runtime::TokioSpawner;
+use crate::stream::{StreamMetadata, StreamState};
+
+use cache::LookupCache;
+pub use lastfm::LastFmProvider;
+pub use musicbrainz::MusicBrainzProvider;
+
+/// User-Agent sent to metadata services (MusicBrainz requires one).
+pub(crate) const USER_AGENT: &str = concat!(
+    "ThaumicCast/",
+    env!("CARGO_PKG_VERSION"),
+    " ( https://github.com/brew-lab/thaumic-cast )"
+);
+
+/// Bracketed title suffixes that are video/upload decoration, not track name.
+const DECORATION_KEYWORDS: &[&str] = &[
+    "official",
+    "video",
+    "audio",
+    "lyric",
+    "visualizer",
+    "visualiser",
+    "hd",
+    "4k",
+    "mv",
+];
+
+/// Configuration for metadata enrichment.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EnrichmentConfig {
+    /// Look up missing track details with online metadata services.
+    pub enabled: bool,
+    /// Query MusicBrainz (no API key needed).
+    pub musicbrainz: bool,
+    /// last.fm API key; the last.fm provider is used only when set.
+    pub lastfm_api_key: Option<String>,
+    /// Maximum number of cached lookups.
+    pub cache_capacity: usize,
+    /// How long cached lookups stay valid (seconds).
+    pub cache_ttl_secs: u64,
+}
+
+impl Default for EnrichmentConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            musicbrainz: true,
+            lastfm_api_key: None,
+            cache_capacity: 512,
+            cache_ttl_secs: 24 * 60 * 60,
+        }
+    }
+}
+
+/// Errors from a provider lookup.
+#[derive(Debug, Error)]
+pub enum EnrichmentError {
+    /// The HTTP request failed or returned an error status.
+    #[error("HTTP request failed: {0}")]
+    Http(#[from] reqwest::Error),
+
+    /// The response body could not be parsed.
+    #[error("Failed to parse response: {0}")]
+    Parse(String),
+}
+
+/// Track details resolved by a provider.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TrackInfo {
+    /// Canonical track title.
+    pub title: String,
+    /// Artist display name.
+    pub artist: String,
+    /// Album (release) title, if known.
+    pub album: Option<String>,
+    /// Cover art URL, if known.
+    pub artwork_url: Option<String>,
+}
+
+impl TrackInfo {
+    /// Fills the gaps in `metadata` with these details.
+    ///
+    /// The title is replaced with the canonical one, since the tab's title
+    /// is what the lookup was based on and may carry decoration
+    /// ("(Official Video)") or an artist prefix. Fields the tab supplied are
+    /// otherwise kept.
+    pub fn apply_to(&self, metadata: &mut StreamMetadata) {
+        metadata.title = Some(self.title.clone());
+        metadata.artist.get_or_insert_with(|| self.artist.clone());
+        if metadata.album.is_none() {
+            metadata.album = self.album.clone();
+        }
+        if metadata.artwork_url.is_none() {
+            metadata.artwork_url = self.artwork_url.clone();
+        }
+    }
+}
+
+/// A lookup request derived from sparse metadata.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EnrichmentQuery {
+    /// Track title with upload decoration removed.
+    pub title: String,
+    /// Artist hint parsed from an "Artist - Title" style title.
+    pub artist: Option<String>,
+}
+
+impl EnrichmentQuery {
+    /// Builds a query if `metadata` needs enriching.
+    ///
+    /// Only metadata with a title and no artist is enriched; anything
+    /// richer already came from the page's MediaSession.
+    pub fn from_metadata(metadata: &StreamMetadata) -> Option<Self> {
+        if metadata.artist.is_some() {
+            return None;
+        }
+        let cleaned = strip_decoration(metadata.title.as_deref()?);
+
+        let (artist, title) = match cleaned.split_once(" - ") {
+            Some((artist, title)) if !artist.trim().is_empty() && !title.trim().is_empty() => {
+                (Some(artist.trim().to_string()), title.trim().to_string())
+            }
+            _ => (None, cleaned),
+        };
+
+        (!title.is_empty()).then_some(Self { title, artist })
+    }
+
+    /// Cache key: case- and whitespace-insensitive.
+    fn cache_key(&self) -> String {
+        match &self.artist {
+            Some(artist) => format!("{}\u{1F}{}", normalize(artist), normalize(&self.title)),
+            None => normalize(&self.title),
+        }
+    }
+}
+
+/// Removes bracketed upload decoration like "(Official Video)" or "[HD]".
+fn strip_decoration(title: &str) -> String {
+    let mut out = String::with_capacity(title.len());
+    let mut rest = title;
+
+    while let Some(open) = rest.find(['(', '[']) {
+        let close_char = if rest[open..].starts_with('(') {
+            ')'
+        } else {
+            ']'
+        };
+        let Some(close) = rest[open..].find(close_char).map(|i| open + i) else {
+            break;
+        };
+        let inner = rest[open + 1..close].to_lowercase();
+        let decorative = inner
+            .split(|c: char| !c.is_alphanumeric())
+            .any(|word| DECORATION_KEYWORDS.contains(&word));
+
+        out.push_str(&rest[..open]);
+        if !decorative {
+            out.push_str(&rest[open..=close]);
+        }
+        rest = &rest[close + 1..];
+    }
+    out.push_str(rest);
+
+    out.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Lowercases and collapses whitespace for comparison.
+pub(crate) fn normalize(text: &str) -> String {
+    text.split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+/// A source of track details.
+///
+/// Implementations should return `Ok(None)` for no confident match rather
+/// than a best guess; a wrong artist is worse than none.
+#[async_trait]
+pub trait MetadataProvider: Send + Sync {
+    /// Short name for logs.
+    fn name(&self) -> &'static str;
+
+    /// Minimum time between requests to this provider.
+    fn min_interval(&self) -> Duration;
+
+    /// Looks up track details for the query.
+    async fn lookup(&self, query: &EnrichmentQuery) -> Result<Option<TrackInfo>, EnrichmentError>;
+}
+
+/// A provider with its request pacing.
+struct PacedProvider {
+    provider: Arc<dyn MetadataProvider>,
+    /// When the next request may be sent. Held across the wait so
+    /// concurrent lookups queue instead of bursting.
+    next_request: tokio::sync::Mutex<tokio::time::Instant>,
+}
+
+impl PacedProvider {
+    async fn lookup(&self, query: &EnrichmentQuery) -> Result<Option<TrackInfo>, EnrichmentError> {
+        let mut next_request = self.next_request.lock().await;
+        tokio::time::sleep_until(*next_request).await;
+        *next_request = tokio::time::Instant::now() + self.provider.min_interval();
+        drop(next_request);
+
+        self.provider.lookup(query).await
+    }
+}
+
+/// Resolves missing track details through a chain of providers.
+pub struct MetadataEnricher {
+    providers: Vec<PacedProvider>,
+    cache: parking_lot::Mutex<LookupCache>,
+    spawner: TokioSpawner,
+}
+
+impl MetadataEnricher {
+    /// Creates an enricher that tries `providers` in order.
+    pub fn new(
+        providers: Vec<Arc<dyn MetadataProvider>>,
+        cache_capacity: usize,
+        cache_ttl: Duration,
+        spawner: TokioSpawner,
+    ) -> Self {
+        let now = tokio::time::Instant::now();
+        Self {
+            providers: providers
+                .into_iter()
+                .map(|provider| PacedProvider {
+                    provider,
+                    next_request: tokio::sync::Mutex::new(now),
+                })
+                .collect(),
+            cache: parking_lot::Mutex::new(LookupCache::new(cache_capacity, cache_ttl)),
+            spawner,
+        }
+    }
+
+    /// Builds the enricher described by `config`.
+    ///
+    /// Returns `None` when enrichment is disabled or no provider is usable.
+    pub fn from_config(
+        config: &EnrichmentConfig,
+        client: Client,
+        spawner: TokioSpawner,
+    ) -> Option<Self> {
+        if !config.enabled {
+            return None;
+        }
+
+        let mut providers: Vec<Arc<dyn MetadataProvider>> = Vec::new();
+        if config.musicbrainz {
+            providers.push(Arc::new(MusicBrainzProvider::new(client.clone())));
+        }
+        if let Some(key) = config.lastfm_api_key.as_ref().filter(|k| !k.is_empty()) {
+            providers.push(Arc::new(LastFmProvider::new(client, key.clone())));
+        }
+        if providers.is_empty() {
+            log::warn!("[Enrichment] Enabled but no providers configured");
+            return None;
+        }
+
+        log::info!(
+            "[Enrichment] Enabled with providers: {}",
+            providers
+                .iter()
+                .map(|p| p.name())
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+        Some(Self::new(
+            providers,
+            config.cache_capacity,
+            Duration::from_secs(config.cache_ttl_secs),
+            spawner,
+        ))
+    }
+
+    /// Resolves track details for a query, using the cache when possible.
+    ///
+    /// Provider errors are logged and the next provider is tried. Errors
+    /// aren't cached, so a transient outage doesn't hide a track for a day.
+    pub async fn enrich(&self, query: &EnrichmentQuery) -> Option<TrackInfo> {
+        let key = query.cache_key();
+        if let Some(cached) = self.cache.lock().get(&key, Instant::now()) {
+            return cached;
+        }
+
+        let mut failed = false;
+        for paced in &self.providers {
+            match paced.lookup(query).await {
+                Ok(Some(info)) => {
+                    log::debug!(
+                        "[Enrichment] {} matched {:?} -> {} - {}",
+                        paced.provider.name(),
+                        query.title,
+                        info.artist,
+                        info.title
+                    );
+                    self.cache
+                        .lock()
+                        .insert(key, Some(info.clone()), Instant::now());
+                    return Some(info);
+                }
+                Ok(None) => {}
+                Err(e) => {
+                    log::warn!(
+                        "[Enrichment] {} lookup failed: {}",
+                        paced.provider.name(),
+                        e
+                    );
+                    failed = true;
+                }
+            }
+        }
+
+        if !failed {
+            self.cache.lock().insert(key, None, Instant::now());
+        }
+        None
+    }
+
+    /// Enriches a stream's current metadata in the background.
+    ///
+    /// Does nothing if the metadata isn't sparse. Cached results are applied
+    /// before returning. A background result is applied only if the stream's
+    /// metadata still describes the same track, then passed to `on_enriched`.
+    pub fn enrich_stream(
+        self: &Arc<Self>,
+        stream: Arc<StreamState>,
+        on_enriched: impl FnOnce(&StreamMetadata) + Send + 'static,
+    ) {
+        let Some(query) = EnrichmentQuery::from_metadata(&stream.metadata.read()) else {
+            return;
+        };
+
+        // Apply cached results right away so repeated updates don't flicker
+        let cached = self.cache.lock().get(&query.cache_key(), Instant::now());
+        if let Some(cached) = cached {
+            if let Some(info) = cached {
+                info.apply_to(&mut stream.metadata.write());
+            }
+            return;
+        }
+
+        let enricher = Arc::clone(self);
+        self.spawner.spawn(async move {
+            let Some(info) = enricher.enrich(&query).await else {
+                return;
+            };
+
+            let updated = {
+                let mut metadata = stream.metadata.write();
+                if EnrichmentQuery::from_metadata(&metadata).as_ref() != Some(&query) {
+                    log::debug!(
+                        "[Enrichment] Stream {} moved on before lookup finished",
+                        stream.id
+                    );
+                    return;
+                }
+                info.apply_to(&mut metadata);
+                metadata.clone()
+            };
+            on_enriched(&updated);
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    struct FakeProvider {
+        result: Option<TrackInfo>,
+        calls: AtomicUsize,
+    }
+
+    impl FakeProvider {
+        fn new(result: Option<TrackInfo>) -> Arc<Self> {
+            Arc::new(Self {
+                result,
+                calls: AtomicUsize::new(0),
+            })
+        }
+    }
+
+    #[async_trait]
+    impl MetadataProvider for FakeProvider {
+        fn name(&self) -> &'static str {
+            "fake"
+        }
+
+        fn min_interval(&self) -> Duration {
+            Duration::from_secs(1)
+        }
+
+        async fn lookup(
+            &self,
+            _query: &EnrichmentQuery,
+        ) -> Result<Option<TrackInfo>, EnrichmentError> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            Ok(self.result.clone())
+        }
+    }
+
+    fn track() -> TrackInfo {
+        TrackInfo {
+            title: "Midnight City".to_string(),
+            artist: "M83".to_string(),
+            album: Some("Hurry Up, We're Dreaming".to_string()),
+            artwork_url: None,
+        }
+    }
+
+    fn enricher(providers: Vec<Arc<dyn MetadataProvider>>) -> MetadataEnricher {
+        MetadataEnricher::new(
+            providers,
+            16,
+            Duration::from_secs(60),
+            TokioSpawner::new(tokio::runtime::Handle::current()),
+        )
+    }
+
+    fn titled(title: &str) -> StreamMetadata {
+        StreamMetadata {
+            title: Some(title.to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn builds_queries_only_for_sparse_metadata() {
+        let query = EnrichmentQuery::from_metadata(&titled("Midnight City (Official Video)"));
+        assert_eq!(
+            query,
+            Some(EnrichmentQuery {
+                title: "Midnight City".to_string(),
+                artist: None,
+            })
+        );
+
+        let with_artist = StreamMetadata {
+            artist: Some("M83".to_string()),
+            ..titled("Midnight City")
+        };
+        assert_eq!(EnrichmentQuery::from_metadata(&with_artist), None);
+        assert_eq!(
+            EnrichmentQuery::from_metadata(&StreamMetadata::default()),
+            None
+        );
+        assert_eq!(EnrichmentQuery::from_metadata(&titled(" [HD] ")), None);
+    }
+
+    #[test]
+    fn splits_artist_prefixed_titles() {
+        let query = EnrichmentQuery::from_metadata(&titled("M83 - Midnight City [Official Audio]"));
+        assert_eq!(
+            query,
+            Some(EnrichmentQuery {
+                title: "Midnight City".to_string(),
+                artist: Some("M83".to_string()),
+            })
+        );
+    }
+
+    #[test]
+    fn keeps_meaningful_brackets() {
+        assert_eq!(
+            strip_decoration("Song (Live at Wembley) (Official Video)"),
+            "Song (Live at Wembley)"
+        );
+        assert_eq!(strip_decoration("Song (Remix) [4K]"), "Song (Remix)");
+        assert_eq!(strip_decoration("Unclosed (paren"), "Unclosed (paren");
+    }
+
+    #[test]
+    fn applies_without_overwriting_tab_fields() {
+        let mut metadata = StreamMetadata {
+            album: Some("Tab Album".to_string()),
+            source: Some("YouTube".to_string()),
+            ..titled("M83 - Midnight City")
+        };
+        track().apply_to(&mut metadata);
+
+        assert_eq!(metadata.title.as_deref(), Some("Midnight City"));
+        assert_eq!(metadata.artist.as_deref(), Some("M83"));
+        assert_eq!(metadata.album.as_deref(), Some("Tab Album"));
+        assert_eq!(metadata.source.as_deref(), Some("YouTube"));
+    }
+
+    #[tokio::test]
+    async fn falls_through_providers_and_caches() {
+        let miss = FakeProvider::new(None);
+        let hit = FakeProvider::new(Some(track()));
+        let enricher = enricher(vec![miss.clone(), hit.clone()]);
+        let query = EnrichmentQuery {
+            title: "Midnight City".to_string(),
+            artist: None,
+        };
+
+        assert_eq!(enricher.enrich(&query).await, Some(track()));
+        assert_eq!(enricher.enrich(&query).await, Some(track()));
+        assert_eq!(miss.calls.load(Ordering::SeqCst), 1);
+        assert_eq!(hit.calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn caches_misses() {
+        let miss = FakeProvider::new(None);
+        let enricher = enricher(vec![miss.clone()]);
+        let query = EnrichmentQuery {
+            title: "Some Podcast".to_string(),
+            artist: None,
+        };
+
+        assert_eq!(enricher.enrich(&query).await, None);
+        assert_eq!(enricher.enrich(&query).await, None);
+        assert_eq!(miss.calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn paces_requests_per_provider() {
+        let provider = FakeProvider::new(None);
+        let enricher = enricher(vec![provider.clone()]);
+        let start = tokio::time::Instant::now();
+
+        for title in ["a", "b", "c"] {
+            let query = EnrichmentQuery {
+                title: title.to_string(),
+                artist: None,
+            };
+            enricher.enrich(&query).await;
+        }
+
+        assert_eq!(provider.calls.load(Ordering::SeqCst), 3);
+        assert!(start.elapsed() >= Duration::from_secs(2));
+    }
+
+    #[tokio::test]
+    async fn from_config_requires_enabled_provider() {
+        let spawner = TokioSpawner::new(tokio::runtime::Handle::current());
+        let client = Client::new();
+
+        let disabled = EnrichmentConfig::default();
+        assert!(
+            MetadataEnricher::from_config(&disabled, client.clone(), spawner.clone()).is_none()
+        );
+
+        let no_providers = EnrichmentConfig {
+            enabled: true,
+            musicbrainz: false,
+            lastfm_api_key: Some(String::new()),
+            ..Default::default()
+        };
+        assert!(
+            MetadataEnricher::from_config(&no_providers, client.clone(), spawner.clone()).is_none()
+        );
+
+        let enabled = EnrichmentConfig {
+            enabled: true,
+            ..Default::default()
+        };
+        let enricher = MetadataEnricher::from_config(&enabled, client, spawner).unwrap();
+        assert_eq!(enricher.providers.len(), 1);
+    }
+}
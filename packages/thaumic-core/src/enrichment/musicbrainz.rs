@@ -0,0 +1,207 @@
+//! MusicBrainz recording search provider.
+//!
+//! Uses the public search API (no key required). MusicBrainz asks clients to
+//! stay at or below one request per second and to send an identifying
+//! User-Agent; both are enforced here. Artwork comes from the Cover Art
+//! Archive, which serves images by MusicBrainz release ID.
+
+use std::time::Duration;
+
+use async_trait::async_trait;
+use reqwest::{Client, Url};
+use serde::Deserialize;
+
+use super::{EnrichmentError, EnrichmentQuery, MetadataProvider, TrackInfo, USER_AGENT};
+
+/// Recording search endpoint.
+const SEARCH_URL: &str = "https://musicbrainz.org/ws/2/recording";
+
+/// Minimum search score (0-100) accepted as a match.
+const MIN_SCORE: u8 = 90;
+
+/// MusicBrainz rate limit for anonymous clients.
+const MIN_INTERVAL: Duration = Duration::from_secs(1);
+
+#[derive(Deserialize)]
+struct SearchResponse {
+    #[serde(default)]
+    recordings: Vec<Recording>,
+}
+
+#[derive(Deserialize)]
+struct Recording {
+    title: String,
+    #[serde(default)]
+    score: u8,
+    #[serde(rename = "artist-credit", default)]
+    artist_credit: Vec<ArtistCredit>,
+    #[serde(default)]
+    releases: Vec<Release>,
+}
+
+#[derive(Deserialize)]
+struct ArtistCredit {
+    name: String,
+    #[serde(default)]
+    joinphrase: String,
+}
+
+#[derive(Deserialize)]
+struct Release {
+    id: String,
+    title: String,
+}
+
+/// Looks up recordings on MusicBrainz.
+pub struct MusicBrainzProvider {
+    client: Client,
+}
+
+impl MusicBrainzProvider {
+    pub fn new(client: Client) -> Self {
+        Self { client }
+    }
+}
+
+#[async_trait]
+impl MetadataProvider for MusicBrainzProvider {
+    fn name(&self) -> &'static str {
+        "musicbrainz"
+    }
+
+    fn min_interval(&self) -> Duration {
+        MIN_INTERVAL
+    }
+
+    async fn lookup(&self, query: &EnrichmentQuery) -> Result<Option<TrackInfo>, EnrichmentError> {
+        let url = Url::parse_with_params(
+            SEARCH_URL,
+            [
+                ("query", search_expression(query).as_str()),
+                ("fmt", "json"),
+                ("limit", "1"),
+            ],
+        )
+        .map_err(|e| EnrichmentError::Parse(e.to_string()))?;
+        let response = self
+            .client
+            .get(url)
+            .header(reqwest::header::USER_AGENT, USER_AGENT)
+            .send()
+            .await?
+            .error_for_status()?;
+
+        let body = response.text().await?;
+        parse_response(&body)
+    }
+}
+
+/// Builds a Lucene search expression for the query.
+fn search_expression(query: &EnrichmentQuery) -> String {
+    let mut expr = format!("recording:\"{}\"", escape_lucene(&query.title));
+    if let Some(artist) = &query.artist {
+        expr.push_str(&format!(" AND artist:\"{}\"", escape_lucene(artist)));
+    }
+    expr
+}
+
+/// Escapes characters that are special inside a quoted Lucene phrase.
+fn escape_lucene(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+/// Parses a search response into the best confident match.
+fn parse_response(body: &str) -> Result<Option<TrackInfo>, EnrichmentError> {
+    let response: SearchResponse =
+        serde_json::from_str(body).map_err(|e| EnrichmentError::Parse(e.to_string()))?;
+
+    let Some(recording) = response
+        .recordings
+        .into_iter()
+        .find(|r| r.score >= MIN_SCORE && !r.artist_credit.is_empty())
+    else {
+        return Ok(None);
+    };
+
+    let artist = recording
+        .artist_credit
+        .iter()
+        .map(|c| format!("{}{}", c.name, c.joinphrase))
+        .collect::<String>();
+    let release = recording.releases.into_iter().next();
+
+    Ok(Some(TrackInfo {
+        title: recording.title,
+        artist,
+        artwork_url: release
+            .as_ref()
+            .map(|r| format!("https://coverartarchive.org/release/{}/front-250", r.id)),
+        album: release.map(|r| r.title),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_best_recording() {
+        let body = r#"{
+            "recordings": [{
+                "id": "rec-1",
+                "score": 100,
+                "title": "Harder, Better, Faster, Stronger",
+                "artist-credit": [{"name": "Daft Punk", "joinphrase": ""}],
+                "releases": [{"id": "rel-1", "title": "Discovery"}]
+            }]
+        }"#;
+
+        let info = parse_response(body).unwrap().unwrap();
+        assert_eq!(info.title, "Harder, Better, Faster, Stronger");
+        assert_eq!(info.artist, "Daft Punk");
+        assert_eq!(info.album.as_deref(), Some("Discovery"));
+        assert_eq!(
+            info.artwork_url.as_deref(),
+            Some("https://coverartarchive.org/release/rel-1/front-250")
+        );
+    }
+
+    #[test]
+    fn joins_multiple_artist_credits() {
+        let body = r#"{"recordings": [{
+            "score": 95, "title": "Song",
+            "artist-credit": [
+                {"name": "A", "joinphrase": " feat. "},
+                {"name": "B"}
+            ]
+        }]}"#;
+
+        let info = parse_response(body).unwrap().unwrap();
+        assert_eq!(info.artist, "A feat. B");
+        assert_eq!(info.album, None);
+        assert_eq!(info.artwork_url, None);
+    }
+
+    #[test]
+    fn rejects_low_confidence_matches() {
+        let body = r#"{"recordings": [{
+            "score": 60, "title": "Song",
+            "artist-credit": [{"name": "Someone"}]
+        }]}"#;
+        assert_eq!(parse_response(body).unwrap(), None);
+        assert_eq!(parse_response(r#"{"recordings": []}"#).unwrap(), None);
+        assert!(parse_response("not json").is_err());
+    }
+
+    #[test]
+    fn builds_escaped_search_expression() {
+        let query = EnrichmentQuery {
+            title: r#"Say "Hi""#.to_string(),
+            artist: Some("Band".to_string()),
+        };
+        assert_eq!(
+            search_expression(&query),
+            r#"recording:"Say \"Hi\"" AND artist:"Band""#
+        );
+    }
+}
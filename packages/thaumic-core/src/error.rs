@@ -3,6 +3,7 @@
 //! This module provides a unified error handling system that:
 //! - Defines structured error types using `thiserror`
 //! - Maps errors to appropriate HTTP status codes
+//! - Attaches machine-readable remediation hints to error responses
 //! - Implements `IntoResponse` for automatic JSON error responses
 
 use axum::http::StatusCode;
@@ -11,10 +12,51 @@ use axum::Json;
 use serde::Serialize;
 use thiserror::Error;
 
+use crate::artwork::ArtworkError;
+use crate::outputs::OutputError;
 use crate::sonos::discovery::DiscoveryError;
 use crate::sonos::gena::GenaError;
 use crate::sonos::soap::SoapError;
 
+/// Machine-readable hint describing how a client can recover from an error.
+///
+/// Serialized into the `details` field of API error responses so clients can
+/// present a targeted suggestion instead of parsing the message text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Remediation {
+    /// Suggested action identifier (e.g., `"check_speaker_power"`).
+    pub action: &'static str,
+    /// Short human-readable suggestion for the user.
+    pub hint: &'static str,
+    /// Whether retrying the same request may succeed without user action.
+    pub retryable: bool,
+    /// When the speaker the error is about was last discovered, in
+    /// milliseconds since the Unix epoch.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_seen: Option<u64>,
+}
+
+impl Remediation {
+    /// Creates a new remediation hint.
+    #[must_use]
+    pub const fn new(action: &'static str, hint: &'static str, retryable: bool) -> Self {
+        Self {
+            action,
+            hint,
+            retryable,
+            last_seen: None,
+        }
+    }
+
+    /// Adds when the speaker concerned was last discovered.
+    #[must_use]
+    pub const fn with_last_seen(mut self, last_seen: Option<u64>) -> Self {
+        self.last_seen = last_seen;
+        self
+    }
+}
+
 /// Trait for error types that provide machine-readable error codes.
 ///
 /// Implement this trait to provide consistent error codes across different
@@ -22,8 +64,28 @@ use crate::sonos::soap::SoapError;
 pub trait ErrorCode {
     /// Returns a machine-readable error code for API responses.
     fn code(&self) -> &'static str;
+
+    /// Returns a remediation hint for this error, if one applies.
+    fn remediation(&self) -> Option<Remediation> {
+        None
+    }
+
+    /// Returns the i18n message key for this error (e.g., `"error.ip_unreachable"`).
+    ///
+    /// Clients look this key up in their own locale files and fall back to the
+    /// English `message` when no translation exists.
+    fn message_key(&self) -> String {
+        format!("error.{}", self.code())
+    }
 }
 
+/// Suggestion for speakers that don't answer.
+const SPEAKER_UNREACHABLE: Remediation = Remediation::new(
+    "check_speaker_power",
+    "Check that the speaker is powered on and connected to Wi-Fi",
+    true,
+);
+
 impl ErrorCode for DiscoveryError {
     fn code(&self) -> &'static str {
         match self {
@@ -33,25 +95,68 @@ impl ErrorCode for DiscoveryError {
             Self::AllMethodsFailed(_) => "all_discovery_methods_failed",
             Self::IpUnreachable(_) => "ip_unreachable",
             Self::NotSonosDevice(_) => "not_sonos_device",
+            Self::HostUnresolved(_) => "host_unresolved",
         }
     }
+
+    fn remediation(&self) -> Option<Remediation> {
+        Some(match self {
+            Self::SocketBind(_) | Self::AllMethodsFailed(_) => Remediation::new(
+                "check_firewall",
+                "Allow Thaumic Cast through the firewall and disconnect any VPN",
+                true,
+            ),
+            Self::NoInterfaces => Remediation::new(
+                "check_network_connection",
+                "Connect this computer to the same network as your speakers",
+                true,
+            ),
+            Self::MdnsDaemon(_) => Remediation::new(
+                "restart_app",
+                "Restart Thaumic Cast to reinitialize network discovery",
+                false,
+            ),
+            Self::IpUnreachable(_) => SPEAKER_UNREACHABLE,
+            Self::NotSonosDevice(_) => Remediation::new(
+                "verify_ip_address",
+                "Verify the IP address in the Sonos app under About My System",
+                false,
+            ),
+            Self::HostUnresolved(_) => Remediation::new(
+                "check_hostname",
+                "Check the hostname is known to your router's DNS, or enter the IP address",
+                true,
+            ),
+        })
+    }
 }
 
 /// Application-wide error type for the Thaumic Cast server.
 #[derive(Debug, Error, Serialize)]
 #[serde(tag = "type", content = "details")]
 pub enum ThaumicError {
-    /// Speaker discovery failed (SSDP/network issues).
-    #[error("Discovery failed: {0}")]
-    Discovery(String),
+    /// Speaker discovery or probing failed (SSDP/network issues).
+    ///
+    /// Keeps the discovery error so its code and remediation reach clients.
+    #[error("Discovery failed: {error}")]
+    Discovery {
+        #[serde(serialize_with = "serialize_display")]
+        error: DiscoveryError,
+        /// When the probed speaker was last discovered (ms since epoch).
+        last_seen: Option<u64>,
+    },
 
     /// SOAP request to Sonos speaker failed.
     #[error("SOAP request failed: {0}")]
     Soap(String),
 
     /// Speaker not found or unreachable.
-    #[error("Speaker not found: {0}")]
-    SpeakerNotFound(String),
+    #[error("Speaker not found: {target}")]
+    SpeakerNotFound {
+        target: String,
+        /// When the speaker was last discovered (ms since epoch).
+        last_seen: Option<u64>,
+    },
 
     /// Requested stream ID does not exist.
     #[error("Stream not found: {0}")]
@@ -77,29 +182,171 @@ pub enum ThaumicError {
     /// Returns `"data_dir_not_configured"` for API compatibility.
     #[error("Data directory not configured: {0}")]
     DataDirNotConfigured(String),
+
+    /// The endpoint only serves requests from this machine.
+    #[error("Forbidden: {0}")]
+    Forbidden(String),
+
+    /// The request's API key is missing or wrong.
+    #[error("Unauthorized: {0}")]
+    Unauthorized(String),
+
+    /// Persisting configuration to disk failed.
+    ///
+    /// Returns `"save_failed"` for API compatibility.
+    #[error("Failed to save configuration: {0}")]
+    SaveFailed(String),
+
+    /// Requested operation ID is not running.
+    #[error("Operation not found: {0}")]
+    OperationNotFound(String),
+
+    /// A long-running operation was cancelled before it finished.
+    #[error("Operation cancelled: {0}")]
+    Cancelled(String),
+
+    /// A browser extension the user hasn't approved yet called the API.
+    ///
+    /// The extension retries once the origin is approved in the desktop app.
+    #[error("Origin not approved yet: {0}")]
+    OriginApprovalRequired(String),
+
+    /// The speaker plays a PIN-locked stream and the request's PIN is
+    /// missing or wrong.
+    #[error("Stream locked: {0}")]
+    StreamLocked(String),
+
+    /// The data directory is full or read-only.
+    ///
+    /// Settings changes keep working in memory but are lost on restart.
+    #[error("Storage unavailable: {0}")]
+    StorageUnavailable(String),
 }
 
 impl ThaumicError {
+    /// Creates a [`ThaumicError::SpeakerNotFound`] without a last-seen time.
+    pub fn speaker_not_found(target: impl Into<String>) -> Self {
+        Self::SpeakerNotFound {
+            target: target.into(),
+            last_seen: None,
+        }
+    }
+
+    /// Records when the speaker the error is about was last discovered.
+    ///
+    /// Only discovery and speaker-not-found errors carry it; others are
+    /// returned unchanged.
+    #[must_use]
+    pub fn with_last_seen(mut self, seen: Option<u64>) -> Self {
+        if let Self::Discovery { last_seen, .. } | Self::SpeakerNotFound { last_seen, .. } =
+            &mut self
+        {
+            *last_seen = seen;
+        }
+        self
+    }
+
     /// Returns a machine-readable error code for API responses.
     pub fn code(&self) -> &'static str {
         match self {
-            Self::Discovery(_) => "discovery_failed",
+            Self::Discovery { error, .. } => error.code(),
             Self::Soap(_) => "soap_error",
-            Self::SpeakerNotFound(_) => "speaker_not_found",
+            Self::SpeakerNotFound { .. } => "speaker_not_found",
             Self::StreamNotFound(_) => "stream_not_found",
             Self::InvalidRequest(_) => "invalid_request",
             Self::InvalidIp(_) => "invalid_ip",
             Self::Internal(_) => "internal_error",
             Self::DataDirNotConfigured(_) => "data_dir_not_configured",
+            Self::SaveFailed(_) => "save_failed",
+            Self::Forbidden(_) => "forbidden",
+            Self::Unauthorized(_) => "unauthorized",
+            Self::OperationNotFound(_) => "operation_not_found",
+            Self::Cancelled(_) => "operation_cancelled",
+            Self::OriginApprovalRequired(_) => "origin_approval_required",
+            Self::StreamLocked(_) => "stream_locked",
+            Self::StorageUnavailable(_) => "storage_unavailable",
+        }
+    }
+
+    /// Returns a remediation hint for this error, if one applies.
+    pub fn remediation(&self) -> Option<Remediation> {
+        match self {
+            Self::Discovery { error, last_seen } => error
+                .remediation()
+                .map(|remediation| remediation.with_last_seen(*last_seen)),
+            Self::Soap(_) => Some(SPEAKER_UNREACHABLE),
+            Self::SpeakerNotFound { last_seen, .. } => {
+                Some(SPEAKER_UNREACHABLE.with_last_seen(*last_seen))
+            }
+            Self::StreamNotFound(_) => Some(Remediation::new(
+                "restart_stream",
+                "Start casting again from the browser extension",
+                false,
+            )),
+            Self::InvalidIp(_) => Some(Remediation::new(
+                "verify_ip_address",
+                "Enter the speaker's IPv4 address as shown in the Sonos app",
+                false,
+            )),
+            Self::DataDirNotConfigured(_) => Some(Remediation::new(
+                "configure_data_dir",
+                "Start the server with --data-dir or set THAUMIC_DATA_DIR",
+                false,
+            )),
+            Self::SaveFailed(_) => Some(Remediation::new(
+                "check_disk_permissions",
+                "Check that the data directory exists and is writable",
+                true,
+            )),
+            Self::Unauthorized(_) => Some(Remediation::new(
+                "check_api_key",
+                "Send the server's ingest API key as a Bearer token",
+                false,
+            )),
+            Self::OriginApprovalRequired(_) => Some(Remediation::new(
+                "approve_origin",
+                "Approve this browser extension in the Thaumic Cast desktop app",
+                true,
+            )),
+            Self::StreamLocked(_) => Some(Remediation::new(
+                "enter_stream_pin",
+                "Enter the PIN the stream was locked with",
+                false,
+            )),
+            Self::StorageUnavailable(_) => Some(Remediation::new(
+                "free_disk_space",
+                "Free up disk space or make the data directory writable",
+                true,
+            )),
+            Self::InvalidRequest(_)
+            | Self::Internal(_)
+            | Self::Forbidden(_)
+            | Self::OperationNotFound(_)
+            | Self::Cancelled(_) => None,
         }
     }
 
     /// Maps the error to an appropriate HTTP status code.
     pub fn status_code(&self) -> StatusCode {
         match self {
-            Self::SpeakerNotFound(_) | Self::StreamNotFound(_) => StatusCode::NOT_FOUND,
+            Self::SpeakerNotFound { .. } | Self::StreamNotFound(_) | Self::OperationNotFound(_) => {
+                StatusCode::NOT_FOUND
+            }
+            // Probes of an address the user entered
+            Self::Discovery {
+                error:
+                    DiscoveryError::IpUnreachable(_)
+                    | DiscoveryError::NotSonosDevice(_)
+                    | DiscoveryError::HostUnresolved(_),
+                ..
+            } => StatusCode::BAD_REQUEST,
             Self::InvalidRequest(_) | Self::InvalidIp(_) => StatusCode::BAD_REQUEST,
             Self::DataDirNotConfigured(_) => StatusCode::SERVICE_UNAVAILABLE,
+            Self::Forbidden(_) | Self::OriginApprovalRequired(_) => StatusCode::FORBIDDEN,
+            Self::Unauthorized(_) => StatusCode::UNAUTHORIZED,
+            Self::Cancelled(_) => StatusCode::CONFLICT,
+            Self::StreamLocked(_) => StatusCode::LOCKED,
+            Self::StorageUnavailable(_) => StatusCode::INSUFFICIENT_STORAGE,
             _ => StatusCode::INTERNAL_SERVER_ERROR,
         }
     }
@@ -117,22 +364,54 @@ pub use crate::sonos::soap::SoapResult;
 /// Convenient Result alias for application-wide operations.
 pub type ThaumicResult<T> = Result<T, ThaumicError>;
 
-/// JSON response body for error responses.
-#[derive(Serialize)]
-struct ErrorResponse {
-    error: &'static str,
-    message: String,
-    status: u16,
+impl ErrorCode for ThaumicError {
+    fn code(&self) -> &'static str {
+        ThaumicError::code(self)
+    }
+
+    fn remediation(&self) -> Option<Remediation> {
+        ThaumicError::remediation(self)
+    }
+}
+
+/// JSON body for error responses.
+///
+/// Shared by `ThaumicError`, `api_error` and the desktop's Tauri commands so
+/// every failure reaches clients in the same shape:
+/// `{ error, message, messageKey, status, details? }`.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ApiErrorResponse {
+    /// Machine-readable error code (e.g., `"speaker_not_found"`).
+    pub error: &'static str,
+    /// Human-readable error message (English).
+    pub message: String,
+    /// i18n key clients use to localize `message`.
+    pub message_key: String,
+    /// HTTP status code, also set for errors that never cross HTTP.
+    pub status: u16,
+    /// Remediation hint, if the error provides one.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub details: Option<Remediation>,
+}
+
+impl ApiErrorResponse {
+    /// Builds a response body from any error that provides an error code.
+    pub fn from_error<E: ErrorCode + std::fmt::Display>(status: StatusCode, err: &E) -> Self {
+        Self {
+            error: err.code(),
+            message: err.to_string(),
+            message_key: err.message_key(),
+            status: status.as_u16(),
+            details: err.remediation(),
+        }
+    }
 }
 
 impl IntoResponse for ThaumicError {
     fn into_response(self) -> Response {
         let status = self.status_code();
-        let body = ErrorResponse {
-            error: self.code(),
-            message: self.to_string(),
-            status: status.as_u16(),
-        };
+        let body = ApiErrorResponse::from_error(status, &self);
         (status, Json(body)).into_response()
     }
 }
@@ -150,8 +429,44 @@ impl From<SoapError> for ThaumicError {
 }
 
 impl From<DiscoveryError> for ThaumicError {
-    fn from(err: DiscoveryError) -> Self {
-        Self::Discovery(err.to_string())
+    fn from(error: DiscoveryError) -> Self {
+        Self::Discovery {
+            error,
+            last_seen: None,
+        }
+    }
+}
+
+/// Serializes an error field by its message.
+fn serialize_display<T: std::fmt::Display, S: serde::Serializer>(
+    value: &T,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.collect_str(value)
+}
+
+impl From<ArtworkError> for ThaumicError {
+    fn from(err: ArtworkError) -> Self {
+        match err {
+            ArtworkError::Decode(_) | ArtworkError::InvalidName(_) | ArtworkError::NotFound(_) => {
+                Self::InvalidRequest(err.to_string())
+            }
+            ArtworkError::NoDataDir => Self::DataDirNotConfigured(err.to_string()),
+            ArtworkError::Io(_) => Self::SaveFailed(err.to_string()),
+        }
+    }
+}
+
+impl From<OutputError> for ThaumicError {
+    fn from(err: OutputError) -> Self {
+        match err {
+            OutputError::UnknownTarget(_) | OutputError::DeviceNotFound(_) => {
+                Self::speaker_not_found(err.to_string())
+            }
+            OutputError::AlreadyRegistered(_) | OutputError::Backend(_) => {
+                Self::Internal(err.to_string())
+            }
+        }
     }
 }
 
@@ -172,4 +487,65 @@ mod tests {
         assert_eq!(err.code(), "data_dir_not_configured");
         assert_eq!(err.status_code(), StatusCode::SERVICE_UNAVAILABLE);
     }
+
+    #[test]
+    fn save_failed_keeps_legacy_code() {
+        let err = ThaumicError::SaveFailed("disk full".into());
+        assert_eq!(err.code(), "save_failed");
+        assert_eq!(err.status_code(), StatusCode::INTERNAL_SERVER_ERROR);
+    }
+
+    #[test]
+    fn ip_unreachable_suggests_checking_power() {
+        let err = DiscoveryError::IpUnreachable("192.168.1.50".into());
+        let remediation = err.remediation().expect("remediation");
+        assert_eq!(remediation.action, "check_speaker_power");
+        assert!(remediation.retryable);
+    }
+
+    #[test]
+    fn error_response_includes_details_when_present() {
+        let err = ThaumicError::speaker_not_found("192.168.1.50");
+        let body = ApiErrorResponse::from_error(err.status_code(), &err);
+        let json = serde_json::to_value(&body).unwrap();
+        assert_eq!(json["error"], "speaker_not_found");
+        assert_eq!(json["messageKey"], "error.speaker_not_found");
+        assert_eq!(json["status"], 404);
+        assert_eq!(json["details"]["action"], "check_speaker_power");
+        assert_eq!(json["details"]["retryable"], true);
+        assert!(json["details"].get("lastSeen").is_none());
+
+        let err = err.with_last_seen(Some(1_700_000_000_000));
+        let json =
+            serde_json::to_value(ApiErrorResponse::from_error(err.status_code(), &err)).unwrap();
+        assert_eq!(json["details"]["lastSeen"], 1_700_000_000_000u64);
+    }
+
+    #[test]
+    fn discovery_errors_keep_their_code_and_remediation() {
+        let err = ThaumicError::from(DiscoveryError::IpUnreachable("192.168.1.50".into()))
+            .with_last_seen(Some(1_700_000_000_000));
+        assert_eq!(err.code(), "ip_unreachable");
+        assert_eq!(err.message_key(), "error.ip_unreachable");
+        assert_eq!(err.status_code(), StatusCode::BAD_REQUEST);
+        let remediation = err.remediation().expect("remediation");
+        assert_eq!(remediation.action, "check_speaker_power");
+        assert_eq!(remediation.last_seen, Some(1_700_000_000_000));
+
+        let err = ThaumicError::from(DiscoveryError::NoInterfaces);
+        assert_eq!(err.code(), "no_network_interfaces");
+        assert_eq!(
+            err.remediation().map(|r| r.action),
+            Some("check_network_connection")
+        );
+        assert_eq!(err.status_code(), StatusCode::INTERNAL_SERVER_ERROR);
+    }
+
+    #[test]
+    fn error_response_omits_details_when_absent() {
+        let err = ThaumicError::Internal("boom".into());
+        let body = ApiErrorResponse::from_error(err.status_code(), &err);
+        let json = serde_json::to_value(&body).unwrap();
+        assert!(json.get("details").is_none());
+    }
 }
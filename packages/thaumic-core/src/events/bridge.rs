@@ -2,88 +2,89 @@
 //!
 //! The [`BroadcastEventBridge`] lives at the boundary between domain services
 //! and transport concerns, mapping typed domain events to the WebSocket
-//! broadcast channel.
+//! broadcast channels.
 
 use std::sync::Arc;
 
 use parking_lot::RwLock;
-use tokio::sync::broadcast;
 
+use super::bus::{EventBus, EventBusStats, EventReceiver};
 use super::emitter::EventEmitter;
-use super::{BroadcastEvent, LatencyEvent, NetworkEvent, SonosEvent, StreamEvent, TopologyEvent};
+use super::{
+    BroadcastEvent, LatencyEvent, NetworkEvent, ResourceEvent, SonosEvent, StateEvent, StreamEvent,
+    TopologyEvent,
+};
 
 /// Bridges domain events to the WebSocket broadcast channel.
 ///
 /// This adapter implements [`EventEmitter`] by forwarding events to
-/// a `tokio::sync::broadcast` channel that WebSocket handlers subscribe to.
+/// prioritized `tokio::sync::broadcast` channels that WebSocket handlers
+/// subscribe to (see [`EventPriority`](super::EventPriority)).
 ///
-/// For platform-specific emission (e.g., Tauri frontend), the bridge also
-/// forwards to an optional external emitter that can be set after construction.
+/// For platform-specific emission (e.g., Tauri frontend) and event logs, the
+/// bridge also forwards to external emitters that can be added after
+/// construction.
 ///
 /// # Thread Safety
 ///
 /// The bridge is `Send + Sync` and can be shared across async tasks.
-/// The external emitter uses `RwLock` to allow setting it after construction.
+/// The external emitters use `RwLock` to allow adding them after construction.
 #[derive(Clone)]
 pub struct BroadcastEventBridge {
-    tx: broadcast::Sender<BroadcastEvent>,
-    /// Optional external emitter for platform-specific event delivery
-    external_emitter: Arc<RwLock<Option<Arc<dyn EventEmitter>>>>,
+    bus: EventBus,
+    /// External emitters for platform-specific delivery and event logs
+    external_emitters: Arc<RwLock<Vec<Arc<dyn EventEmitter>>>>,
 }
 
-impl BroadcastEventBridge {
-    /// Creates a new bridge with the given channel capacity.
-    pub fn new(capacity: usize) -> Self {
-        let (tx, _) = broadcast::channel(capacity);
-        Self {
-            tx,
-            external_emitter: Arc::new(RwLock::new(None)),
-        }
+impl Default for BroadcastEventBridge {
+    fn default() -> Self {
+        Self::new()
     }
+}
 
-    /// Creates a new bridge wrapping an existing broadcast sender.
-    pub fn with_sender(tx: broadcast::Sender<BroadcastEvent>) -> Self {
+impl BroadcastEventBridge {
+    /// Creates a new bridge with one bounded channel per event priority.
+    pub fn new() -> Self {
         Self {
-            tx,
-            external_emitter: Arc::new(RwLock::new(None)),
+            bus: EventBus::new(),
+            external_emitters: Arc::new(RwLock::new(Vec::new())),
         }
     }
 
-    /// Sets an external emitter for platform-specific event delivery.
+    /// Adds an external emitter that receives every event.
     ///
-    /// This is typically used by the desktop app to forward events to the
-    /// Tauri frontend in addition to the WebSocket broadcast.
+    /// This is used by the desktop app to forward events to the Tauri
+    /// frontend, and for event log sinks, in addition to the WebSocket
+    /// broadcast.
     ///
     /// Can be called after construction, which is useful when the platform
     /// handle (e.g., Tauri AppHandle) isn't available until later.
-    pub fn set_external_emitter(&self, emitter: Arc<dyn EventEmitter>) {
-        *self.external_emitter.write() = Some(emitter);
+    pub fn add_external_emitter(&self, emitter: Arc<dyn EventEmitter>) {
+        self.external_emitters.write().push(emitter);
     }
 
-    /// Returns a new receiver for the broadcast channel.
+    /// Returns a new receiver for the broadcast channels.
     ///
     /// WebSocket handlers use this to subscribe to events.
-    pub fn subscribe(&self) -> broadcast::Receiver<BroadcastEvent> {
-        self.tx.subscribe()
+    pub fn subscribe(&self) -> EventReceiver {
+        self.bus.subscribe()
     }
 
-    /// Returns a reference to the broadcast sender.
-    pub fn sender(&self) -> &broadcast::Sender<BroadcastEvent> {
-        &self.tx
+    /// Returns events sent and dropped per priority.
+    pub fn stats(&self) -> EventBusStats {
+        self.bus.stats()
     }
 }
 
-/// Generates an [`EventEmitter`] method that forwards to the external emitter
-/// (if set) and then sends to the broadcast channel.
+/// Generates an [`EventEmitter`] method that forwards to the external
+/// emitters and then sends to the broadcast channel of its priority.
 macro_rules! impl_emit {
     ($method:ident, $event_ty:ty, $variant:ident) => {
         fn $method(&self, event: $event_ty) {
-            if let Some(ref emitter) = *self.external_emitter.read() {
+            for emitter in self.external_emitters.read().iter() {
                 emitter.$method(event.clone());
             }
-            if let Err(e) = self.tx.send(BroadcastEvent::$variant(event)) {
-                log::trace!("[EventBridge] No broadcast receivers: {}", e);
-            }
+            self.bus.send(BroadcastEvent::$variant(event));
         }
     };
 }
@@ -94,4 +95,6 @@ impl EventEmitter for BroadcastEventBridge {
     impl_emit!(emit_network, NetworkEvent, Network);
     impl_emit!(emit_topology, TopologyEvent, Topology);
     impl_emit!(emit_latency, LatencyEvent, Latency);
+    impl_emit!(emit_resource, ResourceEvent, Resource);
+    impl_emit!(emit_state, StateEvent, State);
 }
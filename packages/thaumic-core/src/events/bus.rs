@@ -0,0 +1,293 @@
+//! Prioritized event channels behind the [`BroadcastEventBridge`].
+//!
+//! A single broadcast channel drops its oldest events for receivers that fall
+//! behind, whatever they are; during an event storm a burst of latency
+//! updates could push a `PlaybackStopFailed` out before a client read it.
+//! Events are therefore split by [`EventPriority`] into separate bounded
+//! channels, so each class only overflows into itself, and receivers read
+//! the more important channels first.
+//!
+//! [`BroadcastEventBridge`]: super::BroadcastEventBridge
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use serde::Serialize;
+use tokio::sync::broadcast::{self, error::RecvError};
+
+use super::{BroadcastEvent, LatencyEvent, SonosEvent, StreamEvent};
+use crate::protocol_constants::{
+    CHATTY_EVENT_CHANNEL_CAPACITY, CRITICAL_EVENT_CHANNEL_CAPACITY, EVENT_CHANNEL_CAPACITY,
+};
+
+/// How much an event matters to clients, deciding which channel carries it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EventPriority {
+    /// Playback and stream lifecycle; clients act on these.
+    Critical,
+    /// Topology, volume, metadata and everything else.
+    Normal,
+    /// Frequent updates superseded by the next one (latency, position).
+    Chatty,
+}
+
+impl EventPriority {
+    fn index(self) -> usize {
+        self as usize
+    }
+}
+
+impl BroadcastEvent {
+    /// Returns the priority the event is delivered with.
+    #[must_use]
+    pub fn priority(&self) -> EventPriority {
+        match self {
+            Self::Stream(
+                StreamEvent::Created { .. }
+                | StreamEvent::Ended { .. }
+                | StreamEvent::ListenersLost { .. }
+                | StreamEvent::PlaybackStarted { .. }
+                | StreamEvent::PlaybackStopped { .. }
+                | StreamEvent::PlaybackStopFailed { .. }
+                | StreamEvent::PlaybackSuspended { .. }
+                | StreamEvent::PlaybackStoppedExternally { .. }
+                | StreamEvent::PlaybackResumed { .. },
+            )
+            | Self::Sonos(
+                SonosEvent::TransportState { .. }
+                | SonosEvent::SourceChanged { .. }
+                | SonosEvent::SubscriptionLost { .. },
+            ) => EventPriority::Critical,
+            Self::Stream(StreamEvent::Position { .. })
+            | Self::Latency(LatencyEvent::Updated { .. }) => EventPriority::Chatty,
+            _ => EventPriority::Normal,
+        }
+    }
+}
+
+/// Delivery counters for one priority.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PriorityStats {
+    /// Events emitted.
+    pub sent: u64,
+    /// Events receivers missed because they fell behind, summed over
+    /// receivers.
+    pub dropped: u64,
+}
+
+/// Event bus statistics.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EventBusStats {
+    pub critical: PriorityStats,
+    pub normal: PriorityStats,
+    pub chatty: PriorityStats,
+    /// Receivers currently subscribed (WebSocket clients and internal
+    /// services).
+    pub receivers: usize,
+}
+
+#[derive(Default)]
+struct Counters {
+    sent: [AtomicU64; 3],
+    dropped: [AtomicU64; 3],
+}
+
+impl Counters {
+    fn stats(&self, priority: EventPriority) -> PriorityStats {
+        PriorityStats {
+            sent: self.sent[priority.index()].load(Ordering::Relaxed),
+            dropped: self.dropped[priority.index()].load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// One bounded broadcast channel per [`EventPriority`].
+#[derive(Clone)]
+pub(super) struct EventBus {
+    channels: [broadcast::Sender<BroadcastEvent>; 3],
+    counters: Arc<Counters>,
+}
+
+impl EventBus {
+    pub(super) fn new() -> Self {
+        Self::with_capacities([
+            CRITICAL_EVENT_CHANNEL_CAPACITY,
+            EVENT_CHANNEL_CAPACITY,
+            CHATTY_EVENT_CHANNEL_CAPACITY,
+        ])
+    }
+
+    fn with_capacities(capacities: [usize; 3]) -> Self {
+        Self {
+            channels: capacities.map(|capacity| broadcast::channel(capacity).0),
+            counters: Arc::default(),
+        }
+    }
+
+    /// Sends an event on the channel of its priority.
+    pub(super) fn send(&self, event: BroadcastEvent) {
+        let priority = event.priority();
+        self.counters.sent[priority.index()].fetch_add(1, Ordering::Relaxed);
+        if let Err(e) = self.channels[priority.index()].send(event) {
+            log::trace!("[EventBridge] No broadcast receivers: {}", e);
+        }
+    }
+
+    pub(super) fn subscribe(&self) -> EventReceiver {
+        EventReceiver {
+            channels: self.channels.each_ref().map(broadcast::Sender::subscribe),
+            counters: Arc::clone(&self.counters),
+        }
+    }
+
+    pub(super) fn stats(&self) -> EventBusStats {
+        EventBusStats {
+            critical: self.counters.stats(EventPriority::Critical),
+            normal: self.counters.stats(EventPriority::Normal),
+            chatty: self.counters.stats(EventPriority::Chatty),
+            receivers: self.channels[0].receiver_count(),
+        }
+    }
+}
+
+/// Receives events from every priority channel, most important first.
+///
+/// Events of different priorities may arrive out of emission order when
+/// several are waiting: critical ones overtake the rest.
+pub struct EventReceiver {
+    channels: [broadcast::Receiver<BroadcastEvent>; 3],
+    counters: Arc<Counters>,
+}
+
+impl EventReceiver {
+    /// Receives the next event.
+    ///
+    /// Like [`broadcast::Receiver::recv`], returns `Lagged` with the number
+    /// of critical or normal events skipped when this receiver fell behind.
+    /// Skipped chatty events are only counted, since the next one replaces
+    /// them anyway. Cancel safe.
+    ///
+    /// # Errors
+    ///
+    /// `Lagged` as above, `Closed` once the bus is gone.
+    pub async fn recv(&mut self) -> Result<BroadcastEvent, RecvError> {
+        loop {
+            let [critical, normal, chatty] = &mut self.channels;
+            let (priority, result) = tokio::select! {
+                biased;
+                result = critical.recv() => (EventPriority::Critical, result),
+                result = normal.recv() => (EventPriority::Normal, result),
+                result = chatty.recv() => (EventPriority::Chatty, result),
+            };
+            match result {
+                Err(RecvError::Lagged(skipped)) => {
+                    self.counters.dropped[priority.index()].fetch_add(skipped, Ordering::Relaxed);
+                    if priority != EventPriority::Chatty {
+                        return Err(RecvError::Lagged(skipped));
+                    }
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+impl std::fmt::Debug for EventReceiver {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EventReceiver").finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stale_latency() -> BroadcastEvent {
+        BroadcastEvent::Latency(LatencyEvent::Stale {
+            stream_id: "s".into(),
+            speaker_ip: "192.168.1.10".into(),
+            epoch_id: 0,
+            timestamp: 0,
+        })
+    }
+
+    fn stop_failed() -> BroadcastEvent {
+        BroadcastEvent::Stream(StreamEvent::PlaybackStopFailed {
+            stream_id: "s".into(),
+            speaker_ip: "192.168.1.10".into(),
+            error: "timeout".into(),
+            reason: None,
+            timestamp: 0,
+        })
+    }
+
+    #[test]
+    fn classifies_events() {
+        assert_eq!(stop_failed().priority(), EventPriority::Critical);
+        assert_eq!(stale_latency().priority(), EventPriority::Normal);
+    }
+
+    #[tokio::test]
+    async fn chatty_storm_does_not_displace_critical_events() {
+        let bus = EventBus::with_capacities([4, 4, 4]);
+        let mut rx = bus.subscribe();
+
+        let chatty = || {
+            BroadcastEvent::Latency(LatencyEvent::Updated {
+                stream_id: "s".into(),
+                speaker_ip: "192.168.1.10".into(),
+                epoch_id: 0,
+                latency_ms: 100,
+                jitter_ms: 1,
+                confidence: 1.0,
+                timestamp: 0,
+            })
+        };
+        bus.send(stop_failed());
+        for _ in 0..10 {
+            bus.send(chatty());
+        }
+
+        let first = rx.recv().await.unwrap();
+        assert!(matches!(
+            first,
+            BroadcastEvent::Stream(StreamEvent::PlaybackStopFailed { .. })
+        ));
+        // The overflowed chatty events are skipped without an error
+        for _ in 0..4 {
+            assert_eq!(rx.recv().await.unwrap().priority(), EventPriority::Chatty);
+        }
+
+        let stats = bus.stats();
+        assert_eq!(
+            stats.critical,
+            PriorityStats {
+                sent: 1,
+                dropped: 0
+            }
+        );
+        assert_eq!(
+            stats.chatty,
+            PriorityStats {
+                sent: 10,
+                dropped: 6
+            }
+        );
+        assert_eq!(stats.receivers, 1);
+    }
+
+    #[tokio::test]
+    async fn critical_lag_is_reported() {
+        let bus = EventBus::with_capacities([2, 2, 2]);
+        let mut rx = bus.subscribe();
+        for _ in 0..5 {
+            bus.send(stop_failed());
+        }
+
+        assert!(matches!(rx.recv().await, Err(RecvError::Lagged(3))));
+        assert!(rx.recv().await.is_ok());
+        assert_eq!(bus.stats().critical.dropped, 3);
+    }
+}
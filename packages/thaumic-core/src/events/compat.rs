@@ -0,0 +1,161 @@
+//! Event wire format versioning.
+//!
+//! Clients pick the event protocol version they understand when they connect
+//! (`/ws?protocolVersion=N`). Events are sent in the newest shape that version
+//! can parse: anything introduced later is translated to an older equivalent
+//! where one exists and otherwise withheld, so an extension that predates a
+//! server upgrade keeps working instead of choking on unknown payloads.
+//!
+//! Version history:
+//! - 1: sonos, stream, network, topology and latency events. Clients that
+//!   don't send a version are assumed to speak this one.
+//! - 2: adds the `resource` category and the `metadataUpdated`,
+//!   `bufferAdapted` and `feedbackLoopSuspected` stream events.
+//! - 3: adds the `position` stream event.
+//! - 4: adds the `originApprovalRequested` network event.
+//! - 5: adds the `playbackSuspended` and `playbackResumed` stream events.
+//! - 6: adds the `listenersLost` stream event.
+//! - 7: adds the `crossSubnetSpeakers` network event.
+//! - 8: adds the `labelChanged` stream event.
+//! - 9: adds the `playbackStoppedExternally` stream event.
+//! - 10: adds the `lockedSpeakerChanged` stream event.
+//! - 11: adds the `storageDegraded` and `storageRecovered` resource events.
+//! - 12: adds the `speakerAdded`, `speakerRemoved` and `speakerIpChanged`
+//!   topology events.
+//! - 13: adds the `state` category with the `snapshot` and `patch` events.
+//!
+//! When changing an event's shape or adding a variant, bump
+//! [`EVENT_PROTOCOL_VERSION`], record it in [`BroadcastEvent::since_version`]
+//! and add a translation to `downgrade` if older clients can show it somehow.
+
+use std::borrow::Cow;
+
+use super::{BroadcastEvent, NetworkEvent, ResourceEvent, StreamEvent, TopologyEvent};
+
+/// Current event protocol version.
+pub const EVENT_PROTOCOL_VERSION: u32 = 13;
+
+/// Oldest event protocol version still served.
+pub const MIN_EVENT_PROTOCOL_VERSION: u32 = 1;
+
+/// Returns the version to use for a client requesting `requested`.
+///
+/// Clients from before versioning send nothing and get version 1. Requests
+/// outside the supported range are clamped to it.
+pub fn negotiate_version(requested: Option<u32>) -> u32 {
+    requested
+        .unwrap_or(MIN_EVENT_PROTOCOL_VERSION)
+        .clamp(MIN_EVENT_PROTOCOL_VERSION, EVENT_PROTOCOL_VERSION)
+}
+
+impl BroadcastEvent {
+    /// Returns the protocol version that introduced this event's shape.
+    pub fn since_version(&self) -> u32 {
+        match self {
+            Self::Sonos(_) | Self::Latency(_) => 1,
+            Self::Topology(event) => match event {
+                TopologyEvent::GroupsDiscovered { .. } => 1,
+                TopologyEvent::SpeakerAdded { .. }
+                | TopologyEvent::SpeakerRemoved { .. }
+                | TopologyEvent::SpeakerIpChanged { .. } => 12,
+            },
+            Self::Network(event) => match event {
+                NetworkEvent::HealthChanged { .. } => 1,
+                NetworkEvent::OriginApprovalRequested { .. } => 4,
+                NetworkEvent::CrossSubnetSpeakers { .. } => 7,
+            },
+            Self::Stream(event) => match event {
+                StreamEvent::Created { .. }
+                | StreamEvent::Ended { .. }
+                | StreamEvent::PlaybackStarted { .. }
+                | StreamEvent::PlaybackStopped { .. }
+                | StreamEvent::PlaybackStopFailed { .. } => 1,
+                StreamEvent::MetadataUpdated { .. }
+                | StreamEvent::BufferAdapted { .. }
+                | StreamEvent::FeedbackLoopSuspected { .. } => 2,
+                StreamEvent::Position { .. } => 3,
+                StreamEvent::PlaybackSuspended { .. } | StreamEvent::PlaybackResumed { .. } => 5,
+                StreamEvent::ListenersLost { .. } => 6,
+                StreamEvent::LabelChanged { .. } => 8,
+                StreamEvent::PlaybackStoppedExternally { .. } => 9,
+                StreamEvent::LockedSpeakerChanged { .. } => 10,
+            },
+            Self::Resource(event) => match event {
+                ResourceEvent::CpuOverload { .. } | ResourceEvent::CpuRecovered { .. } => 2,
+                ResourceEvent::StorageDegraded { .. } | ResourceEvent::StorageRecovered { .. } => {
+                    11
+                }
+            },
+            Self::State(_) => 13,
+        }
+    }
+}
+
+/// Serializes an event for a client speaking `version`.
+///
+/// Returns `None` if the event has no representation in that version.
+pub fn encode_for_version(event: &BroadcastEvent, version: u32) -> Option<String> {
+    let event = downgrade(event, version)?;
+    match serde_json::to_string(&event) {
+        Ok(json) => Some(json),
+        Err(e) => {
+            log::warn!("[Events] Failed to serialize event: {}", e);
+            None
+        }
+    }
+}
+
+/// Translates an event into a shape `version` understands.
+fn downgrade(event: &BroadcastEvent, version: u32) -> Option<Cow<'_, BroadcastEvent>> {
+    if event.since_version() <= version {
+        return Some(Cow::Borrowed(event));
+    }
+
+    // Nothing added since version 1 has an older equivalent: metadata and
+    // diagnostics are only shown by clients that know about them
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::NetworkHealth;
+
+    #[test]
+    fn negotiates_supported_versions() {
+        assert_eq!(negotiate_version(None), 1);
+        assert_eq!(negotiate_version(Some(0)), MIN_EVENT_PROTOCOL_VERSION);
+        assert_eq!(negotiate_version(Some(2)), 2);
+        assert_eq!(negotiate_version(Some(99)), EVENT_PROTOCOL_VERSION);
+    }
+
+    #[test]
+    fn withholds_events_newer_than_client() {
+        let resource = BroadcastEvent::Resource(ResourceEvent::CpuRecovered {
+            cpu_percent: 12.0,
+            timestamp: 1,
+        });
+        let created = BroadcastEvent::Stream(StreamEvent::Created {
+            stream_id: "abc".to_string(),
+            timestamp: 1,
+        });
+
+        assert_eq!(encode_for_version(&resource, 1), None);
+        assert!(encode_for_version(&resource, 2).is_some());
+        assert_eq!(
+            encode_for_version(&created, 1),
+            serde_json::to_string(&created).ok()
+        );
+    }
+
+    #[test]
+    fn current_version_passes_everything() {
+        let event = BroadcastEvent::Network(NetworkEvent::HealthChanged {
+            health: NetworkHealth::Degraded,
+            reason: None,
+            timestamp: 1,
+        });
+        assert!(event.since_version() <= EVENT_PROTOCOL_VERSION);
+        assert!(encode_for_version(&event, EVENT_PROTOCOL_VERSION).is_some());
+    }
+}
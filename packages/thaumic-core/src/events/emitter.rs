@@ -3,7 +3,9 @@
 //! Services depend on the [`EventEmitter`] trait rather than concrete broadcast
 //! channels, enabling testing and alternative transport implementations.
 
-use super::{LatencyEvent, NetworkEvent, SonosEvent, StreamEvent, TopologyEvent};
+use super::{
+    LatencyEvent, NetworkEvent, ResourceEvent, SonosEvent, StateEvent, StreamEvent, TopologyEvent,
+};
 
 /// Trait for emitting domain events without knowledge of transport.
 ///
@@ -38,6 +40,12 @@ pub trait EventEmitter: Send + Sync {
 
     /// Emits a latency measurement event.
     fn emit_latency(&self, event: LatencyEvent);
+
+    /// Emits a resource usage event.
+    fn emit_resource(&self, event: ResourceEvent);
+
+    /// Emits a coalesced Sonos state update.
+    fn emit_state(&self, event: StateEvent);
 }
 
 #[cfg(test)]
@@ -73,6 +81,8 @@ mod tests {
         fn emit_network(&self, _event: NetworkEvent) {}
         fn emit_topology(&self, _event: TopologyEvent) {}
         fn emit_latency(&self, _event: LatencyEvent) {}
+        fn emit_resource(&self, _event: ResourceEvent) {}
+        fn emit_state(&self, _event: StateEvent) {}
     }
 
     #[test]
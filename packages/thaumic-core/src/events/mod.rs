@@ -2,22 +2,34 @@
 //!
 //! This module provides:
 //! - [`EventEmitter`] trait for domain services to emit events
-//! - [`BroadcastEventBridge`] for WebSocket transport
+//! - [`BroadcastEventBridge`] for WebSocket transport, over prioritized
+//!   channels ([`EventPriority`])
+//! - [`sinks`] for JSON event logs (file, syslog)
+//! - [`compat`] for wire format versioning
 //! - Event types for various domains (streams, network, etc.)
 //!
 //! The `SonosEvent` type is defined in [`crate::sonos::gena`] and re-exported here.
 
 mod bridge;
+mod bus;
+pub mod compat;
 mod emitter;
+pub mod sinks;
 
 pub use bridge::BroadcastEventBridge;
+pub use bus::{EventBusStats, EventPriority, EventReceiver, PriorityStats};
+pub use compat::{EVENT_PROTOCOL_VERSION, MIN_EVENT_PROTOCOL_VERSION};
 pub use emitter::EventEmitter;
+pub use sinks::{EventLogConfig, EventLogSink};
 
 // Re-export SonosEvent from sonos::gena for convenience
 pub use crate::sonos::gena::SonosEvent;
 
 use serde::{Deserialize, Serialize};
 
+use crate::storage::StorageIssue;
+use crate::stream::{FeedbackCause, StreamMetadata};
+
 /// Reasons for removing a speaker from an active cast session.
 ///
 /// - `SourceChanged`: User switched Sonos to another source (Spotify, AirPlay, etc.)
@@ -33,6 +45,18 @@ pub enum SpeakerRemovalReason {
     UserRemoved,
 }
 
+/// What changed on a speaker playing a PIN-locked stream.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum LockedSpeakerChange {
+    Volume,
+    Mute,
+    /// Playback was stopped.
+    Stopped,
+    /// Another source was selected.
+    Source,
+}
+
 /// Events broadcast to clients.
 ///
 /// This enum categorizes all real-time events that can be sent to connected
@@ -54,6 +78,12 @@ pub enum BroadcastEvent {
 
     /// Events related to latency measurement.
     Latency(LatencyEvent),
+
+    /// Events related to process resource usage.
+    Resource(ResourceEvent),
+
+    /// Coalesced changes to the Sonos state.
+    State(StateEvent),
 }
 
 /// Events related to audio stream state changes.
@@ -76,6 +106,38 @@ pub enum StreamEvent {
         /// Unix timestamp in milliseconds.
         timestamp: u64,
     },
+    /// No speaker has pulled a stream for the configured time, so it is
+    /// being stopped. `Ended` follows.
+    ListenersLost {
+        /// The unique identifier for the stream.
+        #[serde(rename = "streamId")]
+        stream_id: String,
+        /// Seconds the stream went without an HTTP listener.
+        #[serde(rename = "idleSecs")]
+        idle_secs: u64,
+        /// Unix timestamp in milliseconds.
+        timestamp: u64,
+    },
+    /// A stream's user-given label was set or cleared.
+    LabelChanged {
+        /// The stream whose label changed.
+        #[serde(rename = "streamId")]
+        stream_id: String,
+        /// The new label, or `None` if it was cleared.
+        label: Option<String>,
+        /// Unix timestamp in milliseconds.
+        timestamp: u64,
+    },
+    /// A stream's track metadata changed, including enrichment results.
+    MetadataUpdated {
+        /// The stream whose metadata changed.
+        #[serde(rename = "streamId")]
+        stream_id: String,
+        /// The stream's current metadata.
+        metadata: StreamMetadata,
+        /// Unix timestamp in milliseconds.
+        timestamp: u64,
+    },
     /// Playback started on a speaker.
     PlaybackStarted {
         /// The stream ID being played.
@@ -120,6 +182,132 @@ pub enum StreamEvent {
         /// Unix timestamp in milliseconds.
         timestamp: u64,
     },
+    /// Another source (Spotify, AirPlay, line-in) took over a speaker.
+    ///
+    /// The session is kept and resumes if the other source stops within
+    /// `resumeWindowSecs`; otherwise `PlaybackStopped` follows.
+    PlaybackSuspended {
+        /// The stream ID that was playing.
+        #[serde(rename = "streamId")]
+        stream_id: String,
+        /// The speaker IP address that switched source.
+        #[serde(rename = "speakerIp")]
+        speaker_ip: String,
+        /// URI of the source now playing.
+        #[serde(rename = "currentUri")]
+        current_uri: String,
+        /// Seconds the session waits for the other source to stop.
+        #[serde(rename = "resumeWindowSecs")]
+        resume_window_secs: u64,
+        /// Unix timestamp in milliseconds.
+        timestamp: u64,
+    },
+    /// A speaker was stopped from the Sonos app while its stream kept running.
+    ///
+    /// With `autoResume` the stream is started on it again right away.
+    /// Otherwise the session is held for `confirmWindowSecs`: clients can ask
+    /// the user and call `POST /api/playback/resume`, and `PlaybackStopped`
+    /// follows if nobody does.
+    PlaybackStoppedExternally {
+        /// The stream ID that was playing.
+        #[serde(rename = "streamId")]
+        stream_id: String,
+        /// The speaker IP address that was stopped.
+        #[serde(rename = "speakerIp")]
+        speaker_ip: String,
+        /// Whether the stream is being resumed without asking.
+        #[serde(rename = "autoResume")]
+        auto_resume: bool,
+        /// Seconds left to confirm resuming (absent with `autoResume`).
+        #[serde(rename = "confirmWindowSecs", skip_serializing_if = "Option::is_none")]
+        confirm_window_secs: Option<u64>,
+        /// Unix timestamp in milliseconds.
+        timestamp: u64,
+    },
+    /// A speaker playing a PIN-locked stream was changed without the PIN,
+    /// typically from the Sonos app, which the lock can't block.
+    LockedSpeakerChanged {
+        /// The locked stream.
+        #[serde(rename = "streamId")]
+        stream_id: String,
+        /// The speaker IP address that was changed.
+        #[serde(rename = "speakerIp")]
+        speaker_ip: String,
+        /// What changed.
+        change: LockedSpeakerChange,
+        /// Unix timestamp in milliseconds.
+        timestamp: u64,
+    },
+    /// A suspended or externally stopped session resumed.
+    PlaybackResumed {
+        /// The stream ID playing again.
+        #[serde(rename = "streamId")]
+        stream_id: String,
+        /// The speaker IP address playing the stream again.
+        #[serde(rename = "speakerIp")]
+        speaker_ip: String,
+        /// Unix timestamp in milliseconds.
+        timestamp: u64,
+    },
+    /// A speaker's cadence queue bound adapted to measured ingest jitter.
+    BufferAdapted {
+        /// The stream ID being played.
+        #[serde(rename = "streamId")]
+        stream_id: String,
+        /// The speaker IP address whose connection adapted.
+        #[serde(rename = "speakerIp")]
+        speaker_ip: String,
+        /// Previous queue bound in milliseconds of audio.
+        #[serde(rename = "previousBufferMs")]
+        previous_buffer_ms: u64,
+        /// New queue bound in milliseconds of audio.
+        #[serde(rename = "bufferMs")]
+        buffer_ms: u64,
+        /// Smoothed ingest jitter in milliseconds.
+        #[serde(rename = "jitterMs")]
+        jitter_ms: u64,
+        /// Unix timestamp in milliseconds.
+        timestamp: u64,
+    },
+    /// The captured tab appears to be playing this stream back into itself.
+    ///
+    /// The user should pick a different tab to cast.
+    FeedbackLoopSuspected {
+        /// The stream ID being fed back into itself.
+        #[serde(rename = "streamId")]
+        stream_id: String,
+        /// Which signal triggered the warning.
+        cause: FeedbackCause,
+        /// Measured loop delay in milliseconds (audio detection only).
+        #[serde(rename = "loopDelayMs", skip_serializing_if = "Option::is_none")]
+        loop_delay_ms: Option<u64>,
+        /// Unix timestamp in milliseconds.
+        timestamp: u64,
+    },
+    /// A speaker's playback position, pushed once a second while it plays the stream.
+    Position {
+        /// The stream ID being played.
+        #[serde(rename = "streamId")]
+        stream_id: String,
+        /// The speaker IP address that reported the position.
+        #[serde(rename = "speakerIp")]
+        speaker_ip: String,
+        /// The playback epoch ID (changes when the speaker reconnects).
+        #[serde(rename = "epochId")]
+        epoch_id: u64,
+        /// Position reported by the speaker (`RelTime`, second precision).
+        #[serde(rename = "relTimeMs")]
+        rel_time_ms: u64,
+        /// Track duration reported by the speaker, if any.
+        #[serde(rename = "trackDurationMs", skip_serializing_if = "Option::is_none")]
+        track_duration_ms: Option<u64>,
+        /// Stream time since the epoch's first audio frame when the position
+        /// was sampled, mapping `relTimeMs` onto the stream's timeline.
+        #[serde(rename = "streamElapsedMs")]
+        stream_elapsed_ms: u64,
+        /// Unix timestamp in milliseconds when the position was sampled.
+        timestamp: u64,
+    },
 }
 
 /// Network health status.
@@ -133,6 +321,23 @@ pub enum NetworkHealth {
     Degraded,
 }
 
+/// Network changes that can let speakers on another subnet work.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CrossSubnetHint {
+    /// Run an mDNS reflector (e.g. Avahi's `enable-reflector`, or the
+    /// router's mDNS repeater) between the subnets.
+    MdnsReflector,
+    /// Make sure IGMP snooping has a querier on the speakers' VLAN, or turn
+    /// it off, so SSDP multicast reaches them.
+    IgmpSnooping,
+    /// Add the speakers by IP and turn on unicast-only mode.
+    UnicastOnly,
+    /// Let the speakers open connections to this server, which they need to
+    /// fetch audio and deliver events.
+    AllowInbound,
+}
+
 /// Events related to network health and speaker reachability.
 #[derive(Debug, Clone, Serialize)]
 #[serde(tag = "type", rename_all = "camelCase")]
@@ -147,6 +352,30 @@ pub enum NetworkEvent {
         /// Unix timestamp in milliseconds.
         timestamp: u64,
     },
+    /// A browser extension that isn't trusted yet called the API and is
+    /// waiting for the user to approve it.
+    OriginApprovalRequested {
+        /// The extension's origin (`chrome-extension://<id>` for Chrome and
+        /// Edge, `moz-extension://<uuid>` for Firefox).
+        origin: String,
+        /// Unix timestamp in milliseconds.
+        timestamp: u64,
+    },
+    /// The set of speakers on subnets this host has no address on changed.
+    /// Multicast discovery and the speakers' connections back to us often
+    /// don't cross subnets without help.
+    CrossSubnetSpeakers {
+        /// IP the speakers are told to reach us at.
+        #[serde(rename = "localIp")]
+        local_ip: String,
+        /// Speakers on other subnets (empty once there are none left).
+        #[serde(rename = "speakerIps")]
+        speaker_ips: Vec<String>,
+        /// Network changes worth checking, most likely first.
+        hints: Vec<CrossSubnetHint>,
+        /// Unix timestamp in milliseconds.
+        timestamp: u64,
+    },
 }
 
 /// Events from topology discovery operations.
@@ -160,6 +389,43 @@ pub enum TopologyEvent {
         /// Unix timestamp in milliseconds.
         timestamp: u64,
     },
+    /// A speaker appeared in the topology. Follows the `GroupsDiscovered`
+    /// that includes it.
+    SpeakerAdded {
+        /// The new speaker.
+        speaker: crate::sonos::types::ZoneGroupMember,
+        /// Unix timestamp in milliseconds.
+        timestamp: u64,
+    },
+    /// A speaker left the topology (unplugged, offline or ignored).
+    SpeakerRemoved {
+        /// The speaker's UUID.
+        uuid: String,
+        /// The address it was last seen at.
+        #[serde(rename = "speakerIp")]
+        speaker_ip: String,
+        /// Its room name.
+        #[serde(rename = "zoneName")]
+        zone_name: String,
+        /// Unix timestamp in milliseconds.
+        timestamp: u64,
+    },
+    /// A known speaker is now at a different address (usually DHCP).
+    SpeakerIpChanged {
+        /// The speaker's UUID.
+        uuid: String,
+        /// Its room name.
+        #[serde(rename = "zoneName")]
+        zone_name: String,
+        /// The previous address.
+        #[serde(rename = "oldIp")]
+        old_ip: String,
+        /// The new address.
+        #[serde(rename = "newIp")]
+        new_ip: String,
+        /// Unix timestamp in milliseconds.
+        timestamp: u64,
+    },
 }
 
 /// Events related to audio latency measurement.
@@ -204,6 +470,82 @@ pub enum LatencyEvent {
     },
 }
 
+/// Coalesced updates of the Sonos state (groups, transport states, volumes
+/// and mutes), as returned by `GET /api/state`.
+///
+/// Changes are collected for a short window and sent as one event, so a burst
+/// of GENA notifications doesn't make clients re-render the whole household
+/// several times. Revisions increase by one per event; a client that misses
+/// one should fetch the full state again.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum StateEvent {
+    /// The full state, sent first and whenever the topology changed.
+    Snapshot {
+        /// Revision of this state.
+        revision: u64,
+        /// The state, shaped like `GET /api/state` without streams and
+        /// sessions.
+        state: serde_json::Value,
+        /// Unix timestamp in milliseconds.
+        timestamp: u64,
+    },
+    /// Entries that changed since the previous revision.
+    Patch {
+        /// Revision after applying the patch.
+        revision: u64,
+        /// Changed maps (`transportStates`, `groupVolumes`, ...), each with
+        /// only its changed speakers; `null` removes a speaker's entry.
+        changes: serde_json::Value,
+        /// Unix timestamp in milliseconds.
+        timestamp: u64,
+    },
+}
+
+/// Events related to process resource usage.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum ResourceEvent {
+    /// CPU usage stayed high while streaming; audio is likely to stutter.
+    CpuOverload {
+        /// Process CPU usage as a percentage of total machine capacity.
+        #[serde(rename = "cpuPercent")]
+        cpu_percent: f32,
+        /// How long CPU has been above the threshold, in seconds.
+        #[serde(rename = "sustainedSecs")]
+        sustained_secs: u64,
+        /// Number of streams active when the overload was detected.
+        #[serde(rename = "activeStreams")]
+        active_streams: usize,
+        /// Unix timestamp in milliseconds.
+        timestamp: u64,
+    },
+    /// CPU usage dropped back below the recovery threshold.
+    CpuRecovered {
+        /// Process CPU usage as a percentage of total machine capacity.
+        #[serde(rename = "cpuPercent")]
+        cpu_percent: f32,
+        /// Unix timestamp in milliseconds.
+        timestamp: u64,
+    },
+    /// The data directory is full or read-only; settings changes are kept in
+    /// memory and lost on restart.
+    StorageDegraded {
+        /// The data directory.
+        path: String,
+        issue: StorageIssue,
+        /// Unix timestamp in milliseconds.
+        timestamp: u64,
+    },
+    /// The data directory can be written to again.
+    StorageRecovered {
+        /// The data directory.
+        path: String,
+        /// Unix timestamp in milliseconds.
+        timestamp: u64,
+    },
+}
+
 // From implementations for converting inner events to BroadcastEvent
 impl From<SonosEvent> for BroadcastEvent {
     fn from(event: SonosEvent) -> Self {
@@ -234,3 +576,14 @@ impl From<LatencyEvent> for BroadcastEvent {
         BroadcastEvent::Latency(event)
     }
 }
+impl From<ResourceEvent> for BroadcastEvent {
+    fn from(event: ResourceEvent) -> Self {
+        BroadcastEvent::Resource(event)
+    }
+}
+
+impl From<StateEvent> for BroadcastEvent {
+    fn from(event: StateEvent) -> Self {
+        BroadcastEvent::State(event)
+    }
+}
@@ -0,0 +1,123 @@
+//! Size-rotated append-only event file.
+
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use super::LineWriter;
+
+/// Appends lines to a file, rotating `path` → `path.1` → `path.2` ... once
+/// it would grow past `max_bytes`.
+pub(super) struct RotatingFile {
+    path: PathBuf,
+    max_bytes: u64,
+    max_files: usize,
+    file: File,
+    size: u64,
+}
+
+impl RotatingFile {
+    pub(super) fn open(path: &Path, max_bytes: u64, max_files: usize) -> std::io::Result<Self> {
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent)?;
+        }
+        let file = append(path)?;
+        let size = file.metadata()?.len();
+        Ok(Self {
+            path: path.to_path_buf(),
+            max_bytes,
+            max_files,
+            file,
+            size,
+        })
+    }
+
+    fn rotated(&self, n: usize) -> PathBuf {
+        let mut name = self.path.clone().into_os_string();
+        name.push(format!(".{}", n));
+        PathBuf::from(name)
+    }
+
+    fn rotate(&mut self) -> std::io::Result<()> {
+        if self.max_files == 0 {
+            // No history kept: start the file over
+            self.file = File::create(&self.path)?;
+        } else {
+            let _ = std::fs::remove_file(self.rotated(self.max_files));
+            for n in (1..self.max_files).rev() {
+                let from = self.rotated(n);
+                if from.exists() {
+                    std::fs::rename(&from, self.rotated(n + 1))?;
+                }
+            }
+            std::fs::rename(&self.path, self.rotated(1))?;
+            self.file = append(&self.path)?;
+        }
+        self.size = 0;
+        Ok(())
+    }
+}
+
+impl LineWriter for RotatingFile {
+    fn write_line(&mut self, line: &str) -> std::io::Result<()> {
+        let len = line.len() as u64 + 1;
+        if self.size > 0 && self.size + len > self.max_bytes {
+            self.rotate()?;
+        }
+
+        let mut buf = Vec::with_capacity(line.len() + 1);
+        buf.extend_from_slice(line.as_bytes());
+        buf.push(b'\n');
+        self.file.write_all(&buf)?;
+        self.size += len;
+        Ok(())
+    }
+}
+
+fn append(path: &Path) -> std::io::Result<File> {
+    OpenOptions::new().create(true).append(true).open(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn read(path: &Path) -> String {
+        std::fs::read_to_string(path).unwrap_or_default()
+    }
+
+    #[test]
+    fn appends_and_rotates_by_size() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("logs").join("events.log");
+        let mut file = RotatingFile::open(&path, 12, 2).unwrap();
+
+        for line in [
+            "aaaaa", "bbbbb", "ccccc", "ddddd", "eeeee", "fffff", "ggggg",
+        ] {
+            file.write_line(line).unwrap();
+        }
+
+        assert_eq!(read(&path), "ggggg\n");
+        assert_eq!(
+            read(&dir.path().join("logs/events.log.1")),
+            "eeeee\nfffff\n"
+        );
+        assert_eq!(
+            read(&dir.path().join("logs/events.log.2")),
+            "ccccc\nddddd\n"
+        );
+        assert!(!dir.path().join("logs/events.log.3").exists());
+    }
+
+    #[test]
+    fn continues_existing_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("events.log");
+        std::fs::write(&path, "old\n").unwrap();
+
+        let mut file = RotatingFile::open(&path, 1024, 1).unwrap();
+        file.write_line("new").unwrap();
+        assert_eq!(read(&path), "old\nnew\n");
+    }
+}
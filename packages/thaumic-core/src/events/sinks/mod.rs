@@ -0,0 +1,238 @@
+//! Event log sinks.
+//!
+//! [`EventEmitter`] implementations that record every event as a line of
+//! JSON, for people who want event history they can grep or feed to their
+//! own tooling:
+//!
+//! - [`EventLogSink::file`]: appends to a size-rotated file
+//! - [`EventLogSink::syslog`]: forwards to the local syslog/journald socket or
+//!   a remote syslog server over UDP
+//!
+//! Sinks are attached to the [`BroadcastEventBridge`](super::BroadcastEventBridge)
+//! and write on a dedicated thread, so a slow disk or socket never delays the
+//! service emitting the event. If the writer falls behind, events are dropped
+//! rather than queued without bound.
+
+mod file;
+mod syslog;
+
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{self, SyncSender, TrySendError};
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+
+use file::RotatingFile;
+use syslog::SyslogWriter;
+
+use super::{
+    BroadcastEvent, EventEmitter, LatencyEvent, NetworkEvent, ResourceEvent, SonosEvent,
+    StateEvent, StreamEvent, TopologyEvent,
+};
+
+/// Events buffered for the writer thread before new ones are dropped.
+const QUEUE_CAPACITY: usize = 1024;
+
+/// Configuration for event log sinks. All sinks are off by default.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EventLogConfig {
+    /// File to append JSON events to.
+    pub file: Option<PathBuf>,
+    /// Size at which the event file is rotated.
+    pub file_max_bytes: u64,
+    /// Rotated files kept alongside the current one (`events.log.1`, ...).
+    pub file_max_files: usize,
+    /// Forward events to syslog.
+    pub syslog: bool,
+    /// Remote syslog server (`host:port`, UDP). Uses the local syslog
+    /// socket when unset.
+    pub syslog_address: Option<String>,
+}
+
+impl Default for EventLogConfig {
+    fn default() -> Self {
+        Self {
+            file: None,
+            file_max_bytes: 10 * 1024 * 1024,
+            file_max_files: 5,
+            syslog: false,
+            syslog_address: None,
+        }
+    }
+}
+
+impl EventLogConfig {
+    /// Opens every configured sink.
+    ///
+    /// A sink that fails to open is logged and skipped.
+    pub fn sinks(&self) -> Vec<Arc<dyn EventEmitter>> {
+        let mut sinks: Vec<Arc<dyn EventEmitter>> = Vec::new();
+
+        if let Some(path) = &self.file {
+            match EventLogSink::file(path, self.file_max_bytes, self.file_max_files) {
+                Ok(sink) => {
+                    log::info!("[EventLog] Writing events to {}", path.display());
+                    sinks.push(Arc::new(sink));
+                }
+                Err(e) => log::warn!(
+                    "[EventLog] Failed to open event file {}: {}",
+                    path.display(),
+                    e
+                ),
+            }
+        }
+
+        if self.syslog {
+            match EventLogSink::syslog(self.syslog_address.as_deref()) {
+                Ok(sink) => {
+                    log::info!("[EventLog] Forwarding events to syslog");
+                    sinks.push(Arc::new(sink));
+                }
+                Err(e) => log::warn!("[EventLog] Failed to connect to syslog: {}", e),
+            }
+        }
+
+        sinks
+    }
+}
+
+/// Destination for serialized event lines.
+trait LineWriter: Send + 'static {
+    fn write_line(&mut self, line: &str) -> std::io::Result<()>;
+}
+
+/// Records events as JSON lines, written on a dedicated thread.
+pub struct EventLogSink {
+    tx: SyncSender<BroadcastEvent>,
+    /// Events dropped because the writer fell behind, reported on the next write.
+    dropped: Arc<AtomicU64>,
+}
+
+impl EventLogSink {
+    /// Opens a sink appending to `path`, rotating it once it reaches
+    /// `max_bytes` and keeping `max_files` rotated files.
+    pub fn file(path: &Path, max_bytes: u64, max_files: usize) -> std::io::Result<Self> {
+        Self::spawn("file", RotatingFile::open(path, max_bytes, max_files)?)
+    }
+
+    /// Opens a sink forwarding to syslog: the local socket, or the remote
+    /// server at `address` (`host:port`, UDP) when given.
+    pub fn syslog(address: Option<&str>) -> std::io::Result<Self> {
+        Self::spawn("syslog", SyslogWriter::connect(address)?)
+    }
+
+    fn spawn(name: &str, mut writer: impl LineWriter) -> std::io::Result<Self> {
+        let (tx, rx) = mpsc::sync_channel::<BroadcastEvent>(QUEUE_CAPACITY);
+        let dropped = Arc::new(AtomicU64::new(0));
+        let thread_dropped = Arc::clone(&dropped);
+        let thread_name = name.to_string();
+
+        std::thread::Builder::new()
+            .name(format!("event-log-{}", name))
+            .spawn(move || {
+                // Ends when the sink (and its sender) is dropped
+                for event in rx {
+                    let skipped = thread_dropped.swap(0, Ordering::Relaxed);
+                    if skipped > 0 {
+                        log::warn!(
+                            "[EventLog] {} fell behind, dropped {} event(s)",
+                            thread_name,
+                            skipped
+                        );
+                    }
+                    let line = match serde_json::to_string(&event) {
+                        Ok(line) => line,
+                        Err(e) => {
+                            log::warn!("[EventLog] Failed to serialize event: {}", e);
+                            continue;
+                        }
+                    };
+                    if let Err(e) = writer.write_line(&line) {
+                        log::warn!("[EventLog] {} write failed: {}", thread_name, e);
+                    }
+                }
+            })?;
+
+        Ok(Self { tx, dropped })
+    }
+
+    fn send(&self, event: BroadcastEvent) {
+        match self.tx.try_send(event) {
+            Ok(()) => {}
+            Err(TrySendError::Full(_)) => {
+                self.dropped.fetch_add(1, Ordering::Relaxed);
+            }
+            Err(TrySendError::Disconnected(_)) => {
+                log::trace!("[EventLog] Writer thread has exited");
+            }
+        }
+    }
+}
+
+impl EventEmitter for EventLogSink {
+    fn emit_stream(&self, event: StreamEvent) {
+        // Live progress for UIs, not history
+        if matches!(event, StreamEvent::Position { .. }) {
+            return;
+        }
+        self.send(event.into());
+    }
+
+    fn emit_sonos(&self, event: SonosEvent) {
+        self.send(event.into());
+    }
+
+    fn emit_network(&self, event: NetworkEvent) {
+        self.send(event.into());
+    }
+
+    fn emit_topology(&self, event: TopologyEvent) {
+        self.send(event.into());
+    }
+
+    fn emit_latency(&self, event: LatencyEvent) {
+        self.send(event.into());
+    }
+
+    fn emit_resource(&self, event: ResourceEvent) {
+        self.send(event.into());
+    }
+
+    fn emit_state(&self, _event: StateEvent) {
+        // Derived from the sonos and topology events already logged
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn file_sink_writes_json_lines() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("events.log");
+        let sink = EventLogSink::file(&path, 1024 * 1024, 1).unwrap();
+
+        sink.emit_stream(StreamEvent::Created {
+            stream_id: "abc".to_string(),
+            timestamp: 1,
+        });
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        let contents = loop {
+            let contents = std::fs::read_to_string(&path).unwrap_or_default();
+            if !contents.is_empty() || Instant::now() > deadline {
+                break contents;
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        };
+
+        let event: serde_json::Value = serde_json::from_str(contents.trim_end()).unwrap();
+        assert_eq!(event["category"], "stream");
+        assert_eq!(event["type"], "created");
+        assert_eq!(event["streamId"], "abc");
+    }
+}
@@ -0,0 +1,140 @@
+//! Syslog forwarding.
+//!
+//! Locally, messages go to the syslog socket (`/dev/log`, which journald also
+//! listens on, or `/var/run/syslog` on macOS) in the traditional BSD format
+//! that local daemons expect. Remote servers get RFC 5424 messages over UDP.
+//! Events are logged with facility `daemon` and severity `info`.
+
+use std::net::{SocketAddr, ToSocketAddrs, UdpSocket};
+
+use super::LineWriter;
+
+/// Identifier messages are tagged with.
+const APP_NAME: &str = "thaumic-cast";
+
+/// Priority for facility `daemon` (3) and severity `info` (6).
+const PRIORITY: u8 = 3 * 8 + 6;
+
+/// Local syslog sockets, in the order tried.
+#[cfg(unix)]
+const LOCAL_SOCKETS: &[&str] = &["/dev/log", "/var/run/syslog", "/var/run/log"];
+
+enum Transport {
+    #[cfg(unix)]
+    Local(std::os::unix::net::UnixDatagram),
+    Udp(UdpSocket),
+}
+
+/// Writes event lines as syslog messages.
+pub(super) struct SyslogWriter {
+    transport: Transport,
+    hostname: String,
+    pid: u32,
+}
+
+impl SyslogWriter {
+    /// Connects to the remote server at `address`, or the local socket.
+    pub(super) fn connect(address: Option<&str>) -> std::io::Result<Self> {
+        let transport = match address {
+            Some(address) => {
+                let remote = address.to_socket_addrs()?.next().ok_or_else(|| {
+                    std::io::Error::new(std::io::ErrorKind::NotFound, "syslog address not found")
+                })?;
+                let local: SocketAddr = if remote.is_ipv4() {
+                    ([0, 0, 0, 0], 0).into()
+                } else {
+                    ([0u16; 8], 0).into()
+                };
+                let socket = UdpSocket::bind(local)?;
+                socket.connect(remote)?;
+                Transport::Udp(socket)
+            }
+            None => connect_local()?,
+        };
+
+        Ok(Self {
+            transport,
+            hostname: hostname::get()
+                .ok()
+                .and_then(|h| h.into_string().ok())
+                .unwrap_or_else(|| "-".to_string()),
+            pid: std::process::id(),
+        })
+    }
+}
+
+#[cfg(unix)]
+fn connect_local() -> std::io::Result<Transport> {
+    let socket = std::os::unix::net::UnixDatagram::unbound()?;
+    let mut last_err = None;
+    for path in LOCAL_SOCKETS {
+        match socket.connect(path) {
+            Ok(()) => return Ok(Transport::Local(socket)),
+            Err(e) => last_err = Some(e),
+        }
+    }
+    Err(last_err.unwrap_or_else(|| std::io::Error::other("no local syslog socket")))
+}
+
+#[cfg(not(unix))]
+fn connect_local() -> std::io::Result<Transport> {
+    Err(std::io::Error::new(
+        std::io::ErrorKind::Unsupported,
+        "no local syslog on this platform; set a syslog address",
+    ))
+}
+
+impl LineWriter for SyslogWriter {
+    fn write_line(&mut self, line: &str) -> std::io::Result<()> {
+        match &self.transport {
+            #[cfg(unix)]
+            Transport::Local(socket) => socket.send(local_message(self.pid, line).as_bytes()),
+            Transport::Udp(socket) => {
+                socket.send(remote_message(&self.hostname, self.pid, line).as_bytes())
+            }
+        }
+        .map(|_| ())
+    }
+}
+
+/// Formats a message for a local syslog daemon; it adds timestamp and host.
+fn local_message(pid: u32, line: &str) -> String {
+    format!("<{}>{}[{}]: {}", PRIORITY, APP_NAME, pid, line)
+}
+
+/// Formats an RFC 5424 message. The timestamp is left for the server to fill.
+fn remote_message(hostname: &str, pid: u32, line: &str) -> String {
+    format!(
+        "<{}>1 - {} {} {} - - {}",
+        PRIORITY, hostname, APP_NAME, pid, line
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formats_messages() {
+        assert_eq!(local_message(42, "{}"), "<30>thaumic-cast[42]: {}");
+        assert_eq!(
+            remote_message("host", 42, "{}"),
+            "<30>1 - host thaumic-cast 42 - - {}"
+        );
+    }
+
+    #[test]
+    fn sends_to_remote_server() {
+        let server = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let address = server.local_addr().unwrap().to_string();
+
+        let mut writer = SyslogWriter::connect(Some(&address)).unwrap();
+        writer.write_line(r#"{"category":"stream"}"#).unwrap();
+
+        let mut buf = [0u8; 512];
+        let len = server.recv(&mut buf).unwrap();
+        let message = std::str::from_utf8(&buf[..len]).unwrap();
+        assert!(message.starts_with("<30>1 - "));
+        assert!(message.ends_with(r#" - - {"category":"stream"}"#));
+    }
+}
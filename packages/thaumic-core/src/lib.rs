@@ -9,11 +9,17 @@
 //! The crate is organized into several modules:
 //!
 //! - [`runtime`]: Tokio-based task spawning
+//! - [`clock`]: Time source for background services
 //! - [`events`]: Event system for real-time client communication
 //! - [`context`]: Network configuration and URL building
 //! - [`state`]: Core application state and configuration
 //! - [`sonos`]: Sonos speaker control and discovery (UPnP/SOAP)
+//! - [`outputs`]: Registry of playback backends for other devices
 //! - [`stream`]: Audio streaming and transcoding
+//! - [`enrichment`]: Online lookup of missing track metadata
+//! - [`scrobble`]: Scrobbling to ListenBrainz and Last.fm
+//! - [`stats`]: Local casting statistics
+//! - [`storage`]: Data directory write health
 //! - [`error`]: Centralized error types
 //!
 //! # Abstraction Traits
@@ -23,6 +29,8 @@
 //!
 //! - [`EventEmitter`](events::EventEmitter): Emitting domain events
 //! - [`IpDetector`](context::IpDetector): Local IP detection
+//! - [`Clock`](clock::Clock): Time and sleeps for background services
+//! - [`OutputTarget`](outputs::OutputTarget): Playback on non-Sonos devices
 //!
 //! Each trait has default implementations suitable for the standalone server.
 //! The desktop app provides Tauri-specific implementations.
@@ -35,38 +43,80 @@ pub mod api;
 pub mod artwork;
 pub mod bootstrap;
 pub mod capture;
+pub mod clock;
+#[cfg(test)]
+mod conformance;
 pub mod context;
+pub mod enrichment;
 pub mod error;
 pub mod events;
 mod mdns_advertise;
+pub mod operations;
+pub mod outputs;
 pub mod protocol_constants;
 pub mod runtime;
+pub mod scrobble;
 pub mod services;
 pub mod sonos;
 pub mod state;
+pub mod stats;
+pub mod storage;
 pub mod stream;
 pub mod streaming_runtime;
+#[cfg(any(test, feature = "e2e"))]
+#[doc(hidden)]
+pub mod test_support;
 pub mod utils;
 
 // Re-export commonly used types at the crate root
-pub use artwork::{ArtworkConfig, ArtworkSource};
-pub use context::{IpDetector, LocalIpDetector, NetworkContext, NetworkError, UrlBuilder};
-pub use error::{DiscoveryResult, ErrorCode, GenaResult, SoapResult, ThaumicError, ThaumicResult};
+pub use artwork::{ArtworkConfig, ArtworkError, ArtworkLibrary, ArtworkSource};
+pub use clock::{Clock, MockClock, SystemClock};
+pub use context::{
+    IpDetector, LocalIpDetector, NetworkContext, NetworkError, SubnetAddress, UrlBuilder,
+};
+pub use enrichment::{EnrichmentConfig, MetadataEnricher, MetadataProvider};
+pub use error::{
+    ApiErrorResponse, DiscoveryResult, ErrorCode, GenaResult, Remediation, SoapResult,
+    ThaumicError, ThaumicResult,
+};
 pub use events::{
-    BroadcastEvent, BroadcastEventBridge, EventEmitter, LatencyEvent, NetworkEvent, NetworkHealth,
-    SonosEvent, SpeakerRemovalReason, StreamEvent, TopologyEvent,
+    BroadcastEvent, BroadcastEventBridge, CrossSubnetHint, EventBusStats, EventEmitter,
+    EventLogConfig, EventLogSink, EventPriority, EventReceiver, LatencyEvent, LockedSpeakerChange,
+    NetworkEvent, NetworkHealth, ResourceEvent, SonosEvent, SpeakerRemovalReason, StateEvent,
+    StreamEvent, TopologyEvent, EVENT_PROTOCOL_VERSION,
+};
+pub use operations::{Operation, OperationInfo, OperationKind, OperationRegistry};
+pub use outputs::{
+    OutputDevice, OutputError, OutputPlayRequest, OutputTarget, OutputTargetRegistry,
 };
 pub use runtime::TokioSpawner;
-pub use state::{Config, ManualSpeakerConfig, SonosState, StreamingConfig};
-pub use utils::{now_millis, validate_speaker_ip, IpValidationError};
+pub use scrobble::{ScrobbleConfig, ScrobbleSink};
+pub use state::{
+    Config, CoordinatorPin, CoordinatorPreferences, ExternalStopPolicy, GenaListener,
+    ManualSpeakerConfig, SonosState, SpeakerSetup, SpeakerSetupImport, StreamingConfig,
+};
+pub use stats::{StatsStore, StatsSummary};
+pub use storage::StorageIssue;
+pub use utils::{now_millis, validate_speaker_host, validate_speaker_ip, IpValidationError};
 
 // Re-export Sonos types
-pub use sonos::discovery::{probe_speaker_by_ip, Speaker};
-pub use sonos::types::{TransportState, ZoneGroup};
+pub use sonos::bandwidth::{
+    estimate_bandwidth, estimate_bandwidth_with_progress, BandwidthError, BandwidthEstimate,
+};
+pub use sonos::discovery::{
+    probe_speaker_by_host, probe_speaker_by_ip, resolve_speaker_host, DiscoveryDiagnostics, Speaker,
+};
+pub use sonos::inventory::{collect_inventory, FirmwareInventory, SpeakerFirmware};
+pub use sonos::soap::{SoapClient, SoapSpeakerStats};
+pub use sonos::types::{SpeakerMove, TransportState, ZoneGroup};
 pub use sonos::{SonosClient, SonosClientImpl, SonosPlayback, SonosService, SonosTopologyClient};
 
 // Re-export service types
-pub use services::playback_session_store::PlaybackSession;
+pub use services::gena_event_processor::GenaNotifyStats;
+pub use services::origin_approvals::{OriginApprovalState, OriginApprovals, PendingOrigin};
+pub use services::playback_session_store::{PlaybackSession, PlaybackSessionInfo};
+pub use services::resource_monitor::ResourceStats;
+pub use services::rtp_receiver::RtpIngestConfig;
 
 // Re-export capture types
 pub use capture::{
@@ -83,7 +133,10 @@ pub use bootstrap::{bootstrap_services, bootstrap_services_with_network, Bootstr
 pub use streaming_runtime::StreamingRuntime;
 
 // Re-export API types
-pub use api::{start_server, AppState, ServerError, WsConnectionManager};
+pub use api::{
+    start_server, AppState, ClientInfo, ClientPurpose, ConnectedClient, ExtensionBrowser,
+    IngestConfig, OriginPolicy, OriginPolicyBuilder, ServerError, WsConnectionManager,
+};
 
 /// Default artwork for Sonos album art display.
 ///
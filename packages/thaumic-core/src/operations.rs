@@ -0,0 +1,242 @@
+//! Registry of in-flight long-running operations.
+//!
+//! Discovery runs, speaker probes and bandwidth tests can take several
+//! seconds. Each one registers itself here for as long as it runs, reporting
+//! progress as it goes, so clients can list what the server is busy with
+//! (`GET /api/operations`) and cancel an operation they no longer want
+//! (`POST /api/operations/{id}/cancel`).
+//!
+//! An [`Operation`] is a guard: it unregisters itself when dropped, so an
+//! operation that finishes, fails or is cancelled never lingers in the list.
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use parking_lot::Mutex;
+use serde::Serialize;
+use tokio_util::sync::CancellationToken;
+
+use crate::utils::now_millis;
+
+/// What a long-running operation is doing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum OperationKind {
+    /// Full topology refresh: SSDP/mDNS scan, manual speaker probes, GENA sync.
+    Discovery,
+    /// Probe of a single IP to check it is a Sonos speaker.
+    SpeakerProbe,
+    /// Throughput test against a speaker.
+    BandwidthTest,
+    /// Check that a speaker can fetch audio from this server.
+    ReachabilityCheck,
+}
+
+/// Snapshot of an in-flight operation.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OperationInfo {
+    /// Unique identifier, used to cancel the operation.
+    pub id: String,
+    pub kind: OperationKind,
+    /// What the operation acts on (e.g., a speaker IP), if anything.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub target: Option<String>,
+    /// Current step (e.g., `"discovering"`), if the operation reports steps.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stage: Option<&'static str>,
+    /// Units of work done so far.
+    pub completed: u64,
+    /// Total units of work, when known.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub total: Option<u64>,
+    /// Whether cancellation has been requested.
+    pub cancelling: bool,
+    /// Unix timestamp (ms) when the operation started.
+    pub started_at: u64,
+}
+
+struct Entry {
+    info: OperationInfo,
+    token: CancellationToken,
+}
+
+/// Tracks in-flight long-running operations.
+#[derive(Default)]
+pub struct OperationRegistry {
+    entries: Mutex<HashMap<String, Entry>>,
+    next_id: AtomicU64,
+}
+
+impl OperationRegistry {
+    /// Creates an empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers a new operation.
+    ///
+    /// The operation stays listed until the returned guard is dropped.
+    pub fn start(self: &Arc<Self>, kind: OperationKind, target: Option<String>) -> Operation {
+        let id = format!("op-{}", self.next_id.fetch_add(1, Ordering::Relaxed) + 1);
+        let token = CancellationToken::new();
+
+        self.entries.lock().insert(
+            id.clone(),
+            Entry {
+                info: OperationInfo {
+                    id: id.clone(),
+                    kind,
+                    target,
+                    stage: None,
+                    completed: 0,
+                    total: None,
+                    cancelling: false,
+                    started_at: now_millis(),
+                },
+                token: token.clone(),
+            },
+        );
+
+        Operation {
+            id,
+            token,
+            registry: Arc::clone(self),
+        }
+    }
+
+    /// Returns all in-flight operations, oldest first.
+    pub fn list(&self) -> Vec<OperationInfo> {
+        let mut operations: Vec<OperationInfo> = self
+            .entries
+            .lock()
+            .values()
+            .map(|entry| entry.info.clone())
+            .collect();
+        operations.sort_by_key(|op| (op.started_at, op.id.len(), op.id.clone()));
+        operations
+    }
+
+    /// Requests cancellation of the operation with `id`.
+    ///
+    /// Returns `false` if no such operation is running.
+    pub fn cancel(&self, id: &str) -> bool {
+        let mut entries = self.entries.lock();
+        let Some(entry) = entries.get_mut(id) else {
+            return false;
+        };
+        if !entry.info.cancelling {
+            log::info!("[Operations] Cancelling {} ({:?})", id, entry.info.kind);
+            entry.info.cancelling = true;
+            entry.token.cancel();
+        }
+        true
+    }
+
+    fn update(&self, id: &str, f: impl FnOnce(&mut OperationInfo)) {
+        if let Some(entry) = self.entries.lock().get_mut(id) {
+            f(&mut entry.info);
+        }
+    }
+}
+
+/// Guard for a registered operation. Unregisters it when dropped.
+pub struct Operation {
+    id: String,
+    token: CancellationToken,
+    registry: Arc<OperationRegistry>,
+}
+
+impl Operation {
+    /// Returns the operation's identifier.
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// Returns the token cancelled when a client cancels this operation.
+    pub fn token(&self) -> &CancellationToken {
+        &self.token
+    }
+
+    /// Reports the current step.
+    pub fn set_stage(&self, stage: &'static str) {
+        self.registry
+            .update(&self.id, |info| info.stage = Some(stage));
+    }
+
+    /// Reports units of work done so far, and the total when known.
+    pub fn set_progress(&self, completed: u64, total: Option<u64>) {
+        self.registry.update(&self.id, |info| {
+            info.completed = completed;
+            info.total = total;
+        });
+    }
+
+    /// Runs `future` until it completes or the operation is cancelled.
+    ///
+    /// Returns `None` if cancelled; the future is dropped at its current
+    /// await point.
+    pub async fn run<F: Future>(&self, future: F) -> Option<F::Output> {
+        tokio::select! {
+            _ = self.token.cancelled() => {
+                log::info!("[Operations] {} cancelled", self.id);
+                None
+            }
+            output = future => Some(output),
+        }
+    }
+}
+
+impl Drop for Operation {
+    fn drop(&mut self) {
+        self.registry.entries.lock().remove(&self.id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lists_operations_until_dropped() {
+        let registry = Arc::new(OperationRegistry::new());
+        let probe = registry.start(OperationKind::SpeakerProbe, Some("192.168.1.20".into()));
+        let discovery = registry.start(OperationKind::Discovery, None);
+        discovery.set_stage("discovering");
+        discovery.set_progress(1, Some(4));
+
+        let listed = registry.list();
+        assert_eq!(listed.len(), 2);
+        let info = listed.iter().find(|op| op.id == discovery.id()).unwrap();
+        assert_eq!(info.stage, Some("discovering"));
+        assert_eq!((info.completed, info.total), (1, Some(4)));
+
+        drop(probe);
+        let listed = registry.list();
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].kind, OperationKind::Discovery);
+
+        drop(discovery);
+        assert!(registry.list().is_empty());
+    }
+
+    #[tokio::test]
+    async fn cancel_stops_running_future() {
+        let registry = Arc::new(OperationRegistry::new());
+        let op = registry.start(OperationKind::BandwidthTest, None);
+
+        assert!(registry.cancel(op.id()));
+        assert!(registry.list()[0].cancelling);
+        assert_eq!(op.run(std::future::pending::<()>()).await, None);
+        assert!(!registry.cancel("op-unknown"));
+    }
+
+    #[tokio::test]
+    async fn run_returns_output_when_not_cancelled() {
+        let registry = Arc::new(OperationRegistry::new());
+        let op = registry.start(OperationKind::Discovery, None);
+        assert_eq!(op.run(async { 7 }).await, Some(7));
+    }
+}
@@ -0,0 +1,340 @@
+//! Playback backends for devices other than Sonos speakers.
+//!
+//! Sonos support is built in. Other ecosystems (Bluesound, KEF, ...) plug in
+//! by implementing [`OutputTarget`] and registering it with the
+//! [`OutputTargetRegistry`] at startup. Their devices are then listed by
+//! `/api/outputs` and can play any stream, without changes to the core
+//! modules.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use futures::future::join_all;
+use parking_lot::RwLock;
+use serde::Serialize;
+use thiserror::Error;
+
+use crate::stream::{AudioCodec, AudioFormat, StreamMetadata};
+
+/// Errors reported by output targets and their registry.
+#[derive(Debug, Error)]
+pub enum OutputError {
+    /// Another target already uses this ID.
+    #[error("Output target already registered: {0}")]
+    AlreadyRegistered(String),
+
+    /// No target is registered under this ID.
+    #[error("Unknown output target: {0}")]
+    UnknownTarget(String),
+
+    /// The device wasn't found by the target's last discovery.
+    #[error("Output device not found: {0}")]
+    DeviceNotFound(String),
+
+    /// The backend failed to talk to a device.
+    #[error("{0}")]
+    Backend(String),
+}
+
+/// A device found by an output target.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OutputDevice {
+    /// ID of the target that found the device (e.g. `"bluesound"`).
+    pub target: String,
+    /// Stable device ID, unique within its target.
+    pub id: String,
+    /// Friendly name shown to the user.
+    pub name: String,
+    /// Network address the backend controls the device at.
+    pub address: String,
+    /// Model name, if the device reports one.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model_name: Option<String>,
+}
+
+/// What an output device should play.
+#[derive(Debug, Clone)]
+pub struct OutputPlayRequest {
+    /// URL the device fetches the stream from.
+    pub stream_url: String,
+    /// Codec the stream is served in.
+    pub codec: AudioCodec,
+    /// Audio format of the stream.
+    pub audio_format: AudioFormat,
+    /// Current track details, for devices that display them.
+    pub metadata: Option<StreamMetadata>,
+    /// Album art URL.
+    pub artwork_url: String,
+}
+
+/// A playback backend for one kind of device.
+#[async_trait]
+pub trait OutputTarget: Send + Sync {
+    /// Short, unique ID for the backend (e.g. `"bluesound"`).
+    fn id(&self) -> &str;
+
+    /// Finds the backend's devices on the network.
+    ///
+    /// Returned devices must carry this target's [`id`](Self::id).
+    async fn discover(&self) -> Result<Vec<OutputDevice>, OutputError>;
+
+    /// Starts playing a stream on a device.
+    async fn play(
+        &self,
+        device: &OutputDevice,
+        request: &OutputPlayRequest,
+    ) -> Result<(), OutputError>;
+
+    /// Stops playback on a device.
+    async fn stop(&self, device: &OutputDevice) -> Result<(), OutputError>;
+}
+
+/// Registered output targets and the devices they last discovered.
+#[derive(Default)]
+pub struct OutputTargetRegistry {
+    targets: RwLock<Vec<Arc<dyn OutputTarget>>>,
+    /// Devices from each target's last discovery, keyed by target ID.
+    devices: RwLock<HashMap<String, Vec<OutputDevice>>>,
+}
+
+impl OutputTargetRegistry {
+    /// Creates an empty registry.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers a target.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`OutputError::AlreadyRegistered`] if a target with the same
+    /// ID is already registered.
+    pub fn register(&self, target: Arc<dyn OutputTarget>) -> Result<(), OutputError> {
+        let mut targets = self.targets.write();
+        if targets.iter().any(|t| t.id() == target.id()) {
+            return Err(OutputError::AlreadyRegistered(target.id().to_string()));
+        }
+        log::info!("[Outputs] Registered output target '{}'", target.id());
+        targets.push(target);
+        Ok(())
+    }
+
+    /// Returns the IDs of the registered targets, in registration order.
+    #[must_use]
+    pub fn target_ids(&self) -> Vec<String> {
+        self.targets
+            .read()
+            .iter()
+            .map(|t| t.id().to_string())
+            .collect()
+    }
+
+    fn target(&self, id: &str) -> Result<Arc<dyn OutputTarget>, OutputError> {
+        self.targets
+            .read()
+            .iter()
+            .find(|t| t.id() == id)
+            .cloned()
+            .ok_or_else(|| OutputError::UnknownTarget(id.to_string()))
+    }
+
+    /// Runs every target's discovery in parallel and returns all devices.
+    ///
+    /// A failing target is logged and keeps the devices it found last time,
+    /// so one broken backend doesn't hide the others.
+    pub async fn discover(&self) -> Vec<OutputDevice> {
+        let targets = self.targets.read().clone();
+        let results = join_all(targets.iter().map(|target| async move {
+            let mut result = target.discover().await;
+            if let Ok(devices) = &mut result {
+                for device in devices {
+                    device.target = target.id().to_string();
+                }
+            }
+            (target.id().to_string(), result)
+        }))
+        .await;
+
+        let mut cached = self.devices.write();
+        for (id, result) in results {
+            match result {
+                Ok(devices) => {
+                    cached.insert(id, devices);
+                }
+                Err(e) => log::warn!("[Outputs] Discovery failed for '{}': {}", id, e),
+            }
+        }
+        targets
+            .iter()
+            .filter_map(|target| cached.get(target.id()))
+            .flatten()
+            .cloned()
+            .collect()
+    }
+
+    /// Looks up a device from its target's last discovery.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the target isn't registered or didn't find the
+    /// device.
+    pub fn device(&self, target: &str, id: &str) -> Result<OutputDevice, OutputError> {
+        self.target(target)?;
+        self.devices
+            .read()
+            .get(target)
+            .and_then(|devices| devices.iter().find(|d| d.id == id))
+            .cloned()
+            .ok_or_else(|| OutputError::DeviceNotFound(format!("{}/{}", target, id)))
+    }
+
+    /// Plays a stream on a discovered device.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the device is unknown or its backend fails.
+    pub async fn play(
+        &self,
+        target: &str,
+        id: &str,
+        request: &OutputPlayRequest,
+    ) -> Result<(), OutputError> {
+        let device = self.device(target, id)?;
+        self.target(target)?.play(&device, request).await
+    }
+
+    /// Stops playback on a discovered device.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the device is unknown or its backend fails.
+    pub async fn stop(&self, target: &str, id: &str) -> Result<(), OutputError> {
+        let device = self.device(target, id)?;
+        self.target(target)?.stop(&device).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use parking_lot::Mutex;
+
+    struct FakeTarget {
+        id: &'static str,
+        devices: Mutex<Result<Vec<OutputDevice>, String>>,
+        played: Mutex<Vec<(String, String)>>,
+    }
+
+    impl FakeTarget {
+        fn new(id: &'static str, device_ids: &[&str]) -> Arc<Self> {
+            let devices = device_ids
+                .iter()
+                .map(|device| OutputDevice {
+                    target: String::new(),
+                    id: device.to_string(),
+                    name: format!("{} {}", id, device),
+                    address: "192.168.1.60".to_string(),
+                    model_name: None,
+                })
+                .collect();
+            Arc::new(Self {
+                id,
+                devices: Mutex::new(Ok(devices)),
+                played: Mutex::default(),
+            })
+        }
+    }
+
+    #[async_trait]
+    impl OutputTarget for FakeTarget {
+        fn id(&self) -> &str {
+            self.id
+        }
+
+        async fn discover(&self) -> Result<Vec<OutputDevice>, OutputError> {
+            self.devices.lock().clone().map_err(OutputError::Backend)
+        }
+
+        async fn play(
+            &self,
+            device: &OutputDevice,
+            request: &OutputPlayRequest,
+        ) -> Result<(), OutputError> {
+            self.played
+                .lock()
+                .push((device.id.clone(), request.stream_url.clone()));
+            Ok(())
+        }
+
+        async fn stop(&self, _device: &OutputDevice) -> Result<(), OutputError> {
+            Ok(())
+        }
+    }
+
+    fn play_request() -> OutputPlayRequest {
+        OutputPlayRequest {
+            stream_url: "http://192.168.1.100:8080/stream/s1/live".to_string(),
+            codec: AudioCodec::Pcm,
+            audio_format: AudioFormat::default(),
+            metadata: None,
+            artwork_url: String::new(),
+        }
+    }
+
+    #[test]
+    fn target_ids_must_be_unique() {
+        let registry = OutputTargetRegistry::new();
+        registry.register(FakeTarget::new("kef", &[])).unwrap();
+        assert!(matches!(
+            registry.register(FakeTarget::new("kef", &[])),
+            Err(OutputError::AlreadyRegistered(_))
+        ));
+        assert_eq!(registry.target_ids(), vec!["kef"]);
+    }
+
+    #[tokio::test]
+    async fn discovery_merges_targets_and_survives_failures() {
+        let registry = OutputTargetRegistry::new();
+        let bluesound = FakeTarget::new("bluesound", &["node"]);
+        registry.register(bluesound.clone()).unwrap();
+        registry
+            .register(FakeTarget::new("kef", &["ls50"]))
+            .unwrap();
+
+        let devices = registry.discover().await;
+        let ids: Vec<_> = devices
+            .iter()
+            .map(|d| (d.target.as_str(), d.id.as_str()))
+            .collect();
+        assert_eq!(ids, vec![("bluesound", "node"), ("kef", "ls50")]);
+
+        // A failing target keeps its last devices
+        *bluesound.devices.lock() = Err("timed out".to_string());
+        assert_eq!(registry.discover().await.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn playback_goes_to_the_devices_target() {
+        let registry = OutputTargetRegistry::new();
+        let kef = FakeTarget::new("kef", &["ls50"]);
+        registry.register(kef.clone()).unwrap();
+
+        // Devices are only known after discovery
+        assert!(matches!(
+            registry.play("kef", "ls50", &play_request()).await,
+            Err(OutputError::DeviceNotFound(_))
+        ));
+        registry.discover().await;
+
+        registry.play("kef", "ls50", &play_request()).await.unwrap();
+        assert_eq!(kef.played.lock()[0].0, "ls50");
+        assert!(matches!(
+            registry.stop("sonos", "ls50").await,
+            Err(OutputError::UnknownTarget(_))
+        ));
+    }
+}
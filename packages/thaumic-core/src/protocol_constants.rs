@@ -12,14 +12,22 @@
 /// 1 hour is a reasonable default per UPnP spec recommendations.
 pub const GENA_SUBSCRIPTION_TIMEOUT_SECS: u64 = 3600;
 
-/// Time before subscription expiry to trigger renewal (seconds).
+/// Default time before subscription expiry to trigger renewal (seconds).
 ///
-/// 5 minutes provides comfortable buffer for network delays.
+/// 5 minutes provides comfortable buffer for network delays. Widened per
+/// speaker when a speaker turns out to drop subscriptions before they expire.
 pub const GENA_RENEWAL_BUFFER_SECS: u64 = 300;
 
 /// Interval between subscription renewal checks (seconds).
 pub const GENA_RENEWAL_CHECK_SECS: u64 = 60;
 
+/// Interval between full topology refreshes while a ZoneGroupTopology
+/// subscription is active (seconds).
+///
+/// Grouping changes then arrive as NOTIFYs, so the periodic scan is only a
+/// fallback for missed events and speakers joining the network.
+pub const TOPOLOGY_FALLBACK_REFRESH_SECS: u64 = 300;
+
 // ─────────────────────────────────────────────────────────────────────────────
 // Audio Standards
 // ─────────────────────────────────────────────────────────────────────────────
@@ -58,6 +66,18 @@ pub const ICY_METAINT: usize = 8192;
 /// 10 seconds is reasonable for LAN operations.
 pub const SOAP_TIMEOUT_SECS: u64 = 10;
 
+/// Maximum concurrent SOAP connections to a single speaker.
+///
+/// Speakers serve control requests from a small embedded HTTP server; more
+/// parallel connections than this only queue up on the speaker's side.
+pub const SOAP_MAX_CONNECTIONS_PER_SPEAKER: usize = 4;
+
+/// How long an idle keep-alive SOAP connection is kept open (seconds).
+///
+/// Long enough to span the bursts of calls a group start or volume drag
+/// makes, short enough not to hold sockets to speakers that went quiet.
+pub const SOAP_POOL_IDLE_TIMEOUT_SECS: u64 = 20;
+
 /// Maximum size of GENA notification body (bytes).
 pub const MAX_GENA_BODY_SIZE: usize = 64 * 1024;
 
@@ -82,17 +102,37 @@ pub const SERVICE_ID: &str = "thaumic-cast";
 // Streaming Configuration Constants
 // ─────────────────────────────────────────────────────────────────────────────
 
-/// Capacity of the event broadcast channel for WebSocket clients.
+/// Capacity of the event broadcast channel for WebSocket clients, for
+/// events of normal priority.
 pub const EVENT_CHANNEL_CAPACITY: usize = 100;
 
+/// Capacity of the broadcast channel for critical events (playback and
+/// stream lifecycle), which other events can't displace.
+pub const CRITICAL_EVENT_CHANNEL_CAPACITY: usize = 256;
+
+/// Capacity of the broadcast channel for chatty events (latency updates,
+/// playback position), whose overflow is only counted.
+pub const CHATTY_EVENT_CHANNEL_CAPACITY: usize = 64;
+
 /// Capacity of the internal GENA event channel (SubscriptionLost events).
 pub const GENA_EVENT_CHANNEL_CAPACITY: usize = 64;
 
 /// WebSocket heartbeat timeout (seconds).
-pub const WS_HEARTBEAT_TIMEOUT_SECS: u64 = 30;
+///
+/// A connection that sends nothing for this long (no message, no pong) is
+/// considered dead and reaped. Covers two missed server pings.
+pub const WS_HEARTBEAT_TIMEOUT_SECS: u64 = 20;
+
+/// Interval between server-initiated WebSocket pings (seconds).
+pub const WS_PING_INTERVAL_SECS: u64 = 8;
 
-/// Interval between WebSocket heartbeat checks (seconds).
-pub const WS_HEARTBEAT_CHECK_INTERVAL_SECS: u64 = 1;
+/// How long sending a WebSocket ping may block before the connection is
+/// dropped (seconds). A half-open peer eventually fills the send buffer.
+pub const WS_PING_SEND_TIMEOUT_SECS: u64 = 5;
+
+/// How long a stream created through `/api/ingest` waits for its upload to
+/// start before it is removed (seconds).
+pub const INGEST_UPLOAD_TIMEOUT_SECS: u64 = 30;
 
 /// Default frame duration for injected silence (ms).
 /// Used as fallback when client doesn't specify frame_size_samples.
@@ -121,6 +161,12 @@ pub const MAX_STREAMING_BUFFER_MS: u64 = 1000;
 /// Default streaming buffer size (ms).
 pub const DEFAULT_STREAMING_BUFFER_MS: u64 = 200;
 
+/// Lowest sample rate accepted from clients (Hz).
+pub const MIN_SAMPLE_RATE: u32 = 8_000;
+
+/// Highest sample rate accepted from clients (Hz).
+pub const MAX_SAMPLE_RATE: u32 = 192_000;
+
 /// Maximum cadence queue size (frames).
 /// Calculated using MIN_FRAME_DURATION_MS to ensure buffer can hold enough frames
 /// at the smallest possible frame duration.
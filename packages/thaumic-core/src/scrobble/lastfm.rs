@@ -0,0 +1,132 @@
+//! Last.fm scrobbling.
+//!
+//! Needs an API key and secret (from a Last.fm API account) and a session
+//! key for the user, obtained once through Last.fm's authentication flow.
+//! Write calls are signed: `api_sig` is the MD5 of every parameter name and
+//! value concatenated in name order, followed by the secret.
+
+use async_trait::async_trait;
+use reqwest::Client;
+use serde::Deserialize;
+
+use super::{Listen, ScrobbleError, ScrobbleSink};
+
+/// API root.
+const API_URL: &str = "https://ws.audioscrobbler.com/2.0/";
+
+/// Last.fm credentials for scrobbling.
+#[derive(Debug, Clone)]
+pub struct LastFmCredentials {
+    pub api_key: String,
+    pub api_secret: String,
+    pub session_key: String,
+}
+
+/// Error body returned by Last.fm, with HTTP 200 for some failures.
+#[derive(Deserialize)]
+struct ApiError {
+    error: u32,
+    message: String,
+}
+
+/// Submits scrobbles to Last.fm.
+pub struct LastFmSink {
+    client: Client,
+    credentials: LastFmCredentials,
+}
+
+impl LastFmSink {
+    pub fn new(client: Client, credentials: LastFmCredentials) -> Self {
+        Self {
+            client,
+            credentials,
+        }
+    }
+
+    async fn call(&self, method: &str, listen: &Listen) -> Result<(), ScrobbleError> {
+        let mut params = vec![
+            ("method", method.to_string()),
+            ("artist", listen.artist.clone()),
+            ("track", listen.title.clone()),
+            ("api_key", self.credentials.api_key.clone()),
+            ("sk", self.credentials.session_key.clone()),
+        ];
+        if let Some(album) = &listen.album {
+            params.push(("album", album.clone()));
+        }
+        if method == "track.scrobble" {
+            params.push(("timestamp", listen.listened_at.to_string()));
+        }
+        let signature = sign(&params, &self.credentials.api_secret);
+        params.push(("api_sig", signature));
+        params.push(("format", "json".to_string()));
+
+        let body = self
+            .client
+            .post(API_URL)
+            .form(&params)
+            .send()
+            .await?
+            .error_for_status()?
+            .text()
+            .await?;
+
+        match serde_json::from_str::<ApiError>(&body) {
+            Ok(e) => Err(ScrobbleError::Rejected(format!(
+                "{} ({})",
+                e.message, e.error
+            ))),
+            Err(_) => Ok(()),
+        }
+    }
+}
+
+#[async_trait]
+impl ScrobbleSink for LastFmSink {
+    fn name(&self) -> &'static str {
+        "lastfm"
+    }
+
+    async fn now_playing(&self, listen: &Listen) -> Result<(), ScrobbleError> {
+        self.call("track.updateNowPlaying", listen).await
+    }
+
+    async fn scrobble(&self, listen: &Listen) -> Result<(), ScrobbleError> {
+        self.call("track.scrobble", listen).await
+    }
+}
+
+/// Computes a Last.fm API method signature.
+fn sign(params: &[(&str, String)], secret: &str) -> String {
+    let mut sorted: Vec<_> = params.iter().collect();
+    sorted.sort_by_key(|(name, _)| *name);
+
+    let mut input = String::new();
+    for (name, value) in sorted {
+        input.push_str(name);
+        input.push_str(value);
+    }
+    input.push_str(secret);
+    format!("{:x}", md5::compute(input.as_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn signs_sorted_params_with_secret() {
+        let params = [
+            ("method", "auth.getSession".to_string()),
+            ("api_key", "key".to_string()),
+            ("token", "tok".to_string()),
+        ];
+        // md5("api_keykeymethodauth.getSessiontokentoksecret")
+        let expected = format!(
+            "{:x}",
+            md5::compute(b"api_keykeymethodauth.getSessiontokentoksecret")
+        );
+        assert_eq!(sign(&params, "secret"), expected);
+        assert_eq!(expected.len(), 32);
+    }
+}
@@ -0,0 +1,118 @@
+//! ListenBrainz listen submission.
+//!
+//! Authenticates with a user token from <https://listenbrainz.org/settings/>.
+
+use async_trait::async_trait;
+use reqwest::Client;
+use serde_json::{json, Value};
+
+use super::{Listen, ScrobbleError, ScrobbleSink, SUBMISSION_CLIENT};
+
+/// Listen submission endpoint.
+const SUBMIT_URL: &str = "https://api.listenbrainz.org/1/submit-listens";
+
+/// Submits listens to ListenBrainz.
+pub struct ListenBrainzSink {
+    client: Client,
+    token: String,
+}
+
+impl ListenBrainzSink {
+    pub fn new(client: Client, token: String) -> Self {
+        Self { client, token }
+    }
+
+    async fn submit(&self, body: Value) -> Result<(), ScrobbleError> {
+        self.client
+            .post(SUBMIT_URL)
+            .header(
+                reqwest::header::AUTHORIZATION,
+                format!("Token {}", self.token),
+            )
+            .json(&body)
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl ScrobbleSink for ListenBrainzSink {
+    fn name(&self) -> &'static str {
+        "listenbrainz"
+    }
+
+    async fn now_playing(&self, listen: &Listen) -> Result<(), ScrobbleError> {
+        self.submit(submission(listen, false)).await
+    }
+
+    async fn scrobble(&self, listen: &Listen) -> Result<(), ScrobbleError> {
+        self.submit(submission(listen, true)).await
+    }
+}
+
+/// Builds a `playing_now` or `single` listen submission.
+fn submission(listen: &Listen, completed: bool) -> Value {
+    let mut track_metadata = json!({
+        "artist_name": listen.artist,
+        "track_name": listen.title,
+        "additional_info": {
+            "submission_client": SUBMISSION_CLIENT,
+            "submission_client_version": env!("CARGO_PKG_VERSION"),
+        },
+    });
+    if let Some(album) = &listen.album {
+        track_metadata["release_name"] = json!(album);
+    }
+
+    let mut payload = json!({ "track_metadata": track_metadata });
+    if completed {
+        payload["listened_at"] = json!(listen.listened_at);
+    }
+
+    json!({
+        "listen_type": if completed { "single" } else { "playing_now" },
+        "payload": [payload],
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn listen() -> Listen {
+        Listen {
+            artist: "Artist".to_string(),
+            title: "Title".to_string(),
+            album: Some("Album".to_string()),
+            listened_at: 1_700_000_000,
+        }
+    }
+
+    #[test]
+    fn builds_single_listen() {
+        let body = submission(&listen(), true);
+        assert_eq!(body["listen_type"], "single");
+        let payload = &body["payload"][0];
+        assert_eq!(payload["listened_at"], 1_700_000_000);
+        assert_eq!(payload["track_metadata"]["artist_name"], "Artist");
+        assert_eq!(payload["track_metadata"]["track_name"], "Title");
+        assert_eq!(payload["track_metadata"]["release_name"], "Album");
+    }
+
+    #[test]
+    fn playing_now_has_no_timestamp() {
+        let body = submission(
+            &Listen {
+                album: None,
+                ..listen()
+            },
+            false,
+        );
+        assert_eq!(body["listen_type"], "playing_now");
+        let payload = &body["payload"][0];
+        assert!(payload.get("listened_at").is_none());
+        assert!(payload["track_metadata"].get("release_name").is_none());
+    }
+}
@@ -0,0 +1,182 @@
+//! Scrobbling of tracks cast through the server.
+//!
+//! A [`ScrobbleSink`] submits "now playing" updates and completed listens to
+//! one service. [`ScrobbleService`](crate::services::ScrobbleService) feeds
+//! the sinks from stream events; the rules deciding what counts as a listen
+//! live in [`tracker`].
+
+pub mod lastfm;
+pub mod listenbrainz;
+pub(crate) mod tracker;
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::protocol_constants::APP_NAME;
+
+pub use lastfm::{LastFmCredentials, LastFmSink};
+pub use listenbrainz::ListenBrainzSink;
+
+/// Client name reported to scrobble services.
+pub(crate) const SUBMISSION_CLIENT: &str = APP_NAME;
+
+/// Shortest play time accepted for `scrobble_after_secs` (Last.fm minimum).
+const MIN_SCROBBLE_AFTER_SECS: u64 = 30;
+
+/// Longest play time accepted for `scrobble_after_secs` (Last.fm's 4-minute rule).
+const MAX_SCROBBLE_AFTER_SECS: u64 = 240;
+
+/// Scrobbling configuration. Scrobbling is on when any service is configured.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ScrobbleConfig {
+    /// ListenBrainz user token.
+    pub listenbrainz_token: Option<String>,
+    /// Last.fm API key.
+    pub lastfm_api_key: Option<String>,
+    /// Last.fm API shared secret.
+    pub lastfm_api_secret: Option<String>,
+    /// Last.fm session key for the user.
+    pub lastfm_session_key: Option<String>,
+    /// Seconds a track must play before it's scrobbled (30-240).
+    ///
+    /// Captured tabs don't report track length, so Last.fm's "half the
+    /// track" rule can't be applied; the default approximates it for a
+    /// typical track.
+    pub scrobble_after_secs: u64,
+}
+
+impl Default for ScrobbleConfig {
+    fn default() -> Self {
+        Self {
+            listenbrainz_token: None,
+            lastfm_api_key: None,
+            lastfm_api_secret: None,
+            lastfm_session_key: None,
+            scrobble_after_secs: 120,
+        }
+    }
+}
+
+impl ScrobbleConfig {
+    /// Returns the scrobble threshold clamped to the range services accept.
+    pub fn scrobble_after_secs(&self) -> u64 {
+        self.scrobble_after_secs
+            .clamp(MIN_SCROBBLE_AFTER_SECS, MAX_SCROBBLE_AFTER_SECS)
+    }
+
+    /// Builds the sinks for every configured service.
+    ///
+    /// Last.fm needs all three of key, secret, and session key; a partial
+    /// set is logged and skipped.
+    pub fn sinks(&self, client: &Client) -> Vec<Arc<dyn ScrobbleSink>> {
+        let mut sinks: Vec<Arc<dyn ScrobbleSink>> = Vec::new();
+
+        if let Some(token) = non_empty(&self.listenbrainz_token) {
+            sinks.push(Arc::new(ListenBrainzSink::new(client.clone(), token)));
+        }
+
+        match (
+            non_empty(&self.lastfm_api_key),
+            non_empty(&self.lastfm_api_secret),
+            non_empty(&self.lastfm_session_key),
+        ) {
+            (Some(api_key), Some(api_secret), Some(session_key)) => {
+                sinks.push(Arc::new(LastFmSink::new(
+                    client.clone(),
+                    LastFmCredentials {
+                        api_key,
+                        api_secret,
+                        session_key,
+                    },
+                )));
+            }
+            (None, None, None) => {}
+            _ => {
+                log::warn!("[Scrobble] Last.fm needs an API key, secret, and session key; skipping")
+            }
+        }
+
+        sinks
+    }
+}
+
+fn non_empty(value: &Option<String>) -> Option<String> {
+    value.as_ref().filter(|v| !v.trim().is_empty()).cloned()
+}
+
+/// Errors from submitting to a scrobble service.
+#[derive(Debug, Error)]
+pub enum ScrobbleError {
+    /// The HTTP request failed or returned an error status.
+    #[error("HTTP request failed: {0}")]
+    Http(#[from] reqwest::Error),
+
+    /// The service rejected the submission.
+    #[error("Rejected: {0}")]
+    Rejected(String),
+}
+
+/// A track listen to report.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Listen {
+    pub artist: String,
+    pub title: String,
+    pub album: Option<String>,
+    /// Unix time (seconds) the track started playing.
+    pub listened_at: u64,
+}
+
+/// A scrobble service.
+#[async_trait]
+pub trait ScrobbleSink: Send + Sync {
+    /// Short name for logs.
+    fn name(&self) -> &'static str;
+
+    /// Reports the track that just started playing.
+    async fn now_playing(&self, listen: &Listen) -> Result<(), ScrobbleError>;
+
+    /// Records a completed listen.
+    async fn scrobble(&self, listen: &Listen) -> Result<(), ScrobbleError>;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builds_sinks_for_complete_credentials_only() {
+        let client = Client::new();
+        assert!(ScrobbleConfig::default().sinks(&client).is_empty());
+
+        let config = ScrobbleConfig {
+            listenbrainz_token: Some("token".to_string()),
+            lastfm_api_key: Some("key".to_string()),
+            lastfm_api_secret: Some("secret".to_string()),
+            ..Default::default()
+        };
+        let names: Vec<_> = config.sinks(&client).iter().map(|s| s.name()).collect();
+        assert_eq!(names, vec!["listenbrainz"]);
+
+        let config = ScrobbleConfig {
+            lastfm_session_key: Some("sk".to_string()),
+            ..config
+        };
+        assert_eq!(config.sinks(&client).len(), 2);
+    }
+
+    #[test]
+    fn clamps_threshold() {
+        let config = |secs| ScrobbleConfig {
+            scrobble_after_secs: secs,
+            ..Default::default()
+        };
+        assert_eq!(config(5).scrobble_after_secs(), 30);
+        assert_eq!(config(120).scrobble_after_secs(), 120);
+        assert_eq!(config(600).scrobble_after_secs(), 240);
+    }
+}
@@ -0,0 +1,309 @@
+//! Per-stream listening state that decides when a track counts as played.
+
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, Instant};
+
+use super::Listen;
+
+/// Track identity used for scrobbling.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Track {
+    pub artist: String,
+    pub title: String,
+    pub album: Option<String>,
+}
+
+/// Something the tracker wants sent to the scrobble services.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum ScrobbleAction {
+    /// The track started playing on at least one speaker.
+    NowPlaying(Listen),
+    /// The track has played long enough to count as a listen.
+    Scrobble(Listen),
+}
+
+struct TrackPlay {
+    track: Track,
+    /// Unix time (seconds) playback of this track began, once it has.
+    started_at: Option<u64>,
+    /// Play time accumulated before the current playing span.
+    played: Duration,
+    /// Start of the current playing span, while speakers are playing.
+    playing_since: Option<Instant>,
+    now_playing_sent: bool,
+    scrobbled: bool,
+}
+
+impl TrackPlay {
+    fn played(&self, now: Instant) -> Duration {
+        self.played
+            + self
+                .playing_since
+                .map_or(Duration::ZERO, |since| now.duration_since(since))
+    }
+
+    fn listen(&self) -> Listen {
+        Listen {
+            artist: self.track.artist.clone(),
+            title: self.track.title.clone(),
+            album: self.track.album.clone(),
+            listened_at: self.started_at.unwrap_or_default(),
+        }
+    }
+
+    fn start_playing(&mut self, now: Instant, unix_secs: u64) -> Option<ScrobbleAction> {
+        if self.playing_since.is_some() {
+            return None;
+        }
+        self.playing_since = Some(now);
+        self.started_at.get_or_insert(unix_secs);
+        if self.now_playing_sent {
+            return None;
+        }
+        self.now_playing_sent = true;
+        Some(ScrobbleAction::NowPlaying(self.listen()))
+    }
+
+    fn stop_playing(&mut self, now: Instant) {
+        self.played = self.played(now);
+        self.playing_since = None;
+    }
+
+    fn check(&mut self, now: Instant, threshold: Duration) -> Option<ScrobbleAction> {
+        if self.scrobbled || self.played(now) < threshold {
+            return None;
+        }
+        self.scrobbled = true;
+        Some(ScrobbleAction::Scrobble(self.listen()))
+    }
+}
+
+#[derive(Default)]
+struct StreamPlay {
+    /// Speakers currently playing this stream.
+    speakers: HashSet<String>,
+    track: Option<TrackPlay>,
+}
+
+/// Tracks what each stream is playing and for how long.
+///
+/// Play time only accrues while at least one speaker is playing the stream,
+/// so a paused or muted-by-stop cast doesn't scrobble. Track durations
+/// aren't known for captured tabs, so a track is scrobbled once it has
+/// played for a fixed threshold.
+pub(crate) struct ScrobbleTracker {
+    threshold: Duration,
+    streams: HashMap<String, StreamPlay>,
+}
+
+impl ScrobbleTracker {
+    pub(crate) fn new(threshold: Duration) -> Self {
+        Self {
+            threshold,
+            streams: HashMap::new(),
+        }
+    }
+
+    /// Records the stream's current track (`None` if not scrobbleable).
+    pub(crate) fn on_metadata(
+        &mut self,
+        stream_id: &str,
+        track: Option<Track>,
+        now: Instant,
+        unix_secs: u64,
+    ) -> Vec<ScrobbleAction> {
+        let threshold = self.threshold;
+        let stream = self.streams.entry(stream_id.to_string()).or_default();
+        if stream.track.as_ref().map(|t| &t.track) == track.as_ref() {
+            return Vec::new();
+        }
+
+        let mut actions = Vec::new();
+        if let Some(previous) = stream.track.as_mut() {
+            actions.extend(previous.check(now, threshold));
+        }
+
+        stream.track = track.map(|track| TrackPlay {
+            track,
+            started_at: None,
+            played: Duration::ZERO,
+            playing_since: None,
+            now_playing_sent: false,
+            scrobbled: false,
+        });
+        if !stream.speakers.is_empty() {
+            if let Some(current) = stream.track.as_mut() {
+                actions.extend(current.start_playing(now, unix_secs));
+            }
+        }
+        actions
+    }
+
+    /// Records a speaker starting playback of the stream.
+    pub(crate) fn on_playback_started(
+        &mut self,
+        stream_id: &str,
+        speaker_ip: &str,
+        now: Instant,
+        unix_secs: u64,
+    ) -> Option<ScrobbleAction> {
+        let stream = self.streams.entry(stream_id.to_string()).or_default();
+        stream.speakers.insert(speaker_ip.to_string());
+        stream.track.as_mut()?.start_playing(now, unix_secs)
+    }
+
+    /// Records a speaker stopping playback of the stream.
+    pub(crate) fn on_playback_stopped(
+        &mut self,
+        stream_id: &str,
+        speaker_ip: &str,
+        now: Instant,
+    ) -> Option<ScrobbleAction> {
+        let threshold = self.threshold;
+        let stream = self.streams.get_mut(stream_id)?;
+        stream.speakers.remove(speaker_ip);
+        let track = stream.track.as_mut()?;
+        let action = track.check(now, threshold);
+        if stream.speakers.is_empty() {
+            track.stop_playing(now);
+        }
+        action
+    }
+
+    /// Forgets an ended stream, scrobbling its track if it qualifies.
+    pub(crate) fn on_stream_ended(
+        &mut self,
+        stream_id: &str,
+        now: Instant,
+    ) -> Option<ScrobbleAction> {
+        let threshold = self.threshold;
+        self.streams
+            .remove(stream_id)?
+            .track
+            .as_mut()?
+            .check(now, threshold)
+    }
+
+    /// Returns scrobbles for tracks that have crossed the threshold.
+    pub(crate) fn poll(&mut self, now: Instant) -> Vec<ScrobbleAction> {
+        let threshold = self.threshold;
+        self.streams
+            .values_mut()
+            .filter_map(|s| s.track.as_mut()?.check(now, threshold))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const THRESHOLD: Duration = Duration::from_secs(120);
+
+    fn track(title: &str) -> Option<Track> {
+        Some(Track {
+            artist: "Artist".to_string(),
+            title: title.to_string(),
+            album: None,
+        })
+    }
+
+    fn is_scrobble(action: &ScrobbleAction, title: &str) -> bool {
+        matches!(action, ScrobbleAction::Scrobble(l) if l.title == title)
+    }
+
+    #[test]
+    fn scrobbles_once_after_threshold_of_playback() {
+        let mut tracker = ScrobbleTracker::new(THRESHOLD);
+        let t0 = Instant::now();
+
+        assert!(tracker.on_metadata("s", track("A"), t0, 1_000).is_empty());
+        let now_playing = tracker.on_playback_started("s", "10.0.0.1", t0, 1_005);
+        assert!(
+            matches!(now_playing, Some(ScrobbleAction::NowPlaying(ref l)) if l.listened_at == 1_005)
+        );
+
+        assert!(tracker.poll(t0 + Duration::from_secs(119)).is_empty());
+        let due = tracker.poll(t0 + Duration::from_secs(120));
+        assert_eq!(due.len(), 1);
+        assert!(is_scrobble(&due[0], "A"));
+        assert!(tracker.poll(t0 + Duration::from_secs(300)).is_empty());
+    }
+
+    #[test]
+    fn only_counts_time_while_playing() {
+        let mut tracker = ScrobbleTracker::new(THRESHOLD);
+        let t0 = Instant::now();
+        tracker.on_metadata("s", track("A"), t0, 0);
+
+        tracker.on_playback_started("s", "10.0.0.1", t0, 0);
+        tracker.on_playback_stopped("s", "10.0.0.1", t0 + Duration::from_secs(60));
+        // Long pause with no speakers
+        assert!(tracker.poll(t0 + Duration::from_secs(600)).is_empty());
+
+        tracker.on_playback_started("s", "10.0.0.1", t0 + Duration::from_secs(600), 0);
+        assert!(tracker.poll(t0 + Duration::from_secs(659)).is_empty());
+        assert_eq!(tracker.poll(t0 + Duration::from_secs(660)).len(), 1);
+    }
+
+    #[test]
+    fn keeps_playing_while_any_speaker_plays() {
+        let mut tracker = ScrobbleTracker::new(THRESHOLD);
+        let t0 = Instant::now();
+        tracker.on_metadata("s", track("A"), t0, 0);
+        tracker.on_playback_started("s", "10.0.0.1", t0, 0);
+        assert!(tracker
+            .on_playback_started("s", "10.0.0.2", t0, 0)
+            .is_none());
+
+        tracker.on_playback_stopped("s", "10.0.0.1", t0 + Duration::from_secs(10));
+        assert_eq!(tracker.poll(t0 + Duration::from_secs(120)).len(), 1);
+    }
+
+    #[test]
+    fn skipped_tracks_are_not_scrobbled() {
+        let mut tracker = ScrobbleTracker::new(THRESHOLD);
+        let t0 = Instant::now();
+        tracker.on_metadata("s", track("A"), t0, 0);
+        tracker.on_playback_started("s", "10.0.0.1", t0, 0);
+
+        let actions = tracker.on_metadata("s", track("B"), t0 + Duration::from_secs(30), 30);
+        assert_eq!(actions.len(), 1);
+        assert!(matches!(&actions[0], ScrobbleAction::NowPlaying(l) if l.title == "B"));
+    }
+
+    #[test]
+    fn repeated_metadata_does_not_restart_track() {
+        let mut tracker = ScrobbleTracker::new(THRESHOLD);
+        let t0 = Instant::now();
+        tracker.on_metadata("s", track("A"), t0, 0);
+        tracker.on_playback_started("s", "10.0.0.1", t0, 0);
+
+        let later = t0 + Duration::from_secs(100);
+        assert!(tracker.on_metadata("s", track("A"), later, 100).is_empty());
+        assert_eq!(tracker.poll(t0 + Duration::from_secs(120)).len(), 1);
+    }
+
+    #[test]
+    fn stream_end_scrobbles_qualifying_track() {
+        let mut tracker = ScrobbleTracker::new(THRESHOLD);
+        let t0 = Instant::now();
+        tracker.on_metadata("s", track("A"), t0, 0);
+        tracker.on_playback_started("s", "10.0.0.1", t0, 0);
+
+        let action = tracker.on_stream_ended("s", t0 + Duration::from_secs(130));
+        assert!(action.is_some_and(|a| is_scrobble(&a, "A")));
+        assert!(tracker.poll(t0 + Duration::from_secs(500)).is_empty());
+    }
+
+    #[test]
+    fn unscrobbleable_metadata_clears_track() {
+        let mut tracker = ScrobbleTracker::new(THRESHOLD);
+        let t0 = Instant::now();
+        tracker.on_metadata("s", track("A"), t0, 0);
+        tracker.on_playback_started("s", "10.0.0.1", t0, 0);
+        tracker.on_metadata("s", None, t0 + Duration::from_secs(5), 5);
+
+        assert!(tracker.poll(t0 + Duration::from_secs(500)).is_empty());
+    }
+}
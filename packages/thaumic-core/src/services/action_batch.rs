@@ -0,0 +1,402 @@
+//! Ordered batches of speaker control actions.
+//!
+//! Applying a "scene" (set some volumes, group a few rooms, start a stream)
+//! used to take one HTTP round trip per step. `POST /api/actions/batch` runs
+//! the whole list server-side, in order, and reports a result per step.
+//!
+//! Batches are not atomic: speakers apply each command as it arrives, so a
+//! batch that fails halfway leaves its earlier steps applied. Instead, every
+//! applied step reports the action that undoes it, and the response collects
+//! those in reverse order into a rollback batch the client can post back.
+
+use std::future::Future;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{ApiErrorResponse, ThaumicError, ThaumicResult};
+use crate::events::SpeakerRemovalReason;
+use crate::services::StreamCoordinator;
+use crate::sonos::SonosClient;
+use crate::state::SonosState;
+
+/// Maximum number of actions in one batch.
+pub const MAX_BATCH_ACTIONS: usize = 32;
+
+/// One control action in a batch.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(
+    tag = "action",
+    rename_all = "camelCase",
+    rename_all_fields = "camelCase"
+)]
+pub enum BatchAction {
+    /// Sets volume, routed like `POST /api/speakers/{ip}/volume`.
+    SetVolume { ip: String, volume: u8 },
+    /// Sets mute, routed like `POST /api/speakers/{ip}/mute`.
+    SetMute { ip: String, mute: bool },
+    /// Joins the speaker to the group coordinated by `coordinator_ip`.
+    JoinGroup { ip: String, coordinator_ip: String },
+    /// Makes the speaker leave its group and play standalone.
+    LeaveGroup { ip: String },
+    /// Starts playback of an existing stream on the speaker.
+    Play { ip: String, stream_id: String },
+    /// Stops playback of a stream on the speaker.
+    Stop { ip: String, stream_id: String },
+}
+
+/// Body of `POST /api/actions/batch`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BatchRequest {
+    pub actions: Vec<BatchAction>,
+    /// Keep going after a failed step instead of skipping the rest.
+    #[serde(default)]
+    pub continue_on_error: bool,
+    /// PIN of locked streams the batch's speakers play.
+    #[serde(default)]
+    pub pin: Option<String>,
+}
+
+impl BatchRequest {
+    /// Rejects empty and oversized batches.
+    pub fn validate(&self) -> ThaumicResult<()> {
+        if self.actions.is_empty() {
+            return Err(ThaumicError::InvalidRequest("Batch has no actions".into()));
+        }
+        if self.actions.len() > MAX_BATCH_ACTIONS {
+            return Err(ThaumicError::InvalidRequest(format!(
+                "Batch has {} actions; at most {} are allowed",
+                self.actions.len(),
+                MAX_BATCH_ACTIONS
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Outcome of one step.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum StepStatus {
+    Applied,
+    Failed,
+    /// Not attempted because an earlier step failed.
+    Skipped,
+}
+
+/// Result of one step, in request order.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BatchStepResult {
+    pub action: BatchAction,
+    pub status: StepStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<ApiErrorResponse>,
+    /// Action that undoes this step, when it was applied and is reversible.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub undo: Option<BatchAction>,
+}
+
+/// Response of `POST /api/actions/batch`.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BatchResult {
+    /// Number of steps applied.
+    pub applied: usize,
+    /// Number of steps that failed.
+    pub failed: usize,
+    pub steps: Vec<BatchStepResult>,
+    /// Undo actions of the applied steps, last step first.
+    pub rollback: Vec<BatchAction>,
+}
+
+/// Runs `actions` in order through `apply`, which performs one action and
+/// returns its undo action.
+///
+/// Unless `continue_on_error` is set, steps after the first failure are
+/// skipped.
+pub(crate) async fn run_steps<F, Fut>(
+    actions: Vec<BatchAction>,
+    continue_on_error: bool,
+    mut apply: F,
+) -> BatchResult
+where
+    F: FnMut(BatchAction) -> Fut,
+    Fut: Future<Output = ThaumicResult<Option<BatchAction>>>,
+{
+    let mut steps = Vec::with_capacity(actions.len());
+    let mut stopped = false;
+
+    for action in actions {
+        if stopped {
+            steps.push(BatchStepResult {
+                action,
+                status: StepStatus::Skipped,
+                error: None,
+                undo: None,
+            });
+            continue;
+        }
+
+        match apply(action.clone()).await {
+            Ok(undo) => steps.push(BatchStepResult {
+                action,
+                status: StepStatus::Applied,
+                error: None,
+                undo,
+            }),
+            Err(e) => {
+                log::warn!("[Batch] {:?} failed: {}", action, e);
+                steps.push(BatchStepResult {
+                    action,
+                    status: StepStatus::Failed,
+                    error: Some(ApiErrorResponse::from_error(e.status_code(), &e)),
+                    undo: None,
+                });
+                stopped = !continue_on_error;
+            }
+        }
+    }
+
+    BatchResult {
+        applied: count(&steps, StepStatus::Applied),
+        failed: count(&steps, StepStatus::Failed),
+        rollback: steps.iter().rev().filter_map(|s| s.undo.clone()).collect(),
+        steps,
+    }
+}
+
+fn count(steps: &[BatchStepResult], status: StepStatus) -> usize {
+    steps.iter().filter(|s| s.status == status).count()
+}
+
+/// Applies batch actions against the live services.
+pub struct ActionBatch<'a> {
+    pub sonos: &'a dyn SonosClient,
+    pub stream_coordinator: &'a StreamCoordinator,
+    pub sonos_state: &'a SonosState,
+    /// Artwork URL for the DIDL-Lite metadata of `play` steps.
+    pub artwork_url: &'a str,
+}
+
+impl ActionBatch<'_> {
+    /// Runs a validated batch request.
+    pub async fn run(&self, request: BatchRequest) -> ThaumicResult<BatchResult> {
+        request.validate()?;
+        log::info!("[Batch] Running {} action(s)", request.actions.len());
+        let pin = request.pin.as_deref();
+        Ok(
+            run_steps(request.actions, request.continue_on_error, |action| {
+                self.apply(action, pin)
+            })
+            .await,
+        )
+    }
+
+    async fn apply(
+        &self,
+        action: BatchAction,
+        pin: Option<&str>,
+    ) -> ThaumicResult<Option<BatchAction>> {
+        let coordinator = self.stream_coordinator;
+        match &action {
+            BatchAction::Stop { stream_id, .. } => {
+                coordinator.authorize_stream(stream_id, pin, None)?;
+            }
+            BatchAction::Play { ip, stream_id } => {
+                coordinator.authorize_speaker(ip, pin, Some(stream_id))?;
+            }
+            BatchAction::SetVolume { ip, .. }
+            | BatchAction::SetMute { ip, .. }
+            | BatchAction::JoinGroup { ip, .. }
+            | BatchAction::LeaveGroup { ip } => {
+                coordinator.authorize_speaker(ip, pin, None)?;
+            }
+        }
+        match action {
+            BatchAction::SetVolume { ip, volume } => {
+                // Undo is best effort: without the previous level there is none
+                let previous = coordinator.get_volume_routed(self.sonos, &ip).await.ok();
+                coordinator
+                    .set_volume_routed(self.sonos, &ip, volume)
+                    .await?;
+                Ok(previous.map(|volume| BatchAction::SetVolume { ip, volume }))
+            }
+            BatchAction::SetMute { ip, mute } => {
+                let previous = coordinator.get_mute_routed(self.sonos, &ip).await.ok();
+                coordinator.set_mute_routed(self.sonos, &ip, mute).await?;
+                Ok(previous.map(|mute| BatchAction::SetMute { ip, mute }))
+            }
+            BatchAction::JoinGroup { ip, coordinator_ip } => {
+                let coordinator_uuid = self
+                    .sonos_state
+                    .get_coordinator_uuid_by_ip(&coordinator_ip)
+                    .ok_or_else(|| {
+                        ThaumicError::speaker_not_found(coordinator_ip.clone()).with_last_seen(
+                            self.sonos_state
+                                .speaker_addresses
+                                .last_seen(&coordinator_ip),
+                        )
+                    })?;
+                let undo = self.regroup_action(&ip);
+                self.sonos.join_group(&ip, &coordinator_uuid).await?;
+                Ok(Some(undo))
+            }
+            BatchAction::LeaveGroup { ip } => {
+                let undo = match self.regroup_action(&ip) {
+                    join @ BatchAction::JoinGroup { .. } => Some(join),
+                    // Already coordinating its own group: nothing to restore
+                    _ => None,
+                };
+                self.sonos.leave_group(&ip).await?;
+                Ok(undo)
+            }
+            BatchAction::Play { ip, stream_id } => {
+                coordinator
+                    .start_playback(&ip, &stream_id, None, self.artwork_url)
+                    .await?;
+                Ok(Some(BatchAction::Stop { ip, stream_id }))
+            }
+            BatchAction::Stop { ip, stream_id } => {
+                let stopped = coordinator
+                    .stop_playback_speaker(&stream_id, &ip, Some(SpeakerRemovalReason::UserRemoved))
+                    .await;
+                if stopped.is_empty() {
+                    return Err(ThaumicError::Soap(format!(
+                        "Failed to stop stream {} on {}",
+                        stream_id, ip
+                    )));
+                }
+                Ok(Some(BatchAction::Play { ip, stream_id }))
+            }
+        }
+    }
+
+    /// Returns the action that puts `ip` back into its current group:
+    /// rejoining its coordinator, or leaving again if it coordinates its own.
+    fn regroup_action(&self, ip: &str) -> BatchAction {
+        let groups = self.sonos_state.groups.read();
+        let coordinator_ip = groups
+            .iter()
+            .find(|g| g.members.iter().any(|m| m.ip == ip))
+            .map(|g| g.coordinator_ip.clone())
+            .filter(|coordinator_ip| coordinator_ip != ip);
+        match coordinator_ip {
+            Some(coordinator_ip) => BatchAction::JoinGroup {
+                ip: ip.to_string(),
+                coordinator_ip,
+            },
+            None => BatchAction::LeaveGroup { ip: ip.to_string() },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn volume(ip: &str, volume: u8) -> BatchAction {
+        BatchAction::SetVolume {
+            ip: ip.to_string(),
+            volume,
+        }
+    }
+
+    #[test]
+    fn parses_actions_by_tag() {
+        let request: BatchRequest = serde_json::from_value(serde_json::json!({
+            "actions": [
+                { "action": "setVolume", "ip": "192.168.1.20", "volume": 30 },
+                { "action": "joinGroup", "ip": "192.168.1.21", "coordinatorIp": "192.168.1.20" },
+                { "action": "play", "ip": "192.168.1.20", "streamId": "7d3c" }
+            ]
+        }))
+        .unwrap();
+
+        assert!(!request.continue_on_error);
+        assert_eq!(
+            request.actions[1],
+            BatchAction::JoinGroup {
+                ip: "192.168.1.21".into(),
+                coordinator_ip: "192.168.1.20".into(),
+            }
+        );
+        assert!(request.validate().is_ok());
+    }
+
+    #[test]
+    fn rejects_empty_and_oversized_batches() {
+        let empty = BatchRequest {
+            actions: vec![],
+            continue_on_error: false,
+            pin: None,
+        };
+        assert!(empty.validate().is_err());
+
+        let oversized = BatchRequest {
+            actions: vec![volume("192.168.1.20", 10); MAX_BATCH_ACTIONS + 1],
+            continue_on_error: false,
+            pin: None,
+        };
+        assert!(oversized.validate().is_err());
+    }
+
+    #[tokio::test]
+    async fn skips_after_failure_and_rolls_back_in_reverse() {
+        let actions = vec![
+            volume("192.168.1.20", 30),
+            volume("192.168.1.21", 40),
+            volume("192.168.1.22", 50),
+            volume("192.168.1.23", 60),
+        ];
+
+        let result = run_steps(actions, false, |action| async move {
+            match action {
+                BatchAction::SetVolume { ip, .. } if ip == "192.168.1.22" => {
+                    Err(ThaumicError::speaker_not_found(ip))
+                }
+                BatchAction::SetVolume { ip, .. } => Ok(Some(volume(&ip, 20))),
+                _ => unreachable!(),
+            }
+        })
+        .await;
+
+        let statuses: Vec<_> = result.steps.iter().map(|s| s.status).collect();
+        assert_eq!(
+            statuses,
+            [
+                StepStatus::Applied,
+                StepStatus::Applied,
+                StepStatus::Failed,
+                StepStatus::Skipped
+            ]
+        );
+        assert_eq!((result.applied, result.failed), (2, 1));
+        assert_eq!(
+            result.steps[2].error.as_ref().map(|e| e.error),
+            Some("speaker_not_found")
+        );
+        assert_eq!(
+            result.rollback,
+            [volume("192.168.1.21", 20), volume("192.168.1.20", 20)]
+        );
+    }
+
+    #[tokio::test]
+    async fn continues_after_failure_when_asked() {
+        let actions = vec![volume("192.168.1.20", 30), volume("192.168.1.21", 40)];
+
+        let result = run_steps(actions, true, |action| async move {
+            match action {
+                BatchAction::SetVolume { ip, .. } if ip == "192.168.1.20" => {
+                    Err(ThaumicError::Soap("timed out".into()))
+                }
+                _ => Ok(None),
+            }
+        })
+        .await;
+
+        assert_eq!((result.applied, result.failed), (1, 1));
+        assert!(result.rollback.is_empty());
+    }
+}
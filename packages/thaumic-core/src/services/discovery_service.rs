@@ -3,21 +3,26 @@
 //! This is a facade that orchestrates:
 //! - [`TopologyMonitor`] - Background discovery and subscription management
 //! - [`GenaEventProcessor`] - Event processing and state updates
+//! - [`SpeakerCache`] - Speakers kept across restarts for a fast first topology
 
 use std::sync::Arc;
+use std::time::Instant;
 
 use reqwest::Client;
 use tokio::sync::{mpsc, Notify};
 
+use crate::clock::Clock;
 use crate::context::NetworkContext;
 use crate::events::{EventEmitter, SonosEvent};
+use crate::operations::OperationRegistry;
 use crate::runtime::TokioSpawner;
 use crate::sonos::gena::GenaSubscriptionManager;
 use crate::sonos::subscription_arbiter::SubscriptionArbiter;
 use crate::sonos::SonosTopologyClient;
 use crate::state::SonosState;
 
-use super::gena_event_processor::GenaEventProcessor;
+use super::gena_event_processor::{GenaEventProcessor, GenaNotifyStats};
+use super::speaker_cache::SpeakerCache;
 use super::stream_coordinator::StreamCoordinator;
 use super::topology_monitor::{TopologyMonitor, TopologyMonitorConfig};
 
@@ -30,6 +35,7 @@ pub struct DiscoveryService {
     event_processor: Arc<GenaEventProcessor>,
     gena_manager: Arc<GenaSubscriptionManager>,
     sonos_state: Arc<SonosState>,
+    speaker_cache: Arc<SpeakerCache>,
 }
 
 impl DiscoveryService {
@@ -43,10 +49,13 @@ impl DiscoveryService {
     /// * `network` - Network configuration (port, local IP)
     /// * `http_client` - HTTP client for GENA requests
     /// * `topology_refresh_interval_secs` - Interval between automatic topology refreshes
+    /// * `unicast_only` - Skip SSDP and mDNS, relying on manual speaker IPs
     /// * `spawner` - Task spawner for background tasks
     /// * `gena_manager` - Pre-created GENA subscription manager (shared with StreamCoordinator)
     /// * `gena_event_rx` - Receiver for GENA events
     /// * `arbiter` - Subscription arbiter for RenderingControl/GroupRenderingControl conflict resolution
+    /// * `operations` - Registry that discovery runs report progress to
+    /// * `clock` - Time source for the refresh schedule
     #[allow(clippy::too_many_arguments)]
     pub fn new(
         sonos: Arc<dyn SonosTopologyClient>,
@@ -56,12 +65,16 @@ impl DiscoveryService {
         network: NetworkContext,
         http_client: Client,
         topology_refresh_interval_secs: u64,
+        unicast_only: bool,
         spawner: TokioSpawner,
         gena_manager: Arc<GenaSubscriptionManager>,
         gena_event_rx: mpsc::Receiver<SonosEvent>,
         refresh_notify: Arc<Notify>,
         arbiter: Arc<SubscriptionArbiter>,
+        operations: Arc<OperationRegistry>,
+        clock: Arc<dyn Clock>,
     ) -> Self {
+        let speaker_cache = Arc::new(SpeakerCache::new());
         let topology_monitor = Arc::new(TopologyMonitor::new(
             sonos,
             Arc::clone(&gena_manager),
@@ -69,10 +82,15 @@ impl DiscoveryService {
             Arc::clone(&emitter),
             TopologyMonitorConfig {
                 topology_refresh_interval_secs,
+                unicast_only,
                 network,
                 refresh_notify: Arc::clone(&refresh_notify),
                 http_client,
                 spawner: spawner.clone(),
+                operations,
+                clock,
+                speaker_cache: Arc::clone(&speaker_cache),
+                stream_coordinator: Arc::clone(&stream_coordinator),
             },
             arbiter,
         ));
@@ -92,6 +110,7 @@ impl DiscoveryService {
             event_processor,
             gena_manager,
             sonos_state,
+            speaker_cache,
         }
     }
 
@@ -110,15 +129,26 @@ impl DiscoveryService {
         &self.topology_monitor
     }
 
+    /// Returns the speakers found by the last discovery, possibly in an
+    /// earlier run.
+    pub fn speaker_cache(&self) -> &Arc<SpeakerCache> {
+        &self.speaker_cache
+    }
+
     /// Triggers a manual topology refresh.
     pub fn trigger_refresh(&self) {
         self.topology_monitor.trigger_refresh();
     }
 
-    /// Sets the app data directory for loading manual speaker configuration.
+    /// Sets the app data directory for loading manual speaker configuration,
+    /// ignored speakers and the speaker cache.
     ///
     /// This should be called after the app is set up and the AppHandle is available.
     pub fn set_app_data_dir(&self, path: impl AsRef<std::path::Path>) {
+        self.speaker_cache.set_data_dir(path.as_ref());
+        self.sonos_state
+            .ignored_speakers
+            .set_data_dir(path.as_ref());
         self.topology_monitor.set_app_data_dir(path);
     }
 
@@ -137,8 +167,14 @@ impl DiscoveryService {
     /// Handles a GENA NOTIFY event from an HTTP handler.
     ///
     /// Parses the notification, updates internal state, and broadcasts to WebSocket clients.
-    pub fn handle_gena_notify(&self, sid: &str, body: &str) -> Vec<SonosEvent> {
-        self.event_processor.handle_gena_notify(sid, body)
+    /// `received` is when the request arrived.
+    pub fn handle_gena_notify(&self, sid: &str, body: &str, received: Instant) -> Vec<SonosEvent> {
+        self.event_processor.handle_gena_notify(sid, body, received)
+    }
+
+    /// Returns GENA NOTIFY processing lag and event queue depth.
+    pub fn gena_notify_stats(&self) -> GenaNotifyStats {
+        self.event_processor.notify_stats()
     }
 
     /// Starts the GENA renewal background task.
@@ -4,18 +4,24 @@
 //! - Processing GENA NOTIFY requests
 //! - Updating SonosState based on event types
 //! - Broadcasting events to WebSocket clients
+//! - Measuring how long NOTIFYs take to reach state and clients
 
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 use parking_lot::Mutex;
+use serde::Serialize;
 use tokio::sync::{mpsc, Notify};
 
-use crate::events::{EventEmitter, SonosEvent};
+use crate::events::{EventEmitter, LockedSpeakerChange, SonosEvent, TopologyEvent};
 use crate::runtime::TokioSpawner;
 use crate::services::stream_coordinator::StreamCoordinator;
 use crate::sonos::gena::GenaSubscriptionManager;
 use crate::sonos::gena_parser;
 use crate::sonos::services::SonosService;
+use crate::sonos::types::TransportState;
+use crate::sonos::zone_groups::merge_household;
 use crate::state::SonosState;
 
 /// Dependencies required for event processing.
@@ -29,10 +35,80 @@ struct EventProcessorDeps {
     stream_coordinator: Arc<StreamCoordinator>,
 }
 
+/// GENA NOTIFY processing statistics.
+///
+/// Lags are measured from the moment a NOTIFY request arrives, so they show
+/// our own backlog; a speaker that is slow to notify doesn't add to them.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GenaNotifyStats {
+    /// NOTIFY requests for known subscriptions.
+    pub notifies: u64,
+    /// Events parsed from them.
+    pub events: u64,
+    /// Mean time from receiving a NOTIFY to its state being applied.
+    pub avg_apply_lag_ms: u64,
+    pub max_apply_lag_ms: u64,
+    /// Mean time from receiving a NOTIFY to its events being emitted.
+    pub avg_emit_lag_ms: u64,
+    pub max_emit_lag_ms: u64,
+    /// Emit lag of the most recent NOTIFY.
+    pub last_emit_lag_ms: u64,
+    /// Internal events (e.g. lost subscriptions) waiting to be processed.
+    pub queue_depth: usize,
+}
+
+/// Lag counters behind [`GenaNotifyStats`].
+#[derive(Default)]
+struct NotifyLag {
+    notifies: AtomicU64,
+    events: AtomicU64,
+    total_apply_ms: AtomicU64,
+    max_apply_ms: AtomicU64,
+    total_emit_ms: AtomicU64,
+    max_emit_ms: AtomicU64,
+    last_emit_ms: AtomicU64,
+}
+
+impl NotifyLag {
+    fn record(&self, events: usize, apply: Duration, emit: Duration) {
+        let apply_ms = apply.as_millis() as u64;
+        let emit_ms = emit.as_millis() as u64;
+        self.notifies.fetch_add(1, Ordering::Relaxed);
+        self.events.fetch_add(events as u64, Ordering::Relaxed);
+        self.total_apply_ms.fetch_add(apply_ms, Ordering::Relaxed);
+        self.max_apply_ms.fetch_max(apply_ms, Ordering::Relaxed);
+        self.total_emit_ms.fetch_add(emit_ms, Ordering::Relaxed);
+        self.max_emit_ms.fetch_max(emit_ms, Ordering::Relaxed);
+        self.last_emit_ms.store(emit_ms, Ordering::Relaxed);
+    }
+
+    fn stats(&self, queue_depth: usize) -> GenaNotifyStats {
+        let notifies = self.notifies.load(Ordering::Relaxed);
+        let mean = |total: &AtomicU64| {
+            total
+                .load(Ordering::Relaxed)
+                .checked_div(notifies)
+                .unwrap_or(0)
+        };
+        GenaNotifyStats {
+            notifies,
+            events: self.events.load(Ordering::Relaxed),
+            avg_apply_lag_ms: mean(&self.total_apply_ms),
+            max_apply_lag_ms: self.max_apply_ms.load(Ordering::Relaxed),
+            avg_emit_lag_ms: mean(&self.total_emit_ms),
+            max_emit_lag_ms: self.max_emit_ms.load(Ordering::Relaxed),
+            last_emit_lag_ms: self.last_emit_ms.load(Ordering::Relaxed),
+            queue_depth,
+        }
+    }
+}
+
 /// Processes GENA events and updates application state.
 pub struct GenaEventProcessor {
     gena_manager: Arc<GenaSubscriptionManager>,
     deps: EventProcessorDeps,
+    lag: NotifyLag,
     gena_event_rx: Arc<Mutex<Option<mpsc::Receiver<SonosEvent>>>>,
     /// Task spawner for background tasks.
     spawner: TokioSpawner,
@@ -57,16 +133,23 @@ impl GenaEventProcessor {
                 refresh_notify,
                 stream_coordinator,
             },
+            lag: NotifyLag::default(),
             gena_event_rx: Arc::new(Mutex::new(Some(gena_event_rx))),
             spawner,
         }
     }
 
+    /// Returns NOTIFY processing lag and the depth of the internal event queue.
+    pub fn notify_stats(&self) -> GenaNotifyStats {
+        self.lag.stats(self.gena_manager.queued_events())
+    }
+
     /// Handles a GENA NOTIFY event from an HTTP handler.
     ///
     /// Resolves the subscription, routes to the appropriate parser by service type,
-    /// updates internal state, and broadcasts to WebSocket clients.
-    pub fn handle_gena_notify(&self, sid: &str, body: &str) -> Vec<SonosEvent> {
+    /// updates internal state, and broadcasts to WebSocket clients. `received`
+    /// is when the request arrived, for the lag statistics.
+    pub fn handle_gena_notify(&self, sid: &str, body: &str, received: Instant) -> Vec<SonosEvent> {
         let Some((ip, service)) = self.gena_manager.resolve_sid(sid) else {
             return vec![];
         };
@@ -80,7 +163,18 @@ impl GenaEventProcessor {
             SonosService::GroupRenderingControl => {
                 gena_parser::parse_group_rendering_events(&ip, body)
             }
-            SonosService::ZoneGroupTopology => gena_parser::parse_zone_topology_events(body),
+            SonosService::ZoneGroupTopology => gena_parser::parse_zone_topology_events(body)
+                .into_iter()
+                .map(|event| match event {
+                    SonosEvent::ZoneGroupsUpdated { groups, timestamp } => {
+                        SonosEvent::ZoneGroupsUpdated {
+                            groups: self.deps.sonos_state.ignored_speakers.filter_groups(groups),
+                            timestamp,
+                        }
+                    }
+                    other => other,
+                })
+                .collect(),
             SonosService::RenderingControl => {
                 let events = gena_parser::parse_rendering_control_events(&ip, body);
                 log::info!(
@@ -92,20 +186,26 @@ impl GenaEventProcessor {
             }
         };
 
+        let mut applied = received.elapsed();
         for event in &events {
-            self.process_event(event);
+            Self::apply_event(&self.deps, event);
+            applied = received.elapsed();
+            self.deps.emitter.emit_sonos(event.clone());
         }
+        self.lag.record(events.len(), applied, received.elapsed());
 
         events
     }
 
-    /// Processes a single GENA event: updates local state cache and broadcasts to clients.
-    fn process_event(&self, event: &SonosEvent) {
-        Self::process_event_with_deps(&self.deps, event);
+    /// Core event processing logic shared between sync and async contexts:
+    /// updates local state cache and broadcasts to clients.
+    fn process_event_with_deps(deps: &EventProcessorDeps, event: &SonosEvent) {
+        Self::apply_event(deps, event);
+        deps.emitter.emit_sonos(event.clone());
     }
 
-    /// Core event processing logic shared between sync and async contexts.
-    fn process_event_with_deps(deps: &EventProcessorDeps, event: &SonosEvent) {
+    /// Updates local state for a single GENA event.
+    fn apply_event(deps: &EventProcessorDeps, event: &SonosEvent) {
         match event {
             SonosEvent::TransportState {
                 speaker_ip,
@@ -117,9 +217,21 @@ impl GenaEventProcessor {
                     speaker_ip,
                     transport_state
                 );
-                deps.sonos_state
+                let previous = deps
+                    .sonos_state
                     .transport_states
                     .insert(speaker_ip.clone(), *transport_state);
+                // Playing straight to Stopped is someone pressing Stop (or
+                // the speaker giving up); our own stops are filtered out there
+                if previous == Some(TransportState::Playing)
+                    && *transport_state == TransportState::Stopped
+                {
+                    deps.stream_coordinator
+                        .report_speaker_change(speaker_ip, LockedSpeakerChange::Stopped);
+                    deps.stream_coordinator.handle_external_stop(speaker_ip);
+                }
+                deps.stream_coordinator
+                    .handle_transport_state(speaker_ip, *transport_state);
             }
             SonosEvent::GroupVolume {
                 speaker_ip,
@@ -133,9 +245,14 @@ impl GenaEventProcessor {
                     volume,
                     fixed
                 );
-                deps.sonos_state
+                let previous = deps
+                    .sonos_state
                     .group_volumes
                     .insert(speaker_ip.clone(), *volume);
+                if previous.is_some_and(|previous| previous != *volume) {
+                    deps.stream_coordinator
+                        .report_speaker_change(speaker_ip, LockedSpeakerChange::Volume);
+                }
                 if let Some(is_fixed) = fixed {
                     deps.sonos_state
                         .group_volume_fixed
@@ -150,19 +267,36 @@ impl GenaEventProcessor {
                     speaker_ip,
                     muted
                 );
-                deps.sonos_state
+                let previous = deps
+                    .sonos_state
                     .group_mutes
                     .insert(speaker_ip.clone(), *muted);
+                if previous.is_some_and(|previous| previous != *muted) {
+                    deps.stream_coordinator
+                        .report_speaker_change(speaker_ip, LockedSpeakerChange::Mute);
+                }
             }
-            SonosEvent::ZoneGroupsUpdated { groups, .. } => {
-                // GENA notification bodies can carry stale topology data
-                // (e.g., still showing combined groups after an unjoin).
-                // Instead of directly overwriting state with potentially stale
-                // data, signal the topology monitor to do a fresh SOAP fetch.
+            SonosEvent::ZoneGroupsUpdated { groups, timestamp } => {
+                // Apply the embedded ZoneGroupState right away so clients see
+                // grouping changes made in the Sonos app without waiting for a
+                // poll. Notification bodies can carry stale topology data
+                // (e.g., still showing combined groups after an unjoin), so the
+                // topology monitor confirms with a fresh SOAP fetch and syncs
+                // coordinator subscriptions. The event only covers the
+                // household of the speaker that sent it.
                 log::info!(
                     "[GenaEventProcessor] Zone groups changed ({} groups), requesting topology refresh",
                     groups.len()
                 );
+                let groups = {
+                    let mut current = deps.sonos_state.groups.write();
+                    *current = merge_household(&current, groups.clone());
+                    current.clone()
+                };
+                deps.emitter.emit_topology(TopologyEvent::GroupsDiscovered {
+                    groups,
+                    timestamp: *timestamp,
+                });
                 deps.refresh_notify.notify_one();
             }
             SonosEvent::SourceChanged {
@@ -177,8 +311,11 @@ impl GenaEventProcessor {
                     current_uri,
                     expected_uri
                 );
-                // Clean up playback session - speaker is no longer playing our stream
-                deps.stream_coordinator.handle_source_changed(speaker_ip);
+                deps.stream_coordinator
+                    .report_speaker_change(speaker_ip, LockedSpeakerChange::Source);
+                // Suspend the playback session - speaker is no longer playing our stream
+                deps.stream_coordinator
+                    .handle_source_changed(speaker_ip, current_uri);
             }
             SonosEvent::SubscriptionLost {
                 speaker_ip,
@@ -195,9 +332,6 @@ impl GenaEventProcessor {
                 deps.refresh_notify.notify_one();
             }
         }
-
-        // Emit event to listeners
-        deps.emitter.emit_sonos(event.clone());
     }
 
     /// Spawns a task to forward internal GENA events (e.g., SubscriptionLost) to WebSocket clients.
@@ -220,3 +354,27 @@ impl GenaEventProcessor {
         });
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn notify_lag_averages_per_notify() {
+        let lag = NotifyLag::default();
+        assert_eq!(lag.stats(0), GenaNotifyStats::default());
+
+        lag.record(2, Duration::from_millis(10), Duration::from_millis(12));
+        lag.record(1, Duration::from_millis(30), Duration::from_millis(40));
+
+        let stats = lag.stats(3);
+        assert_eq!(stats.notifies, 2);
+        assert_eq!(stats.events, 3);
+        assert_eq!(stats.avg_apply_lag_ms, 20);
+        assert_eq!(stats.max_apply_lag_ms, 30);
+        assert_eq!(stats.avg_emit_lag_ms, 26);
+        assert_eq!(stats.max_emit_lag_ms, 40);
+        assert_eq!(stats.last_emit_lag_ms, 40);
+        assert_eq!(stats.queue_depth, 3);
+    }
+}
@@ -0,0 +1,132 @@
+//! Session handoff to other devices.
+//!
+//! A cast is tied to the speakers it plays on, but its stream is a plain HTTP
+//! resource any player on the LAN can open. `GET /api/streams/{id}/handoff`
+//! packages what another device needs to listen along (stream URL, track
+//! metadata, how long the stream has been running) into a small payload,
+//! with a short text meant for a QR code, so a phone can pick up the cast in
+//! a room without speakers. `GET /api/streams/{id}/qr.png` serves that code
+//! as an image.
+
+use image::{ImageFormat, Luma};
+use qrcode::QrCode;
+use serde::Serialize;
+
+use crate::context::NetworkContext;
+use crate::error::{ThaumicError, ThaumicResult};
+use crate::services::StreamCoordinator;
+use crate::stream::{AudioCodec, StreamMetadata};
+
+/// What another device needs to follow a stream.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionHandoff {
+    pub stream_id: String,
+    /// URL a media player can open to listen to the stream.
+    pub stream_url: String,
+    pub codec: AudioCodec,
+    /// User-given name for the stream.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    pub metadata: StreamMetadata,
+    /// Time since the stream received its first audio, in milliseconds.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub elapsed_ms: Option<u64>,
+    /// Speakers currently playing the stream.
+    pub speaker_ips: Vec<String>,
+    /// Text to encode in a QR code. Currently the stream URL, which phone
+    /// cameras offer to open directly.
+    pub qr_text: String,
+}
+
+/// Builds the handoff payload for a stream.
+pub fn build_handoff(
+    stream_coordinator: &StreamCoordinator,
+    network: &NetworkContext,
+    stream_id: &str,
+) -> ThaumicResult<SessionHandoff> {
+    let stream = stream_coordinator
+        .get_stream(stream_id)
+        .ok_or_else(|| ThaumicError::StreamNotFound(stream_id.to_string()))?;
+
+    let stream_url = listener_stream_url(&network.stream_url(stream_id), stream.codec);
+    let mut speaker_ips: Vec<String> = stream_coordinator
+        .get_all_sessions()
+        .into_iter()
+        .filter(|session| session.stream_id == stream_id)
+        .map(|session| session.speaker_ip)
+        .collect();
+    speaker_ips.sort();
+    let metadata = stream.metadata.read().clone();
+
+    Ok(SessionHandoff {
+        stream_id: stream_id.to_string(),
+        qr_text: stream_url.clone(),
+        stream_url,
+        codec: stream.codec,
+        label: stream.label(),
+        metadata,
+        elapsed_ms: stream
+            .timing
+            .first_frame_at()
+            .map(|first| first.elapsed().as_millis() as u64),
+        speaker_ips,
+    })
+}
+
+/// Smallest width of a rendered QR code in pixels, quiet zone included.
+const QR_MIN_SIZE: u32 = 256;
+
+/// Renders `text` as a QR code PNG.
+pub fn render_qr_png(text: &str) -> ThaumicResult<Vec<u8>> {
+    let code = QrCode::new(text)
+        .map_err(|e| ThaumicError::Internal(format!("Failed to encode QR code: {}", e)))?;
+    let image = code
+        .render::<Luma<u8>>()
+        .min_dimensions(QR_MIN_SIZE, QR_MIN_SIZE)
+        .build();
+
+    let mut png = Vec::new();
+    image
+        .write_to(&mut std::io::Cursor::new(&mut png), ImageFormat::Png)
+        .map_err(|e| ThaumicError::Internal(format!("Failed to encode QR code PNG: {}", e)))?;
+    Ok(png)
+}
+
+/// Appends the extension media players expect for the codec.
+///
+/// Unlike the URIs given to speakers, MP3 and AAC keep plain `http://`.
+fn listener_stream_url(base_url: &str, codec: AudioCodec) -> String {
+    match codec {
+        AudioCodec::Pcm => format!("{}.wav", base_url),
+        AudioCodec::Flac => format!("{}.flac", base_url),
+        AudioCodec::Aac | AudioCodec::Mp3 => base_url.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn listener_urls_stay_plain_http() {
+        let base = "http://192.168.1.10:49400/stream/7d3c/live";
+        assert_eq!(
+            listener_stream_url(base, AudioCodec::Flac),
+            "http://192.168.1.10:49400/stream/7d3c/live.flac"
+        );
+        assert_eq!(
+            listener_stream_url(base, AudioCodec::Pcm),
+            "http://192.168.1.10:49400/stream/7d3c/live.wav"
+        );
+        assert_eq!(listener_stream_url(base, AudioCodec::Mp3), base);
+    }
+
+    #[test]
+    fn renders_qr_codes_as_png() {
+        let png = render_qr_png("http://192.168.1.10:49400/stream/7d3c/live.flac").unwrap();
+        let image = image::load_from_memory_with_format(&png, ImageFormat::Png).unwrap();
+        assert!(image.width() >= QR_MIN_SIZE);
+        assert_eq!(image.width(), image.height());
+    }
+}
@@ -0,0 +1,239 @@
+//! Cross-map consistency checks for debug builds.
+//!
+//! Streams, playback sessions, suspensions and speaker state live in separate
+//! maps that mutating operations keep in step by hand. The checks here spell
+//! out what "in step" means so drift shows up next to the operation that
+//! caused it instead of as a confusing symptom much later. Violations are
+//! logged as errors; under `cargo test` they panic.
+//!
+//! The module only exists with `debug_assertions`, so release builds pay
+//! nothing for it.
+
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+use crate::services::{GroupRole, PlaybackSession};
+
+/// A broken cross-map invariant.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Violation {
+    /// A session refers to a stream that isn't registered.
+    SessionWithoutStream {
+        stream_id: String,
+        speaker_ip: String,
+    },
+    /// A slave session's coordinator isn't playing the same stream.
+    SlaveWithoutCoordinator {
+        stream_id: String,
+        speaker_ip: String,
+        coordinator_ip: Option<String>,
+    },
+    /// A speaker is both playing and suspended by another source.
+    PlayingAndSuspended { speaker_ip: String },
+    /// A transport state is kept for a speaker outside the topology.
+    UnknownTransportState { speaker_ip: String },
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::SessionWithoutStream {
+                stream_id,
+                speaker_ip,
+            } => write!(
+                f,
+                "session {} -> {} refers to a missing stream",
+                speaker_ip, stream_id
+            ),
+            Self::SlaveWithoutCoordinator {
+                stream_id,
+                speaker_ip,
+                coordinator_ip,
+            } => write!(
+                f,
+                "slave {} on stream {} has no coordinator session (coordinator: {:?})",
+                speaker_ip, stream_id, coordinator_ip
+            ),
+            Self::PlayingAndSuspended { speaker_ip } => {
+                write!(f, "speaker {} is both playing and suspended", speaker_ip)
+            }
+            Self::UnknownTransportState { speaker_ip } => {
+                write!(f, "transport state kept for unknown speaker {}", speaker_ip)
+            }
+        }
+    }
+}
+
+/// Checks playback sessions against the stream registry and suspensions.
+///
+/// # Arguments
+/// * `sessions` - Every active playback session
+/// * `stream_exists` - Whether a stream ID is registered
+/// * `suspended_ips` - Speakers whose session is suspended
+pub(crate) fn check_sessions(
+    sessions: &[PlaybackSession],
+    stream_exists: impl Fn(&str) -> bool,
+    suspended_ips: &HashSet<String>,
+) -> Vec<Violation> {
+    let coordinators: HashMap<(&str, &str), ()> = sessions
+        .iter()
+        .filter(|s| s.role == GroupRole::Coordinator)
+        .map(|s| ((s.stream_id.as_str(), s.speaker_ip.as_str()), ()))
+        .collect();
+
+    let mut violations = Vec::new();
+    for session in sessions {
+        if !stream_exists(&session.stream_id) {
+            violations.push(Violation::SessionWithoutStream {
+                stream_id: session.stream_id.clone(),
+                speaker_ip: session.speaker_ip.clone(),
+            });
+        }
+        if session.role == GroupRole::Slave {
+            let has_coordinator = session
+                .coordinator_ip
+                .as_deref()
+                .is_some_and(|ip| coordinators.contains_key(&(session.stream_id.as_str(), ip)));
+            if !has_coordinator {
+                violations.push(Violation::SlaveWithoutCoordinator {
+                    stream_id: session.stream_id.clone(),
+                    speaker_ip: session.speaker_ip.clone(),
+                    coordinator_ip: session.coordinator_ip.clone(),
+                });
+            }
+        }
+        if suspended_ips.contains(&session.speaker_ip) {
+            violations.push(Violation::PlayingAndSuspended {
+                speaker_ip: session.speaker_ip.clone(),
+            });
+        }
+    }
+    violations
+}
+
+/// Checks that transport states are only kept for speakers in the topology.
+pub(crate) fn check_transport_states<'a>(
+    state_ips: impl IntoIterator<Item = &'a String>,
+    known_ips: &HashSet<String>,
+) -> Vec<Violation> {
+    state_ips
+        .into_iter()
+        .filter(|ip| !known_ips.contains(*ip))
+        .map(|ip| Violation::UnknownTransportState {
+            speaker_ip: ip.clone(),
+        })
+        .collect()
+}
+
+/// Reports violations found after `operation`.
+///
+/// Logs each one; in tests, panics so the offending test fails.
+pub(crate) fn report(operation: &str, violations: &[Violation]) {
+    for violation in violations {
+        log::error!("[Invariants] After {}: {}", operation, violation);
+    }
+    if cfg!(test) && !violations.is_empty() {
+        panic!(
+            "invariants violated after {}: {}",
+            operation,
+            violations
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join("; ")
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stream::AudioCodec;
+
+    fn session(stream_id: &str, ip: &str, coordinator_ip: Option<&str>) -> PlaybackSession {
+        PlaybackSession {
+            stream_id: stream_id.to_string(),
+            speaker_ip: ip.to_string(),
+            stream_url: String::new(),
+            codec: AudioCodec::Pcm,
+            role: if coordinator_ip.is_some() {
+                GroupRole::Slave
+            } else {
+                GroupRole::Coordinator
+            },
+            coordinator_ip: coordinator_ip.map(str::to_string),
+            coordinator_uuid: None,
+            original_coordinator_uuid: None,
+            artwork_url: None,
+        }
+    }
+
+    #[test]
+    fn consistent_sessions_pass() {
+        let sessions = [
+            session("s1", "10.0.0.1", None),
+            session("s1", "10.0.0.2", Some("10.0.0.1")),
+        ];
+        assert!(check_sessions(&sessions, |id| id == "s1", &HashSet::new()).is_empty());
+    }
+
+    #[test]
+    fn flags_sessions_for_missing_streams() {
+        let sessions = [session("gone", "10.0.0.1", None)];
+        assert_eq!(
+            check_sessions(&sessions, |_| false, &HashSet::new()),
+            vec![Violation::SessionWithoutStream {
+                stream_id: "gone".to_string(),
+                speaker_ip: "10.0.0.1".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn flags_slaves_whose_coordinator_plays_another_stream() {
+        let sessions = [
+            session("s1", "10.0.0.1", None),
+            session("s2", "10.0.0.2", Some("10.0.0.1")),
+        ];
+        let violations = check_sessions(&sessions, |_| true, &HashSet::new());
+        assert!(matches!(
+            violations.as_slice(),
+            [Violation::SlaveWithoutCoordinator { speaker_ip, .. }] if speaker_ip == "10.0.0.2"
+        ));
+    }
+
+    #[test]
+    fn flags_speakers_both_playing_and_suspended() {
+        let sessions = [session("s1", "10.0.0.1", None)];
+        let suspended: HashSet<String> = ["10.0.0.1".to_string()].into();
+        assert_eq!(
+            check_sessions(&sessions, |_| true, &suspended),
+            vec![Violation::PlayingAndSuspended {
+                speaker_ip: "10.0.0.1".to_string()
+            }]
+        );
+    }
+
+    #[test]
+    fn flags_transport_states_for_unknown_speakers() {
+        let known: HashSet<String> = ["10.0.0.1".to_string()].into();
+        let states = ["10.0.0.1".to_string(), "10.0.0.9".to_string()];
+        assert_eq!(
+            check_transport_states(&states, &known),
+            vec![Violation::UnknownTransportState {
+                speaker_ip: "10.0.0.9".to_string()
+            }]
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "invariants violated after test op")]
+    fn report_panics_in_tests() {
+        report(
+            "test op",
+            &[Violation::PlayingAndSuspended {
+                speaker_ip: "10.0.0.1".to_string(),
+            }],
+        );
+    }
+}
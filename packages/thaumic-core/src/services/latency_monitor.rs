@@ -23,25 +23,52 @@
 //! - Exponential moving average for stability
 //! - Incremental variance (jitter) calculation for confidence scoring
 //! - Track restart detection to maintain continuity
+//! - Delta-compressed updates: at most one a second per speaker, and only
+//!   when latency, jitter or confidence moved noticeably, with a keepalive
+//!   snapshot every 10 seconds otherwise
+//!
+//! # Position Updates
+//!
+//! Every playing speaker also gets a position push (`stream.position`) once a
+//! second, whether or not latency is measured for it, so clients can drive a
+//! progress display without polling. Speakers without latency measurement are
+//! only polled at that rate.
 
+use std::collections::HashSet;
 use std::net::IpAddr;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 
 use dashmap::DashMap;
-use tokio::sync::mpsc;
+use tokio::sync::{mpsc, oneshot};
 use tokio_util::sync::CancellationToken;
 
-use crate::events::{EventEmitter, LatencyEvent};
+use crate::clock::Clock;
+use crate::events::{EventEmitter, LatencyEvent, StreamEvent};
 use crate::runtime::TokioSpawner;
 use crate::sonos::traits::SonosPlayback;
 use crate::stream::{PlaybackEpoch, StreamRegistry, StreamTiming};
-use crate::utils::now_millis;
 
 /// Polling interval for position queries.
 /// 500ms is sufficient since Sonos RelTime only has 1-second precision.
 const POLL_INTERVAL_MS: u64 = 500;
 
+/// Interval between position updates for a speaker.
+const POSITION_INTERVAL_MS: u64 = 1000;
+
+/// Minimum time between latency updates for a speaker.
+const MIN_EMIT_INTERVAL_MS: u64 = 1000;
+
+/// Change in latency that warrants an update before the keepalive.
+const LATENCY_CHANGE_THRESHOLD_MS: u64 = 20;
+
+/// Change in jitter that warrants an update before the keepalive.
+const JITTER_CHANGE_THRESHOLD_MS: u64 = 10;
+
+/// Interval after which an unchanged measurement is sent again, so clients
+/// joining late or missing an update still get a recent snapshot.
+const KEEPALIVE_INTERVAL_SECS: u64 = 10;
+
 /// Minimum samples needed before emitting latency updates.
 const MIN_SAMPLES_FOR_CONFIDENCE: usize = 5;
 
@@ -54,7 +81,7 @@ const EMA_ALPHA: f64 = 0.3;
 const STALE_EPOCH_TIMEOUT_SECS: u64 = 30;
 
 /// Key for identifying a monitoring session (stream_id, speaker_ip).
-type SessionKey = (String, String);
+pub(crate) type SessionKey = (String, String);
 
 /// Result of epoch synchronization check.
 enum EpochStatus {
@@ -66,8 +93,31 @@ enum EpochStatus {
     Stale,
 }
 
+/// A latency measurement as last sent to clients.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct EmittedLatency {
+    latency_ms: u64,
+    jitter_ms: u64,
+    confidence: f32,
+}
+
+impl EmittedLatency {
+    /// Whether `self` differs enough from `previous` to be worth sending.
+    fn differs_from(&self, previous: &Self) -> bool {
+        self.latency_ms.abs_diff(previous.latency_ms) >= LATENCY_CHANGE_THRESHOLD_MS
+            || self.jitter_ms.abs_diff(previous.jitter_ms) >= JITTER_CHANGE_THRESHOLD_MS
+            || self.confidence != previous.confidence
+    }
+}
+
 /// Tracks latency measurement state for a single speaker.
 struct LatencySession {
+    /// Whether latency is measured, or only position updates are pushed.
+    measure_latency: bool,
+    /// When the speaker's position was last queried.
+    last_poll: Option<Instant>,
+    /// When we last emitted a position update.
+    last_position_emit: Option<Instant>,
     /// Last observed Sonos RelTime (ms) for detecting track restarts.
     /// When RelTime goes backwards, we know the track restarted.
     last_sonos_reltime_ms: Option<u64>,
@@ -84,6 +134,8 @@ struct LatencySession {
     running_m2: f64,
     /// When we last emitted an update.
     last_emit: Option<Instant>,
+    /// Measurement last emitted, to suppress updates that barely differ.
+    last_emitted: Option<EmittedLatency>,
     /// Last epoch ID we measured against.
     /// If this changes, Sonos reconnected and we need to reset.
     last_epoch_id: u64,
@@ -96,8 +148,11 @@ struct LatencySession {
 
 impl LatencySession {
     /// Creates a new monitoring session.
-    fn new() -> Self {
+    fn new(measure_latency: bool) -> Self {
         Self {
+            measure_latency,
+            last_poll: None,
+            last_position_emit: None,
             last_sonos_reltime_ms: None,
             sonos_offset_ms: 0,
             ema_latency: 0.0,
@@ -105,6 +160,7 @@ impl LatencySession {
             running_mean: 0.0,
             running_m2: 0.0,
             last_emit: None,
+            last_emitted: None,
             last_epoch_id: 0,
             last_valid_position: None,
             stale_emitted: false,
@@ -122,6 +178,7 @@ impl LatencySession {
         self.running_m2 = 0.0;
         self.last_valid_position = None;
         self.stale_emitted = false;
+        self.last_emitted = None;
     }
 
     /// Syncs with current epoch for a speaker IP.
@@ -130,7 +187,12 @@ impl LatencySession {
     /// or Stale if we haven't received valid position data recently.
     /// Resets session if epoch changed, but seeds EMA with previous value
     /// to avoid "jump to 0 then climb back" behavior.
-    fn sync_epoch(&mut self, timing: &StreamTiming, speaker_ip: IpAddr) -> EpochStatus {
+    fn sync_epoch(
+        &mut self,
+        timing: &StreamTiming,
+        speaker_ip: IpAddr,
+        now: Instant,
+    ) -> EpochStatus {
         let epoch = match timing.current_epoch_for(speaker_ip) {
             Some(e) => e,
             None => return EpochStatus::NoEpoch,
@@ -154,11 +216,12 @@ impl LatencySession {
 
         // Check for stale epoch (no valid position data in a while)
         if let Some(last_valid) = self.last_valid_position {
-            if last_valid.elapsed().as_secs() > STALE_EPOCH_TIMEOUT_SECS {
+            let since_valid = now.saturating_duration_since(last_valid).as_secs();
+            if since_valid > STALE_EPOCH_TIMEOUT_SECS {
                 log::debug!(
                     "[LatencyMonitor] Epoch {} appears stale (no valid position for {}s)",
                     epoch.id,
-                    last_valid.elapsed().as_secs()
+                    since_valid
                 );
                 return EpochStatus::Stale;
             }
@@ -169,8 +232,8 @@ impl LatencySession {
 
     /// Records that we received valid position info (for stale detection).
     /// Also clears stale_emitted flag so we can emit again if it goes stale later.
-    fn record_valid_position(&mut self) {
-        self.last_valid_position = Some(Instant::now());
+    fn record_valid_position(&mut self, now: Instant) {
+        self.last_valid_position = Some(now);
         self.stale_emitted = false;
     }
 
@@ -312,17 +375,70 @@ impl LatencySession {
         }
     }
 
-    /// Returns true if enough time has passed to emit an update (rate limiting).
-    fn should_emit(&self) -> bool {
-        match self.last_emit {
-            Some(last) => last.elapsed() >= Duration::from_millis(1000),
-            None => self.sample_count >= MIN_SAMPLES_FOR_CONFIDENCE,
+    /// Returns the current measurement as it would be emitted.
+    fn snapshot(&self) -> EmittedLatency {
+        EmittedLatency {
+            latency_ms: self.latency_ms(),
+            jitter_ms: self.jitter_ms(),
+            confidence: self.confidence(),
+        }
+    }
+
+    /// Returns true if an update should be emitted now.
+    ///
+    /// Updates are rate limited, and after the first one only sent when the
+    /// measurement changed beyond the thresholds or the keepalive is due.
+    fn should_emit(&self, now: Instant) -> bool {
+        let Some(last) = self.last_emit else {
+            return self.sample_count >= MIN_SAMPLES_FOR_CONFIDENCE;
+        };
+        let since = now.saturating_duration_since(last);
+        if since < Duration::from_millis(MIN_EMIT_INTERVAL_MS) {
+            return false;
+        }
+        match &self.last_emitted {
+            Some(previous) => {
+                since >= Duration::from_secs(KEEPALIVE_INTERVAL_SECS)
+                    || self.snapshot().differs_from(previous)
+            }
+            None => true,
         }
     }
 
     /// Marks that we just emitted an update.
-    fn mark_emitted(&mut self) {
-        self.last_emit = Some(Instant::now());
+    fn mark_emitted(&mut self, now: Instant) {
+        self.last_emit = Some(now);
+        self.last_emitted = Some(self.snapshot());
+    }
+
+    /// Returns true if the speaker is due for a position query.
+    ///
+    /// Latency sessions are polled on every tick; others at the position rate.
+    fn should_poll(&self, now: Instant) -> bool {
+        self.measure_latency || position_due(self.last_poll, now)
+    }
+
+    /// Returns true if a position update is due, and marks it emitted.
+    fn take_position_due(&mut self, now: Instant) -> bool {
+        if !position_due(self.last_position_emit, now) {
+            return false;
+        }
+        self.last_position_emit = Some(now);
+        true
+    }
+}
+
+/// Whether a once-a-second action last done at `last` is due again.
+///
+/// Allows half a poll interval of slack so tick jitter doesn't push the
+/// action to the following tick.
+fn position_due(last: Option<Instant>, now: Instant) -> bool {
+    match last {
+        Some(last) => {
+            now.saturating_duration_since(last)
+                >= Duration::from_millis(POSITION_INTERVAL_MS - POLL_INTERVAL_MS / 2)
+        }
+        None => true,
     }
 }
 
@@ -332,6 +448,8 @@ enum MonitorCommand {
     Start {
         stream_id: String,
         speaker_ip: String,
+        /// Measure latency, not just push position updates.
+        measure_latency: bool,
     },
     /// Stop monitoring for a single speaker.
     StopSpeaker {
@@ -340,6 +458,11 @@ enum MonitorCommand {
     },
     /// Stop all monitoring for a stream.
     StopStream { stream_id: String },
+    /// Stop monitoring every pair not in `live`, replying with the count.
+    Retain {
+        live: HashSet<SessionKey>,
+        reply: oneshot::Sender<usize>,
+    },
 }
 
 /// Latency monitoring service.
@@ -360,6 +483,8 @@ pub struct LatencyMonitor {
     cancel: CancellationToken,
     /// Task spawner for background tasks.
     spawner: TokioSpawner,
+    /// Time source for polling, rate limiting and stale detection.
+    clock: Arc<dyn Clock>,
 }
 
 impl LatencyMonitor {
@@ -374,12 +499,14 @@ impl LatencyMonitor {
     /// * `emitter` - Event emitter for latency updates
     /// * `cancel` - Cancellation token for graceful shutdown
     /// * `spawner` - Task spawner for background tasks
+    /// * `clock` - Time source for polling, rate limiting and stale detection
     pub fn new(
         sonos: Arc<dyn SonosPlayback>,
         stream_registry: Arc<StreamRegistry>,
         emitter: Arc<dyn EventEmitter>,
         cancel: CancellationToken,
         spawner: TokioSpawner,
+        clock: Arc<dyn Clock>,
     ) -> Self {
         let (command_tx, command_rx) = mpsc::channel(32);
 
@@ -391,6 +518,7 @@ impl LatencyMonitor {
             emitter,
             cancel,
             spawner,
+            clock,
         }
     }
 
@@ -405,21 +533,36 @@ impl LatencyMonitor {
             let stream_registry = Arc::clone(&self.stream_registry);
             let emitter = Arc::clone(&self.emitter);
             let cancel = self.cancel.clone();
+            let clock = Arc::clone(&self.clock);
             self.spawner.spawn(async move {
-                Self::run_monitor(sonos, stream_registry, emitter, rx, cancel).await;
+                Self::run_monitor(sonos, stream_registry, emitter, rx, cancel, clock).await;
             });
         }
     }
 
     /// Starts monitoring latency for a stream/speaker pair.
     ///
-    /// Call this when playback starts on a speaker.
+    /// Call this when playback starts on a speaker and video sync is enabled.
+    /// Position updates are pushed as well.
     pub async fn start_monitoring(&self, stream_id: &str, speaker_ip: &str) {
+        self.send_start(stream_id, speaker_ip, true).await;
+    }
+
+    /// Starts pushing position updates for a stream/speaker pair, without
+    /// measuring latency.
+    ///
+    /// Call this when playback starts on a speaker and video sync is disabled.
+    pub async fn start_position_updates(&self, stream_id: &str, speaker_ip: &str) {
+        self.send_start(stream_id, speaker_ip, false).await;
+    }
+
+    async fn send_start(&self, stream_id: &str, speaker_ip: &str, measure_latency: bool) {
         let _ = self
             .command_tx
             .send(MonitorCommand::Start {
                 stream_id: stream_id.to_string(),
                 speaker_ip: speaker_ip.to_string(),
+                measure_latency,
             })
             .await;
     }
@@ -449,6 +592,22 @@ impl LatencyMonitor {
             .await;
     }
 
+    /// Stops monitoring every stream/speaker pair not in `live`.
+    ///
+    /// Returns how many pairs were dropped (0 if the monitor isn't running).
+    pub async fn retain_sessions(&self, live: HashSet<(String, String)>) -> usize {
+        let (reply, rx) = oneshot::channel();
+        if self
+            .command_tx
+            .send(MonitorCommand::Retain { live, reply })
+            .await
+            .is_err()
+        {
+            return 0;
+        }
+        rx.await.unwrap_or(0)
+    }
+
     /// Background task that performs the actual monitoring.
     async fn run_monitor(
         sonos: Arc<dyn SonosPlayback>,
@@ -456,13 +615,15 @@ impl LatencyMonitor {
         emitter: Arc<dyn EventEmitter>,
         mut command_rx: mpsc::Receiver<MonitorCommand>,
         cancel: CancellationToken,
+        clock: Arc<dyn Clock>,
     ) {
         let sessions: DashMap<SessionKey, LatencySession> = DashMap::new();
 
-        // Use interval instead of sleep to reduce timer allocations and prevent drift.
-        // Delay mode skips missed ticks rather than bursting to catch up.
-        let mut poll_interval = tokio::time::interval(Duration::from_millis(POLL_INTERVAL_MS));
-        poll_interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        // The next poll is scheduled as soon as one fires, so slow position
+        // queries don't push the schedule back; a poll that overruns the
+        // interval skips the missed tick rather than bursting to catch up.
+        let poll_period = Duration::from_millis(POLL_INTERVAL_MS);
+        let mut next_poll = clock.sleep(poll_period);
 
         log::info!("[LatencyMonitor] Background task started");
 
@@ -475,14 +636,17 @@ impl LatencyMonitor {
 
                 Some(cmd) = command_rx.recv() => {
                     match cmd {
-                        MonitorCommand::Start { stream_id, speaker_ip } => {
+                        MonitorCommand::Start { stream_id, speaker_ip, measure_latency } => {
                             let key = (stream_id.clone(), speaker_ip.clone());
-                            if !sessions.contains_key(&key) {
+                            if let Some(mut session) = sessions.get_mut(&key) {
+                                // Video sync enabled after playback started
+                                session.measure_latency |= measure_latency;
+                            } else {
                                 log::info!(
-                                    "[LatencyMonitor] Starting monitoring: stream={}, speaker={}",
-                                    stream_id, speaker_ip
+                                    "[LatencyMonitor] Starting monitoring: stream={}, speaker={}, latency={}",
+                                    stream_id, speaker_ip, measure_latency
                                 );
-                                sessions.insert(key, LatencySession::new());
+                                sessions.insert(key, LatencySession::new(measure_latency));
                             }
                         }
                         MonitorCommand::StopSpeaker { stream_id, speaker_ip } => {
@@ -501,10 +665,16 @@ impl LatencyMonitor {
                                 stream_id
                             );
                         }
+                        MonitorCommand::Retain { live, reply } => {
+                            let before = sessions.len();
+                            sessions.retain(|k, _| live.contains(k));
+                            let _ = reply.send(before - sessions.len());
+                        }
                     }
                 }
 
-                _ = poll_interval.tick() => {
+                _ = &mut next_poll => {
+                    next_poll = clock.sleep(poll_period);
                     // Poll all active sessions, collecting orphaned ones for cleanup.
                     // Sessions become orphaned when StreamGuard::drop removes the stream
                     // without calling stop_stream (e.g., WS handler panic/unexpected exit).
@@ -515,6 +685,9 @@ impl LatencyMonitor {
                         // Extract key before mutable borrow to satisfy borrow checker
                         let (stream_id, speaker_ip) = entry.key().clone();
                         let session = entry.value_mut();
+                        if !session.should_poll(clock.now()) {
+                            continue;
+                        }
 
                         // Get stream for timing info
                         let stream = match stream_registry.get_stream(&stream_id) {
@@ -538,18 +711,18 @@ impl LatencyMonitor {
                         };
 
                         // Sync epoch - skip if no epoch yet, emit stale if stale
-                        let epoch = match session.sync_epoch(&stream.timing, speaker_ip_addr) {
+                        let epoch = match session.sync_epoch(&stream.timing, speaker_ip_addr, clock.now()) {
                             EpochStatus::Valid(e) => e,
                             EpochStatus::NoEpoch => continue, // Sonos hasn't started consuming yet
                             EpochStatus::Stale => {
                                 // Emit stale event once per stale transition
-                                if session.should_emit_stale() {
+                                if session.measure_latency && session.should_emit_stale() {
                                     let epoch_id = session.last_epoch_id();
                                     let event = LatencyEvent::Stale {
                                         stream_id: stream_id.clone(),
                                         speaker_ip: speaker_ip.clone(),
                                         epoch_id,
-                                        timestamp: now_millis(),
+                                        timestamp: clock.now_millis(),
                                     };
                                     emitter.emit_latency(event);
                                     session.mark_stale_emitted();
@@ -565,10 +738,12 @@ impl LatencyMonitor {
                         };
 
                         // Get time elapsed since audio epoch (T0 for this Sonos connection)
-                        let stream_elapsed_ms = epoch.audio_epoch.elapsed().as_millis() as u64;
+                        let start = clock.now();
+                        let stream_elapsed_ms =
+                            start.saturating_duration_since(epoch.audio_epoch).as_millis() as u64;
 
                         // Query Sonos position with RTT measurement
-                        let start = Instant::now();
+                        session.last_poll = Some(start);
                         let position = match sonos.get_position_info(&speaker_ip).await {
                             Ok(p) => p,
                             Err(e) => {
@@ -579,7 +754,8 @@ impl LatencyMonitor {
                                 continue;
                             }
                         };
-                        let rtt = start.elapsed();
+                        let now = clock.now();
+                        let rtt = now.saturating_duration_since(start);
                         let rtt_ms = rtt.as_millis() as u32;
 
                         // Verify Sonos is playing OUR stream (not previous content)
@@ -601,6 +777,23 @@ impl LatencyMonitor {
                             stream_id
                         );
 
+                        if session.take_position_due(now) {
+                            emitter.emit_stream(StreamEvent::Position {
+                                stream_id: stream_id.clone(),
+                                speaker_ip: speaker_ip.clone(),
+                                epoch_id: epoch.id,
+                                rel_time_ms: position.rel_time_ms,
+                                track_duration_ms: position.track_duration_ms,
+                                stream_elapsed_ms,
+                                timestamp: clock.now_millis(),
+                            });
+                        }
+
+                        if !session.measure_latency {
+                            session.record_valid_position(now);
+                            continue;
+                        }
+
                         // Calculate absolute latency (handles track restarts via offset)
                         let latency_ms = session.calculate_latency(
                             stream_elapsed_ms,
@@ -609,12 +802,12 @@ impl LatencyMonitor {
                         );
 
                         // Record that we received valid position info (for stale detection)
-                        session.record_valid_position();
+                        session.record_valid_position(now);
 
                         session.record_latency(latency_ms);
 
                         // Emit update if appropriate
-                        if session.should_emit() {
+                        if session.should_emit(now) {
                             let event = LatencyEvent::Updated {
                                 stream_id: stream_id.clone(),
                                 speaker_ip: speaker_ip.clone(),
@@ -622,10 +815,10 @@ impl LatencyMonitor {
                                 latency_ms: session.latency_ms(),
                                 jitter_ms: session.jitter_ms(),
                                 confidence: session.confidence(),
-                                timestamp: now_millis(),
+                                timestamp: clock.now_millis(),
                             };
                             emitter.emit_latency(event);
-                            session.mark_emitted();
+                            session.mark_emitted(now);
 
                             log::debug!(
                                 "[LatencyMonitor] stream={}, speaker={}: latency={}ms, jitter={}ms, confidence={:.2}",
@@ -656,3 +849,109 @@ impl LatencyMonitor {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::MockClock;
+
+    const SPEAKER: &str = "192.168.1.50";
+
+    fn timing_with_epoch(clock: &MockClock) -> StreamTiming {
+        let timing = StreamTiming::new();
+        let now = clock.now();
+        timing.start_new_epoch(Some(now), now, SPEAKER.parse().unwrap());
+        timing
+    }
+
+    #[test]
+    fn epoch_goes_stale_without_valid_positions() {
+        let clock = MockClock::new();
+        let timing = timing_with_epoch(&clock);
+        let ip = SPEAKER.parse().unwrap();
+        let mut session = LatencySession::new(true);
+
+        session.record_valid_position(clock.now());
+        clock.advance(Duration::from_secs(STALE_EPOCH_TIMEOUT_SECS));
+        assert!(matches!(
+            session.sync_epoch(&timing, ip, clock.now()),
+            EpochStatus::Valid(_)
+        ));
+
+        clock.advance(Duration::from_secs(1));
+        assert!(matches!(
+            session.sync_epoch(&timing, ip, clock.now()),
+            EpochStatus::Stale
+        ));
+
+        session.record_valid_position(clock.now());
+        assert!(matches!(
+            session.sync_epoch(&timing, ip, clock.now()),
+            EpochStatus::Valid(_)
+        ));
+    }
+
+    #[test]
+    fn updates_are_rate_limited_to_one_per_second() {
+        let clock = MockClock::new();
+        let mut session = LatencySession::new(true);
+        for _ in 0..MIN_SAMPLES_FOR_CONFIDENCE {
+            session.record_latency(250);
+        }
+
+        assert!(session.should_emit(clock.now()));
+        session.mark_emitted(clock.now());
+        session.record_latency(400);
+        clock.advance(Duration::from_millis(999));
+        assert!(!session.should_emit(clock.now()));
+        clock.advance(Duration::from_millis(1));
+        assert!(session.should_emit(clock.now()));
+    }
+
+    #[test]
+    fn unchanged_latency_is_only_sent_as_keepalive() {
+        let clock = MockClock::new();
+        let mut session = LatencySession::new(true);
+        for _ in 0..MIN_SAMPLES_FOR_CONFIDENCE {
+            session.record_latency(250);
+        }
+        session.mark_emitted(clock.now());
+
+        // A few milliseconds of drift isn't worth an update
+        session.record_latency(255);
+        clock.advance(Duration::from_secs(2));
+        assert!(!session.should_emit(clock.now()));
+
+        clock.advance(Duration::from_secs(KEEPALIVE_INTERVAL_SECS - 2));
+        assert!(session.should_emit(clock.now()));
+        session.mark_emitted(clock.now());
+
+        // A real change goes out at the next rate-limited slot
+        for _ in 0..3 {
+            session.record_latency(400);
+        }
+        clock.advance(Duration::from_millis(MIN_EMIT_INTERVAL_MS));
+        assert!(session.should_emit(clock.now()));
+    }
+
+    #[test]
+    fn position_only_sessions_poll_at_the_position_rate() {
+        let clock = MockClock::new();
+        let mut session = LatencySession::new(false);
+        assert!(session.should_poll(clock.now()));
+
+        session.last_poll = Some(clock.now());
+        assert!(session.take_position_due(clock.now()));
+        clock.advance(Duration::from_millis(POLL_INTERVAL_MS));
+        assert!(!session.should_poll(clock.now()));
+        assert!(!session.take_position_due(clock.now()));
+
+        // A tick arriving slightly early still counts as the next second
+        clock.advance(Duration::from_millis(POLL_INTERVAL_MS - 10));
+        assert!(session.should_poll(clock.now()));
+        assert!(session.take_position_due(clock.now()));
+
+        session.measure_latency = true;
+        assert!(session.should_poll(clock.now()));
+    }
+}
@@ -0,0 +1,196 @@
+//! Stops streams that have lost all their listeners.
+//!
+//! A stream normally ends when its source does. If the speakers playing it
+//! disconnect and never come back (powered off, network change, someone
+//! grouped them elsewhere), the tab or ingest keeps feeding a stream nobody
+//! hears. After [`StreamingConfig::idle_listener_secs`] without an HTTP
+//! listener the stream is stopped, announced with a
+//! [`StreamEvent::ListenersLost`](crate::events::StreamEvent::ListenersLost)
+//! event ahead of the usual `Ended`.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use tokio_util::sync::CancellationToken;
+
+use crate::runtime::TokioSpawner;
+use crate::services::StreamCoordinator;
+use crate::state::StreamingConfig;
+
+/// Interval between listener checks.
+const CHECK_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Tracks how long each stream has gone without listeners.
+#[derive(Debug, Default)]
+struct IdleTracker {
+    /// When each currently unheard stream was first seen without listeners.
+    idle_since: HashMap<String, Instant>,
+}
+
+impl IdleTracker {
+    /// Records a check of every live stream as `(id, listener count)` and
+    /// returns the streams idle for at least `timeout`, with their idle time.
+    ///
+    /// Streams that are gone are forgotten; a stream that is reported as
+    /// expired is forgotten too, so it is only returned once.
+    fn observe(
+        &mut self,
+        streams: impl IntoIterator<Item = (String, usize)>,
+        now: Instant,
+        timeout: Duration,
+    ) -> Vec<(String, Duration)> {
+        let mut still_idle = HashMap::new();
+        let mut expired = Vec::new();
+
+        for (id, listeners) in streams {
+            if listeners > 0 {
+                continue;
+            }
+            let since = self.idle_since.get(&id).copied().unwrap_or(now);
+            let idle = now.saturating_duration_since(since);
+            if idle >= timeout {
+                expired.push((id, idle));
+            } else {
+                still_idle.insert(id, since);
+            }
+        }
+
+        self.idle_since = still_idle;
+        expired
+    }
+}
+
+/// Background service stopping streams without listeners.
+pub struct ListenerWatchdog {
+    /// Time without listeners after which a stream is stopped.
+    timeout: Duration,
+    stream_coordinator: Arc<StreamCoordinator>,
+    cancel: CancellationToken,
+    spawner: TokioSpawner,
+    /// Guards against starting the check task twice.
+    started: AtomicBool,
+}
+
+impl ListenerWatchdog {
+    /// Creates the watchdog, or `None` if idle streams are kept forever.
+    ///
+    /// Note: Call `start()` to spawn the background check task.
+    pub fn from_config(
+        config: &StreamingConfig,
+        stream_coordinator: Arc<StreamCoordinator>,
+        cancel: CancellationToken,
+        spawner: TokioSpawner,
+    ) -> Option<Self> {
+        let timeout = Duration::from_secs(config.idle_listener_secs?);
+        Some(Self {
+            timeout,
+            stream_coordinator,
+            cancel,
+            spawner,
+            started: AtomicBool::new(false),
+        })
+    }
+
+    /// Starts the background check task.
+    ///
+    /// Can only be called once; subsequent calls are no-ops.
+    pub fn start(&self) {
+        if self.started.swap(true, Ordering::SeqCst) {
+            return;
+        }
+
+        let timeout = self.timeout;
+        let coordinator = Arc::clone(&self.stream_coordinator);
+        let cancel = self.cancel.clone();
+
+        self.spawner.spawn(async move {
+            let mut tracker = IdleTracker::default();
+            let mut interval = tokio::time::interval(CHECK_INTERVAL.min(timeout));
+
+            loop {
+                tokio::select! {
+                    _ = cancel.cancelled() => {
+                        log::debug!("[ListenerWatchdog] Shutting down");
+                        break;
+                    }
+                    _ = interval.tick() => {}
+                }
+
+                let registry = coordinator.stream_registry();
+                let streams = registry
+                    .list_stream_ids()
+                    .into_iter()
+                    .filter(|id| !coordinator.has_suspended_playback(id))
+                    .filter_map(|id| {
+                        let listeners = registry.get_stream(&id)?.listener_count();
+                        Some((id, listeners))
+                    });
+
+                for (stream_id, idle) in tracker.observe(streams, Instant::now(), timeout) {
+                    coordinator.end_idle_stream(&stream_id, idle).await;
+                }
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TIMEOUT: Duration = Duration::from_secs(60);
+
+    fn stream(id: &str, listeners: usize) -> (String, usize) {
+        (id.to_string(), listeners)
+    }
+
+    #[test]
+    fn expires_streams_idle_past_the_timeout_once() {
+        let mut tracker = IdleTracker::default();
+        let start = Instant::now();
+
+        assert!(tracker.observe([stream("a", 0)], start, TIMEOUT).is_empty());
+        assert!(tracker
+            .observe([stream("a", 0)], start + TIMEOUT / 2, TIMEOUT)
+            .is_empty());
+
+        let expired = tracker.observe([stream("a", 0)], start + TIMEOUT, TIMEOUT);
+        assert_eq!(expired, vec![("a".to_string(), TIMEOUT)]);
+
+        // Already reported; a stream still around starts a fresh idle period
+        assert!(tracker
+            .observe([stream("a", 0)], start + TIMEOUT * 2, TIMEOUT)
+            .is_empty());
+    }
+
+    #[test]
+    fn a_listener_resets_the_idle_time() {
+        let mut tracker = IdleTracker::default();
+        let start = Instant::now();
+
+        tracker.observe([stream("a", 0)], start, TIMEOUT);
+        tracker.observe([stream("a", 1)], start + TIMEOUT / 2, TIMEOUT);
+
+        assert!(tracker
+            .observe([stream("a", 0)], start + TIMEOUT, TIMEOUT)
+            .is_empty());
+    }
+
+    #[test]
+    fn streams_with_listeners_are_never_expired() {
+        let mut tracker = IdleTracker::default();
+        let start = Instant::now();
+
+        tracker.observe([stream("a", 2), stream("b", 0)], start, TIMEOUT);
+        let expired = tracker.observe(
+            [stream("a", 2), stream("b", 0)],
+            start + TIMEOUT * 2,
+            TIMEOUT,
+        );
+
+        assert_eq!(expired.len(), 1);
+        assert_eq!(expired[0].0, "b");
+    }
+}
@@ -3,17 +3,44 @@
 //! This module contains the business logic services that orchestrate
 //! between the API layer and infrastructure (sonos/, stream/).
 
+pub mod action_batch;
 pub mod discovery_service;
 pub mod gena_event_processor;
+pub mod handoff;
+#[cfg(debug_assertions)]
+pub(crate) mod invariants;
 pub mod latency_monitor;
+pub mod listener_watchdog;
+pub mod origin_approvals;
 pub mod playback_session_store;
+pub mod resource_monitor;
+pub mod resource_reaper;
+pub mod rtp_receiver;
+pub mod script_hooks;
+pub mod scrobble_service;
+pub mod speaker_cache;
+pub mod state_snapshots;
+pub mod stats_recorder;
+pub mod storage_monitor;
 pub mod stream_coordinator;
 pub(crate) mod sync_group_manager;
 pub mod topology_monitor;
 pub(crate) mod volume_router;
 
 pub use discovery_service::DiscoveryService;
+pub use gena_event_processor::GenaNotifyStats;
 pub use latency_monitor::LatencyMonitor;
-pub use playback_session_store::{GroupRole, PlaybackResult, PlaybackSession};
+pub use listener_watchdog::ListenerWatchdog;
+pub use origin_approvals::{OriginApprovalState, OriginApprovals, PendingOrigin};
+pub use playback_session_store::{GroupRole, PlaybackResult, PlaybackSession, PlaybackSessionInfo};
+pub use resource_monitor::{ResourceMonitor, ResourceStats};
+pub use resource_reaper::{ReconciliationReport, ResourceReaper};
+pub use rtp_receiver::{RtpIngestConfig, RtpReceiver};
+pub use script_hooks::ScriptHooks;
+pub use scrobble_service::ScrobbleService;
+pub use speaker_cache::{CachedSpeaker, SpeakerCache};
+pub use state_snapshots::StateSnapshotter;
+pub use stats_recorder::StatsRecorder;
+pub use storage_monitor::StorageMonitor;
 pub use stream_coordinator::{CaptureStreamSession, StreamCoordinator};
 pub use topology_monitor::{TopologyMonitor, TopologyMonitorConfig};
@@ -0,0 +1,393 @@
+//! Approval of browser extension origins.
+//!
+//! Every unpacked or sideloaded build of the extension gets its own ID, and
+//! Firefox assigns a random one per installation, so extension origins can't
+//! all be listed up front. When an extension the
+//! server doesn't trust yet calls the API, the request is refused with an
+//! `origin_approval_required` error and the origin is queued for approval.
+//! The desktop app learns about it through an `originApprovalRequested`
+//! network event and asks the user; headless servers list pending origins at
+//! `GET /api/origins`.
+//!
+//! Approved origins are trusted alongside the configured [`OriginPolicy`] and
+//! persisted to `trusted_origins.json` in the app data directory. Denials
+//! last until restart, so a denied extension stops prompting but can be
+//! approved later.
+
+use std::collections::{BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use axum::http::{HeaderValue, Method};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use tower_http::cors::{AllowOrigin, CorsLayer};
+
+use crate::api::cors::{cors_layer, ExtensionBrowser};
+use crate::api::OriginPolicy;
+use crate::error::{ThaumicError, ThaumicResult};
+use crate::events::{EventEmitter, NetworkEvent};
+use crate::storage;
+use crate::utils::now_millis;
+
+const APPROVALS_FILE: &str = "trusted_origins.json";
+
+/// Pending origins kept at most; the least recently seen is dropped beyond it.
+const MAX_PENDING: usize = 32;
+
+/// An extension origin waiting for the user's decision.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PendingOrigin {
+    pub origin: String,
+    /// Browser the extension runs in.
+    pub browser: ExtensionBrowser,
+    /// Unix timestamp (ms) of the first refused request.
+    pub first_seen: u64,
+    /// Unix timestamp (ms) of the latest refused request.
+    pub last_seen: u64,
+    /// Requests refused so far.
+    pub attempts: u32,
+}
+
+/// Approved, pending and denied extension origins.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OriginApprovalState {
+    pub approved: Vec<String>,
+    pub pending: Vec<PendingOrigin>,
+    pub denied: Vec<String>,
+}
+
+/// Contents of `trusted_origins.json`.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct TrustedOrigins {
+    origins: BTreeSet<String>,
+}
+
+#[derive(Default)]
+struct Inner {
+    approved: BTreeSet<String>,
+    pending: HashMap<String, PendingOrigin>,
+    denied: BTreeSet<String>,
+}
+
+/// Tracks which browser extensions the user has approved.
+pub struct OriginApprovals {
+    policy: OriginPolicy,
+    inner: Mutex<Inner>,
+    data_dir: Mutex<Option<PathBuf>>,
+    emitter: Arc<dyn EventEmitter>,
+}
+
+impl OriginApprovals {
+    /// Creates the tracker on top of the configured `policy`.
+    pub fn new(policy: OriginPolicy, emitter: Arc<dyn EventEmitter>) -> Self {
+        Self {
+            policy,
+            inner: Mutex::new(Inner::default()),
+            data_dir: Mutex::new(None),
+            emitter,
+        }
+    }
+
+    /// Sets the directory approvals are persisted to and loads saved ones.
+    pub fn set_data_dir(&self, dir: impl AsRef<Path>) {
+        let dir = dir.as_ref().to_path_buf();
+        let loaded = load(&dir);
+        self.inner.lock().approved.extend(loaded.origins);
+        *self.data_dir.lock() = Some(dir);
+    }
+
+    /// Whether requests from `origin` are trusted.
+    pub fn allows(&self, origin: &str) -> bool {
+        self.policy.allows(origin)
+            || self
+                .inner
+                .lock()
+                .approved
+                .contains(&origin.to_ascii_lowercase())
+    }
+
+    /// Checks a request's `Origin` header.
+    ///
+    /// Trusted origins pass, as do non-extension origins, which are left to
+    /// CORS. An unknown extension is queued for approval (announced the first
+    /// time it is seen) and refused with
+    /// [`ThaumicError::OriginApprovalRequired`] until the user decides.
+    /// Extension origins whose ID doesn't match their browser's format are
+    /// refused outright.
+    pub fn check(&self, origin: &str) -> ThaumicResult<()> {
+        let Some(browser) = ExtensionBrowser::from_origin(origin) else {
+            return Ok(());
+        };
+        if self.allows(origin) {
+            return Ok(());
+        }
+        if !browser.is_valid_origin(origin) {
+            return Err(ThaumicError::Forbidden(format!(
+                "Malformed extension origin: {}",
+                origin
+            )));
+        }
+        let origin = origin.to_ascii_lowercase();
+        let now = now_millis();
+
+        let first_request = {
+            let mut inner = self.inner.lock();
+            if inner.denied.contains(&origin) {
+                return Err(ThaumicError::Forbidden(format!(
+                    "Extension {} was denied access",
+                    origin
+                )));
+            }
+            let first_request = !inner.pending.contains_key(&origin);
+            if first_request && inner.pending.len() >= MAX_PENDING {
+                let oldest = inner
+                    .pending
+                    .values()
+                    .min_by_key(|p| p.last_seen)
+                    .map(|p| p.origin.clone());
+                if let Some(oldest) = oldest {
+                    inner.pending.remove(&oldest);
+                }
+            }
+            let pending = inner
+                .pending
+                .entry(origin.clone())
+                .or_insert_with(|| PendingOrigin {
+                    origin: origin.clone(),
+                    browser,
+                    first_seen: now,
+                    last_seen: now,
+                    attempts: 0,
+                });
+            pending.last_seen = now;
+            pending.attempts = pending.attempts.saturating_add(1);
+            first_request
+        };
+
+        if first_request {
+            log::info!("[Origins] {} is waiting for approval", origin);
+            self.emitter
+                .emit_network(NetworkEvent::OriginApprovalRequested {
+                    origin: origin.clone(),
+                    timestamp: now,
+                });
+        }
+        Err(ThaumicError::OriginApprovalRequired(origin))
+    }
+
+    /// Returns approved, pending (oldest first) and denied origins.
+    pub fn state(&self) -> OriginApprovalState {
+        let inner = self.inner.lock();
+        let mut pending: Vec<PendingOrigin> = inner.pending.values().cloned().collect();
+        pending.sort_by_key(|p| p.first_seen);
+        OriginApprovalState {
+            approved: inner.approved.iter().cloned().collect(),
+            pending,
+            denied: inner.denied.iter().cloned().collect(),
+        }
+    }
+
+    /// Trusts `origin` from now on and persists the decision.
+    pub fn approve(&self, origin: &str) -> ThaumicResult<()> {
+        let origin = validate(origin)?;
+        {
+            let mut inner = self.inner.lock();
+            inner.pending.remove(&origin);
+            inner.denied.remove(&origin);
+            if !inner.approved.insert(origin.clone()) {
+                return Ok(());
+            }
+        }
+        log::info!("[Origins] Approved {}", origin);
+        self.save()
+    }
+
+    /// Refuses `origin` without asking again until restart.
+    pub fn deny(&self, origin: &str) -> ThaumicResult<()> {
+        let origin = validate(origin)?;
+        let was_approved = {
+            let mut inner = self.inner.lock();
+            inner.pending.remove(&origin);
+            inner.denied.insert(origin.clone());
+            inner.approved.remove(&origin)
+        };
+        log::info!("[Origins] Denied {}", origin);
+        if was_approved {
+            self.save()?;
+        }
+        Ok(())
+    }
+
+    /// A CORS layer allowing the configured policy plus approved origins.
+    pub fn cors_layer(self: &Arc<Self>, methods: &[Method]) -> CorsLayer {
+        let approvals = Arc::clone(self);
+        cors_layer(
+            AllowOrigin::predicate(move |origin: &HeaderValue, _| {
+                origin.to_str().is_ok_and(|origin| approvals.allows(origin))
+            }),
+            methods,
+        )
+    }
+
+    /// Writes approved origins to disk, if a data directory is set.
+    fn save(&self) -> ThaumicResult<()> {
+        let Some(dir) = self.data_dir.lock().clone() else {
+            return Ok(());
+        };
+        let trusted = TrustedOrigins {
+            origins: self.inner.lock().approved.clone(),
+        };
+        save(&dir, &trusted).map_err(|e| ThaumicError::SaveFailed(e.to_string()))
+    }
+}
+
+/// Checks that `origin` is a single, well-formed extension origin and
+/// normalizes it.
+fn validate(origin: &str) -> ThaumicResult<String> {
+    let origin = origin.trim().to_ascii_lowercase();
+    match ExtensionBrowser::from_origin(&origin) {
+        Some(browser) if browser.is_valid_origin(&origin) => Ok(origin),
+        _ => Err(ThaumicError::InvalidRequest(format!(
+            "Not a Chrome, Edge or Firefox extension origin: {}",
+            origin
+        ))),
+    }
+}
+
+fn load(dir: &Path) -> TrustedOrigins {
+    match std::fs::read_to_string(dir.join(APPROVALS_FILE)) {
+        Ok(contents) => serde_json::from_str(&contents).unwrap_or_else(|e| {
+            log::warn!("[Origins] Ignoring unreadable {}: {}", APPROVALS_FILE, e);
+            TrustedOrigins::default()
+        }),
+        Err(_) => TrustedOrigins::default(),
+    }
+}
+
+/// Writes approvals atomically (temp file + rename), keeping them in memory
+/// only while the data directory is full or read-only.
+fn save(dir: &Path, trusted: &TrustedOrigins) -> std::io::Result<()> {
+    let contents = serde_json::to_string_pretty(trusted)?;
+    storage::tolerate(storage::write_atomic(dir, APPROVALS_FILE, contents))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::{
+        LatencyEvent, ResourceEvent, SonosEvent, StateEvent, StreamEvent, TopologyEvent,
+    };
+
+    const EXTENSION: &str = "chrome-extension://abcdefghijklmnopabcdefghijklmnop";
+
+    #[derive(Default)]
+    struct RecordingEmitter {
+        requested: Mutex<Vec<String>>,
+    }
+
+    impl EventEmitter for RecordingEmitter {
+        fn emit_stream(&self, _event: StreamEvent) {}
+        fn emit_sonos(&self, _event: SonosEvent) {}
+        fn emit_network(&self, event: NetworkEvent) {
+            if let NetworkEvent::OriginApprovalRequested { origin, .. } = event {
+                self.requested.lock().push(origin);
+            }
+        }
+        fn emit_topology(&self, _event: TopologyEvent) {}
+        fn emit_latency(&self, _event: LatencyEvent) {}
+        fn emit_resource(&self, _event: ResourceEvent) {}
+        fn emit_state(&self, _event: StateEvent) {}
+    }
+
+    fn tracker() -> (OriginApprovals, Arc<RecordingEmitter>) {
+        let emitter = Arc::new(RecordingEmitter::default());
+        let policy = OriginPolicy::trusted_with(&[]).unwrap();
+        (
+            OriginApprovals::new(policy, Arc::clone(&emitter) as Arc<dyn EventEmitter>),
+            emitter,
+        )
+    }
+
+    #[test]
+    fn unknown_extension_is_challenged_once() {
+        let (approvals, emitter) = tracker();
+
+        for _ in 0..3 {
+            assert!(matches!(
+                approvals.check(EXTENSION),
+                Err(ThaumicError::OriginApprovalRequired(_))
+            ));
+        }
+        assert_eq!(*emitter.requested.lock(), vec![EXTENSION.to_string()]);
+        assert_eq!(approvals.state().pending[0].attempts, 3);
+
+        // Web pages are left to CORS
+        assert!(approvals.check("https://example.com").is_ok());
+        assert!(approvals.check("http://localhost:5173").is_ok());
+    }
+
+    #[test]
+    fn approval_and_denial_settle_pending_origins() {
+        let (approvals, _) = tracker();
+        let other = "chrome-extension://ponmlkjihgfedcbaponmlkjihgfedcba";
+        let _ = approvals.check(EXTENSION);
+        let _ = approvals.check(other);
+
+        approvals.approve(EXTENSION).unwrap();
+        approvals.deny(other).unwrap();
+
+        assert!(approvals.check(EXTENSION).is_ok());
+        assert!(matches!(
+            approvals.check(other),
+            Err(ThaumicError::Forbidden(_))
+        ));
+        let state = approvals.state();
+        assert!(state.pending.is_empty());
+        assert_eq!(state.approved, vec![EXTENSION.to_string()]);
+
+        assert!(approvals.approve("https://example.com").is_err());
+        assert!(approvals.approve("chrome-extension://*").is_err());
+    }
+
+    #[test]
+    fn firefox_extensions_are_challenged_by_scheme() {
+        let (approvals, emitter) = tracker();
+        let firefox = "moz-extension://0b4f7a52-6c8e-4d7a-9a8e-3f1c2d5e6b7a";
+
+        assert!(matches!(
+            approvals.check(firefox),
+            Err(ThaumicError::OriginApprovalRequired(_))
+        ));
+        assert_eq!(
+            approvals.state().pending[0].browser,
+            ExtensionBrowser::Firefox
+        );
+        approvals.approve(firefox).unwrap();
+        assert!(approvals.check(firefox).is_ok());
+
+        // A Chromium-style ID under the Firefox scheme is never queued
+        assert!(matches!(
+            approvals.check("moz-extension://abcdefghijklmnopabcdefghijklmnop"),
+            Err(ThaumicError::Forbidden(_))
+        ));
+        assert!(approvals
+            .approve("moz-extension://abcdefghijklmnopabcdefghijklmnop")
+            .is_err());
+        assert_eq!(emitter.requested.lock().len(), 1);
+    }
+
+    #[test]
+    fn approvals_persist_across_instances() {
+        let dir = tempfile::tempdir().unwrap();
+        let (approvals, _) = tracker();
+        approvals.set_data_dir(dir.path());
+        approvals.approve(EXTENSION).unwrap();
+
+        let (reloaded, _) = tracker();
+        reloaded.set_data_dir(dir.path());
+        assert!(reloaded.allows(EXTENSION));
+    }
+}
@@ -3,9 +3,12 @@
 //! Provides O(1) session lookups by (stream_id, speaker_ip) composite key
 //! and by speaker_ip alone via a secondary index.
 
+use std::collections::HashMap;
+
 use dashmap::DashMap;
 
-use crate::stream::AudioCodec;
+use crate::sonos::types::SpeakerMove;
+use crate::stream::{AudioCodec, StreamMetadata};
 
 /// Composite key for playback sessions: (stream_id, speaker_ip).
 /// Allows multiple speakers to receive the same stream (multi-group casting).
@@ -71,6 +74,25 @@ pub struct PlaybackSession {
     /// Used to restore group membership after streaming ends.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub original_coordinator_uuid: Option<String>,
+    /// For coordinators: the artwork URL playback started with, reused when
+    /// resuming. For slaves: None.
+    #[serde(skip)]
+    pub artwork_url: Option<String>,
+}
+
+/// A playback session together with what its stream is casting, for
+/// activity views.
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlaybackSessionInfo {
+    #[serde(flatten)]
+    pub session: PlaybackSession,
+    /// The stream's user-given label.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    /// The stream's current track and source page details.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<StreamMetadata>,
 }
 
 /// Result of starting playback on a single speaker.
@@ -144,6 +166,41 @@ impl PlaybackSessionStore {
         removed
     }
 
+    /// Moves the sessions of speakers that changed address to their new IP,
+    /// including the coordinator address slaves point at.
+    ///
+    /// Returns the number of sessions moved.
+    pub fn rekey_speakers(&self, moves: &[SpeakerMove]) -> usize {
+        let taken: Vec<PlaybackSession> = moves
+            .iter()
+            .filter_map(|m| {
+                let (_, key) = self.ip_index.remove(&m.old_ip)?;
+                let (_, mut session) = self.sessions.remove(&key)?;
+                session.speaker_ip = m.new_ip.clone();
+                Some(session)
+            })
+            .collect();
+        let moved = taken.len();
+        for session in taken {
+            self.insert(session);
+        }
+
+        let new_ips: HashMap<&str, &str> = moves
+            .iter()
+            .map(|m| (m.old_ip.as_str(), m.new_ip.as_str()))
+            .collect();
+        for mut session in self.sessions.iter_mut() {
+            let new_ip = session
+                .coordinator_ip
+                .as_deref()
+                .and_then(|ip| new_ips.get(ip));
+            if let Some(new_ip) = new_ip {
+                session.coordinator_ip = Some((*new_ip).to_string());
+            }
+        }
+        moved
+    }
+
     /// Gets a session by (stream_id, speaker_ip).
     pub fn get(&self, stream_id: &str, speaker_ip: &str) -> Option<PlaybackSession> {
         let key = PlaybackSessionKey::new(stream_id, speaker_ip);
@@ -338,6 +395,7 @@ mod tests {
             coordinator_ip: None,
             coordinator_uuid: Some("RINCON_XXX".to_string()),
             original_coordinator_uuid: None,
+            artwork_url: None,
         };
 
         assert_eq!(session.role, GroupRole::Coordinator);
@@ -356,6 +414,7 @@ mod tests {
             coordinator_ip: Some("192.168.1.100".to_string()),
             coordinator_uuid: Some("RINCON_XXX".to_string()),
             original_coordinator_uuid: None,
+            artwork_url: None,
         };
 
         assert_eq!(session.role, GroupRole::Slave);
@@ -374,6 +433,7 @@ mod tests {
             coordinator_ip: Some("192.168.1.100".to_string()),
             coordinator_uuid: Some("RINCON_STREAMING".to_string()),
             original_coordinator_uuid: Some("RINCON_ORIGINAL".to_string()),
+            artwork_url: None,
         };
 
         assert_eq!(session.role, GroupRole::Slave);
@@ -394,6 +454,7 @@ mod tests {
             coordinator_ip: None,
             coordinator_uuid: Some("RINCON_KITCHEN".to_string()),
             original_coordinator_uuid: Some("RINCON_LIVING".to_string()),
+            artwork_url: None,
         };
 
         assert_eq!(session.role, GroupRole::Coordinator);
@@ -414,6 +475,7 @@ mod tests {
             coordinator_ip: None,
             coordinator_uuid: Some("RINCON_XXX".to_string()),
             original_coordinator_uuid: None,
+            artwork_url: None,
         };
 
         let json = serde_json::to_value(&session).unwrap();
@@ -435,6 +497,7 @@ mod tests {
             coordinator_ip: Some("192.168.1.100".to_string()),
             coordinator_uuid: Some("RINCON_XXX".to_string()),
             original_coordinator_uuid: Some("RINCON_ORIGINAL".to_string()),
+            artwork_url: None,
         };
 
         let json = serde_json::to_value(&session).unwrap();
@@ -463,6 +526,7 @@ mod tests {
             },
             coordinator_uuid: Some("RINCON_100".to_string()),
             original_coordinator_uuid: None,
+            artwork_url: None,
         }
     }
 
@@ -659,4 +723,28 @@ mod tests {
         // Old session still accessible by key
         assert!(store.get("s1", "192.168.1.100").is_some());
     }
+
+    #[test]
+    fn rekey_speakers_follows_coordinator_to_new_address() {
+        let store = PlaybackSessionStore::new();
+        store.insert(make_session("s1", "192.168.1.100", GroupRole::Coordinator));
+        store.insert(make_session("s1", "192.168.1.101", GroupRole::Slave));
+
+        let moved = store.rekey_speakers(&[SpeakerMove {
+            uuid: "RINCON_100".to_string(),
+            old_ip: "192.168.1.100".to_string(),
+            new_ip: "192.168.1.150".to_string(),
+        }]);
+
+        assert_eq!(moved, 1);
+        assert!(store.get_by_speaker_ip("192.168.1.100").is_none());
+        let coordinator = store.get("s1", "192.168.1.150").unwrap();
+        assert_eq!(coordinator.speaker_ip, "192.168.1.150");
+        assert_eq!(
+            store.find_coordinator_ip_for_stream("s1").as_deref(),
+            Some("192.168.1.150")
+        );
+        let slave = store.get_by_speaker_ip("192.168.1.101").unwrap();
+        assert_eq!(slave.coordinator_ip.as_deref(), Some("192.168.1.150"));
+    }
 }
@@ -0,0 +1,345 @@
+//! Process resource self-monitoring.
+//!
+//! Periodically samples this process's CPU and memory usage plus host network
+//! throughput, and exposes the latest sample for stats displays. When CPU stays
+//! high while streams are active, a [`ResourceEvent::CpuOverload`] warning is
+//! emitted: encoding can no longer keep up in real time, so speakers are
+//! likely to stutter.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use parking_lot::RwLock;
+use serde::Serialize;
+use sysinfo::{Networks, Pid, ProcessRefreshKind, ProcessesToUpdate, System};
+use tokio_util::sync::CancellationToken;
+
+use crate::events::{EventEmitter, ResourceEvent};
+use crate::runtime::TokioSpawner;
+use crate::stream::StreamRegistry;
+use crate::utils::now_millis;
+
+/// Interval between resource samples.
+const SAMPLE_INTERVAL: Duration = Duration::from_secs(5);
+
+/// CPU usage (percent of total machine capacity) considered overloaded.
+const CPU_OVERLOAD_PERCENT: f32 = 85.0;
+
+/// CPU usage below which an overload is considered recovered (hysteresis).
+const CPU_RECOVERED_PERCENT: f32 = 70.0;
+
+/// Consecutive high samples required before warning (~15s at 5s sampling).
+const OVERLOAD_SAMPLES: u32 = 3;
+
+/// Latest resource usage sample.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResourceStats {
+    /// Process CPU usage as a percentage of total machine capacity (0-100).
+    pub cpu_percent: f32,
+    /// Process resident memory in bytes.
+    pub memory_bytes: u64,
+    /// Host network receive rate across non-loopback interfaces (bytes/sec).
+    pub network_rx_bytes_per_sec: u64,
+    /// Host network transmit rate across non-loopback interfaces (bytes/sec).
+    pub network_tx_bytes_per_sec: u64,
+}
+
+/// Overload state change produced by [`OverloadDetector`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum OverloadTransition {
+    Overloaded,
+    Recovered,
+}
+
+/// Tracks sustained high CPU with hysteresis.
+#[derive(Debug, Default)]
+struct OverloadDetector {
+    /// Consecutive samples at or above the overload threshold.
+    high_samples: u32,
+    /// Whether an overload warning is currently active.
+    overloaded: bool,
+}
+
+impl OverloadDetector {
+    /// Records a sample and returns a transition if the overload state changed.
+    ///
+    /// Only counts as overload while streams are active, since idle CPU spikes
+    /// (e.g., discovery) don't affect audio delivery.
+    fn observe(&mut self, cpu_percent: f32, active_streams: usize) -> Option<OverloadTransition> {
+        if active_streams > 0 && cpu_percent >= CPU_OVERLOAD_PERCENT {
+            self.high_samples = self.high_samples.saturating_add(1);
+        } else {
+            self.high_samples = 0;
+        }
+
+        if !self.overloaded && self.high_samples >= OVERLOAD_SAMPLES {
+            self.overloaded = true;
+            return Some(OverloadTransition::Overloaded);
+        }
+
+        if self.overloaded && (active_streams == 0 || cpu_percent < CPU_RECOVERED_PERCENT) {
+            self.overloaded = false;
+            return Some(OverloadTransition::Recovered);
+        }
+
+        None
+    }
+}
+
+/// Samples system probes and converts them into [`ResourceStats`].
+struct Sampler {
+    system: System,
+    networks: Networks,
+    pid: Option<Pid>,
+    cpu_count: f32,
+    last_sample: Instant,
+}
+
+impl Sampler {
+    fn new() -> Self {
+        let pid = sysinfo::get_current_pid()
+            .map_err(|e| log::warn!("[ResourceMonitor] Cannot resolve own PID: {}", e))
+            .ok();
+        let cpu_count = std::thread::available_parallelism()
+            .map(|n| n.get())
+            .unwrap_or(1) as f32;
+
+        let mut sampler = Self {
+            system: System::new(),
+            networks: Networks::new_with_refreshed_list(),
+            pid,
+            cpu_count,
+            last_sample: Instant::now(),
+        };
+        // Prime CPU accounting; the first reading after this is meaningful
+        sampler.refresh_process();
+        sampler
+    }
+
+    fn refresh_process(&mut self) {
+        if let Some(pid) = self.pid {
+            self.system.refresh_processes_specifics(
+                ProcessesToUpdate::Some(&[pid]),
+                true,
+                ProcessRefreshKind::nothing().with_cpu().with_memory(),
+            );
+        }
+    }
+
+    fn sample(&mut self) -> ResourceStats {
+        self.refresh_process();
+        self.networks.refresh(true);
+
+        let elapsed = self.last_sample.elapsed().as_secs_f64().max(f64::EPSILON);
+        self.last_sample = Instant::now();
+
+        let (cpu_percent, memory_bytes) = self
+            .pid
+            .and_then(|pid| self.system.process(pid))
+            .map(|p| ((p.cpu_usage() / self.cpu_count).min(100.0), p.memory()))
+            .unwrap_or_default();
+
+        let (rx, tx) = self
+            .networks
+            .iter()
+            .filter(|(name, _)| !is_loopback(name))
+            .fold((0u64, 0u64), |(rx, tx), (_, data)| {
+                (rx + data.received(), tx + data.transmitted())
+            });
+
+        ResourceStats {
+            cpu_percent,
+            memory_bytes,
+            network_rx_bytes_per_sec: (rx as f64 / elapsed) as u64,
+            network_tx_bytes_per_sec: (tx as f64 / elapsed) as u64,
+        }
+    }
+}
+
+/// Returns whether an interface name refers to a loopback adapter.
+fn is_loopback(name: &str) -> bool {
+    name.starts_with("lo") || name.contains("Loopback")
+}
+
+/// Background service sampling process resource usage.
+pub struct ResourceMonitor {
+    /// Latest sample (zeroed until the first sample completes).
+    stats: Arc<RwLock<ResourceStats>>,
+    /// Stream registry for the active stream count.
+    stream_registry: Arc<StreamRegistry>,
+    emitter: Arc<dyn EventEmitter>,
+    cancel: CancellationToken,
+    spawner: TokioSpawner,
+    /// Guards against starting the sampling task twice.
+    started: AtomicBool,
+}
+
+impl ResourceMonitor {
+    /// Creates a new ResourceMonitor.
+    ///
+    /// Note: Call `start()` to spawn the background sampling task.
+    ///
+    /// # Arguments
+    /// * `stream_registry` - Stream registry for the active stream count
+    /// * `emitter` - Event emitter for overload warnings
+    /// * `cancel` - Cancellation token for graceful shutdown
+    /// * `spawner` - Task spawner for background tasks
+    pub fn new(
+        stream_registry: Arc<StreamRegistry>,
+        emitter: Arc<dyn EventEmitter>,
+        cancel: CancellationToken,
+        spawner: TokioSpawner,
+    ) -> Self {
+        Self {
+            stats: Arc::new(RwLock::new(ResourceStats::default())),
+            stream_registry,
+            emitter,
+            cancel,
+            spawner,
+            started: AtomicBool::new(false),
+        }
+    }
+
+    /// Returns the most recent resource sample.
+    #[must_use]
+    pub fn stats(&self) -> ResourceStats {
+        *self.stats.read()
+    }
+
+    /// Starts the background sampling task.
+    ///
+    /// Can only be called once; subsequent calls are no-ops.
+    pub fn start(&self) {
+        if self.started.swap(true, Ordering::SeqCst) {
+            return;
+        }
+
+        let stats = Arc::clone(&self.stats);
+        let stream_registry = Arc::clone(&self.stream_registry);
+        let emitter = Arc::clone(&self.emitter);
+        let cancel = self.cancel.clone();
+
+        self.spawner.spawn(async move {
+            let mut sampler = Sampler::new();
+            let mut detector = OverloadDetector::default();
+            let mut interval = tokio::time::interval(SAMPLE_INTERVAL);
+            // The first tick fires immediately; skip it so CPU has a baseline
+            interval.tick().await;
+
+            loop {
+                tokio::select! {
+                    _ = cancel.cancelled() => {
+                        log::debug!("[ResourceMonitor] Shutting down");
+                        break;
+                    }
+                    _ = interval.tick() => {}
+                }
+
+                let sample = sampler.sample();
+                *stats.write() = sample;
+
+                let active_streams = stream_registry.stream_count();
+                match detector.observe(sample.cpu_percent, active_streams) {
+                    Some(OverloadTransition::Overloaded) => {
+                        log::warn!(
+                            "[ResourceMonitor] Sustained CPU {:.0}% with {} stream(s); audio may stutter",
+                            sample.cpu_percent,
+                            active_streams
+                        );
+                        emitter.emit_resource(ResourceEvent::CpuOverload {
+                            cpu_percent: sample.cpu_percent,
+                            sustained_secs: SAMPLE_INTERVAL.as_secs() * u64::from(OVERLOAD_SAMPLES),
+                            active_streams,
+                            timestamp: now_millis(),
+                        });
+                    }
+                    Some(OverloadTransition::Recovered) => {
+                        log::info!(
+                            "[ResourceMonitor] CPU recovered ({:.0}%)",
+                            sample.cpu_percent
+                        );
+                        emitter.emit_resource(ResourceEvent::CpuRecovered {
+                            cpu_percent: sample.cpu_percent,
+                            timestamp: now_millis(),
+                        });
+                    }
+                    None => {}
+                }
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn warns_only_after_sustained_high_cpu() {
+        let mut detector = OverloadDetector::default();
+        for _ in 1..OVERLOAD_SAMPLES {
+            assert_eq!(detector.observe(95.0, 1), None);
+        }
+        assert_eq!(
+            detector.observe(95.0, 1),
+            Some(OverloadTransition::Overloaded)
+        );
+        // No repeated warnings while still overloaded
+        assert_eq!(detector.observe(95.0, 1), None);
+    }
+
+    #[test]
+    fn ignores_high_cpu_without_streams() {
+        let mut detector = OverloadDetector::default();
+        for _ in 0..OVERLOAD_SAMPLES * 2 {
+            assert_eq!(detector.observe(99.0, 0), None);
+        }
+    }
+
+    #[test]
+    fn single_dip_resets_the_streak() {
+        let mut detector = OverloadDetector::default();
+        for _ in 1..OVERLOAD_SAMPLES {
+            detector.observe(95.0, 1);
+        }
+        detector.observe(50.0, 1);
+        assert_eq!(detector.observe(95.0, 1), None);
+    }
+
+    #[test]
+    fn recovers_below_hysteresis_threshold() {
+        let mut detector = OverloadDetector::default();
+        for _ in 0..OVERLOAD_SAMPLES {
+            detector.observe(95.0, 1);
+        }
+        // Between thresholds: still overloaded
+        assert_eq!(detector.observe(80.0, 1), None);
+        assert_eq!(
+            detector.observe(60.0, 1),
+            Some(OverloadTransition::Recovered)
+        );
+    }
+
+    #[test]
+    fn recovers_when_streams_end() {
+        let mut detector = OverloadDetector::default();
+        for _ in 0..OVERLOAD_SAMPLES {
+            detector.observe(95.0, 1);
+        }
+        assert_eq!(
+            detector.observe(95.0, 0),
+            Some(OverloadTransition::Recovered)
+        );
+    }
+
+    #[test]
+    fn loopback_interfaces_are_excluded() {
+        assert!(is_loopback("lo"));
+        assert!(is_loopback("lo0"));
+        assert!(is_loopback("Loopback Pseudo-Interface 1"));
+        assert!(!is_loopback("eth0"));
+        assert!(!is_loopback("en0"));
+    }
+}
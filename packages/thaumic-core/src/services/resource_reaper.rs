@@ -0,0 +1,361 @@
+//! Periodic audit of leaked resources.
+//!
+//! Streams, playback sessions, GENA subscriptions and latency monitors are
+//! created and torn down by different services. Normal teardown keeps them
+//! consistent, but a panicked task or a missed event can leave one behind.
+//! The reaper looks for such leftovers on an interval and cleans them up:
+//!
+//! - streams with no speakers, no listeners and no new audio since the
+//!   previous audit are removed
+//! - sessions whose stream is gone are dropped
+//! - playback subscriptions for speakers no longer in the topology are
+//!   cancelled
+//! - latency monitoring for sessions that no longer exist is stopped
+//!
+//! Each audit that repairs something logs a reconciliation report.
+
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use tokio_util::sync::CancellationToken;
+
+use crate::runtime::TokioSpawner;
+use crate::services::{LatencyMonitor, StreamCoordinator};
+use crate::sonos::gena::GenaSubscriptionManager;
+use crate::sonos::SonosService;
+use crate::state::SonosState;
+
+/// Interval between audits.
+const AUDIT_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Subscriptions tied to a speaker being in the topology.
+///
+/// ZoneGroupTopology is left alone: it may be held on any reachable device,
+/// including ones that never appear as group members.
+const SPEAKER_SERVICES: [SonosService; 3] = [
+    SonosService::AVTransport,
+    SonosService::GroupRenderingControl,
+    SonosService::RenderingControl,
+];
+
+/// What one audit found and repaired.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ReconciliationReport {
+    /// Streams removed for having no speakers, listeners or audio.
+    pub orphaned_streams: usize,
+    /// Playback sessions dropped because their stream was gone.
+    pub orphaned_sessions: usize,
+    /// Subscriptions cancelled for speakers that left the topology.
+    pub stale_subscriptions: usize,
+    /// Latency monitors stopped for sessions that no longer exist.
+    pub stale_latency_monitors: usize,
+}
+
+impl ReconciliationReport {
+    /// Returns whether the audit found nothing to repair.
+    #[must_use]
+    pub fn is_clean(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+impl fmt::Display for ReconciliationReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} orphaned stream(s), {} orphaned session(s), {} stale subscription(s), {} stale latency monitor(s)",
+            self.orphaned_streams,
+            self.orphaned_sessions,
+            self.stale_subscriptions,
+            self.stale_latency_monitors
+        )
+    }
+}
+
+/// A stream's state as seen by one audit.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct StreamSnapshot {
+    id: String,
+    /// Whether any speaker plays the stream or is suspended from it.
+    has_playback: bool,
+    listeners: usize,
+    last_frame_at: Option<Instant>,
+}
+
+/// Picks out streams that stayed unused across two audits.
+#[derive(Debug, Default)]
+struct OrphanTracker {
+    /// Unused streams from the previous audit, with their newest frame time.
+    suspects: HashMap<String, Option<Instant>>,
+}
+
+impl OrphanTracker {
+    /// Records an audit and returns the streams to remove.
+    ///
+    /// A stream is a suspect while nothing plays or pulls it, and an orphan
+    /// when it is still a suspect at the next audit without having received
+    /// any audio in between.
+    fn observe(&mut self, streams: impl IntoIterator<Item = StreamSnapshot>) -> Vec<String> {
+        let mut suspects = HashMap::new();
+        let mut orphans = Vec::new();
+
+        for stream in streams {
+            if stream.has_playback || stream.listeners > 0 {
+                continue;
+            }
+            if self.suspects.get(&stream.id) == Some(&stream.last_frame_at) {
+                orphans.push(stream.id);
+            } else {
+                suspects.insert(stream.id, stream.last_frame_at);
+            }
+        }
+
+        self.suspects = suspects;
+        orphans
+    }
+}
+
+/// Returns `(ip, service)` subscriptions held for speakers outside `known_ips`.
+fn stale_subscriptions(
+    subscribed: impl IntoIterator<Item = (String, SonosService)>,
+    known_ips: &HashSet<String>,
+) -> Vec<(String, SonosService)> {
+    subscribed
+        .into_iter()
+        .filter(|(ip, _)| !known_ips.contains(ip))
+        .collect()
+}
+
+/// Background service reconciling leaked resources.
+pub struct ResourceReaper {
+    stream_coordinator: Arc<StreamCoordinator>,
+    latency_monitor: Arc<LatencyMonitor>,
+    gena_manager: Arc<GenaSubscriptionManager>,
+    sonos_state: Arc<SonosState>,
+    cancel: CancellationToken,
+    spawner: TokioSpawner,
+    /// Guards against starting the audit task twice.
+    started: AtomicBool,
+}
+
+impl ResourceReaper {
+    /// Creates a new ResourceReaper.
+    ///
+    /// Note: Call `start()` to spawn the background audit task.
+    ///
+    /// # Arguments
+    /// * `stream_coordinator` - Owner of streams and playback sessions
+    /// * `latency_monitor` - Latency monitor to prune
+    /// * `gena_manager` - GENA subscriptions to prune
+    /// * `sonos_state` - Current topology, for the set of known speakers
+    /// * `cancel` - Cancellation token for graceful shutdown
+    /// * `spawner` - Task spawner for background tasks
+    pub fn new(
+        stream_coordinator: Arc<StreamCoordinator>,
+        latency_monitor: Arc<LatencyMonitor>,
+        gena_manager: Arc<GenaSubscriptionManager>,
+        sonos_state: Arc<SonosState>,
+        cancel: CancellationToken,
+        spawner: TokioSpawner,
+    ) -> Self {
+        Self {
+            stream_coordinator,
+            latency_monitor,
+            gena_manager,
+            sonos_state,
+            cancel,
+            spawner,
+            started: AtomicBool::new(false),
+        }
+    }
+
+    /// Starts the background audit task.
+    ///
+    /// Can only be called once; subsequent calls are no-ops.
+    pub fn start(self: &Arc<Self>) {
+        if self.started.swap(true, Ordering::SeqCst) {
+            return;
+        }
+
+        let reaper = Arc::clone(self);
+        self.spawner.spawn(async move {
+            let mut tracker = OrphanTracker::default();
+            let mut interval = tokio::time::interval(AUDIT_INTERVAL);
+            // The first tick fires immediately; nothing can have leaked yet
+            interval.tick().await;
+
+            loop {
+                tokio::select! {
+                    _ = reaper.cancel.cancelled() => {
+                        log::debug!("[ResourceReaper] Shutting down");
+                        break;
+                    }
+                    _ = interval.tick() => {}
+                }
+
+                let report = reaper.audit(&mut tracker).await;
+                if report.is_clean() {
+                    log::debug!("[ResourceReaper] Audit found nothing to reconcile");
+                } else {
+                    log::warn!("[ResourceReaper] Reconciled {}", report);
+                }
+            }
+        });
+    }
+
+    /// Runs one audit, repairing what it finds.
+    async fn audit(&self, tracker: &mut OrphanTracker) -> ReconciliationReport {
+        let mut report = ReconciliationReport::default();
+        let coordinator = &self.stream_coordinator;
+
+        // Sessions first, so streams they kept alive are judged without them
+        for speaker_ip in coordinator.remove_orphaned_sessions() {
+            log::warn!(
+                "[ResourceReaper] Dropped session on {} for a stream that no longer exists",
+                speaker_ip
+            );
+            report.orphaned_sessions += 1;
+        }
+
+        let registry = coordinator.stream_registry();
+        let snapshots = registry.list_stream_ids().into_iter().filter_map(|id| {
+            let stream = registry.get_stream(&id)?;
+            Some(StreamSnapshot {
+                has_playback: coordinator.has_playback(&id),
+                listeners: stream.listener_count(),
+                last_frame_at: stream.last_frame_at(),
+                id,
+            })
+        });
+        for stream_id in tracker.observe(snapshots) {
+            log::warn!(
+                "[ResourceReaper] Removing stream {}: no speakers, listeners or audio",
+                stream_id
+            );
+            coordinator.remove_stream_async(&stream_id).await;
+            report.orphaned_streams += 1;
+        }
+
+        report.stale_latency_monitors = self
+            .latency_monitor
+            .retain_sessions(coordinator.session_keys())
+            .await;
+
+        // Without a topology every speaker would look unknown
+        let known_ips: HashSet<String> = self
+            .sonos_state
+            .groups
+            .read()
+            .iter()
+            .flat_map(|group| group.members.iter().map(|m| m.ip.clone()))
+            .collect();
+        if !known_ips.is_empty() {
+            let subscribed = SPEAKER_SERVICES.iter().flat_map(|&service| {
+                self.gena_manager
+                    .get_subscribed_ips(service)
+                    .into_iter()
+                    .map(move |ip| (ip, service))
+            });
+            for (ip, service) in stale_subscriptions(subscribed, &known_ips) {
+                log::warn!(
+                    "[ResourceReaper] Cancelling {} subscription for unknown speaker {}",
+                    service.name(),
+                    ip
+                );
+                self.gena_manager
+                    .unsubscribe_by_ip_and_service(&ip, service)
+                    .await;
+                report.stale_subscriptions += 1;
+            }
+        }
+
+        report
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn unused(id: &str, last_frame_at: Option<Instant>) -> StreamSnapshot {
+        StreamSnapshot {
+            id: id.to_string(),
+            has_playback: false,
+            listeners: 0,
+            last_frame_at,
+        }
+    }
+
+    #[test]
+    fn streams_unused_across_two_audits_are_orphans() {
+        let mut tracker = OrphanTracker::default();
+        let frame = Some(Instant::now());
+
+        assert!(tracker.observe([unused("a", frame)]).is_empty());
+        assert_eq!(tracker.observe([unused("a", frame)]), vec!["a"]);
+    }
+
+    #[test]
+    fn new_audio_or_playback_clears_suspicion() {
+        let mut tracker = OrphanTracker::default();
+        let start = Instant::now();
+
+        tracker.observe([unused("fed", Some(start)), unused("played", None)]);
+        let orphans = tracker.observe([
+            unused("fed", Some(start + Duration::from_secs(1))),
+            StreamSnapshot {
+                has_playback: true,
+                ..unused("played", None)
+            },
+        ]);
+        assert!(orphans.is_empty());
+
+        // Playback ended again: a fresh audit is needed before removal
+        assert!(tracker.observe([unused("played", None)]).is_empty());
+    }
+
+    #[test]
+    fn streams_with_listeners_are_kept() {
+        let mut tracker = OrphanTracker::default();
+        let listened = StreamSnapshot {
+            listeners: 1,
+            ..unused("a", None)
+        };
+
+        tracker.observe([listened.clone()]);
+        assert!(tracker.observe([listened]).is_empty());
+    }
+
+    #[test]
+    fn only_subscriptions_for_unknown_speakers_are_stale() {
+        let known: HashSet<String> = ["192.168.1.10".to_string()].into();
+        let subscribed = vec![
+            ("192.168.1.10".to_string(), SonosService::AVTransport),
+            ("192.168.1.99".to_string(), SonosService::AVTransport),
+            ("192.168.1.99".to_string(), SonosService::RenderingControl),
+        ];
+
+        let stale = stale_subscriptions(subscribed, &known);
+        assert_eq!(
+            stale,
+            vec![
+                ("192.168.1.99".to_string(), SonosService::AVTransport),
+                ("192.168.1.99".to_string(), SonosService::RenderingControl),
+            ]
+        );
+    }
+
+    #[test]
+    fn report_is_clean_only_when_nothing_was_repaired() {
+        assert!(ReconciliationReport::default().is_clean());
+        let report = ReconciliationReport {
+            stale_subscriptions: 1,
+            ..Default::default()
+        };
+        assert!(!report.is_clean());
+        assert!(report.to_string().contains("1 stale subscription(s)"));
+    }
+}
@@ -0,0 +1,457 @@
+//! RTP audio ingest.
+//!
+//! Listens on a UDP port for an RTP stream of uncompressed L16 audio, as sent
+//! by `ffmpeg -f rtp`, AES67-style gateways or a hardware line-in box, and
+//! feeds it into a regular stream that can be cast like any other. The stream
+//! is created when the first packet arrives and removed once the sender goes
+//! quiet. A new SSRC (sender restart) replaces the stream.
+//!
+//! The static payload types 10 and 11 (L16, 44.1 kHz stereo and mono) are
+//! understood as such; dynamic payload types (96-127) are taken to be L16 in
+//! the configured format, which `GET /api/ingest/rtp.sdp` describes for
+//! senders that read SDP. Compressed payloads such as Opus aren't decoded.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use bytes::{Bytes, BytesMut};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use tokio::net::UdpSocket;
+use tokio_util::sync::CancellationToken;
+
+use crate::error::{ThaumicError, ThaumicResult};
+use crate::protocol_constants::{
+    DEFAULT_STREAMING_BUFFER_MS, MAX_SAMPLE_RATE, MIN_SAMPLE_RATE, SILENCE_FRAME_DURATION_MS,
+};
+use crate::runtime::TokioSpawner;
+use crate::services::StreamCoordinator;
+use crate::stream::{AudioCodec, AudioFormat, StreamMetadata};
+
+/// Largest datagram read from the socket. RTP audio stays under the MTU.
+const MAX_PACKET_BYTES: usize = 2048;
+
+/// Dynamic payload type advertised in the SDP description.
+pub const RTP_PAYLOAD_TYPE: u8 = 96;
+
+/// Configuration for the RTP ingest listener.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct RtpIngestConfig {
+    /// UDP port to receive RTP on. `None` disables the listener.
+    pub port: Option<u16>,
+    /// Sample rate of dynamic payload types (Hz).
+    pub sample_rate: u32,
+    /// Channel count of dynamic payload types (1 or 2).
+    pub channels: u16,
+    /// Seconds without packets after which the stream is removed.
+    pub idle_timeout_secs: u64,
+}
+
+impl Default for RtpIngestConfig {
+    fn default() -> Self {
+        Self {
+            port: None,
+            sample_rate: 48_000,
+            channels: 2,
+            idle_timeout_secs: 5,
+        }
+    }
+}
+
+impl RtpIngestConfig {
+    /// Checks the port and the audio format of dynamic payloads.
+    pub fn validate(&self) -> ThaumicResult<()> {
+        if self.port == Some(0) {
+            return Err(ThaumicError::InvalidRequest(
+                "The RTP ingest listener needs a fixed port".into(),
+            ));
+        }
+        if !(MIN_SAMPLE_RATE..=MAX_SAMPLE_RATE).contains(&self.sample_rate) {
+            return Err(ThaumicError::InvalidRequest(format!(
+                "Invalid RTP sample rate {}. Must be {}-{} Hz.",
+                self.sample_rate, MIN_SAMPLE_RATE, MAX_SAMPLE_RATE
+            )));
+        }
+        if !matches!(self.channels, 1 | 2) {
+            return Err(ThaumicError::InvalidRequest(format!(
+                "Invalid RTP channel count {}. Must be 1 or 2.",
+                self.channels
+            )));
+        }
+        if self.idle_timeout_secs == 0 {
+            return Err(ThaumicError::InvalidRequest(
+                "The RTP idle timeout must be at least 1 second".into(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Describes the expected stream as an SDP session, addressed to `ip`.
+    pub fn sdp(&self, ip: &str) -> Option<String> {
+        let port = self.port?;
+        Some(format!(
+            "v=0\r\n\
+             o=- 0 0 IN IP4 {ip}\r\n\
+             s=Thaumic Cast RTP ingest\r\n\
+             c=IN IP4 {ip}\r\n\
+             t=0 0\r\n\
+             m=audio {port} RTP/AVP {pt}\r\n\
+             a=rtpmap:{pt} L16/{rate}/{channels}\r\n\
+             a=recvonly\r\n",
+            pt = RTP_PAYLOAD_TYPE,
+            rate = self.sample_rate,
+            channels = self.channels,
+        ))
+    }
+
+    /// Audio format of a payload type, or `None` if it isn't L16.
+    fn format_for(&self, payload_type: u8) -> Option<AudioFormat> {
+        match payload_type {
+            10 => Some(AudioFormat::new(44_100, 2, 16)),
+            11 => Some(AudioFormat::new(44_100, 1, 16)),
+            96..=127 => Some(AudioFormat::new(self.sample_rate, self.channels, 16)),
+            _ => None,
+        }
+    }
+}
+
+/// The parts of an RTP packet the receiver uses.
+#[derive(Debug, PartialEq, Eq)]
+struct RtpPacket<'a> {
+    payload_type: u8,
+    sequence: u16,
+    ssrc: u32,
+    payload: &'a [u8],
+}
+
+/// Parses an RTP packet (RFC 3550), skipping CSRCs, header extensions and
+/// padding. Returns `None` for anything that isn't RTP version 2.
+fn parse_packet(data: &[u8]) -> Option<RtpPacket<'_>> {
+    if data.len() < 12 || data[0] >> 6 != 2 {
+        return None;
+    }
+    let has_padding = data[0] & 0x20 != 0;
+    let has_extension = data[0] & 0x10 != 0;
+    let csrc_count = usize::from(data[0] & 0x0f);
+
+    let mut start = 12 + 4 * csrc_count;
+    if has_extension {
+        let header = data.get(start..start + 4)?;
+        let words = usize::from(u16::from_be_bytes([header[2], header[3]]));
+        start += 4 + 4 * words;
+    }
+    let mut end = data.len();
+    if has_padding {
+        end = end.checked_sub(usize::from(*data.last()?))?;
+    }
+
+    Some(RtpPacket {
+        payload_type: data[1] & 0x7f,
+        sequence: u16::from_be_bytes([data[2], data[3]]),
+        ssrc: u32::from_be_bytes([data[8], data[9], data[10], data[11]]),
+        payload: data.get(start..end)?,
+    })
+}
+
+/// The sender currently feeding a stream.
+struct RtpSource {
+    ssrc: u32,
+    payload_type: u8,
+    stream_id: String,
+    last_sequence: u16,
+    frame_bytes: usize,
+    /// Little-endian samples not yet pushed as a full frame.
+    pending: BytesMut,
+}
+
+impl RtpSource {
+    /// Whether `sequence` comes after the last packet, allowing for
+    /// wraparound. Late and duplicate packets are dropped.
+    fn is_next(&self, sequence: u16) -> bool {
+        let ahead = sequence.wrapping_sub(self.last_sequence);
+        ahead != 0 && ahead < 0x8000
+    }
+}
+
+/// Swaps L16 samples from network byte order to the little-endian PCM the
+/// stream pipeline expects. A trailing odd byte is ignored.
+fn append_l16(pending: &mut BytesMut, payload: &[u8]) {
+    pending.reserve(payload.len());
+    for sample in payload.chunks_exact(2) {
+        pending.extend_from_slice(&[sample[1], sample[0]]);
+    }
+}
+
+/// Background service receiving RTP audio into a stream.
+pub struct RtpReceiver {
+    config: RtpIngestConfig,
+    stream_coordinator: Arc<StreamCoordinator>,
+    /// Stream currently fed by RTP, if a sender is active.
+    current_stream: Arc<Mutex<Option<String>>>,
+    cancel: CancellationToken,
+    spawner: TokioSpawner,
+    /// Guards against starting the listener twice.
+    started: AtomicBool,
+}
+
+impl RtpReceiver {
+    /// Creates a receiver, or `None` when no RTP port is configured.
+    ///
+    /// Note: Call `start()` to bind the socket and spawn the receive task.
+    pub fn from_config(
+        config: &RtpIngestConfig,
+        stream_coordinator: Arc<StreamCoordinator>,
+        cancel: CancellationToken,
+        spawner: TokioSpawner,
+    ) -> Option<Self> {
+        config.port?;
+        Some(Self {
+            config: config.clone(),
+            stream_coordinator,
+            current_stream: Arc::new(Mutex::new(None)),
+            cancel,
+            spawner,
+            started: AtomicBool::new(false),
+        })
+    }
+
+    /// Returns the ID of the stream RTP is currently feeding.
+    #[must_use]
+    pub fn current_stream(&self) -> Option<String> {
+        self.current_stream.lock().clone()
+    }
+
+    /// Binds the UDP port and starts receiving.
+    ///
+    /// Can only be called once; subsequent calls are no-ops.
+    pub fn start(&self) {
+        if self.started.swap(true, Ordering::SeqCst) {
+            return;
+        }
+        let Some(port) = self.config.port else {
+            return;
+        };
+
+        let config = self.config.clone();
+        let coordinator = Arc::clone(&self.stream_coordinator);
+        let current_stream = Arc::clone(&self.current_stream);
+        let cancel = self.cancel.clone();
+
+        self.spawner.spawn(async move {
+            let socket = match UdpSocket::bind(("0.0.0.0", port)).await {
+                Ok(socket) => socket,
+                Err(e) => {
+                    log::error!("[RTP] Failed to bind UDP port {}: {}", port, e);
+                    return;
+                }
+            };
+            log::info!("[RTP] Listening for RTP audio on UDP port {}", port);
+
+            let idle_timeout = Duration::from_secs(config.idle_timeout_secs);
+            let mut source: Option<RtpSource> = None;
+            let mut buf = [0u8; MAX_PACKET_BYTES];
+
+            loop {
+                let received = tokio::select! {
+                    _ = cancel.cancelled() => break,
+                    result = tokio::time::timeout(idle_timeout, socket.recv_from(&mut buf)) => result,
+                };
+                let len = match received {
+                    Ok(Ok((len, _))) => len,
+                    Ok(Err(e)) => {
+                        log::warn!("[RTP] Receive failed: {}", e);
+                        continue;
+                    }
+                    Err(_) => {
+                        if let Some(ended) = source.take() {
+                            log::info!("[RTP] Sender went quiet, ending stream {}", ended.stream_id);
+                            coordinator.remove_stream(&ended.stream_id);
+                            *current_stream.lock() = None;
+                        }
+                        continue;
+                    }
+                };
+                let Some(packet) = parse_packet(&buf[..len]) else {
+                    continue;
+                };
+
+                let is_current = source.as_ref().is_some_and(|s| {
+                    s.ssrc == packet.ssrc && s.payload_type == packet.payload_type
+                });
+                if !is_current {
+                    if let Some(replaced) = source.take() {
+                        coordinator.remove_stream(&replaced.stream_id);
+                    }
+                    source = Self::open_source(&config, &coordinator, &packet);
+                    *current_stream.lock() = source.as_ref().map(|s| s.stream_id.clone());
+                } else if let Some(current) = source.as_mut() {
+                    if !current.is_next(packet.sequence) {
+                        continue;
+                    }
+                    current.last_sequence = packet.sequence;
+                }
+
+                let Some(current) = source.as_mut() else {
+                    continue;
+                };
+                append_l16(&mut current.pending, packet.payload);
+                while current.pending.len() >= current.frame_bytes {
+                    let frame: Bytes = current.pending.split_to(current.frame_bytes).freeze();
+                    if coordinator.push_frame(&current.stream_id, frame).is_none() {
+                        // Stream removed elsewhere; the next packet opens a new one
+                        source = None;
+                        *current_stream.lock() = None;
+                        break;
+                    }
+                }
+            }
+
+            if let Some(ended) = source {
+                coordinator.remove_stream(&ended.stream_id);
+            }
+            log::debug!("[RTP] Shutting down");
+        });
+    }
+
+    /// Creates a stream for a new sender.
+    fn open_source(
+        config: &RtpIngestConfig,
+        coordinator: &StreamCoordinator,
+        packet: &RtpPacket<'_>,
+    ) -> Option<RtpSource> {
+        let Some(format) = config.format_for(packet.payload_type) else {
+            log::warn!(
+                "[RTP] Ignoring payload type {} from SSRC {:08x}: only L16 is supported",
+                packet.payload_type,
+                packet.ssrc
+            );
+            return None;
+        };
+        let stream_id = match coordinator.create_stream(
+            AudioCodec::Pcm,
+            format,
+            DEFAULT_STREAMING_BUFFER_MS,
+            SILENCE_FRAME_DURATION_MS,
+        ) {
+            Ok(id) => id,
+            Err(e) => {
+                log::warn!("[RTP] Cannot create stream: {}", e);
+                return None;
+            }
+        };
+        coordinator.update_metadata(
+            &stream_id,
+            StreamMetadata {
+                title: Some("RTP input".to_string()),
+                source: Some("RTP".to_string()),
+                ..Default::default()
+            },
+        );
+        log::info!(
+            "[RTP] New sender {:08x} ({:?}), created stream {}",
+            packet.ssrc,
+            format,
+            stream_id
+        );
+
+        Some(RtpSource {
+            ssrc: packet.ssrc,
+            payload_type: packet.payload_type,
+            stream_id,
+            last_sequence: packet.sequence,
+            frame_bytes: format.frame_bytes(SILENCE_FRAME_DURATION_MS),
+            pending: BytesMut::new(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn packet(payload_type: u8, sequence: u16, extra_header: &[u8], payload: &[u8]) -> Vec<u8> {
+        let mut data = vec![0x80, payload_type];
+        data.extend_from_slice(&sequence.to_be_bytes());
+        data.extend_from_slice(&1234u32.to_be_bytes());
+        data.extend_from_slice(&0xdead_beefu32.to_be_bytes());
+        data.extend_from_slice(extra_header);
+        data.extend_from_slice(payload);
+        data
+    }
+
+    #[test]
+    fn parses_plain_packets() {
+        let data = packet(96, 7, &[], &[1, 2, 3, 4]);
+        assert_eq!(
+            parse_packet(&data),
+            Some(RtpPacket {
+                payload_type: 96,
+                sequence: 7,
+                ssrc: 0xdead_beef,
+                payload: &[1, 2, 3, 4],
+            })
+        );
+        assert!(parse_packet(&data[..11]).is_none());
+        let mut rtcp_like = data.clone();
+        rtcp_like[0] = 0x40;
+        assert!(parse_packet(&rtcp_like).is_none());
+    }
+
+    #[test]
+    fn skips_csrcs_extensions_and_padding() {
+        // One CSRC, a one-word extension, and two bytes of padding
+        let mut data = packet(
+            96,
+            1,
+            &[0, 0, 0, 9, 0xbe, 0xde, 0, 1, 0xaa, 0xbb, 0xcc, 0xdd],
+            &[5, 6, 0, 2],
+        );
+        data[0] |= 0x20 | 0x10 | 0x01;
+        assert_eq!(parse_packet(&data).unwrap().payload, &[5, 6]);
+    }
+
+    #[test]
+    fn swaps_l16_to_little_endian() {
+        let mut pending = BytesMut::new();
+        append_l16(&mut pending, &[0x12, 0x34, 0xab, 0xcd, 0xff]);
+        assert_eq!(&pending[..], &[0x34, 0x12, 0xcd, 0xab]);
+    }
+
+    #[test]
+    fn sequence_order_survives_wraparound() {
+        let mut source = RtpSource {
+            ssrc: 1,
+            payload_type: 96,
+            stream_id: String::new(),
+            last_sequence: u16::MAX,
+            frame_bytes: 4,
+            pending: BytesMut::new(),
+        };
+        assert!(source.is_next(0));
+        assert!(!source.is_next(u16::MAX));
+        source.last_sequence = 10;
+        assert!(!source.is_next(9));
+        assert!(source.is_next(12));
+    }
+
+    #[test]
+    fn dynamic_payloads_use_the_configured_format() {
+        let config = RtpIngestConfig {
+            port: Some(5004),
+            sample_rate: 44_100,
+            channels: 1,
+            ..Default::default()
+        };
+        assert!(config.validate().is_ok());
+        assert_eq!(config.format_for(97), Some(AudioFormat::new(44_100, 1, 16)));
+        assert_eq!(config.format_for(10), Some(AudioFormat::new(44_100, 2, 16)));
+        assert_eq!(config.format_for(14), None);
+
+        let sdp = config.sdp("192.168.1.10").unwrap();
+        assert!(sdp.contains("m=audio 5004 RTP/AVP 96\r\n"));
+        assert!(sdp.contains("a=rtpmap:96 L16/44100/1\r\n"));
+        assert!(RtpIngestConfig::default().sdp("192.168.1.10").is_none());
+    }
+}
@@ -0,0 +1,488 @@
+//! User scripts run on events.
+//!
+//! Every `*.rhai` file in the data directory's `scripts` folder that defines
+//! `fn on_event(event)` is called with each broadcast event, as a map with
+//! the same fields clients get over the WebSocket. Scripts run sandboxed:
+//! no file, network or module access, and a cap on the work one call may do.
+//! They see the system through a small API:
+//!
+//! - `speakers()`: rooms as `#{ ip, name, model, coordinatorIp, group }`
+//! - `streams()`: active streams as
+//!   `#{ id, codec, label, title, artist, source, listeners }`
+//! - `set_volume(ip, volume)` and `set_mute(ip, muted)`
+//! - `webhook(url)` / `webhook(url, body)`: POSTs `body` as JSON
+//!
+//! Actions are collected during the call and carried out afterwards, so a
+//! script never waits on the network. For example, to dim the lights when
+//! a cast starts:
+//!
+//! ```rhai
+//! fn on_event(event) {
+//!     if event.type == "playbackStarted" {
+//!         webhook("http://homeassistant.local:8123/api/webhook/cast", #{ scene: "movie" });
+//!     }
+//! }
+//! ```
+//!
+//! Scripts are loaded when background tasks start; restart to pick up
+//! changes.
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use parking_lot::{Mutex, RwLock};
+use reqwest::Client;
+use rhai::module_resolvers::DummyModuleResolver;
+use rhai::{Array, CallFnOptions, Dynamic, Engine, Map, Scope, AST};
+use serde_json::{json, Value};
+use tokio::sync::broadcast::error::RecvError;
+use tokio_util::sync::CancellationToken;
+
+use crate::events::{BroadcastEvent, EventReceiver};
+use crate::runtime::TokioSpawner;
+use crate::services::StreamCoordinator;
+use crate::sonos::SonosClient;
+use crate::state::SonosState;
+
+/// Folder in the data directory that scripts are loaded from.
+const SCRIPTS_DIR: &str = "scripts";
+
+/// Most operations one script call may run before it is aborted.
+const MAX_OPERATIONS: u64 = 100_000;
+
+/// Timeout for webhook requests.
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Something a script asked for, carried out after it returns.
+#[derive(Debug, Clone, PartialEq)]
+enum ScriptAction {
+    SetVolume { ip: String, volume: u8 },
+    SetMute { ip: String, mute: bool },
+    Webhook { url: String, body: Value },
+}
+
+/// What the script API reads and writes during one call.
+#[derive(Default)]
+struct ScriptContext {
+    speakers: Array,
+    streams: Array,
+    actions: Vec<ScriptAction>,
+}
+
+struct Script {
+    name: String,
+    ast: AST,
+}
+
+/// Sandboxed engine with the loaded scripts.
+struct ScriptRunner {
+    engine: Engine,
+    scripts: Vec<Script>,
+    context: Arc<Mutex<ScriptContext>>,
+}
+
+impl ScriptRunner {
+    fn new() -> Self {
+        let context = Arc::new(Mutex::new(ScriptContext::default()));
+        let mut engine = Engine::new();
+        engine
+            .set_module_resolver(DummyModuleResolver::new())
+            .set_max_operations(MAX_OPERATIONS)
+            .set_max_call_levels(32)
+            .set_max_expr_depths(64, 32)
+            .set_max_string_size(64 * 1024)
+            .set_max_array_size(10_000)
+            .set_max_map_size(10_000)
+            .disable_symbol("eval")
+            .on_print(|text| log::info!("[Scripts] {}", text))
+            .on_debug(|text, _, _| log::debug!("[Scripts] {}", text));
+
+        let ctx = Arc::clone(&context);
+        engine.register_fn("speakers", move || ctx.lock().speakers.clone());
+        let ctx = Arc::clone(&context);
+        engine.register_fn("streams", move || ctx.lock().streams.clone());
+        let ctx = Arc::clone(&context);
+        engine.register_fn("set_volume", move |ip: &str, volume: i64| {
+            ctx.lock().actions.push(ScriptAction::SetVolume {
+                ip: ip.to_string(),
+                volume: volume.clamp(0, 100) as u8,
+            });
+        });
+        let ctx = Arc::clone(&context);
+        engine.register_fn("set_mute", move |ip: &str, mute: bool| {
+            ctx.lock().actions.push(ScriptAction::SetMute {
+                ip: ip.to_string(),
+                mute,
+            });
+        });
+        let ctx = Arc::clone(&context);
+        engine.register_fn("webhook", move |url: &str| {
+            ctx.lock().actions.push(ScriptAction::Webhook {
+                url: url.to_string(),
+                body: Value::Null,
+            });
+        });
+        let ctx = Arc::clone(&context);
+        engine.register_fn("webhook", move |url: &str, body: Map| {
+            let body = rhai::serde::from_dynamic(&Dynamic::from_map(body)).unwrap_or(Value::Null);
+            ctx.lock().actions.push(ScriptAction::Webhook {
+                url: url.to_string(),
+                body,
+            });
+        });
+
+        Self {
+            engine,
+            scripts: Vec::new(),
+            context,
+        }
+    }
+
+    /// Compiles a script. Scripts without an `on_event(event)` function are
+    /// skipped with a warning.
+    fn load(&mut self, name: &str, source: &str) -> Result<(), String> {
+        let ast = self.engine.compile(source).map_err(|e| e.to_string())?;
+        if !ast
+            .iter_functions()
+            .any(|f| f.name == "on_event" && f.params.len() == 1)
+        {
+            return Err("no on_event(event) function".to_string());
+        }
+        self.scripts.push(Script {
+            name: name.to_string(),
+            ast,
+        });
+        Ok(())
+    }
+
+    /// Loads every `*.rhai` file in `dir`, in name order.
+    fn load_dir(&mut self, dir: &Path) {
+        let Ok(entries) = std::fs::read_dir(dir) else {
+            return;
+        };
+        let mut paths: Vec<PathBuf> = entries
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.extension().is_some_and(|ext| ext == "rhai"))
+            .collect();
+        paths.sort();
+
+        for path in paths {
+            let name = path
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            let result = std::fs::read_to_string(&path)
+                .map_err(|e| e.to_string())
+                .and_then(|source| self.load(&name, &source));
+            match result {
+                Ok(()) => log::info!("[Scripts] Loaded {}", name),
+                Err(e) => log::warn!("[Scripts] Skipping {}: {}", name, e),
+            }
+        }
+    }
+
+    /// Calls every script's `on_event` and returns the actions they asked for.
+    fn run(&self, event: &BroadcastEvent, speakers: Array, streams: Array) -> Vec<ScriptAction> {
+        let event = match rhai::serde::to_dynamic(event) {
+            Ok(event) => event,
+            Err(e) => {
+                log::warn!("[Scripts] Failed to convert event: {}", e);
+                return Vec::new();
+            }
+        };
+        {
+            let mut context = self.context.lock();
+            context.speakers = speakers;
+            context.streams = streams;
+        }
+
+        for script in &self.scripts {
+            let queued = self.context.lock().actions.len();
+            let options = CallFnOptions::new().eval_ast(false);
+            let result = self.engine.call_fn_with_options::<Dynamic>(
+                options,
+                &mut Scope::new(),
+                &script.ast,
+                "on_event",
+                (event.clone(),),
+            );
+            if let Err(e) = result {
+                // Half-finished work is dropped rather than carried out
+                log::warn!("[Scripts] {} failed: {}", script.name, e);
+                self.context.lock().actions.truncate(queued);
+            }
+        }
+        std::mem::take(&mut self.context.lock().actions)
+    }
+}
+
+/// Background service running user scripts on events.
+pub struct ScriptHooks {
+    stream_coordinator: Arc<StreamCoordinator>,
+    sonos: Arc<dyn SonosClient>,
+    sonos_state: Arc<SonosState>,
+    http_client: Client,
+    data_dir: RwLock<Option<PathBuf>>,
+    /// Event receiver, taken when the service starts.
+    events: Mutex<Option<EventReceiver>>,
+    cancel: CancellationToken,
+    spawner: TokioSpawner,
+}
+
+impl ScriptHooks {
+    /// Creates a new ScriptHooks service.
+    ///
+    /// Note: Call `start()` to load scripts and spawn the background task.
+    ///
+    /// # Arguments
+    /// * `stream_coordinator` - Routes volume changes and lists streams
+    /// * `sonos` - Sonos client for volume control
+    /// * `sonos_state` - Current groups, for `speakers()`
+    /// * `http_client` - HTTP client for webhooks
+    /// * `events` - Event bus receiver
+    /// * `cancel` - Cancellation token for graceful shutdown
+    /// * `spawner` - Task spawner for background tasks
+    pub fn new(
+        stream_coordinator: Arc<StreamCoordinator>,
+        sonos: Arc<dyn SonosClient>,
+        sonos_state: Arc<SonosState>,
+        http_client: Client,
+        events: EventReceiver,
+        cancel: CancellationToken,
+        spawner: TokioSpawner,
+    ) -> Self {
+        Self {
+            stream_coordinator,
+            sonos,
+            sonos_state,
+            http_client,
+            data_dir: RwLock::new(None),
+            events: Mutex::new(Some(events)),
+            cancel,
+            spawner,
+        }
+    }
+
+    /// Sets the data directory whose `scripts` folder is loaded on start.
+    pub fn set_data_dir(&self, dir: impl AsRef<Path>) {
+        *self.data_dir.write() = Some(dir.as_ref().to_path_buf());
+    }
+
+    /// Loads the scripts and starts running them on events.
+    ///
+    /// Does nothing without a data directory or scripts. Can only be called
+    /// once; subsequent calls are no-ops.
+    pub fn start(&self) {
+        let Some(mut events) = self.events.lock().take() else {
+            return;
+        };
+        let Some(dir) = self
+            .data_dir
+            .read()
+            .as_ref()
+            .map(|dir| dir.join(SCRIPTS_DIR))
+        else {
+            return;
+        };
+        let mut runner = ScriptRunner::new();
+        runner.load_dir(&dir);
+        if runner.scripts.is_empty() {
+            return;
+        }
+        let runner = Arc::new(runner);
+
+        let stream_coordinator = Arc::clone(&self.stream_coordinator);
+        let sonos = Arc::clone(&self.sonos);
+        let sonos_state = Arc::clone(&self.sonos_state);
+        let http_client = self.http_client.clone();
+        let cancel = self.cancel.clone();
+
+        self.spawner.spawn(async move {
+            loop {
+                let event = tokio::select! {
+                    _ = cancel.cancelled() => break,
+                    event = events.recv() => match event {
+                        Ok(event) => event,
+                        Err(RecvError::Lagged(skipped)) => {
+                            log::warn!("[Scripts] Missed {} event(s)", skipped);
+                            continue;
+                        }
+                        Err(RecvError::Closed) => break,
+                    },
+                };
+
+                // Scripts may run up to MAX_OPERATIONS each, so they run on
+                // the blocking pool rather than holding up a runtime worker
+                let speakers = speakers_snapshot(&sonos_state);
+                let streams = streams_snapshot(&stream_coordinator);
+                let scripts = Arc::clone(&runner);
+                let actions = match tokio::task::spawn_blocking(move || {
+                    scripts.run(&event, speakers, streams)
+                })
+                .await
+                {
+                    Ok(actions) => actions,
+                    Err(e) => {
+                        log::warn!("[Scripts] Script run panicked: {}", e);
+                        continue;
+                    }
+                };
+                for action in actions {
+                    match action {
+                        ScriptAction::SetVolume { ip, volume } => {
+                            if let Err(e) = stream_coordinator
+                                .set_volume_routed(&*sonos, &ip, volume)
+                                .await
+                            {
+                                log::warn!("[Scripts] set_volume({}) failed: {}", ip, e);
+                            }
+                        }
+                        ScriptAction::SetMute { ip, mute } => {
+                            if let Err(e) =
+                                stream_coordinator.set_mute_routed(&*sonos, &ip, mute).await
+                            {
+                                log::warn!("[Scripts] set_mute({}) failed: {}", ip, e);
+                            }
+                        }
+                        ScriptAction::Webhook { url, body } => {
+                            let request = http_client.post(&url).timeout(WEBHOOK_TIMEOUT);
+                            let request = match &body {
+                                Value::Null => request,
+                                body => request.json(body),
+                            };
+                            match request.send().await {
+                                Ok(response) if !response.status().is_success() => {
+                                    log::warn!(
+                                        "[Scripts] Webhook {} returned {}",
+                                        url,
+                                        response.status()
+                                    );
+                                }
+                                Ok(_) => {}
+                                Err(e) => log::warn!("[Scripts] Webhook {} failed: {}", url, e),
+                            }
+                        }
+                    }
+                }
+            }
+        });
+    }
+}
+
+fn speakers_snapshot(sonos_state: &SonosState) -> Array {
+    sonos_state
+        .groups
+        .read()
+        .iter()
+        .flat_map(|group| {
+            group.members.iter().map(move |member| {
+                json!({
+                    "ip": member.ip,
+                    "name": member.zone_name,
+                    "model": member.model,
+                    "coordinatorIp": group.coordinator_ip,
+                    "group": group.label,
+                })
+            })
+        })
+        .filter_map(|speaker| rhai::serde::to_dynamic(speaker).ok())
+        .collect()
+}
+
+fn streams_snapshot(stream_coordinator: &StreamCoordinator) -> Array {
+    let registry = stream_coordinator.stream_registry();
+    registry
+        .list_stream_ids()
+        .into_iter()
+        .filter_map(|id| registry.get_stream(&id))
+        .map(|stream| {
+            let metadata = stream.metadata.read();
+            json!({
+                "id": stream.id,
+                "codec": stream.codec,
+                "label": stream.label(),
+                "title": metadata.title,
+                "artist": metadata.artist,
+                "source": metadata.source,
+                "listeners": stream.listener_count(),
+            })
+        })
+        .filter_map(|stream| rhai::serde::to_dynamic(stream).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::StreamEvent;
+
+    fn playback_started() -> BroadcastEvent {
+        StreamEvent::PlaybackStarted {
+            stream_id: "s1".to_string(),
+            speaker_ip: "192.168.1.50".to_string(),
+            stream_url: "http://192.168.1.100:8080/stream/s1/live".to_string(),
+            timestamp: 0,
+        }
+        .into()
+    }
+
+    #[test]
+    fn scripts_react_to_events_through_the_api() {
+        let mut runner = ScriptRunner::new();
+        runner
+            .load(
+                "cast.rhai",
+                r#"
+                fn on_event(event) {
+                    if event.type != "playbackStarted" { return; }
+                    for speaker in speakers() {
+                        if speaker.ip == event.speakerIp {
+                            set_volume(speaker.ip, 150);
+                            webhook("http://hooks.local/cast", #{ room: speaker.name });
+                        }
+                    }
+                }
+                "#,
+            )
+            .unwrap();
+
+        let speaker = rhai::serde::to_dynamic(json!({ "ip": "192.168.1.50", "name": "Kitchen" }));
+        let actions = runner.run(&playback_started(), vec![speaker.unwrap()], Array::new());
+        assert_eq!(
+            actions,
+            vec![
+                ScriptAction::SetVolume {
+                    ip: "192.168.1.50".to_string(),
+                    volume: 100,
+                },
+                ScriptAction::Webhook {
+                    url: "http://hooks.local/cast".to_string(),
+                    body: json!({ "room": "Kitchen" }),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn failing_scripts_are_stopped_without_side_effects() {
+        let mut runner = ScriptRunner::new();
+        assert!(runner.load("empty.rhai", "let x = 1;").is_err());
+        runner
+            .load(
+                "spin.rhai",
+                r#"fn on_event(event) { loop { set_mute("192.168.1.50", true); } }"#,
+            )
+            .unwrap();
+        runner
+            .load(
+                "import.rhai",
+                r#"fn on_event(event) { set_mute("192.168.1.50", true); import "fs" as fs; }"#,
+            )
+            .unwrap();
+
+        assert!(runner
+            .run(&playback_started(), Array::new(), Array::new())
+            .is_empty());
+    }
+}
@@ -0,0 +1,198 @@
+//! Scrobbling event consumer.
+//!
+//! Listens on the event bus for metadata updates and playback changes, feeds
+//! them to a [`ScrobbleTracker`], and submits the resulting "now playing"
+//! updates and listens to every configured [`ScrobbleSink`]. Submissions run
+//! in their own tasks so a slow service never holds up event handling.
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use parking_lot::Mutex;
+use tokio::sync::broadcast::error::RecvError;
+use tokio_util::sync::CancellationToken;
+
+use crate::events::{BroadcastEvent, EventReceiver, StreamEvent};
+use crate::runtime::TokioSpawner;
+use crate::scrobble::tracker::{ScrobbleAction, ScrobbleTracker, Track};
+use crate::scrobble::ScrobbleSink;
+use crate::stream::StreamMetadata;
+use crate::utils::now_millis;
+
+/// How often play time is checked against the scrobble threshold.
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Background service submitting scrobbles for cast tracks.
+pub struct ScrobbleService {
+    sinks: Vec<Arc<dyn ScrobbleSink>>,
+    threshold: Duration,
+    /// Event receiver, taken when the service starts.
+    events: Mutex<Option<EventReceiver>>,
+    cancel: CancellationToken,
+    spawner: TokioSpawner,
+}
+
+impl ScrobbleService {
+    /// Creates a new ScrobbleService.
+    ///
+    /// Note: Call `start()` to spawn the background task.
+    ///
+    /// # Arguments
+    /// * `sinks` - Services to submit to
+    /// * `threshold` - Play time after which a track is scrobbled
+    /// * `events` - Event bus receiver (subscribe before streams can start)
+    /// * `cancel` - Cancellation token for graceful shutdown
+    /// * `spawner` - Task spawner for background tasks
+    pub fn new(
+        sinks: Vec<Arc<dyn ScrobbleSink>>,
+        threshold: Duration,
+        events: EventReceiver,
+        cancel: CancellationToken,
+        spawner: TokioSpawner,
+    ) -> Self {
+        Self {
+            sinks,
+            threshold,
+            events: Mutex::new(Some(events)),
+            cancel,
+            spawner,
+        }
+    }
+
+    /// Starts the background task.
+    ///
+    /// Can only be called once; subsequent calls are no-ops.
+    pub fn start(&self) {
+        let Some(mut events) = self.events.lock().take() else {
+            return;
+        };
+
+        let sinks = self.sinks.clone();
+        let cancel = self.cancel.clone();
+        let spawner = self.spawner.clone();
+        let mut tracker = ScrobbleTracker::new(self.threshold);
+
+        self.spawner.spawn(async move {
+            let mut interval = tokio::time::interval(POLL_INTERVAL);
+
+            loop {
+                let actions = tokio::select! {
+                    _ = cancel.cancelled() => {
+                        log::debug!("[Scrobble] Shutting down");
+                        break;
+                    }
+                    _ = interval.tick() => tracker.poll(Instant::now()),
+                    event = events.recv() => match event {
+                        Ok(BroadcastEvent::Stream(event)) => handle_event(&mut tracker, event),
+                        Ok(_) => continue,
+                        Err(RecvError::Lagged(skipped)) => {
+                            log::warn!("[Scrobble] Missed {} event(s)", skipped);
+                            continue;
+                        }
+                        Err(RecvError::Closed) => break,
+                    },
+                };
+
+                for action in actions {
+                    dispatch(&sinks, &spawner, action);
+                }
+            }
+        });
+    }
+}
+
+/// Feeds a stream event to the tracker.
+fn handle_event(tracker: &mut ScrobbleTracker, event: StreamEvent) -> Vec<ScrobbleAction> {
+    let now = Instant::now();
+    let unix_secs = now_millis() / 1000;
+
+    match event {
+        StreamEvent::MetadataUpdated {
+            stream_id,
+            metadata,
+            ..
+        } => tracker.on_metadata(&stream_id, track_from(&metadata), now, unix_secs),
+        StreamEvent::PlaybackStarted {
+            stream_id,
+            speaker_ip,
+            ..
+        } => tracker
+            .on_playback_started(&stream_id, &speaker_ip, now, unix_secs)
+            .into_iter()
+            .collect(),
+        StreamEvent::PlaybackStopped {
+            stream_id,
+            speaker_ip,
+            ..
+        } => tracker
+            .on_playback_stopped(&stream_id, &speaker_ip, now)
+            .into_iter()
+            .collect(),
+        StreamEvent::Ended { stream_id, .. } => tracker
+            .on_stream_ended(&stream_id, now)
+            .into_iter()
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Returns the track to scrobble, if the metadata names both artist and title.
+fn track_from(metadata: &StreamMetadata) -> Option<Track> {
+    let non_empty = |value: &Option<String>| {
+        value
+            .as_deref()
+            .map(str::trim)
+            .filter(|v| !v.is_empty())
+            .map(str::to_string)
+    };
+
+    Some(Track {
+        artist: non_empty(&metadata.artist)?,
+        title: non_empty(&metadata.title)?,
+        album: non_empty(&metadata.album),
+    })
+}
+
+/// Submits an action to every sink in the background.
+fn dispatch(sinks: &[Arc<dyn ScrobbleSink>], spawner: &TokioSpawner, action: ScrobbleAction) {
+    for sink in sinks {
+        let sink = Arc::clone(sink);
+        let action = action.clone();
+        spawner.spawn(async move {
+            let (kind, result) = match &action {
+                ScrobbleAction::NowPlaying(listen) => {
+                    ("now playing", sink.now_playing(listen).await)
+                }
+                ScrobbleAction::Scrobble(listen) => ("scrobble", sink.scrobble(listen).await),
+            };
+            if let Err(e) = result {
+                log::warn!("[Scrobble] {} {} failed: {}", sink.name(), kind, e);
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn requires_artist_and_title() {
+        let metadata = |title: Option<&str>, artist: Option<&str>| StreamMetadata {
+            title: title.map(str::to_string),
+            artist: artist.map(str::to_string),
+            ..Default::default()
+        };
+
+        assert_eq!(track_from(&metadata(Some("Song"), None)), None);
+        assert_eq!(track_from(&metadata(Some("Song"), Some("  "))), None);
+        assert_eq!(
+            track_from(&metadata(Some(" Song "), Some("Band"))),
+            Some(Track {
+                artist: "Band".to_string(),
+                title: "Song".to_string(),
+                album: None,
+            })
+        );
+    }
+}
@@ -0,0 +1,153 @@
+//! Persistent cache of discovered speakers.
+//!
+//! A full discovery waits several seconds for SSDP and mDNS answers, so
+//! groups used to show up that long after launch. The speakers found by the
+//! last discovery are saved to `speakers.json` in the app data directory;
+//! on startup their IPs are asked for the zone group topology right away
+//! while the first full discovery runs.
+
+use std::path::{Path, PathBuf};
+
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+
+use crate::sonos::discovery::Speaker;
+use crate::storage;
+
+const CACHE_FILE: &str = "speakers.json";
+
+/// A speaker as last discovered.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CachedSpeaker {
+    pub uuid: String,
+    pub ip: String,
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model_name: Option<String>,
+    /// Boost or Bridge, which can't answer topology requests usefully.
+    #[serde(default)]
+    pub infrastructure: bool,
+}
+
+impl From<&Speaker> for CachedSpeaker {
+    fn from(speaker: &Speaker) -> Self {
+        Self {
+            uuid: speaker.uuid.clone(),
+            ip: speaker.ip.clone(),
+            name: speaker.name.clone(),
+            model_name: speaker.model_name.clone(),
+            infrastructure: speaker.is_infrastructure_device(),
+        }
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct CacheFile {
+    speakers: Vec<CachedSpeaker>,
+}
+
+/// Speakers found by the last successful discovery.
+#[derive(Default)]
+pub struct SpeakerCache {
+    speakers: Mutex<Vec<CachedSpeaker>>,
+    data_dir: Mutex<Option<PathBuf>>,
+}
+
+impl SpeakerCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the directory the cache is persisted to and loads saved speakers.
+    pub fn set_data_dir(&self, dir: impl AsRef<Path>) {
+        let dir = dir.as_ref().to_path_buf();
+        let loaded = load(&dir);
+        {
+            let mut speakers = self.speakers.lock();
+            if speakers.is_empty() {
+                *speakers = loaded.speakers;
+            }
+        }
+        *self.data_dir.lock() = Some(dir);
+    }
+
+    /// Returns the cached speakers, those able to answer topology requests
+    /// first.
+    pub fn speakers(&self) -> Vec<CachedSpeaker> {
+        let mut speakers = self.speakers.lock().clone();
+        speakers.sort_by_key(|speaker| speaker.infrastructure);
+        speakers
+    }
+
+    /// Replaces the cache with the speakers of a discovery, saving it if
+    /// anything changed.
+    pub fn update(&self, speakers: &[Speaker]) {
+        let mut updated: Vec<CachedSpeaker> = speakers.iter().map(CachedSpeaker::from).collect();
+        updated.sort_by(|a, b| a.uuid.cmp(&b.uuid));
+        {
+            let mut cached = self.speakers.lock();
+            if *cached == updated {
+                return;
+            }
+            *cached = updated.clone();
+        }
+
+        let Some(dir) = self.data_dir.lock().clone() else {
+            return;
+        };
+        if let Err(e) = save(&dir, &CacheFile { speakers: updated }) {
+            log::warn!("[SpeakerCache] Failed to save {}: {}", CACHE_FILE, e);
+        }
+    }
+}
+
+fn load(dir: &Path) -> CacheFile {
+    match std::fs::read_to_string(dir.join(CACHE_FILE)) {
+        Ok(contents) => serde_json::from_str(&contents).unwrap_or_else(|e| {
+            log::warn!("[SpeakerCache] Ignoring unreadable {}: {}", CACHE_FILE, e);
+            CacheFile::default()
+        }),
+        Err(_) => CacheFile::default(),
+    }
+}
+
+/// Writes the cache atomically (temp file + rename). The cache is only a
+/// head start, so it is simply not saved while the data directory is full
+/// or read-only.
+fn save(dir: &Path, cache: &CacheFile) -> std::io::Result<()> {
+    let contents = serde_json::to_string_pretty(cache)?;
+    storage::tolerate(storage::write_atomic(dir, CACHE_FILE, contents))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn speaker(ip: &str, uuid: &str, model: &str) -> Speaker {
+        Speaker::new(
+            ip.to_string(),
+            uuid.to_string(),
+            format!("Room {}", ip),
+            Some(model.to_string()),
+        )
+    }
+
+    #[test]
+    fn speakers_survive_a_restart() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = SpeakerCache::new();
+        cache.set_data_dir(dir.path());
+        cache.update(&[
+            speaker("192.168.1.30", "RINCON_B", "Sonos Boost"),
+            speaker("192.168.1.20", "RINCON_A", "Sonos One"),
+        ]);
+
+        let reloaded = SpeakerCache::new();
+        reloaded.set_data_dir(dir.path());
+        let speakers = reloaded.speakers();
+        assert_eq!(speakers.len(), 2);
+        assert_eq!(speakers[0].ip, "192.168.1.20");
+        assert!(speakers[1].infrastructure);
+    }
+}
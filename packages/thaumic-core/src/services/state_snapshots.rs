@@ -0,0 +1,238 @@
+//! Coalesced Sonos state updates.
+//!
+//! A single change on the Sonos side (a regroup, a volume sweep in the Sonos
+//! app) arrives as a burst of GENA notifications and topology events within a
+//! few milliseconds. This service waits for a burst to settle, then compares
+//! the state with what it last sent and emits one [`StateEvent`]: the full
+//! state after a topology change, otherwise a patch with only the speakers
+//! whose transport state, volume or mute changed.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use parking_lot::Mutex;
+use serde_json::{Map, Value};
+use tokio::sync::broadcast::error::RecvError;
+use tokio_util::sync::CancellationToken;
+
+use crate::events::{BroadcastEvent, EventEmitter, EventReceiver, StateEvent};
+use crate::runtime::TokioSpawner;
+use crate::state::SonosState;
+use crate::utils::now_millis;
+
+/// How long changes are collected after the first one before an update is
+/// sent.
+const DEBOUNCE_WINDOW: Duration = Duration::from_millis(100);
+
+/// Remembers the last state sent and turns the next one into an event.
+#[derive(Default)]
+struct SnapshotTracker {
+    last: Option<Value>,
+    revision: u64,
+}
+
+impl SnapshotTracker {
+    /// Returns the event bringing clients from the last state sent to
+    /// `current`, or `None` if nothing changed.
+    fn next(&mut self, current: Value, timestamp: u64) -> Option<StateEvent> {
+        let changes = match &self.last {
+            Some(last) => diff_state(last, &current),
+            None => Map::new(),
+        };
+        let full = self.last.is_none() || changes.contains_key("groups");
+        if !full && changes.is_empty() {
+            return None;
+        }
+
+        self.revision += 1;
+        self.last = Some(current.clone());
+        Some(if full {
+            StateEvent::Snapshot {
+                revision: self.revision,
+                state: current,
+                timestamp,
+            }
+        } else {
+            StateEvent::Patch {
+                revision: self.revision,
+                changes: Value::Object(changes),
+                timestamp,
+            }
+        })
+    }
+}
+
+/// Returns the top-level entries of `current` that differ from `previous`.
+///
+/// Maps keyed by speaker IP only keep their changed entries, with `null` for
+/// removed ones; anything else is replaced whole.
+fn diff_state(previous: &Value, current: &Value) -> Map<String, Value> {
+    let mut changes = Map::new();
+    let (Some(previous), Some(current)) = (previous.as_object(), current.as_object()) else {
+        return changes;
+    };
+
+    for (key, value) in current {
+        let old = previous.get(key);
+        match (old.and_then(Value::as_object), value.as_object()) {
+            (Some(old), Some(new)) => {
+                let mut entries: Map<String, Value> = new
+                    .iter()
+                    .filter(|(ip, v)| old.get(*ip) != Some(*v))
+                    .map(|(ip, v)| (ip.clone(), v.clone()))
+                    .collect();
+                for ip in old.keys().filter(|ip| !new.contains_key(*ip)) {
+                    entries.insert(ip.clone(), Value::Null);
+                }
+                if !entries.is_empty() {
+                    changes.insert(key.clone(), Value::Object(entries));
+                }
+            }
+            _ if old != Some(value) => {
+                changes.insert(key.clone(), value.clone());
+            }
+            _ => {}
+        }
+    }
+    changes
+}
+
+/// Background service emitting debounced state snapshots and patches.
+pub struct StateSnapshotter {
+    sonos_state: Arc<SonosState>,
+    emitter: Arc<dyn EventEmitter>,
+    /// Event receiver, taken when the service starts.
+    events: Mutex<Option<EventReceiver>>,
+    cancel: CancellationToken,
+    spawner: TokioSpawner,
+}
+
+impl StateSnapshotter {
+    /// Creates a new StateSnapshotter.
+    ///
+    /// Note: Call `start()` to spawn the background task.
+    ///
+    /// # Arguments
+    /// * `sonos_state` - Sonos state the updates are taken from
+    /// * `emitter` - Emitter the updates are sent through
+    /// * `events` - Event bus receiver, signalling possible state changes
+    /// * `cancel` - Cancellation token for graceful shutdown
+    /// * `spawner` - Task spawner for background tasks
+    pub fn new(
+        sonos_state: Arc<SonosState>,
+        emitter: Arc<dyn EventEmitter>,
+        events: EventReceiver,
+        cancel: CancellationToken,
+        spawner: TokioSpawner,
+    ) -> Self {
+        Self {
+            sonos_state,
+            emitter,
+            events: Mutex::new(Some(events)),
+            cancel,
+            spawner,
+        }
+    }
+
+    /// Starts the background task.
+    ///
+    /// Can only be called once; subsequent calls are no-ops.
+    pub fn start(&self) {
+        let Some(mut events) = self.events.lock().take() else {
+            return;
+        };
+
+        let sonos_state = Arc::clone(&self.sonos_state);
+        let emitter = Arc::clone(&self.emitter);
+        let cancel = self.cancel.clone();
+
+        self.spawner.spawn(async move {
+            let mut tracker = SnapshotTracker::default();
+            loop {
+                // Sonos and topology events follow the state change they
+                // report; a lagged receiver may have missed one
+                tokio::select! {
+                    _ = cancel.cancelled() => break,
+                    event = events.recv() => match event {
+                        Ok(BroadcastEvent::Sonos(_) | BroadcastEvent::Topology(_))
+                        | Err(RecvError::Lagged(_)) => {}
+                        Ok(_) => continue,
+                        Err(RecvError::Closed) => break,
+                    },
+                }
+
+                // Let the rest of the burst arrive
+                let window = tokio::time::sleep(DEBOUNCE_WINDOW);
+                tokio::pin!(window);
+                loop {
+                    tokio::select! {
+                        _ = cancel.cancelled() => return,
+                        _ = &mut window => break,
+                        event = events.recv() => {
+                            if matches!(event, Err(RecvError::Closed)) {
+                                return;
+                            }
+                        }
+                    }
+                }
+
+                if let Some(event) = tracker.next(sonos_state.to_json(), now_millis()) {
+                    emitter.emit_state(event);
+                }
+            }
+            log::debug!("[StateSnapshots] Shutting down");
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn state(volumes: Value, groups: Value) -> Value {
+        json!({
+            "groups": groups,
+            "transportStates": {},
+            "groupVolumes": volumes,
+            "groupMutes": {},
+        })
+    }
+
+    #[test]
+    fn sends_patches_between_topology_changes() {
+        let mut tracker = SnapshotTracker::default();
+        let groups = json!([{ "id": "g1" }]);
+
+        let first = tracker.next(
+            state(json!({ "10.0.0.1": 20, "10.0.0.2": 30 }), groups.clone()),
+            1,
+        );
+        assert!(matches!(
+            first,
+            Some(StateEvent::Snapshot { revision: 1, .. })
+        ));
+
+        let unchanged = state(json!({ "10.0.0.1": 20, "10.0.0.2": 30 }), groups.clone());
+        assert!(tracker.next(unchanged, 2).is_none());
+
+        let patch = tracker.next(state(json!({ "10.0.0.1": 25 }), groups), 3);
+        let Some(StateEvent::Patch {
+            revision, changes, ..
+        }) = patch
+        else {
+            panic!("expected a patch, got {:?}", patch);
+        };
+        assert_eq!(revision, 2);
+        assert_eq!(
+            changes,
+            json!({ "groupVolumes": { "10.0.0.1": 25, "10.0.0.2": null } })
+        );
+
+        let regrouped = tracker.next(state(json!({ "10.0.0.1": 25 }), json!([])), 4);
+        assert!(matches!(
+            regrouped,
+            Some(StateEvent::Snapshot { revision: 3, .. })
+        ));
+    }
+}
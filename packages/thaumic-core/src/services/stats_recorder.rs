@@ -0,0 +1,274 @@
+//! Casting statistics event consumer.
+//!
+//! Listens on the event bus for stream and playback events and accumulates
+//! counters in the [`StatsStore`]. Play time is credited periodically so a
+//! crash loses at most one flush interval, and long casts are split across
+//! days close to midnight.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use parking_lot::Mutex;
+use tokio::sync::broadcast::error::RecvError;
+use tokio_util::sync::CancellationToken;
+
+use crate::events::{BroadcastEvent, EventReceiver, StreamEvent};
+use crate::runtime::TokioSpawner;
+use crate::state::SonosState;
+use crate::stats::{preset_label, StatsStore};
+use crate::stream::StreamRegistry;
+use crate::utils::now_millis;
+
+/// How often play time is credited and stats are written to disk.
+const FLUSH_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Playing speakers of one stream, with when each was last credited.
+#[derive(Default)]
+struct StreamActivity {
+    speakers: HashMap<String, Instant>,
+    /// When stream time was last credited, while any speaker is playing.
+    since: Option<Instant>,
+}
+
+/// Tracks play spans and credits them to the store.
+struct ActivityTracker {
+    store: Arc<StatsStore>,
+    streams: HashMap<String, StreamActivity>,
+}
+
+impl ActivityTracker {
+    fn new(store: Arc<StatsStore>) -> Self {
+        Self {
+            store,
+            streams: HashMap::new(),
+        }
+    }
+
+    fn playback_started(&mut self, stream_id: &str, speaker_ip: &str, now: Instant) {
+        let stream = self.streams.entry(stream_id.to_string()).or_default();
+        stream.speakers.entry(speaker_ip.to_string()).or_insert(now);
+        stream.since.get_or_insert(now);
+    }
+
+    fn playback_stopped(&mut self, stream_id: &str, speaker_ip: &str, now: Instant, now_ms: u64) {
+        let Some(stream) = self.streams.get_mut(stream_id) else {
+            return;
+        };
+        if let Some(since) = stream.speakers.remove(speaker_ip) {
+            self.store
+                .add_speaker_time(speaker_ip, elapsed_ms(since, now));
+        }
+        if stream.speakers.is_empty() {
+            if let Some(since) = stream.since.take() {
+                self.store.add_cast_time(now_ms, elapsed_ms(since, now));
+            }
+        }
+    }
+
+    fn stream_ended(&mut self, stream_id: &str, now: Instant, now_ms: u64) {
+        let Some(mut stream) = self.streams.remove(stream_id) else {
+            return;
+        };
+        for (ip, since) in stream.speakers.drain() {
+            self.store.add_speaker_time(&ip, elapsed_ms(since, now));
+        }
+        if let Some(since) = stream.since {
+            self.store.add_cast_time(now_ms, elapsed_ms(since, now));
+        }
+    }
+
+    /// Credits all ongoing play time up to `now`.
+    fn credit(&mut self, now: Instant, now_ms: u64) {
+        for stream in self.streams.values_mut() {
+            for (ip, since) in stream.speakers.iter_mut() {
+                self.store.add_speaker_time(ip, elapsed_ms(*since, now));
+                *since = now;
+            }
+            if let Some(since) = stream.since.as_mut() {
+                self.store.add_cast_time(now_ms, elapsed_ms(*since, now));
+                *since = now;
+            }
+        }
+    }
+}
+
+fn elapsed_ms(since: Instant, now: Instant) -> u64 {
+    now.saturating_duration_since(since).as_millis() as u64
+}
+
+/// Background service recording casting statistics.
+pub struct StatsRecorder {
+    store: Arc<StatsStore>,
+    stream_registry: Arc<StreamRegistry>,
+    sonos_state: Arc<SonosState>,
+    /// Event receiver, taken when the service starts.
+    events: Mutex<Option<EventReceiver>>,
+    cancel: CancellationToken,
+    spawner: TokioSpawner,
+}
+
+impl StatsRecorder {
+    /// Creates a new StatsRecorder.
+    ///
+    /// Note: Call `start()` to spawn the background task.
+    ///
+    /// # Arguments
+    /// * `store` - Store the counters are written to
+    /// * `stream_registry` - Stream registry for stream formats
+    /// * `sonos_state` - Sonos state for room names
+    /// * `events` - Event bus receiver (subscribe before streams can start)
+    /// * `cancel` - Cancellation token for graceful shutdown
+    /// * `spawner` - Task spawner for background tasks
+    pub fn new(
+        store: Arc<StatsStore>,
+        stream_registry: Arc<StreamRegistry>,
+        sonos_state: Arc<SonosState>,
+        events: EventReceiver,
+        cancel: CancellationToken,
+        spawner: TokioSpawner,
+    ) -> Self {
+        Self {
+            store,
+            stream_registry,
+            sonos_state,
+            events: Mutex::new(Some(events)),
+            cancel,
+            spawner,
+        }
+    }
+
+    /// Starts the background task.
+    ///
+    /// Can only be called once; subsequent calls are no-ops.
+    pub fn start(&self) {
+        let Some(mut events) = self.events.lock().take() else {
+            return;
+        };
+
+        let store = Arc::clone(&self.store);
+        let stream_registry = Arc::clone(&self.stream_registry);
+        let sonos_state = Arc::clone(&self.sonos_state);
+        let cancel = self.cancel.clone();
+        let mut tracker = ActivityTracker::new(Arc::clone(&store));
+
+        self.spawner.spawn(async move {
+            let mut interval = tokio::time::interval(FLUSH_INTERVAL);
+            // The first tick fires immediately; nothing to credit yet
+            interval.tick().await;
+
+            loop {
+                let event = tokio::select! {
+                    _ = cancel.cancelled() => break,
+                    _ = interval.tick() => {
+                        tracker.credit(Instant::now(), now_millis());
+                        flush(&store);
+                        continue;
+                    }
+                    event = events.recv() => match event {
+                        Ok(BroadcastEvent::Stream(event)) => event,
+                        Ok(_) => continue,
+                        Err(RecvError::Lagged(skipped)) => {
+                            log::warn!("[Stats] Missed {} event(s)", skipped);
+                            continue;
+                        }
+                        Err(RecvError::Closed) => break,
+                    },
+                };
+
+                let now = Instant::now();
+                match event {
+                    StreamEvent::Created { stream_id, .. } => {
+                        if let Some(stream) = stream_registry.get_stream(&stream_id) {
+                            store.record_stream(&preset_label(stream.codec, &stream.audio_format));
+                        }
+                    }
+                    StreamEvent::PlaybackStarted {
+                        stream_id,
+                        speaker_ip,
+                        ..
+                    } => {
+                        store.record_play(&speaker_ip, room_name(&sonos_state, &speaker_ip));
+                        tracker.playback_started(&stream_id, &speaker_ip, now);
+                    }
+                    StreamEvent::PlaybackStopped {
+                        stream_id,
+                        speaker_ip,
+                        ..
+                    } => tracker.playback_stopped(&stream_id, &speaker_ip, now, now_millis()),
+                    StreamEvent::Ended { stream_id, .. } => {
+                        tracker.stream_ended(&stream_id, now, now_millis())
+                    }
+                    _ => {}
+                }
+            }
+
+            // Keep the time played up to shutdown
+            tracker.credit(Instant::now(), now_millis());
+            flush(&store);
+            log::debug!("[Stats] Shutting down");
+        });
+    }
+}
+
+fn flush(store: &StatsStore) {
+    if let Err(e) = store.flush() {
+        log::warn!("[Stats] Failed to save stats: {}", e);
+    }
+}
+
+/// Returns the room name of a speaker from the current topology.
+fn room_name(sonos_state: &SonosState, speaker_ip: &str) -> Option<String> {
+    sonos_state
+        .groups
+        .read()
+        .iter()
+        .flat_map(|g| &g.members)
+        .find(|m| m.ip == speaker_ip)
+        .map(|m| m.zone_name.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOW_MS: u64 = 1_709_294_400_000;
+
+    #[test]
+    fn credits_speaker_and_stream_time() {
+        let store = Arc::new(StatsStore::new());
+        let mut tracker = ActivityTracker::new(Arc::clone(&store));
+        let t0 = Instant::now();
+        let secs = |s| t0 + Duration::from_secs(s);
+
+        tracker.playback_started("s", "10.0.0.1", t0);
+        tracker.playback_started("s", "10.0.0.2", secs(600));
+        tracker.credit(secs(1800), NOW_MS);
+        tracker.playback_stopped("s", "10.0.0.1", secs(3600), NOW_MS);
+        tracker.stream_ended("s", secs(5400), NOW_MS);
+
+        let summary = store.summary(NOW_MS);
+        let hours: HashMap<_, _> = summary
+            .speakers
+            .iter()
+            .map(|s| (s.ip.as_str(), s.hours))
+            .collect();
+        assert_eq!(hours["10.0.0.1"], 1.0);
+        assert_eq!(hours["10.0.0.2"], 1.33);
+        // Stream time counts once, however many speakers play it
+        assert_eq!(summary.total_hours, 1.5);
+    }
+
+    #[test]
+    fn idle_stream_accrues_no_time() {
+        let store = Arc::new(StatsStore::new());
+        let mut tracker = ActivityTracker::new(Arc::clone(&store));
+        let t0 = Instant::now();
+
+        tracker.playback_started("s", "10.0.0.1", t0);
+        tracker.playback_stopped("s", "10.0.0.1", t0 + Duration::from_secs(60), NOW_MS);
+        tracker.credit(t0 + Duration::from_secs(3600), NOW_MS);
+
+        assert_eq!(store.summary(NOW_MS).total_hours, 0.02);
+    }
+}
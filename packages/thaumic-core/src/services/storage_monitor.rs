@@ -0,0 +1,154 @@
+//! Data directory health monitoring.
+//!
+//! Checks the data directory when it is set, so a full or read-only disk is
+//! reported at startup rather than on the first lost setting, then watches
+//! for the directory being marked degraded by a failed write (see
+//! [`crate::storage`]). While degraded it is probed periodically, and
+//! [`ResourceEvent::StorageDegraded`] / [`ResourceEvent::StorageRecovered`]
+//! are emitted as its state changes.
+
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use parking_lot::Mutex;
+use tokio_util::sync::CancellationToken;
+
+use crate::error::{ThaumicError, ThaumicResult};
+use crate::events::{EventEmitter, ResourceEvent};
+use crate::runtime::TokioSpawner;
+use crate::storage::{self, StorageIssue};
+use crate::utils::now_millis;
+
+/// Interval between checks of the data directory.
+const CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+/// The watched directory and the state last reported for it.
+#[derive(Default)]
+struct Watched {
+    dir: Option<PathBuf>,
+    reported: Option<StorageIssue>,
+}
+
+/// Background service reporting whether the data directory can be written.
+pub struct StorageMonitor {
+    watched: Arc<Mutex<Watched>>,
+    emitter: Arc<dyn EventEmitter>,
+    cancel: CancellationToken,
+    spawner: TokioSpawner,
+    /// Guards against starting the check task twice.
+    started: AtomicBool,
+}
+
+impl StorageMonitor {
+    /// Creates a new StorageMonitor.
+    ///
+    /// Note: Call `start()` to spawn the background check task.
+    ///
+    /// # Arguments
+    /// * `emitter` - Event emitter for storage health changes
+    /// * `cancel` - Cancellation token for graceful shutdown
+    /// * `spawner` - Task spawner for background tasks
+    pub fn new(
+        emitter: Arc<dyn EventEmitter>,
+        cancel: CancellationToken,
+        spawner: TokioSpawner,
+    ) -> Self {
+        Self {
+            watched: Arc::new(Mutex::new(Watched::default())),
+            emitter,
+            cancel,
+            spawner,
+            started: AtomicBool::new(false),
+        }
+    }
+
+    /// Sets the data directory and checks that it can be written to.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ThaumicError::StorageUnavailable`] if writing a probe file
+    /// fails. Settings changes are then kept in memory only.
+    pub fn set_data_dir(&self, dir: impl AsRef<Path>) -> ThaumicResult<()> {
+        let dir = dir.as_ref().to_path_buf();
+        let probed = storage::probe(&dir);
+        {
+            let mut watched = self.watched.lock();
+            watched.dir = Some(dir.clone());
+            watched.reported = None;
+        }
+        report_changes(&self.watched, self.emitter.as_ref());
+        probed.map_err(|e| {
+            ThaumicError::StorageUnavailable(format!("{} can't be written: {}", dir.display(), e))
+        })
+    }
+
+    /// Returns why the data directory can't be written to, if it can't.
+    #[must_use]
+    pub fn issue(&self) -> Option<StorageIssue> {
+        self.watched.lock().reported
+    }
+
+    /// Starts the background check task.
+    ///
+    /// Can only be called once; subsequent calls are no-ops.
+    pub fn start(&self) {
+        if self.started.swap(true, Ordering::SeqCst) {
+            return;
+        }
+
+        let watched = Arc::clone(&self.watched);
+        let emitter = Arc::clone(&self.emitter);
+        let cancel = self.cancel.clone();
+
+        self.spawner.spawn(async move {
+            let mut interval = tokio::time::interval(CHECK_INTERVAL);
+            interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+            loop {
+                tokio::select! {
+                    _ = cancel.cancelled() => {
+                        log::debug!("[StorageMonitor] Shutting down");
+                        break;
+                    }
+                    _ = interval.tick() => {}
+                }
+
+                // Healthy directories aren't probed: failed writes mark them
+                let dir = watched.lock().dir.clone();
+                if let Some(dir) = dir.filter(|dir| storage::issue(dir).is_some()) {
+                    let _ = storage::probe(&dir);
+                }
+                report_changes(&watched, emitter.as_ref());
+            }
+        });
+    }
+}
+
+/// Emits an event if the directory's state changed since last reported.
+fn report_changes(watched: &Mutex<Watched>, emitter: &dyn EventEmitter) {
+    let mut watched = watched.lock();
+    let Some(dir) = watched.dir.clone() else {
+        return;
+    };
+    let issue = storage::issue(&dir);
+    if issue == watched.reported {
+        return;
+    }
+    watched.reported = issue;
+    drop(watched);
+
+    let path = dir.display().to_string();
+    emitter.emit_resource(match issue {
+        Some(issue) => ResourceEvent::StorageDegraded {
+            path,
+            issue,
+            timestamp: now_millis(),
+        },
+        None => ResourceEvent::StorageRecovered {
+            path,
+            timestamp: now_millis(),
+        },
+    });
+}
@@ -5,32 +5,42 @@
 //! - Start/stop playback on Sonos speakers (supports multi-group)
 //! - Track which streams are playing on which speakers
 //! - Track expected stream URLs for source change detection
+//! - Suspend sessions while another source plays, and resume them after
+//! - Apply the external stop policy to speakers stopped from the Sonos app
+//! - Guard PIN-locked streams and alert on changes made around the lock
 //! - Broadcast stream lifecycle events to WebSocket clients
 
+use std::collections::HashSet;
+use std::net::IpAddr;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 use bytes::Bytes;
+use dashmap::{DashMap, DashSet};
 use parking_lot::Mutex;
 use tokio::sync::Notify;
 
 use crate::capture::{AudioSink, AudioSource, BufferFlags, CaptureHandle};
 
 use crate::context::NetworkContext;
-use crate::error::ThaumicResult;
-use crate::events::{EventEmitter, SpeakerRemovalReason, StreamEvent};
+use crate::enrichment::MetadataEnricher;
+use crate::error::{SoapResult, ThaumicError, ThaumicResult};
+use crate::events::{EventEmitter, LockedSpeakerChange, SpeakerRemovalReason, StreamEvent};
+use crate::sonos::compat;
 use crate::sonos::subscription_arbiter::SubscriptionArbiter;
-use crate::sonos::types::TransportState;
+use crate::sonos::types::{DeviceRole, SpeakerMove, TransportState};
 use crate::sonos::utils::build_sonos_stream_uri;
 use crate::sonos::SonosPlayback;
-use crate::state::{SonosState, StreamingConfig};
+use crate::state::{rekey_by_ip, ExternalStopPolicy, SonosState, StreamingConfig};
 use crate::stream::{
-    AudioCodec, AudioFormat, CleanupOrder, StreamMetadata, StreamRegistry, StreamState,
+    feedback, AudioCodec, AudioFormat, CleanupOrder, FeedbackCause, PinCheck, StartupStage,
+    StreamMetadata, StreamRegistry, StreamState,
 };
 use crate::utils::now_millis;
 
 use super::playback_session_store::{
-    GroupRole, PlaybackResult, PlaybackSession, PlaybackSessionStore,
+    GroupRole, PlaybackResult, PlaybackSession, PlaybackSessionInfo, PlaybackSessionStore,
 };
 use super::sync_group_manager::SyncGroupManager;
 use super::volume_router::VolumeRouter;
@@ -124,6 +134,28 @@ struct SinglePlaybackParams<'a> {
     metadata: Option<&'a StreamMetadata>,
 }
 
+/// How long after a takeover transport states are ignored for resuming.
+///
+/// The NOTIFY that reports the new source can also carry a stopped or paused
+/// state while the other source is still starting.
+const TAKEOVER_SETTLE: Duration = Duration::from_secs(2);
+
+/// Longest stream label accepted, in characters.
+pub const MAX_STREAM_LABEL_CHARS: usize = 64;
+
+/// Accepted stream PIN lengths, in digits.
+pub const STREAM_PIN_DIGITS: std::ops::RangeInclusive<usize> = 4..=8;
+
+/// How long after an authorized API change to a locked speaker its NOTIFYs
+/// are taken as echoes of that change rather than alerts.
+const LOCK_ECHO_WINDOW: Duration = Duration::from_secs(3);
+
+/// A session set aside while another source plays on its speaker.
+struct SuspendedPlayback {
+    session: PlaybackSession,
+    since: Instant,
+}
+
 /// Service responsible for stream lifecycle and playback orchestration.
 pub struct StreamCoordinator {
     /// Sonos client for playback control.
@@ -139,6 +171,25 @@ pub struct StreamCoordinator {
     emitter: Arc<dyn EventEmitter>,
     /// Sync group lifecycle manager.
     sync_group: SyncGroupManager,
+    /// Fills in sparse tab metadata (disabled unless configured).
+    enricher: Option<Arc<MetadataEnricher>>,
+    /// Sessions suspended by another source, keyed by speaker IP.
+    suspended: DashMap<String, SuspendedPlayback>,
+    /// How long suspended sessions wait to resume (`None` ends them).
+    takeover_window: Option<Duration>,
+    /// What to do when a speaker is stopped from the Sonos app.
+    external_stop: ExternalStopPolicy,
+    /// How long [`ExternalStopPolicy::Ask`] holds a stopped session.
+    external_stop_window: Duration,
+    /// Speakers stopped from the Sonos app awaiting confirmation to resume,
+    /// with when they stopped.
+    stop_confirmations: DashMap<String, Instant>,
+    /// Speakers we are stopping ourselves, whose `Stopped` is not external.
+    stopping: DashSet<String>,
+    /// Speakers we paused ourselves (keeping their session), with when.
+    pausing: DashMap<String, Instant>,
+    /// Speakers of locked streams last changed through the API, with when.
+    authorized_changes: DashMap<String, Instant>,
 }
 
 impl StreamCoordinator {
@@ -160,6 +211,12 @@ impl StreamCoordinator {
         arbiter: Arc<SubscriptionArbiter>,
     ) -> Self {
         let sessions = Arc::new(PlaybackSessionStore::new());
+        let takeover_window = streaming_config
+            .takeover_resume_secs
+            .map(Duration::from_secs);
+        let coordinator_preferences = streaming_config.coordinator.clone();
+        let external_stop = streaming_config.external_stop;
+        let external_stop_window = Duration::from_secs(streaming_config.external_stop_confirm_secs);
         let stream_registry = Arc::new(StreamRegistry::new(streaming_config));
         let sync_group = SyncGroupManager::new(
             Arc::clone(&sessions),
@@ -169,6 +226,7 @@ impl StreamCoordinator {
             arbiter,
             Arc::clone(&stream_registry),
             network.clone(),
+            coordinator_preferences,
         );
         Self {
             sonos,
@@ -178,6 +236,15 @@ impl StreamCoordinator {
             sessions,
             emitter,
             sync_group,
+            enricher: None,
+            suspended: DashMap::new(),
+            takeover_window,
+            external_stop,
+            external_stop_window,
+            stop_confirmations: DashMap::new(),
+            stopping: DashSet::new(),
+            pausing: DashMap::new(),
+            authorized_changes: DashMap::new(),
         }
     }
 
@@ -186,6 +253,11 @@ impl StreamCoordinator {
         self.sync_group.set_topology_refresh(notify);
     }
 
+    /// Sets the enricher used to fill in sparse tab metadata.
+    pub fn set_metadata_enricher(&mut self, enricher: Arc<MetadataEnricher>) {
+        self.enricher = Some(enricher);
+    }
+
     /// Emits a stream event to all listeners.
     fn emit_event(&self, event: StreamEvent) {
         self.emitter.emit_stream(event);
@@ -442,6 +514,8 @@ impl StreamCoordinator {
         // Find and remove ALL playback sessions for this stream (multi-group support)
         let removed = self.sessions.remove_all_for_stream(stream_id);
         let speaker_ips: Vec<String> = removed.iter().map(|s| s.speaker_ip.clone()).collect();
+        self.suspended
+            .retain(|_, suspended| suspended.session.stream_id != stream_id);
 
         self.stream_registry.remove_stream(stream_id);
 
@@ -451,6 +525,8 @@ impl StreamCoordinator {
             timestamp: now_millis(),
         });
 
+        #[cfg(debug_assertions)]
+        self.check_invariants("remove_stream");
         speaker_ips
     }
 
@@ -477,6 +553,8 @@ impl StreamCoordinator {
                 self.stream_registry.remove_stream(stream_id);
                 self.sync_group.stop_speakers(&speaker_ips).await;
                 self.sessions.remove_all_for_stream(stream_id);
+                self.suspended
+                    .retain(|_, suspended| suspended.session.stream_id != stream_id);
                 self.emit_event(StreamEvent::Ended {
                     stream_id: stream_id.to_string(),
                     timestamp: now_millis(),
@@ -487,6 +565,35 @@ impl StreamCoordinator {
                 self.remove_stream(stream_id);
             }
         }
+        #[cfg(debug_assertions)]
+        self.check_invariants("remove_stream_async");
+    }
+
+    /// Stops a stream no speaker has pulled for `idle`.
+    ///
+    /// Emits `ListenersLost` ahead of the usual cleanup and `Ended` event.
+    pub async fn end_idle_stream(&self, stream_id: &str, idle: Duration) {
+        log::info!(
+            "Stopping stream {}: no listeners for {}s",
+            stream_id,
+            idle.as_secs()
+        );
+        self.emit_event(StreamEvent::ListenersLost {
+            stream_id: stream_id.to_string(),
+            idle_secs: idle.as_secs(),
+            timestamp: now_millis(),
+        });
+        self.remove_stream_async(stream_id).await;
+    }
+
+    /// Returns whether a speaker of the stream is suspended by another source.
+    ///
+    /// Such a stream has no listeners until the other source stops, and the
+    /// takeover window decides its fate instead.
+    pub fn has_suspended_playback(&self, stream_id: &str) -> bool {
+        self.suspended
+            .iter()
+            .any(|entry| entry.session.stream_id == stream_id)
     }
 
     /// Gets a stream by ID.
@@ -500,16 +607,276 @@ impl StreamCoordinator {
     /// `Some(false)` if the stream exists but this wasn't the first frame,
     /// `None` if the stream was not found.
     pub fn push_frame(&self, stream_id: &str, data: Bytes) -> Option<bool> {
-        self.stream_registry
+        let stream = self.stream_registry.get_stream(stream_id)?;
+
+        if let Some(delay_ms) = stream.check_feedback(&data) {
+            log::warn!(
+                "[StreamCoordinator] Stream {} repeats itself every {}ms, likely a feedback loop",
+                stream_id,
+                delay_ms
+            );
+            self.emit_event(StreamEvent::FeedbackLoopSuspected {
+                stream_id: stream_id.to_string(),
+                cause: FeedbackCause::Audio,
+                loop_delay_ms: Some(delay_ms),
+                timestamp: now_millis(),
+            });
+        }
+
+        Some(stream.push_frame(data))
+    }
+
+    /// Sets or clears (`None` or blank) a stream's user-given label.
+    ///
+    /// Returns the stored label, trimmed. Emits
+    /// [`StreamEvent::LabelChanged`] when it changes.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the stream doesn't exist or the label is too long
+    /// or contains control characters.
+    pub fn set_stream_label(
+        &self,
+        stream_id: &str,
+        label: Option<&str>,
+    ) -> ThaumicResult<Option<String>> {
+        let stream = self
+            .stream_registry
+            .get_stream(stream_id)
+            .ok_or_else(|| ThaumicError::StreamNotFound(stream_id.to_string()))?;
+        let label = normalize_label(label).map_err(ThaumicError::InvalidRequest)?;
+
+        if stream.set_label(label.clone()) {
+            log::info!(
+                "[StreamCoordinator] Stream {} labelled {:?}",
+                stream_id,
+                label
+            );
+            self.emit_event(StreamEvent::LabelChanged {
+                stream_id: stream_id.to_string(),
+                label: label.clone(),
+                timestamp: now_millis(),
+            });
+        }
+        Ok(label)
+    }
+
+    /// Locks a stream so that controlling its speakers through the API
+    /// requires `pin`.
+    ///
+    /// The connection casting the stream is not held to the lock. Changes
+    /// made from the Sonos app can't be blocked; they are reported as
+    /// [`StreamEvent::LockedSpeakerChanged`] instead.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the stream doesn't exist, the PIN isn't 4 to 8
+    /// digits, or the stream is already locked.
+    pub fn lock_stream(&self, stream_id: &str, pin: &str) -> ThaumicResult<()> {
+        let stream = self
+            .stream_registry
+            .get_stream(stream_id)
+            .ok_or_else(|| ThaumicError::StreamNotFound(stream_id.to_string()))?;
+        validate_pin(pin).map_err(ThaumicError::InvalidRequest)?;
+        if stream.is_locked() {
+            return Err(ThaumicError::StreamLocked(format!(
+                "{} is already locked",
+                stream_id
+            )));
+        }
+        stream.set_pin(Some(pin.to_string()));
+        log::info!("[StreamCoordinator] Stream {} locked", stream_id);
+        Ok(())
+    }
+
+    /// Unlocks a stream locked with `pin`. Unlocking an unlocked stream
+    /// succeeds.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the stream doesn't exist or the PIN is wrong.
+    pub fn unlock_stream(&self, stream_id: &str, pin: &str) -> ThaumicResult<()> {
+        let stream = self
+            .stream_registry
             .get_stream(stream_id)
-            .map(|stream| stream.push_frame(data))
+            .ok_or_else(|| ThaumicError::StreamNotFound(stream_id.to_string()))?;
+        require_pin(&stream, Some(pin), || {
+            format!("Wrong PIN for {}", stream_id)
+        })?;
+        if stream.is_locked() {
+            stream.set_pin(None);
+            log::info!("[StreamCoordinator] Stream {} unlocked", stream_id);
+        }
+        Ok(())
+    }
+
+    /// Checks that a request may control `speaker_ip`.
+    ///
+    /// Speakers not playing a locked stream are always allowed. Otherwise
+    /// `pin` must match, unless the request comes from the connection
+    /// casting the stream (`own_stream`). Allowed changes to locked speakers
+    /// are remembered so their NOTIFYs don't raise alerts.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ThaumicError::StreamLocked`] if the PIN is missing or wrong.
+    pub fn authorize_speaker(
+        &self,
+        speaker_ip: &str,
+        pin: Option<&str>,
+        own_stream: Option<&str>,
+    ) -> ThaumicResult<()> {
+        let Some(stream) = self.locked_stream_for(speaker_ip) else {
+            return Ok(());
+        };
+        if own_stream != Some(stream.id.as_str()) {
+            require_pin(&stream, pin, || {
+                format!("{} plays locked stream {}", speaker_ip, stream.id)
+            })
+            .inspect_err(|_| {
+                log::warn!(
+                    "[StreamCoordinator] Refused change to {} without the PIN of stream {}",
+                    speaker_ip,
+                    stream.id
+                );
+            })?;
+        }
+        self.authorized_changes
+            .insert(speaker_ip.to_string(), Instant::now());
+        Ok(())
+    }
+
+    /// Checks that a request may stop or replace stream `stream_id`; see
+    /// [`Self::authorize_speaker`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ThaumicError::StreamLocked`] if the PIN is missing or wrong.
+    pub fn authorize_stream(
+        &self,
+        stream_id: &str,
+        pin: Option<&str>,
+        own_stream: Option<&str>,
+    ) -> ThaumicResult<()> {
+        let Some(stream) = self.stream_registry.get_stream(stream_id) else {
+            return Ok(());
+        };
+        if own_stream != Some(stream_id) {
+            require_pin(&stream, pin, || format!("{} is locked", stream_id))?;
+        }
+        for speaker_ip in self.sessions.get_ips_for_stream(stream_id) {
+            self.authorized_changes.insert(speaker_ip, Instant::now());
+        }
+        Ok(())
+    }
+
+    /// Reports a change seen on a speaker's NOTIFYs, emitting
+    /// [`StreamEvent::LockedSpeakerChanged`] if the speaker plays a locked
+    /// stream and the change wasn't made through the API.
+    ///
+    /// Call before the change is otherwise handled, as stops and source
+    /// changes end the speaker's session.
+    ///
+    /// # Returns
+    /// `true` if an alert was emitted.
+    pub fn report_speaker_change(&self, speaker_ip: &str, change: LockedSpeakerChange) -> bool {
+        if self.is_own_stop(speaker_ip) {
+            return false;
+        }
+        let Some(stream) = self.locked_stream_for(speaker_ip) else {
+            return false;
+        };
+        let authorized = self
+            .authorized_changes
+            .get(speaker_ip)
+            .is_some_and(|at| at.elapsed() < LOCK_ECHO_WINDOW);
+        if authorized {
+            return false;
+        }
+
+        log::warn!(
+            "[StreamCoordinator] {:?} change on {} outside the lock of stream {}",
+            change,
+            speaker_ip,
+            stream.id
+        );
+        self.emit_event(StreamEvent::LockedSpeakerChanged {
+            stream_id: stream.id.clone(),
+            speaker_ip: speaker_ip.to_string(),
+            change,
+            timestamp: now_millis(),
+        });
+        true
+    }
+
+    /// Whether a speaker's `Stopped` comes from a stop or pause we sent.
+    fn is_own_stop(&self, speaker_ip: &str) -> bool {
+        self.stopping.contains(speaker_ip)
+            || self
+                .pausing
+                .get(speaker_ip)
+                .is_some_and(|at| at.elapsed() < LOCK_ECHO_WINDOW)
+    }
+
+    /// Returns the stream a speaker plays if that stream is locked.
+    fn locked_stream_for(&self, speaker_ip: &str) -> Option<Arc<StreamState>> {
+        let session = self.sessions.get_by_speaker_ip(speaker_ip)?;
+        self.stream_registry
+            .get_stream(&session.stream_id)
+            .filter(|stream| stream.is_locked())
     }
 
     /// Updates metadata for a stream.
+    ///
+    /// Warns if the metadata shows the captured tab is playing one of our
+    /// own streams. Otherwise, sparse metadata is enriched in the background
+    /// when an enricher is configured.
     pub fn update_metadata(&self, stream_id: &str, metadata: StreamMetadata) {
-        if let Some(stream) = self.stream_registry.get_stream(stream_id) {
-            stream.update_metadata(metadata);
+        let Some(stream) = self.stream_registry.get_stream(stream_id) else {
+            return;
+        };
+
+        let server_host = format!(
+            "{}:{}",
+            self.network.get_local_ip(),
+            self.network.get_port()
+        );
+        let was_own_stream = metadata_mentions_own_stream(&stream.metadata.read(), &server_host);
+        let is_own_stream = metadata_mentions_own_stream(&metadata, &server_host);
+        stream.update_metadata(metadata);
+
+        // Warn on the transition only; metadata updates repeat on every track
+        if is_own_stream && !was_own_stream {
+            log::warn!(
+                "[StreamCoordinator] Stream {} is capturing a tab that plays our own stream",
+                stream_id
+            );
+            self.emit_event(StreamEvent::FeedbackLoopSuspected {
+                stream_id: stream_id.to_string(),
+                cause: FeedbackCause::Metadata,
+                loop_delay_ms: None,
+                timestamp: now_millis(),
+            });
+        }
+
+        if let Some(enricher) = self.enricher.as_ref().filter(|_| !is_own_stream) {
+            let emitter = Arc::clone(&self.emitter);
+            let id = stream_id.to_string();
+            enricher.enrich_stream(Arc::clone(&stream), move |metadata| {
+                emitter.emit_stream(StreamEvent::MetadataUpdated {
+                    stream_id: id,
+                    metadata: metadata.clone(),
+                    timestamp: now_millis(),
+                });
+            });
         }
+
+        let metadata = stream.metadata.read().clone();
+        self.emit_event(StreamEvent::MetadataUpdated {
+            stream_id: stream_id.to_string(),
+            metadata,
+            timestamp: now_millis(),
+        });
     }
 
     /// Starts playback of a stream on multiple Sonos speakers.
@@ -527,6 +894,8 @@ impl StreamCoordinator {
     ///
     /// Returns results for each speaker (best-effort: continues on individual failures).
     /// Each successful speaker gets a `StreamEvent::PlaybackStarted` event.
+    /// Members the topology marks as non-playable (bonded satellites,
+    /// subwoofers) fail without being contacted.
     ///
     /// # Arguments
     /// * `speaker_ips` - IP addresses of the Sonos speakers
@@ -541,6 +910,95 @@ impl StreamCoordinator {
         metadata: Option<&StreamMetadata>,
         artwork_url: &str,
         sync_speakers: bool,
+    ) -> Vec<PlaybackResult> {
+        let (playable, mut rejected) = self.resolve_cast_targets(speaker_ips, stream_id);
+
+        // A new cast replaces whatever the speaker was suspended from
+        for ip in &playable {
+            if let Some((_, suspended)) = self.suspended.remove(ip) {
+                if suspended.session.stream_id != stream_id {
+                    self.end_session(&suspended.session.stream_id, ip, None);
+                }
+            }
+        }
+
+        let mut results = self
+            .start_playable(&playable, stream_id, metadata, artwork_url, sync_speakers)
+            .await;
+        results.append(&mut rejected);
+        #[cfg(debug_assertions)]
+        self.check_invariants("start_playback_multi");
+        results
+    }
+
+    /// Splits targets into speakers that can be cast to and failure results
+    /// for those that can't.
+    ///
+    /// Members of a bonded set are replaced by the set's primary, so casting
+    /// to one half of a stereo pair plays on the whole pair. S1 speakers are
+    /// rejected if they can't play the stream's format, rather than left to
+    /// fail silently.
+    fn resolve_cast_targets(
+        &self,
+        speaker_ips: &[String],
+        stream_id: &str,
+    ) -> (Vec<String>, Vec<PlaybackResult>) {
+        let mut playable: Vec<String> = Vec::with_capacity(speaker_ips.len());
+        let mut rejected = Vec::new();
+        let s1_incompatibility = self
+            .get_stream(stream_id)
+            .and_then(|stream| compat::s1_incompatibility(stream.codec, &stream.audio_format));
+
+        for ip in speaker_ips {
+            let role = self.sonos_state.get_member_by_ip(ip).map(|m| m.role);
+            let target = match role {
+                Some(DeviceRole::Player) | None => ip.clone(),
+                Some(DeviceRole::Bridge) => {
+                    rejected.push(unplayable_result(ip, "Device has no audio output"));
+                    continue;
+                }
+                Some(DeviceRole::Satellite) | Some(DeviceRole::Subwoofer) => {
+                    match self.sonos_state.get_bond_primary_ip(ip) {
+                        Some(primary_ip) => {
+                            log::info!(
+                                "[Playback] {} is bonded to {}, casting to the set instead",
+                                ip,
+                                primary_ip
+                            );
+                            primary_ip
+                        }
+                        None => {
+                            rejected.push(unplayable_result(
+                                ip,
+                                "Speaker is bonded to another speaker; cast to its room instead",
+                            ));
+                            continue;
+                        }
+                    }
+                }
+            };
+            if let Some(reason) = &s1_incompatibility {
+                if self.sonos_state.is_s1_speaker(&target) {
+                    rejected.push(unplayable_result(ip, reason));
+                    continue;
+                }
+            }
+            if !playable.contains(&target) {
+                playable.push(target);
+            }
+        }
+
+        (playable, rejected)
+    }
+
+    /// Starts playback on speakers already known to be castable.
+    async fn start_playable(
+        &self,
+        speaker_ips: &[String],
+        stream_id: &str,
+        metadata: Option<&StreamMetadata>,
+        artwork_url: &str,
+        sync_speakers: bool,
     ) -> Vec<PlaybackResult> {
         // Handle empty case
         if speaker_ips.is_empty() {
@@ -560,8 +1018,8 @@ impl StreamCoordinator {
             .map(|s| s.audio_format)
             .unwrap_or_default();
 
-        let url_builder = self.network.url_builder();
-        let stream_url = url_builder.stream_url(stream_id);
+        // Each speaker fetches the stream from our address on its subnet
+        let stream_url_for = |ip: &str| self.network.url_builder_for(ip).stream_url(stream_id);
 
         // Single speaker: no grouping needed, use direct playback
         if speaker_ips.len() == 1 {
@@ -569,7 +1027,7 @@ impl StreamCoordinator {
                 .start_single_playback(SinglePlaybackParams {
                     speaker_ip: &speaker_ips[0],
                     stream_id,
-                    stream_url: &stream_url,
+                    stream_url: &stream_url_for(&speaker_ips[0]),
                     codec,
                     audio_format: &audio_format,
                     artwork_url,
@@ -600,13 +1058,16 @@ impl StreamCoordinator {
                 );
             }
 
+            let stream_urls: Vec<String> =
+                speaker_ips.iter().map(|ip| stream_url_for(ip)).collect();
             let futures: Vec<_> = speaker_ips
                 .iter()
-                .map(|speaker_ip| {
+                .zip(&stream_urls)
+                .map(|(speaker_ip, stream_url)| {
                     self.start_single_playback(SinglePlaybackParams {
                         speaker_ip,
                         stream_id,
-                        stream_url: &stream_url,
+                        stream_url,
                         codec,
                         audio_format: &audio_format,
                         artwork_url,
@@ -632,7 +1093,7 @@ impl StreamCoordinator {
             .start_single_playback(SinglePlaybackParams {
                 speaker_ip: &coordinator_ip,
                 stream_id,
-                stream_url: &stream_url,
+                stream_url: &stream_url_for(&coordinator_ip),
                 codec,
                 audio_format: &audio_format,
                 artwork_url,
@@ -691,6 +1152,11 @@ impl StreamCoordinator {
     }
 
     /// Starts playback on a single speaker.
+    ///
+    /// Play is sent as soon as `SetAVTransportURI` succeeds. The stream keeps
+    /// buffering audio throughout, and the HTTP handler counts that audio
+    /// towards its prefill, so the speaker's GET isn't delayed by a full
+    /// buffer's worth after the SOAP round trips.
     async fn start_single_playback(&self, params: SinglePlaybackParams<'_>) -> PlaybackResult {
         let SinglePlaybackParams {
             speaker_ip,
@@ -797,18 +1263,36 @@ impl StreamCoordinator {
 
         log::info!("Starting playback: {} -> {}", speaker_ip, stream_url);
 
-        match self
-            .sonos
-            .play_uri(
-                speaker_ip,
-                stream_url,
-                codec,
-                audio_format,
-                metadata,
-                artwork_url,
-            )
-            .await
-        {
+        // Time each SOAP step of the cold start against the stream's creation
+        let startup = self
+            .stream_registry
+            .get_stream(stream_id)
+            .zip(speaker_ip.parse::<IpAddr>().ok());
+        let record_startup = |stage| {
+            if let Some((stream, ip)) = &startup {
+                stream.record_startup(*ip, stage);
+            }
+        };
+
+        record_startup(StartupStage::PlaybackRequested);
+        let started = async {
+            self.sonos
+                .set_transport_uri(
+                    speaker_ip,
+                    stream_url,
+                    codec,
+                    audio_format,
+                    metadata,
+                    artwork_url,
+                )
+                .await?;
+            record_startup(StartupStage::TransportUriSet);
+            self.sonos.play(speaker_ip).await?;
+            record_startup(StartupStage::PlayAcknowledged);
+            SoapResult::Ok(())
+        };
+
+        match started.await {
             Ok(()) => {
                 // Look up speaker's UUID for cleanup operations
                 let coordinator_uuid = self.sonos_state.get_member_uuid_by_ip(speaker_ip);
@@ -830,6 +1314,7 @@ impl StreamCoordinator {
                     coordinator_ip: None,
                     coordinator_uuid,
                     original_coordinator_uuid,
+                    artwork_url: Some(artwork_url.to_string()),
                 });
 
                 // Broadcast playback started event
@@ -926,6 +1411,15 @@ impl StreamCoordinator {
         speaker_ip: &str,
         reason: Option<SpeakerRemovalReason>,
     ) -> Vec<String> {
+        // A suspended speaker plays another source now; just forget the session
+        if let Some((_, suspended)) = self
+            .suspended
+            .remove_if(speaker_ip, |_, s| s.session.stream_id == stream_id)
+        {
+            self.end_session(&suspended.session.stream_id, speaker_ip, reason);
+            return vec![speaker_ip.to_string()];
+        }
+
         // Guard: session must exist, otherwise emit failure so client can clear pending state
         if self.sessions.get(stream_id, speaker_ip).is_none() {
             log::warn!(
@@ -943,9 +1437,29 @@ impl StreamCoordinator {
             return Vec::new();
         }
 
-        self.sync_group
+        self.stop_confirmations.remove(speaker_ip);
+        self.stopping.insert(speaker_ip.to_string());
+        let stopped = self
+            .sync_group
             .stop_speaker_for_stream(stream_id, speaker_ip, reason)
-            .await
+            .await;
+        self.stopping.remove(speaker_ip);
+        #[cfg(debug_assertions)]
+        self.check_invariants("stop_playback_speaker");
+        stopped
+    }
+
+    /// Pauses a speaker, keeping its session so Play picks the stream up again.
+    ///
+    /// Live streams are paused with Stop; the speaker is marked first so the
+    /// resulting `Stopped` NOTIFY isn't handled as an external stop or a
+    /// change outside a stream lock.
+    pub async fn pause_speaker(&self, speaker_ip: &str) -> crate::error::SoapResult<()> {
+        self.stop_confirmations.remove(speaker_ip);
+        self.pausing.insert(speaker_ip.to_string(), Instant::now());
+        self.sonos.stop(speaker_ip).await.inspect_err(|_| {
+            self.pausing.remove(speaker_ip);
+        })
     }
 
     /// Gets all active playback sessions.
@@ -953,6 +1467,79 @@ impl StreamCoordinator {
         self.sessions.all_sessions()
     }
 
+    /// Gets all active playback sessions with their streams' current
+    /// metadata, so UIs can show the page being cast.
+    pub fn get_session_infos(&self) -> Vec<PlaybackSessionInfo> {
+        self.sessions
+            .all_sessions()
+            .into_iter()
+            .map(|session| {
+                let stream = self
+                    .stream_registry
+                    .get_stream(&session.stream_id)
+                    .map(|stream| (stream.label(), stream.metadata.read().clone()));
+                let (label, metadata) = stream.unzip();
+                PlaybackSessionInfo {
+                    session,
+                    label: label.flatten(),
+                    metadata,
+                }
+            })
+            .collect()
+    }
+
+    /// Returns whether any speaker plays the stream or is suspended from it.
+    pub fn has_playback(&self, stream_id: &str) -> bool {
+        self.sessions.has_sessions_for_stream(stream_id) || self.has_suspended_playback(stream_id)
+    }
+
+    /// Returns `(stream_id, speaker_ip)` of every session, suspended ones included.
+    pub fn session_keys(&self) -> HashSet<(String, String)> {
+        self.sessions
+            .all_sessions()
+            .into_iter()
+            .map(|s| (s.stream_id, s.speaker_ip))
+            .chain(self.suspended.iter().map(|entry| {
+                (
+                    entry.session.stream_id.clone(),
+                    entry.session.speaker_ip.clone(),
+                )
+            }))
+            .collect()
+    }
+
+    /// Drops sessions, suspended ones included, whose stream no longer exists.
+    ///
+    /// Stream removal takes its sessions along, so any found here leaked.
+    /// Returns the speaker IPs whose sessions were dropped.
+    pub fn remove_orphaned_sessions(&self) -> Vec<String> {
+        let mut removed = Vec::new();
+        for session in self.sessions.all_sessions() {
+            if self
+                .stream_registry
+                .get_stream(&session.stream_id)
+                .is_none()
+                && self
+                    .sessions
+                    .remove(&session.stream_id, &session.speaker_ip)
+                    .is_some()
+            {
+                removed.push(session.speaker_ip);
+            }
+        }
+        self.suspended.retain(|ip, suspended| {
+            let orphaned = self
+                .stream_registry
+                .get_stream(&suspended.session.stream_id)
+                .is_none();
+            if orphaned {
+                removed.push(ip.clone());
+            }
+            !orphaned
+        });
+        removed
+    }
+
     /// Stops all playback and clears all streams.
     ///
     /// This performs a complete cleanup by calling `remove_stream_async()` for
@@ -996,18 +1583,23 @@ impl StreamCoordinator {
     /// Handles a source change event for a speaker.
     ///
     /// When a speaker switches to another source (Spotify, AirPlay, etc.),
-    /// this cleans up the playback session and emits `PlaybackStopped`.
-    /// If this was the last speaker for the stream, the stream is also ended.
+    /// its playback session is suspended and `PlaybackSuspended` is emitted.
+    /// If the other source stops within the takeover window, the stream
+    /// resumes on the speaker; otherwise the session ends with
+    /// `PlaybackStopped`, and the stream too if this was its last speaker.
+    /// Sync group members, and every speaker when the window is disabled, end
+    /// right away.
     ///
     /// Unlike `stop_playback_speaker`, this does NOT send a SOAP stop command
     /// since the speaker has already stopped playing our stream.
     ///
     /// # Arguments
     /// * `speaker_ip` - IP address of the speaker that changed source
+    /// * `current_uri` - URI of the source now playing
     ///
     /// # Returns
-    /// `true` if a session was found and removed, `false` otherwise.
-    pub fn handle_source_changed(&self, speaker_ip: &str) -> bool {
+    /// `true` if a session was found and suspended or removed, `false` otherwise.
+    pub fn handle_source_changed(self: &Arc<Self>, speaker_ip: &str, current_uri: &str) -> bool {
         // Find the session for this speaker
         let Some(key) = self.sessions.get_key_by_speaker_ip(speaker_ip) else {
             log::debug!(
@@ -1017,56 +1609,469 @@ impl StreamCoordinator {
             return false;
         };
 
-        let stream_id = key.stream_id.clone();
-
         // Remove the session (no SOAP command needed - speaker already stopped)
-        self.sessions.remove(&key.stream_id, &key.speaker_ip);
+        let Some(session) = self.sessions.remove(&key.stream_id, &key.speaker_ip) else {
+            return false;
+        };
+        let stream_id = key.stream_id;
+
+        let window = self
+            .takeover_window
+            .filter(|_| session.role == GroupRole::Coordinator);
+        let Some(window) = window else {
+            log::info!(
+                "Removed playback session for {} due to source change (stream: {})",
+                speaker_ip,
+                stream_id
+            );
+            self.end_session(
+                &stream_id,
+                speaker_ip,
+                Some(SpeakerRemovalReason::SourceChanged),
+            );
+            return true;
+        };
 
+        let since = Instant::now();
+        self.suspended
+            .insert(speaker_ip.to_string(), SuspendedPlayback { session, since });
         log::info!(
-            "Removed playback session for {} due to source change (stream: {})",
+            "Suspended playback on {} while another source plays (stream: {}, resumes within {}s)",
             speaker_ip,
-            stream_id
+            stream_id,
+            window.as_secs()
         );
-
-        // Emit PlaybackStopped with source_changed reason
-        self.emit_event(StreamEvent::PlaybackStopped {
-            stream_id: stream_id.clone(),
+        #[cfg(debug_assertions)]
+        self.check_invariants("handle_source_changed");
+        self.emit_event(StreamEvent::PlaybackSuspended {
+            stream_id,
             speaker_ip: speaker_ip.to_string(),
-            reason: Some(SpeakerRemovalReason::SourceChanged),
+            current_uri: current_uri.to_string(),
+            resume_window_secs: window.as_secs(),
             timestamp: now_millis(),
         });
 
-        // Clean up stream if this was the last session
-        self.sync_group.cleanup_stream_if_no_sessions(&stream_id);
+        let coordinator = Arc::clone(self);
+        let speaker_ip = speaker_ip.to_string();
+        tokio::spawn(async move {
+            tokio::time::sleep(window).await;
+            if let Some((_, expired)) = coordinator
+                .suspended
+                .remove_if(&speaker_ip, |_, s| s.since == since)
+            {
+                log::info!(
+                    "Other source kept {} past the takeover window, ending its session",
+                    speaker_ip
+                );
+                coordinator.end_session(
+                    &expired.session.stream_id,
+                    &speaker_ip,
+                    Some(SpeakerRemovalReason::SourceChanged),
+                );
+            }
+        });
 
         true
     }
 
-    /// Handles HTTP resume event from a speaker.
+    /// Moves the playback sessions and pending stop or resume state of
+    /// speakers that changed address to their new IP.
     ///
-    /// Called by the HTTP layer when a speaker reconnects to an existing stream.
-    /// This is the only place that sends Play commands on HTTP resume, maintaining
-    /// separation of concerns (HTTP layer serves audio, coordinator controls playback).
-    ///
-    /// # Arguments
-    /// * `speaker_ip` - IP address of the speaker that resumed HTTP connection
-    ///
-    /// # Returns
-    /// `true` if a Play command was sent, `false` otherwise.
-    pub async fn on_http_resume(&self, speaker_ip: &str) -> bool {
-        match self.ensure_playing(speaker_ip).await {
-            Ok(sent_play) => sent_play,
-            Err(e) => {
-                log::warn!(
-                    "[Resume] Play command on HTTP resume failed for {}: {}",
-                    speaker_ip,
-                    e
-                );
-                true // Play was attempted even though it failed
-            }
+    /// Speakers keep fetching the stream from us after a DHCP change, so
+    /// playback goes on; only our bookkeeping needs to follow them.
+    pub fn rekey_speakers(&self, moves: &[SpeakerMove]) {
+        let moved = self.sessions.rekey_speakers(moves);
+        if moved > 0 {
+            log::info!(
+                "[StreamCoordinator] Moved {} playback session(s) to new speaker addresses",
+                moved
+            );
         }
-    }
-}
+
+        rekey_by_ip(&self.suspended, moves);
+        for m in moves {
+            if let Some(mut suspended) = self.suspended.get_mut(&m.new_ip) {
+                suspended.session.speaker_ip = m.new_ip.clone();
+            }
+        }
+        rekey_by_ip(&self.stop_confirmations, moves);
+        rekey_by_ip(&self.pausing, moves);
+        rekey_by_ip(&self.authorized_changes, moves);
+        let stopping: Vec<&SpeakerMove> = moves
+            .iter()
+            .filter(|m| self.stopping.remove(&m.old_ip).is_some())
+            .collect();
+        for m in stopping {
+            self.stopping.insert(m.new_ip.clone());
+        }
+    }
+
+    /// Resumes a suspended session once the source that took over stops.
+    ///
+    /// Called for every transport state change; does nothing unless the
+    /// speaker has a suspended session and is now stopped. A paused source
+    /// still holds the speaker and may be picked up again, so it doesn't
+    /// count. A
+    /// speaker awaiting confirmation after an external stop that plays again
+    /// (someone pressed Play in the Sonos app) counts as resumed.
+    pub fn handle_transport_state(self: &Arc<Self>, speaker_ip: &str, state: TransportState) {
+        if state == TransportState::Playing {
+            if self.stop_confirmations.remove(speaker_ip).is_some() {
+                self.emit_resumed_after_external_stop(speaker_ip);
+            }
+            return;
+        }
+        if state != TransportState::Stopped {
+            return;
+        }
+        let Some((_, suspended)) = self
+            .suspended
+            .remove_if(speaker_ip, |_, s| s.since.elapsed() >= TAKEOVER_SETTLE)
+        else {
+            return;
+        };
+
+        let coordinator = Arc::clone(self);
+        let speaker_ip = speaker_ip.to_string();
+        tokio::spawn(async move {
+            coordinator.resume_session(&speaker_ip, suspended).await;
+        });
+    }
+
+    /// Applies the external stop policy to a speaker that went from playing
+    /// to stopped.
+    ///
+    /// Only coordinators of a running stream count; stops we send ourselves,
+    /// streams being torn down and sync group members are left alone. Under
+    /// [`ExternalStopPolicy::Resume`] the stream is played again right away,
+    /// under [`ExternalStopPolicy::Ask`] the session is held until
+    /// [`Self::confirm_resume`] or the confirmation window ends it.
+    ///
+    /// # Returns
+    /// `true` if the stop was handled as external, `false` otherwise.
+    pub fn handle_external_stop(self: &Arc<Self>, speaker_ip: &str) -> bool {
+        if self.external_stop == ExternalStopPolicy::Stop || self.is_own_stop(speaker_ip) {
+            return false;
+        }
+        let Some(session) = self.sessions.get_by_speaker_ip(speaker_ip) else {
+            return false;
+        };
+        if session.role != GroupRole::Coordinator || self.get_stream(&session.stream_id).is_none() {
+            return false;
+        }
+        let stream_id = session.stream_id;
+
+        if self.external_stop == ExternalStopPolicy::Resume {
+            log::info!(
+                "{} was stopped from outside, resuming stream {}",
+                speaker_ip,
+                stream_id
+            );
+            self.emit_event(StreamEvent::PlaybackStoppedExternally {
+                stream_id: stream_id.clone(),
+                speaker_ip: speaker_ip.to_string(),
+                auto_resume: true,
+                confirm_window_secs: None,
+                timestamp: now_millis(),
+            });
+            let coordinator = Arc::clone(self);
+            let speaker_ip = speaker_ip.to_string();
+            tokio::spawn(async move {
+                coordinator
+                    .replay_after_external_stop(&stream_id, &speaker_ip)
+                    .await;
+            });
+            return true;
+        }
+
+        let since = Instant::now();
+        self.stop_confirmations
+            .insert(speaker_ip.to_string(), since);
+        let window = self.external_stop_window;
+        log::info!(
+            "{} was stopped from outside, holding stream {} for {}s",
+            speaker_ip,
+            stream_id,
+            window.as_secs()
+        );
+        self.emit_event(StreamEvent::PlaybackStoppedExternally {
+            stream_id,
+            speaker_ip: speaker_ip.to_string(),
+            auto_resume: false,
+            confirm_window_secs: Some(window.as_secs()),
+            timestamp: now_millis(),
+        });
+
+        let coordinator = Arc::clone(self);
+        let speaker_ip = speaker_ip.to_string();
+        tokio::spawn(async move {
+            tokio::time::sleep(window).await;
+            if coordinator
+                .stop_confirmations
+                .remove_if(&speaker_ip, |_, s| *s == since)
+                .is_some()
+            {
+                log::info!(
+                    "Resume of {} was not confirmed, ending its session",
+                    speaker_ip
+                );
+                coordinator.drop_session(&speaker_ip, SpeakerRemovalReason::PlaybackStopped);
+            }
+        });
+
+        true
+    }
+
+    /// Resumes a speaker held after an external stop.
+    ///
+    /// # Returns
+    /// `false` if the speaker is not awaiting confirmation.
+    pub async fn confirm_resume(&self, speaker_ip: &str) -> bool {
+        if self.stop_confirmations.remove(speaker_ip).is_none() {
+            return false;
+        }
+        let Some(stream_id) = self
+            .sessions
+            .get_by_speaker_ip(speaker_ip)
+            .map(|session| session.stream_id)
+        else {
+            return false;
+        };
+        self.replay_after_external_stop(&stream_id, speaker_ip)
+            .await;
+        true
+    }
+
+    /// Sends Play to a speaker stopped from outside, whose stream URI is
+    /// still loaded, ending its session if that fails.
+    async fn replay_after_external_stop(&self, stream_id: &str, speaker_ip: &str) {
+        match self.sonos.play(speaker_ip).await {
+            Ok(()) => self.emit_resumed_after_external_stop(speaker_ip),
+            Err(e) => {
+                log::warn!(
+                    "Failed to resume stream {} on {}: {}",
+                    stream_id,
+                    speaker_ip,
+                    e
+                );
+                self.drop_session(speaker_ip, SpeakerRemovalReason::PlaybackStopped);
+            }
+        }
+    }
+
+    /// Emits `PlaybackResumed` for a speaker playing again after an external stop.
+    fn emit_resumed_after_external_stop(&self, speaker_ip: &str) {
+        let Some(session) = self.sessions.get_by_speaker_ip(speaker_ip) else {
+            return;
+        };
+        log::info!(
+            "Stream {} plays on {} again after an external stop",
+            session.stream_id,
+            speaker_ip
+        );
+        self.emit_event(StreamEvent::PlaybackResumed {
+            stream_id: session.stream_id,
+            speaker_ip: speaker_ip.to_string(),
+            timestamp: now_millis(),
+        });
+    }
+
+    /// Removes a speaker's session without stopping it and reports it stopped.
+    fn drop_session(&self, speaker_ip: &str, reason: SpeakerRemovalReason) {
+        let Some(key) = self.sessions.get_key_by_speaker_ip(speaker_ip) else {
+            return;
+        };
+        if self
+            .sessions
+            .remove(&key.stream_id, &key.speaker_ip)
+            .is_some()
+        {
+            self.end_session(&key.stream_id, speaker_ip, Some(reason));
+        }
+    }
+
+    /// Plays a suspended session's stream on its speaker again.
+    async fn resume_session(&self, speaker_ip: &str, suspended: SuspendedPlayback) {
+        let SuspendedPlayback { session, .. } = suspended;
+        let stream_id = session.stream_id;
+        let Some(stream) = self.get_stream(&stream_id) else {
+            log::info!(
+                "Not resuming {}: stream {} ended while suspended",
+                speaker_ip,
+                stream_id
+            );
+            return;
+        };
+
+        let metadata = stream.metadata.read().clone();
+        let artwork_url = session
+            .artwork_url
+            .unwrap_or_else(|| self.network.url_builder().artwork_url());
+        match self
+            .start_playback(speaker_ip, &stream_id, Some(&metadata), &artwork_url)
+            .await
+        {
+            Ok(()) => {
+                log::info!(
+                    "Resumed stream {} on {} after the other source stopped",
+                    stream_id,
+                    speaker_ip
+                );
+                self.emit_event(StreamEvent::PlaybackResumed {
+                    stream_id,
+                    speaker_ip: speaker_ip.to_string(),
+                    timestamp: now_millis(),
+                });
+            }
+            Err(e) => {
+                log::warn!(
+                    "Failed to resume stream {} on {}: {}",
+                    stream_id,
+                    speaker_ip,
+                    e
+                );
+                self.end_session(
+                    &stream_id,
+                    speaker_ip,
+                    Some(SpeakerRemovalReason::SourceChanged),
+                );
+            }
+        }
+    }
+
+    /// Checks sessions against streams and suspensions, reporting drift.
+    #[cfg(debug_assertions)]
+    fn check_invariants(&self, operation: &str) {
+        let suspended_ips: HashSet<String> = self
+            .suspended
+            .iter()
+            .map(|entry| entry.key().clone())
+            .collect();
+        let violations = super::invariants::check_sessions(
+            &self.sessions.all_sessions(),
+            |id| self.stream_registry.get_stream(id).is_some(),
+            &suspended_ips,
+        );
+        super::invariants::report(operation, &violations);
+    }
+
+    /// Emits `PlaybackStopped` for a session that is already gone and ends
+    /// the stream if no speakers remain.
+    fn end_session(&self, stream_id: &str, speaker_ip: &str, reason: Option<SpeakerRemovalReason>) {
+        self.emit_event(StreamEvent::PlaybackStopped {
+            stream_id: stream_id.to_string(),
+            speaker_ip: speaker_ip.to_string(),
+            reason,
+            timestamp: now_millis(),
+        });
+
+        // Clean up stream if this was the last session
+        self.sync_group.cleanup_stream_if_no_sessions(stream_id);
+    }
+
+    /// Handles HTTP resume event from a speaker.
+    ///
+    /// Called by the HTTP layer when a speaker reconnects to an existing stream.
+    /// This is the only place that sends Play commands on HTTP resume, maintaining
+    /// separation of concerns (HTTP layer serves audio, coordinator controls playback).
+    ///
+    /// # Arguments
+    /// * `speaker_ip` - IP address of the speaker that resumed HTTP connection
+    ///
+    /// # Returns
+    /// `true` if a Play command was sent, `false` otherwise.
+    pub async fn on_http_resume(&self, speaker_ip: &str) -> bool {
+        match self.ensure_playing(speaker_ip).await {
+            Ok(sent_play) => sent_play,
+            Err(e) => {
+                log::warn!(
+                    "[Resume] Play command on HTTP resume failed for {}: {}",
+                    speaker_ip,
+                    e
+                );
+                true // Play was attempted even though it failed
+            }
+        }
+    }
+}
+
+/// Trims a label, treating blank as no label.
+pub(crate) fn normalize_label(label: Option<&str>) -> Result<Option<String>, String> {
+    let Some(label) = label.map(str::trim).filter(|l| !l.is_empty()) else {
+        return Ok(None);
+    };
+    if label.chars().count() > MAX_STREAM_LABEL_CHARS {
+        return Err(format!(
+            "Label is longer than {} characters",
+            MAX_STREAM_LABEL_CHARS
+        ));
+    }
+    if label.chars().any(char::is_control) {
+        return Err("Label contains control characters".to_string());
+    }
+    Ok(Some(label.to_string()))
+}
+
+/// Checks `pin` against a stream, refusing with `refusal` if it is missing
+/// or wrong, or with a retry hint while wrong PINs are backed off.
+fn require_pin(
+    stream: &StreamState,
+    pin: Option<&str>,
+    refusal: impl FnOnce() -> String,
+) -> ThaumicResult<()> {
+    match stream.check_pin(pin) {
+        PinCheck::Accepted => Ok(()),
+        PinCheck::Rejected => Err(ThaumicError::StreamLocked(refusal())),
+        PinCheck::Backoff(wait) => Err(ThaumicError::StreamLocked(format!(
+            "Too many wrong PINs for {}, try again in {} s",
+            stream.id,
+            wait.as_secs() + 1
+        ))),
+    }
+}
+
+/// Checks that a stream PIN is 4 to 8 ASCII digits.
+fn validate_pin(pin: &str) -> Result<(), String> {
+    if !STREAM_PIN_DIGITS.contains(&pin.len()) || !pin.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(format!(
+            "PIN must be {} to {} digits",
+            STREAM_PIN_DIGITS.start(),
+            STREAM_PIN_DIGITS.end()
+        ));
+    }
+    Ok(())
+}
+
+/// Returns whether any metadata field points at one of our own streams.
+fn metadata_mentions_own_stream(metadata: &StreamMetadata, server_host: &str) -> bool {
+    // The page URL only counts when it is ours; plenty of sites have
+    // `/stream/` paths
+    let url_is_ours = metadata
+        .source_url
+        .as_deref()
+        .is_some_and(|url| !server_host.is_empty() && url.contains(server_host));
+    url_is_ours
+        || [
+            &metadata.title,
+            &metadata.artist,
+            &metadata.source,
+            &metadata.source_title,
+        ]
+        .into_iter()
+        .flatten()
+        .any(|text| feedback::mentions_own_stream(text, server_host))
+}
+
+/// Failure result for a speaker that can't be cast to.
+fn unplayable_result(speaker_ip: &str, reason: &str) -> PlaybackResult {
+    log::warn!("[Playback] Not casting to {}: {}", speaker_ip, reason);
+    PlaybackResult {
+        speaker_ip: speaker_ip.to_string(),
+        success: false,
+        stream_url: None,
+        error: Some(reason.to_string()),
+    }
+}
 
 #[cfg(test)]
 mod tests {
@@ -1085,6 +2090,40 @@ mod tests {
         assert!(!"x-rincon-queue:RINCON_XXX#0".starts_with("x-rincon:"));
     }
 
+    #[test]
+    fn labels_are_trimmed_and_bounded() {
+        assert_eq!(normalize_label(None), Ok(None));
+        assert_eq!(normalize_label(Some("   ")), Ok(None));
+        assert_eq!(
+            normalize_label(Some("  Vinyl rip ")),
+            Ok(Some("Vinyl rip".to_string()))
+        );
+        assert!(normalize_label(Some(&"x".repeat(MAX_STREAM_LABEL_CHARS + 1))).is_err());
+        assert!(normalize_label(Some("Football\nstream")).is_err());
+    }
+
+    #[test]
+    fn only_our_own_page_url_marks_a_feedback_loop() {
+        let host = "192.168.1.10:49400";
+        let page = |url: &str| StreamMetadata {
+            source_url: Some(url.to_string()),
+            ..Default::default()
+        };
+        assert!(metadata_mentions_own_stream(
+            &page("http://192.168.1.10:49400/stream/7d3c/live.wav"),
+            host
+        ));
+        assert!(!metadata_mentions_own_stream(
+            &page("https://radio.example.com/stream/jazz"),
+            host
+        ));
+        let titled = StreamMetadata {
+            source_title: Some("live.flac (audio/flac)".to_string()),
+            ..Default::default()
+        };
+        assert!(metadata_mentions_own_stream(&titled, host));
+    }
+
     #[test]
     fn x_rincon_uri_not_transformed_for_pcm() {
         // This tests the fix for the bug where x-rincon URIs were incorrectly
@@ -1139,14 +2178,18 @@ mod tests {
 
     mod coordinator_promotion {
         use super::*;
+        use crate::clock::SystemClock;
         use crate::context::NetworkContext;
         use crate::error::SoapResult;
         use crate::events::{EventEmitter, NetworkEvent, SonosEvent, StreamEvent, TopologyEvent};
+        use crate::sonos::discovery::Speaker;
         use crate::sonos::gena::GenaSubscriptionManager;
         use crate::sonos::subscription_arbiter::SubscriptionArbiter;
         use crate::sonos::traits::SonosPlayback;
-        use crate::sonos::types::{PositionInfo, ZoneGroup, ZoneGroupMember};
-        use crate::state::{SonosState, StreamingConfig};
+        use crate::sonos::types::{Bond, BondKind, PositionInfo, ZoneGroup, ZoneGroupMember};
+        use crate::state::{
+            CoordinatorPin, CoordinatorPreferences, ExternalStopPolicy, SonosState, StreamingConfig,
+        };
         use crate::stream::{AudioCodec, AudioFormat};
         use async_trait::async_trait;
         use std::sync::atomic::{AtomicUsize, Ordering};
@@ -1183,8 +2226,10 @@ mod tests {
             }
             fn emit_sonos(&self, _: SonosEvent) {}
             fn emit_latency(&self, _: crate::events::LatencyEvent) {}
+            fn emit_resource(&self, _: crate::events::ResourceEvent) {}
             fn emit_network(&self, _: NetworkEvent) {}
             fn emit_topology(&self, _: TopologyEvent) {}
+            fn emit_state(&self, _: crate::events::StateEvent) {}
         }
 
         /// Mock SonosPlayback that tracks call counts per method.
@@ -1194,8 +2239,10 @@ mod tests {
             leave_group_count: AtomicUsize,
             join_group_count: AtomicUsize,
             switch_to_queue_count: AtomicUsize,
-            /// If set, play_uri returns this error.
+            /// If set, setting the transport URI returns this error.
             play_uri_fail: Mutex<Option<String>>,
+            /// Artwork URLs passed when setting the transport URI, in order.
+            artwork_urls: Mutex<Vec<String>>,
         }
 
         impl TrackingSonosPlayback {
@@ -1207,6 +2254,7 @@ mod tests {
                     join_group_count: AtomicUsize::new(0),
                     switch_to_queue_count: AtomicUsize::new(0),
                     play_uri_fail: Mutex::new(None),
+                    artwork_urls: Mutex::new(Vec::new()),
                 }
             }
 
@@ -1218,16 +2266,20 @@ mod tests {
 
         #[async_trait]
         impl SonosPlayback for TrackingSonosPlayback {
-            async fn play_uri(
+            async fn set_transport_uri(
                 &self,
                 _: &str,
                 _: &str,
                 _: AudioCodec,
                 _: &AudioFormat,
                 _: Option<&StreamMetadata>,
-                _: &str,
+                artwork_url: &str,
             ) -> SoapResult<()> {
                 self.play_uri_count.fetch_add(1, Ordering::SeqCst);
+                self.artwork_urls
+                    .lock()
+                    .unwrap()
+                    .push(artwork_url.to_string());
                 if let Some(msg) = self.play_uri_fail.lock().unwrap().as_ref() {
                     return Err(crate::sonos::soap::SoapError::Fault(msg.clone()));
                 }
@@ -1248,6 +2300,7 @@ mod tests {
                 Ok(PositionInfo {
                     track_uri: String::new(),
                     rel_time_ms: 0,
+                    track_duration_ms: None,
                 })
             }
             async fn join_group(&self, _: &str, _: &str) -> SoapResult<()> {
@@ -1268,6 +2321,8 @@ mod tests {
                 .map(|(ip, uuid)| ZoneGroup {
                     id: uuid.to_string(),
                     name: format!("Room {}", ip),
+                    label: format!("Room {}", ip),
+                    room_count: 1,
                     coordinator_uuid: uuid.to_string(),
                     coordinator_ip: ip.to_string(),
                     members: vec![ZoneGroupMember {
@@ -1275,6 +2330,7 @@ mod tests {
                         ip: ip.to_string(),
                         zone_name: format!("Room {}", ip),
                         model: "One".to_string(),
+                        ..Default::default()
                     }],
                 })
                 .collect();
@@ -1290,19 +2346,29 @@ mod tests {
             sonos: Arc<dyn SonosPlayback>,
             sonos_state: Arc<SonosState>,
             emitter: Arc<dyn EventEmitter>,
+        ) -> StreamCoordinator {
+            create_coordinator_with_config(sonos, sonos_state, emitter, StreamingConfig::default())
+        }
+
+        /// Like [`create_coordinator_with`], with a custom streaming config.
+        fn create_coordinator_with_config(
+            sonos: Arc<dyn SonosPlayback>,
+            sonos_state: Arc<SonosState>,
+            emitter: Arc<dyn EventEmitter>,
+            streaming_config: StreamingConfig,
         ) -> StreamCoordinator {
             let client = reqwest::Client::builder()
                 .timeout(std::time::Duration::from_millis(1))
                 .build()
                 .unwrap();
-            let (gena_manager, _rx) = GenaSubscriptionManager::new(client);
+            let (gena_manager, _rx) = GenaSubscriptionManager::new(client, SystemClock::arc());
             let arbiter = Arc::new(SubscriptionArbiter::new(Arc::new(gena_manager)));
             StreamCoordinator::new(
                 sonos,
                 sonos_state,
                 NetworkContext::for_test(),
                 emitter,
-                StreamingConfig::default(),
+                streaming_config,
                 arbiter,
             )
         }
@@ -1337,6 +2403,7 @@ mod tests {
                 coordinator_ip: None,
                 coordinator_uuid: Some("RINCON_COORD".to_string()),
                 original_coordinator_uuid: None,
+                artwork_url: None,
             });
             coord.insert_test_session(PlaybackSession {
                 stream_id: stream_id.clone(),
@@ -1347,6 +2414,7 @@ mod tests {
                 coordinator_ip: Some("192.168.1.100".to_string()),
                 coordinator_uuid: Some("RINCON_COORD".to_string()),
                 original_coordinator_uuid: None,
+                artwork_url: None,
             });
             coord.insert_test_session(PlaybackSession {
                 stream_id: stream_id.clone(),
@@ -1357,6 +2425,7 @@ mod tests {
                 coordinator_ip: Some("192.168.1.100".to_string()),
                 coordinator_uuid: Some("RINCON_COORD".to_string()),
                 original_coordinator_uuid: None,
+                artwork_url: None,
             });
 
             // Remove the coordinator
@@ -1411,6 +2480,484 @@ mod tests {
             assert_eq!(sonos.join_group_count.load(Ordering::SeqCst), 1);
         }
 
+        #[tokio::test]
+        async fn external_stop_waits_for_confirmation_to_resume() {
+            let emitter = Arc::new(CollectingEventEmitter::new());
+            let config = StreamingConfig {
+                external_stop: ExternalStopPolicy::Ask,
+                ..StreamingConfig::default()
+            };
+            let coord = Arc::new(create_coordinator_with_config(
+                Arc::new(TrackingSonosPlayback::new()),
+                create_sonos_state_with_members(&[("192.168.1.100", "RINCON_COORD")]),
+                Arc::clone(&emitter) as Arc<dyn EventEmitter>,
+                config,
+            ));
+            let stream_id = coord
+                .create_stream(AudioCodec::Aac, AudioFormat::default(), 200, 20)
+                .unwrap();
+            coord.insert_test_session(PlaybackSession {
+                stream_id: stream_id.clone(),
+                speaker_ip: "192.168.1.100".to_string(),
+                stream_url: "http://127.0.0.1:0/stream/test/live".to_string(),
+                codec: AudioCodec::Aac,
+                role: GroupRole::Coordinator,
+                coordinator_ip: None,
+                coordinator_uuid: Some("RINCON_COORD".to_string()),
+                original_coordinator_uuid: None,
+                artwork_url: None,
+            });
+
+            assert!(!coord.confirm_resume("192.168.1.100").await);
+            assert!(coord.handle_external_stop("192.168.1.100"));
+            assert!(matches!(
+                emitter.events.lock().unwrap().last(),
+                Some(StreamEvent::PlaybackStoppedExternally {
+                    auto_resume: false,
+                    confirm_window_secs: Some(60),
+                    ..
+                })
+            ));
+
+            assert!(coord.confirm_resume("192.168.1.100").await);
+            assert!(matches!(
+                emitter.events.lock().unwrap().last(),
+                Some(StreamEvent::PlaybackResumed { .. })
+            ));
+            assert!(emitter.playback_stopped_ips().is_empty());
+            assert_eq!(coord.get_all_sessions().len(), 1);
+            assert!(!coord.confirm_resume("192.168.1.100").await);
+        }
+
+        #[tokio::test]
+        async fn external_stop_is_ignored_by_default() {
+            let coord = Arc::new(create_coordinator_with(
+                Arc::new(TrackingSonosPlayback::new()),
+                create_sonos_state_with_members(&[("192.168.1.100", "RINCON_COORD")]),
+                Arc::new(CollectingEventEmitter::new()),
+            ));
+            let stream_id = coord
+                .create_stream(AudioCodec::Aac, AudioFormat::default(), 200, 20)
+                .unwrap();
+            coord.insert_test_session(PlaybackSession {
+                stream_id,
+                speaker_ip: "192.168.1.100".to_string(),
+                stream_url: "http://127.0.0.1:0/stream/test/live".to_string(),
+                codec: AudioCodec::Aac,
+                role: GroupRole::Coordinator,
+                coordinator_ip: None,
+                coordinator_uuid: Some("RINCON_COORD".to_string()),
+                original_coordinator_uuid: None,
+                artwork_url: None,
+            });
+
+            assert!(!coord.handle_external_stop("192.168.1.100"));
+        }
+
+        #[tokio::test]
+        async fn own_pause_is_not_an_external_stop() {
+            let sonos = Arc::new(TrackingSonosPlayback::new());
+            let emitter = Arc::new(CollectingEventEmitter::new());
+            let config = StreamingConfig {
+                external_stop: ExternalStopPolicy::Resume,
+                ..StreamingConfig::default()
+            };
+            let coord = Arc::new(create_coordinator_with_config(
+                Arc::clone(&sonos) as Arc<dyn SonosPlayback>,
+                create_sonos_state_with_members(&[("192.168.1.100", "RINCON_COORD")]),
+                Arc::clone(&emitter) as Arc<dyn EventEmitter>,
+                config,
+            ));
+            let stream_id = coord
+                .create_stream(AudioCodec::Aac, AudioFormat::default(), 200, 20)
+                .unwrap();
+            coord.insert_test_session(PlaybackSession {
+                stream_id: stream_id.clone(),
+                speaker_ip: "192.168.1.100".to_string(),
+                stream_url: "http://127.0.0.1:0/stream/test/live".to_string(),
+                codec: AudioCodec::Aac,
+                role: GroupRole::Coordinator,
+                coordinator_ip: None,
+                coordinator_uuid: Some("RINCON_COORD".to_string()),
+                original_coordinator_uuid: None,
+                artwork_url: None,
+            });
+            coord.lock_stream(&stream_id, "2468").unwrap();
+
+            coord.pause_speaker("192.168.1.100").await.unwrap();
+            assert_eq!(sonos.stop_count.load(Ordering::SeqCst), 1);
+
+            // The Stopped NOTIFY that follows is ours
+            assert!(!coord.report_speaker_change("192.168.1.100", LockedSpeakerChange::Stopped));
+            assert!(!coord.handle_external_stop("192.168.1.100"));
+            assert!(!emitter.events.lock().unwrap().iter().any(|e| matches!(
+                e,
+                StreamEvent::LockedSpeakerChanged { .. }
+                    | StreamEvent::PlaybackStoppedExternally { .. }
+            )));
+            assert_eq!(coord.get_all_sessions().len(), 1);
+
+            // A later stop from the Sonos app still counts
+            coord.pausing.clear();
+            assert!(coord.handle_external_stop("192.168.1.100"));
+        }
+
+        /// Creates a coordinator with one stream cast to 192.168.1.100,
+        /// returning it with the stream ID.
+        fn coordinator_casting(
+            sonos: Arc<TrackingSonosPlayback>,
+            emitter: Arc<CollectingEventEmitter>,
+            takeover_resume_secs: Option<u64>,
+        ) -> (Arc<StreamCoordinator>, String) {
+            let config = StreamingConfig {
+                takeover_resume_secs,
+                ..StreamingConfig::default()
+            };
+            let coord = Arc::new(create_coordinator_with_config(
+                sonos,
+                create_sonos_state_with_members(&[("192.168.1.100", "RINCON_COORD")]),
+                emitter,
+                config,
+            ));
+            let stream_id = coord
+                .create_stream(AudioCodec::Aac, AudioFormat::default(), 200, 20)
+                .unwrap();
+            coord.insert_test_session(PlaybackSession {
+                stream_id: stream_id.clone(),
+                speaker_ip: "192.168.1.100".to_string(),
+                stream_url: "http://127.0.0.1:0/stream/test/live".to_string(),
+                codec: AudioCodec::Aac,
+                role: GroupRole::Coordinator,
+                coordinator_ip: None,
+                coordinator_uuid: Some("RINCON_COORD".to_string()),
+                original_coordinator_uuid: None,
+                artwork_url: Some("http://127.0.0.1:0/art/first.jpg".to_string()),
+            });
+            (coord, stream_id)
+        }
+
+        /// Backdates a suspension past the settle window.
+        fn settle_suspension(coord: &StreamCoordinator, speaker_ip: &str) {
+            coord.suspended.get_mut(speaker_ip).unwrap().since -= TAKEOVER_SETTLE;
+        }
+
+        #[tokio::test]
+        async fn source_change_suspends_the_session() {
+            let emitter = Arc::new(CollectingEventEmitter::new());
+            let (coord, stream_id) = coordinator_casting(
+                Arc::new(TrackingSonosPlayback::new()),
+                Arc::clone(&emitter),
+                Some(60),
+            );
+
+            assert!(coord.handle_source_changed("192.168.1.100", "x-sonos-spotify:track"));
+            assert!(coord.has_suspended_playback(&stream_id));
+            assert!(coord.get_all_sessions().is_empty());
+            assert!(coord.get_stream(&stream_id).is_some());
+            assert!(emitter.playback_stopped_ips().is_empty());
+            assert!(matches!(
+                emitter.events.lock().unwrap().last(),
+                Some(StreamEvent::PlaybackSuspended {
+                    resume_window_secs: 60,
+                    ..
+                })
+            ));
+        }
+
+        #[tokio::test]
+        async fn suspension_ignores_stops_while_settling_and_pauses() {
+            let sonos = Arc::new(TrackingSonosPlayback::new());
+            let (coord, stream_id) = coordinator_casting(
+                Arc::clone(&sonos),
+                Arc::new(CollectingEventEmitter::new()),
+                Some(60),
+            );
+            coord.handle_source_changed("192.168.1.100", "x-sonos-spotify:track");
+
+            // The other source passes through Stopped while it starts
+            coord.handle_transport_state("192.168.1.100", TransportState::Stopped);
+            settle_suspension(&coord, "192.168.1.100");
+            coord.handle_transport_state("192.168.1.100", TransportState::Paused);
+            tokio::task::yield_now().await;
+
+            assert!(coord.has_suspended_playback(&stream_id));
+            assert_eq!(sonos.play_uri_count.load(Ordering::SeqCst), 0);
+        }
+
+        #[tokio::test]
+        async fn suspension_resumes_when_the_other_source_stops() {
+            let sonos = Arc::new(TrackingSonosPlayback::new());
+            let emitter = Arc::new(CollectingEventEmitter::new());
+            let (coord, stream_id) =
+                coordinator_casting(Arc::clone(&sonos), Arc::clone(&emitter), Some(60));
+            coord.handle_source_changed("192.168.1.100", "x-sonos-spotify:track");
+            settle_suspension(&coord, "192.168.1.100");
+
+            coord.handle_transport_state("192.168.1.100", TransportState::Stopped);
+            for _ in 0..100 {
+                if !coord.get_all_sessions().is_empty() {
+                    break;
+                }
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+
+            assert!(!coord.has_suspended_playback(&stream_id));
+            assert_eq!(coord.get_all_sessions().len(), 1);
+            assert_eq!(
+                *sonos.artwork_urls.lock().unwrap(),
+                ["http://127.0.0.1:0/art/first.jpg"]
+            );
+            assert!(emitter
+                .events
+                .lock()
+                .unwrap()
+                .iter()
+                .any(|e| matches!(e, StreamEvent::PlaybackResumed { .. })));
+        }
+
+        #[tokio::test(start_paused = true)]
+        async fn suspension_ends_after_the_takeover_window() {
+            let emitter = Arc::new(CollectingEventEmitter::new());
+            let (coord, stream_id) = coordinator_casting(
+                Arc::new(TrackingSonosPlayback::new()),
+                Arc::clone(&emitter),
+                Some(1),
+            );
+            coord.handle_source_changed("192.168.1.100", "x-sonos-spotify:track");
+
+            tokio::time::sleep(Duration::from_secs(2)).await;
+
+            assert!(!coord.has_suspended_playback(&stream_id));
+            assert!(matches!(
+                emitter
+                    .events
+                    .lock()
+                    .unwrap()
+                    .iter()
+                    .find(|e| matches!(e, StreamEvent::PlaybackStopped { .. })),
+                Some(StreamEvent::PlaybackStopped {
+                    reason: Some(SpeakerRemovalReason::SourceChanged),
+                    ..
+                })
+            ));
+        }
+
+        #[tokio::test]
+        async fn stopping_a_suspended_speaker_forgets_it() {
+            let sonos = Arc::new(TrackingSonosPlayback::new());
+            let emitter = Arc::new(CollectingEventEmitter::new());
+            let (coord, stream_id) =
+                coordinator_casting(Arc::clone(&sonos), Arc::clone(&emitter), Some(60));
+            coord.handle_source_changed("192.168.1.100", "x-sonos-spotify:track");
+
+            let stopped = coord
+                .stop_playback_speaker(
+                    &stream_id,
+                    "192.168.1.100",
+                    Some(SpeakerRemovalReason::UserRemoved),
+                )
+                .await;
+
+            assert_eq!(stopped, ["192.168.1.100"]);
+            assert!(!coord.has_suspended_playback(&stream_id));
+            // The other source keeps playing
+            assert_eq!(sonos.stop_count.load(Ordering::SeqCst), 0);
+            assert_eq!(emitter.playback_stopped_ips(), ["192.168.1.100"]);
+        }
+
+        #[tokio::test]
+        async fn locked_stream_needs_its_pin_and_alerts_on_outside_changes() {
+            let emitter = Arc::new(CollectingEventEmitter::new());
+            let coord = create_coordinator_with(
+                Arc::new(TrackingSonosPlayback::new()),
+                create_sonos_state_with_members(&[("192.168.1.100", "RINCON_COORD")]),
+                Arc::clone(&emitter) as Arc<dyn EventEmitter>,
+            );
+            let stream_id = coord
+                .create_stream(AudioCodec::Aac, AudioFormat::default(), 200, 20)
+                .unwrap();
+            coord.insert_test_session(PlaybackSession {
+                stream_id: stream_id.clone(),
+                speaker_ip: "192.168.1.100".to_string(),
+                stream_url: "http://127.0.0.1:0/stream/test/live".to_string(),
+                codec: AudioCodec::Aac,
+                role: GroupRole::Coordinator,
+                coordinator_ip: None,
+                coordinator_uuid: Some("RINCON_COORD".to_string()),
+                original_coordinator_uuid: None,
+                artwork_url: None,
+            });
+
+            assert!(coord.lock_stream(&stream_id, "12ab").is_err());
+            coord.lock_stream(&stream_id, "2468").unwrap();
+            assert!(matches!(
+                coord.lock_stream(&stream_id, "1357"),
+                Err(ThaumicError::StreamLocked(_))
+            ));
+
+            assert!(coord
+                .authorize_speaker("192.168.1.100", None, None)
+                .is_err());
+            assert!(coord
+                .authorize_speaker("192.168.1.100", Some("1357"), None)
+                .is_err());
+            assert!(coord.authorize_stream(&stream_id, None, None).is_err());
+            coord
+                .authorize_speaker("192.168.1.100", None, Some(&stream_id))
+                .unwrap();
+            coord
+                .authorize_speaker("192.168.1.101", None, None)
+                .unwrap();
+
+            // The NOTIFY echoing an authorized change is not an alert
+            assert!(!coord.report_speaker_change("192.168.1.100", LockedSpeakerChange::Volume));
+            coord.authorized_changes.clear();
+            assert!(coord.report_speaker_change("192.168.1.100", LockedSpeakerChange::Mute));
+            assert!(matches!(
+                emitter.events.lock().unwrap().last(),
+                Some(StreamEvent::LockedSpeakerChanged {
+                    change: LockedSpeakerChange::Mute,
+                    ..
+                })
+            ));
+
+            assert!(coord.unlock_stream(&stream_id, "1357").is_err());
+            coord.unlock_stream(&stream_id, "2468").unwrap();
+            coord
+                .authorize_speaker("192.168.1.100", None, None)
+                .unwrap();
+            coord.authorized_changes.clear();
+            assert!(!coord.report_speaker_change("192.168.1.100", LockedSpeakerChange::Stopped));
+        }
+
+        #[tokio::test]
+        async fn wrong_pins_back_off() {
+            let coord = create_coordinator_with(
+                Arc::new(TrackingSonosPlayback::new()),
+                create_sonos_state_with_members(&[("192.168.1.100", "RINCON_COORD")]),
+                Arc::new(CollectingEventEmitter::new()),
+            );
+            let stream_id = coord
+                .create_stream(AudioCodec::Aac, AudioFormat::default(), 200, 20)
+                .unwrap();
+            coord.lock_stream(&stream_id, "2468").unwrap();
+            let stream = coord.stream_registry().get_stream(&stream_id).unwrap();
+
+            // Missing PINs aren't guesses, and a correct PIN resets the count
+            for _ in 0..10 {
+                assert_eq!(stream.check_pin(None), PinCheck::Rejected);
+            }
+            for _ in 0..4 {
+                assert_eq!(stream.check_pin(Some("1357")), PinCheck::Rejected);
+            }
+            assert_eq!(stream.check_pin(Some("2468")), PinCheck::Accepted);
+            for _ in 0..5 {
+                assert_eq!(stream.check_pin(Some("1357")), PinCheck::Rejected);
+            }
+
+            // Now even the right PIN waits out the backoff
+            assert!(matches!(
+                stream.check_pin(Some("2468")),
+                PinCheck::Backoff(_)
+            ));
+            let err = coord.unlock_stream(&stream_id, "2468").unwrap_err();
+            assert!(err.to_string().contains("Too many wrong PINs"));
+            assert!(stream.is_locked());
+        }
+
+        #[tokio::test]
+        async fn rejects_satellites_without_contacting_them() {
+            let sonos = Arc::new(TrackingSonosPlayback::new());
+            let sonos_state = create_sonos_state_with_members(&[("192.168.1.100", "RINCON_SUB")]);
+            sonos_state.groups.write()[0].members[0].role = DeviceRole::Subwoofer;
+            let coord = create_coordinator_with(
+                Arc::clone(&sonos) as Arc<dyn SonosPlayback>,
+                sonos_state,
+                Arc::new(CollectingEventEmitter::new()),
+            );
+            let stream_id = coord
+                .create_stream(AudioCodec::Aac, AudioFormat::default(), 200, 20)
+                .unwrap();
+
+            let results = coord
+                .start_playback_multi(&["192.168.1.100".to_string()], &stream_id, None, "", true)
+                .await;
+
+            assert_eq!(results.len(), 1);
+            assert!(!results[0].success);
+            assert!(results[0].error.as_deref().unwrap().contains("bonded"));
+            assert_eq!(sonos.play_uri_count.load(Ordering::SeqCst), 0);
+        }
+
+        #[tokio::test]
+        async fn casts_to_stereo_pair_primary() {
+            let sonos = Arc::new(TrackingSonosPlayback::new());
+            let sonos_state = create_sonos_state_with_members(&[
+                ("192.168.1.100", "RINCON_LEFT"),
+                ("192.168.1.101", "RINCON_RIGHT"),
+            ]);
+            {
+                let mut groups = sonos_state.groups.write();
+                for group in groups.iter_mut() {
+                    group.members[0].bond = Some(Bond {
+                        kind: BondKind::StereoPair,
+                        primary_uuid: "RINCON_LEFT".to_string(),
+                    });
+                }
+                groups[1].members[0].role = DeviceRole::Satellite;
+            }
+            let coord = create_coordinator_with(
+                Arc::clone(&sonos) as Arc<dyn SonosPlayback>,
+                sonos_state,
+                Arc::new(CollectingEventEmitter::new()),
+            );
+
+            let (targets, rejected) = coord.resolve_cast_targets(
+                &["192.168.1.101".to_string(), "192.168.1.100".to_string()],
+                "",
+            );
+            assert_eq!(targets, vec!["192.168.1.100"]);
+            assert!(rejected.is_empty());
+        }
+
+        #[tokio::test]
+        async fn rejects_flac_on_s1_speakers() {
+            let sonos = Arc::new(TrackingSonosPlayback::new());
+            let sonos_state = create_sonos_state_with_members(&[
+                ("192.168.1.100", "RINCON_S1"),
+                ("192.168.1.101", "RINCON_S2"),
+            ]);
+            let mut s1 = Speaker::new(
+                "192.168.1.100".into(),
+                "RINCON_S1".into(),
+                "Kitchen".into(),
+                None,
+            );
+            s1.sw_gen = Some(1);
+            sonos_state.s1_speakers.update(&[s1]);
+            let coord = create_coordinator_with(
+                Arc::clone(&sonos) as Arc<dyn SonosPlayback>,
+                sonos_state,
+                Arc::new(CollectingEventEmitter::new()),
+            );
+            let speakers = ["192.168.1.100".to_string(), "192.168.1.101".to_string()];
+
+            let flac = coord
+                .create_stream(AudioCodec::Flac, AudioFormat::default(), 200, 20)
+                .unwrap();
+            let (targets, rejected) = coord.resolve_cast_targets(&speakers, &flac);
+            assert_eq!(targets, vec!["192.168.1.101"]);
+            assert_eq!(rejected[0].speaker_ip, "192.168.1.100");
+            assert!(rejected[0].error.as_deref().unwrap().contains("FLAC"));
+
+            let aac = coord
+                .create_stream(AudioCodec::Aac, AudioFormat::default(), 200, 20)
+                .unwrap();
+            let (targets, rejected) = coord.resolve_cast_targets(&speakers, &aac);
+            assert_eq!(targets.len(), 2);
+            assert!(rejected.is_empty());
+        }
+
         #[tokio::test]
         async fn promote_with_single_slave_becomes_standalone() {
             let sonos = Arc::new(TrackingSonosPlayback::new());
@@ -1438,6 +2985,7 @@ mod tests {
                 coordinator_ip: None,
                 coordinator_uuid: Some("RINCON_COORD".to_string()),
                 original_coordinator_uuid: None,
+                artwork_url: None,
             });
             coord.insert_test_session(PlaybackSession {
                 stream_id: stream_id.clone(),
@@ -1448,6 +2996,7 @@ mod tests {
                 coordinator_ip: Some("192.168.1.100".to_string()),
                 coordinator_uuid: Some("RINCON_COORD".to_string()),
                 original_coordinator_uuid: None,
+                artwork_url: None,
             });
 
             let stopped = coord
@@ -1493,6 +3042,7 @@ mod tests {
                 coordinator_ip: None,
                 coordinator_uuid: Some("RINCON_COORD".to_string()),
                 original_coordinator_uuid: None,
+                artwork_url: None,
             });
             coord.insert_test_session(PlaybackSession {
                 stream_id: stream_id.clone(),
@@ -1503,6 +3053,7 @@ mod tests {
                 coordinator_ip: Some("192.168.1.100".to_string()),
                 coordinator_uuid: Some("RINCON_COORD".to_string()),
                 original_coordinator_uuid: None,
+                artwork_url: None,
             });
 
             let stopped = coord
@@ -1537,6 +3088,7 @@ mod tests {
                 coordinator_ip: None,
                 coordinator_uuid: Some("RINCON_COORD".to_string()),
                 original_coordinator_uuid: None,
+                artwork_url: None,
             });
 
             let stopped = coord
@@ -1572,6 +3124,7 @@ mod tests {
                 coordinator_ip: None,
                 coordinator_uuid: Some("RINCON_COORD".to_string()),
                 original_coordinator_uuid: None,
+                artwork_url: None,
             });
             coord.insert_test_session(PlaybackSession {
                 stream_id: "stream1".to_string(),
@@ -1582,6 +3135,7 @@ mod tests {
                 coordinator_ip: Some("192.168.1.100".to_string()),
                 coordinator_uuid: Some("RINCON_COORD".to_string()),
                 original_coordinator_uuid: None,
+                artwork_url: None,
             });
 
             let stopped = coord
@@ -1610,6 +3164,8 @@ mod tests {
                 *groups = vec![ZoneGroup {
                     id: "streaming_group".to_string(),
                     name: "Kitchen".to_string(),
+                    label: "Kitchen + 1".to_string(),
+                    room_count: 2,
                     coordinator_uuid: "RINCON_KITCHEN".to_string(),
                     coordinator_ip: "192.168.1.100".to_string(),
                     members: vec![
@@ -1618,12 +3174,14 @@ mod tests {
                             ip: "192.168.1.100".to_string(),
                             zone_name: "Kitchen".to_string(),
                             model: "One".to_string(),
+                            ..Default::default()
                         },
                         ZoneGroupMember {
                             uuid: "RINCON_OFFICE".to_string(),
                             ip: "192.168.1.101".to_string(),
                             zone_name: "Office".to_string(),
                             model: "One".to_string(),
+                            ..Default::default()
                         },
                     ],
                 }];
@@ -1650,6 +3208,7 @@ mod tests {
                 coordinator_ip: None,
                 coordinator_uuid: Some("RINCON_KITCHEN".to_string()),
                 original_coordinator_uuid: None,
+                artwork_url: None,
             });
             coord.insert_test_session(PlaybackSession {
                 stream_id: stream_id.clone(),
@@ -1661,6 +3220,7 @@ mod tests {
                 coordinator_uuid: Some("RINCON_KITCHEN".to_string()),
                 // Key: Office was standalone before streaming, so no original group
                 original_coordinator_uuid: None,
+                artwork_url: None,
             });
 
             // Remove Kitchen (coordinator) → Office promoted
@@ -1682,5 +3242,96 @@ mod tests {
                  not re-query topology which would return Kitchen's UUID"
             );
         }
+
+        /// Sets up three standalone speakers: a Wi-Fi "One", a wired "Five"
+        /// and a Wi-Fi "Arc", and a coordinator with the given preferences.
+        fn coordinator_with_preferences(prefs: CoordinatorPreferences) -> StreamCoordinator {
+            let sonos_state = create_sonos_state_with_members(&[
+                ("192.168.1.100", "RINCON_ONE"),
+                ("192.168.1.101", "RINCON_FIVE"),
+                ("192.168.1.102", "RINCON_ARC"),
+            ]);
+            for (group, (model, wired)) in sonos_state.groups.write().iter_mut().zip([
+                ("one", false),
+                ("five", true),
+                ("arc", false),
+            ]) {
+                group.members[0].model = model.to_string();
+                group.members[0].wired = Some(wired);
+            }
+            create_coordinator_with_config(
+                Arc::new(TrackingSonosPlayback::new()),
+                sonos_state,
+                Arc::new(CollectingEventEmitter::new()),
+                StreamingConfig {
+                    coordinator: prefs,
+                    ..Default::default()
+                },
+            )
+        }
+
+        fn selected_ip(coord: &StreamCoordinator, ips: &[&str]) -> String {
+            let ips: Vec<String> = ips.iter().map(|ip| ip.to_string()).collect();
+            coord.sync_group.select_coordinator(&ips).unwrap().0
+        }
+
+        #[test]
+        fn coordinator_selection_follows_preferences() {
+            let ips = ["192.168.1.100", "192.168.1.101", "192.168.1.102"];
+
+            let coord = coordinator_with_preferences(CoordinatorPreferences::default());
+            assert_eq!(selected_ip(&coord, &ips), "192.168.1.100");
+
+            let coord = coordinator_with_preferences(CoordinatorPreferences {
+                prefer_wired: true,
+                ..Default::default()
+            });
+            assert_eq!(selected_ip(&coord, &ips), "192.168.1.101");
+
+            // Among Wi-Fi speakers, the strongest signal wins
+            coord
+                .sonos_state
+                .signal_strengths
+                .insert("192.168.1.100".to_string(), 22);
+            coord
+                .sonos_state
+                .signal_strengths
+                .insert("192.168.1.102".to_string(), 45);
+            assert_eq!(
+                selected_ip(&coord, &["192.168.1.100", "192.168.1.102"]),
+                "192.168.1.102"
+            );
+
+            let coord = coordinator_with_preferences(CoordinatorPreferences {
+                model_priority: vec!["ARC".to_string(), "five".to_string()],
+                ..Default::default()
+            });
+            assert_eq!(selected_ip(&coord, &ips), "192.168.1.102");
+        }
+
+        #[test]
+        fn pinned_coordinator_applies_to_its_exact_set() {
+            let coord = coordinator_with_preferences(CoordinatorPreferences {
+                prefer_wired: true,
+                pins: vec![CoordinatorPin {
+                    speakers: vec![
+                        "room 192.168.1.100".to_string(),
+                        "192.168.1.101".to_string(),
+                    ],
+                    coordinator: "192.168.1.100".to_string(),
+                }],
+                ..Default::default()
+            });
+
+            assert_eq!(
+                selected_ip(&coord, &["192.168.1.101", "192.168.1.100"]),
+                "192.168.1.100"
+            );
+            // A different set ignores the pin
+            assert_eq!(
+                selected_ip(&coord, &["192.168.1.100", "192.168.1.101", "192.168.1.102"]),
+                "192.168.1.101"
+            );
+        }
     }
 }
@@ -7,6 +7,7 @@
 //! - Coordinator promotion (slave takeover when coordinator removed)
 //! - Original group restoration after streaming ends
 
+use std::cmp::Reverse;
 use std::sync::Arc;
 use std::time::Duration;
 
@@ -16,7 +17,7 @@ use crate::context::NetworkContext;
 use crate::events::{EventEmitter, SpeakerRemovalReason, StreamEvent};
 use crate::sonos::subscription_arbiter::SubscriptionArbiter;
 use crate::sonos::SonosPlayback;
-use crate::state::SonosState;
+use crate::state::{CoordinatorPreferences, SonosState};
 use crate::stream::{AudioCodec, StreamRegistry};
 use crate::utils::now_millis;
 
@@ -37,11 +38,13 @@ pub(crate) struct SyncGroupManager {
     arbiter: Arc<SubscriptionArbiter>,
     stream_registry: Arc<StreamRegistry>,
     network: NetworkContext,
+    preferences: CoordinatorPreferences,
     topology_refresh: Option<Arc<Notify>>,
 }
 
 impl SyncGroupManager {
     /// Creates a new SyncGroupManager.
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         sessions: Arc<PlaybackSessionStore>,
         sonos: Arc<dyn SonosPlayback>,
@@ -50,6 +53,7 @@ impl SyncGroupManager {
         arbiter: Arc<SubscriptionArbiter>,
         stream_registry: Arc<StreamRegistry>,
         network: NetworkContext,
+        preferences: CoordinatorPreferences,
     ) -> Self {
         Self {
             sessions,
@@ -59,6 +63,7 @@ impl SyncGroupManager {
             arbiter,
             stream_registry,
             network,
+            preferences,
             topology_refresh: None,
         }
     }
@@ -132,8 +137,11 @@ impl SyncGroupManager {
 
     /// Selects a coordinator speaker for synchronized playback.
     ///
-    /// Prefers speakers that are already Sonos group coordinators (have a coordinator UUID).
-    /// Falls back to the first speaker with any resolvable UUID.
+    /// A pin for exactly this set of speakers wins outright. Otherwise, the
+    /// configured preferences rank the candidates: wired speakers first (if
+    /// `prefer_wired`), then by `model_priority`, then by Wi-Fi signal (again
+    /// if `prefer_wired`). Ties go to speakers that are already Sonos group
+    /// coordinators, then to the earliest in the list.
     ///
     /// # Returns
     /// Tuple of (coordinator_ip, coordinator_uuid, remaining_slave_ips), or None if
@@ -142,29 +150,80 @@ impl SyncGroupManager {
         &self,
         speaker_ips: &[String],
     ) -> Option<(String, String, Vec<String>)> {
-        // Try to find a speaker that's already a Sonos group coordinator
-        for ip in speaker_ips {
-            if let Some(uuid) = self.sonos_state.get_coordinator_uuid_by_ip(ip) {
-                let slaves: Vec<_> = speaker_ips.iter().filter(|s| *s != ip).cloned().collect();
-                log::info!(
-                    "[GroupSync] Selected coordinator {} (uuid={}) - existing Sonos coordinator",
-                    ip,
-                    uuid
-                );
-                return Some((ip.clone(), uuid, slaves));
+        let with_slaves = |ip: &String, uuid: String, reason: &str| {
+            let slaves: Vec<_> = speaker_ips.iter().filter(|s| *s != ip).cloned().collect();
+            log::info!(
+                "[GroupSync] Selected coordinator {} (uuid={}) - {}",
+                ip,
+                uuid,
+                reason
+            );
+            Some((ip.clone(), uuid, slaves))
+        };
+
+        if let Some(ip) = self.pinned_coordinator(speaker_ips) {
+            if let Some(uuid) = self.sonos_state.get_member_uuid_by_ip(ip) {
+                return with_slaves(ip, uuid, "pinned");
             }
         }
 
-        // Fallback: use first speaker if we can get its UUID from member lookup
-        let first = speaker_ips.first()?;
-        let uuid = self.sonos_state.get_member_uuid_by_ip(first)?;
-        let slaves: Vec<_> = speaker_ips.iter().skip(1).cloned().collect();
-        log::info!(
-            "[GroupSync] Selected coordinator {} (uuid={}) - first speaker fallback",
-            first,
-            uuid
-        );
-        Some((first.clone(), uuid, slaves))
+        let prefs = &self.preferences;
+        let ranked = speaker_ips
+            .iter()
+            .enumerate()
+            .filter_map(|(index, ip)| {
+                let member = self.sonos_state.get_member_by_ip(ip)?;
+                let existing = self.sonos_state.get_coordinator_uuid_by_ip(ip).is_some();
+                let signal = prefs
+                    .prefer_wired
+                    .then(|| self.sonos_state.signal_strengths.get(ip).map(|s| *s))
+                    .flatten();
+                let rank = (
+                    prefs.prefer_wired && member.wired != Some(true),
+                    model_rank(&prefs.model_priority, &member.model),
+                    Reverse(signal),
+                    !existing,
+                    index,
+                );
+                Some((rank, ip, member.uuid))
+            })
+            .min_by(|a, b| a.0.cmp(&b.0));
+
+        let ((not_wired, model, Reverse(signal), not_existing, _), ip, uuid) = ranked?;
+        let reason = if prefs.prefer_wired && !not_wired {
+            "wired speaker"
+        } else if model < prefs.model_priority.len() {
+            "preferred model"
+        } else if signal.is_some() {
+            "strongest Wi-Fi signal"
+        } else if !not_existing {
+            "existing Sonos coordinator"
+        } else {
+            "first speaker fallback"
+        };
+        with_slaves(ip, uuid, reason)
+    }
+
+    /// Returns the coordinator pinned for exactly this set of speakers.
+    fn pinned_coordinator<'a>(&self, speaker_ips: &'a [String]) -> Option<&'a String> {
+        let matches = |ip: &str, name: &str| {
+            ip == name
+                || self
+                    .sonos_state
+                    .get_member_by_ip(ip)
+                    .is_some_and(|m| m.zone_name.eq_ignore_ascii_case(name))
+        };
+
+        self.preferences.pins.iter().find_map(|pin| {
+            let same_set = pin.speakers.len() == speaker_ips.len()
+                && speaker_ips
+                    .iter()
+                    .all(|ip| pin.speakers.iter().any(|name| matches(ip, name)));
+            if !same_set {
+                return None;
+            }
+            speaker_ips.iter().find(|ip| matches(ip, &pin.coordinator))
+        })
     }
 
     // ─────────────────────────────────────────────────────────────────────────────
@@ -280,6 +339,7 @@ impl SyncGroupManager {
                     coordinator_ip: Some(coordinator_ip.to_string()),
                     coordinator_uuid: Some(coordinator_uuid.to_string()),
                     original_coordinator_uuid,
+                    artwork_url: None,
                 });
 
                 self.emit_event(StreamEvent::PlaybackStarted {
@@ -896,6 +956,7 @@ impl SyncGroupManager {
             coordinator_ip: None,
             coordinator_uuid: Some(promoted_uuid.clone()),
             original_coordinator_uuid: promoted_original_coordinator,
+            artwork_url: Some(artwork_url),
         });
 
         log::info!(
@@ -954,6 +1015,7 @@ impl SyncGroupManager {
                     coordinator_ip: Some(promoted_ip.clone()),
                     coordinator_uuid: Some(promoted_uuid.clone()),
                     original_coordinator_uuid: slave_session.original_coordinator_uuid.clone(),
+                    artwork_url: None,
                 });
             })
             .collect();
@@ -966,3 +1028,13 @@ impl SyncGroupManager {
         Ok(vec![coordinator_ip.to_string()])
     }
 }
+
+/// Position of the first `priority` entry found in `model`, or the list's
+/// length when none is.
+fn model_rank(priority: &[String], model: &str) -> usize {
+    let model = model.to_lowercase();
+    priority
+        .iter()
+        .position(|wanted| model.contains(&wanted.to_lowercase()))
+        .unwrap_or(priority.len())
+}
@@ -2,34 +2,69 @@
 //!
 //! Responsibilities:
 //! - Background topology discovery loop
+//! - Fast startup from the speakers cached by the last discovery
 //! - IP change detection and re-subscription
 //! - GENA subscription lifecycle management
 //! - Manual refresh coordination
+//! - Event-driven topology updates, with polling as a fallback
+//! - Passive SSDP NOTIFY listening for speakers joining or leaving
+//! - Periodic DNS re-resolution of manual speakers added by hostname
+//! - Speaker added/removed/address-changed events from consecutive topologies
 //! - Network health monitoring
+//! - Wi-Fi signal strength collection
+//! - Cross-subnet speaker detection
 
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use futures::future::join_all;
-use parking_lot::RwLock;
+use parking_lot::{Mutex, RwLock};
 use reqwest::Client;
-use tokio::sync::Notify;
+use tokio::sync::{mpsc, watch, Notify};
 use tokio_util::sync::CancellationToken;
 
+use crate::clock::Clock;
 use crate::context::NetworkContext;
-use crate::error::{ThaumicError, ThaumicResult};
-use crate::events::{EventEmitter, NetworkEvent, NetworkHealth, TopologyEvent};
+use crate::error::{SoapResult, ThaumicError, ThaumicResult};
+use crate::events::{CrossSubnetHint, EventEmitter, NetworkEvent, NetworkHealth, TopologyEvent};
+use crate::operations::{Operation, OperationKind, OperationRegistry};
+use crate::protocol_constants::TOPOLOGY_FALLBACK_REFRESH_SECS;
 use crate::runtime::TokioSpawner;
-use crate::sonos::discovery::{probe_speaker_by_ip, Speaker};
+use crate::sonos::discovery::ssdp::{self, SsdpNotification};
+use crate::sonos::discovery::{probe_speaker_by_host, resolve_speaker_host, Speaker};
 use crate::sonos::gena::GenaSubscriptionManager;
+use crate::sonos::network_status::collect_network_status;
 use crate::sonos::subscription_arbiter::SubscriptionArbiter;
-use crate::sonos::types::ZoneGroup;
+use crate::sonos::types::{SpeakerMove, ZoneGroup, ZoneGroupMember};
 use crate::sonos::SonosService;
 use crate::sonos::SonosTopologyClient;
 use crate::state::{ManualSpeakerConfig, SonosState};
+use crate::utils::is_hostname;
+
+use super::speaker_cache::SpeakerCache;
+use super::stream_coordinator::StreamCoordinator;
+
+/// Phases of a full topology refresh, reported as operation progress.
+const REFRESH_PHASES: u64 = 4;
+
+/// How long announcements from one speaker are ignored after they triggered
+/// a refresh. Speakers repeat each NOTIFY for several device types, and one
+/// outside the household would otherwise trigger a refresh every time.
+const NOTIFY_REFRESH_COOLDOWN: Duration = Duration::from_secs(60);
+
+/// How often manual speakers added by hostname are looked up again, so a
+/// new address from DHCP is picked up without waiting for a full refresh.
+const MANUAL_HOST_RESOLVE_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Zone groups of one Sonos household.
+struct Household {
+    /// Speaker the groups were fetched from.
+    source_ip: String,
+    groups: Vec<ZoneGroup>,
+}
 
 /// Current network health state with reason.
 #[derive(Debug, Clone)]
@@ -38,6 +73,9 @@ pub struct NetworkHealthState {
     pub health: NetworkHealth,
     /// Reason for the current health status (if degraded).
     pub reason: Option<String>,
+    /// UUIDs of speakers running S1 software, whose streams are limited
+    /// (see [`crate::sonos::compat`]).
+    pub s1_speakers: Vec<String>,
 }
 
 impl Default for NetworkHealthState {
@@ -45,6 +83,7 @@ impl Default for NetworkHealthState {
         Self {
             health: NetworkHealth::Ok,
             reason: None,
+            s1_speakers: Vec::new(),
         }
     }
 }
@@ -53,6 +92,8 @@ impl Default for NetworkHealthState {
 pub struct TopologyMonitorConfig {
     /// Interval between automatic topology refreshes (seconds).
     pub topology_refresh_interval_secs: u64,
+    /// Skip SSDP and mDNS, relying on manually added speaker IPs.
+    pub unicast_only: bool,
     /// Network configuration (port, local IP).
     pub network: NetworkContext,
     /// Notifier for manual refresh requests.
@@ -61,6 +102,14 @@ pub struct TopologyMonitorConfig {
     pub http_client: Client,
     /// Task spawner for background tasks.
     pub spawner: TokioSpawner,
+    /// Registry that full discovery runs report progress to.
+    pub operations: Arc<OperationRegistry>,
+    /// Time source for the refresh schedule and event timestamps.
+    pub clock: Arc<dyn Clock>,
+    /// Speakers of the last discovery, used before the first one completes.
+    pub speaker_cache: Arc<SpeakerCache>,
+    /// Playback sessions to move when a speaker changes address.
+    pub stream_coordinator: Arc<StreamCoordinator>,
 }
 
 /// Monitors Sonos network topology and manages GENA subscriptions.
@@ -77,19 +126,41 @@ pub struct TopologyMonitor {
     speakers_discovered: AtomicBool,
     /// Interval between automatic topology refreshes (seconds).
     topology_refresh_interval_secs: u64,
+    /// Skip SSDP and mDNS, relying on manually added speaker IPs.
+    unicast_only: bool,
     /// Network configuration (port, local IP).
     network: NetworkContext,
+    /// Speakers on other subnets as last reported, sorted.
+    cross_subnet_speakers: Mutex<Vec<String>>,
     refresh_notify: Arc<Notify>,
     /// Token to signal background tasks to stop.
     cancel_token: CancellationToken,
+    /// Whether discovery is paused (no SSDP/mDNS scans or GENA subscriptions).
+    paused: watch::Sender<bool>,
     /// App data directory for loading manual speaker configuration.
     app_data_dir: RwLock<Option<PathBuf>>,
+    /// Last resolved address of each manual speaker added by hostname.
+    resolved_hosts: Mutex<HashMap<String, String>>,
+    /// Last successful probe of each manual speaker, by configured entry.
+    /// Used while a probe fails, so manual speakers are never pruned.
+    manual_speakers: Mutex<HashMap<String, Speaker>>,
     /// HTTP client for probing manual speaker IPs.
     http_client: Client,
     /// Task spawner for background tasks.
     spawner: TokioSpawner,
     /// Subscription arbiter for RenderingControl/GroupRenderingControl conflict resolution.
     arbiter: Arc<SubscriptionArbiter>,
+    /// Registry that full discovery runs report progress to.
+    operations: Arc<OperationRegistry>,
+    /// Time source for the refresh schedule and event timestamps.
+    clock: Arc<dyn Clock>,
+    /// Speakers of the last discovery, used before the first one completes.
+    speaker_cache: Arc<SpeakerCache>,
+    /// Playback sessions to move when a speaker changes address.
+    stream_coordinator: Arc<StreamCoordinator>,
+    /// Members of the last published topology by UUID, diffed against the
+    /// next one for speaker lifecycle events. `None` until the first one.
+    published_members: Mutex<Option<BTreeMap<String, ZoneGroupMember>>>,
 }
 
 impl TopologyMonitor {
@@ -117,13 +188,23 @@ impl TopologyMonitor {
             network_health: RwLock::new(NetworkHealthState::default()),
             speakers_discovered: AtomicBool::new(false),
             topology_refresh_interval_secs: config.topology_refresh_interval_secs,
+            unicast_only: config.unicast_only,
             network: config.network,
+            cross_subnet_speakers: Mutex::new(Vec::new()),
             refresh_notify: config.refresh_notify,
             cancel_token: CancellationToken::new(),
+            paused: watch::Sender::new(false),
             app_data_dir: RwLock::new(None),
+            resolved_hosts: Mutex::new(HashMap::new()),
+            manual_speakers: Mutex::new(HashMap::new()),
             http_client: config.http_client,
             spawner: config.spawner,
             arbiter,
+            operations: config.operations,
+            clock: config.clock,
+            speaker_cache: config.speaker_cache,
+            stream_coordinator: config.stream_coordinator,
+            published_members: Mutex::new(None),
         }
     }
 
@@ -149,7 +230,10 @@ impl TopologyMonitor {
 
     /// Returns the current network health state.
     pub fn get_network_health(&self) -> NetworkHealthState {
-        self.network_health.read().clone()
+        NetworkHealthState {
+            s1_speakers: self.sonos_state.s1_speakers.uuids(),
+            ..self.network_health.read().clone()
+        }
     }
 
     /// Updates network health and emits an event if it changed.
@@ -170,10 +254,7 @@ impl TopologyMonitor {
             state.health = health;
             state.reason = reason.clone();
 
-            let timestamp = std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .unwrap_or_default()
-                .as_millis() as u64;
+            let timestamp = self.clock.now_millis();
 
             self.emitter.emit_network(NetworkEvent::HealthChanged {
                 health,
@@ -185,11 +266,96 @@ impl TopologyMonitor {
         }
     }
 
+    /// Broadcasts a new topology, followed by an event for each speaker that
+    /// was added, removed or moved since the last one.
+    ///
+    /// The first topology only sets the baseline, so startup doesn't announce
+    /// every speaker as new.
+    fn publish_groups(&self, groups: Vec<ZoneGroup>) {
+        let timestamp = self.clock.now_millis();
+        let members = members_by_uuid(&groups);
+        let changes = self
+            .published_members
+            .lock()
+            .replace(members.clone())
+            .map(|previous| speaker_changes(&previous, &members, timestamp))
+            .unwrap_or_default();
+
+        self.emitter
+            .emit_topology(TopologyEvent::GroupsDiscovered { groups, timestamp });
+        for change in changes {
+            self.emitter.emit_topology(change);
+        }
+    }
+
+    /// Reports speakers on subnets we have no address on, when that set
+    /// changes.
+    fn check_cross_subnet_speakers(&self, speakers: &[Speaker]) {
+        let mut speaker_ips: Vec<String> = speakers
+            .iter()
+            .filter(|s| self.network.is_cross_subnet(&s.ip) == Some(true))
+            .map(|s| s.ip.clone())
+            .collect();
+        speaker_ips.sort();
+
+        let mut reported = self.cross_subnet_speakers.lock();
+        if *reported == speaker_ips {
+            return;
+        }
+        *reported = speaker_ips.clone();
+        drop(reported);
+
+        let local_ip = self.network.get_local_ip();
+        if !speaker_ips.is_empty() {
+            log::warn!(
+                "[TopologyMonitor] Speakers on other subnets than {}: {}",
+                local_ip,
+                speaker_ips.join(", ")
+            );
+        }
+        self.emitter
+            .emit_network(NetworkEvent::CrossSubnetSpeakers {
+                local_ip,
+                hints: cross_subnet_hints(self.unicast_only),
+                speaker_ips,
+                timestamp: self.clock.now_millis(),
+            });
+    }
+
     /// Triggers a manual topology refresh.
     pub fn trigger_refresh(&self) {
         self.refresh_notify.notify_one();
     }
 
+    /// Pauses discovery until [`resume`](Self::resume) is called.
+    ///
+    /// The monitoring loop drops all GENA subscriptions and stops scanning the
+    /// network. Existing zone groups are kept so the UI still shows the last
+    /// known topology. Returns `false` if discovery was already paused.
+    pub fn pause(&self) -> bool {
+        let changed = self
+            .paused
+            .send_if_modified(|paused| !std::mem::replace(paused, true));
+        if changed {
+            // Wake the loop so it unsubscribes now rather than at the next tick
+            self.refresh_notify.notify_one();
+        }
+        changed
+    }
+
+    /// Resumes paused discovery with an immediate full refresh.
+    ///
+    /// Returns `false` if discovery was not paused.
+    pub fn resume(&self) -> bool {
+        self.paused
+            .send_if_modified(|paused| std::mem::replace(paused, false))
+    }
+
+    /// Returns whether discovery is currently paused.
+    pub fn is_paused(&self) -> bool {
+        *self.paused.borrow()
+    }
+
     /// Starts the GENA renewal background task.
     pub fn start_renewal_task(&self) {
         self.gena_manager.clone().start_renewal_task(&self.spawner);
@@ -204,21 +370,28 @@ impl TopologyMonitor {
     /// - Responds to manual refresh requests
     /// - Stops gracefully when the cancellation token is triggered
     pub fn start_monitoring(self: Arc<Self>) {
+        self.start_notify_listener();
+        self.start_warm_start();
+        self.start_host_resolver();
+
         let cancel_token = self.cancel_token.clone();
         let spawner = self.spawner.clone();
         spawner.spawn(async move {
-            // Wait for the server to start and port to be assigned
+            // Wait for the server to start and port to be assigned. The port may
+            // already be set when monitoring starts after the server (lingering mode).
             loop {
+                let port_assigned = self.network.port_notify.notified();
+                tokio::pin!(port_assigned);
+                port_assigned.as_mut().enable();
+                if self.network.get_port() > 0 {
+                    break;
+                }
                 tokio::select! {
                     _ = cancel_token.cancelled() => {
                         log::info!("[TopologyMonitor] Cancelled while waiting for server");
                         return;
                     }
-                    _ = self.network.port_notify.notified() => {
-                        if self.network.get_port() > 0 {
-                            break;
-                        }
-                    }
+                    _ = port_assigned => {}
                 }
             }
 
@@ -227,25 +400,54 @@ impl TopologyMonitor {
             let mut callback_url = self.network.gena_callback_url();
             log::info!("[TopologyMonitor] GENA callback URL: {}", callback_url);
 
-            let mut interval =
-                tokio::time::interval(Duration::from_secs(self.topology_refresh_interval_secs));
+            // The first refresh runs immediately
+            let refresh_period = Duration::from_secs(self.topology_refresh_interval_secs);
+            let mut next_refresh = self.clock.sleep(Duration::ZERO);
+
+            let mut paused_rx = self.paused.subscribe();
+            let mut last_full_refresh: Option<Instant> = None;
 
             loop {
-                let is_manual_refresh = tokio::select! {
+                let mut is_manual_refresh = tokio::select! {
                     _ = cancel_token.cancelled() => {
                         log::info!("[TopologyMonitor] Shutting down monitoring loop");
                         break;
                     }
-                    _ = interval.tick() => false,
+                    _ = &mut next_refresh => {
+                        next_refresh = self.clock.sleep(refresh_period);
+                        false
+                    }
                     _ = self.refresh_notify.notified() => {
                         log::info!("[TopologyMonitor] Manual refresh triggered");
                         true
                     }
                 };
 
-                // Reset interval after manual refresh to push back automatic refresh
+                if *paused_rx.borrow_and_update() {
+                    log::info!("[TopologyMonitor] Discovery paused, dropping GENA subscriptions");
+                    self.gena_manager.unsubscribe_all().await;
+
+                    tokio::select! {
+                        _ = cancel_token.cancelled() => {
+                            log::info!("[TopologyMonitor] Shutting down monitoring loop");
+                            break;
+                        }
+                        result = paused_rx.wait_for(|paused| !*paused) => {
+                            if result.is_err() {
+                                break;
+                            }
+                        }
+                    }
+
+                    log::info!("[TopologyMonitor] Discovery resumed");
+                    next_refresh = self.clock.sleep(refresh_period);
+                    // Full refresh: speakers may have changed while paused
+                    is_manual_refresh = false;
+                }
+
+                // Reset schedule after manual refresh to push back automatic refresh
                 if is_manual_refresh {
-                    interval.reset();
+                    next_refresh = self.clock.sleep(refresh_period);
                 }
 
                 // Check for IP changes (e.g., laptop moved networks)
@@ -264,12 +466,43 @@ impl TopologyMonitor {
                         self.gena_manager.unsubscribe_all().await;
                     }
                 }
+                // An interface coming or going changes the callback address
+                // speakers on its subnet should use
+                if self.network.refresh_subnet_addresses() {
+                    log::warn!(
+                        "[TopologyMonitor] Local subnets changed: {:?}. Re-subscribing...",
+                        self.network
+                            .subnet_addresses()
+                            .iter()
+                            .map(ToString::to_string)
+                            .collect::<Vec<_>>()
+                    );
+                    self.arbiter.leave_all_sync_sessions(&callback_url).await;
+                    self.gena_manager.unsubscribe_all().await;
+                }
+
+                // While ZoneGroupTopology events arrive, grouping changes trigger
+                // their own refreshes; the periodic scan only catches missed events
+                // and new speakers
+                let topology_events_active = !self
+                    .gena_manager
+                    .get_subscribed_ips(SonosService::ZoneGroupTopology)
+                    .is_empty();
+                if !is_manual_refresh
+                    && !full_refresh_due(
+                        last_full_refresh,
+                        topology_events_active,
+                        self.clock.now(),
+                    )
+                {
+                    continue;
+                }
 
                 // Manual refreshes (from sync session join/unjoin) use the quick path
                 // that skips SSDP discovery (~5s) and goes straight to SOAP (~300ms).
                 // Falls back to full refresh if quick path fails (no known speakers, etc).
                 if is_manual_refresh {
-                    match self.quick_refresh_zone_groups().await {
+                    match self.quick_refresh_zone_groups(&callback_url).await {
                         Ok(()) => {
                             log::info!("[TopologyMonitor] Quick refresh succeeded");
                             continue;
@@ -283,47 +516,239 @@ impl TopologyMonitor {
                     }
                 }
 
-                if let Err(e) = self.refresh_topology(&callback_url).await {
-                    match &e {
-                        ThaumicError::SpeakerNotFound(_) => {
-                            log::debug!("[TopologyMonitor] No speakers discovered");
-                        }
-                        _ => {
-                            log::error!("[TopologyMonitor] {}", e);
-                        }
+                // Full refreshes take several seconds; register them so clients
+                // can watch progress and cancel a scan they don't need
+                last_full_refresh = Some(self.clock.now());
+                let operation = self.operations.start(OperationKind::Discovery, None);
+                match operation
+                    .run(self.refresh_topology(&callback_url, &operation))
+                    .await
+                {
+                    Some(Ok(())) => {}
+                    Some(Err(ThaumicError::SpeakerNotFound { .. })) => {
+                        log::debug!("[TopologyMonitor] No speakers discovered");
+                    }
+                    Some(Err(e)) => {
+                        log::error!("[TopologyMonitor] {}", e);
+                    }
+                    None => {
+                        log::info!("[TopologyMonitor] Topology refresh cancelled");
+                    }
+                }
+            }
+        });
+    }
+
+    /// Fetches the topology from cached speaker IPs so groups appear right
+    /// after launch, while the first full discovery is still running.
+    ///
+    /// Cached speakers are asked in turn until one answers. The result is
+    /// dropped if the full discovery got there first.
+    fn start_warm_start(self: &Arc<Self>) {
+        let cached = self.speaker_cache.speakers();
+        if cached.is_empty() {
+            return;
+        }
+        let monitor = Arc::clone(self);
+        self.spawner.spawn(async move {
+            let started = Instant::now();
+            let ignored = &monitor.sonos_state.ignored_speakers;
+            for speaker in cached
+                .iter()
+                .filter(|speaker| !ignored.is_ignored(&speaker.uuid, &speaker.ip))
+            {
+                if monitor.cancel_token.is_cancelled() {
+                    return;
+                }
+                let groups = match monitor.sonos.get_zone_groups(&speaker.ip).await {
+                    Ok(groups) if !groups.is_empty() => ignored.filter_groups(groups),
+                    Ok(_) => continue,
+                    Err(e) => {
+                        log::debug!(
+                            "[TopologyMonitor] Cached speaker {} ({}) not answering: {}",
+                            speaker.name,
+                            speaker.ip,
+                            e
+                        );
+                        continue;
+                    }
+                };
+
+                {
+                    let mut state = monitor.sonos_state.groups.write();
+                    if monitor.speakers_discovered.load(Ordering::Relaxed) {
+                        return;
+                    }
+                    *state = groups.clone();
+                }
+                log::info!(
+                    "[TopologyMonitor] {} group(s) from cached speaker {} in {}ms",
+                    groups.len(),
+                    speaker.ip,
+                    started.elapsed().as_millis()
+                );
+                monitor.publish_groups(groups);
+                return;
+            }
+            log::info!(
+                "[TopologyMonitor] None of {} cached speaker(s) answered",
+                cached.len()
+            );
+        });
+    }
+
+    /// Starts looking up manual speakers added by hostname every
+    /// [`MANUAL_HOST_RESOLVE_INTERVAL`].
+    ///
+    /// A hostname resolving to a different address than at the last probe
+    /// triggers a refresh, so a speaker that got a new address from DHCP
+    /// keeps working even while GENA events defer full refreshes.
+    fn start_host_resolver(self: &Arc<Self>) {
+        let monitor = Arc::clone(self);
+        self.spawner.spawn(async move {
+            loop {
+                tokio::select! {
+                    _ = monitor.cancel_token.cancelled() => return,
+                    _ = monitor.clock.sleep(MANUAL_HOST_RESOLVE_INTERVAL) => {}
+                }
+                if monitor.is_paused() {
+                    continue;
+                }
+                let Some(app_data_dir) = monitor.get_app_data_dir() else {
+                    continue;
+                };
+                let config = ManualSpeakerConfig::load(&app_data_dir);
+                for host in config.speaker_ips.iter().filter(|e| is_hostname(e)) {
+                    let Ok(ip) = resolve_speaker_host(host).await else {
+                        continue;
+                    };
+                    let previous = monitor.resolved_hosts.lock().get(host).cloned();
+                    if previous.is_some_and(|previous| previous != ip) {
+                        log::info!(
+                            "[TopologyMonitor] Manual speaker {} now resolves to {}, refreshing topology",
+                            host,
+                            ip
+                        );
+                        monitor.trigger_refresh();
+                        break;
                     }
                 }
             }
         });
     }
 
+    /// Starts listening for SSDP NOTIFY announcements.
+    ///
+    /// Speakers the topology doesn't know yet (or knows at another address)
+    /// announcing themselves, and known speakers saying goodbye, trigger a
+    /// quick refresh, so they show up within seconds instead of at the next
+    /// scheduled scan. Skipped in unicast-only mode.
+    fn start_notify_listener(self: &Arc<Self>) {
+        if self.unicast_only {
+            return;
+        }
+
+        let (tx, mut rx) = mpsc::channel(64);
+        let cancel_token = self.cancel_token.clone();
+        self.spawner.spawn(async move {
+            if let Err(e) = ssdp::listen_notify(tx, cancel_token).await {
+                log::warn!(
+                    "[TopologyMonitor] SSDP NOTIFY listener unavailable ({}), relying on periodic discovery",
+                    e
+                );
+            }
+        });
+
+        let monitor = Arc::clone(self);
+        self.spawner.spawn(async move {
+            let mut last_triggered: HashMap<String, Instant> = HashMap::new();
+            while let Some(notification) = rx.recv().await {
+                if monitor.is_paused() {
+                    continue;
+                }
+                let Some(uuid) = monitor.topology_change(&notification) else {
+                    continue;
+                };
+
+                let now = monitor.clock.now();
+                last_triggered.retain(|_, at| now.duration_since(*at) < NOTIFY_REFRESH_COOLDOWN);
+                if last_triggered.contains_key(&uuid) {
+                    continue;
+                }
+                last_triggered.insert(uuid.clone(), now);
+
+                log::info!(
+                    "[TopologyMonitor] SSDP {} from {}, refreshing topology",
+                    match notification {
+                        SsdpNotification::Alive(_) => "alive",
+                        SsdpNotification::ByeBye { .. } => "byebye",
+                    },
+                    uuid
+                );
+                monitor.trigger_refresh();
+            }
+        });
+    }
+
+    /// Returns the speaker UUID if an announcement differs from the known
+    /// topology: a new speaker, a known one at a new address, or a known
+    /// one leaving.
+    fn topology_change(&self, notification: &SsdpNotification) -> Option<String> {
+        let groups = self.sonos_state.groups.read();
+        let known = |uuid: &str| {
+            groups
+                .iter()
+                .flat_map(|g| g.members.iter())
+                .find(|m| m.uuid == uuid)
+        };
+
+        let ignored = &self.sonos_state.ignored_speakers;
+        match notification {
+            SsdpNotification::Alive(speaker) if ignored.is_ignored(&speaker.uuid, &speaker.ip) => {
+                None
+            }
+            SsdpNotification::Alive(speaker) => (!known(&speaker.uuid)
+                .is_some_and(|m| m.ip == speaker.ip))
+            .then(|| speaker.uuid.clone()),
+            SsdpNotification::ByeBye { uuid } => known(uuid).map(|_| uuid.clone()),
+        }
+    }
+
     /// Performs a lightweight zone group refresh using a known speaker IP.
     ///
     /// Skips SSDP discovery and goes straight to the SOAP `GetZoneGroupState` call,
     /// reducing refresh time from ~5s to ~300ms. Used for manual refresh triggers
-    /// (e.g., after sync session join/unjoin) where we know speakers are already on
-    /// the network and just need updated group topology.
-    async fn quick_refresh_zone_groups(&self) -> ThaumicResult<()> {
-        // Pick a coordinator IP from current state
-        let coordinator_ip = {
-            let groups = self.sonos_state.groups.read();
-            groups.first().map(|g| g.coordinator_ip.clone())
-        };
+    /// (e.g., after sync session join/unjoin, or a ZoneGroupTopology event) where we
+    /// know speakers are already on the network and just need updated group topology.
+    /// Coordinator subscriptions follow the new topology.
+    async fn quick_refresh_zone_groups(&self, callback_url: &str) -> ThaumicResult<()> {
+        // Ask the current coordinators, one per household
+        let coordinators: Vec<(String, String)> = self
+            .sonos_state
+            .groups
+            .read()
+            .iter()
+            .map(|g| (g.coordinator_uuid.clone(), g.coordinator_ip.clone()))
+            .collect();
 
-        let ip = coordinator_ip.ok_or_else(|| {
-            ThaumicError::SpeakerNotFound("no known speakers for quick refresh".to_string())
-        })?;
+        let Some((_, ip)) = coordinators.first() else {
+            return Err(ThaumicError::speaker_not_found(
+                "no known speakers for quick refresh",
+            ));
+        };
 
         log::info!(
             "[TopologyMonitor] Quick refresh: fetching zone groups from {} (SOAP only)",
             ip
         );
 
-        let groups = self
-            .sonos
-            .get_zone_groups(&ip)
+        let groups: Vec<ZoneGroup> = self
+            .fetch_households(&coordinators)
             .await
-            .map_err(|e| ThaumicError::Soap(format!("quick refresh SOAP failed: {}", e)))?;
+            .map_err(|e| ThaumicError::Soap(format!("quick refresh SOAP failed: {}", e)))?
+            .into_iter()
+            .flat_map(|h| h.groups)
+            .collect();
 
         log::info!(
             "[TopologyMonitor] Quick refresh: {} groups found",
@@ -336,12 +761,13 @@ impl TopologyMonitor {
             *state = groups.clone();
         }
 
-        let timestamp = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap_or_default()
-            .as_millis() as u64;
-        self.emitter
-            .emit_topology(TopologyEvent::GroupsDiscovered { groups, timestamp });
+        let coordinator_ips: HashSet<String> =
+            groups.iter().map(|g| g.coordinator_ip.clone()).collect();
+        self.publish_groups(groups);
+
+        self.sync_coordinator_subscriptions(&coordinator_ips, callback_url)
+            .await;
+        self.cleanup_stale_subscriptions(&coordinator_ips).await;
 
         Ok(())
     }
@@ -350,14 +776,22 @@ impl TopologyMonitor {
     ///
     /// Discovers speakers, fetches zone groups, updates state, and syncs subscriptions.
     /// Tracks network health based on discovery and communication success.
-    async fn refresh_topology(&self, callback_url: &str) -> ThaumicResult<()> {
+    /// Reports each phase to `operation`.
+    async fn refresh_topology(
+        &self,
+        callback_url: &str,
+        operation: &Operation,
+    ) -> ThaumicResult<()> {
         log::info!(
             "[TopologyMonitor] Refreshing topology (speakers_discovered={})",
             self.speakers_discovered.load(Ordering::Relaxed)
         );
 
-        // Phase 1a: SSDP Discovery
-        let mut speakers = match self.sonos.discover_speakers().await {
+        // Phase 1a: SSDP Discovery. In unicast-only mode only the remote
+        // subnets are searched, if any are configured.
+        report_phase(operation, 0, "discovering");
+        let discovered = self.sonos.discover_speakers().await;
+        let mut speakers = match discovered {
             Ok(speakers) => {
                 log::info!(
                     "[TopologyMonitor] Discovery found {} speakers",
@@ -377,6 +811,7 @@ impl TopologyMonitor {
         };
 
         // Phase 1b: Probe manual speaker IPs
+        report_phase(operation, 1, "probing_manual_speakers");
         let manual_speakers = self.probe_manual_speakers().await;
         if !manual_speakers.is_empty() {
             log::info!(
@@ -392,6 +827,16 @@ impl TopologyMonitor {
             }
         }
 
+        let found = speakers.len();
+        let ignored = &self.sonos_state.ignored_speakers;
+        speakers.retain(|s| !ignored.is_ignored(&s.uuid, &s.ip));
+        if speakers.len() < found {
+            log::debug!(
+                "[TopologyMonitor] Ignoring {} speaker(s) on the ignore list",
+                found - speakers.len()
+            );
+        }
+
         if speakers.is_empty() {
             // Atomically read and reset the discovery flag
             let was_previously_discovered = self.speakers_discovered.swap(false, Ordering::Relaxed);
@@ -406,14 +851,7 @@ impl TopologyMonitor {
                 state.clear();
             }
 
-            let timestamp = std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .unwrap_or_default()
-                .as_millis() as u64;
-            self.emitter.emit_topology(TopologyEvent::GroupsDiscovered {
-                groups: Vec::new(),
-                timestamp,
-            });
+            self.publish_groups(Vec::new());
 
             // No speakers found - warn about potential VPN/firewall issues
             self.set_network_health(
@@ -421,36 +859,52 @@ impl TopologyMonitor {
                 Some("speakers_unreachable".to_string()),
             );
 
-            return Err(ThaumicError::SpeakerNotFound(
-                "no speakers discovered".to_string(),
-            ));
+            return Err(ThaumicError::speaker_not_found("no speakers discovered"));
         }
 
         // Discovery succeeded - mark that we've seen speakers
         let was_first_discovery = !self.speakers_discovered.swap(true, Ordering::Relaxed);
+        self.check_cross_subnet_speakers(&speakers);
+        self.speaker_cache.update(&speakers);
+        self.sonos_state.s1_speakers.update(&speakers);
+
+        // Speakers are identified by UUID; one seen at a new address (DHCP)
+        // takes its sessions, subscriptions and state along
+        let moves = self.sonos_state.speaker_addresses.observe(
+            speakers.iter().map(|s| (s.uuid.as_str(), s.ip.as_str())),
+            self.clock.now_millis(),
+        );
+        if !moves.is_empty() {
+            self.rekey_speakers(&moves);
+        }
 
         let current_speaker_ips: HashSet<String> = speakers.iter().map(|s| s.ip.clone()).collect();
 
-        // Phase 2: Fetch zone groups (HTTP/SOAP call to speaker)
+        // Phase 2: Fetch zone groups (HTTP/SOAP call to speakers)
+        report_phase(operation, 2, "fetching_groups");
         // Prefer playable speakers - network infrastructure devices (Boost, Bridge)
         // don't participate in zone groups and return empty topology data
-        let query_speaker = speakers
+        let mut query_speakers: Vec<(String, String)> = speakers
             .iter()
-            .find(|s| !s.is_infrastructure_device())
-            .unwrap_or(&speakers[0]);
+            .filter(|s| !s.is_infrastructure_device())
+            .map(|s| (s.uuid.clone(), s.ip.clone()))
+            .collect();
+        if query_speakers.is_empty() {
+            query_speakers.push((speakers[0].uuid.clone(), speakers[0].ip.clone()));
+        }
 
         log::info!(
-            "[TopologyMonitor] Fetching zone groups from {} ({}) (SOAP call)...",
-            query_speaker.ip,
-            query_speaker.name
+            "[TopologyMonitor] Fetching zone groups from {} (SOAP call)...",
+            query_speakers[0].1
         );
-        let groups: Vec<ZoneGroup> = match self.sonos.get_zone_groups(&query_speaker.ip).await {
-            Ok(groups) => {
+        let households = match self.fetch_households(&query_speakers).await {
+            Ok(households) => {
                 log::info!(
-                    "[TopologyMonitor] SOAP succeeded: {} groups found",
-                    groups.len()
+                    "[TopologyMonitor] SOAP succeeded: {} groups found in {} household(s)",
+                    households.iter().map(|h| h.groups.len()).sum::<usize>(),
+                    households.len()
                 );
-                groups
+                households
             }
             Err(e) => {
                 log::error!(
@@ -465,6 +919,10 @@ impl TopologyMonitor {
                 return Err(e.into());
             }
         };
+        let groups: Vec<ZoneGroup> = households
+            .iter()
+            .flat_map(|h| h.groups.iter().cloned())
+            .collect();
 
         // Update stored groups and broadcast to clients
         {
@@ -477,14 +935,9 @@ impl TopologyMonitor {
         }
 
         // Broadcast groups update to WebSocket clients and Tauri frontend
-        let timestamp = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap_or_default()
-            .as_millis() as u64;
-        self.emitter.emit_topology(TopologyEvent::GroupsDiscovered {
-            groups: groups.clone(),
-            timestamp,
-        });
+        self.publish_groups(groups.clone());
+
+        self.refresh_signal_strengths(&groups);
 
         // Collect coordinator IPs
         let coordinator_ips: HashSet<String> =
@@ -492,9 +945,22 @@ impl TopologyMonitor {
 
         // Clean up stale state entries for speakers that left the network
         self.sonos_state.cleanup_stale_entries(&current_speaker_ips);
+        #[cfg(debug_assertions)]
+        {
+            let state_ips: Vec<String> = self
+                .sonos_state
+                .transport_states
+                .iter()
+                .map(|entry| entry.key().clone())
+                .collect();
+            let violations =
+                super::invariants::check_transport_states(&state_ips, &current_speaker_ips);
+            super::invariants::report("topology refresh", &violations);
+        }
 
         // Sync subscriptions with current topology
-        self.ensure_topology_subscription(&speakers, &current_speaker_ips, callback_url)
+        report_phase(operation, 3, "subscribing");
+        self.ensure_topology_subscriptions(&households, callback_url)
             .await;
 
         self.sync_coordinator_subscriptions(&coordinator_ips, callback_url)
@@ -548,6 +1014,50 @@ impl TopologyMonitor {
         Ok(())
     }
 
+    /// Moves the IP-keyed state of speakers that changed address to their
+    /// new IP.
+    fn rekey_speakers(&self, moves: &[SpeakerMove]) {
+        for m in moves {
+            log::info!(
+                "[TopologyMonitor] Speaker {} moved from {} to {}",
+                m.uuid,
+                m.old_ip,
+                m.new_ip
+            );
+        }
+        self.sonos_state.rekey_speakers(moves);
+        self.arbiter.rekey_speakers(moves);
+        let subscriptions = self.gena_manager.rekey_speakers(moves);
+        log::debug!(
+            "[TopologyMonitor] Moved {} GENA subscription(s) to new speaker addresses",
+            subscriptions
+        );
+        self.stream_coordinator.rekey_speakers(moves);
+    }
+
+    /// Reads the Wi-Fi signal of every wireless speaker in the background,
+    /// for coordinator election and diagnostics.
+    fn refresh_signal_strengths(&self, groups: &[ZoneGroup]) {
+        let members: Vec<_> = groups
+            .iter()
+            .flat_map(|g| g.members.iter().cloned())
+            .collect();
+        let client = self.http_client.clone();
+        let sonos_state = Arc::clone(&self.sonos_state);
+        self.spawner.spawn(async move {
+            for speaker in collect_network_status(&client, &members).await {
+                match speaker.signal_strength {
+                    Some(rssi) => {
+                        sonos_state.signal_strengths.insert(speaker.ip, rssi);
+                    }
+                    None => {
+                        sonos_state.signal_strengths.remove(&speaker.ip);
+                    }
+                }
+            }
+        });
+    }
+
     /// Cleans up all GENA subscriptions and stops background tasks (for graceful shutdown).
     pub async fn shutdown(&self) {
         log::info!("[TopologyMonitor] Initiating shutdown");
@@ -555,10 +1065,11 @@ impl TopologyMonitor {
         self.gena_manager.shutdown().await;
     }
 
-    /// Probes manually configured speaker IPs and returns valid speakers.
+    /// Probes manually configured speakers and returns valid speakers.
     ///
-    /// Loads IPs from ManualSpeakerConfig and probes each in parallel.
-    /// Invalid/unreachable IPs are logged and skipped.
+    /// Loads IPs and hostnames from ManualSpeakerConfig and probes each in
+    /// parallel, resolving hostnames again every time.
+    /// Invalid/unreachable entries are logged and skipped.
     async fn probe_manual_speakers(&self) -> Vec<Speaker> {
         let app_data_dir = match self.app_data_dir.read().clone() {
             Some(path) => path,
@@ -574,30 +1085,57 @@ impl TopologyMonitor {
         }
 
         log::debug!(
-            "[TopologyMonitor] Probing {} manual speaker IP(s)",
+            "[TopologyMonitor] Probing {} manual speaker(s)",
             config.speaker_ips.len()
         );
 
-        // Probe all IPs in parallel
+        // Probe all entries in parallel
         let futures: Vec<_> = config
             .speaker_ips
             .iter()
-            .map(|ip| {
-                let ip = ip.clone();
+            .map(|entry| {
+                let entry = entry.clone();
                 let client = self.http_client.clone();
                 async move {
-                    match probe_speaker_by_ip(&client, &ip).await {
+                    match probe_speaker_by_host(&client, &entry).await {
                         Ok(speaker) => {
                             log::debug!(
-                                "[TopologyMonitor] Manual IP {} is valid: {}",
-                                ip,
-                                speaker.name
+                                "[TopologyMonitor] Manual speaker {} is valid: {} ({})",
+                                entry,
+                                speaker.name,
+                                speaker.ip
                             );
+                            if is_hostname(&entry) {
+                                let previous = self
+                                    .resolved_hosts
+                                    .lock()
+                                    .insert(entry.clone(), speaker.ip.clone());
+                                if previous.is_some_and(|previous| previous != speaker.ip) {
+                                    log::info!(
+                                        "[TopologyMonitor] Manual speaker {} moved to {}",
+                                        entry,
+                                        speaker.ip
+                                    );
+                                }
+                            }
+                            self.manual_speakers
+                                .lock()
+                                .insert(entry.clone(), speaker.clone());
                             Some(speaker)
                         }
                         Err(e) => {
-                            log::warn!("[TopologyMonitor] Manual IP {} probe failed: {}", ip, e);
-                            None
+                            let last_known = self.manual_speakers.lock().get(&entry).cloned();
+                            log::warn!(
+                                "[TopologyMonitor] Manual speaker {} probe failed: {}{}",
+                                entry,
+                                e,
+                                if last_known.is_some() {
+                                    ", keeping it"
+                                } else {
+                                    ""
+                                }
+                            );
+                            last_known
                         }
                     }
                 }
@@ -605,6 +1143,12 @@ impl TopologyMonitor {
             .collect();
 
         let results = join_all(futures).await;
+        self.resolved_hosts
+            .lock()
+            .retain(|host, _| config.speaker_ips.contains(host));
+        self.manual_speakers
+            .lock()
+            .retain(|entry, _| config.speaker_ips.contains(entry));
         results.into_iter().flatten().collect()
     }
 
@@ -612,44 +1156,124 @@ impl TopologyMonitor {
     // Subscription Management Helpers
     // ─────────────────────────────────────────────────────────────────────────────
 
-    /// Ensures a ZoneGroupTopology subscription exists on a valid speaker.
-    async fn ensure_topology_subscription(
-        &self,
-        speakers: &[Speaker],
-        current_speaker_ips: &HashSet<String>,
-        callback_url: &str,
-    ) {
+    /// Fetches the zone groups of every household the speakers belong to.
+    ///
+    /// A speaker only reports the groups of its own household, so speakers
+    /// missing from the groups fetched so far are asked in turn; with a
+    /// single household only the first one is. A failure on the first
+    /// speaker is returned. A later household that doesn't answer keeps its
+    /// last known groups, so speakers reachable only through routed subnets
+    /// aren't dropped over a lost request.
+    ///
+    /// # Arguments
+    /// * `speakers` - `(uuid, ip)` of the speakers to ask, preferred first
+    async fn fetch_households(&self, speakers: &[(String, String)]) -> SoapResult<Vec<Household>> {
+        let mut households: Vec<Household> = Vec::new();
+        let mut covered: HashSet<String> = HashSet::new();
+
+        for (uuid, ip) in speakers {
+            if covered.contains(uuid) {
+                continue;
+            }
+            let mut groups = match self.sonos.get_zone_groups(ip).await {
+                Ok(groups) => self.sonos_state.ignored_speakers.filter_groups(groups),
+                Err(e) if households.is_empty() => return Err(e),
+                Err(e) => {
+                    log::warn!(
+                        "[TopologyMonitor] Failed to fetch zone groups from {}: {}, keeping its household's last known groups",
+                        ip,
+                        e
+                    );
+                    let known = self.sonos_state.groups.read();
+                    let household: HashSet<&str> = known
+                        .iter()
+                        .filter(|g| g.members.iter().any(|m| m.uuid == *uuid))
+                        .flat_map(|g| g.members.iter().map(|m| m.uuid.as_str()))
+                        .collect();
+                    known
+                        .iter()
+                        .filter(|g| {
+                            g.members
+                                .iter()
+                                .any(|m| household.contains(m.uuid.as_str()))
+                        })
+                        .cloned()
+                        .collect()
+                }
+            };
+
+            // A speaker missing from its own household's topology reports
+            // the same groups again
+            groups.retain(|g| {
+                !households
+                    .iter()
+                    .any(|h| h.groups.iter().any(|k| k.id == g.id))
+            });
+            covered.insert(uuid.clone());
+            if groups.is_empty() && !households.is_empty() {
+                continue;
+            }
+            covered.extend(
+                groups
+                    .iter()
+                    .flat_map(|g| g.members.iter().map(|m| m.uuid.clone())),
+            );
+            if !households.is_empty() {
+                log::info!(
+                    "[TopologyMonitor] {} belongs to another household ({} groups)",
+                    ip,
+                    groups.len()
+                );
+            }
+            households.push(Household {
+                source_ip: ip.clone(),
+                groups,
+            });
+        }
+
+        Ok(households)
+    }
+
+    /// Ensures each household has a ZoneGroupTopology subscription on one
+    /// of its speakers.
+    async fn ensure_topology_subscriptions(&self, households: &[Household], callback_url: &str) {
         let topology_ips = self
             .gena_manager
             .get_subscribed_ips(SonosService::ZoneGroupTopology);
-        let has_valid_sub = topology_ips
-            .iter()
-            .any(|ip| current_speaker_ips.contains(ip));
 
-        if !has_valid_sub {
-            if let Some(speaker) = speakers.first() {
-                match self
-                    .gena_manager
-                    .subscribe(
-                        speaker.ip.clone(),
-                        SonosService::ZoneGroupTopology,
-                        callback_url.to_string(),
-                    )
-                    .await
-                {
-                    Ok(()) => {
-                        log::info!(
-                            "[TopologyMonitor] Subscribed to ZoneGroupTopology on {}",
-                            speaker.ip
-                        );
-                    }
-                    Err(e) => {
-                        log::error!(
-                            "[TopologyMonitor] Failed to subscribe to ZoneGroupTopology on {}: {}",
-                            speaker.ip,
-                            e
-                        );
-                    }
+        for household in households {
+            let has_valid_sub = topology_ips.iter().any(|ip| {
+                *ip == household.source_ip
+                    || household
+                        .groups
+                        .iter()
+                        .any(|g| g.members.iter().any(|m| m.ip == *ip))
+            });
+            if has_valid_sub {
+                continue;
+            }
+
+            match self
+                .gena_manager
+                .subscribe(
+                    household.source_ip.clone(),
+                    SonosService::ZoneGroupTopology,
+                    callback_url.to_string(),
+                )
+                .await
+            {
+                Ok(()) => {
+                    log::info!(
+                        "[TopologyMonitor] Subscribed to ZoneGroupTopology on {}",
+                        household.source_ip
+                    );
+                }
+                Err(e) => {
+                    log::error!(
+                        "[TopologyMonitor] Failed to subscribe to ZoneGroupTopology on {}: {}",
+                        household.source_ip,
+                        e
+                    );
                 }
             }
         }
@@ -755,3 +1379,150 @@ impl TopologyMonitor {
         }
     }
 }
+
+fn report_phase(operation: &Operation, phase: u64, stage: &'static str) {
+    operation.set_stage(stage);
+    operation.set_progress(phase, Some(REFRESH_PHASES));
+}
+
+/// Whether an automatic tick should run a full refresh.
+///
+/// Always true until the first full refresh, or when no ZoneGroupTopology
+/// subscription delivers events. Otherwise only after the fallback interval.
+fn full_refresh_due(
+    last_full_refresh: Option<Instant>,
+    topology_events_active: bool,
+    now: Instant,
+) -> bool {
+    match last_full_refresh {
+        Some(last) if topology_events_active => {
+            now.saturating_duration_since(last)
+                >= Duration::from_secs(TOPOLOGY_FALLBACK_REFRESH_SECS)
+        }
+        _ => true,
+    }
+}
+
+/// Returns what to suggest for speakers on other subnets. In unicast-only
+/// mode multicast no longer matters, only the speakers' connections back.
+fn cross_subnet_hints(unicast_only: bool) -> Vec<CrossSubnetHint> {
+    if unicast_only {
+        vec![CrossSubnetHint::AllowInbound]
+    } else {
+        vec![
+            CrossSubnetHint::MdnsReflector,
+            CrossSubnetHint::IgmpSnooping,
+            CrossSubnetHint::UnicastOnly,
+            CrossSubnetHint::AllowInbound,
+        ]
+    }
+}
+
+/// Indexes the members of `groups` by UUID.
+fn members_by_uuid(groups: &[ZoneGroup]) -> BTreeMap<String, ZoneGroupMember> {
+    groups
+        .iter()
+        .flat_map(|g| g.members.iter())
+        .map(|m| (m.uuid.clone(), m.clone()))
+        .collect()
+}
+
+/// Returns the speaker lifecycle events between two topologies.
+fn speaker_changes(
+    previous: &BTreeMap<String, ZoneGroupMember>,
+    current: &BTreeMap<String, ZoneGroupMember>,
+    timestamp: u64,
+) -> Vec<TopologyEvent> {
+    let mut changes = Vec::new();
+    for (uuid, member) in current {
+        match previous.get(uuid) {
+            None => changes.push(TopologyEvent::SpeakerAdded {
+                speaker: member.clone(),
+                timestamp,
+            }),
+            Some(old) if old.ip != member.ip => changes.push(TopologyEvent::SpeakerIpChanged {
+                uuid: uuid.clone(),
+                zone_name: member.zone_name.clone(),
+                old_ip: old.ip.clone(),
+                new_ip: member.ip.clone(),
+                timestamp,
+            }),
+            Some(_) => {}
+        }
+    }
+    for (uuid, member) in previous {
+        if !current.contains_key(uuid) {
+            changes.push(TopologyEvent::SpeakerRemoved {
+                uuid: uuid.clone(),
+                speaker_ip: member.ip.clone(),
+                zone_name: member.zone_name.clone(),
+                timestamp,
+            });
+        }
+    }
+    changes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::MockClock;
+
+    #[test]
+    fn topology_events_defer_full_refresh() {
+        let clock = MockClock::new();
+        let last = Some(clock.now());
+        assert!(full_refresh_due(None, true, clock.now()));
+        assert!(full_refresh_due(last, false, clock.now()));
+        assert!(!full_refresh_due(last, true, clock.now()));
+
+        clock.advance(
+            Duration::from_secs(TOPOLOGY_FALLBACK_REFRESH_SECS) - Duration::from_millis(1),
+        );
+        assert!(!full_refresh_due(last, true, clock.now()));
+        clock.advance(Duration::from_millis(1));
+        assert!(full_refresh_due(last, true, clock.now()));
+    }
+
+    fn member(uuid: &str, ip: &str) -> ZoneGroupMember {
+        ZoneGroupMember {
+            uuid: uuid.into(),
+            ip: ip.into(),
+            zone_name: uuid.to_lowercase(),
+            ..Default::default()
+        }
+    }
+
+    fn members(list: &[(&str, &str)]) -> BTreeMap<String, ZoneGroupMember> {
+        let groups = [ZoneGroup {
+            members: list.iter().map(|(uuid, ip)| member(uuid, ip)).collect(),
+            ..Default::default()
+        }];
+        members_by_uuid(&groups)
+    }
+
+    #[test]
+    fn diffs_speakers_between_topologies() {
+        let previous = members(&[("A", "10.0.0.1"), ("B", "10.0.0.2"), ("C", "10.0.0.3")]);
+        let current = members(&[("A", "10.0.0.1"), ("B", "10.0.0.9"), ("D", "10.0.0.4")]);
+
+        let changes = speaker_changes(&previous, &current, 7);
+        assert_eq!(changes.len(), 3);
+        assert!(matches!(
+            &changes[0],
+            TopologyEvent::SpeakerIpChanged { uuid, old_ip, new_ip, .. }
+                if uuid == "B" && old_ip == "10.0.0.2" && new_ip == "10.0.0.9"
+        ));
+        assert!(matches!(
+            &changes[1],
+            TopologyEvent::SpeakerAdded { speaker, .. } if speaker.uuid == "D"
+        ));
+        assert!(matches!(
+            &changes[2],
+            TopologyEvent::SpeakerRemoved { uuid, speaker_ip, zone_name, timestamp: 7 }
+                if uuid == "C" && speaker_ip == "10.0.0.3" && zone_name == "c"
+        ));
+
+        assert!(speaker_changes(&current, &current, 7).is_empty());
+    }
+}
@@ -222,6 +222,7 @@ mod tests {
             },
             coordinator_uuid: Some("RINCON_100".to_string()),
             original_coordinator_uuid: None,
+            artwork_url: None,
         }
     }
 
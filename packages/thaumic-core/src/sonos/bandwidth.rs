@@ -0,0 +1,295 @@
+//! On-demand bandwidth estimation between this server and a speaker.
+//!
+//! Sonos speakers only pull audio at playback rate, so the live stream can't
+//! reveal spare capacity. Instead we open a separate TCP connection to the
+//! speaker's HTTP port and upload a short burst as a request body, timing how
+//! fast the socket accepts it. The send buffer is kept small so the kernel
+//! can't absorb most of the burst and inflate the result.
+//!
+//! The speaker rejects the request (unknown path), but the body must still
+//! cross the network before its TCP window opens, which is what we measure.
+
+use std::net::{IpAddr, SocketAddr};
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+use thiserror::Error;
+use tokio::io::AsyncWriteExt;
+use tokio::net::TcpSocket;
+
+use crate::error::{ErrorCode, Remediation};
+use crate::sonos::utils::SONOS_PORT;
+use crate::stream::AudioCodec;
+
+/// Total burst size uploaded to the speaker.
+const PROBE_BYTES: usize = 2 * 1024 * 1024;
+
+/// Write chunk size for the burst.
+const PROBE_CHUNK_BYTES: usize = 16 * 1024;
+
+/// Requested socket send buffer size (limits how much the kernel can absorb).
+const PROBE_SEND_BUFFER_BYTES: u32 = 64 * 1024;
+
+/// Minimum bytes that must be sent for the estimate to be meaningful.
+const MIN_PROBE_BYTES: u64 = 256 * 1024;
+
+/// Timeout for establishing the TCP connection.
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Maximum time spent uploading the burst (slow links stop early).
+const MAX_PROBE_DURATION: Duration = Duration::from_secs(4);
+
+/// Throughput (kbps) needed to stream PCM with 2x headroom (48kHz/16-bit stereo ≈ 1536 kbps).
+const PCM_MIN_KBPS: u64 = 3_072;
+
+/// Throughput (kbps) needed to stream FLAC with 2x headroom (≈ 1000 kbps typical).
+const FLAC_MIN_KBPS: u64 = 2_000;
+
+/// Errors that can occur while estimating bandwidth.
+#[derive(Debug, Error)]
+pub enum BandwidthError {
+    /// The speaker IP could not be parsed.
+    #[error("Invalid speaker address: {0}")]
+    InvalidAddress(String),
+
+    /// Could not connect to the speaker.
+    #[error("Cannot connect to speaker: {0}")]
+    Connect(String),
+
+    /// The speaker closed the connection before enough data was sent.
+    #[error("Probe ended after {0} bytes, too few for an estimate")]
+    Incomplete(u64),
+}
+
+impl ErrorCode for BandwidthError {
+    fn code(&self) -> &'static str {
+        match self {
+            Self::InvalidAddress(_) => "invalid_ip",
+            Self::Connect(_) => "ip_unreachable",
+            Self::Incomplete(_) => "bandwidth_probe_incomplete",
+        }
+    }
+
+    fn remediation(&self) -> Option<Remediation> {
+        match self {
+            Self::InvalidAddress(_) => None,
+            Self::Connect(_) => Some(Remediation::new(
+                "check_speaker_power",
+                "Check that the speaker is powered on and connected to Wi-Fi",
+                true,
+            )),
+            Self::Incomplete(_) => Some(Remediation::new(
+                "retry",
+                "Try the bandwidth test again",
+                true,
+            )),
+        }
+    }
+}
+
+/// Result of a bandwidth test against one speaker.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BandwidthEstimate {
+    /// Speaker IP address that was measured.
+    pub speaker_ip: String,
+    /// Bytes accepted by the socket during the test.
+    pub bytes_sent: u64,
+    /// Duration of the upload in milliseconds.
+    pub duration_ms: u64,
+    /// Estimated throughput in kilobits per second.
+    pub throughput_kbps: u64,
+    /// Highest-quality codec the link can sustain with headroom.
+    pub recommended_codec: AudioCodec,
+}
+
+/// Recommends a codec for the measured throughput.
+///
+/// Lossless codecs need roughly 2x their bitrate as headroom to ride out
+/// Wi-Fi dips; otherwise a compressed codec is safer.
+#[must_use]
+pub fn recommend_codec(throughput_kbps: u64) -> AudioCodec {
+    if throughput_kbps >= PCM_MIN_KBPS {
+        AudioCodec::Pcm
+    } else if throughput_kbps >= FLAC_MIN_KBPS {
+        AudioCodec::Flac
+    } else {
+        AudioCodec::Aac
+    }
+}
+
+/// Estimates throughput from this server to the speaker at `ip`.
+///
+/// Takes up to a few seconds. Does not interrupt playback: the burst goes to
+/// the speaker's control port, not to the audio stream.
+///
+/// # Errors
+/// * `InvalidAddress` - `ip` is not an IP address
+/// * `Connect` - the speaker's HTTP port is unreachable
+/// * `Incomplete` - the connection closed before a usable sample was sent
+pub async fn estimate_bandwidth(ip: &str) -> Result<BandwidthEstimate, BandwidthError> {
+    estimate_bandwidth_with_progress(ip, |_, _| {}).await
+}
+
+/// Like [`estimate_bandwidth`], calling `on_progress(sent, total)` with the
+/// bytes uploaded so far as the burst goes out.
+pub async fn estimate_bandwidth_with_progress(
+    ip: &str,
+    on_progress: impl Fn(u64, u64),
+) -> Result<BandwidthEstimate, BandwidthError> {
+    let addr: IpAddr = ip
+        .parse()
+        .map_err(|_| BandwidthError::InvalidAddress(ip.to_string()))?;
+    let (bytes_sent, elapsed) =
+        measure_upload(SocketAddr::new(addr, SONOS_PORT), on_progress).await?;
+
+    let throughput_kbps = throughput_kbps(bytes_sent, elapsed);
+    log::info!(
+        "[Bandwidth] {}: {} bytes in {}ms ≈ {} kbps",
+        ip,
+        bytes_sent,
+        elapsed.as_millis(),
+        throughput_kbps
+    );
+
+    Ok(BandwidthEstimate {
+        speaker_ip: ip.to_string(),
+        bytes_sent,
+        duration_ms: elapsed.as_millis() as u64,
+        throughput_kbps,
+        recommended_codec: recommend_codec(throughput_kbps),
+    })
+}
+
+/// Uploads the burst to `addr` and returns bytes accepted and elapsed time.
+async fn measure_upload(
+    addr: SocketAddr,
+    on_progress: impl Fn(u64, u64),
+) -> Result<(u64, Duration), BandwidthError> {
+    let socket = match addr {
+        SocketAddr::V4(_) => TcpSocket::new_v4(),
+        SocketAddr::V6(_) => TcpSocket::new_v6(),
+    }
+    .map_err(|e| BandwidthError::Connect(e.to_string()))?;
+    // Best effort: some platforms clamp or ignore the requested size
+    let _ = socket.set_send_buffer_size(PROBE_SEND_BUFFER_BYTES);
+
+    let mut stream = tokio::time::timeout(CONNECT_TIMEOUT, socket.connect(addr))
+        .await
+        .map_err(|_| BandwidthError::Connect("connection timed out".to_string()))?
+        .map_err(|e| BandwidthError::Connect(e.to_string()))?;
+    let _ = stream.set_nodelay(true);
+
+    let header = format!(
+        "POST /thaumic-bandwidth-probe HTTP/1.1\r\n\
+         Host: {}\r\n\
+         Content-Type: application/octet-stream\r\n\
+         Content-Length: {}\r\n\
+         Connection: close\r\n\r\n",
+        addr, PROBE_BYTES
+    );
+    stream
+        .write_all(header.as_bytes())
+        .await
+        .map_err(|e| BandwidthError::Connect(e.to_string()))?;
+
+    let chunk = vec![0u8; PROBE_CHUNK_BYTES];
+    let started = Instant::now();
+    let deadline = tokio::time::Instant::from_std(started + MAX_PROBE_DURATION);
+    let mut sent: u64 = 0;
+
+    while sent < PROBE_BYTES as u64 {
+        match tokio::time::timeout_at(deadline, stream.write_all(&chunk)).await {
+            Ok(Ok(())) => {
+                sent += PROBE_CHUNK_BYTES as u64;
+                on_progress(sent, PROBE_BYTES as u64);
+            }
+            // Speaker closed early or the link is slow: use what we have
+            Ok(Err(_)) | Err(_) => break,
+        }
+    }
+    let elapsed = started.elapsed();
+    let _ = stream.shutdown().await;
+
+    if sent < MIN_PROBE_BYTES {
+        return Err(BandwidthError::Incomplete(sent));
+    }
+
+    // Up to one send buffer may still be queued locally; don't count it
+    let delivered = sent.saturating_sub(u64::from(PROBE_SEND_BUFFER_BYTES));
+    Ok((delivered, elapsed))
+}
+
+/// Converts bytes over a duration to kilobits per second.
+fn throughput_kbps(bytes: u64, elapsed: Duration) -> u64 {
+    let secs = elapsed.as_secs_f64().max(0.001);
+    ((bytes as f64 * 8.0) / 1000.0 / secs) as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::AsyncReadExt;
+    use tokio::net::TcpListener;
+
+    #[test]
+    fn recommends_pcm_only_with_headroom() {
+        assert_eq!(recommend_codec(50_000), AudioCodec::Pcm);
+        assert_eq!(recommend_codec(PCM_MIN_KBPS), AudioCodec::Pcm);
+        assert_eq!(recommend_codec(PCM_MIN_KBPS - 1), AudioCodec::Flac);
+        assert_eq!(recommend_codec(FLAC_MIN_KBPS - 1), AudioCodec::Aac);
+        assert_eq!(recommend_codec(0), AudioCodec::Aac);
+    }
+
+    #[test]
+    fn throughput_converts_bytes_to_kbps() {
+        assert_eq!(throughput_kbps(125_000, Duration::from_secs(1)), 1_000);
+        assert_eq!(throughput_kbps(250_000, Duration::from_millis(500)), 4_000);
+    }
+
+    #[tokio::test]
+    async fn measures_upload_to_a_reading_peer() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (mut conn, _) = listener.accept().await.unwrap();
+            let mut buf = vec![0u8; 64 * 1024];
+            while conn.read(&mut buf).await.map(|n| n > 0).unwrap_or(false) {}
+        });
+
+        let progress = std::sync::atomic::AtomicU64::new(0);
+        let (bytes, elapsed) = measure_upload(addr, |sent, _| {
+            progress.store(sent, std::sync::atomic::Ordering::Relaxed)
+        })
+        .await
+        .unwrap();
+        assert_eq!(
+            progress.load(std::sync::atomic::Ordering::Relaxed),
+            PROBE_BYTES as u64
+        );
+        assert_eq!(
+            bytes,
+            PROBE_BYTES as u64 - u64::from(PROBE_SEND_BUFFER_BYTES)
+        );
+        assert!(elapsed < MAX_PROBE_DURATION);
+    }
+
+    #[tokio::test]
+    async fn reports_incomplete_when_peer_closes_immediately() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (conn, _) = listener.accept().await.unwrap();
+            drop(conn);
+        });
+
+        let result = measure_upload(addr, |_, _| {}).await;
+        assert!(matches!(result, Err(BandwidthError::Incomplete(_))));
+    }
+
+    #[tokio::test]
+    async fn rejects_invalid_address() {
+        let result = estimate_bandwidth("not-an-ip").await;
+        assert!(matches!(result, Err(BandwidthError::InvalidAddress(_))));
+    }
+}
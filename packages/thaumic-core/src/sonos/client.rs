@@ -11,15 +11,19 @@
 //! - `grouping` - Group join/leave coordination
 
 use async_trait::async_trait;
-use reqwest::Client;
+use futures::stream::{BoxStream, StreamExt};
 use std::sync::{Arc, OnceLock};
 
 use crate::error::{DiscoveryResult, SoapResult};
-use crate::sonos::discovery::{DiscoveryConfig, DiscoveryCoordinator, Speaker};
+use crate::sonos::discovery::{
+    DeviceDescriptionCache, DiscoveryConfig, DiscoveryCoordinator, DiscoveryDiagnostics, Speaker,
+};
 use crate::sonos::grouping;
 use crate::sonos::playback;
+use crate::sonos::soap::SoapClient;
 use crate::sonos::traits::{SonosDiscovery, SonosPlayback, SonosTopology, SonosVolumeControl};
 use crate::sonos::types::{PositionInfo, ZoneGroup};
+use crate::sonos::uri_variants::UriQuirks;
 use crate::sonos::volume;
 use crate::sonos::zone_groups;
 use crate::stream::{AudioCodec, AudioFormat, StreamMetadata};
@@ -29,18 +33,22 @@ use crate::stream::{AudioCodec, AudioFormat, StreamMetadata};
 /// This struct wraps the free functions in submodules to provide
 /// a testable, injectable interface for Sonos operations.
 pub struct SonosClientImpl {
-    /// HTTP client for Sonos communication.
-    client: Client,
+    /// Pooled SOAP client for speaker control.
+    client: SoapClient,
     /// Discovery coordinator (lazily initialized).
     discovery_coordinator: OnceLock<Arc<DiscoveryCoordinator>>,
     /// Discovery configuration.
     discovery_config: DiscoveryConfig,
+    /// Device descriptions kept across discovery runs.
+    description_cache: Arc<DeviceDescriptionCache>,
+    /// Stream URI formats speakers accepted.
+    uri_quirks: Arc<UriQuirks>,
 }
 
 impl std::fmt::Debug for SonosClientImpl {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("SonosClientImpl")
-            .field("client", &"Client")
+            .field("client", &self.client)
             .field("discovery_config", &self.discovery_config)
             .finish()
     }
@@ -52,34 +60,57 @@ impl Clone for SonosClientImpl {
             client: self.client.clone(),
             discovery_coordinator: OnceLock::new(),
             discovery_config: self.discovery_config.clone(),
+            description_cache: Arc::clone(&self.description_cache),
+            uri_quirks: Arc::clone(&self.uri_quirks),
         }
     }
 }
 
 impl SonosClientImpl {
-    /// Creates a new SonosClientImpl with the given HTTP client.
+    /// Creates a new SonosClientImpl with the given SOAP client.
     ///
     /// # Arguments
-    /// * `client` - The HTTP client to use for all Sonos communication
+    /// * `client` - The SOAP client to send all speaker control requests through
     #[must_use]
-    pub fn new(client: Client) -> Self {
+    pub fn new(client: SoapClient) -> Self {
         Self {
             client,
             discovery_coordinator: OnceLock::new(),
             discovery_config: DiscoveryConfig::default(),
+            description_cache: Arc::new(DeviceDescriptionCache::new()),
+            uri_quirks: Arc::default(),
         }
     }
 
+    /// Uses `cache` for device descriptions, so they can be persisted and
+    /// shared with whoever owns the cache.
+    #[must_use]
+    pub fn with_description_cache(mut self, cache: Arc<DeviceDescriptionCache>) -> Self {
+        self.description_cache = cache;
+        self
+    }
+
+    /// Uses `config` for discovery instead of the defaults.
+    #[must_use]
+    pub fn with_discovery_config(mut self, config: DiscoveryConfig) -> Self {
+        self.discovery_config = config;
+        self
+    }
+
     /// Gets or creates the discovery coordinator.
     fn get_discovery_coordinator(&self) -> &Arc<DiscoveryCoordinator> {
-        self.discovery_coordinator
-            .get_or_init(|| Arc::new(DiscoveryCoordinator::new(self.discovery_config.clone())))
+        self.discovery_coordinator.get_or_init(|| {
+            Arc::new(DiscoveryCoordinator::with_description_cache(
+                self.discovery_config.clone(),
+                Arc::clone(&self.description_cache),
+            ))
+        })
     }
 }
 
 #[async_trait]
 impl SonosPlayback for SonosClientImpl {
-    async fn play_uri(
+    async fn set_transport_uri(
         &self,
         ip: &str,
         uri: &str,
@@ -88,8 +119,9 @@ impl SonosPlayback for SonosClientImpl {
         metadata: Option<&StreamMetadata>,
         artwork_url: &str,
     ) -> SoapResult<()> {
-        playback::play_uri(
+        playback::set_transport_uri(
             &self.client,
+            &self.uri_quirks,
             ip,
             uri,
             codec,
@@ -172,4 +204,16 @@ impl SonosDiscovery for SonosClientImpl {
     async fn discover_speakers(&self) -> DiscoveryResult<Vec<Speaker>> {
         self.get_discovery_coordinator().discover_speakers().await
     }
+
+    fn discover_speakers_stream(&self) -> BoxStream<'_, DiscoveryResult<Speaker>> {
+        self.get_discovery_coordinator()
+            .discover_speakers_stream()
+            .boxed()
+    }
+
+    fn last_discovery_diagnostics(&self) -> Option<DiscoveryDiagnostics> {
+        self.discovery_coordinator
+            .get()
+            .and_then(|coordinator| coordinator.last_diagnostics())
+    }
 }
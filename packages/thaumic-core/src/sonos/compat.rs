@@ -0,0 +1,124 @@
+//! Compatibility mode for Sonos S1 speakers.
+//!
+//! Speakers left on S1 software (older models, or households that never
+//! moved to the S2 app) accept a stream they can't decode and then sit
+//! silent or stop after a few seconds. Their generation is reported in the
+//! device description, so discovery records them here and playback refuses
+//! formats they can't play with a reason the user can act on.
+
+use dashmap::DashSet;
+
+use crate::sonos::discovery::Speaker;
+use crate::stream::{AudioCodec, AudioFormat};
+
+/// Highest sample rate S1 software plays.
+pub const S1_MAX_SAMPLE_RATE: u32 = 48_000;
+
+/// Warning attached to S1 speakers in speaker and health responses.
+pub const S1_WARNING: &str =
+    "Sonos S1 speaker: FLAC is disabled and sample rates are capped at 48 kHz";
+
+/// Returns why an S1 speaker can't play a stream, or `None` if it can.
+#[must_use]
+pub fn s1_incompatibility(codec: AudioCodec, format: &AudioFormat) -> Option<String> {
+    if codec == AudioCodec::Flac {
+        return Some("Sonos S1 speakers can't play FLAC streams; use PCM or AAC".to_string());
+    }
+    if format.sample_rate > S1_MAX_SAMPLE_RATE {
+        return Some(format!(
+            "Sonos S1 speakers play at most {} Hz, the stream is {} Hz",
+            S1_MAX_SAMPLE_RATE, format.sample_rate
+        ));
+    }
+    None
+}
+
+/// Returns the compatibility warning for a speaker's software generation.
+#[must_use]
+pub fn warning_for(sw_gen: Option<u8>) -> Option<&'static str> {
+    (sw_gen == Some(1)).then_some(S1_WARNING)
+}
+
+/// UUIDs of the speakers running S1 software.
+#[derive(Debug, Default)]
+pub struct S1Speakers {
+    uuids: DashSet<String>,
+}
+
+impl S1Speakers {
+    /// Records the generation of discovered speakers. Speakers that didn't
+    /// report one keep what was recorded before.
+    pub fn update(&self, speakers: &[Speaker]) {
+        for speaker in speakers {
+            match speaker.sw_gen {
+                Some(1) if self.uuids.insert(speaker.uuid.clone()) => {
+                    log::info!(
+                        "[Compat] {} ({}) runs Sonos S1, enabling compatibility mode",
+                        speaker.name,
+                        speaker.ip
+                    );
+                }
+                Some(1) | None => {}
+                Some(_) => {
+                    self.uuids.remove(&speaker.uuid);
+                }
+            }
+        }
+    }
+
+    /// Returns true if the speaker runs S1 software.
+    #[must_use]
+    pub fn contains(&self, uuid: &str) -> bool {
+        self.uuids.contains(uuid)
+    }
+
+    /// Returns the UUIDs of all S1 speakers, sorted.
+    #[must_use]
+    pub fn uuids(&self) -> Vec<String> {
+        let mut uuids: Vec<String> = self.uuids.iter().map(|uuid| uuid.clone()).collect();
+        uuids.sort();
+        uuids
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rejects_flac_and_high_sample_rates() {
+        let cd = AudioFormat::new(44_100, 2, 16);
+        let hires = AudioFormat::new(96_000, 2, 24);
+
+        assert!(s1_incompatibility(AudioCodec::Pcm, &cd).is_none());
+        assert!(s1_incompatibility(AudioCodec::Aac, &AudioFormat::default()).is_none());
+        assert!(s1_incompatibility(AudioCodec::Flac, &cd)
+            .unwrap()
+            .contains("FLAC"));
+        assert!(s1_incompatibility(AudioCodec::Pcm, &hires)
+            .unwrap()
+            .contains("96000 Hz"));
+    }
+
+    #[test]
+    fn tracks_generation_changes() {
+        let s1 = S1Speakers::default();
+        let mut speaker = Speaker::new(
+            "192.168.1.10".into(),
+            "RINCON_A".into(),
+            "Kitchen".into(),
+            None,
+        );
+        speaker.sw_gen = Some(1);
+        s1.update(std::slice::from_ref(&speaker));
+        assert!(s1.contains("RINCON_A"));
+
+        speaker.sw_gen = None;
+        s1.update(std::slice::from_ref(&speaker));
+        assert!(s1.contains("RINCON_A"));
+
+        speaker.sw_gen = Some(2);
+        s1.update(std::slice::from_ref(&speaker));
+        assert!(s1.uuids().is_empty());
+    }
+}
@@ -0,0 +1,268 @@
+//! Persistent cache of speaker device descriptions.
+//!
+//! Every full discovery used to fetch `device_description.xml` from every
+//! speaker, although a speaker's name and model rarely change. Descriptions
+//! are now cached per (UUID, description URL) and reused while fresh. Once
+//! stale they are revalidated with a conditional request (`If-None-Match` /
+//! `If-Modified-Since`), so an unchanged description costs a 304 instead of
+//! a download and parse.
+//!
+//! The cache is persisted to `device_descriptions.json` in the app data
+//! directory when one is configured, so a restart doesn't refetch everything.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+
+use super::types::{normalize_uuid, DeviceInfo};
+use crate::storage;
+
+const CACHE_FILE: &str = "device_descriptions.json";
+
+/// Entries not revalidated for this long are dropped (speaker left the network).
+const MAX_ENTRY_AGE: Duration = Duration::from_secs(30 * 24 * 60 * 60);
+
+/// HTTP validators for a conditional description request.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(super) struct Validators {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub etag: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_modified: Option<String>,
+}
+
+impl Validators {
+    fn is_empty(&self) -> bool {
+        self.etag.is_none() && self.last_modified.is_none()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CachedDescription {
+    #[serde(flatten)]
+    info: DeviceInfo,
+    #[serde(flatten)]
+    validators: Validators,
+    /// Unix timestamp (ms) of the last fetch or successful revalidation.
+    validated_at: u64,
+}
+
+/// Result of a cache lookup.
+#[derive(Debug)]
+pub(super) enum Lookup {
+    /// Cached and within the TTL: use without a request.
+    Fresh(DeviceInfo),
+    /// Cached but past the TTL: revalidate with these validators.
+    Stale(DeviceInfo, Validators),
+    /// Not cached.
+    Miss,
+}
+
+/// Device descriptions keyed by speaker UUID and description URL.
+#[derive(Default)]
+pub struct DeviceDescriptionCache {
+    entries: Mutex<HashMap<String, CachedDescription>>,
+    data_dir: Mutex<Option<PathBuf>>,
+    /// Set when entries changed since the last flush.
+    dirty: AtomicBool,
+}
+
+impl DeviceDescriptionCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the directory the cache is persisted to and loads saved entries.
+    ///
+    /// Entries cached before the directory was set take precedence.
+    pub fn set_data_dir(&self, dir: impl AsRef<Path>) {
+        let dir = dir.as_ref().to_path_buf();
+        let loaded = load(&dir);
+        {
+            let mut entries = self.entries.lock();
+            for (key, entry) in loaded {
+                entries.entry(key).or_insert(entry);
+            }
+        }
+        *self.data_dir.lock() = Some(dir);
+    }
+
+    pub(super) fn lookup(&self, uuid: &str, url: &str, ttl: Duration, now_ms: u64) -> Lookup {
+        let entries = self.entries.lock();
+        let Some(entry) = entries.get(&key(uuid, url)) else {
+            return Lookup::Miss;
+        };
+        if now_ms.saturating_sub(entry.validated_at) < ttl.as_millis() as u64 {
+            return Lookup::Fresh(entry.info.clone());
+        }
+        if entry.validators.is_empty() {
+            // Nothing to revalidate with: treat as a miss and refetch
+            return Lookup::Miss;
+        }
+        Lookup::Stale(entry.info.clone(), entry.validators.clone())
+    }
+
+    /// Stores a freshly fetched description.
+    pub(super) fn insert(
+        &self,
+        uuid: &str,
+        url: &str,
+        info: &DeviceInfo,
+        validators: Validators,
+        now_ms: u64,
+    ) {
+        let mut entries = self.entries.lock();
+        entries.insert(
+            key(uuid, url),
+            CachedDescription {
+                info: info.clone(),
+                validators,
+                validated_at: now_ms,
+            },
+        );
+        let oldest = now_ms.saturating_sub(MAX_ENTRY_AGE.as_millis() as u64);
+        entries.retain(|_, entry| entry.validated_at > oldest);
+        self.dirty.store(true, Ordering::Relaxed);
+    }
+
+    /// Marks a cached description as still valid (the speaker answered 304).
+    pub(super) fn revalidated(&self, uuid: &str, url: &str, now_ms: u64) {
+        if let Some(entry) = self.entries.lock().get_mut(&key(uuid, url)) {
+            entry.validated_at = now_ms;
+            self.dirty.store(true, Ordering::Relaxed);
+        }
+    }
+
+    /// Writes the cache to disk if it changed and a data directory is set.
+    pub fn flush(&self) -> std::io::Result<()> {
+        let Some(dir) = self.data_dir.lock().clone() else {
+            return Ok(());
+        };
+        if !self.dirty.swap(false, Ordering::Relaxed) {
+            return Ok(());
+        }
+
+        let contents = serde_json::to_string_pretty(&*self.entries.lock())?;
+        let result = save(&dir, &contents);
+        if result.is_err() {
+            self.dirty.store(true, Ordering::Relaxed);
+        }
+        result
+    }
+}
+
+fn key(uuid: &str, url: &str) -> String {
+    format!("{} {}", normalize_uuid(uuid), url)
+}
+
+fn load(dir: &Path) -> HashMap<String, CachedDescription> {
+    match std::fs::read_to_string(dir.join(CACHE_FILE)) {
+        Ok(contents) => serde_json::from_str(&contents).unwrap_or_else(|e| {
+            log::warn!("[Discovery] Ignoring unreadable {}: {}", CACHE_FILE, e);
+            HashMap::new()
+        }),
+        Err(_) => HashMap::new(),
+    }
+}
+
+/// Writes the cache atomically (temp file + rename), skipped while the data
+/// directory is full or read-only.
+fn save(dir: &Path, contents: &str) -> std::io::Result<()> {
+    storage::tolerate(storage::write_atomic(dir, CACHE_FILE, contents))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const URL: &str = "http://192.168.1.20:1400/xml/device_description.xml";
+    const TTL: Duration = Duration::from_secs(60);
+    const NOW_MS: u64 = 1_700_000_000_000;
+
+    fn info() -> DeviceInfo {
+        DeviceInfo {
+            uuid: "uuid:RINCON_000E58A0B1C201400".to_string(),
+            friendly_name: "Living Room".to_string(),
+            model_name: Some("Sonos One".to_string()),
+            software_version: Some("79.1-56100".to_string()),
+            ..Default::default()
+        }
+    }
+
+    fn etag() -> Validators {
+        Validators {
+            etag: Some("\"abc\"".to_string()),
+            last_modified: None,
+        }
+    }
+
+    #[test]
+    fn fresh_then_stale_after_ttl() {
+        let cache = DeviceDescriptionCache::new();
+        cache.insert(
+            "uuid:RINCON_000E58A0B1C201400",
+            URL,
+            &info(),
+            etag(),
+            NOW_MS,
+        );
+
+        // Looked up by the normalized UUID discovery reports
+        assert!(matches!(
+            cache.lookup("RINCON_000E58A0B1C201400", URL, TTL, NOW_MS + 1_000),
+            Lookup::Fresh(_)
+        ));
+        match cache.lookup("RINCON_000E58A0B1C201400", URL, TTL, NOW_MS + 61_000) {
+            Lookup::Stale(info, validators) => {
+                assert_eq!(info.friendly_name, "Living Room");
+                assert_eq!(validators, etag());
+            }
+            other => panic!("expected stale entry, got {:?}", other),
+        }
+
+        cache.revalidated("RINCON_000E58A0B1C201400", URL, NOW_MS + 61_000);
+        assert!(matches!(
+            cache.lookup("RINCON_000E58A0B1C201400", URL, TTL, NOW_MS + 62_000),
+            Lookup::Fresh(_)
+        ));
+    }
+
+    #[test]
+    fn stale_entry_without_validators_is_refetched() {
+        let cache = DeviceDescriptionCache::new();
+        cache.insert("RINCON_A", URL, &info(), Validators::default(), NOW_MS);
+        assert!(matches!(
+            cache.lookup("RINCON_A", URL, TTL, NOW_MS + 61_000),
+            Lookup::Miss
+        ));
+        assert!(matches!(
+            cache.lookup("RINCON_B", URL, TTL, NOW_MS),
+            Lookup::Miss
+        ));
+    }
+
+    #[test]
+    fn persists_across_instances() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = DeviceDescriptionCache::new();
+        cache.set_data_dir(dir.path());
+        cache.insert("RINCON_A", URL, &info(), etag(), NOW_MS);
+        cache.flush().unwrap();
+
+        let reloaded = DeviceDescriptionCache::new();
+        reloaded.set_data_dir(dir.path());
+        match reloaded.lookup("RINCON_A", URL, TTL, NOW_MS) {
+            Lookup::Fresh(info) => {
+                assert_eq!(info.model_name.as_deref(), Some("Sonos One"));
+                assert_eq!(info.software_version.as_deref(), Some("79.1-56100"));
+            }
+            other => panic!("expected fresh entry, got {:?}", other),
+        }
+    }
+}
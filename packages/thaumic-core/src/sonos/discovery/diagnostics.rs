@@ -0,0 +1,195 @@
+//! Diagnostics of the last discovery run.
+//!
+//! Failures of a single method or interface don't fail discovery as long as
+//! something else finds speakers, so they used to only show up in logs. The
+//! coordinator records each run here: what every method got, per interface
+//! for SSDP, how long it took, and how many results were merged or dropped.
+
+use serde::Serialize;
+use std::net::Ipv4Addr;
+
+use super::types::DiscoveryMethod;
+
+/// Maximum distinct errors kept per interface.
+const MAX_INTERFACE_ERRORS: usize = 8;
+
+/// How a discovery method fared in a run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum MethodStatus {
+    /// Ran without error (possibly finding nothing).
+    Succeeded,
+    /// Ran and failed; see the method's `error`.
+    Failed,
+    /// Disabled, or (subnet scan) not needed because other methods found
+    /// speakers.
+    Skipped,
+}
+
+/// What one interface sent and received during an SSDP search.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InterfaceReport {
+    /// Interface name (e.g. "en0").
+    pub name: String,
+    pub ip: Ipv4Addr,
+    /// Datagrams received, Sonos or not.
+    pub responses: usize,
+    /// Responses that weren't from a Sonos speaker.
+    pub dropped: usize,
+    /// Socket, send and receive errors, each listed once.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<String>,
+}
+
+impl InterfaceReport {
+    pub(crate) fn new(name: &str, ip: Ipv4Addr) -> Self {
+        Self {
+            name: name.to_string(),
+            ip,
+            responses: 0,
+            dropped: 0,
+            errors: Vec::new(),
+        }
+    }
+
+    /// Records an error unless it was already recorded.
+    pub(crate) fn record_error(&mut self, error: String) {
+        if self.errors.len() < MAX_INTERFACE_ERRORS && !self.errors.contains(&error) {
+            self.errors.push(error);
+        }
+    }
+}
+
+/// Result of one discovery method in a run.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MethodReport {
+    pub method: DiscoveryMethod,
+    pub status: MethodStatus,
+    /// Raw answers: SSDP datagrams, resolved mDNS services or answering
+    /// subnet scan addresses.
+    pub responses: usize,
+    /// Answers that weren't usable Sonos speakers.
+    pub dropped: usize,
+    /// Speakers the method returned.
+    pub speakers: usize,
+    pub duration_ms: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// Per-interface results (SSDP only).
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub interfaces: Vec<InterfaceReport>,
+}
+
+impl MethodReport {
+    pub(crate) fn new(method: DiscoveryMethod) -> Self {
+        Self {
+            method,
+            status: MethodStatus::Skipped,
+            responses: 0,
+            dropped: 0,
+            speakers: 0,
+            duration_ms: 0,
+            error: None,
+            interfaces: Vec::new(),
+        }
+    }
+}
+
+/// Diagnostics of one discovery run.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiscoveryDiagnostics {
+    /// When the run started (Unix milliseconds).
+    pub started_at: u64,
+    /// Time until every method had answered.
+    pub duration_ms: u64,
+    pub methods: Vec<MethodReport>,
+    /// Speakers found by all methods together, duplicates included.
+    pub found: usize,
+    /// Distinct speakers after merging by UUID.
+    pub unique: usize,
+    /// Duplicates merged into another result (`found - unique`).
+    pub merged: usize,
+    /// Answers dropped across methods (not Sonos, no usable UUID).
+    pub dropped: usize,
+    /// Speakers whose device description resolved, once fetching is done.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub described: Option<usize>,
+    /// Speakers kept without a description (fallback name, no model).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub undescribed: Option<usize>,
+    /// Time spent fetching device descriptions, once done.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description_ms: Option<u64>,
+    /// Set when the run failed as a whole.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl DiscoveryDiagnostics {
+    /// Summarizes the method reports of a run.
+    pub(crate) fn new(
+        started_at: u64,
+        duration_ms: u64,
+        methods: Vec<MethodReport>,
+        unique: usize,
+    ) -> Self {
+        let found = methods.iter().map(|m| m.speakers).sum::<usize>();
+        let dropped = methods.iter().map(|m| m.dropped).sum();
+        Self {
+            started_at,
+            duration_ms,
+            methods,
+            found,
+            unique,
+            merged: found.saturating_sub(unique),
+            dropped,
+            described: None,
+            undescribed: None,
+            description_ms: None,
+            error: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn summarizes_methods() {
+        let mut multicast = MethodReport::new(DiscoveryMethod::SsdpMulticast);
+        multicast.status = MethodStatus::Succeeded;
+        multicast.responses = 7;
+        multicast.dropped = 2;
+        multicast.speakers = 3;
+        let mut mdns = MethodReport::new(DiscoveryMethod::Mdns);
+        mdns.status = MethodStatus::Succeeded;
+        mdns.responses = 2;
+        mdns.speakers = 2;
+        let mut broadcast = MethodReport::new(DiscoveryMethod::SsdpBroadcast);
+        broadcast.status = MethodStatus::Failed;
+        broadcast.error = Some("no usable network interfaces found".to_string());
+
+        let diagnostics = DiscoveryDiagnostics::new(0, 5000, vec![multicast, broadcast, mdns], 3);
+        assert_eq!(diagnostics.found, 5);
+        assert_eq!(diagnostics.merged, 2);
+        assert_eq!(diagnostics.dropped, 2);
+
+        let json = serde_json::to_value(&diagnostics).unwrap();
+        assert_eq!(json["methods"][0]["method"], "ssdpMulticast");
+        assert_eq!(json["methods"][1]["status"], "failed");
+        assert!(json.get("described").is_none());
+    }
+
+    #[test]
+    fn interface_errors_are_listed_once() {
+        let mut report = InterfaceReport::new("en0", Ipv4Addr::new(192, 168, 1, 2));
+        for _ in 0..3 {
+            report.record_error("send failed: network unreachable".to_string());
+        }
+        assert_eq!(report.errors.len(), 1);
+    }
+}
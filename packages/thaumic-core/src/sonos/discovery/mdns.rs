@@ -6,17 +6,21 @@
 //!
 //! # Key Design Points
 //!
+//! - One browse runs for the lifetime of the [`MdnsBrowser`]; discovery runs
+//!   read its cache of resolved services instead of starting a browse each,
+//!   so speakers appearing or leaving are picked up as they announce it and
+//!   the daemon only re-queries on its own backoff schedule
 //! - Uses resolved record data (IP from SRV/A answers) as primary, not string parsing
 //! - Extracts UUID from service instance name as best-effort
-//! - Calls `stop_browse()` after timeout to avoid accumulating daemon work
 //! - Isolated in this module for forward compatibility (mdns-sd may deprecate `ServiceResolved`)
 
 use mdns_sd::{ResolvedService, ScopedIp, ServiceDaemon, ServiceEvent};
-use std::collections::HashMap;
+use parking_lot::Mutex;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
-use std::time::Duration;
-use tokio::time::timeout;
+use std::time::{Duration, Instant};
 
+use super::diagnostics::MethodReport;
 use super::types::{DiscoveredSpeaker, DiscoveryError, DiscoveryMethod};
 
 /// Sonos mDNS service type (note: trailing dot is required by mdns-sd).
@@ -25,7 +29,8 @@ const SONOS_SERVICE_TYPE: &str = "_sonos._tcp.local.";
 /// Configuration for mDNS discovery.
 #[derive(Debug, Clone)]
 pub struct MdnsConfig {
-    /// How long to browse for services.
+    /// How long after the browse starts discovery runs wait for services to
+    /// resolve. Only the first run usually waits.
     pub browse_timeout: Duration,
 }
 
@@ -37,80 +42,151 @@ impl Default for MdnsConfig {
     }
 }
 
-/// Discovers Sonos speakers using mDNS/Bonjour.
-///
-/// Browses for `_sonos._tcp.local.` services and extracts IP addresses
-/// and UUIDs from the resolved service records.
-///
-/// # Arguments
-///
-/// * `daemon` - Shared mDNS service daemon (reused across discovery calls)
-/// * `config` - mDNS discovery configuration
-///
-/// # Returns
-///
-/// A list of discovered speakers with their IPs and UUIDs.
-pub async fn discover_mdns(
-    daemon: &Arc<ServiceDaemon>,
-    config: &MdnsConfig,
-) -> Result<Vec<DiscoveredSpeaker>, DiscoveryError> {
-    log::debug!(
-        "[mDNS] Starting discovery, browse timeout: {}ms",
-        config.browse_timeout.as_millis()
-    );
-
-    // Start browsing for Sonos services
-    let receiver = daemon
-        .browse(SONOS_SERVICE_TYPE)
-        .map_err(|e| DiscoveryError::MdnsDaemon(e.to_string()))?;
-
-    let mut discovered: HashMap<String, DiscoveredSpeaker> = HashMap::new();
-
-    // Collect resolved services within timeout
-    let start = std::time::Instant::now();
-    while start.elapsed() < config.browse_timeout {
-        let remaining = config.browse_timeout.saturating_sub(start.elapsed());
-
-        match timeout(remaining, async { receiver.recv_async().await }).await {
-            Ok(Ok(event)) => {
-                if let ServiceEvent::ServiceResolved(info) = event {
-                    log::trace!("[mDNS] Service resolved: {:?}", info.fullname);
-
-                    // Extract speaker info from resolved service
-                    if let Some(speaker) = parse_mdns_service(&info) {
-                        let key = speaker.uuid.clone();
-                        log::debug!(
-                            "[mDNS] Discovered speaker: ip={}, uuid={}",
-                            speaker.ip,
-                            speaker.uuid
-                        );
-                        discovered.insert(key, speaker);
-                    }
-                }
-            }
-            Ok(Err(e)) => {
-                log::debug!("[mDNS] Receiver channel closed: {:?}", e);
-                break;
+/// Resolved Sonos services, kept up to date by the browse.
+#[derive(Debug, Default)]
+struct MdnsCache {
+    /// Usable services by full name.
+    speakers: HashMap<String, DiscoveredSpeaker>,
+    /// Full names of services resolved without an IPv4 address or UUID.
+    unusable: HashSet<String>,
+}
+
+impl MdnsCache {
+    /// Records a resolved service. Returns true for a speaker not cached
+    /// before.
+    fn resolved(&mut self, fullname: &str, speaker: Option<DiscoveredSpeaker>) -> bool {
+        match speaker {
+            Some(speaker) => {
+                self.unusable.remove(fullname);
+                let known = self
+                    .speakers
+                    .values()
+                    .any(|cached| cached.uuid == speaker.uuid);
+                self.speakers.insert(fullname.to_string(), speaker);
+                !known
             }
-            Err(_) => {
-                // Timeout - normal termination
-                break;
+            None => {
+                self.speakers.remove(fullname);
+                self.unusable.insert(fullname.to_string());
+                false
             }
         }
     }
 
-    // Stop browsing to avoid accumulating daemon work
-    if let Err(e) = daemon.stop_browse(SONOS_SERVICE_TYPE) {
-        log::warn!("[mDNS] Failed to stop browse: {:?}", e);
+    /// Forgets a service that said goodbye or expired.
+    fn removed(&mut self, fullname: &str) -> Option<DiscoveredSpeaker> {
+        self.unusable.remove(fullname);
+        self.speakers.remove(fullname)
     }
 
-    let speakers: Vec<_> = discovered.into_values().collect();
-    log::debug!(
-        "[mDNS] Discovery complete: {} speaker(s) found",
-        speakers.len()
-    );
+    /// Returns the cached speakers, one per UUID.
+    fn speakers(&self) -> Vec<DiscoveredSpeaker> {
+        let by_uuid: HashMap<&str, &DiscoveredSpeaker> = self
+            .speakers
+            .values()
+            .map(|speaker| (speaker.uuid.as_str(), speaker))
+            .collect();
+        by_uuid.into_values().cloned().collect()
+    }
+}
 
-    Ok(speakers)
+/// Long-lived browse for Sonos services.
+///
+/// The browse runs on its own thread until the browser is dropped.
+pub struct MdnsBrowser {
+    daemon: ServiceDaemon,
+    cache: Arc<Mutex<MdnsCache>>,
+    started: Instant,
+}
+
+impl MdnsBrowser {
+    /// Starts browsing for Sonos services on `daemon`.
+    pub fn start(daemon: ServiceDaemon) -> Result<Self, DiscoveryError> {
+        let receiver = daemon
+            .browse(SONOS_SERVICE_TYPE)
+            .map_err(|e| DiscoveryError::MdnsDaemon(e.to_string()))?;
+        let cache = Arc::new(Mutex::new(MdnsCache::default()));
+
+        let events_cache = Arc::clone(&cache);
+        std::thread::Builder::new()
+            .name("mdns-browse".to_string())
+            .spawn(move || {
+                while let Ok(event) = receiver.recv() {
+                    match event {
+                        ServiceEvent::ServiceResolved(info) => {
+                            log::trace!("[mDNS] Service resolved: {:?}", info.fullname);
+                            let speaker = parse_mdns_service(&info);
+                            if let Some(speaker) = &speaker {
+                                log::debug!(
+                                    "[mDNS] Resolved speaker: ip={}, uuid={}",
+                                    speaker.ip,
+                                    speaker.uuid
+                                );
+                            }
+                            if events_cache.lock().resolved(&info.fullname, speaker) {
+                                log::info!("[mDNS] New speaker announced: {}", info.fullname);
+                            }
+                        }
+                        ServiceEvent::ServiceRemoved(_, fullname) => {
+                            if let Some(speaker) = events_cache.lock().removed(&fullname) {
+                                log::info!("[mDNS] Speaker {} ({}) left", speaker.uuid, speaker.ip);
+                            }
+                        }
+                        ServiceEvent::SearchStopped(_) => break,
+                        _ => {}
+                    }
+                }
+                log::debug!("[mDNS] Browse stopped");
+            })
+            .map_err(|e| DiscoveryError::MdnsDaemon(e.to_string()))?;
+
+        log::debug!("[mDNS] Browsing for {}", SONOS_SERVICE_TYPE);
+        Ok(Self {
+            daemon,
+            cache,
+            started: Instant::now(),
+        })
+    }
+
+    /// Returns the speakers currently announced.
+    ///
+    /// Waits until `config.browse_timeout` has passed since the browse
+    /// started, so the first run still sees the initial answers.
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - mDNS discovery configuration
+    /// * `report` - Records resolved services and those that weren't usable
+    pub async fn speakers(
+        &self,
+        config: &MdnsConfig,
+        report: &mut MethodReport,
+    ) -> Vec<DiscoveredSpeaker> {
+        let warm_up = config.browse_timeout.saturating_sub(self.started.elapsed());
+        if !warm_up.is_zero() {
+            log::debug!(
+                "[mDNS] Waiting {}ms for the browse to resolve services",
+                warm_up.as_millis()
+            );
+            tokio::time::sleep(warm_up).await;
+        }
+
+        let cache = self.cache.lock();
+        let speakers = cache.speakers();
+        report.responses += cache.speakers.len() + cache.unusable.len();
+        report.dropped += cache.unusable.len();
+        log::debug!("[mDNS] {} speaker(s) announced", speakers.len());
+        speakers
+    }
+}
+
+impl Drop for MdnsBrowser {
+    fn drop(&mut self) {
+        // Shutting the daemon down also ends the browse thread
+        if let Err(e) = self.daemon.shutdown() {
+            log::warn!("[mDNS] Failed to shut down daemon: {:?}", e);
+        }
+    }
 }
 
 /// Parses a resolved mDNS service into a DiscoveredSpeaker.
@@ -177,7 +253,6 @@ fn extract_uuid_from_name(name: &str) -> Option<String> {
 
 /// Creates a new mDNS service daemon.
 ///
-/// This should be called once and the daemon reused across discovery calls.
 /// The daemon spawns a background thread for mDNS operations.
 pub fn create_daemon() -> Result<ServiceDaemon, DiscoveryError> {
     ServiceDaemon::new().map_err(|e| DiscoveryError::MdnsDaemon(e.to_string()))
@@ -187,6 +262,27 @@ pub fn create_daemon() -> Result<ServiceDaemon, DiscoveryError> {
 mod tests {
     use super::*;
 
+    #[test]
+    fn cache_follows_announcements() {
+        let mut cache = MdnsCache::default();
+        let kitchen = || {
+            DiscoveredSpeaker::new(
+                "192.168.1.10".into(),
+                "RINCON_KITCHEN".into(),
+                DiscoveryMethod::Mdns,
+            )
+        };
+
+        assert!(cache.resolved("Kitchen._sonos._tcp.local.", Some(kitchen())));
+        assert!(!cache.resolved("Kitchen._sonos._tcp.local.", Some(kitchen())));
+        assert!(!cache.resolved("Printer._sonos._tcp.local.", None));
+        assert_eq!(cache.speakers().len(), 1);
+        assert_eq!(cache.unusable.len(), 1);
+
+        assert!(cache.removed("Kitchen._sonos._tcp.local.").is_some());
+        assert!(cache.speakers().is_empty());
+    }
+
     #[test]
     fn test_extract_uuid_standard_format() {
         assert_eq!(
@@ -1,7 +1,8 @@
 //! Multi-method Sonos speaker discovery.
 //!
 //! Combines SSDP (multicast + broadcast) and mDNS discovery methods
-//! for improved reliability across different network configurations.
+//! for improved reliability across different network configurations, with
+//! a subnet scan as the last resort when multicast is blocked altogether.
 //!
 //! # Architecture
 //!
@@ -9,35 +10,64 @@
 //! DiscoveryCoordinator
 //! ├── SSDP Multicast (239.255.255.250:1900)
 //! ├── SSDP Broadcast (directed per-interface + 255.255.255.255)
-//! └── mDNS (_sonos._tcp.local.)
+//! ├── mDNS (_sonos._tcp.local.)
+//! ├── Remote subnets (unicast M-SEARCH / :1400 on configured ranges)
+//! └── Subnet scan (:1400 on each local address, only if the others find nothing)
 //! ```
 //!
 //! # Discovery Pipeline
 //!
 //! 1. Run all enabled methods in parallel
-//! 2. Collect raw discoveries (with method tracking)
+//! 2. Collect raw discoveries (with method tracking), falling back to the
+//!    subnet scan if there are none
 //! 3. Normalize UUIDs and merge duplicates
-//! 4. Fetch device descriptions (unified, with caching)
+//! 4. Fetch device descriptions (unified, with caching), yielding each
+//!    speaker as soon as its description resolves. Descriptions are kept in
+//!    a persistent [`DeviceDescriptionCache`] and only refetched once stale.
+//!
+//! Each run's per-method results are kept as [`DiscoveryDiagnostics`].
+//!
+//! Between runs, [`ssdp::listen_notify`] passes on the NOTIFY announcements
+//! speakers send when they join or leave the network.
 
+mod description_cache;
+pub mod diagnostics;
 pub mod mdns;
+pub mod remote_subnets;
 pub mod ssdp;
+pub mod subnet_scan;
 pub mod types;
 
+pub use description_cache::DeviceDescriptionCache;
+pub use diagnostics::{DiscoveryDiagnostics, InterfaceReport, MethodReport, MethodStatus};
+pub use remote_subnets::RemoteSubnets;
+
 pub use types::{
     normalize_uuid, DeviceInfo, DiscoveredSpeaker, DiscoveryError, DiscoveryErrorKind,
     DiscoveryMethod, DiscoveryResult, Speaker,
 };
 
-use mdns_sd::ServiceDaemon;
+use async_stream::stream;
+use futures::stream::{self, Stream, StreamExt};
+use parking_lot::Mutex;
 use quick_xml::events::Event;
 use quick_xml::reader::Reader;
-use reqwest::Client;
+use reqwest::header::{ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED};
+use reqwest::{Client, StatusCode};
+use std::cmp::Reverse;
 use std::collections::HashMap;
 use std::sync::{Arc, OnceLock};
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
-use self::mdns::MdnsConfig;
+use self::description_cache::{Lookup, Validators};
+use self::mdns::{MdnsBrowser, MdnsConfig};
+use self::remote_subnets::RemoteSubnetsConfig;
 use self::ssdp::SsdpConfig;
+use self::subnet_scan::SubnetScanConfig;
+use crate::utils::{is_hostname, now_millis, validate_speaker_ip};
+
+/// Device descriptions resolved during one discovery run, keyed by URL.
+type DescriptionCache = Arc<tokio::sync::Mutex<HashMap<String, Option<DeviceInfo>>>>;
 
 /// Configuration for the discovery coordinator.
 #[derive(Debug, Clone)]
@@ -48,14 +78,25 @@ pub struct DiscoveryConfig {
     pub ssdp_broadcast_enabled: bool,
     /// Enable mDNS discovery.
     pub mdns_enabled: bool,
+    /// Scan the local subnets when no other method finds a speaker.
+    pub subnet_scan_enabled: bool,
     /// SSDP configuration.
     pub ssdp: SsdpConfig,
     /// mDNS configuration.
     pub mdns: MdnsConfig,
+    /// Subnet scan configuration.
+    pub subnet_scan: SubnetScanConfig,
+    /// Ranges on other VLANs or routed subnets, searched by unicast on
+    /// every run. Shared so they can be changed at runtime.
+    pub remote_subnets: Arc<RemoteSubnets>,
+    /// Remote subnet search configuration.
+    pub remote_subnets_search: RemoteSubnetsConfig,
     /// Timeout for fetching device descriptions.
     pub description_fetch_timeout: Duration,
     /// Maximum concurrent device description fetches.
     pub max_concurrent_fetches: usize,
+    /// How long a cached device description is used without revalidating.
+    pub description_cache_ttl: Duration,
 }
 
 impl Default for DiscoveryConfig {
@@ -64,28 +105,62 @@ impl Default for DiscoveryConfig {
             ssdp_multicast_enabled: true,
             ssdp_broadcast_enabled: true,
             mdns_enabled: true,
+            subnet_scan_enabled: true,
             ssdp: SsdpConfig::default(),
             mdns: MdnsConfig::default(),
+            subnet_scan: SubnetScanConfig::default(),
+            remote_subnets: Arc::default(),
+            remote_subnets_search: RemoteSubnetsConfig::default(),
             description_fetch_timeout: Duration::from_secs(2),
             max_concurrent_fetches: 8,
+            description_cache_ttl: Duration::from_secs(60 * 60),
+        }
+    }
+}
+
+impl DiscoveryConfig {
+    /// Disables every multicast and broadcast method and the subnet scan,
+    /// for networks where speakers are only reachable by their configured
+    /// IPs. Discovery then only searches the remote subnets.
+    #[must_use]
+    pub fn unicast_only() -> Self {
+        Self {
+            ssdp_multicast_enabled: false,
+            ssdp_broadcast_enabled: false,
+            mdns_enabled: false,
+            subnet_scan_enabled: false,
+            ..Self::default()
         }
     }
 }
 
 /// Coordinates multiple discovery methods for reliable speaker detection.
 ///
-/// Runs SSDP (multicast + broadcast) and mDNS in parallel, merges results,
+/// Runs SSDP (multicast + broadcast), mDNS and the remote subnet search in
+/// parallel, scans the local subnets if they find nothing, merges results,
 /// and fetches device descriptions with caching.
 pub struct DiscoveryCoordinator {
     config: DiscoveryConfig,
     http_client: Client,
-    /// Lazily initialized mDNS daemon (reused across discovery calls)
-    mdns_daemon: OnceLock<Arc<ServiceDaemon>>,
+    /// Lazily started mDNS browse, read by every discovery call.
+    mdns_browser: OnceLock<MdnsBrowser>,
+    /// Device descriptions kept across discovery runs.
+    description_cache: Arc<DeviceDescriptionCache>,
+    /// Diagnostics of the most recent run.
+    last_diagnostics: Mutex<Option<DiscoveryDiagnostics>>,
 }
 
 impl DiscoveryCoordinator {
     /// Creates a new coordinator with the given configuration.
     pub fn new(config: DiscoveryConfig) -> Self {
+        Self::with_description_cache(config, Arc::new(DeviceDescriptionCache::new()))
+    }
+
+    /// Creates a coordinator that keeps device descriptions in `description_cache`.
+    pub fn with_description_cache(
+        config: DiscoveryConfig,
+        description_cache: Arc<DeviceDescriptionCache>,
+    ) -> Self {
         let http_client = Client::builder()
             .timeout(config.description_fetch_timeout)
             .build()
@@ -100,26 +175,32 @@ impl DiscoveryCoordinator {
         Self {
             config,
             http_client,
-            mdns_daemon: OnceLock::new(),
+            mdns_browser: OnceLock::new(),
+            description_cache,
+            last_diagnostics: Mutex::new(None),
         }
     }
 
-    /// Gets or creates the mDNS daemon.
-    fn get_mdns_daemon(&self) -> Result<&Arc<ServiceDaemon>, DiscoveryError> {
+    /// Returns the diagnostics of the most recent run, if any.
+    pub fn last_diagnostics(&self) -> Option<DiscoveryDiagnostics> {
+        self.last_diagnostics.lock().clone()
+    }
+
+    /// Gets or starts the mDNS browse.
+    fn get_mdns_browser(&self) -> Result<&MdnsBrowser, DiscoveryError> {
         // OnceLock doesn't have get_or_try_init in stable, so we handle errors differently
-        if let Some(daemon) = self.mdns_daemon.get() {
-            return Ok(daemon);
+        if let Some(browser) = self.mdns_browser.get() {
+            return Ok(browser);
         }
 
-        // Try to create the daemon
-        let daemon = mdns::create_daemon()?;
+        let browser = MdnsBrowser::start(mdns::create_daemon()?)?;
 
-        // Try to set it (may fail if another thread set it first, which is fine)
-        let _ = self.mdns_daemon.set(Arc::new(daemon));
+        // Another thread may have started one first; the spare shuts down on drop
+        let _ = self.mdns_browser.set(browser);
 
         // Return whatever is in the cell now
-        self.mdns_daemon.get().ok_or_else(|| {
-            DiscoveryError::MdnsDaemon("failed to initialize mDNS daemon".to_string())
+        self.mdns_browser.get().ok_or_else(|| {
+            DiscoveryError::MdnsDaemon("failed to initialize mDNS browse".to_string())
         })
     }
 
@@ -129,23 +210,113 @@ impl DiscoveryCoordinator {
     ///
     /// A list of discovered speakers with resolved metadata.
     pub async fn discover_speakers(&self) -> Result<Vec<Speaker>, DiscoveryError> {
-        let mut method_errors: Vec<(DiscoveryMethod, DiscoveryErrorKind)> = Vec::new();
+        self.discover_speakers_stream()
+            .collect::<Vec<_>>()
+            .await
+            .into_iter()
+            .collect()
+    }
+
+    /// Discovers speakers, yielding each one as soon as its device
+    /// description resolves.
+    ///
+    /// The network scan itself still runs to completion first (duplicates
+    /// can only be merged once every method has answered), but callers no
+    /// longer wait for the slowest description fetch. If every discovery
+    /// method fails, the stream yields a single error and ends.
+    pub fn discover_speakers_stream(&self) -> impl Stream<Item = DiscoveryResult<Speaker>> + '_ {
+        stream! {
+            let (candidates, diagnostics) = self.discover_candidates().await;
+            let started_at = diagnostics.started_at;
+            *self.last_diagnostics.lock() = Some(diagnostics);
+            let candidates = match candidates {
+                Ok(candidates) => candidates,
+                Err(e) => {
+                    yield Err(e);
+                    return;
+                }
+            };
+
+            log::info!(
+                "[Discovery] Fetching device descriptions for {} speaker(s)...",
+                candidates.len()
+            );
+
+            // Cache for device descriptions by URL
+            let cache: DescriptionCache = Arc::new(tokio::sync::Mutex::new(HashMap::new()));
+            let mut resolved = stream::iter(candidates)
+                .map(|speaker| self.resolve_speaker(speaker, &cache))
+                .buffer_unordered(self.config.max_concurrent_fetches);
+
+            let fetch_started = Instant::now();
+            let mut count = 0;
+            let mut described = 0;
+            while let Some((speaker, has_description)) = resolved.next().await {
+                count += 1;
+                if has_description {
+                    described += 1;
+                }
+                yield Ok(speaker);
+            }
+
+            log::info!(
+                "[Discovery] Device description fetch complete, returned {} speaker(s)",
+                count
+            );
+
+            // Unless a newer run has replaced them meanwhile
+            if let Some(diagnostics) = self
+                .last_diagnostics
+                .lock()
+                .as_mut()
+                .filter(|d| d.started_at == started_at)
+            {
+                diagnostics.described = Some(described);
+                diagnostics.undescribed = Some(count - described);
+                diagnostics.description_ms = Some(fetch_started.elapsed().as_millis() as u64);
+            }
+
+            if let Err(e) = self.description_cache.flush() {
+                log::warn!("[Discovery] Failed to save device description cache: {}", e);
+            }
+        }
+    }
+
+    /// Runs all enabled discovery methods and returns merged candidates in
+    /// description fetch order, with the diagnostics of the run.
+    async fn discover_candidates(
+        &self,
+    ) -> (
+        Result<Vec<DiscoveredSpeaker>, DiscoveryError>,
+        DiscoveryDiagnostics,
+    ) {
+        let started_at = now_millis();
+        let started = Instant::now();
+        let mut multicast_report = MethodReport::new(DiscoveryMethod::SsdpMulticast);
+        let mut broadcast_report = MethodReport::new(DiscoveryMethod::SsdpBroadcast);
+        let mut mdns_report = MethodReport::new(DiscoveryMethod::Mdns);
+        let mut subnet_report = MethodReport::new(DiscoveryMethod::SubnetScan);
+        let mut remote_report = MethodReport::new(DiscoveryMethod::RemoteSubnets);
+        let remote_ranges = self.config.remote_subnets.entries();
 
         log::info!(
-            "[Discovery] Starting parallel discovery (multicast={}, broadcast={}, mdns={})",
+            "[Discovery] Starting parallel discovery (multicast={}, broadcast={}, mdns={}, remote_subnets={})",
             self.config.ssdp_multicast_enabled,
             self.config.ssdp_broadcast_enabled,
-            self.config.mdns_enabled
+            self.config.mdns_enabled,
+            remote_ranges.len()
         );
 
         // Launch enabled discovery methods in parallel
-        let (multicast_result, broadcast_result, mdns_result) = tokio::join!(
+        let (multicast_result, broadcast_result, mdns_result, remote_result) = tokio::join!(
             async {
                 if self.config.ssdp_multicast_enabled {
                     log::debug!("[Discovery] SSDP multicast starting...");
-                    let result = ssdp::discover_multicast(&self.config.ssdp).await;
+                    let start = Instant::now();
+                    let result =
+                        ssdp::discover_multicast(&self.config.ssdp, &mut multicast_report).await;
                     log::debug!("[Discovery] SSDP multicast finished");
-                    Some(result)
+                    Some((result, start.elapsed()))
                 } else {
                     None
                 }
@@ -153,9 +324,11 @@ impl DiscoveryCoordinator {
             async {
                 if self.config.ssdp_broadcast_enabled {
                     log::debug!("[Discovery] SSDP broadcast starting...");
-                    let result = ssdp::discover_broadcast(&self.config.ssdp).await;
+                    let start = Instant::now();
+                    let result =
+                        ssdp::discover_broadcast(&self.config.ssdp, &mut broadcast_report).await;
                     log::debug!("[Discovery] SSDP broadcast finished");
-                    Some(result)
+                    Some((result, start.elapsed()))
                 } else {
                     None
                 }
@@ -163,17 +336,34 @@ impl DiscoveryCoordinator {
             async {
                 if self.config.mdns_enabled {
                     log::debug!("[Discovery] mDNS starting...");
-                    match self.get_mdns_daemon() {
-                        Ok(daemon) => {
-                            let result = mdns::discover_mdns(daemon, &self.config.mdns).await;
+                    let start = Instant::now();
+                    match self.get_mdns_browser() {
+                        Ok(browser) => {
+                            let speakers =
+                                browser.speakers(&self.config.mdns, &mut mdns_report).await;
                             log::debug!("[Discovery] mDNS finished");
-                            Some(result)
+                            Some((Ok(speakers), start.elapsed()))
                         }
-                        Err(e) => Some(Err(e)),
+                        Err(e) => Some((Err(e), start.elapsed())),
                     }
                 } else {
                     None
                 }
+            },
+            async {
+                if remote_ranges.is_empty() {
+                    return None;
+                }
+                log::debug!("[Discovery] Remote subnet search starting...");
+                let start = Instant::now();
+                let result = remote_subnets::discover_remote(
+                    &self.config.remote_subnets_search,
+                    &remote_ranges,
+                    &mut remote_report,
+                )
+                .await;
+                log::debug!("[Discovery] Remote subnet search finished");
+                Some((result, start.elapsed()))
             }
         );
 
@@ -181,83 +371,74 @@ impl DiscoveryCoordinator {
 
         // Collect results and track errors
         let mut all_discovered: Vec<DiscoveredSpeaker> = Vec::new();
-
-        if let Some(result) = multicast_result {
-            match result {
-                Ok(speakers) => {
-                    log::info!(
-                        "[Discovery] SSDP multicast found {} speaker(s)",
-                        speakers.len()
-                    );
-                    all_discovered.extend(speakers);
-                }
-                Err(e) => {
-                    log::warn!("[Discovery] SSDP multicast failed: {}", e);
-                    method_errors.push((
-                        DiscoveryMethod::SsdpMulticast,
-                        error_to_kind(&e, self.config.ssdp.discovery_timeout.as_millis() as u64),
-                    ));
-                }
-            }
+        let mut method_errors: Vec<(DiscoveryMethod, DiscoveryErrorKind)> = Vec::new();
+        let ssdp_timeout_ms = self.config.ssdp.discovery_timeout.as_millis() as u64;
+        for (report, result, timeout_ms) in [
+            (&mut multicast_report, multicast_result, ssdp_timeout_ms),
+            (&mut broadcast_report, broadcast_result, ssdp_timeout_ms),
+            (
+                &mut mdns_report,
+                mdns_result,
+                self.config.mdns.browse_timeout.as_millis() as u64,
+            ),
+            (
+                &mut remote_report,
+                remote_result,
+                self.config.remote_subnets_search.search_timeout.as_millis() as u64,
+            ),
+        ] {
+            collect_method_result(
+                report,
+                result,
+                timeout_ms,
+                &mut all_discovered,
+                &mut method_errors,
+            );
         }
 
-        if let Some(result) = broadcast_result {
-            match result {
-                Ok(speakers) => {
-                    log::info!(
-                        "[Discovery] SSDP broadcast found {} speaker(s)",
-                        speakers.len()
-                    );
-                    all_discovered.extend(speakers);
-                }
-                Err(e) => {
-                    log::warn!("[Discovery] SSDP broadcast failed: {}", e);
-                    method_errors.push((
-                        DiscoveryMethod::SsdpBroadcast,
-                        error_to_kind(&e, self.config.ssdp.discovery_timeout.as_millis() as u64),
-                    ));
-                }
-            }
+        // Last resort for networks that drop multicast: probe every address
+        if all_discovered.is_empty() && self.config.subnet_scan_enabled {
+            log::info!("[Discovery] Nothing found, scanning local subnets...");
+            let start = Instant::now();
+            let result =
+                subnet_scan::discover_subnet(&self.config.subnet_scan, &mut subnet_report).await;
+            collect_method_result(
+                &mut subnet_report,
+                Some((result, start.elapsed())),
+                self.config.subnet_scan.probe_timeout.as_millis() as u64,
+                &mut all_discovered,
+                &mut method_errors,
+            );
         }
 
-        if let Some(result) = mdns_result {
-            match result {
-                Ok(speakers) => {
-                    log::info!("[Discovery] mDNS found {} speaker(s)", speakers.len());
-                    all_discovered.extend(speakers);
-                }
-                Err(e) => {
-                    log::warn!("[Discovery] mDNS failed: {}", e);
-                    method_errors.push((
-                        DiscoveryMethod::Mdns,
-                        error_to_kind(&e, self.config.mdns.browse_timeout.as_millis() as u64),
-                    ));
-                }
-            }
-        }
+        let reports = vec![
+            multicast_report,
+            broadcast_report,
+            mdns_report,
+            remote_report,
+            subnet_report,
+        ];
+        let duration_ms = started.elapsed().as_millis() as u64;
 
         // If all methods failed, return error
         if all_discovered.is_empty() && !method_errors.is_empty() {
-            return Err(DiscoveryError::AllMethodsFailed(method_errors));
+            let error = DiscoveryError::AllMethodsFailed(method_errors);
+            let mut diagnostics = DiscoveryDiagnostics::new(started_at, duration_ms, reports, 0);
+            diagnostics.error = Some(error.to_string());
+            return (Err(error), diagnostics);
         }
 
         // Normalize UUIDs and merge duplicates
-        let merged = self.merge_discovered(all_discovered);
+        let mut merged = self.merge_discovered(all_discovered);
 
         log::info!("[Discovery] {} unique speaker(s) after merge", merged.len());
 
-        // Fetch device descriptions
-        log::info!(
-            "[Discovery] Fetching device descriptions for {} speaker(s)...",
-            merged.len()
-        );
-        let speakers = self.fetch_device_descriptions(merged).await;
-        log::info!(
-            "[Discovery] Device description fetch complete, returning {} speaker(s)",
-            speakers.len()
-        );
+        // Fetches start in this order, so the cheapest, most certain ones
+        // resolve (and reach callers) first
+        merged.sort_by_key(fetch_priority);
 
-        Ok(speakers)
+        let diagnostics = DiscoveryDiagnostics::new(started_at, duration_ms, reports, merged.len());
+        (Ok(merged), diagnostics)
     }
 
     /// Merges discovered speakers by normalized UUID.
@@ -285,132 +466,181 @@ impl DiscoveryCoordinator {
         speakers
     }
 
-    /// Fetches device descriptions for all discovered speakers.
+    /// Resolves a discovered speaker's metadata from its device description.
     ///
-    /// Uses caching per (ip, port) to avoid duplicate requests.
-    async fn fetch_device_descriptions(&self, discovered: Vec<DiscoveredSpeaker>) -> Vec<Speaker> {
-        use futures::stream::{self, StreamExt};
-
-        // Cache for device descriptions by URL
-        let cache: Arc<tokio::sync::Mutex<HashMap<String, Option<DeviceInfo>>>> =
-            Arc::new(tokio::sync::Mutex::new(HashMap::new()));
-
-        let speakers: Vec<Speaker> = stream::iter(discovered)
-            .map(|speaker| {
-                let client = &self.http_client;
-                let cache = Arc::clone(&cache);
-                async move {
-                    let info = self
-                        .fetch_device_info_cached(client, &speaker, &cache)
-                        .await;
-
-                    Speaker {
-                        ip: speaker.preferred_ip().to_string(),
-                        uuid: info
-                            .as_ref()
-                            .map(|i| normalize_uuid(&i.uuid))
-                            .unwrap_or_else(|| speaker.uuid.clone()),
-                        name: info
-                            .as_ref()
-                            .map(|i| i.friendly_name.clone())
-                            .unwrap_or_else(|| format!("Sonos ({})", speaker.ip)),
-                        model_name: info.and_then(|i| i.model_name),
-                    }
-                }
-            })
-            .buffer_unordered(self.config.max_concurrent_fetches)
-            .collect()
-            .await;
-
-        speakers
+    /// Uses caching per URL to avoid duplicate requests. Also returns whether
+    /// the description resolved; without it the speaker gets a fallback name.
+    async fn resolve_speaker(
+        &self,
+        speaker: DiscoveredSpeaker,
+        cache: &DescriptionCache,
+    ) -> (Speaker, bool) {
+        let info = self.fetch_device_info_cached(&speaker, cache).await;
+        let has_description = info.is_some();
+
+        let ip = speaker.preferred_ip().to_string();
+        let mut resolved = match info {
+            Some(info) => Speaker::from_description(ip, info),
+            None => Speaker::new(
+                ip,
+                speaker.uuid.clone(),
+                format!("Sonos ({})", speaker.ip),
+                None,
+            ),
+        };
+        resolved.household_id = speaker.household_id;
+        (resolved, has_description)
     }
 
-    /// Fetches device info with caching.
+    /// Fetches device info, trying the SSDP LOCATION first (authoritative)
+    /// and then the standard ports 1400 and 1410.
     async fn fetch_device_info_cached(
         &self,
-        client: &Client,
         speaker: &DiscoveredSpeaker,
-        cache: &Arc<tokio::sync::Mutex<HashMap<String, Option<DeviceInfo>>>>,
+        cache: &DescriptionCache,
     ) -> Option<DeviceInfo> {
-        // Try SSDP LOCATION first (authoritative)
-        if let Some(ref location) = speaker.location {
-            {
-                let cache_guard = cache.lock().await;
-                if let Some(cached) = cache_guard.get(location) {
-                    return cached.clone();
-                }
-            }
+        let ip = speaker.preferred_ip();
+        let fallbacks =
+            [1400, 1410].map(|port| format!("http://{}:{}/xml/device_description.xml", ip, port));
 
-            let info = fetch_device_description(client, location).await;
-            {
-                let mut cache_guard = cache.lock().await;
-                cache_guard.insert(location.clone(), info.clone());
-            }
-            if info.is_some() {
-                return info;
+        for url in speaker.location.iter().cloned().chain(fallbacks) {
+            if let Some(info) = self.describe(&speaker.uuid, &url, cache).await {
+                return Some(info);
             }
         }
+        None
+    }
 
-        // Fallback: try port 1400
-        let url_1400 = format!(
-            "http://{}:1400/xml/device_description.xml",
-            speaker.preferred_ip()
-        );
-        {
-            let cache_guard = cache.lock().await;
-            if let Some(cached) = cache_guard.get(&url_1400) {
-                return cached.clone();
-            }
+    /// Resolves one description URL, at most once per discovery run.
+    async fn describe(
+        &self,
+        uuid: &str,
+        url: &str,
+        cache: &DescriptionCache,
+    ) -> Option<DeviceInfo> {
+        if let Some(cached) = cache.lock().await.get(url) {
+            return cached.clone();
         }
 
-        let info = fetch_device_description(client, &url_1400).await;
-        {
-            let mut cache_guard = cache.lock().await;
-            cache_guard.insert(url_1400.clone(), info.clone());
-        }
-        if info.is_some() {
-            return info;
-        }
+        let info = self.describe_with_persistent_cache(uuid, url).await;
+        cache.lock().await.insert(url.to_string(), info.clone());
+        info
+    }
 
-        // Fallback: try port 1410
-        let url_1410 = format!(
-            "http://{}:1410/xml/device_description.xml",
-            speaker.preferred_ip()
-        );
-        {
-            let cache_guard = cache.lock().await;
-            if let Some(cached) = cache_guard.get(&url_1410) {
-                return cached.clone();
+    /// Resolves a description from the persistent cache, revalidating or
+    /// fetching it when needed.
+    async fn describe_with_persistent_cache(&self, uuid: &str, url: &str) -> Option<DeviceInfo> {
+        let now = now_millis();
+        let stale =
+            match self
+                .description_cache
+                .lookup(uuid, url, self.config.description_cache_ttl, now)
+            {
+                Lookup::Fresh(info) => return Some(info),
+                Lookup::Stale(info, validators) => Some((info, validators)),
+                Lookup::Miss => None,
+            };
+
+        let validators = stale.as_ref().map(|(_, validators)| validators);
+        match fetch_device_description(&self.http_client, url, validators).await {
+            FetchOutcome::Fetched(info, validators) => {
+                self.description_cache
+                    .insert(uuid, url, &info, validators, now);
+                Some(*info)
             }
+            FetchOutcome::NotModified => {
+                log::trace!("[Discovery] Description unchanged: {}", url);
+                self.description_cache.revalidated(uuid, url, now);
+                stale.map(|(info, _)| info)
+            }
+            FetchOutcome::Failed => None,
         }
-
-        let info = fetch_device_description(client, &url_1410).await;
-        {
-            let mut cache_guard = cache.lock().await;
-            cache_guard.insert(url_1410, info.clone());
-        }
-        info
     }
 }
 
+/// Sort key for description fetches: speakers with an SSDP LOCATION first
+/// (one authoritative request, no port guessing), then those confirmed by
+/// the most discovery methods.
+fn fetch_priority(speaker: &DiscoveredSpeaker) -> (bool, Reverse<usize>) {
+    (speaker.location.is_none(), Reverse(speaker.methods.len()))
+}
+
+/// Outcome of a device description request.
+enum FetchOutcome {
+    /// Downloaded and parsed, with the validators to revalidate it later.
+    Fetched(Box<DeviceInfo>, Validators),
+    /// The conditional request matched: the cached copy is current.
+    NotModified,
+    /// Unreachable, or not a parseable description.
+    Failed,
+}
+
 /// Fetches and parses device description XML.
 ///
+/// Sends a conditional request when `validators` are given.
 /// Extracts UDN (canonical UUID), friendlyName, modelName, and modelNumber.
-async fn fetch_device_description(client: &Client, url: &str) -> Option<DeviceInfo> {
-    let response = client.get(url).send().await.ok()?;
-    let body = response.text().await.ok()?;
+async fn fetch_device_description(
+    client: &Client,
+    url: &str,
+    validators: Option<&Validators>,
+) -> FetchOutcome {
+    let mut request = client.get(url);
+    if let Some(validators) = validators {
+        if let Some(etag) = &validators.etag {
+            request = request.header(IF_NONE_MATCH, etag);
+        }
+        if let Some(last_modified) = &validators.last_modified {
+            request = request.header(IF_MODIFIED_SINCE, last_modified);
+        }
+    }
 
-    parse_device_description(&body)
+    let Ok(response) = request.send().await else {
+        return FetchOutcome::Failed;
+    };
+    if response.status() == StatusCode::NOT_MODIFIED {
+        return FetchOutcome::NotModified;
+    }
+
+    let header = |name| {
+        response
+            .headers()
+            .get(name)
+            .and_then(|v| v.to_str().ok())
+            .map(str::to_string)
+    };
+    let validators = Validators {
+        etag: header(ETAG),
+        last_modified: header(LAST_MODIFIED),
+    };
+
+    match response
+        .text()
+        .await
+        .ok()
+        .as_deref()
+        .and_then(parse_device_description)
+    {
+        Some(info) => FetchOutcome::Fetched(Box::new(info), validators),
+        None => FetchOutcome::Failed,
+    }
 }
 
 /// Parses device description XML.
-fn parse_device_description(xml: &str) -> Option<DeviceInfo> {
+///
+/// Version fields are taken from their first occurrence, which belongs to
+/// the root ZonePlayer device rather than its embedded sub-devices.
+pub(crate) fn parse_device_description(xml: &str) -> Option<DeviceInfo> {
     let mut reader = Reader::from_str(xml);
     let mut buf = Vec::new();
 
     let mut uuid = None;
     let mut friendly_name = None;
     let mut model_name = None;
+    let mut model_number = None;
+    let mut software_version = None;
+    let mut display_version = None;
+    let mut hardware_version = None;
+    let mut sw_gen = None;
 
     loop {
         match reader.read_event_into(&mut buf) {
@@ -428,6 +658,24 @@ fn parse_device_description(xml: &str) -> Option<DeviceInfo> {
                     b"modelName" => {
                         model_name = reader.read_text(e.name()).ok().map(|t| t.to_string());
                     }
+                    b"modelNumber" if model_number.is_none() => {
+                        model_number = reader.read_text(e.name()).ok().map(|t| t.to_string());
+                    }
+                    b"softwareVersion" if software_version.is_none() => {
+                        software_version = reader.read_text(e.name()).ok().map(|t| t.to_string());
+                    }
+                    b"displayVersion" if display_version.is_none() => {
+                        display_version = reader.read_text(e.name()).ok().map(|t| t.to_string());
+                    }
+                    b"hardwareVersion" if hardware_version.is_none() => {
+                        hardware_version = reader.read_text(e.name()).ok().map(|t| t.to_string());
+                    }
+                    b"swGen" if sw_gen.is_none() => {
+                        sw_gen = reader
+                            .read_text(e.name())
+                            .ok()
+                            .and_then(|t| t.trim().parse().ok());
+                    }
                     _ => {}
                 }
             }
@@ -447,11 +695,49 @@ fn parse_device_description(xml: &str) -> Option<DeviceInfo> {
             uuid,
             friendly_name,
             model_name,
+            model_number,
+            software_version,
+            display_version,
+            hardware_version,
+            sw_gen,
         }),
         _ => None,
     }
 }
 
+/// Collects the speakers of a method that ran, recording its outcome in
+/// `report`. Methods that didn't run (`None`) stay skipped.
+fn collect_method_result(
+    report: &mut MethodReport,
+    result: Option<(Result<Vec<DiscoveredSpeaker>, DiscoveryError>, Duration)>,
+    timeout_ms: u64,
+    discovered: &mut Vec<DiscoveredSpeaker>,
+    method_errors: &mut Vec<(DiscoveryMethod, DiscoveryErrorKind)>,
+) {
+    let Some((result, elapsed)) = result else {
+        return;
+    };
+    report.duration_ms = elapsed.as_millis() as u64;
+    match result {
+        Ok(speakers) => {
+            log::info!(
+                "[Discovery] {} found {} speaker(s)",
+                report.method,
+                speakers.len()
+            );
+            report.status = MethodStatus::Succeeded;
+            report.speakers = speakers.len();
+            discovered.extend(speakers);
+        }
+        Err(e) => {
+            log::warn!("[Discovery] {} failed: {}", report.method, e);
+            report.status = MethodStatus::Failed;
+            report.error = Some(e.to_string());
+            method_errors.push((report.method, error_to_kind(&e, timeout_ms)));
+        }
+    }
+}
+
 /// Converts a DiscoveryError to a DiscoveryErrorKind.
 fn error_to_kind(error: &DiscoveryError, timeout_ms: u64) -> DiscoveryErrorKind {
     match error {
@@ -471,11 +757,11 @@ fn error_to_kind(error: &DiscoveryError, timeout_ms: u64) -> DiscoveryErrorKind
             configured_ms: timeout_ms,
         },
         // These are only used for manual IP probing
-        DiscoveryError::IpUnreachable(_) | DiscoveryError::NotSonosDevice(_) => {
-            DiscoveryErrorKind::Timeout {
-                configured_ms: timeout_ms,
-            }
-        }
+        DiscoveryError::IpUnreachable(_)
+        | DiscoveryError::NotSonosDevice(_)
+        | DiscoveryError::HostUnresolved(_) => DiscoveryErrorKind::Timeout {
+            configured_ms: timeout_ms,
+        },
     }
 }
 
@@ -534,12 +820,7 @@ pub async fn probe_speaker_by_ip(client: &Client, ip: &str) -> Result<Speaker, D
     let url_1400 = format!("http://{}:1400/xml/device_description.xml", ip);
     match probe_url(client, &url_1400).await {
         ProbeResult::Success(info) => {
-            return Ok(Speaker {
-                ip: ip.to_string(),
-                uuid: normalize_uuid(&info.uuid),
-                name: info.friendly_name,
-                model_name: info.model_name,
-            });
+            return Ok(Speaker::from_description(ip.to_string(), info));
         }
         ProbeResult::NotSonos => {
             any_reachable = true;
@@ -553,12 +834,7 @@ pub async fn probe_speaker_by_ip(client: &Client, ip: &str) -> Result<Speaker, D
     // Try port 1410 as fallback (some Sonos devices)
     let url_1410 = format!("http://{}:1410/xml/device_description.xml", ip);
     match probe_url(client, &url_1410).await {
-        ProbeResult::Success(info) => Ok(Speaker {
-            ip: ip.to_string(),
-            uuid: normalize_uuid(&info.uuid),
-            name: info.friendly_name,
-            model_name: info.model_name,
-        }),
+        ProbeResult::Success(info) => Ok(Speaker::from_description(ip.to_string(), info)),
         ProbeResult::NotSonos => Err(DiscoveryError::NotSonosDevice(ip.to_string())),
         ProbeResult::Unreachable => {
             // If either port was reachable, the device exists but isn't Sonos
@@ -571,6 +847,55 @@ pub async fn probe_speaker_by_ip(client: &Client, ip: &str) -> Result<Speaker, D
     }
 }
 
+/// How long a hostname lookup may take before the speaker counts as
+/// unresolved.
+const HOST_RESOLVE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Resolves a manual speaker address to an IPv4 address.
+///
+/// IP addresses are returned as given. Hostnames are looked up through the
+/// system resolver, taking the first IPv4 address that is valid for a
+/// speaker.
+///
+/// # Errors
+/// * `HostUnresolved` - The lookup failed, timed out or gave no usable address
+pub async fn resolve_speaker_host(host: &str) -> Result<String, DiscoveryError> {
+    if !is_hostname(host) {
+        return Ok(host.to_string());
+    }
+
+    let lookup = tokio::net::lookup_host((host, 1400));
+    let addresses = match tokio::time::timeout(HOST_RESOLVE_TIMEOUT, lookup).await {
+        Ok(Ok(addresses)) => addresses,
+        Ok(Err(e)) => {
+            log::debug!("[Discovery] Lookup of {} failed: {}", host, e);
+            return Err(DiscoveryError::HostUnresolved(host.to_string()));
+        }
+        Err(_) => {
+            log::debug!("[Discovery] Lookup of {} timed out", host);
+            return Err(DiscoveryError::HostUnresolved(host.to_string()));
+        }
+    };
+
+    addresses
+        .filter_map(|addr| validate_speaker_ip(&addr.ip()).ok())
+        .map(|ip| ip.to_string())
+        .next()
+        .ok_or_else(|| DiscoveryError::HostUnresolved(host.to_string()))
+}
+
+/// Probes a manual speaker address, resolving it first if it's a hostname.
+///
+/// The returned speaker carries the resolved IP address.
+///
+/// # Errors
+/// * `HostUnresolved` - The hostname couldn't be resolved
+/// * Otherwise as [`probe_speaker_by_ip`]
+pub async fn probe_speaker_by_host(client: &Client, host: &str) -> Result<Speaker, DiscoveryError> {
+    let ip = resolve_speaker_host(host).await?;
+    probe_speaker_by_ip(client, &ip).await
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -594,6 +919,44 @@ mod tests {
         assert_eq!(info.uuid, "uuid:RINCON_ABC123456789");
         assert_eq!(info.friendly_name, "Living Room");
         assert_eq!(info.model_name, Some("Sonos Arc".to_string()));
+        assert_eq!(info.model_number.as_deref(), Some("S19"));
+    }
+
+    #[test]
+    fn parse_device_description_reads_root_versions() {
+        let xml = r#"<?xml version="1.0"?>
+<root xmlns="urn:schemas-upnp-org:device-1-0">
+  <device>
+    <friendlyName>192.168.1.20 - Sonos One - RINCON_000E58A0B1C201400</friendlyName>
+    <modelNumber>S18</modelNumber>
+    <modelName>Sonos One</modelName>
+    <softwareVersion>79.1-56100</softwareVersion>
+    <swGen>2</swGen>
+    <hardwareVersion>1.20.1.6-2.0</hardwareVersion>
+    <displayVersion>16.4</displayVersion>
+    <UDN>uuid:RINCON_000E58A0B1C201400</UDN>
+    <deviceList>
+      <device>
+        <modelNumber>S18-MR</modelNumber>
+        <softwareVersion>1.0</softwareVersion>
+        <UDN>uuid:RINCON_000E58A0B1C201400_MR</UDN>
+      </device>
+    </deviceList>
+  </device>
+</root>"#;
+
+        let info = parse_device_description(xml).unwrap();
+        assert_eq!(info.model_number.as_deref(), Some("S18"));
+        assert_eq!(info.software_version.as_deref(), Some("79.1-56100"));
+        assert_eq!(info.display_version.as_deref(), Some("16.4"));
+        assert_eq!(info.hardware_version.as_deref(), Some("1.20.1.6-2.0"));
+        assert_eq!(info.sw_gen, Some(2));
+
+        let speaker = Speaker::from_description("192.168.1.20".into(), info);
+        assert_eq!(speaker.uuid, "RINCON_000E58A0B1C201400");
+        assert_eq!(speaker.display_version.as_deref(), Some("16.4"));
+        assert_eq!(speaker.hardware_version.as_deref(), Some("1.20.1.6-2.0"));
+        assert_eq!(speaker.sw_gen, Some(2));
     }
 
     #[test]
@@ -614,6 +977,101 @@ mod tests {
         assert!(info.model_name.is_none());
     }
 
+    #[test]
+    fn fetches_located_and_corroborated_speakers_first() {
+        let mdns_only = DiscoveredSpeaker::new(
+            "192.168.1.30".into(),
+            "RINCON_C".into(),
+            DiscoveryMethod::Mdns,
+        );
+        let mut corroborated = DiscoveredSpeaker::new(
+            "192.168.1.20".into(),
+            "RINCON_B".into(),
+            DiscoveryMethod::Mdns,
+        );
+        corroborated.merge(DiscoveredSpeaker::new(
+            "192.168.1.20".into(),
+            "RINCON_B".into(),
+            DiscoveryMethod::SsdpBroadcast,
+        ));
+        let located = DiscoveredSpeaker::with_location(
+            "192.168.1.10".into(),
+            "RINCON_A".into(),
+            "http://192.168.1.10:1400/xml/device_description.xml".into(),
+            DiscoveryMethod::SsdpMulticast,
+        );
+
+        let mut candidates = [mdns_only, corroborated, located];
+        candidates.sort_by_key(fetch_priority);
+
+        let order: Vec<&str> = candidates.iter().map(|s| s.uuid.as_str()).collect();
+        assert_eq!(order, ["RINCON_A", "RINCON_B", "RINCON_C"]);
+    }
+
+    #[tokio::test]
+    async fn revalidates_stale_descriptions_conditionally() {
+        use axum::http::{header, HeaderMap, StatusCode};
+        use axum::response::IntoResponse;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        const XML: &str = r#"<root><device><friendlyName>Kitchen</friendlyName><UDN>uuid:RINCON_A</UDN></device></root>"#;
+
+        let downloads = Arc::new(AtomicUsize::new(0));
+        let not_modified = Arc::new(AtomicUsize::new(0));
+        let app = {
+            let downloads = Arc::clone(&downloads);
+            let not_modified = Arc::clone(&not_modified);
+            axum::Router::new().route(
+                "/xml/device_description.xml",
+                axum::routing::get(move |headers: HeaderMap| async move {
+                    if headers
+                        .get(header::IF_NONE_MATCH)
+                        .is_some_and(|v| v == "\"v1\"")
+                    {
+                        not_modified.fetch_add(1, Ordering::SeqCst);
+                        return StatusCode::NOT_MODIFIED.into_response();
+                    }
+                    downloads.fetch_add(1, Ordering::SeqCst);
+                    ([(header::ETAG, "\"v1\"")], XML).into_response()
+                }),
+            )
+        };
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!(
+            "http://{}/xml/device_description.xml",
+            listener.local_addr().unwrap()
+        );
+        tokio::spawn(async move { axum::serve(listener, app).await });
+
+        let cache = Arc::new(DeviceDescriptionCache::new());
+        let always_stale = DiscoveryCoordinator::with_description_cache(
+            DiscoveryConfig {
+                description_cache_ttl: Duration::ZERO,
+                ..DiscoveryConfig::default()
+            },
+            Arc::clone(&cache),
+        );
+
+        for _ in 0..2 {
+            let info = always_stale
+                .describe_with_persistent_cache("RINCON_A", &url)
+                .await
+                .expect("description");
+            assert_eq!(info.friendly_name, "Kitchen");
+        }
+        assert_eq!(downloads.load(Ordering::SeqCst), 1);
+        assert_eq!(not_modified.load(Ordering::SeqCst), 1);
+
+        // Within the TTL the cached copy is used without any request
+        let fresh = DiscoveryCoordinator::with_description_cache(DiscoveryConfig::default(), cache);
+        assert!(fresh
+            .describe_with_persistent_cache("RINCON_A", &url)
+            .await
+            .is_some());
+        assert_eq!(downloads.load(Ordering::SeqCst), 1);
+        assert_eq!(not_modified.load(Ordering::SeqCst), 1);
+    }
+
     #[test]
     fn test_parse_device_description_missing_required() {
         let xml = r#"<?xml version="1.0"?>
@@ -626,4 +1084,17 @@ mod tests {
         let info = parse_device_description(xml);
         assert!(info.is_none());
     }
+
+    #[tokio::test]
+    async fn resolve_speaker_host_passes_ips_through() {
+        assert_eq!(
+            resolve_speaker_host("192.168.1.20").await.unwrap(),
+            "192.168.1.20"
+        );
+        // localhost only resolves to loopback, which no speaker can have
+        assert!(matches!(
+            resolve_speaker_host("localhost").await,
+            Err(DiscoveryError::HostUnresolved(_))
+        ));
+    }
 }
@@ -0,0 +1,356 @@
+//! Unicast discovery of speakers on remote subnets.
+//!
+//! Multicast doesn't cross VLANs or routed subnets, so SSDP and mDNS never
+//! see speakers on, say, a separate IoT VLAN. For each configured range a
+//! unicast M-SEARCH is sent to every address, which speakers answer just
+//! like a multicast one. Ranges where nothing answered (UDP 1900 is often
+//! filtered between VLANs while the speakers' port 1400 is not) fall back to
+//! fetching the device description from every address, as the subnet scan
+//! does.
+//!
+//! Ranges come from the configuration and, for the desktop app, from
+//! `remote_subnets.json` in the data directory ([`RemoteSubnets`]).
+
+use futures::stream::{self, StreamExt};
+use parking_lot::RwLock;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashSet};
+use std::net::{Ipv4Addr, SocketAddr};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use tokio::net::UdpSocket;
+use tokio::time::timeout;
+
+use super::diagnostics::MethodReport;
+use super::ssdp::{build_msearch_message, parse_ssdp_response};
+use super::subnet_scan::{hosts, parse_cidr, probe, Probe, MIN_PREFIX_LEN};
+use super::types::{DiscoveredSpeaker, DiscoveryError, DiscoveryMethod};
+use crate::error::{ThaumicError, ThaumicResult};
+use crate::storage;
+
+const REMOTE_SUBNETS_FILE: &str = "remote_subnets.json";
+
+/// Configuration for remote subnet discovery.
+#[derive(Debug, Clone)]
+pub struct RemoteSubnetsConfig {
+    /// How long to wait for M-SEARCH answers.
+    pub search_timeout: Duration,
+    /// Number of M-SEARCH rounds sent to every address.
+    pub send_count: u64,
+    /// Timeout for each description probe, connecting included.
+    pub probe_timeout: Duration,
+    /// Maximum description probes in flight.
+    pub max_concurrent: usize,
+}
+
+impl Default for RemoteSubnetsConfig {
+    fn default() -> Self {
+        Self {
+            search_timeout: Duration::from_secs(3),
+            send_count: 2,
+            probe_timeout: Duration::from_millis(800),
+            max_concurrent: 64,
+        }
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct RemoteSubnetsFile {
+    subnets: Vec<String>,
+}
+
+/// Subnets to search by unicast, as IPv4 CIDR ranges.
+///
+/// Entries come from [`Config::remote_subnets`](crate::state::Config) and
+/// from [`set_saved`](Self::set_saved); the latter are saved to
+/// `remote_subnets.json` in the data directory.
+#[derive(Debug, Default)]
+pub struct RemoteSubnets {
+    /// Ranges from the configuration, which can't be changed at runtime.
+    configured: RwLock<Vec<String>>,
+    /// Ranges saved in the data directory.
+    saved: RwLock<Vec<String>>,
+    data_dir: RwLock<Option<PathBuf>>,
+}
+
+impl RemoteSubnets {
+    /// Sets the ranges from the configuration, skipping invalid ones.
+    pub fn set_configured(&self, subnets: &[String]) {
+        *self.configured.write() = subnets
+            .iter()
+            .filter_map(|subnet| match normalize_subnet(subnet) {
+                Ok(subnet) => Some(subnet),
+                Err(e) => {
+                    log::warn!("[RemoteSubnets] Skipping {:?}: {}", subnet, e);
+                    None
+                }
+            })
+            .collect();
+    }
+
+    /// Sets the directory saved ranges are persisted to, and loads them.
+    pub fn set_data_dir(&self, dir: impl AsRef<Path>) {
+        let dir = dir.as_ref().to_path_buf();
+        let loaded = match std::fs::read_to_string(dir.join(REMOTE_SUBNETS_FILE)) {
+            Ok(contents) => serde_json::from_str(&contents).unwrap_or_else(|e| {
+                log::warn!(
+                    "[RemoteSubnets] Ignoring unreadable {}: {}",
+                    REMOTE_SUBNETS_FILE,
+                    e
+                );
+                RemoteSubnetsFile::default()
+            }),
+            Err(_) => RemoteSubnetsFile::default(),
+        };
+        *self.saved.write() = loaded
+            .subnets
+            .iter()
+            .filter_map(|subnet| normalize_subnet(subnet).ok())
+            .collect();
+        *self.data_dir.write() = Some(dir);
+    }
+
+    /// Returns every range, configured ones first.
+    pub fn entries(&self) -> Vec<String> {
+        let mut entries = self.configured.read().clone();
+        for subnet in self.saved.read().iter() {
+            if !entries.contains(subnet) {
+                entries.push(subnet.clone());
+            }
+        }
+        entries
+    }
+
+    /// Returns the ranges saved in the data directory.
+    pub fn saved(&self) -> Vec<String> {
+        self.saved.read().clone()
+    }
+
+    /// Replaces the saved ranges.
+    ///
+    /// Returns the ranges as stored, with host bits cleared.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a range isn't an IPv4 CIDR range of /20 or
+    /// narrower, no data directory is set, or saving fails.
+    pub fn set_saved(&self, subnets: &[String]) -> ThaumicResult<Vec<String>> {
+        let mut normalized: Vec<String> = Vec::new();
+        for subnet in subnets {
+            let subnet = normalize_subnet(subnet).map_err(ThaumicError::InvalidRequest)?;
+            if !normalized.contains(&subnet) {
+                normalized.push(subnet);
+            }
+        }
+        let dir = self.data_dir.read().clone().ok_or_else(|| {
+            ThaumicError::DataDirNotConfigured("remote subnets can't be saved".into())
+        })?;
+        let mut saved = self.saved.write();
+        save_remote_subnets(&dir, &normalized)
+            .map_err(|e| ThaumicError::SaveFailed(e.to_string()))?;
+        log::info!("[RemoteSubnets] Searching {:?}", normalized);
+        *saved = normalized.clone();
+        Ok(normalized)
+    }
+}
+
+/// Normalizes a range to `network/prefix`.
+fn normalize_subnet(subnet: &str) -> Result<String, String> {
+    parse_cidr(subnet)
+        .map(|(network, prefix)| format!("{}/{}", network, prefix))
+        .ok_or_else(|| {
+            format!(
+                "{:?} is not an IPv4 range in CIDR notation, /{} or narrower",
+                subnet, MIN_PREFIX_LEN
+            )
+        })
+}
+
+/// Writes the saved ranges atomically (temp file + rename), keeping them in
+/// memory only while the data directory is full or read-only.
+fn save_remote_subnets(dir: &Path, subnets: &[String]) -> std::io::Result<()> {
+    let file = RemoteSubnetsFile {
+        subnets: subnets.to_vec(),
+    };
+    storage::tolerate(storage::write_atomic(
+        dir,
+        REMOTE_SUBNETS_FILE,
+        serde_json::to_string_pretty(&file)?,
+    ))
+}
+
+/// Discovers Sonos speakers on the given ranges by unicast.
+///
+/// Datagrams received and probes answered are counted in `report`.
+///
+/// # Errors
+///
+/// `SocketBind` if no UDP socket could be opened for the M-SEARCH.
+pub async fn discover_remote(
+    config: &RemoteSubnetsConfig,
+    subnets: &[String],
+    report: &mut MethodReport,
+) -> Result<Vec<DiscoveredSpeaker>, DiscoveryError> {
+    let ranges: Vec<(Ipv4Addr, u8)> = subnets
+        .iter()
+        .filter_map(|subnet| parse_cidr(subnet))
+        .collect();
+    let targets: BTreeSet<Ipv4Addr> = ranges
+        .iter()
+        .flat_map(|(network, prefix)| hosts(*network, *prefix))
+        .collect();
+    log::debug!(
+        "[RemoteSubnets] Searching {} address(es) in {} range(s)",
+        targets.len(),
+        ranges.len()
+    );
+
+    let mut speakers = search(config, &targets, report).await?;
+
+    // Description probes for ranges no speaker answered the M-SEARCH in
+    let answered: HashSet<&str> = speakers.iter().map(|s| s.ip.as_str()).collect();
+    let silent_ranges: Vec<&(Ipv4Addr, u8)> = ranges
+        .iter()
+        .filter(|(network, prefix)| {
+            !hosts(*network, *prefix).any(|ip| answered.contains(ip.to_string().as_str()))
+        })
+        .collect();
+    if silent_ranges.is_empty() {
+        return Ok(speakers);
+    }
+    let probe_targets: BTreeSet<Ipv4Addr> = silent_ranges
+        .iter()
+        .flat_map(|(network, prefix)| hosts(*network, *prefix))
+        .collect();
+    log::debug!(
+        "[RemoteSubnets] No M-SEARCH answers from {} range(s), probing {} address(es)",
+        silent_ranges.len(),
+        probe_targets.len()
+    );
+
+    let client = Client::builder()
+        .timeout(config.probe_timeout)
+        .connect_timeout(config.probe_timeout)
+        .build()
+        .map_err(|e| DiscoveryError::SocketBind(std::io::Error::other(e)))?;
+    let probes: Vec<Probe> = stream::iter(probe_targets)
+        .map(|ip| probe(&client, ip, DiscoveryMethod::RemoteSubnets))
+        .buffer_unordered(config.max_concurrent.max(1))
+        .collect()
+        .await;
+    for probe in probes {
+        match probe {
+            Probe::NoAnswer => {}
+            Probe::NotSonos => {
+                report.responses += 1;
+                report.dropped += 1;
+            }
+            Probe::Speaker(speaker) => {
+                report.responses += 1;
+                speakers.push(speaker);
+            }
+        }
+    }
+    Ok(speakers)
+}
+
+/// Sends a unicast M-SEARCH to every target and collects the answers.
+async fn search(
+    config: &RemoteSubnetsConfig,
+    targets: &BTreeSet<Ipv4Addr>,
+    report: &mut MethodReport,
+) -> Result<Vec<DiscoveredSpeaker>, DiscoveryError> {
+    let socket = UdpSocket::bind(SocketAddr::from((Ipv4Addr::UNSPECIFIED, 0)))
+        .await
+        .map_err(DiscoveryError::SocketBind)?;
+    let msg = build_msearch_message(1);
+
+    let send = async {
+        let mut failed = 0;
+        for round in 0..config.send_count {
+            if round > 0 {
+                tokio::time::sleep(Duration::from_millis(500)).await;
+            }
+            for ip in targets {
+                if socket.send_to(msg.as_bytes(), (*ip, 1900)).await.is_err() {
+                    failed += 1;
+                }
+            }
+        }
+        failed
+    };
+
+    let receive = async {
+        let mut speakers: Vec<DiscoveredSpeaker> = Vec::new();
+        let mut buf = [0u8; 2048];
+        let start = Instant::now();
+        while let Some(remaining) = config.search_timeout.checked_sub(start.elapsed()) {
+            let Ok(Ok((amt, src))) = timeout(remaining, socket.recv_from(&mut buf)).await else {
+                break;
+            };
+            report.responses += 1;
+            let response = String::from_utf8_lossy(&buf[..amt]);
+            match parse_ssdp_response(
+                &response,
+                &src.ip().to_string(),
+                DiscoveryMethod::RemoteSubnets,
+            ) {
+                Some(speaker) if !speakers.iter().any(|s| s.uuid == speaker.uuid) => {
+                    log::debug!(
+                        "[RemoteSubnets] Discovered speaker: ip={}, uuid={}",
+                        speaker.ip,
+                        speaker.uuid
+                    );
+                    speakers.push(speaker);
+                }
+                Some(_) => {}
+                None => report.dropped += 1,
+            }
+        }
+        speakers
+    };
+
+    let (failed, speakers) = tokio::join!(send, receive);
+    if failed > 0 {
+        log::debug!("[RemoteSubnets] {} M-SEARCH send(s) failed", failed);
+    }
+    Ok(speakers)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn saved_subnets_are_normalized_and_persisted() {
+        let dir = tempfile::tempdir().unwrap();
+        let subnets = RemoteSubnets::default();
+        subnets.set_configured(&["10.0.20.0/24".to_string(), "bogus".to_string()]);
+        subnets.set_data_dir(dir.path());
+
+        assert_eq!(
+            subnets
+                .set_saved(&["10.0.30.7/24".to_string(), "10.0.30.0/24".to_string()])
+                .unwrap(),
+            vec!["10.0.30.0/24"]
+        );
+        assert!(matches!(
+            subnets.set_saved(&["10.0.0.0/8".to_string()]),
+            Err(ThaumicError::InvalidRequest(_))
+        ));
+        assert_eq!(subnets.entries(), vec!["10.0.20.0/24", "10.0.30.0/24"]);
+
+        let reloaded = RemoteSubnets::default();
+        reloaded.set_data_dir(dir.path());
+        assert_eq!(reloaded.saved(), vec!["10.0.30.0/24"]);
+    }
+
+    #[test]
+    fn saving_requires_a_data_dir() {
+        assert!(matches!(
+            RemoteSubnets::default().set_saved(&["10.0.30.0/24".to_string()]),
+            Err(ThaumicError::DataDirNotConfigured(_))
+        ));
+    }
+}
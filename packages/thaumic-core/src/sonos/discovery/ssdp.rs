@@ -10,6 +10,10 @@
 //!
 //! Both methods use the same socket for send AND receive since devices reply
 //! unicast back to the sending socket/port.
+//!
+//! Alongside these active searches, [`listen_notify`] passively receives the
+//! `ssdp:alive`/`ssdp:byebye` announcements speakers multicast when they join
+//! or leave the network.
 
 use local_ip_address::list_afinet_netifas;
 use socket2::{Domain, Protocol, Socket, Type};
@@ -18,9 +22,11 @@ use std::net::{IpAddr, Ipv4Addr, SocketAddr};
 use std::sync::Arc;
 use std::time::Duration;
 use tokio::net::UdpSocket;
-use tokio::sync::Mutex;
+use tokio::sync::{mpsc, Mutex};
 use tokio::time::timeout;
+use tokio_util::sync::CancellationToken;
 
+use super::diagnostics::{InterfaceReport, MethodReport};
 use super::types::{is_virtual_interface, DiscoveredSpeaker, DiscoveryError, DiscoveryMethod};
 
 // ─────────────────────────────────────────────────────────────────────────────
@@ -75,6 +81,12 @@ fn find_ignore_ascii_case(haystack: &str, needle: &str) -> Option<usize> {
 /// Standard SSDP multicast address and port (protocol specification).
 const MULTICAST_ADDR: &str = "239.255.255.250:1900";
 
+/// SSDP multicast group, joined to receive NOTIFY announcements.
+const MULTICAST_GROUP: Ipv4Addr = Ipv4Addr::new(239, 255, 255, 250);
+
+/// Port NOTIFY announcements are sent to.
+const SSDP_PORT: u16 = 1900;
+
 /// Limited broadcast address for fallback.
 const LIMITED_BROADCAST_ADDR: &str = "255.255.255.255:1900";
 
@@ -85,7 +97,7 @@ const SONOS_SEARCH_TARGET: &str = "urn:schemas-upnp-org:device:ZonePlayer:1";
 ///
 /// Note: HOST header always uses the multicast address per SSDP spec,
 /// even when sending via broadcast.
-fn build_msearch_message(mx: u64) -> String {
+pub(super) fn build_msearch_message(mx: u64) -> String {
     format!(
         "M-SEARCH * HTTP/1.1\r\n\
          HOST: 239.255.255.250:1900\r\n\
@@ -196,7 +208,7 @@ fn create_socket(iface_ip: Ipv4Addr, enable_broadcast: bool) -> Result<UdpSocket
 ///
 /// Returns None if the response doesn't appear to be from a Sonos device.
 /// Uses ASCII case-insensitive comparison to avoid allocations during discovery burst.
-fn parse_ssdp_response(
+pub(super) fn parse_ssdp_response(
     response: &str,
     src_ip: &str,
     method: DiscoveryMethod,
@@ -230,15 +242,18 @@ fn parse_ssdp_response(
         return None;
     }
 
-    match location {
-        Some(loc) => Some(DiscoveredSpeaker::with_location(
-            src_ip.to_string(),
-            uuid,
-            loc,
-            method,
-        )),
-        None => Some(DiscoveredSpeaker::new(src_ip.to_string(), uuid, method)),
-    }
+    let household_id = response
+        .lines()
+        .find(|l| starts_with_ignore_ascii_case(l, "x-rincon-household:"))
+        .map(|l| l["x-rincon-household:".len()..].trim().to_string())
+        .filter(|id| !id.is_empty());
+
+    let mut speaker = match location {
+        Some(loc) => DiscoveredSpeaker::with_location(src_ip.to_string(), uuid, loc, method),
+        None => DiscoveredSpeaker::new(src_ip.to_string(), uuid, method),
+    };
+    speaker.household_id = household_id;
+    Some(speaker)
 }
 
 /// Configuration for SSDP discovery.
@@ -268,10 +283,12 @@ impl Default for SsdpConfig {
 /// Discovers Sonos speakers using SSDP multicast.
 ///
 /// Sends M-SEARCH queries to 239.255.255.250:1900 on all non-virtual interfaces.
+/// Responses and errors per interface are recorded in `report`.
 pub async fn discover_multicast(
     config: &SsdpConfig,
+    report: &mut MethodReport,
 ) -> Result<Vec<DiscoveredSpeaker>, DiscoveryError> {
-    discover_ssdp(config, DiscoveryMethod::SsdpMulticast, false).await
+    discover_ssdp(config, DiscoveryMethod::SsdpMulticast, false, report).await
 }
 
 /// Discovers Sonos speakers using SSDP broadcast.
@@ -279,10 +296,13 @@ pub async fn discover_multicast(
 /// Uses two layers:
 /// 1. Directed broadcast per interface (e.g., 192.168.1.255)
 /// 2. Limited broadcast fallback (255.255.255.255)
+///
+/// Responses and errors per interface are recorded in `report`.
 pub async fn discover_broadcast(
     config: &SsdpConfig,
+    report: &mut MethodReport,
 ) -> Result<Vec<DiscoveredSpeaker>, DiscoveryError> {
-    discover_ssdp(config, DiscoveryMethod::SsdpBroadcast, true).await
+    discover_ssdp(config, DiscoveryMethod::SsdpBroadcast, true, report).await
 }
 
 /// Internal SSDP discovery implementation.
@@ -293,6 +313,7 @@ async fn discover_ssdp(
     config: &SsdpConfig,
     method: DiscoveryMethod,
     use_broadcast: bool,
+    report: &mut MethodReport,
 ) -> Result<Vec<DiscoveredSpeaker>, DiscoveryError> {
     let interfaces = get_interfaces();
 
@@ -316,6 +337,9 @@ async fn discover_ssdp(
                     iface.ip,
                     e
                 );
+                let mut interface = InterfaceReport::new(&iface.name, iface.ip);
+                interface.record_error(e.to_string());
+                report.interfaces.push(interface);
             }
         }
     }
@@ -374,6 +398,7 @@ async fn discover_ssdp(
             let target_addrs = get_target_addrs(&iface);
 
             async move {
+                let mut errors = Vec::new();
                 for i in 0..send_count {
                     if i > 0 {
                         tokio::time::sleep(retry_delay).await;
@@ -388,6 +413,7 @@ async fn discover_ssdp(
                                 i + 1,
                                 e
                             );
+                            errors.push(format!("send to {} failed: {}", target, e));
                         } else {
                             log::trace!(
                                 "[{}] Sent M-SEARCH from {} to {}",
@@ -398,6 +424,7 @@ async fn discover_ssdp(
                         }
                     }
                 }
+                errors
             }
         })
         .collect();
@@ -413,6 +440,7 @@ async fn discover_ssdp(
             let discovery_timeout = config.discovery_timeout;
 
             async move {
+                let mut report = InterfaceReport::new(&iface_name, iface_ip);
                 let mut buf = [0u8; 2048];
                 let start = std::time::Instant::now();
 
@@ -427,10 +455,11 @@ async fn discover_ssdp(
                     let remaining = discovery_timeout.saturating_sub(start.elapsed());
                     match timeout(remaining, socket.recv_from(&mut buf)).await {
                         Ok(Ok((amt, src))) => {
+                            report.responses += 1;
                             let response = String::from_utf8_lossy(&buf[..amt]);
-                            if let Some(speaker) =
-                                parse_ssdp_response(&response, &src.ip().to_string(), method)
-                            {
+                            let parsed =
+                                parse_ssdp_response(&response, &src.ip().to_string(), method);
+                            if let Some(speaker) = parsed {
                                 log::debug!(
                                     "[{}] Discovered speaker: ip={}, uuid={}, via {}",
                                     method,
@@ -439,6 +468,8 @@ async fn discover_ssdp(
                                     iface_name
                                 );
                                 discovered.lock().await.push(speaker);
+                            } else {
+                                report.dropped += 1;
                             }
                         }
                         Ok(Err(e)) => {
@@ -449,6 +480,7 @@ async fn discover_ssdp(
                                 iface_ip,
                                 e
                             );
+                            report.record_error(format!("receive failed: {}", e));
                         }
                         Err(_) => break, // Timeout
                     }
@@ -461,6 +493,7 @@ async fn discover_ssdp(
                     iface_ip,
                     start.elapsed().as_millis()
                 );
+                report
             }
         })
         .collect();
@@ -472,12 +505,22 @@ async fn discover_ssdp(
         send_futures.len(),
         recv_futures.len()
     );
-    let (_, _) = tokio::join!(
+    let (send_errors, interface_reports) = tokio::join!(
         futures::future::join_all(send_futures),
         futures::future::join_all(recv_futures)
     );
     log::trace!("[{}] All send and recv futures completed", method);
 
+    // Both lists follow the order of `sockets`
+    for (mut interface, errors) in interface_reports.into_iter().zip(send_errors) {
+        for error in errors {
+            interface.record_error(error);
+        }
+        report.responses += interface.responses;
+        report.dropped += interface.dropped;
+        report.interfaces.push(interface);
+    }
+
     // Extract discovered speakers
     let mut discovered = std::mem::take(&mut *discovered.lock().await);
 
@@ -497,6 +540,149 @@ async fn discover_ssdp(
     Ok(discovered)
 }
 
+/// An SSDP announcement from a Sonos speaker.
+#[derive(Debug, Clone)]
+pub enum SsdpNotification {
+    /// The speaker is on the network (`ssdp:alive`).
+    Alive(DiscoveredSpeaker),
+    /// The speaker is leaving the network (`ssdp:byebye`).
+    ByeBye {
+        /// RINCON UUID of the departing speaker.
+        uuid: String,
+    },
+}
+
+/// Parses a NOTIFY announcement.
+///
+/// Returns None for other messages (M-SEARCH requests from other clients),
+/// unknown NTS values, and non-Sonos devices. `ssdp:byebye` carries no
+/// SERVER or LOCATION header, so only its RINCON UUID identifies a speaker.
+fn parse_ssdp_notify(message: &str, src_ip: &str) -> Option<SsdpNotification> {
+    if !starts_with_ignore_ascii_case(message, "NOTIFY ") {
+        return None;
+    }
+
+    let nts = message
+        .lines()
+        .find(|l| starts_with_ignore_ascii_case(l, "nts:"))
+        .map(|l| l[4..].trim())?;
+
+    if nts.eq_ignore_ascii_case("ssdp:alive") {
+        parse_ssdp_response(message, src_ip, DiscoveryMethod::SsdpMulticast)
+            .map(SsdpNotification::Alive)
+    } else if nts.eq_ignore_ascii_case("ssdp:byebye") {
+        let uuid = message
+            .lines()
+            .find(|l| starts_with_ignore_ascii_case(l, "usn:"))
+            .and_then(|l| find_ignore_ascii_case(l, "uuid:").map(|idx| &l[idx + 5..]))
+            .and_then(|s| s.split("::").next())?;
+        uuid.starts_with("RINCON_")
+            .then(|| SsdpNotification::ByeBye {
+                uuid: uuid.to_string(),
+            })
+    } else {
+        None
+    }
+}
+
+/// Creates a socket on the SSDP port that has joined the multicast group on
+/// every usable interface.
+///
+/// SO_REUSEADDR/SO_REUSEPORT let it share the port with other SSDP stacks
+/// on the host.
+fn create_notify_socket(interfaces: &[InterfaceInfo]) -> Result<UdpSocket, DiscoveryError> {
+    let socket = Socket::new(Domain::IPV4, Type::DGRAM, Some(Protocol::UDP))
+        .map_err(DiscoveryError::SocketBind)?;
+
+    if let Err(e) = socket.set_reuse_address(true) {
+        log::warn!("Failed to set SO_REUSEADDR on SSDP listener: {}", e);
+    }
+
+    #[cfg(unix)]
+    if let Err(e) = socket.set_reuse_port(true) {
+        log::warn!("Failed to set SO_REUSEPORT on SSDP listener: {}", e);
+    }
+
+    socket
+        .set_nonblocking(true)
+        .map_err(DiscoveryError::SocketBind)?;
+
+    let bind_addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::UNSPECIFIED), SSDP_PORT);
+    socket
+        .bind(&bind_addr.into())
+        .map_err(DiscoveryError::SocketBind)?;
+
+    let joined = interfaces
+        .iter()
+        .filter(
+            |iface| match socket.join_multicast_v4(&MULTICAST_GROUP, &iface.ip) {
+                Ok(()) => true,
+                Err(e) => {
+                    log::warn!(
+                        "Failed to join SSDP multicast group on {} ({}): {}",
+                        iface.name,
+                        iface.ip,
+                        e
+                    );
+                    false
+                }
+            },
+        )
+        .count();
+    if joined == 0 {
+        return Err(DiscoveryError::NoInterfaces);
+    }
+
+    let std_socket: std::net::UdpSocket = socket.into();
+    UdpSocket::from_std(std_socket).map_err(DiscoveryError::SocketBind)
+}
+
+/// Listens for Sonos NOTIFY announcements until `cancel` fires or the
+/// receiver is dropped.
+///
+/// Speakers announce themselves when they power up or change address and
+/// say goodbye when shutting down, so this picks up changes seconds after
+/// they happen rather than at the next M-SEARCH. Returns an error only if
+/// the listener can't be set up (port 1900 taken exclusively, no
+/// interfaces).
+pub async fn listen_notify(
+    tx: mpsc::Sender<SsdpNotification>,
+    cancel: CancellationToken,
+) -> Result<(), DiscoveryError> {
+    let interfaces = get_interfaces();
+    if interfaces.is_empty() {
+        return Err(DiscoveryError::NoInterfaces);
+    }
+    let socket = create_notify_socket(&interfaces)?;
+    log::info!(
+        "[SSDP] Listening for NOTIFY announcements on {} interface(s)",
+        interfaces.len()
+    );
+
+    let mut buf = [0u8; 2048];
+    loop {
+        let (amt, src) = tokio::select! {
+            _ = cancel.cancelled() => return Ok(()),
+            result = socket.recv_from(&mut buf) => match result {
+                Ok(received) => received,
+                Err(e) => {
+                    log::warn!("[SSDP] NOTIFY listener recv error: {}", e);
+                    continue;
+                }
+            },
+        };
+
+        let message = String::from_utf8_lossy(&buf[..amt]);
+        let Some(notification) = parse_ssdp_notify(&message, &src.ip().to_string()) else {
+            continue;
+        };
+        log::trace!("[SSDP] NOTIFY from {}: {:?}", src.ip(), notification);
+        if tx.send(notification).await.is_err() {
+            return Ok(());
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -527,6 +713,7 @@ X-RINCON-HOUSEHOLD: Sonos_abc123
         assert_eq!(speaker.ip, "192.168.1.10");
         assert_eq!(speaker.uuid, "RINCON_ABC12345678901400");
         assert!(speaker.location.is_some());
+        assert_eq!(speaker.household_id.as_deref(), Some("Sonos_abc123"));
     }
 
     #[test]
@@ -588,4 +775,50 @@ usn: UUID:RINCON_ABC12345678901400::urn:schemas-upnp-org:device:ZonePlayer:1
         assert_eq!(find_ignore_ascii_case("no match here", "uuid:"), None);
         assert_eq!(find_ignore_ascii_case("test", ""), Some(0)); // Empty needle
     }
+
+    #[test]
+    fn test_parse_ssdp_notify_alive() {
+        let message = "NOTIFY * HTTP/1.1\r\n\
+HOST: 239.255.255.250:1900\r\n\
+CACHE-CONTROL: max-age = 1800\r\n\
+LOCATION: http://192.168.1.10:1400/xml/device_description.xml\r\n\
+NT: urn:schemas-upnp-org:device:ZonePlayer:1\r\n\
+NTS: ssdp:alive\r\n\
+SERVER: Linux UPnP/1.0 Sonos/63.2-88230 (ZPS1)\r\n\
+USN: uuid:RINCON_ABC12345678901400::urn:schemas-upnp-org:device:ZonePlayer:1\r\n\r\n";
+
+        let Some(SsdpNotification::Alive(speaker)) = parse_ssdp_notify(message, "192.168.1.10")
+        else {
+            panic!("expected an alive notification");
+        };
+        assert_eq!(speaker.ip, "192.168.1.10");
+        assert_eq!(speaker.uuid, "RINCON_ABC12345678901400");
+        assert!(speaker.location.is_some());
+    }
+
+    #[test]
+    fn test_parse_ssdp_notify_byebye() {
+        let message = "NOTIFY * HTTP/1.1\r\n\
+HOST: 239.255.255.250:1900\r\n\
+NT: uuid:RINCON_ABC12345678901400\r\n\
+NTS: ssdp:byebye\r\n\
+USN: uuid:RINCON_ABC12345678901400\r\n\r\n";
+
+        assert!(matches!(
+            parse_ssdp_notify(message, "192.168.1.10"),
+            Some(SsdpNotification::ByeBye { uuid }) if uuid == "RINCON_ABC12345678901400"
+        ));
+    }
+
+    #[test]
+    fn test_parse_ssdp_notify_ignores_other_messages() {
+        let search = build_msearch_message(1);
+        assert!(parse_ssdp_notify(&search, "192.168.1.50").is_none());
+
+        let other_device = "NOTIFY * HTTP/1.1\r\n\
+NTS: ssdp:alive\r\n\
+LOCATION: http://192.168.1.20/description.xml\r\n\
+USN: uuid:2f402f80-da50-11e1-9b23-001788255acc\r\n\r\n";
+        assert!(parse_ssdp_notify(other_device, "192.168.1.20").is_none());
+    }
 }
@@ -0,0 +1,224 @@
+//! Subnet scan fallback discovery.
+//!
+//! Probes `http://<ip>:1400/xml/device_description.xml` on every address of
+//! the local subnets. This is slow and noisy next to SSDP and mDNS, so the
+//! coordinator only runs it when they find nothing, typically on guest Wi-Fi
+//! or mesh networks that drop multicast.
+//!
+//! Without configured ranges, the /24 around each interface address is
+//! scanned (the same assumption [`super::ssdp::get_interfaces`] makes for
+//! broadcast addresses).
+
+use futures::stream::{self, StreamExt};
+use reqwest::Client;
+use std::collections::BTreeSet;
+use std::net::Ipv4Addr;
+use std::time::Duration;
+
+use super::diagnostics::MethodReport;
+use super::parse_device_description;
+use super::ssdp::get_interfaces;
+use super::types::{DiscoveredSpeaker, DiscoveryError, DiscoveryMethod};
+
+/// Shortest prefix accepted for a scan range (at most 4094 hosts).
+pub const MIN_PREFIX_LEN: u8 = 20;
+
+/// Configuration for subnet scan discovery.
+#[derive(Debug, Clone)]
+pub struct SubnetScanConfig {
+    /// IPv4 ranges to scan in CIDR notation (e.g. `192.168.1.0/24`).
+    /// Empty scans the /24 of each local interface.
+    pub cidrs: Vec<String>,
+    /// Timeout for each probe, connecting included.
+    pub probe_timeout: Duration,
+    /// Maximum probes in flight.
+    pub max_concurrent: usize,
+}
+
+impl Default for SubnetScanConfig {
+    fn default() -> Self {
+        Self {
+            cidrs: Vec::new(),
+            probe_timeout: Duration::from_millis(800),
+            max_concurrent: 64,
+        }
+    }
+}
+
+/// Discovers Sonos speakers by probing every address of the scan ranges.
+///
+/// Addresses that answered, and those that turned out not to be speakers,
+/// are counted in `report`.
+///
+/// # Errors
+///
+/// `NoInterfaces` if there is nothing to scan: no configured range is valid
+/// and, without configured ranges, no interface has an IPv4 address.
+pub async fn discover_subnet(
+    config: &SubnetScanConfig,
+    report: &mut MethodReport,
+) -> Result<Vec<DiscoveredSpeaker>, DiscoveryError> {
+    let local_ips: Vec<Ipv4Addr> = get_interfaces().iter().map(|iface| iface.ip).collect();
+    let ranges: Vec<(Ipv4Addr, u8)> = if config.cidrs.is_empty() {
+        local_ips.iter().map(|ip| (network(*ip, 24), 24)).collect()
+    } else {
+        config
+            .cidrs
+            .iter()
+            .filter_map(|cidr| {
+                let range = parse_cidr(cidr);
+                if range.is_none() {
+                    log::warn!(
+                        "[SubnetScan] Ignoring invalid range {} (IPv4 CIDR, /{} or narrower)",
+                        cidr,
+                        MIN_PREFIX_LEN
+                    );
+                }
+                range
+            })
+            .collect()
+    };
+    if ranges.is_empty() {
+        return Err(DiscoveryError::NoInterfaces);
+    }
+
+    // Ranges may overlap; our own addresses can't be speakers
+    let targets: BTreeSet<Ipv4Addr> = ranges
+        .iter()
+        .flat_map(|(base, prefix)| hosts(*base, *prefix))
+        .filter(|ip| !local_ips.contains(ip))
+        .collect();
+    log::debug!(
+        "[SubnetScan] Probing {} address(es) in {} range(s)",
+        targets.len(),
+        ranges.len()
+    );
+
+    let client = Client::builder()
+        .timeout(config.probe_timeout)
+        .connect_timeout(config.probe_timeout)
+        .build()
+        .map_err(|e| DiscoveryError::SocketBind(std::io::Error::other(e)))?;
+
+    let probes: Vec<Probe> = stream::iter(targets)
+        .map(|ip| probe(&client, ip, DiscoveryMethod::SubnetScan))
+        .buffer_unordered(config.max_concurrent.max(1))
+        .collect()
+        .await;
+
+    let mut speakers = Vec::new();
+    for probe in probes {
+        match probe {
+            Probe::NoAnswer => {}
+            Probe::NotSonos => {
+                report.responses += 1;
+                report.dropped += 1;
+            }
+            Probe::Speaker(speaker) => {
+                report.responses += 1;
+                speakers.push(speaker);
+            }
+        }
+    }
+    Ok(speakers)
+}
+
+/// Outcome of probing one address.
+pub(super) enum Probe {
+    /// Nothing listening on port 1400, or no answer in time.
+    NoAnswer,
+    /// Something answered, but not with a Sonos device description.
+    NotSonos,
+    Speaker(DiscoveredSpeaker),
+}
+
+/// Fetches one address's device description, attributing a speaker found
+/// there to `method`.
+pub(super) async fn probe(client: &Client, ip: Ipv4Addr, method: DiscoveryMethod) -> Probe {
+    let url = format!("http://{}:1400/xml/device_description.xml", ip);
+    let Ok(response) = client.get(&url).send().await else {
+        return Probe::NoAnswer;
+    };
+    if !response.status().is_success() {
+        return Probe::NotSonos;
+    }
+    let Some(info) = response
+        .text()
+        .await
+        .ok()
+        .and_then(|body| parse_device_description(&body))
+    else {
+        return Probe::NotSonos;
+    };
+    log::debug!("[{}] Found {} at {}", method, info.friendly_name, ip);
+    Probe::Speaker(DiscoveredSpeaker::with_location(
+        ip.to_string(),
+        info.uuid,
+        url,
+        method,
+    ))
+}
+
+/// Parses an IPv4 CIDR range into its network address and prefix length.
+///
+/// Host bits are cleared (`192.168.1.77/24` is `192.168.1.0/24`). Ranges
+/// wider than [`MIN_PREFIX_LEN`] are rejected.
+pub fn parse_cidr(cidr: &str) -> Option<(Ipv4Addr, u8)> {
+    let (addr, prefix) = cidr.trim().split_once('/')?;
+    let addr: Ipv4Addr = addr.parse().ok()?;
+    let prefix: u8 = prefix.parse().ok()?;
+    if !(MIN_PREFIX_LEN..=32).contains(&prefix) {
+        return None;
+    }
+    Some((network(addr, prefix), prefix))
+}
+
+/// Clears the host bits of an address.
+fn network(addr: Ipv4Addr, prefix: u8) -> Ipv4Addr {
+    let mask = u32::MAX.checked_shl(32 - u32::from(prefix)).unwrap_or(0);
+    Ipv4Addr::from(u32::from(addr) & mask)
+}
+
+/// Returns the host addresses of a range, leaving out the network and
+/// broadcast addresses where the range has them.
+pub(super) fn hosts(network: Ipv4Addr, prefix: u8) -> impl Iterator<Item = Ipv4Addr> {
+    let first = u32::from(network);
+    let size = 1u64 << (32 - u32::from(prefix));
+    let (start, end) = if size > 2 {
+        (u64::from(first) + 1, u64::from(first) + size - 1)
+    } else {
+        (u64::from(first), u64::from(first) + size)
+    };
+    (start..end).map(|ip| Ipv4Addr::from(ip as u32))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_cidr_ranges() {
+        assert_eq!(
+            parse_cidr("192.168.1.77/24"),
+            Some((Ipv4Addr::new(192, 168, 1, 0), 24))
+        );
+        assert_eq!(
+            parse_cidr(" 10.0.20.5/32 "),
+            Some((Ipv4Addr::new(10, 0, 20, 5), 32))
+        );
+        assert_eq!(parse_cidr("10.0.0.0/8"), None);
+        assert_eq!(parse_cidr("10.0.0.0"), None);
+        assert_eq!(parse_cidr("fe80::/64"), None);
+    }
+
+    #[test]
+    fn hosts_skip_network_and_broadcast() {
+        let hosts: Vec<_> = hosts(Ipv4Addr::new(192, 168, 1, 0), 24).collect();
+        assert_eq!(hosts.len(), 254);
+        assert_eq!(hosts.first(), Some(&Ipv4Addr::new(192, 168, 1, 1)));
+        assert_eq!(hosts.last(), Some(&Ipv4Addr::new(192, 168, 1, 254)));
+
+        let single: Vec<_> = super::hosts(Ipv4Addr::new(10, 0, 20, 5), 32).collect();
+        assert_eq!(single, vec![Ipv4Addr::new(10, 0, 20, 5)]);
+    }
+}
@@ -1,14 +1,19 @@
 //! Shared types for Sonos speaker discovery.
 //!
-//! This module contains types used across all discovery methods (SSDP, mDNS)
+//! This module contains types used across all discovery methods (SSDP, mDNS,
+//! subnet scan, remote subnets)
 //! and the coordinator that merges their results.
 
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use std::collections::HashSet;
 use thiserror::Error;
 
+use crate::sonos::compat;
+use crate::sonos::types::DeviceRole;
+
 /// Discovery method identifier for tracking which methods found each speaker.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "camelCase")]
 pub enum DiscoveryMethod {
     /// SSDP multicast to 239.255.255.250:1900
     SsdpMulticast,
@@ -16,6 +21,10 @@ pub enum DiscoveryMethod {
     SsdpBroadcast,
     /// mDNS/Bonjour via _sonos._tcp.local.
     Mdns,
+    /// Device description probes across the local subnets (last resort)
+    SubnetScan,
+    /// Unicast M-SEARCH and description probes to configured remote subnets
+    RemoteSubnets,
 }
 
 impl std::fmt::Display for DiscoveryMethod {
@@ -24,6 +33,8 @@ impl std::fmt::Display for DiscoveryMethod {
             Self::SsdpMulticast => write!(f, "SSDP multicast"),
             Self::SsdpBroadcast => write!(f, "SSDP broadcast"),
             Self::Mdns => write!(f, "mDNS"),
+            Self::SubnetScan => write!(f, "subnet scan"),
+            Self::RemoteSubnets => write!(f, "remote subnets"),
         }
     }
 }
@@ -83,6 +94,10 @@ pub enum DiscoveryError {
     /// IP responds but is not a valid Sonos device.
     #[error("not a Sonos device: {0}")]
     NotSonosDevice(String),
+
+    /// Hostname has no usable IPv4 address in DNS.
+    #[error("hostname not resolved: {0}")]
+    HostUnresolved(String),
 }
 
 /// Convenient Result alias for speaker discovery operations.
@@ -100,24 +115,75 @@ pub struct Speaker {
     /// Model name (e.g., "Sonos Arc", "Sonos Boost").
     #[serde(rename = "modelName", skip_serializing_if = "Option::is_none")]
     pub model_name: Option<String>,
+    /// Device role guessed from the model name. Bonded satellites are only
+    /// identified by the topology (see [`ZoneGroupMember::role`]).
+    ///
+    /// [`ZoneGroupMember::role`]: crate::sonos::types::ZoneGroupMember::role
+    pub role: DeviceRole,
+    /// Internal software build (e.g., "79.1-56100").
+    #[serde(rename = "softwareVersion", skip_serializing_if = "Option::is_none")]
+    pub software_version: Option<String>,
+    /// Version shown in the Sonos app (e.g., "16.4").
+    #[serde(rename = "displayVersion", skip_serializing_if = "Option::is_none")]
+    pub display_version: Option<String>,
+    /// Hardware revision (e.g., "1.8.1.2-1.2").
+    #[serde(rename = "hardwareVersion", skip_serializing_if = "Option::is_none")]
+    pub hardware_version: Option<String>,
+    /// Software generation: 1 for S1, 2 for S2.
+    #[serde(rename = "swGen", skip_serializing_if = "Option::is_none")]
+    pub sw_gen: Option<u8>,
+    /// Household (`Sonos_...`) the speaker belongs to, shared by all speakers
+    /// of one Sonos system. Only known for speakers that answered SSDP.
+    #[serde(rename = "householdId", skip_serializing_if = "Option::is_none")]
+    pub household_id: Option<String>,
+    /// Limits applied to the speaker's streams, for S1 speakers.
+    #[serde(
+        rename = "compatibilityWarning",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub compatibility_warning: Option<&'static str>,
 }
 
-/// Known Sonos infrastructure device models that don't participate in zone groups.
-/// These are network bridges/extenders, not playable speakers.
-const INFRASTRUCTURE_MODELS: &[&str] = &["boost", "bridge"];
-
 impl Speaker {
+    /// Creates a speaker, classifying its role from the model name.
+    pub fn new(ip: String, uuid: String, name: String, model_name: Option<String>) -> Self {
+        let role = DeviceRole::from_model_name(model_name.as_deref());
+        Self {
+            ip,
+            name,
+            uuid,
+            model_name,
+            role,
+            software_version: None,
+            display_version: None,
+            hardware_version: None,
+            sw_gen: None,
+            household_id: None,
+            compatibility_warning: None,
+        }
+    }
+
+    /// Creates a speaker from its device description, keeping the firmware
+    /// details it reports.
+    pub fn from_description(ip: String, info: DeviceInfo) -> Self {
+        Self {
+            software_version: info.software_version,
+            display_version: info.display_version,
+            hardware_version: info.hardware_version,
+            sw_gen: info.sw_gen,
+            compatibility_warning: compat::warning_for(info.sw_gen),
+            ..Self::new(
+                ip,
+                normalize_uuid(&info.uuid),
+                info.friendly_name,
+                info.model_name,
+            )
+        }
+    }
+
     /// Returns true if this is a non-playable infrastructure device (Boost, Bridge).
     pub fn is_infrastructure_device(&self) -> bool {
-        self.model_name
-            .as_ref()
-            .map(|m| {
-                let model_lower = m.to_lowercase();
-                INFRASTRUCTURE_MODELS
-                    .iter()
-                    .any(|&infra| model_lower.contains(infra))
-            })
-            .unwrap_or(false)
+        self.role == DeviceRole::Bridge
     }
 }
 
@@ -136,6 +202,8 @@ pub struct DiscoveredSpeaker {
     pub candidate_ips: Vec<String>,
     /// Which discovery methods found this speaker.
     pub methods: HashSet<DiscoveryMethod>,
+    /// Household ID from the SSDP `X-RINCON-HOUSEHOLD` header.
+    pub household_id: Option<String>,
 }
 
 impl DiscoveredSpeaker {
@@ -149,6 +217,7 @@ impl DiscoveredSpeaker {
             uuid,
             location: None,
             methods,
+            household_id: None,
         }
     }
 
@@ -168,6 +237,7 @@ impl DiscoveredSpeaker {
     ///
     /// - Unions the methods set
     /// - Prefers SSDP LOCATION if present
+    /// - Keeps the household ID if either has one
     /// - Keeps all candidate IPs
     pub fn merge(&mut self, other: DiscoveredSpeaker) {
         // Union methods
@@ -177,6 +247,9 @@ impl DiscoveredSpeaker {
         if self.location.is_none() && other.location.is_some() {
             self.location = other.location;
         }
+        if self.household_id.is_none() {
+            self.household_id = other.household_id;
+        }
 
         // Add candidate IPs (avoid duplicates)
         for ip in other.candidate_ips {
@@ -260,14 +333,31 @@ pub fn normalize_uuid(raw: &str) -> String {
 }
 
 /// Device information fetched from device description XML.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct DeviceInfo {
     /// Canonical UUID from UDN field.
     pub uuid: String,
     /// Friendly name for display.
     pub friendly_name: String,
     /// Model name (e.g., "Sonos Arc").
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub model_name: Option<String>,
+    /// Model number (e.g., "S19").
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model_number: Option<String>,
+    /// Internal software build (e.g., "79.1-56100").
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub software_version: Option<String>,
+    /// User-facing software version shown in the Sonos app (e.g., "16.4").
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub display_version: Option<String>,
+    /// Hardware revision (e.g., "1.8.1.2-1.2").
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hardware_version: Option<String>,
+    /// Software generation: 1 for S1, 2 for S2.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sw_gen: Option<u8>,
 }
 
 /// Virtual interface prefixes to filter out during discovery.
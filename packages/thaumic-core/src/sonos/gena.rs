@@ -12,6 +12,8 @@ use thiserror::Error;
 use tokio::sync::mpsc;
 use tokio_util::sync::CancellationToken;
 
+use crate::clock::Clock;
+use crate::context::NetworkContext;
 use crate::protocol_constants::{
     GENA_EVENT_CHANNEL_CAPACITY, GENA_RENEWAL_BUFFER_SECS, GENA_RENEWAL_CHECK_SECS,
 };
@@ -20,7 +22,7 @@ use crate::runtime::TokioSpawner;
 use super::gena_client::GenaClient;
 use super::gena_store::GenaSubscriptionStore;
 use super::services::SonosService;
-use super::types::{TransportState, ZoneGroup};
+use super::types::{SpeakerMove, TransportState, ZoneGroup};
 
 /// Errors that can occur during GENA subscription operations.
 #[derive(Debug, Error)]
@@ -113,6 +115,10 @@ pub struct GenaSubscriptionManager {
     event_tx: mpsc::Sender<SonosEvent>,
     /// Token to signal background tasks to stop.
     cancel_token: CancellationToken,
+    /// Time source for expiry tracking and the renewal schedule.
+    clock: Arc<dyn Clock>,
+    /// Picks the callback address on each speaker's subnet, if set.
+    network: Option<NetworkContext>,
 }
 
 impl GenaSubscriptionManager {
@@ -124,17 +130,40 @@ impl GenaSubscriptionManager {
     ///
     /// # Arguments
     /// * `http_client` - The HTTP client to use for GENA requests
-    pub fn new(http_client: Client) -> (Self, mpsc::Receiver<SonosEvent>) {
+    /// * `clock` - Time source for expiry tracking and renewal checks
+    pub fn new(http_client: Client, clock: Arc<dyn Clock>) -> (Self, mpsc::Receiver<SonosEvent>) {
         let (event_tx, event_rx) = mpsc::channel(GENA_EVENT_CHANNEL_CAPACITY);
         let manager = Self {
-            store: GenaSubscriptionStore::new(),
+            store: GenaSubscriptionStore::with_clock(Arc::clone(&clock)),
             client: GenaClient::new(http_client),
             event_tx,
             cancel_token: CancellationToken::new(),
+            clock,
+            network: None,
         };
         (manager, event_rx)
     }
 
+    /// Gives speakers on one of the network's subnet addresses a callback
+    /// URL on that address instead of the one passed to [`subscribe`].
+    ///
+    /// [`subscribe`]: Self::subscribe
+    #[must_use]
+    pub fn with_network(mut self, network: NetworkContext) -> Self {
+        self.network = Some(network);
+        self
+    }
+
+    /// Returns the callback URL to give the speaker at `ip`.
+    fn callback_url_for(&self, ip: &str, default_url: String) -> String {
+        match &self.network {
+            Some(network) if network.speaker_facing_ip(ip).is_some() => {
+                network.gena_callback_url_for(ip)
+            }
+            _ => default_url,
+        }
+    }
+
     /// Checks if a subscription exists for the given IP and service.
     #[must_use]
     pub fn is_subscribed(&self, ip: &str, service: SonosService) -> bool {
@@ -174,6 +203,8 @@ impl GenaSubscriptionManager {
 
     /// Starts a background task to renew subscriptions before they expire.
     ///
+    /// How long before expiry each speaker is renewed adapts to how the
+    /// speaker behaves (see [`GenaSubscriptionStore::record_early_expiry`]).
     /// The task will stop gracefully when the cancellation token is triggered.
     ///
     /// # Arguments
@@ -181,14 +212,14 @@ impl GenaSubscriptionManager {
     pub fn start_renewal_task(self: Arc<Self>, spawner: &TokioSpawner) {
         let cancel_token = self.cancel_token.clone();
         spawner.spawn(async move {
-            let mut interval = tokio::time::interval(Duration::from_secs(GENA_RENEWAL_CHECK_SECS));
+            let check_interval = Duration::from_secs(GENA_RENEWAL_CHECK_SECS);
             loop {
                 tokio::select! {
                     _ = cancel_token.cancelled() => {
                         log::info!("[GENA] Renewal task shutting down");
                         break;
                     }
-                    _ = interval.tick() => {}
+                    _ = self.clock.sleep(check_interval) => {}
                 }
 
                 let to_renew = self.store.get_expiring(GENA_RENEWAL_BUFFER_SECS);
@@ -197,6 +228,7 @@ impl GenaSubscriptionManager {
                     match self.client.renew(&ip, service, &sid).await {
                         Ok(timeout_secs) => {
                             self.store.update_expiry(&sid, timeout_secs);
+                            self.store.record_renewal(&ip);
                             log::debug!(
                                 "[GENA] Renewed subscription {} for {} ({})",
                                 sid,
@@ -212,6 +244,23 @@ impl GenaSubscriptionManager {
                                 e
                             );
 
+                            // 412 Precondition Failed: the speaker no longer
+                            // knows the SID, so it expired before we renewed
+                            if matches!(e, GenaError::RenewalFailed(412)) {
+                                if let Some(margin) = self.store.record_early_expiry(
+                                    &sid,
+                                    Duration::from_secs(GENA_RENEWAL_BUFFER_SECS),
+                                    check_interval,
+                                ) {
+                                    log::info!(
+                                        "[GENA] {} subscription on {} expired early, renewing {}s before expiry",
+                                        service.name(),
+                                        ip,
+                                        margin.as_secs()
+                                    );
+                                }
+                            }
+
                             // Remove the failed subscription
                             self.store.remove(&sid);
 
@@ -245,6 +294,7 @@ impl GenaSubscriptionManager {
     ///
     /// If a subscription already exists or is in-flight for the (IP, service) pair,
     /// this returns immediately without creating a duplicate subscription.
+    /// `callback_url` is the default; see [`with_network`](Self::with_network).
     pub async fn subscribe(
         &self,
         ip: String,
@@ -262,6 +312,7 @@ impl GenaSubscriptionManager {
             return Ok(());
         }
 
+        let callback_url = self.callback_url_for(&ip, callback_url);
         match self.client.subscribe(&ip, service, &callback_url).await {
             Ok(response) => {
                 self.store.insert(
@@ -388,4 +439,16 @@ impl GenaSubscriptionManager {
     pub fn subscription_count(&self) -> usize {
         self.store.len()
     }
+
+    /// Moves the subscriptions of speakers that changed address to their new
+    /// IP. Returns the number of subscriptions moved.
+    pub fn rekey_speakers(&self, moves: &[SpeakerMove]) -> usize {
+        self.store.rekey_ips(moves)
+    }
+
+    /// Returns the number of internal events waiting in the event channel.
+    #[must_use]
+    pub fn queued_events(&self) -> usize {
+        self.event_tx.max_capacity() - self.event_tx.capacity()
+    }
 }
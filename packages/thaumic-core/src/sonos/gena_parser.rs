@@ -210,16 +210,27 @@ pub fn parse_zone_topology_events(body: &str) -> Vec<SonosEvent> {
 /// Checks if the current URI matches the expected stream URL.
 ///
 /// Handles various URI schemes used by Sonos (x-rincon-mp3radio://, aac://, etc.)
-/// by comparing just the host+path portion.
+/// by comparing just the host+path portion. The `.wav`/`.flac` extension is
+/// ignored too, since speakers with URI quirks play the stream without it.
 #[must_use]
 pub fn is_matching_stream_url(current_uri: &str, expected_uri: &str) -> bool {
     // Extract everything after the last "://" to get host+path
     // This handles nested schemes like "aac://http://host/path" -> "host/path"
     fn extract_host_path(url: &str) -> &str {
-        match url.rfind("://") {
+        let host_path = match url.rfind("://") {
             Some(idx) => &url[idx + 3..],
             None => url,
-        }
+        };
+        [".wav", ".flac"]
+            .iter()
+            .find_map(|ext| {
+                let split = host_path.len().checked_sub(ext.len())?;
+                let suffix = host_path.get(split..)?;
+                suffix
+                    .eq_ignore_ascii_case(ext)
+                    .then(|| &host_path[..split])
+            })
+            .unwrap_or(host_path)
     }
 
     extract_host_path(current_uri).eq_ignore_ascii_case(extract_host_path(expected_uri))
@@ -249,6 +260,18 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn test_is_matching_stream_url_ignores_extension() {
+        assert!(is_matching_stream_url(
+            "http://192.168.1.100:8080/stream/abc/live",
+            "http://192.168.1.100:8080/stream/abc/live.wav"
+        ));
+        assert!(!is_matching_stream_url(
+            "http://192.168.1.100:8080/stream/abc/live",
+            "http://192.168.1.100:8080/stream/abd/live.wav"
+        ));
+    }
+
     #[test]
     fn test_is_matching_stream_url_case_insensitive() {
         assert!(is_matching_stream_url(
@@ -1,13 +1,24 @@
 //! GENA subscription state management.
 //!
 //! Pure data structure for tracking active subscriptions without I/O operations.
+//!
+//! The store also learns how early each speaker should be renewed. Some
+//! speakers honor the granted timeout, others silently drop subscriptions
+//! before it runs out; a renewal that arrives after that is rejected and
+//! events are lost until we resubscribe. When a renewal finds a subscription
+//! already gone, the speaker's renewal margin is widened to cover how early
+//! it expired, then narrows back as renewals keep succeeding.
 
 use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
 use std::time::{Duration, Instant};
 
 use parking_lot::RwLock;
 
+use crate::clock::{Clock, SystemClock};
+
 use super::services::SonosService;
+use super::types::SpeakerMove;
 
 /// Internal subscription state (keyed by SID in the subscriptions HashMap).
 pub(crate) struct Subscription {
@@ -15,6 +26,19 @@ pub(crate) struct Subscription {
     pub service: SonosService,
     pub callback_url: String,
     pub expires_at: Instant,
+    /// When the speaker last granted (or renewed) the subscription.
+    pub granted_at: Instant,
+    /// Timeout the speaker granted.
+    pub timeout: Duration,
+}
+
+/// Learned renewal margin for one speaker.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct RenewalMargin {
+    /// Current margin before expiry at which to renew.
+    current: Duration,
+    /// Smallest margin that would have caught the earliest expiry seen.
+    floor: Duration,
 }
 
 /// A composite key for deduplicating subscriptions (IP + service).
@@ -45,15 +69,27 @@ pub struct GenaSubscriptionStore {
     subscription_keys: RwLock<HashMap<SubscriptionKey, String>>,
     /// In-flight subscription requests (prevents TOCTOU race in subscribe).
     pending_subscriptions: RwLock<HashSet<SubscriptionKey>>,
+    /// Renewal margins learned per speaker IP. Speakers without an entry
+    /// use the default margin.
+    renewal_margins: RwLock<HashMap<String, RenewalMargin>>,
+    /// Time source for grant and expiry times.
+    clock: Arc<dyn Clock>,
 }
 
 impl GenaSubscriptionStore {
-    /// Creates a new empty subscription store.
+    /// Creates a new empty subscription store on the system clock.
     pub fn new() -> Self {
+        Self::with_clock(SystemClock::arc())
+    }
+
+    /// Creates a new empty subscription store timed by `clock`.
+    pub fn with_clock(clock: Arc<dyn Clock>) -> Self {
         Self {
             subscriptions: RwLock::new(HashMap::new()),
             subscription_keys: RwLock::new(HashMap::new()),
             pending_subscriptions: RwLock::new(HashSet::new()),
+            renewal_margins: RwLock::new(HashMap::new()),
+            clock,
         }
     }
 
@@ -131,6 +167,8 @@ impl GenaSubscriptionStore {
         timeout_secs: u64,
     ) {
         let key = SubscriptionKey::new(&ip, service);
+        let now = self.clock.now();
+        let timeout = Duration::from_secs(timeout_secs);
 
         self.subscriptions.write().insert(
             sid.clone(),
@@ -138,7 +176,9 @@ impl GenaSubscriptionStore {
                 ip,
                 service,
                 callback_url,
-                expires_at: Instant::now() + Duration::from_secs(timeout_secs),
+                expires_at: now + timeout,
+                granted_at: now,
+                timeout,
             },
         );
         self.subscription_keys.write().insert(key.clone(), sid);
@@ -169,25 +209,39 @@ impl GenaSubscriptionStore {
     /// Updates the expiration time for a subscription.
     pub fn update_expiry(&self, sid: &str, timeout_secs: u64) {
         if let Some(sub) = self.subscriptions.write().get_mut(sid) {
-            sub.expires_at = Instant::now() + Duration::from_secs(timeout_secs);
+            let now = self.clock.now();
+            sub.timeout = Duration::from_secs(timeout_secs);
+            sub.granted_at = now;
+            sub.expires_at = now + sub.timeout;
         }
     }
 
-    /// Returns subscriptions that need renewal (expiring within buffer time).
+    /// Returns subscriptions that need renewal (expiring within their
+    /// speaker's renewal margin).
+    ///
+    /// The margin is never more than half the granted timeout, so speakers
+    /// granting short timeouts aren't renewed on every check.
     ///
     /// # Arguments
-    /// * `buffer_secs` - Subscriptions expiring within this many seconds are returned
+    /// * `buffer_secs` - Margin for speakers without a learned one
     ///
     /// # Returns
     /// A vector of (sid, ip, service, callback_url) tuples for subscriptions needing renewal.
     pub fn get_expiring(&self, buffer_secs: u64) -> Vec<(String, String, SonosService, String)> {
-        let now = Instant::now();
-        let buffer = Duration::from_secs(buffer_secs);
+        let now = self.clock.now();
+        let default = Duration::from_secs(buffer_secs);
+        let margins = self.renewal_margins.read();
 
         self.subscriptions
             .read()
             .iter()
-            .filter(|(_, sub)| sub.expires_at.saturating_duration_since(now) < buffer)
+            .filter(|(_, sub)| {
+                let margin = margins
+                    .get(&sub.ip)
+                    .map_or(default, |m| m.current)
+                    .min(sub.timeout / 2);
+                sub.expires_at.saturating_duration_since(now) < margin
+            })
             .map(|(sid, sub)| {
                 (
                     sid.clone(),
@@ -199,6 +253,125 @@ impl GenaSubscriptionStore {
             .collect()
     }
 
+    /// Returns the renewal margin used for a speaker, given the default.
+    #[must_use]
+    pub fn renewal_margin(&self, ip: &str, default: Duration) -> Duration {
+        self.renewal_margins
+            .read()
+            .get(ip)
+            .map_or(default, |m| m.current)
+    }
+
+    /// Records that a renewal found the subscription already expired.
+    ///
+    /// The subscription expired somewhere between its last grant and now,
+    /// up to `check_interval` before we noticed, so its speaker's margin is
+    /// widened to cover the unused part of the timeout plus one check
+    /// interval, and at least doubled. A subscription lost with more than
+    /// half its timeout unused is treated as a reboot or network outage and
+    /// leaves the margin alone.
+    ///
+    /// Returns the new margin if it changed.
+    pub fn record_early_expiry(
+        &self,
+        sid: &str,
+        default: Duration,
+        check_interval: Duration,
+    ) -> Option<Duration> {
+        let now = self.clock.now();
+        let (ip, unused) = {
+            let subscriptions = self.subscriptions.read();
+            let sub = subscriptions.get(sid)?;
+            let held = now.saturating_duration_since(sub.granted_at);
+            let unused = sub.timeout.saturating_sub(held);
+            if unused > sub.timeout / 2 {
+                return None;
+            }
+            (sub.ip.clone(), unused)
+        };
+
+        let mut margins = self.renewal_margins.write();
+        let margin = margins.entry(ip).or_insert(RenewalMargin {
+            current: default,
+            floor: default,
+        });
+        let needed = unused + check_interval;
+        margin.floor = margin.floor.max(needed);
+        margin.current = (margin.current * 2).max(margin.floor);
+        Some(margin.current)
+    }
+
+    /// Records a successful renewal, narrowing a widened margin a quarter of
+    /// the way back toward the smallest margin known to work for the speaker.
+    pub fn record_renewal(&self, ip: &str) {
+        let mut margins = self.renewal_margins.write();
+        if let Some(margin) = margins.get_mut(ip) {
+            let excess = margin.current.saturating_sub(margin.floor);
+            margin.current -= excess / 4;
+        }
+    }
+
+    /// Moves the subscriptions of speakers that changed address to their
+    /// new IP, so renewals reach them there. SIDs belong to the speaker, not
+    /// its address, and stay valid.
+    ///
+    /// A subscription whose service is already subscribed at the new address
+    /// stays at the old one, where its next renewal fails.
+    ///
+    /// Returns the number of subscriptions moved.
+    pub fn rekey_ips(&self, moves: &[SpeakerMove]) -> usize {
+        let mut subscriptions = self.subscriptions.write();
+        let mut keys = self.subscription_keys.write();
+
+        // Take every moving key out first, so speakers swapping addresses
+        // don't collide with each other
+        let moving: Vec<(SubscriptionKey, &str)> = moves
+            .iter()
+            .flat_map(|m| {
+                keys.keys()
+                    .filter(|key| key.ip == m.old_ip)
+                    .map(|key| (key.clone(), m.new_ip.as_str()))
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+        let taken: Vec<(SubscriptionKey, String, &str)> = moving
+            .into_iter()
+            .filter_map(|(key, new_ip)| {
+                let sid = keys.remove(&key)?;
+                Some((key, sid, new_ip))
+            })
+            .collect();
+
+        let mut moved = 0;
+        for (old_key, sid, new_ip) in taken {
+            let new_key = SubscriptionKey::new(new_ip, old_key.service);
+            if keys.contains_key(&new_key) {
+                keys.insert(old_key, sid);
+                continue;
+            }
+            if let Some(sub) = subscriptions.get_mut(&sid) {
+                sub.ip = new_ip.to_string();
+            }
+            keys.insert(new_key, sid);
+            moved += 1;
+        }
+        drop(keys);
+        drop(subscriptions);
+
+        let mut margins = self.renewal_margins.write();
+        let margins_moved: Vec<_> = moves
+            .iter()
+            .filter_map(|m| {
+                margins
+                    .remove(&m.old_ip)
+                    .map(|margin| (m.new_ip.clone(), margin))
+            })
+            .collect();
+        margins.extend(margins_moved);
+
+        moved
+    }
+
     /// Gets all SIDs for a specific IP.
     pub fn get_sids_by_ip(&self, ip: &str) -> Vec<String> {
         self.subscriptions
@@ -243,6 +416,7 @@ impl Default for GenaSubscriptionStore {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::clock::MockClock;
 
     #[test]
     fn new_store_is_empty() {
@@ -414,4 +588,151 @@ mod tests {
         assert!(sids.contains(&"uuid:1".to_string()));
         assert!(sids.contains(&"uuid:2".to_string()));
     }
+
+    /// Returns a store on a mock clock, with the clock to advance it.
+    fn mock_store() -> (GenaSubscriptionStore, Arc<MockClock>) {
+        let clock = Arc::new(MockClock::new());
+        let store = GenaSubscriptionStore::with_clock(Arc::clone(&clock) as Arc<dyn Clock>);
+        (store, clock)
+    }
+
+    /// Inserts a subscription for the test speaker with the given timeout.
+    fn insert_for(store: &GenaSubscriptionStore, sid: &str, timeout_secs: u64) {
+        store.insert(
+            sid.to_string(),
+            "192.168.1.100".to_string(),
+            SonosService::AVTransport,
+            "http://callback".to_string(),
+            timeout_secs,
+        );
+    }
+
+    const DEFAULT: Duration = Duration::from_secs(300);
+    const CHECK: Duration = Duration::from_secs(60);
+
+    #[test]
+    fn early_expiry_widens_margin_then_narrows_to_floor() {
+        let (store, clock) = mock_store();
+        // Granted 1800s, rejected at 1500s: expired at least 300s early
+        insert_for(&store, "uuid:1", 1800);
+        clock.advance(Duration::from_secs(1500));
+
+        let margin = store.record_early_expiry("uuid:1", DEFAULT, CHECK).unwrap();
+        assert_eq!(margin, Duration::from_secs(600));
+        assert_eq!(store.renewal_margin("192.168.1.100", DEFAULT), margin);
+        assert_eq!(store.renewal_margin("192.168.1.101", DEFAULT), DEFAULT);
+
+        for _ in 0..50 {
+            store.record_renewal("192.168.1.100");
+        }
+        let narrowed = store.renewal_margin("192.168.1.100", DEFAULT);
+        assert!(narrowed > Duration::from_secs(359));
+        assert!(narrowed < Duration::from_secs(361));
+    }
+
+    #[test]
+    fn loss_early_in_the_timeout_leaves_margin_alone() {
+        let (store, clock) = mock_store();
+        // Rejected 10 minutes into an hour: the speaker rebooted
+        insert_for(&store, "uuid:1", 3600);
+        clock.advance(Duration::from_secs(600));
+
+        assert!(store
+            .record_early_expiry("uuid:1", DEFAULT, CHECK)
+            .is_none());
+        assert_eq!(store.renewal_margin("192.168.1.100", DEFAULT), DEFAULT);
+    }
+
+    #[test]
+    fn subscription_expires_into_margin_until_renewed() {
+        let (store, clock) = mock_store();
+        insert_for(&store, "uuid:1", 1800);
+
+        clock.advance(Duration::from_secs(1499));
+        assert!(store.get_expiring(300).is_empty());
+        clock.advance(Duration::from_secs(2));
+        assert_eq!(store.get_expiring(300).len(), 1);
+
+        store.update_expiry("uuid:1", 1800);
+        assert!(store.get_expiring(300).is_empty());
+    }
+
+    #[test]
+    fn get_expiring_uses_learned_margin_capped_at_half_timeout() {
+        let (store, clock) = mock_store();
+        // 1140s left of 1800s: outside the default margin
+        insert_for(&store, "uuid:1", 1800);
+        clock.advance(Duration::from_secs(660));
+        assert!(store.get_expiring(300).is_empty());
+
+        store.renewal_margins.write().insert(
+            "192.168.1.100".to_string(),
+            RenewalMargin {
+                current: Duration::from_secs(1200),
+                floor: Duration::from_secs(1200),
+            },
+        );
+        // Capped at 900s, still outside
+        assert!(store.get_expiring(300).is_empty());
+
+        // Short grant: 100s left of 240s renews at 120s, not the default 300s,
+        // while uuid:1 with 1000s left is still outside its capped margin
+        insert_for(&store, "uuid:2", 240);
+        clock.advance(Duration::from_secs(140));
+        let sids: Vec<_> = store
+            .get_expiring(300)
+            .into_iter()
+            .map(|(sid, ..)| sid)
+            .collect();
+        assert_eq!(sids, ["uuid:2"]);
+    }
+
+    #[test]
+    fn rekey_ips_moves_subscriptions_to_new_address() {
+        let store = GenaSubscriptionStore::new();
+        for (sid, service) in [
+            ("uuid:av", SonosService::AVTransport),
+            ("uuid:grc", SonosService::GroupRenderingControl),
+        ] {
+            store.insert(
+                sid.to_string(),
+                "192.168.1.100".to_string(),
+                service,
+                "http://callback".to_string(),
+                300,
+            );
+        }
+        // Already subscribed at the new address; the old one stays put
+        store.insert(
+            "uuid:grc-new".to_string(),
+            "192.168.1.200".to_string(),
+            SonosService::GroupRenderingControl,
+            "http://callback".to_string(),
+            300,
+        );
+
+        let moved = store.rekey_ips(&[SpeakerMove {
+            uuid: "RINCON_A".to_string(),
+            old_ip: "192.168.1.100".to_string(),
+            new_ip: "192.168.1.200".to_string(),
+        }]);
+
+        assert_eq!(moved, 1);
+        assert_eq!(
+            store.get("uuid:av"),
+            Some(("192.168.1.200".to_string(), SonosService::AVTransport))
+        );
+        assert_eq!(
+            store.get_sid_by_ip_and_service("192.168.1.200", SonosService::AVTransport),
+            Some("uuid:av".to_string())
+        );
+        assert_eq!(
+            store.get_sid_by_ip_and_service("192.168.1.200", SonosService::GroupRenderingControl),
+            Some("uuid:grc-new".to_string())
+        );
+        assert_eq!(
+            store.get_sids_by_ip("192.168.1.100"),
+            vec!["uuid:grc".to_string()]
+        );
+    }
 }
@@ -3,12 +3,10 @@
 //! Handles joining speakers to coordinators for synchronized playback
 //! and unjoining them back to standalone mode.
 
-use reqwest::Client;
-
 use crate::error::SoapResult;
 use crate::sonos::retry::with_retry;
 use crate::sonos::services::SonosService;
-use crate::sonos::soap::soap_request;
+use crate::sonos::soap::{soap_request, SoapClient};
 
 /// Joins a speaker to a coordinator for synchronized playback.
 ///
@@ -25,7 +23,7 @@ use crate::sonos::soap::soap_request;
 /// # Note
 /// This creates a temporary group for streaming purposes and does not modify
 /// the user's permanent Sonos group configuration.
-pub async fn join_group(client: &Client, ip: &str, coordinator_uuid: &str) -> SoapResult<()> {
+pub async fn join_group(client: &SoapClient, ip: &str, coordinator_uuid: &str) -> SoapResult<()> {
     let group_uri = format!("x-rincon:{}", coordinator_uuid);
 
     log::info!(
@@ -80,7 +78,7 @@ pub async fn join_group(client: &Client, ip: &str, coordinator_uuid: &str) -> So
 /// # Note
 /// This is safe to call on speakers that are already standalone - the
 /// action is idempotent.
-pub async fn leave_group(client: &Client, ip: &str) -> SoapResult<()> {
+pub async fn leave_group(client: &SoapClient, ip: &str) -> SoapResult<()> {
     log::info!("[Sonos] Speaker {} leaving group (becoming standalone)", ip);
 
     soap_request(
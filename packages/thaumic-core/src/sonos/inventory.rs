@@ -0,0 +1,378 @@
+//! Speaker firmware inventory and known-issue detection.
+//!
+//! Collects each speaker's model and software version from its device
+//! description, filling gaps from the `/status/zp` support page (which
+//! also reports the hardware series). Speakers are then checked against a
+//! table of firmware with known streaming issues, and against the newest
+//! software seen in the household: Sonos expects every player to run the
+//! same version, and a player left behind after an update is a common cause
+//! of grouping and playback failures.
+
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+
+use futures::future::join_all;
+use reqwest::Client;
+use serde::Serialize;
+
+use crate::error::Remediation;
+use crate::sonos::discovery::{normalize_uuid, parse_device_description, DeviceInfo};
+use crate::sonos::types::ZoneGroupMember;
+use crate::sonos::utils::{build_sonos_url, extract_xml_text};
+
+/// Firmware details of one speaker.
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SpeakerFirmware {
+    pub ip: String,
+    pub uuid: String,
+    /// Room name.
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model_number: Option<String>,
+    /// Hardware series (e.g., "A101"), from `/status/zp`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub series_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hardware_version: Option<String>,
+    /// Internal software build (e.g., "79.1-56100").
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub software_version: Option<String>,
+    /// Version shown in the Sonos app (e.g., "16.4").
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub display_version: Option<String>,
+    /// Software generation: 1 for S1, 2 for S2.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sw_gen: Option<u8>,
+    /// Whether the speaker answered at all.
+    pub reachable: bool,
+    /// Known issues affecting this speaker.
+    pub issues: Vec<FirmwareIssue>,
+}
+
+/// A firmware problem detected on a speaker.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FirmwareIssue {
+    /// Stable identifier (e.g., `"update_available"`).
+    pub id: &'static str,
+    /// Human-readable description of the problem.
+    pub summary: &'static str,
+    pub remediation: Remediation,
+}
+
+/// Firmware inventory of the household.
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FirmwareInventory {
+    pub speakers: Vec<SpeakerFirmware>,
+    /// Newest software build found on any speaker.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub latest_software_version: Option<String>,
+    /// Number of speakers with at least one issue.
+    pub flagged: usize,
+}
+
+/// Firmware with a known issue, matched by predicate.
+struct KnownIssue {
+    issue: FirmwareIssue,
+    applies: fn(&SpeakerFirmware) -> bool,
+}
+
+/// Firmware known to misbehave with our streams.
+const KNOWN_ISSUES: &[KnownIssue] = &[KnownIssue {
+    issue: FirmwareIssue {
+        id: "s1_no_hires_flac",
+        summary: "S1 software cannot play 24-bit FLAC streams",
+        remediation: Remediation::new(
+            "use_16_bit",
+            "Stream to this speaker at 16-bit, or move it to the S2 app if supported",
+            false,
+        ),
+    },
+    applies: |speaker| speaker.sw_gen == Some(1),
+}];
+
+const UPDATE_AVAILABLE: FirmwareIssue = FirmwareIssue {
+    id: "update_available",
+    summary: "Runs older software than other speakers in the household",
+    remediation: Remediation::new(
+        "update_speaker",
+        "Update the speaker from the Sonos app (Settings > System > System Updates)",
+        false,
+    ),
+};
+
+/// Support information from the `/status/zp` page.
+#[derive(Debug, Default, PartialEq)]
+struct StatusInfo {
+    software_version: Option<String>,
+    hardware_version: Option<String>,
+    series_id: Option<String>,
+    sw_gen: Option<u8>,
+}
+
+/// Collects the firmware inventory of the given speakers.
+///
+/// Speakers are queried in parallel. Members sharing a UUID (reported by
+/// more than one group) are only queried once.
+pub async fn collect_inventory(client: &Client, members: &[ZoneGroupMember]) -> FirmwareInventory {
+    let mut seen = HashSet::new();
+    let members: Vec<&ZoneGroupMember> = members
+        .iter()
+        .filter(|member| seen.insert(normalize_uuid(&member.uuid)))
+        .collect();
+
+    let speakers = join_all(members.into_iter().map(|member| async move {
+        let (description, status) = tokio::join!(
+            fetch_description(client, &member.ip),
+            fetch_status(client, &member.ip)
+        );
+        speaker_firmware(member, description, status)
+    }))
+    .await;
+
+    let inventory = build_inventory(speakers);
+    for speaker in inventory.speakers.iter().filter(|s| !s.issues.is_empty()) {
+        let ids: Vec<&str> = speaker.issues.iter().map(|issue| issue.id).collect();
+        log::warn!(
+            "[Inventory] {} ({}) on {}: {}",
+            speaker.name,
+            speaker.ip,
+            speaker
+                .software_version
+                .as_deref()
+                .unwrap_or("unknown software"),
+            ids.join(", ")
+        );
+    }
+    inventory
+}
+
+async fn fetch_description(client: &Client, ip: &str) -> Option<DeviceInfo> {
+    let url = build_sonos_url(ip, "/xml/device_description.xml");
+    let body = client.get(url).send().await.ok()?.text().await.ok()?;
+    parse_device_description(&body)
+}
+
+async fn fetch_status(client: &Client, ip: &str) -> Option<StatusInfo> {
+    let response = client
+        .get(build_sonos_url(ip, "/status/zp"))
+        .send()
+        .await
+        .ok()?;
+    // Recent firmware disables the support pages
+    if !response.status().is_success() {
+        return None;
+    }
+    Some(parse_status_zp(&response.text().await.ok()?))
+}
+
+/// Parses the `ZPSupportInfo` document served at `/status/zp`.
+fn parse_status_zp(xml: &str) -> StatusInfo {
+    let text = |name| extract_xml_text(xml, name).filter(|value| !value.is_empty());
+    StatusInfo {
+        software_version: text("SoftwareVersion"),
+        hardware_version: text("HardwareVersion"),
+        series_id: text("SeriesID"),
+        sw_gen: text("SWGen").and_then(|value| value.trim().parse().ok()),
+    }
+}
+
+/// Merges what a speaker reported, preferring its device description.
+fn speaker_firmware(
+    member: &ZoneGroupMember,
+    description: Option<DeviceInfo>,
+    status: Option<StatusInfo>,
+) -> SpeakerFirmware {
+    let reachable = description.is_some() || status.is_some();
+    let description = description.unwrap_or_default();
+    let status = status.unwrap_or_default();
+
+    SpeakerFirmware {
+        ip: member.ip.clone(),
+        uuid: member.uuid.clone(),
+        name: member.zone_name.clone(),
+        model_name: description.model_name,
+        model_number: description.model_number,
+        series_id: status.series_id,
+        hardware_version: description.hardware_version.or(status.hardware_version),
+        software_version: description.software_version.or(status.software_version),
+        display_version: description.display_version,
+        sw_gen: description.sw_gen.or(status.sw_gen),
+        reachable,
+        issues: Vec::new(),
+    }
+}
+
+/// Flags known issues and speakers behind the household's newest software.
+fn build_inventory(mut speakers: Vec<SpeakerFirmware>) -> FirmwareInventory {
+    // Newest build per software generation: S1 speakers no longer receive
+    // S2 updates, so they are only compared with each other
+    let mut latest_by_gen: HashMap<Option<u8>, String> = HashMap::new();
+    for speaker in &speakers {
+        if let Some(version) = &speaker.software_version {
+            let latest = latest_by_gen
+                .entry(speaker.sw_gen)
+                .or_insert_with(|| version.clone());
+            if compare_versions(version, latest).is_gt() {
+                *latest = version.clone();
+            }
+        }
+    }
+
+    for speaker in &mut speakers {
+        let mut issues: Vec<FirmwareIssue> = KNOWN_ISSUES
+            .iter()
+            .filter(|known| (known.applies)(speaker))
+            .map(|known| known.issue.clone())
+            .collect();
+
+        let latest = latest_by_gen.get(&speaker.sw_gen);
+        if let (Some(version), Some(latest)) = (&speaker.software_version, latest) {
+            if compare_versions(version, latest).is_lt() {
+                issues.push(UPDATE_AVAILABLE);
+            }
+        }
+        speaker.issues = issues;
+    }
+
+    let latest_software_version = latest_by_gen
+        .into_values()
+        .max_by(|a, b| compare_versions(a, b));
+    let flagged = speakers.iter().filter(|s| !s.issues.is_empty()).count();
+    FirmwareInventory {
+        speakers,
+        latest_software_version,
+        flagged,
+    }
+}
+
+/// Compares Sonos software versions (e.g., "79.1-56100") numerically.
+///
+/// Versions are compared component by component, treating `.` and `-` as
+/// separators. Non-numeric components compare as zero.
+pub fn compare_versions(a: &str, b: &str) -> Ordering {
+    fn components(version: &str) -> Vec<u64> {
+        version
+            .split(['.', '-'])
+            .map(|part| part.trim().parse().unwrap_or(0))
+            .collect()
+    }
+
+    let (a, b) = (components(a), components(b));
+    let len = a.len().max(b.len());
+    (0..len)
+        .map(|i| {
+            let x = a.get(i).copied().unwrap_or(0);
+            let y = b.get(i).copied().unwrap_or(0);
+            x.cmp(&y)
+        })
+        .find(|ordering| ordering.is_ne())
+        .unwrap_or(Ordering::Equal)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn speaker(ip: &str, version: &str, sw_gen: u8) -> SpeakerFirmware {
+        SpeakerFirmware {
+            ip: ip.to_string(),
+            software_version: Some(version.to_string()),
+            sw_gen: Some(sw_gen),
+            reachable: true,
+            ..Default::default()
+        }
+    }
+
+    fn issue_ids(speaker: &SpeakerFirmware) -> Vec<&'static str> {
+        speaker.issues.iter().map(|issue| issue.id).collect()
+    }
+
+    #[test]
+    fn compares_versions_numerically() {
+        assert_eq!(
+            compare_versions("79.1-56100", "79.1-56100"),
+            Ordering::Equal
+        );
+        assert_eq!(compare_versions("79.1-9000", "79.1-56100"), Ordering::Less);
+        assert_eq!(
+            compare_versions("80.0-100", "79.1-56100"),
+            Ordering::Greater
+        );
+        assert_eq!(compare_versions("79.1", "79.1-0"), Ordering::Equal);
+    }
+
+    #[test]
+    fn parses_status_zp() {
+        let xml = r#"<?xml version="1.0" ?>
+<ZPSupportInfo><ZPInfo>
+  <ZoneName>Kitchen</ZoneName>
+  <SoftwareVersion>57.3-77280</SoftwareVersion>
+  <SWGen>1</SWGen>
+  <HardwareVersion>1.8.3.7-2.0</HardwareVersion>
+  <SeriesID>A100</SeriesID>
+  <ExtraInfo></ExtraInfo>
+</ZPInfo></ZPSupportInfo>"#;
+
+        assert_eq!(
+            parse_status_zp(xml),
+            StatusInfo {
+                software_version: Some("57.3-77280".to_string()),
+                hardware_version: Some("1.8.3.7-2.0".to_string()),
+                series_id: Some("A100".to_string()),
+                sw_gen: Some(1),
+            }
+        );
+    }
+
+    #[test]
+    fn flags_outdated_and_s1_speakers() {
+        let inventory = build_inventory(vec![
+            speaker("192.168.1.20", "79.1-56100", 2),
+            speaker("192.168.1.21", "78.2-52020", 2),
+            speaker("192.168.1.22", "57.3-77280", 1),
+        ]);
+
+        assert_eq!(
+            inventory.latest_software_version.as_deref(),
+            Some("79.1-56100")
+        );
+        assert!(issue_ids(&inventory.speakers[0]).is_empty());
+        assert_eq!(issue_ids(&inventory.speakers[1]), vec!["update_available"]);
+        // The only S1 speaker is current for its generation
+        assert_eq!(issue_ids(&inventory.speakers[2]), vec!["s1_no_hires_flac"]);
+        assert_eq!(inventory.flagged, 2);
+    }
+
+    #[test]
+    fn description_takes_precedence_over_status() {
+        let member = ZoneGroupMember {
+            uuid: "RINCON_A".to_string(),
+            ip: "192.168.1.20".to_string(),
+            zone_name: "Kitchen".to_string(),
+            model: "one".to_string(),
+            ..Default::default()
+        };
+        let description = DeviceInfo {
+            software_version: Some("79.1-56100".to_string()),
+            ..Default::default()
+        };
+        let status = StatusInfo {
+            software_version: Some("79.0-1".to_string()),
+            series_id: Some("A101".to_string()),
+            sw_gen: Some(2),
+            ..Default::default()
+        };
+
+        let firmware = speaker_firmware(&member, Some(description), Some(status));
+        assert_eq!(firmware.software_version.as_deref(), Some("79.1-56100"));
+        assert_eq!(firmware.series_id.as_deref(), Some("A101"));
+        assert_eq!(firmware.sw_gen, Some(2));
+        assert!(firmware.reachable);
+        assert!(!speaker_firmware(&member, None, None).reachable);
+    }
+}
@@ -12,6 +12,7 @@
 //! - `zone_groups` - Zone group topology parsing and retrieval
 //! - `didl` - DIDL-Lite metadata formatting for Sonos display
 //! - `playback` - Play, stop, and transport control commands
+//! - `uri_variants` - Alternative stream URI formats and per-speaker quirks
 //! - `volume` - Group and per-speaker volume/mute control
 //! - `grouping` - Group join/leave coordination
 //! - `discovery` - Multi-method speaker discovery (SSDP multicast/broadcast + mDNS)
@@ -19,10 +20,17 @@
 //! - `gena_client` - GENA HTTP operations
 //! - `gena_store` - GENA subscription state management
 //! - `gena_parser` - GENA notification parsing and event construction
+//! - `compat` - Sonos S1 detection and format limits
+//! - `bandwidth` - On-demand server-to-speaker throughput estimation
+//! - `inventory` - Firmware inventory and known-issue detection
+//! - `network_status` - Wired/Wi-Fi connection and signal strength
+//! - `reachability` - Speaker-to-server reachability check
 //! - `soap` - Low-level SOAP protocol implementation
 //! - `utils` - Shared utility functions
 
+pub mod bandwidth;
 pub mod client;
+pub mod compat;
 pub(crate) mod didl;
 pub mod discovery;
 pub mod gena;
@@ -30,13 +38,17 @@ pub mod gena_client;
 pub mod gena_parser;
 pub mod gena_store;
 pub(crate) mod grouping;
+pub mod inventory;
+pub mod network_status;
 pub(crate) mod playback;
+pub mod reachability;
 pub(crate) mod retry;
 pub mod services;
 pub mod soap;
 pub mod subscription_arbiter;
 pub mod traits;
 pub mod types;
+pub mod uri_variants;
 pub mod utils;
 pub(crate) mod volume;
 pub(crate) mod zone_groups;
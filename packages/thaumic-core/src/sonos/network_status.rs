@@ -0,0 +1,126 @@
+//! Speaker network connection status.
+//!
+//! Whether a speaker is wired comes from the zone group topology
+//! (`EthLink`/`WifiEnabled`). Wi-Fi signal strength is read from the
+//! `/status/proc/ath_rincon/status` support page, as the strongest RSSI the
+//! radio reports towards the access point or its SonosNet peers. Recent
+//! firmware disables the support pages, so speakers running it report no
+//! signal at all.
+
+use std::collections::HashSet;
+
+use futures::future::join_all;
+use reqwest::Client;
+use serde::Serialize;
+
+use crate::sonos::discovery::normalize_uuid;
+use crate::sonos::types::ZoneGroupMember;
+use crate::sonos::utils::build_sonos_url;
+
+/// RSSI below which a Wi-Fi link is flagged as weak.
+///
+/// The radio reports dB above the noise floor; Sonos support considers
+/// links under about 30 unreliable for streaming.
+pub const WEAK_SIGNAL_RSSI: u8 = 30;
+
+/// Network connection of one speaker.
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SpeakerNetwork {
+    pub ip: String,
+    pub uuid: String,
+    /// Room name.
+    pub name: String,
+    /// Whether the speaker is on Ethernet, if its firmware reports it.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub wired: Option<bool>,
+    /// Strongest Wi-Fi RSSI (dB above the noise floor), if reported.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signal_strength: Option<u8>,
+    /// Whether the signal is below [`WEAK_SIGNAL_RSSI`].
+    pub weak_signal: bool,
+}
+
+/// Collects the network status of the given speakers.
+///
+/// Speakers are queried in parallel, skipping wired ones. Members sharing a
+/// UUID (reported by more than one group) are only queried once.
+pub async fn collect_network_status(
+    client: &Client,
+    members: &[ZoneGroupMember],
+) -> Vec<SpeakerNetwork> {
+    let mut seen = HashSet::new();
+    let members: Vec<&ZoneGroupMember> = members
+        .iter()
+        .filter(|member| seen.insert(normalize_uuid(&member.uuid)))
+        .collect();
+
+    join_all(members.into_iter().map(|member| async move {
+        let signal_strength = if member.wired == Some(true) {
+            None
+        } else {
+            fetch_signal_strength(client, &member.ip).await
+        };
+        SpeakerNetwork {
+            ip: member.ip.clone(),
+            uuid: member.uuid.clone(),
+            name: member.zone_name.clone(),
+            wired: member.wired,
+            signal_strength,
+            weak_signal: signal_strength.is_some_and(|rssi| rssi < WEAK_SIGNAL_RSSI),
+        }
+    }))
+    .await
+}
+
+/// Reads a speaker's Wi-Fi signal strength from its support page.
+pub async fn fetch_signal_strength(client: &Client, ip: &str) -> Option<u8> {
+    let response = client
+        .get(build_sonos_url(ip, "/status/proc/ath_rincon/status"))
+        .send()
+        .await
+        .ok()?;
+    if !response.status().is_success() {
+        return None;
+    }
+    parse_signal_strength(&response.text().await.ok()?)
+}
+
+/// Returns the strongest RSSI in an `ath_rincon/status` page.
+///
+/// The page lists one RSSI per link (`RSSI: 42`, `rssi=42`); values outside
+/// 0-100 are not RSSI readings and are ignored.
+fn parse_signal_strength(text: &str) -> Option<u8> {
+    let lower = text.to_ascii_lowercase();
+    lower
+        .match_indices("rssi")
+        .filter_map(|(idx, _)| {
+            let value = lower[idx + 4..].trim_start_matches([' ', ':', '=', '\t']);
+            let digits = value
+                .find(|c: char| !c.is_ascii_digit())
+                .map_or(value, |end| &value[..end]);
+            digits.parse::<u8>().ok().filter(|rssi| *rssi <= 100)
+        })
+        .max()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_the_strongest_rssi() {
+        let page = "\
+Mode: Infrastructure\n\
+Node 00:0E:58:AA:BB:CC  RSSI: 27  Noise: -95\n\
+Node 00:0E:58:DD:EE:FF  rssi=41  Noise: -96\n\
+AP 34:12:98:00:11:22 RSSI: 35\n";
+        assert_eq!(parse_signal_strength(page), Some(41));
+    }
+
+    #[test]
+    fn ignores_pages_without_rssi() {
+        assert_eq!(parse_signal_strength("Mode: Wired\n"), None);
+        assert_eq!(parse_signal_strength("RSSI: n/a\nrssi: 250\n"), None);
+    }
+}
@@ -3,32 +3,37 @@
 //! Provides play, stop, and transport control via AVTransport SOAP actions,
 //! including retry logic for transient SOAP errors.
 
-use reqwest::Client;
-
 use crate::error::SoapResult;
 use crate::sonos::didl::format_didl_lite;
-use crate::sonos::retry::with_retry;
+use crate::sonos::retry::{retry_if, with_retry};
 use crate::sonos::services::SonosService;
-use crate::sonos::soap::{soap_request, SoapError};
+use crate::sonos::soap::{soap_request, SoapClient, SoapError};
 use crate::sonos::types::PositionInfo;
-use crate::sonos::utils::{build_sonos_stream_uri, extract_xml_text};
+use crate::sonos::uri_variants::UriQuirks;
+use crate::sonos::utils::extract_xml_text;
 use crate::stream::{AudioCodec, AudioFormat, StreamMetadata};
 
-/// Commands a Sonos speaker to play a specific audio URI.
+/// Points a Sonos speaker's transport at an audio URI without starting it.
 ///
 /// Optionally includes metadata for display on the Sonos UI.
 /// Retries transient SOAP faults (701, 714, 716) with exponential backoff.
+/// If the speaker refuses the URI format (714, 736), the stream URL is tried
+/// in the other formats of the quirk table, and the one accepted is recorded
+/// in `quirks`.
 ///
 /// # Arguments
 /// * `client` - The HTTP client to use for the request
+/// * `quirks` - URI formats speakers accepted before
 /// * `ip` - IP address of the Sonos speaker (coordinator for grouped speakers)
 /// * `uri` - The audio stream URL to play
 /// * `codec` - The audio codec for proper URI formatting and DIDL-Lite metadata
 /// * `audio_format` - Audio format configuration (sample rate, channels, bit depth)
 /// * `metadata` - Optional stream metadata for display (title, artist, source)
 /// * `artwork_url` - URL to the static app icon for album art display
-pub async fn play_uri(
-    client: &Client,
+#[allow(clippy::too_many_arguments)]
+pub async fn set_transport_uri(
+    client: &SoapClient,
+    quirks: &UriQuirks,
     ip: &str,
     uri: &str,
     codec: AudioCodec,
@@ -36,39 +41,53 @@ pub async fn play_uri(
     metadata: Option<&StreamMetadata>,
     artwork_url: &str,
 ) -> SoapResult<()> {
-    // Build Sonos-compatible URI with proper scheme and extension for codec
-    let sonos_uri = build_sonos_stream_uri(uri, codec);
     let didl_metadata = format_didl_lite(uri, codec, audio_format, metadata, artwork_url);
 
-    log::info!("[Sonos] SetAVTransportURI: ip={}, uri={}", ip, sonos_uri);
-
-    let set_uri_args = [
-        ("InstanceID", "0"),
-        ("CurrentURI", sonos_uri.as_str()),
-        ("CurrentURIMetaData", didl_metadata.as_str()),
-    ];
-    with_retry("SetAVTransportURI", || {
-        soap_request(
-            client,
-            ip,
-            SonosService::AVTransport,
-            "SetAVTransportURI",
-            &set_uri_args,
-        )
-    })
-    .await?;
-
-    log::info!("[Sonos] SetAVTransportURI succeeded, sending Play command");
-
-    let play_args = [("InstanceID", "0"), ("Speed", "1")];
-    with_retry("Play", || {
-        soap_request(client, ip, SonosService::AVTransport, "Play", &play_args)
-    })
-    .await?;
+    let candidates = quirks.candidates(ip, codec);
+    let mut last_error = None;
+    for (i, &variant) in candidates.iter().enumerate() {
+        // Sonos-compatible URI with the scheme and extension of this format
+        let sonos_uri = variant.uri(uri, codec);
+        log::info!("[Sonos] SetAVTransportURI: ip={}, uri={}", ip, sonos_uri);
 
-    log::info!("[Sonos] Play command succeeded");
+        let set_uri_args = [
+            ("InstanceID", "0"),
+            ("CurrentURI", sonos_uri.as_str()),
+            ("CurrentURIMetaData", didl_metadata.as_str()),
+        ];
+        // A 714 or 736 most likely means the format, so the next one is tried
+        // right away; only the last is retried, in case the 714 was a
+        // transient one from a source still loading
+        let is_last = i + 1 == candidates.len();
+        let should_retry =
+            |e: &SoapError| e.is_transient() && (is_last || !e.is_format_rejection());
+        let result = retry_if("SetAVTransportURI", should_retry, || {
+            soap_request(
+                client,
+                ip,
+                SonosService::AVTransport,
+                "SetAVTransportURI",
+                &set_uri_args,
+            )
+        })
+        .await;
+
+        match result {
+            Ok(_) => {
+                quirks.record(ip, codec, variant);
+                return Ok(());
+            }
+            Err(e) if e.is_format_rejection() => {
+                log::warn!("[Sonos] {} refused {}: {}", ip, sonos_uri, e);
+                last_error = Some(e);
+            }
+            Err(e) => return Err(e),
+        }
+    }
 
-    Ok(())
+    Err(last_error.unwrap_or_else(|| {
+        SoapError::Fault(format!("No URI format to try for {:?} streams", codec))
+    }))
 }
 
 /// Sends a Play command to resume playback on a Sonos speaker.
@@ -79,7 +98,7 @@ pub async fn play_uri(
 /// # Arguments
 /// * `client` - The HTTP client to use for the request
 /// * `ip` - IP address of the Sonos speaker (coordinator for grouped speakers)
-pub async fn play(client: &Client, ip: &str) -> SoapResult<()> {
+pub async fn play(client: &SoapClient, ip: &str) -> SoapResult<()> {
     log::info!("[Sonos] Sending Play command to {}", ip);
 
     let play_args = [("InstanceID", "0"), ("Speed", "1")];
@@ -104,7 +123,7 @@ pub async fn play(client: &Client, ip: &str) -> SoapResult<()> {
 ///
 /// Unlike `play_uri`/`play`, this intentionally skips `with_retry` — stop is
 /// best-effort cleanup, and retrying would delay teardown for unresponsive speakers.
-pub async fn stop(client: &Client, ip: &str) -> SoapResult<()> {
+pub async fn stop(client: &SoapClient, ip: &str) -> SoapResult<()> {
     let result = soap_request(
         client,
         ip,
@@ -142,7 +161,11 @@ pub async fn stop(client: &Client, ip: &str) -> SoapResult<()> {
 /// # Note
 /// Like `stop`, this intentionally skips `with_retry` — it's a post-stop cleanup
 /// step and retrying would delay teardown for unresponsive speakers.
-pub async fn switch_to_queue(client: &Client, ip: &str, coordinator_uuid: &str) -> SoapResult<()> {
+pub async fn switch_to_queue(
+    client: &SoapClient,
+    ip: &str,
+    coordinator_uuid: &str,
+) -> SoapResult<()> {
     let queue_uri = format!("x-rincon-queue:{}#0", coordinator_uuid);
 
     log::info!(
@@ -185,7 +208,7 @@ pub async fn switch_to_queue(client: &Client, ip: &str, coordinator_uuid: &str)
 /// # Note
 /// The `RelTime` field is in "H:MM:SS" format with second precision. For streams,
 /// this represents elapsed playback time since the stream started.
-pub async fn get_position_info(client: &Client, ip: &str) -> SoapResult<PositionInfo> {
+pub async fn get_position_info(client: &SoapClient, ip: &str) -> SoapResult<PositionInfo> {
     let response = soap_request(
         client,
         ip,
@@ -199,9 +222,14 @@ pub async fn get_position_info(client: &Client, ip: &str) -> SoapResult<Position
     let track_uri = extract_xml_text(&response, "TrackURI").unwrap_or_default();
     let rel_time = extract_xml_text(&response, "RelTime").unwrap_or_else(|| "0:00:00".to_string());
     let rel_time_ms = PositionInfo::parse_time_to_ms(&rel_time);
+    // Streams report "0:00:00" or "NOT_IMPLEMENTED"
+    let track_duration_ms = extract_xml_text(&response, "TrackDuration")
+        .map(|duration| PositionInfo::parse_time_to_ms(&duration))
+        .filter(|&ms| ms > 0);
 
     Ok(PositionInfo {
         track_uri,
         rel_time_ms,
+        track_duration_ms,
     })
 }
@@ -0,0 +1,237 @@
+//! Reverse-path reachability check between a speaker and this server.
+//!
+//! Probing a speaker's web server only proves we can reach it. Casting also
+//! needs the opposite direction: the speaker fetches the stream from us, and
+//! firewalls, client isolation or a wrong local IP can block that while
+//! control requests still work. This check points the speaker at a short
+//! silent clip served under a one-time token and waits for its GET.
+
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::time::{Duration, Instant};
+
+use bytes::{Bytes, BytesMut};
+use parking_lot::Mutex;
+use serde::Serialize;
+use tokio::sync::oneshot;
+use uuid::Uuid;
+
+use crate::context::UrlBuilder;
+use crate::sonos::discovery::probe_speaker_by_ip;
+use crate::sonos::traits::SonosPlayback;
+use crate::stream::{create_wav_header, AudioCodec, AudioFormat};
+
+/// How long to wait for the speaker's request after Play.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(8);
+
+/// Length of the clip served to the speaker.
+const CLIP_SECONDS: u32 = 1;
+
+/// Which directions traffic between this server and the speaker can flow.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReachabilityDirection {
+    /// We can control the speaker and it can fetch audio from us.
+    Both,
+    /// We can control the speaker, but its request for audio never arrived.
+    OutboundOnly,
+    /// The speaker didn't answer, so the reverse path couldn't be tested.
+    Unreachable,
+}
+
+/// Result of a reachability check against one speaker.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReachabilityReport {
+    /// Speaker IP address that was checked.
+    pub speaker_ip: String,
+    pub direction: ReachabilityDirection,
+    /// Milliseconds from setting the clip's URI to the speaker's request.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub request_ms: Option<u64>,
+    /// Address the speaker's request came from, if it differs from its IP
+    /// (e.g. behind NAT).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub request_from: Option<String>,
+    /// Why the check stopped short, if it did.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl ReachabilityReport {
+    fn failed(ip: &str, direction: ReachabilityDirection, error: String) -> Self {
+        Self {
+            speaker_ip: ip.to_string(),
+            direction,
+            request_ms: None,
+            request_from: None,
+            error: Some(error),
+        }
+    }
+}
+
+/// Reachability checks waiting for their speaker's request.
+#[derive(Default)]
+pub struct ReachabilityProbes {
+    pending: Mutex<HashMap<String, Option<oneshot::Sender<IpAddr>>>>,
+}
+
+impl ReachabilityProbes {
+    /// Registers a check, returning its token and a receiver that resolves
+    /// with the requesting address on the first request.
+    fn register(&self) -> (String, oneshot::Receiver<IpAddr>) {
+        let token = Uuid::new_v4().simple().to_string();
+        let (tx, rx) = oneshot::channel();
+        self.pending.lock().insert(token.clone(), Some(tx));
+        (token, rx)
+    }
+
+    fn finish(&self, token: &str) {
+        self.pending.lock().remove(token);
+    }
+
+    /// Records a request for a check's clip.
+    ///
+    /// Returns `false` if the token doesn't belong to a running check.
+    /// Repeat requests (speakers often probe before playing) are accepted
+    /// but only the first is reported.
+    pub fn request_arrived(&self, token: &str, from: IpAddr) -> bool {
+        let mut pending = self.pending.lock();
+        let Some(waiter) = pending.get_mut(token) else {
+            return false;
+        };
+        if let Some(tx) = waiter.take() {
+            let _ = tx.send(from);
+        }
+        true
+    }
+}
+
+/// Returns the clip served to speakers during a check: one second of silence.
+#[must_use]
+pub fn probe_clip() -> Bytes {
+    let format = AudioFormat::default();
+    let data_len = format.frame_bytes(CLIP_SECONDS * 1000);
+    let header = create_wav_header(format.sample_rate, format.channels, format.bits_per_sample);
+    let mut clip = BytesMut::with_capacity(header.len() + data_len);
+    clip.extend_from_slice(&header);
+    clip.resize(header.len() + data_len, 0);
+    clip.freeze()
+}
+
+/// Checks that the speaker at `ip` can fetch audio from this server.
+///
+/// Interrupts whatever the speaker is playing: it plays the clip and is
+/// stopped afterwards. Takes up to about ten seconds.
+pub async fn check_reachability<S: SonosPlayback + ?Sized>(
+    http_client: &reqwest::Client,
+    sonos: &S,
+    probes: &ReachabilityProbes,
+    url_builder: &UrlBuilder,
+    artwork_url: &str,
+    ip: &str,
+) -> ReachabilityReport {
+    if let Err(e) = probe_speaker_by_ip(http_client, ip).await {
+        return ReachabilityReport::failed(ip, ReachabilityDirection::Unreachable, e.to_string());
+    }
+
+    let (token, request) = probes.register();
+    let url = url_builder.reachability_clip_url(&token);
+
+    // Speakers often fetch the URI as soon as it is set, before Play
+    let started = Instant::now();
+    let report = match sonos
+        .set_transport_uri(
+            ip,
+            &url,
+            AudioCodec::Pcm,
+            &AudioFormat::default(),
+            None,
+            artwork_url,
+        )
+        .await
+    {
+        Ok(()) => match sonos.play(ip).await {
+            Ok(()) => wait_for_request(ip, request, started).await,
+            Err(e) => ReachabilityReport::failed(
+                ip,
+                ReachabilityDirection::Unreachable,
+                format!("Play failed: {}", e),
+            ),
+        },
+        Err(e) => ReachabilityReport::failed(
+            ip,
+            ReachabilityDirection::Unreachable,
+            format!("SetAVTransportURI failed: {}", e),
+        ),
+    };
+    probes.finish(&token);
+
+    if let Err(e) = sonos.stop(ip).await {
+        log::debug!("[Reachability] Failed to stop {} after check: {}", ip, e);
+    }
+
+    log::info!(
+        "[Reachability] {}: {:?} (request after {:?}ms)",
+        ip,
+        report.direction,
+        report.request_ms
+    );
+    report
+}
+
+async fn wait_for_request(
+    ip: &str,
+    request: oneshot::Receiver<IpAddr>,
+    started: Instant,
+) -> ReachabilityReport {
+    match tokio::time::timeout(REQUEST_TIMEOUT, request).await {
+        Ok(Ok(from)) => ReachabilityReport {
+            speaker_ip: ip.to_string(),
+            direction: ReachabilityDirection::Both,
+            request_ms: Some(started.elapsed().as_millis() as u64),
+            request_from: (from.to_string() != ip).then(|| from.to_string()),
+            error: None,
+        },
+        _ => ReachabilityReport::failed(
+            ip,
+            ReachabilityDirection::OutboundOnly,
+            format!(
+                "Speaker accepted Play but didn't request audio within {}s; \
+                 a firewall may be blocking incoming connections",
+                REQUEST_TIMEOUT.as_secs()
+            ),
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SPEAKER: IpAddr = IpAddr::V4(std::net::Ipv4Addr::new(192, 168, 1, 50));
+
+    #[test]
+    fn only_running_checks_accept_requests() {
+        let probes = ReachabilityProbes::default();
+        let (token, mut request) = probes.register();
+
+        assert!(!probes.request_arrived("unknown", SPEAKER));
+        assert!(probes.request_arrived(&token, SPEAKER));
+        // Repeat requests are still served
+        assert!(probes.request_arrived(&token, SPEAKER));
+        assert_eq!(request.try_recv().unwrap(), SPEAKER);
+
+        probes.finish(&token);
+        assert!(!probes.request_arrived(&token, SPEAKER));
+    }
+
+    #[test]
+    fn clip_is_one_second_of_silence() {
+        let format = AudioFormat::default();
+        let clip = probe_clip();
+        let data = &clip[44..];
+        assert_eq!(data.len(), format.frame_bytes(1000));
+        assert!(data.iter().all(|&b| b == 0));
+    }
+}
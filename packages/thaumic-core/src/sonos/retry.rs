@@ -6,6 +6,7 @@
 use std::time::Duration;
 
 use crate::error::SoapResult;
+use crate::sonos::soap::SoapError;
 
 /// Retry delays for transient SOAP errors (exponential backoff).
 const RETRY_DELAYS_MS: [u64; 3] = [200, 500, 1000];
@@ -18,7 +19,20 @@ const RETRY_DELAYS_MS: [u64; 3] = [200, 500, 1000];
 /// # Arguments
 /// * `action` - Action name for logging
 /// * `operation` - Closure that performs the SOAP request
-pub(crate) async fn with_retry<F, Fut>(action: &str, mut operation: F) -> SoapResult<String>
+pub(crate) async fn with_retry<F, Fut>(action: &str, operation: F) -> SoapResult<String>
+where
+    F: FnMut() -> Fut,
+    Fut: std::future::Future<Output = SoapResult<String>>,
+{
+    retry_if(action, SoapError::is_transient, operation).await
+}
+
+/// Like [`with_retry`], but only retries errors `should_retry` accepts.
+pub(crate) async fn retry_if<F, Fut>(
+    action: &str,
+    should_retry: impl Fn(&SoapError) -> bool,
+    mut operation: F,
+) -> SoapResult<String>
 where
     F: FnMut() -> Fut,
     Fut: std::future::Future<Output = SoapResult<String>>,
@@ -41,7 +55,7 @@ where
 
         match operation().await {
             Ok(r) => return Ok(r),
-            Err(e) if e.is_transient() => {
+            Err(e) if should_retry(&e) => {
                 log::warn!("[Sonos] {} transient error: {}", action, e);
                 last_error = Some(e);
             }
@@ -2,14 +2,29 @@
 //!
 //! This module handles the raw SOAP envelope building, HTTP transport,
 //! and XML response parsing. For high-level Sonos commands, see `client.rs`.
+//!
+//! Requests go through a [`SoapClient`], which keeps a small pool of
+//! HTTP/1.1 keep-alive connections per speaker. Starting a group sends
+//! several calls to the same speakers in quick succession; reusing the
+//! connection saves a TCP handshake on each of them. The pool also records
+//! per-speaker request statistics, so the effect is measurable.
 
-use std::time::Duration;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
 
+use parking_lot::Mutex;
 use reqwest::Client;
+use serde::Serialize;
 use thiserror::Error;
+use tokio::sync::Semaphore;
 
 use super::utils::{build_sonos_url, escape_xml, extract_xml_text};
-use crate::protocol_constants::SOAP_TIMEOUT_SECS;
+use crate::protocol_constants::{
+    SOAP_MAX_CONNECTIONS_PER_SPEAKER, SOAP_POOL_IDLE_TIMEOUT_SECS, SOAP_TIMEOUT_SECS,
+};
 
 // ─────────────────────────────────────────────────────────────────────────────
 // Error Types
@@ -59,6 +74,223 @@ impl SoapError {
             _ => false,
         }
     }
+
+    /// Returns true if the speaker refused a transport URI in this format,
+    /// so the same stream may still play under another one.
+    ///
+    /// - 714: Illegal MIME type
+    /// - 736: Unsupported play format
+    #[must_use]
+    pub fn is_format_rejection(&self) -> bool {
+        matches!(self, SoapError::Fault(msg) if msg.contains("714") || msg.contains("736"))
+    }
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Connection Pool
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// SOAP request statistics for one speaker.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SoapSpeakerStats {
+    pub ip: String,
+    /// Requests sent, including failed ones.
+    pub requests: u64,
+    /// Requests that failed at the HTTP level or returned a SOAP fault.
+    pub failures: u64,
+    /// TCP connections opened. Requests beyond this reused a connection.
+    pub connections_opened: u64,
+    /// Mean request latency, including any wait for a free connection.
+    pub avg_latency_ms: u64,
+    pub max_latency_ms: u64,
+    /// Requests currently in flight.
+    pub in_flight: usize,
+}
+
+/// Pooled connections and counters for one speaker.
+struct SpeakerPool {
+    client: Client,
+    /// Caps concurrent requests, and therefore open connections.
+    permits: Semaphore,
+    requests: AtomicU64,
+    failures: AtomicU64,
+    connections_opened: Arc<AtomicU64>,
+    total_latency_ms: AtomicU64,
+    max_latency_ms: AtomicU64,
+}
+
+impl SpeakerPool {
+    fn new() -> reqwest::Result<Self> {
+        let connections_opened = Arc::new(AtomicU64::new(0));
+        let client = Client::builder()
+            .http1_only()
+            .pool_max_idle_per_host(SOAP_MAX_CONNECTIONS_PER_SPEAKER)
+            .pool_idle_timeout(Duration::from_secs(SOAP_POOL_IDLE_TIMEOUT_SECS))
+            .tcp_keepalive(Duration::from_secs(SOAP_POOL_IDLE_TIMEOUT_SECS))
+            .timeout(Duration::from_secs(SOAP_TIMEOUT_SECS))
+            .connector_layer(CountConnectsLayer(Arc::clone(&connections_opened)))
+            .build()?;
+
+        Ok(Self {
+            client,
+            permits: Semaphore::new(SOAP_MAX_CONNECTIONS_PER_SPEAKER),
+            requests: AtomicU64::new(0),
+            failures: AtomicU64::new(0),
+            connections_opened,
+            total_latency_ms: AtomicU64::new(0),
+            max_latency_ms: AtomicU64::new(0),
+        })
+    }
+
+    fn record(&self, elapsed: Duration, failed: bool) {
+        let ms = elapsed.as_millis() as u64;
+        self.requests.fetch_add(1, Ordering::Relaxed);
+        self.total_latency_ms.fetch_add(ms, Ordering::Relaxed);
+        self.max_latency_ms.fetch_max(ms, Ordering::Relaxed);
+        if failed {
+            self.failures.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    fn stats(&self, ip: &str) -> SoapSpeakerStats {
+        let requests = self.requests.load(Ordering::Relaxed);
+        SoapSpeakerStats {
+            ip: ip.to_string(),
+            requests,
+            failures: self.failures.load(Ordering::Relaxed),
+            connections_opened: self.connections_opened.load(Ordering::Relaxed),
+            avg_latency_ms: self
+                .total_latency_ms
+                .load(Ordering::Relaxed)
+                .checked_div(requests)
+                .unwrap_or(0),
+            max_latency_ms: self.max_latency_ms.load(Ordering::Relaxed),
+            in_flight: SOAP_MAX_CONNECTIONS_PER_SPEAKER - self.permits.available_permits(),
+        }
+    }
+}
+
+/// Connector layer counting the connections a speaker's client opens.
+#[derive(Clone)]
+struct CountConnectsLayer(Arc<AtomicU64>);
+
+impl<S> tower::Layer<S> for CountConnectsLayer {
+    type Service = CountConnects<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        CountConnects {
+            inner,
+            count: Arc::clone(&self.0),
+        }
+    }
+}
+
+#[derive(Clone)]
+struct CountConnects<S> {
+    inner: S,
+    count: Arc<AtomicU64>,
+}
+
+impl<S: tower::Service<R>, R> tower::Service<R> for CountConnects<S> {
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = S::Future;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: R) -> Self::Future {
+        self.count.fetch_add(1, Ordering::Relaxed);
+        self.inner.call(request)
+    }
+}
+
+/// HTTP client for SOAP calls, pooling keep-alive connections per speaker.
+///
+/// Cheap to clone; clones share the pools and statistics.
+#[derive(Clone, Default)]
+pub struct SoapClient {
+    speakers: Arc<Mutex<HashMap<String, Arc<SpeakerPool>>>>,
+}
+
+impl std::fmt::Debug for SoapClient {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SoapClient")
+            .field("speakers", &self.speakers.lock().len())
+            .finish()
+    }
+}
+
+impl SoapClient {
+    /// Creates a client with no open connections.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns request statistics for every speaker contacted, sorted by IP.
+    pub fn stats(&self) -> Vec<SoapSpeakerStats> {
+        let mut stats: Vec<SoapSpeakerStats> = self
+            .speakers
+            .lock()
+            .iter()
+            .map(|(ip, pool)| pool.stats(ip))
+            .collect();
+        stats.sort_by(|a, b| a.ip.cmp(&b.ip));
+        stats
+    }
+
+    fn pool(&self, ip: &str) -> reqwest::Result<Arc<SpeakerPool>> {
+        let mut speakers = self.speakers.lock();
+        if let Some(pool) = speakers.get(ip) {
+            return Ok(Arc::clone(pool));
+        }
+        let pool = Arc::new(SpeakerPool::new()?);
+        speakers.insert(ip.to_string(), Arc::clone(&pool));
+        Ok(pool)
+    }
+
+    /// Posts a SOAP envelope to `url` on the speaker at `ip`.
+    ///
+    /// Waits for a free connection slot if the speaker already has the
+    /// maximum number of requests in flight.
+    async fn post(
+        &self,
+        ip: &str,
+        url: &str,
+        soap_action: String,
+        body: String,
+    ) -> SoapResult<(reqwest::StatusCode, String)> {
+        let pool = self.pool(ip)?;
+        let start = Instant::now();
+        let _permit = pool
+            .permits
+            .acquire()
+            .await
+            .expect("SOAP connection semaphore is never closed");
+
+        let result = async {
+            let res = pool
+                .client
+                .post(url)
+                .header("Content-Type", "text/xml; charset=\"utf-8\"")
+                .header("SOAPAction", soap_action)
+                .body(body)
+                .send()
+                .await?;
+            let status = res.status();
+            Ok::<_, reqwest::Error>((status, res.text().await?))
+        }
+        .await;
+
+        let failed = match &result {
+            Ok((status, text)) => !status.is_success() || is_fault(text),
+            Err(_) => true,
+        };
+        pool.record(start.elapsed(), failed);
+        Ok(result?)
+    }
 }
 
 // ─────────────────────────────────────────────────────────────────────────────
@@ -72,7 +304,7 @@ impl SoapError {
 /// SOAP faults in the response.
 ///
 /// # Arguments
-/// * `client` - The HTTP client to use for the request
+/// * `client` - The pooled SOAP client to send the request through
 /// * `ip` - IP address of the Sonos speaker
 /// * `endpoint` - The control URL path (e.g., "/MediaRenderer/AVTransport/Control")
 /// * `service` - The UPnP service URN (e.g., "urn:schemas-upnp-org:service:AVTransport:1")
@@ -83,7 +315,7 @@ impl SoapError {
 /// The response body on success, or a `SoapError` if the request fails
 /// or the speaker returns a SOAP fault.
 pub async fn send_soap_request(
-    client: &Client,
+    client: &SoapClient,
     ip: &str,
     endpoint: &str,
     service: &str,
@@ -109,14 +341,9 @@ pub async fn send_soap_request(
     log::info!("[SOAP] {} -> {} (body: {} bytes)", action, url, body.len());
     log::debug!("[SOAP] Request body: {}", body);
 
-    let start = std::time::Instant::now();
+    let start = Instant::now();
     let res = client
-        .post(&url)
-        .header("Content-Type", "text/xml; charset=\"utf-8\"")
-        .header("SOAPAction", format!("\"{}#{}\"", service, action))
-        .body(body)
-        .timeout(Duration::from_secs(SOAP_TIMEOUT_SECS))
-        .send()
+        .post(ip, &url, format!("\"{}#{}\"", service, action), body)
         .await;
 
     let elapsed = start.elapsed();
@@ -124,16 +351,13 @@ pub async fn send_soap_request(
         "[SOAP] {} completed in {:?}: {:?}",
         action,
         elapsed,
-        res.as_ref().map(|r| r.status())
+        res.as_ref().map(|(status, _)| *status)
     );
 
-    let res = res?;
-
-    let status = res.status();
-    let response_text = res.text().await?;
+    let (status, response_text) = res?;
 
     // Check for SOAP fault in response (can occur even on 500 status)
-    if response_text.contains("<s:Fault>") || response_text.contains("<soap:Fault>") {
+    if is_fault(&response_text) {
         let fault_msg = extract_fault_string(&response_text)
             .unwrap_or_else(|| "Unknown SOAP fault".to_string());
         return Err(SoapError::Fault(fault_msg));
@@ -147,9 +371,19 @@ pub async fn send_soap_request(
     Ok(response_text)
 }
 
-/// Extracts the faultstring from a SOAP fault response.
+/// Returns true if a response body is a SOAP fault.
+fn is_fault(xml: &str) -> bool {
+    xml.contains("<s:Fault>") || xml.contains("<soap:Fault>")
+}
+
+/// Extracts the faultstring from a SOAP fault response, followed by the UPnP
+/// error code when the fault carries one (`UPnPError 714`).
 fn extract_fault_string(xml: &str) -> Option<String> {
-    extract_xml_text(xml, "faultstring")
+    let fault = extract_xml_text(xml, "faultstring")?;
+    Some(match extract_xml_text(xml, "errorCode") {
+        Some(code) => format!("{} {}", fault, code.trim()),
+        None => fault,
+    })
 }
 
 // ─────────────────────────────────────────────────────────────────────────────
@@ -165,13 +399,13 @@ use super::services::SonosService;
 /// low-level `send_soap_request`.
 ///
 /// # Arguments
-/// * `client` - The HTTP client to use for the request
+/// * `client` - The pooled SOAP client to send the request through
 /// * `ip` - IP address of the Sonos speaker
 /// * `service` - The Sonos service to target
 /// * `action` - The SOAP action name (e.g., "Play", "Stop", "GetVolume")
 /// * `args` - Key-value pairs for action arguments (order is preserved)
 pub async fn soap_request(
-    client: &Client,
+    client: &SoapClient,
     ip: &str,
     service: SonosService,
     action: &str,
@@ -187,3 +421,75 @@ pub async fn soap_request(
     )
     .await
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::routing::post;
+
+    const IP: &str = "127.0.0.1";
+
+    async fn serve(response: &'static str) -> String {
+        let app = axum::Router::new().route(
+            "/MediaRenderer/AVTransport/Control",
+            post(move || async move { response }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!(
+            "http://{}/MediaRenderer/AVTransport/Control",
+            listener.local_addr().unwrap()
+        );
+        tokio::spawn(async move { axum::serve(listener, app).await });
+        url
+    }
+
+    #[tokio::test]
+    async fn reuses_connection_across_requests() {
+        let url = serve("<s:Envelope><s:Body><u:PlayResponse/></s:Body></s:Envelope>").await;
+        let client = SoapClient::new();
+
+        for _ in 0..3 {
+            let (status, _) = client
+                .post(IP, &url, "\"urn:test#Play\"".into(), String::new())
+                .await
+                .unwrap();
+            assert!(status.is_success());
+        }
+
+        let stats = client.stats();
+        assert_eq!(stats.len(), 1);
+        assert_eq!(stats[0].requests, 3);
+        assert_eq!(stats[0].connections_opened, 1);
+        assert_eq!(stats[0].failures, 0);
+        assert_eq!(stats[0].in_flight, 0);
+    }
+
+    #[tokio::test]
+    async fn counts_faults_as_failures() {
+        let url = serve(
+            "<s:Envelope><s:Body><s:Fault><faultstring>UPnPError</faultstring></s:Fault></s:Body></s:Envelope>",
+        )
+        .await;
+        let client = SoapClient::new();
+
+        client
+            .post(IP, &url, "\"urn:test#Play\"".into(), String::new())
+            .await
+            .unwrap();
+
+        assert_eq!(client.stats()[0].failures, 1);
+    }
+
+    #[test]
+    fn fault_messages_carry_the_upnp_error_code() {
+        let xml = "<s:Envelope><s:Body><s:Fault><faultcode>s:Client</faultcode>\
+            <faultstring>UPnPError</faultstring><detail><UPnPError \
+            xmlns=\"urn:schemas-upnp-org:control-1-0\"><errorCode>714</errorCode>\
+            </UPnPError></detail></s:Fault></s:Body></s:Envelope>";
+        let fault = SoapError::Fault(extract_fault_string(xml).unwrap());
+
+        assert_eq!(fault.to_string(), "SOAP fault: UPnPError 714");
+        assert!(fault.is_format_rejection());
+        assert!(!SoapError::Fault("UPnPError 701".into()).is_format_rejection());
+    }
+}
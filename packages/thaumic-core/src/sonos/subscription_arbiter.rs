@@ -17,6 +17,7 @@ use dashmap::DashSet;
 
 use super::gena::GenaSubscriptionManager;
 use super::services::SonosService;
+use super::types::SpeakerMove;
 
 /// Arbitrates between RenderingControl and GroupRenderingControl subscriptions.
 ///
@@ -48,6 +49,19 @@ impl SubscriptionArbiter {
         self.sync_ips.contains(ip)
     }
 
+    /// Moves speakers that changed address to their new IP in the set of
+    /// sync-active speakers. Their subscriptions are moved by the GENA manager.
+    pub fn rekey_speakers(&self, moves: &[SpeakerMove]) {
+        let moved: Vec<&str> = moves
+            .iter()
+            .filter(|m| self.sync_ips.remove(&m.old_ip).is_some())
+            .map(|m| m.new_ip.as_str())
+            .collect();
+        for ip in moved {
+            self.sync_ips.insert(ip.to_string());
+        }
+    }
+
     /// Enters a sync session for the given speaker IPs.
     ///
     /// For each IP:
@@ -220,6 +234,7 @@ impl SubscriptionArbiter {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::clock::SystemClock;
 
     /// Creates a test arbiter with a real (but unused) GenaSubscriptionManager.
     ///
@@ -230,7 +245,7 @@ mod tests {
             .timeout(std::time::Duration::from_millis(1))
             .build()
             .unwrap();
-        let (gena_manager, _rx) = GenaSubscriptionManager::new(client);
+        let (gena_manager, _rx) = GenaSubscriptionManager::new(client, SystemClock::arc());
         SubscriptionArbiter::new(Arc::new(gena_manager))
     }
 
@@ -4,9 +4,10 @@
 //! Services depend on traits rather than concrete implementations.
 
 use async_trait::async_trait;
+use futures::stream::BoxStream;
 
 use crate::error::{DiscoveryResult, SoapResult};
-use crate::sonos::discovery::Speaker;
+use crate::sonos::discovery::{DiscoveryDiagnostics, Speaker};
 use crate::sonos::types::{PositionInfo, ZoneGroup};
 use crate::stream::{AudioCodec, AudioFormat, StreamMetadata};
 
@@ -17,6 +18,8 @@ use crate::stream::{AudioCodec, AudioFormat, StreamMetadata};
 pub trait SonosPlayback: Send + Sync {
     /// Commands a Sonos speaker to play a specific audio URI.
     ///
+    /// Sets the transport URI, then sends Play.
+    ///
     /// # Arguments
     /// * `ip` - IP address of the Sonos speaker (coordinator for grouped speakers)
     /// * `uri` - The audio stream URL to play
@@ -32,6 +35,29 @@ pub trait SonosPlayback: Send + Sync {
         audio_format: &AudioFormat,
         metadata: Option<&StreamMetadata>,
         artwork_url: &str,
+    ) -> SoapResult<()> {
+        self.set_transport_uri(ip, uri, codec, audio_format, metadata, artwork_url)
+            .await?;
+        self.play(ip).await
+    }
+
+    /// Points a Sonos speaker's transport at an audio URI without starting it.
+    ///
+    /// # Arguments
+    /// * `ip` - IP address of the Sonos speaker (coordinator for grouped speakers)
+    /// * `uri` - The audio stream URL to play
+    /// * `codec` - The audio codec for proper URI formatting and DIDL-Lite metadata
+    /// * `audio_format` - Audio format configuration (sample rate, channels, bit depth)
+    /// * `metadata` - Optional stream metadata for display (title, artist, source)
+    /// * `artwork_url` - URL to the static app icon for album art display
+    async fn set_transport_uri(
+        &self,
+        ip: &str,
+        uri: &str,
+        codec: AudioCodec,
+        audio_format: &AudioFormat,
+        metadata: Option<&StreamMetadata>,
+        artwork_url: &str,
     ) -> SoapResult<()>;
 
     /// Sends a Play command to resume playback on a Sonos speaker.
@@ -112,6 +138,15 @@ pub trait SonosTopology: Send + Sync {
 pub trait SonosDiscovery: Send + Sync {
     /// Discovers Sonos speakers on the local network using SSDP.
     async fn discover_speakers(&self) -> DiscoveryResult<Vec<Speaker>>;
+
+    /// Discovers speakers, yielding each one as soon as its metadata resolves.
+    ///
+    /// Yields a single error and ends if every discovery method fails.
+    fn discover_speakers_stream(&self) -> BoxStream<'_, DiscoveryResult<Speaker>>;
+
+    /// Returns per-method results of the most recent discovery run, or
+    /// `None` if discovery hasn't run yet.
+    fn last_discovery_diagnostics(&self) -> Option<DiscoveryDiagnostics>;
 }
 
 /// Trait for Sonos volume and mute control operations.
@@ -58,6 +58,67 @@ impl std::str::FromStr for TransportState {
 // Zone Groups
 // ─────────────────────────────────────────────────────────────────────────────
 
+/// What a Sonos device can do with a stream.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum DeviceRole {
+    /// Plays audio on its own and can be cast to.
+    #[default]
+    Player,
+    /// Home theater surround or other bonded speaker that only plays what
+    /// its primary plays.
+    Satellite,
+    /// Subwoofer (bonded, or not yet set up with a speaker).
+    Subwoofer,
+    /// Boost or Bridge: a network extender with no audio output.
+    Bridge,
+}
+
+impl DeviceRole {
+    /// Returns whether streams can be sent to the device directly.
+    pub fn is_playable(self) -> bool {
+        self == Self::Player
+    }
+
+    /// Classifies a device by its description model name (e.g., "Sonos Boost").
+    ///
+    /// Only bridges and subwoofers can be told apart by model; satellites are
+    /// regular speakers whose role comes from the topology.
+    pub fn from_model_name(model_name: Option<&str>) -> Self {
+        let Some(model) = model_name.map(str::to_lowercase) else {
+            return Self::Player;
+        };
+        if model.contains("boost") || model.contains("bridge") {
+            Self::Bridge
+        } else if model.split_whitespace().any(|word| word == "sub") {
+            Self::Subwoofer
+        } else {
+            Self::Player
+        }
+    }
+}
+
+/// Kind of bonded set.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum BondKind {
+    /// Two speakers playing left and right channels (possibly with a sub).
+    StereoPair,
+    /// Soundbar or amp with surrounds and/or a sub.
+    HomeTheater,
+    /// A single speaker with a sub.
+    WithSubwoofer,
+}
+
+/// Membership in a bonded set, which Sonos treats as one logical speaker.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Bond {
+    pub kind: BondKind,
+    /// UUID of the set's primary, the only member that can be cast to.
+    pub primary_uuid: String,
+}
+
 /// A speaker within a Sonos zone group.
 ///
 /// Represents an individual Sonos device that is part of a zone group.
@@ -78,6 +139,15 @@ pub struct ZoneGroupMember {
     /// Otherwise, it's the model name extracted from the device icon (e.g., "one", "arc").
     /// Falls back to "Speaker" if neither is available.
     pub model: String,
+    /// Whether the member can be cast to, or plays through another speaker.
+    pub role: DeviceRole,
+    /// Bonded set (stereo pair, home theater) the member belongs to.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bond: Option<Bond>,
+    /// Whether the speaker is connected by Ethernet, if its firmware
+    /// reports it.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub wired: Option<bool>,
 }
 
 /// A Sonos zone group (speakers playing in sync).
@@ -89,8 +159,13 @@ pub struct ZoneGroupMember {
 pub struct ZoneGroup {
     /// Zone group identifier.
     pub id: String,
-    /// Human-readable name (typically the coordinator's zone name).
+    /// Every room in the group, coordinator first (e.g., "Kitchen, Office").
     pub name: String,
+    /// Short display label in the Sonos app's style (e.g., "Kitchen + 1").
+    pub label: String,
+    /// Number of rooms in the group. Bonded sets (stereo pairs, home
+    /// theaters) count as one room.
+    pub room_count: usize,
     /// UUID of the group coordinator.
     pub coordinator_uuid: String,
     /// IP address of the group coordinator.
@@ -101,6 +176,17 @@ pub struct ZoneGroup {
     pub members: Vec<ZoneGroupMember>,
 }
 
+/// A speaker seen at a new address, e.g. after its DHCP lease changed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpeakerMove {
+    /// Unique identifier in RINCON_xxxxx format.
+    pub uuid: String,
+    /// Address the speaker was last known at.
+    pub old_ip: String,
+    /// Address it was just seen at.
+    pub new_ip: String,
+}
+
 // ─────────────────────────────────────────────────────────────────────────────
 // Playback Position
 // ─────────────────────────────────────────────────────────────────────────────
@@ -118,6 +204,10 @@ pub struct PositionInfo {
     ///
     /// Parsed from the SOAP `RelTime` field for easier calculations.
     pub rel_time_ms: u64,
+    /// Duration of the current track in milliseconds.
+    ///
+    /// `None` when the speaker reports no duration, as it does for live streams.
+    pub track_duration_ms: Option<u64>,
 }
 
 impl PositionInfo {
@@ -0,0 +1,128 @@
+//! Alternative stream URI formats for speakers that refuse the usual one.
+//!
+//! Some firmware rejects the URI built by [`build_sonos_stream_uri`] with
+//! UPnP error 714 (illegal MIME type) or 736 (unsupported play format), yet
+//! plays the same stream under another scheme or without the file extension.
+//! Starting playback walks the codec's row of the quirk table until a format
+//! is accepted, and remembers it per speaker so the next start tries it
+//! first.
+
+use dashmap::DashMap;
+
+use crate::sonos::utils::build_sonos_stream_uri;
+use crate::stream::AudioCodec;
+
+/// A way of writing the stream URL in `SetAVTransportURI`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UriVariant {
+    /// `http://` with the codec's file extension (`.wav`, `.flac`).
+    Extension,
+    /// `x-rincon-mp3radio://`, the internet radio scheme.
+    Mp3Radio,
+    /// The stream URL as served: `http://` without an extension.
+    Plain,
+}
+
+impl UriVariant {
+    /// Returns the formats to try for a codec, most compatible first.
+    #[must_use]
+    pub fn candidates(codec: AudioCodec) -> &'static [UriVariant] {
+        match codec {
+            AudioCodec::Pcm | AudioCodec::Flac => &[Self::Extension, Self::Plain],
+            AudioCodec::Aac | AudioCodec::Mp3 => &[Self::Mp3Radio, Self::Plain],
+        }
+    }
+
+    /// Writes `base_uri` (the `http://` stream URL) in this format.
+    #[must_use]
+    pub fn uri(self, base_uri: &str, codec: AudioCodec) -> String {
+        match self {
+            Self::Extension | Self::Mp3Radio => build_sonos_stream_uri(base_uri, codec),
+            Self::Plain => base_uri.to_string(),
+        }
+    }
+}
+
+/// URI formats speakers accepted, by speaker IP and codec.
+#[derive(Debug, Default)]
+pub struct UriQuirks {
+    accepted: DashMap<(String, AudioCodec), UriVariant>,
+}
+
+impl UriQuirks {
+    /// Returns the formats to try on a speaker, the one it accepted last
+    /// time first.
+    #[must_use]
+    pub fn candidates(&self, ip: &str, codec: AudioCodec) -> Vec<UriVariant> {
+        let mut variants = UriVariant::candidates(codec).to_vec();
+        if let Some(accepted) = self.accepted.get(&(ip.to_string(), codec)) {
+            variants.retain(|variant| variant != &*accepted);
+            variants.insert(0, *accepted);
+        }
+        variants
+    }
+
+    /// Records the format a speaker accepted.
+    pub fn record(&self, ip: &str, codec: AudioCodec, variant: UriVariant) {
+        let standard = UriVariant::candidates(codec)[0];
+        if variant == standard {
+            self.accepted.remove(&(ip.to_string(), codec));
+        } else if self.accepted.insert((ip.to_string(), codec), variant) != Some(variant) {
+            log::info!(
+                "[Sonos] {} accepts {:?} streams only as {:?} URIs, remembering",
+                ip,
+                codec,
+                variant
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BASE: &str = "http://192.168.1.10:49400/stream/7d3c/live";
+
+    #[test]
+    fn writes_each_variant() {
+        assert_eq!(
+            UriVariant::Extension.uri(BASE, AudioCodec::Pcm),
+            format!("{}.wav", BASE)
+        );
+        assert_eq!(
+            UriVariant::Mp3Radio.uri(BASE, AudioCodec::Aac),
+            "x-rincon-mp3radio://192.168.1.10:49400/stream/7d3c/live"
+        );
+        assert_eq!(UriVariant::Plain.uri(BASE, AudioCodec::Flac), BASE);
+    }
+
+    #[test]
+    fn tries_the_accepted_variant_first() {
+        let quirks = UriQuirks::default();
+        assert_eq!(
+            quirks.candidates("192.168.1.10", AudioCodec::Pcm),
+            [UriVariant::Extension, UriVariant::Plain]
+        );
+
+        quirks.record("192.168.1.10", AudioCodec::Pcm, UriVariant::Plain);
+        assert_eq!(
+            quirks.candidates("192.168.1.10", AudioCodec::Pcm),
+            [UriVariant::Plain, UriVariant::Extension]
+        );
+        assert_eq!(
+            quirks.candidates("192.168.1.10", AudioCodec::Flac)[0],
+            UriVariant::Extension
+        );
+        assert_eq!(
+            quirks.candidates("192.168.1.11", AudioCodec::Pcm)[0],
+            UriVariant::Extension
+        );
+
+        quirks.record("192.168.1.10", AudioCodec::Pcm, UriVariant::Extension);
+        assert_eq!(
+            quirks.candidates("192.168.1.10", AudioCodec::Pcm)[0],
+            UriVariant::Extension
+        );
+    }
+}
@@ -261,6 +261,27 @@ pub fn get_channel_role(ht_sat_chan_map: &str, uuid: &str) -> Option<String> {
     None
 }
 
+/// Parses channel role from a stereo pair's ChannelMapSet for a given UUID.
+///
+/// # Format
+/// `"UUID1:LF,LF;UUID2:RF,RF;UUID3:SW,SW"`
+///
+/// The same attribute describes a single speaker bonded with a sub
+/// (`"UUID1:LF,RF;UUID2:SW,SW"`); the main speaker then has no role.
+pub fn get_stereo_channel_role(channel_map: &str, uuid: &str) -> Option<String> {
+    channel_map
+        .split(';')
+        .filter_map(|mapping| mapping.split_once(':'))
+        .find(|(map_uuid, _)| *map_uuid == uuid)
+        .and_then(|(_, channels)| match channels {
+            "LF,LF" => Some("Left"),
+            "RF,RF" => Some("Right"),
+            "SW,SW" => Some("Subwoofer"),
+            _ => None,
+        })
+        .map(str::to_string)
+}
+
 // ─────────────────────────────────────────────────────────────────────────────
 // URL Building
 // ─────────────────────────────────────────────────────────────────────────────
@@ -3,11 +3,9 @@
 //! Provides both group-level (GroupRenderingControl) and per-speaker
 //! (RenderingControl) volume and mute operations.
 
-use reqwest::Client;
-
 use crate::error::SoapResult;
 use crate::sonos::services::SonosService;
-use crate::sonos::soap::{soap_request, SoapError};
+use crate::sonos::soap::{soap_request, SoapClient, SoapError};
 use crate::sonos::utils::extract_xml_text;
 
 // ─────────────────────────────────────────────────────────────────────────────
@@ -21,7 +19,7 @@ use crate::sonos::utils::extract_xml_text;
 /// # Arguments
 /// * `client` - The HTTP client to use for the request
 /// * `coordinator_ip` - IP address of the group coordinator
-pub async fn get_group_volume(client: &Client, coordinator_ip: &str) -> SoapResult<u8> {
+pub async fn get_group_volume(client: &SoapClient, coordinator_ip: &str) -> SoapResult<u8> {
     let response = soap_request(
         client,
         coordinator_ip,
@@ -45,7 +43,11 @@ pub async fn get_group_volume(client: &Client, coordinator_ip: &str) -> SoapResu
 /// * `client` - The HTTP client to use for the request
 /// * `coordinator_ip` - IP address of the group coordinator
 /// * `volume` - Desired volume level (0-100, values > 100 are clamped)
-pub async fn set_group_volume(client: &Client, coordinator_ip: &str, volume: u8) -> SoapResult<()> {
+pub async fn set_group_volume(
+    client: &SoapClient,
+    coordinator_ip: &str,
+    volume: u8,
+) -> SoapResult<()> {
     let clamped = volume.min(100);
 
     let clamped_str = clamped.to_string();
@@ -73,7 +75,7 @@ pub async fn set_group_volume(client: &Client, coordinator_ip: &str, volume: u8)
 ///
 /// # Returns
 /// `true` if the group is muted, `false` otherwise
-pub async fn get_group_mute(client: &Client, coordinator_ip: &str) -> SoapResult<bool> {
+pub async fn get_group_mute(client: &SoapClient, coordinator_ip: &str) -> SoapResult<bool> {
     let response = soap_request(
         client,
         coordinator_ip,
@@ -94,7 +96,11 @@ pub async fn get_group_mute(client: &Client, coordinator_ip: &str) -> SoapResult
 /// * `client` - The HTTP client to use for the request
 /// * `coordinator_ip` - IP address of the group coordinator
 /// * `mute` - `true` to mute, `false` to unmute
-pub async fn set_group_mute(client: &Client, coordinator_ip: &str, mute: bool) -> SoapResult<()> {
+pub async fn set_group_mute(
+    client: &SoapClient,
+    coordinator_ip: &str,
+    mute: bool,
+) -> SoapResult<()> {
     soap_request(
         client,
         coordinator_ip,
@@ -122,7 +128,7 @@ pub async fn set_group_mute(client: &Client, coordinator_ip: &str, mute: bool) -
 /// # Arguments
 /// * `client` - The HTTP client to use for the request
 /// * `speaker_ip` - IP address of the speaker
-pub async fn get_speaker_volume(client: &Client, speaker_ip: &str) -> SoapResult<u8> {
+pub async fn get_speaker_volume(client: &SoapClient, speaker_ip: &str) -> SoapResult<u8> {
     let response = soap_request(
         client,
         speaker_ip,
@@ -148,7 +154,11 @@ pub async fn get_speaker_volume(client: &Client, speaker_ip: &str) -> SoapResult
 /// * `client` - The HTTP client to use for the request
 /// * `speaker_ip` - IP address of the speaker
 /// * `volume` - Desired volume level (0-100, values > 100 are clamped)
-pub async fn set_speaker_volume(client: &Client, speaker_ip: &str, volume: u8) -> SoapResult<()> {
+pub async fn set_speaker_volume(
+    client: &SoapClient,
+    speaker_ip: &str,
+    volume: u8,
+) -> SoapResult<()> {
     let clamped = volume.min(100);
 
     let clamped_str = clamped.to_string();
@@ -176,7 +186,7 @@ pub async fn set_speaker_volume(client: &Client, speaker_ip: &str, volume: u8) -
 /// # Arguments
 /// * `client` - The HTTP client to use for the request
 /// * `speaker_ip` - IP address of the speaker
-pub async fn get_speaker_mute(client: &Client, speaker_ip: &str) -> SoapResult<bool> {
+pub async fn get_speaker_mute(client: &SoapClient, speaker_ip: &str) -> SoapResult<bool> {
     let response = soap_request(
         client,
         speaker_ip,
@@ -201,7 +211,7 @@ pub async fn get_speaker_mute(client: &Client, speaker_ip: &str) -> SoapResult<b
 /// * `client` - The HTTP client to use for the request
 /// * `speaker_ip` - IP address of the speaker
 /// * `mute` - `true` to mute, `false` to unmute
-pub async fn set_speaker_mute(client: &Client, speaker_ip: &str, mute: bool) -> SoapResult<()> {
+pub async fn set_speaker_mute(client: &SoapClient, speaker_ip: &str, mute: bool) -> SoapResult<()> {
     soap_request(
         client,
         speaker_ip,
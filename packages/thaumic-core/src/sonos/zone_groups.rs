@@ -3,17 +3,18 @@
 //! Handles parsing ZoneGroupState XML into structured `ZoneGroup` data
 //! and fetching topology from Sonos speakers via SOAP.
 
+use std::collections::HashSet;
+
 use quick_xml::events::Event;
 use quick_xml::reader::Reader;
-use reqwest::Client;
 
 use crate::error::SoapResult;
 use crate::sonos::services::SonosService;
-use crate::sonos::soap::soap_request;
-use crate::sonos::types::{ZoneGroup, ZoneGroupMember};
+use crate::sonos::soap::{soap_request, SoapClient};
+use crate::sonos::types::{Bond, BondKind, DeviceRole, ZoneGroup, ZoneGroupMember};
 use crate::sonos::utils::{
     extract_ip_from_location, extract_model_from_icon, extract_xml_text, get_channel_role,
-    get_xml_attr,
+    get_stereo_channel_role, get_xml_attr,
 };
 
 /// Parses ZoneGroupState XML into a vector of ZoneGroup structures.
@@ -26,12 +27,18 @@ use crate::sonos::utils::{
 ///   as they cannot play audio.
 /// - Groups containing only Zone Bridges are excluded entirely.
 ///
+/// Satellites (surrounds and subwoofers bonded to another speaker) and
+/// invisible members are kept, marked with a non-playable [`DeviceRole`].
+/// Members of a bonded set (stereo pair, home theater) are linked to the
+/// set's primary through their [`Bond`].
+///
 /// # Member Details
 /// Each member includes:
 /// - `uuid`: Unique identifier (RINCON_xxx format)
 /// - `ip`: Local IP address
 /// - `zone_name`: User-configured room name
 /// - `model`: Device model or channel role (for home theater setups)
+/// - `role`: Whether the member can be cast to directly
 pub fn parse_zone_group_xml(xml: &str) -> Vec<ZoneGroup> {
     let mut groups = Vec::new();
     let mut reader = Reader::from_str(xml);
@@ -41,6 +48,8 @@ pub fn parse_zone_group_xml(xml: &str) -> Vec<ZoneGroup> {
     let mut current_coordinator_uuid: Option<String> = None;
     let mut current_group_id = String::new();
     let mut current_members: Vec<ZoneGroupMember> = Vec::new();
+    // Channel map each member belongs to, parallel to `current_members`
+    let mut current_bond_maps: Vec<Option<(BondKind, String)>> = Vec::new();
     let mut coordinator_ip: Option<String> = None;
     let mut coordinator_zone_name: Option<String> = None;
     let mut ht_sat_chan_map: Option<String> = None;
@@ -54,11 +63,12 @@ pub fn parse_zone_group_xml(xml: &str) -> Vec<ZoneGroup> {
                         current_group_id = get_xml_attr(e, b"ID").unwrap_or_default();
                         current_coordinator_uuid = get_xml_attr(e, b"Coordinator");
                         current_members.clear();
+                        current_bond_maps.clear();
                         coordinator_ip = None;
                         coordinator_zone_name = None;
                         ht_sat_chan_map = None;
                     }
-                    b"ZoneGroupMember" | b"Satellite" => {
+                    tag @ (b"ZoneGroupMember" | b"Satellite") => {
                         // Skip Zone Bridges - they can't play audio
                         if get_xml_attr(e, b"IsZoneBridge").as_deref() == Some("1") {
                             continue;
@@ -94,10 +104,45 @@ pub fn parse_zone_group_xml(xml: &str) -> Vec<ZoneGroup> {
                             ht_sat_chan_map = get_xml_attr(e, b"HTSatChanMapSet");
                         }
 
-                        // Determine model: prefer channel role, then icon, then fallback
-                        let model = ht_sat_chan_map
+                        // Satellites carry the home theater's channel map too,
+                        // which matters when the theater isn't the coordinator
+                        let ht_map = get_xml_attr(e, b"HTSatChanMapSet").or_else(|| {
+                            ht_sat_chan_map
+                                .clone()
+                                .filter(|map| get_channel_role(map, &uuid).is_some())
+                        });
+                        let stereo_map = get_xml_attr(e, b"ChannelMapSet");
+
+                        let channel_role = ht_map
                             .as_ref()
                             .and_then(|map| get_channel_role(map, &uuid))
+                            .or_else(|| {
+                                stereo_map
+                                    .as_ref()
+                                    .and_then(|map| get_stereo_channel_role(map, &uuid))
+                            });
+
+                        let bond_map = match (ht_map, stereo_map) {
+                            (Some(map), _) => Some((BondKind::HomeTheater, map)),
+                            (None, Some(map)) if map.contains("LF,LF") => {
+                                Some((BondKind::StereoPair, map))
+                            }
+                            (None, Some(map)) => Some((BondKind::WithSubwoofer, map)),
+                            (None, None) => None,
+                        };
+
+                        // Satellites and hidden members play through their
+                        // primary speaker and can't be cast to themselves
+                        let is_bonded = tag == b"Satellite"
+                            || get_xml_attr(e, b"Invisible").as_deref() == Some("1");
+                        let role = match channel_role.as_deref() {
+                            Some("Subwoofer") => DeviceRole::Subwoofer,
+                            _ if is_bonded => DeviceRole::Satellite,
+                            _ => DeviceRole::Player,
+                        };
+
+                        // Determine model: prefer channel role, then icon, then fallback
+                        let model = channel_role
                             .or_else(|| {
                                 get_xml_attr(e, b"Icon")
                                     .map(|i| extract_model_from_icon(&i))
@@ -105,12 +150,24 @@ pub fn parse_zone_group_xml(xml: &str) -> Vec<ZoneGroup> {
                             })
                             .unwrap_or_else(|| "Speaker".to_string());
 
+                        // S2 firmware reports the Ethernet link; a speaker
+                        // with Wi-Fi switched off must be wired too
+                        let wired = match get_xml_attr(e, b"EthLink").as_deref() {
+                            Some(link) => Some(link == "1"),
+                            None => (get_xml_attr(e, b"WifiEnabled").as_deref() == Some("0"))
+                                .then_some(true),
+                        };
+
                         current_members.push(ZoneGroupMember {
                             uuid,
                             ip,
                             zone_name,
                             model,
+                            role,
+                            bond: None,
+                            wired,
                         });
+                        current_bond_maps.push(bond_map);
                     }
                     _ => {}
                 }
@@ -121,38 +178,15 @@ pub fn parse_zone_group_xml(xml: &str) -> Vec<ZoneGroup> {
                     (current_coordinator_uuid.take(), coordinator_ip.take())
                 {
                     if !current_members.is_empty() {
-                        // Build group name from member zone names:
-                        // - Single room / stereo pair / HT: use coordinator's name
-                        // - Multi-room (x-rincon join): combine unique zone names
-                        let group_name = coordinator_zone_name.take().map_or_else(
-                            || {
-                                let mut unique_names: Vec<&str> = Vec::new();
-                                for m in &current_members {
-                                    if !unique_names.contains(&m.zone_name.as_str()) {
-                                        unique_names.push(&m.zone_name);
-                                    }
-                                }
-                                unique_names.join(", ")
-                            },
-                            |coord_name| {
-                                let mut other_names: Vec<&str> = Vec::new();
-                                for m in &current_members {
-                                    let name = m.zone_name.as_str();
-                                    if name != coord_name.as_str() && !other_names.contains(&name) {
-                                        other_names.push(name);
-                                    }
-                                }
-                                if other_names.is_empty() {
-                                    coord_name
-                                } else {
-                                    format!("{}, {}", coord_name, other_names.join(", "))
-                                }
-                            },
-                        );
+                        resolve_bonds(&mut current_members, &current_bond_maps);
+
+                        let rooms = group_rooms(&current_members, coordinator_zone_name.take());
 
                         groups.push(ZoneGroup {
                             id: current_group_id.clone(),
-                            name: group_name,
+                            name: rooms.join(", "),
+                            label: group_label(&rooms),
+                            room_count: rooms.len(),
                             coordinator_uuid: coord_uuid,
                             coordinator_ip: coord_ip,
                             members: std::mem::take(&mut current_members),
@@ -173,6 +207,100 @@ pub fn parse_zone_group_xml(xml: &str) -> Vec<ZoneGroup> {
     groups
 }
 
+/// Recomputes a group's name, label and room count from its members, after
+/// some were removed.
+pub(crate) fn relabel_group(group: &mut ZoneGroup) {
+    let coordinator_zone_name = group
+        .members
+        .iter()
+        .find(|m| m.uuid == group.coordinator_uuid)
+        .map(|m| m.zone_name.clone());
+    let rooms = group_rooms(&group.members, coordinator_zone_name);
+    group.name = rooms.join(", ");
+    group.label = group_label(&rooms);
+    group.room_count = rooms.len();
+}
+
+/// Replaces one household's groups in a topology spanning several.
+///
+/// A speaker only reports the groups of its own Sonos household, so a
+/// `GetZoneGroupState` result or topology event replaces the groups sharing
+/// a member with it and leaves the other households' groups alone.
+pub(crate) fn merge_household(groups: &[ZoneGroup], household: Vec<ZoneGroup>) -> Vec<ZoneGroup> {
+    let members: HashSet<&str> = household
+        .iter()
+        .flat_map(|g| g.members.iter().map(|m| m.uuid.as_str()))
+        .collect();
+    let others: Vec<ZoneGroup> = groups
+        .iter()
+        .filter(|g| !g.members.iter().any(|m| members.contains(m.uuid.as_str())))
+        .cloned()
+        .collect();
+    household.into_iter().chain(others).collect()
+}
+
+/// Returns the group's room names, coordinator's room first.
+///
+/// Members of a room (stereo pair, home theater satellites) share its zone
+/// name, so each room appears once.
+fn group_rooms(members: &[ZoneGroupMember], coordinator_zone_name: Option<String>) -> Vec<String> {
+    let mut rooms: Vec<String> = coordinator_zone_name.into_iter().collect();
+    for member in members {
+        if !rooms.contains(&member.zone_name) {
+            rooms.push(member.zone_name.clone());
+        }
+    }
+    rooms
+}
+
+/// Builds a Sonos-style label: the first room, plus how many others.
+fn group_label(rooms: &[String]) -> String {
+    match rooms {
+        [] => String::new(),
+        [room] => room.clone(),
+        [first, others @ ..] => format!("{} + {}", first, others.len()),
+    }
+}
+
+/// Links bonded members to their set's primary.
+///
+/// The primary is the set's playable member; if the topology doesn't say
+/// (e.g., the primary is outside this group), the member playing front-left.
+fn resolve_bonds(members: &mut [ZoneGroupMember], bond_maps: &[Option<(BondKind, String)>]) {
+    let bonds: Vec<Option<Bond>> = bond_maps
+        .iter()
+        .map(|bond_map| {
+            let (kind, map) = bond_map.as_ref()?;
+            let channels: Vec<(&str, &str)> = map
+                .split(';')
+                .filter_map(|mapping| mapping.split_once(':'))
+                .collect();
+            let primary_uuid = channels
+                .iter()
+                .map(|(uuid, _)| *uuid)
+                .find(|uuid| {
+                    members
+                        .iter()
+                        .any(|m| m.uuid == *uuid && m.role.is_playable())
+                })
+                .or_else(|| {
+                    channels
+                        .iter()
+                        .find(|(_, channels)| channels.starts_with("LF"))
+                        .map(|(uuid, _)| *uuid)
+                })?;
+            Some(Bond {
+                kind: *kind,
+                primary_uuid: primary_uuid.to_string(),
+            })
+        })
+        .collect();
+
+    for (member, bond) in members.iter_mut().zip(bonds) {
+        member.bond = bond;
+    }
+}
+
 /// Fetches the current zone groups from a Sonos speaker and parses the topology.
 ///
 /// # Arguments
@@ -181,7 +309,7 @@ pub fn parse_zone_group_xml(xml: &str) -> Vec<ZoneGroup> {
 ///
 /// # Returns
 /// A vector of `ZoneGroup` representing the current topology
-pub async fn get_zone_groups(client: &Client, ip: &str) -> SoapResult<Vec<ZoneGroup>> {
+pub async fn get_zone_groups(client: &SoapClient, ip: &str) -> SoapResult<Vec<ZoneGroup>> {
     let response = soap_request(
         client,
         ip,
@@ -223,6 +351,30 @@ mod tests {
         format!("<ZoneGroups>{}</ZoneGroups>", groups.join(""))
     }
 
+    #[test]
+    fn merges_households() {
+        let kitchen = member_xml("RINCON_KITCHEN", "192.168.1.10", "Kitchen");
+        let den = member_xml("RINCON_DEN", "192.168.1.11", "Den");
+        let cabin = member_xml("RINCON_CABIN", "10.8.0.20", "Cabin");
+        let groups = parse_zone_group_xml(&zone_groups_xml(&[
+            group_xml("G1", "RINCON_KITCHEN", std::slice::from_ref(&kitchen)),
+            group_xml("G2", "RINCON_DEN", std::slice::from_ref(&den)),
+            group_xml("G3", "RINCON_CABIN", &[cabin]),
+        ]));
+
+        // Kitchen and Den joined in the first household
+        let joined = parse_zone_group_xml(&zone_groups_xml(&[group_xml(
+            "G1",
+            "RINCON_KITCHEN",
+            &[kitchen, den],
+        )]));
+        let merged = merge_household(&groups, joined);
+
+        let ids: Vec<&str> = merged.iter().map(|g| g.id.as_str()).collect();
+        assert_eq!(ids, vec!["G1", "G3"]);
+        assert_eq!(merged[0].members.len(), 2);
+    }
+
     #[test]
     fn single_speaker_uses_zone_name() {
         let xml = zone_groups_xml(&[group_xml(
@@ -234,6 +386,8 @@ mod tests {
         let groups = parse_zone_group_xml(&xml);
         assert_eq!(groups.len(), 1);
         assert_eq!(groups[0].name, "Kitchen");
+        assert_eq!(groups[0].label, "Kitchen");
+        assert_eq!(groups[0].room_count, 1);
     }
 
     #[test]
@@ -285,6 +439,7 @@ mod tests {
         let groups = parse_zone_group_xml(&xml);
         assert_eq!(groups.len(), 1);
         assert_eq!(groups[0].name, "Office, Kitchen");
+        assert_eq!(groups[0].label, "Office + 1");
     }
 
     #[test]
@@ -302,6 +457,8 @@ mod tests {
         let groups = parse_zone_group_xml(&xml);
         assert_eq!(groups.len(), 1);
         assert_eq!(groups[0].name, "Kitchen, Office, Bedroom");
+        assert_eq!(groups[0].label, "Kitchen + 2");
+        assert_eq!(groups[0].room_count, 3);
     }
 
     #[test]
@@ -321,5 +478,141 @@ mod tests {
         let groups = parse_zone_group_xml(&xml);
         assert_eq!(groups.len(), 1);
         assert_eq!(groups[0].name, "Living Room");
+        assert_eq!(groups[0].label, "Living Room");
+        assert_eq!(groups[0].room_count, 1);
+    }
+
+    #[test]
+    fn reads_the_ethernet_link() {
+        let member = |uuid: &str, ip: &str, attrs: &str| {
+            format!(
+                r#"<ZoneGroupMember UUID="{uuid}" Location="http://{ip}:1400/xml/device_description.xml" ZoneName="{uuid}" {attrs} />"#
+            )
+        };
+        let xml = zone_groups_xml(&[group_xml(
+            "G1",
+            "RINCON_A",
+            &[
+                member("RINCON_A", "192.168.1.10", r#"EthLink="1" WifiEnabled="1""#),
+                member("RINCON_B", "192.168.1.11", r#"EthLink="0" WifiEnabled="1""#),
+                member("RINCON_C", "192.168.1.12", r#"WifiEnabled="0""#),
+                member("RINCON_D", "192.168.1.13", ""),
+            ],
+        )]);
+
+        let groups = parse_zone_group_xml(&xml);
+        let wired: Vec<_> = groups[0].members.iter().map(|m| m.wired).collect();
+        assert_eq!(wired, vec![Some(true), Some(false), Some(true), None]);
+    }
+
+    #[test]
+    fn classifies_member_roles() {
+        let chan_map = "RINCON_BAR:LF,RF;RINCON_SUB:SW;RINCON_LS:LR";
+        let satellite = |uuid: &str, ip: &str| {
+            format!(
+                r#"<Satellite UUID="{uuid}" Location="http://{ip}:1400/xml/device_description.xml" ZoneName="Living Room" HTSatChanMapSet="{chan_map}" Invisible="1" />"#
+            )
+        };
+        let xml = zone_groups_xml(&[
+            format!(
+                r#"<ZoneGroup Coordinator="RINCON_KITCHEN" ID="G1">{}<ZoneGroupMember UUID="RINCON_BAR" Location="http://192.168.1.10:1400/xml/device_description.xml" ZoneName="Living Room" HTSatChanMapSet="{chan_map}">{}{}</ZoneGroupMember></ZoneGroup>"#,
+                member_xml("RINCON_KITCHEN", "192.168.1.20", "Kitchen"),
+                satellite("RINCON_SUB", "192.168.1.11"),
+                satellite("RINCON_LS", "192.168.1.12"),
+            ),
+            group_xml(
+                "G2",
+                "RINCON_BOOST",
+                &[r#"<ZoneGroupMember UUID="RINCON_BOOST" Location="http://192.168.1.30:1400/xml/device_description.xml" ZoneName="BOOST" IsZoneBridge="1" />"#.to_string()],
+            ),
+        ]);
+
+        let groups = parse_zone_group_xml(&xml);
+        assert_eq!(groups.len(), 1, "bridge-only group is excluded");
+        let roles: Vec<(&str, &str, DeviceRole)> = groups[0]
+            .members
+            .iter()
+            .map(|m| (m.uuid.as_str(), m.model.as_str(), m.role))
+            .collect();
+        assert_eq!(
+            roles,
+            vec![
+                ("RINCON_KITCHEN", "Speaker", DeviceRole::Player),
+                ("RINCON_BAR", "Soundbar", DeviceRole::Player),
+                ("RINCON_SUB", "Subwoofer", DeviceRole::Subwoofer),
+                ("RINCON_LS", "Surround Left", DeviceRole::Satellite),
+            ]
+        );
+
+        let home_theater = Some(Bond {
+            kind: BondKind::HomeTheater,
+            primary_uuid: "RINCON_BAR".to_string(),
+        });
+        assert_eq!(groups[0].members[0].bond, None);
+        for member in &groups[0].members[1..] {
+            assert_eq!(member.bond, home_theater, "{}", member.uuid);
+        }
+    }
+
+    #[test]
+    fn links_stereo_pair_to_visible_primary() {
+        let chan_map = "RINCON_LEFT:LF,LF;RINCON_RIGHT:RF,RF";
+        let xml = zone_groups_xml(&[group_xml(
+            "G1",
+            "RINCON_RIGHT",
+            &[
+                format!(
+                    r#"<ZoneGroupMember UUID="RINCON_LEFT" Location="http://192.168.1.10:1400/xml/device_description.xml" ZoneName="Office" ChannelMapSet="{chan_map}" Invisible="1" />"#
+                ),
+                format!(
+                    r#"<ZoneGroupMember UUID="RINCON_RIGHT" Location="http://192.168.1.11:1400/xml/device_description.xml" ZoneName="Office" ChannelMapSet="{chan_map}" />"#
+                ),
+            ],
+        )]);
+
+        let groups = parse_zone_group_xml(&xml);
+        let members = &groups[0].members;
+        assert_eq!(
+            (members[0].model.as_str(), members[0].role),
+            ("Left", DeviceRole::Satellite)
+        );
+        assert_eq!(
+            (members[1].model.as_str(), members[1].role),
+            ("Right", DeviceRole::Player)
+        );
+        for member in members {
+            assert_eq!(
+                member.bond,
+                Some(Bond {
+                    kind: BondKind::StereoPair,
+                    primary_uuid: "RINCON_RIGHT".to_string(),
+                })
+            );
+        }
+    }
+
+    #[test]
+    fn classifies_roles_by_model_name() {
+        assert_eq!(
+            DeviceRole::from_model_name(Some("Sonos Boost")),
+            DeviceRole::Bridge
+        );
+        assert_eq!(
+            DeviceRole::from_model_name(Some("Sonos Bridge")),
+            DeviceRole::Bridge
+        );
+        assert_eq!(
+            DeviceRole::from_model_name(Some("Sonos Sub Mini")),
+            DeviceRole::Subwoofer
+        );
+        assert_eq!(
+            DeviceRole::from_model_name(Some("Sonos Sub")),
+            DeviceRole::Subwoofer
+        );
+        assert_eq!(
+            DeviceRole::from_model_name(Some("Sonos Era 100")),
+            DeviceRole::Player
+        );
+        assert_eq!(DeviceRole::from_model_name(None), DeviceRole::Player);
     }
 }
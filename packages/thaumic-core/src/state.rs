@@ -1,10 +1,13 @@
 //! Core application state types.
 //!
 //! Provides configuration ([`Config`], [`StreamingConfig`]), Sonos runtime
-//! state ([`SonosState`]), and manual speaker persistence ([`ManualSpeakerConfig`]).
+//! state ([`SonosState`]), manual speaker persistence ([`ManualSpeakerConfig`])
+//! and the speaker ignore list ([`IgnoredSpeakers`]).
 
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::hash::Hash;
+use std::net::Ipv4Addr;
+use std::path::{Path, PathBuf};
 use std::sync::OnceLock;
 
 use dashmap::DashMap;
@@ -12,7 +15,18 @@ use parking_lot::{Mutex, RwLock};
 use serde::{Deserialize, Serialize};
 use serde_json::json;
 
-use crate::sonos::types::{TransportState, ZoneGroup};
+use crate::api::ingest::IngestConfig;
+use crate::enrichment::EnrichmentConfig;
+use crate::error::{ThaumicError, ThaumicResult};
+use crate::events::EventLogConfig;
+use crate::protocol_constants::{MAX_STREAMING_BUFFER_MS, MIN_STREAMING_BUFFER_MS};
+use crate::scrobble::ScrobbleConfig;
+use crate::sonos::compat::S1Speakers;
+use crate::sonos::discovery::normalize_uuid;
+use crate::sonos::types::{SpeakerMove, TransportState, ZoneGroup, ZoneGroupMember};
+use crate::sonos::zone_groups::relabel_group;
+use crate::storage;
+use crate::utils::validate_speaker_host;
 
 /// Configuration for audio streaming behavior.
 ///
@@ -29,6 +43,141 @@ pub struct StreamingConfig {
 
     /// Capacity of the broadcast channel for audio frames.
     pub channel_capacity: usize,
+
+    /// Disable Nagle's algorithm on accepted connections.
+    ///
+    /// Keeps each audio write on the wire immediately instead of waiting for
+    /// the previous segment's ACK. Only disable for debugging.
+    #[serde(default = "default_tcp_nodelay")]
+    pub tcp_nodelay: bool,
+
+    /// Idle seconds before TCP keepalive probes start (`None` disables keepalive).
+    ///
+    /// Some routers drop connections that look idle, e.g. during long
+    /// silence periods with a compressed codec. Keepalive keeps NAT and
+    /// firewall state fresh and detects dead speakers sooner.
+    #[serde(default = "default_tcp_keepalive_secs")]
+    pub tcp_keepalive_secs: Option<u64>,
+
+    /// Seconds between keepalive probes once probing has started.
+    #[serde(default = "default_tcp_keepalive_interval_secs")]
+    pub tcp_keepalive_interval_secs: u64,
+
+    /// Socket send buffer size in bytes (`None` keeps the OS default).
+    #[serde(default)]
+    pub send_buffer_bytes: Option<usize>,
+
+    /// Largest PCM cadence buffer, in milliseconds, that ingest jitter may
+    /// grow a speaker's queue to. The stream's own streaming buffer is the
+    /// lower limit.
+    #[serde(default = "default_max_jitter_buffer_ms")]
+    pub max_jitter_buffer_ms: u64,
+
+    /// Seconds a session survives another source taking over its speaker
+    /// (`None` ends it right away).
+    ///
+    /// If the other source stops within this window, the stream resumes on
+    /// the speaker by itself.
+    #[serde(default = "default_takeover_resume_secs")]
+    pub takeover_resume_secs: Option<u64>,
+
+    /// Seconds a stream may go without any speaker pulling it before it is
+    /// stopped (`None` keeps it until its source ends).
+    ///
+    /// Covers speakers that disconnect and never come back, which would
+    /// otherwise leave the stream running as long as the tab keeps casting.
+    #[serde(default = "default_idle_listener_secs")]
+    pub idle_listener_secs: Option<u64>,
+
+    /// How the coordinator of a synchronized cast is chosen.
+    #[serde(default)]
+    pub coordinator: CoordinatorPreferences,
+
+    /// What happens when a speaker is stopped from the Sonos app while its
+    /// stream keeps running.
+    #[serde(default)]
+    pub external_stop: ExternalStopPolicy,
+
+    /// Seconds clients have to confirm resuming a speaker stopped from the
+    /// Sonos app under [`ExternalStopPolicy::Ask`].
+    #[serde(default = "default_external_stop_confirm_secs")]
+    pub external_stop_confirm_secs: u64,
+}
+
+/// What to do when a speaker is stopped from outside (usually the Sonos app)
+/// while the stream it was playing keeps running.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ExternalStopPolicy {
+    /// Leave the speaker stopped; clients decide whether to end the session.
+    #[default]
+    Stop,
+    /// Start the stream on the speaker again right away.
+    Resume,
+    /// Hold the session and ask clients, which resume it with
+    /// `POST /api/playback/resume`. Unconfirmed sessions end.
+    Ask,
+}
+
+/// Preferences for choosing which speaker coordinates a synchronized cast.
+///
+/// The coordinator fetches the stream and relays it to the rest of the
+/// group, so a weak Wi-Fi speaker there degrades every room. Without
+/// preferences a speaker that already coordinates a Sonos group is picked,
+/// then the first selected speaker.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CoordinatorPreferences {
+    /// Prefer speakers connected by Ethernet over Wi-Fi ones, and Wi-Fi
+    /// speakers with a stronger signal over weaker ones.
+    pub prefer_wired: bool,
+    /// Models to prefer, best first, matched case-insensitively against the
+    /// `model` reported for each speaker (e.g. `["arc", "five"]`).
+    pub model_priority: Vec<String>,
+    /// Fixed coordinators for particular sets of speakers, checked first.
+    pub pins: Vec<CoordinatorPin>,
+}
+
+/// A fixed coordinator for one set of speakers.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CoordinatorPin {
+    /// The speakers, by IP or room name. The pin applies when exactly these
+    /// speakers are cast to together, in any order.
+    pub speakers: Vec<String>,
+    /// The speaker to coordinate them, by IP or room name.
+    pub coordinator: String,
+}
+
+fn default_tcp_nodelay() -> bool {
+    true
+}
+
+fn default_tcp_keepalive_secs() -> Option<u64> {
+    Some(30)
+}
+
+fn default_tcp_keepalive_interval_secs() -> u64 {
+    10
+}
+
+fn default_max_jitter_buffer_ms() -> u64 {
+    MAX_STREAMING_BUFFER_MS
+}
+
+fn default_takeover_resume_secs() -> Option<u64> {
+    Some(60)
+}
+
+fn default_idle_listener_secs() -> Option<u64> {
+    Some(600)
+}
+
+fn default_external_stop_confirm_secs() -> u64 {
+    60
+}
+
+fn default_subnet_scan() -> bool {
+    true
 }
 
 impl StreamingConfig {
@@ -46,6 +195,16 @@ impl StreamingConfig {
             max_concurrent_streams,
             buffer_frames,
             channel_capacity,
+            tcp_nodelay: default_tcp_nodelay(),
+            tcp_keepalive_secs: default_tcp_keepalive_secs(),
+            tcp_keepalive_interval_secs: default_tcp_keepalive_interval_secs(),
+            send_buffer_bytes: None,
+            max_jitter_buffer_ms: default_max_jitter_buffer_ms(),
+            takeover_resume_secs: default_takeover_resume_secs(),
+            idle_listener_secs: default_idle_listener_secs(),
+            coordinator: CoordinatorPreferences::default(),
+            external_stop: ExternalStopPolicy::default(),
+            external_stop_confirm_secs: default_external_stop_confirm_secs(),
         };
         config.validate()?;
         Ok(config)
@@ -64,6 +223,31 @@ impl StreamingConfig {
                 "channel_capacity must be >= 1 (broadcast::channel panics on 0)".to_string(),
             );
         }
+        if self.tcp_keepalive_secs == Some(0) {
+            return Err("tcp_keepalive_secs must be >= 1 (use None to disable)".to_string());
+        }
+        if self.tcp_keepalive_interval_secs == 0 {
+            return Err("tcp_keepalive_interval_secs must be >= 1".to_string());
+        }
+        if self.send_buffer_bytes == Some(0) {
+            return Err("send_buffer_bytes must be >= 1 (use None for OS default)".to_string());
+        }
+        if !(MIN_STREAMING_BUFFER_MS..=MAX_STREAMING_BUFFER_MS).contains(&self.max_jitter_buffer_ms)
+        {
+            return Err(format!(
+                "max_jitter_buffer_ms must be between {} and {}",
+                MIN_STREAMING_BUFFER_MS, MAX_STREAMING_BUFFER_MS
+            ));
+        }
+        if self.takeover_resume_secs == Some(0) {
+            return Err("takeover_resume_secs must be >= 1 (use None to disable)".to_string());
+        }
+        if self.idle_listener_secs == Some(0) {
+            return Err("idle_listener_secs must be >= 1 (use None to disable)".to_string());
+        }
+        if self.external_stop_confirm_secs == 0 {
+            return Err("external_stop_confirm_secs must be >= 1".to_string());
+        }
         Ok(())
     }
 }
@@ -74,10 +258,32 @@ impl Default for StreamingConfig {
             max_concurrent_streams: 10,
             buffer_frames: 50,
             channel_capacity: 500,
+            tcp_nodelay: default_tcp_nodelay(),
+            tcp_keepalive_secs: default_tcp_keepalive_secs(),
+            tcp_keepalive_interval_secs: default_tcp_keepalive_interval_secs(),
+            send_buffer_bytes: None,
+            max_jitter_buffer_ms: default_max_jitter_buffer_ms(),
+            takeover_resume_secs: default_takeover_resume_secs(),
+            idle_listener_secs: default_idle_listener_secs(),
+            coordinator: CoordinatorPreferences::default(),
+            external_stop: ExternalStopPolicy::default(),
+            external_stop_confirm_secs: default_external_stop_confirm_secs(),
         }
     }
 }
 
+/// Where speakers deliver GENA event notifications.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum GenaListener {
+    /// NOTIFY requests arrive on the main HTTP port at `/sonos/gena`, so a
+    /// firewall only needs one port opened.
+    #[default]
+    Shared,
+    /// A separate listener on this port accepts NOTIFY requests only.
+    Dedicated(u16),
+}
+
 /// Configuration for the Thaumic Cast application.
 ///
 /// All fields have sensible defaults.
@@ -86,23 +292,84 @@ pub struct Config {
     // Server
     /// Preferred port for the HTTP/WS server (0 = auto-allocate).
     pub preferred_port: u16,
+    /// Where speakers send GENA event notifications.
+    #[serde(default)]
+    pub gena_listener: GenaListener,
+    /// Serve the remote control web UI at `/ui`.
+    #[serde(default)]
+    pub web_ui: bool,
+    /// Extra origins allowed to call the API from a browser, on top of
+    /// loopback pages (see [`crate::api::cors`]).
+    #[serde(default)]
+    pub allowed_origins: Vec<String>,
+    /// Audio upload from apps other than the extension.
+    #[serde(default)]
+    pub ingest: IngestConfig,
 
     // Discovery
     /// Interval for refreshing the Sonos topology (seconds).
     pub topology_refresh_interval: u64,
+    /// Skip SSDP and mDNS entirely and only use manually added speaker IPs
+    /// and those found on `remote_subnets`, for networks where multicast
+    /// doesn't reach the speakers.
+    #[serde(default)]
+    pub unicast_only: bool,
+    /// Probe every address of the local subnets when SSDP and mDNS find no
+    /// speakers (ignored with `unicast_only`).
+    #[serde(default = "default_subnet_scan")]
+    pub subnet_scan: bool,
+    /// IPv4 ranges the subnet scan probes (e.g. `192.168.1.0/24`, at most
+    /// /20 each). Empty scans the /24 of each local interface.
+    #[serde(default)]
+    pub subnet_scan_cidrs: Vec<String>,
+    /// IPv4 ranges on other VLANs or routed subnets (e.g. `10.0.20.0/24`,
+    /// at most /20 each) searched by unicast M-SEARCH and description
+    /// probes on every discovery, for speakers multicast can't reach.
+    /// More can be saved at runtime.
+    #[serde(default)]
+    pub remote_subnets: Vec<String>,
+    /// Speakers to leave out of discovery, topology and events, by UUID
+    /// (`RINCON_...`) or IPv4 address. More can be added at runtime.
+    #[serde(default)]
+    pub ignored_speakers: Vec<String>,
 
     // Streaming
     /// Streaming configuration.
     #[serde(default)]
     pub streaming: StreamingConfig,
+
+    // Metadata
+    /// Online lookup of missing track details.
+    #[serde(default)]
+    pub enrichment: EnrichmentConfig,
+    /// Scrobbling of cast tracks.
+    #[serde(default)]
+    pub scrobbling: ScrobbleConfig,
+
+    // Events
+    /// JSON event logs (file, syslog).
+    #[serde(default)]
+    pub event_log: EventLogConfig,
 }
 
 impl Default for Config {
     fn default() -> Self {
         Self {
             preferred_port: 0,
+            gena_listener: GenaListener::default(),
+            web_ui: false,
+            allowed_origins: Vec::new(),
+            ingest: IngestConfig::default(),
             topology_refresh_interval: 30,
+            unicast_only: false,
+            subnet_scan: default_subnet_scan(),
+            subnet_scan_cidrs: Vec::new(),
+            remote_subnets: Vec::new(),
+            ignored_speakers: Vec::new(),
             streaming: StreamingConfig::default(),
+            enrichment: EnrichmentConfig::default(),
+            scrobbling: ScrobbleConfig::default(),
+            event_log: EventLogConfig::default(),
         }
     }
 }
@@ -111,6 +378,83 @@ impl Default for Config {
 // Sonos Runtime State
 // ─────────────────────────────────────────────────────────────────────────────
 
+/// Last known address of every speaker, by UUID, and when it was seen there.
+///
+/// Runtime state is keyed by IP, which SOAP and GENA calls need. The UUID is
+/// what stays the same when DHCP hands a speaker another address, so
+/// discovery reports every speaker here and gets back the ones that moved,
+/// whose IP-keyed state it then re-keys.
+#[derive(Debug, Default)]
+pub struct SpeakerAddresses {
+    by_uuid: DashMap<String, String>,
+    /// When each speaker was last discovered, in ms since the Unix epoch.
+    seen_at: DashMap<String, u64>,
+}
+
+impl SpeakerAddresses {
+    /// Returns the last known address of a speaker.
+    #[must_use]
+    pub fn ip_of(&self, uuid: &str) -> Option<String> {
+        self.by_uuid.get(uuid).map(|ip| ip.value().clone())
+    }
+
+    /// Returns the UUID of the speaker last seen at an address.
+    #[must_use]
+    pub fn uuid_at(&self, ip: &str) -> Option<String> {
+        self.by_uuid
+            .iter()
+            .find(|entry| entry.value() == ip)
+            .map(|entry| entry.key().clone())
+    }
+
+    /// Returns when the speaker last seen at an address was discovered, in
+    /// ms since the Unix epoch.
+    #[must_use]
+    pub fn last_seen(&self, ip: &str) -> Option<u64> {
+        let uuid = self.uuid_at(ip)?;
+        self.seen_at.get(&uuid).map(|at| *at)
+    }
+
+    /// Records where speakers were seen at `now_ms`, as `(uuid, ip)` pairs,
+    /// and returns those known at another address before.
+    pub fn observe<'a>(
+        &self,
+        speakers: impl IntoIterator<Item = (&'a str, &'a str)>,
+        now_ms: u64,
+    ) -> Vec<SpeakerMove> {
+        let mut moves = Vec::new();
+        for (uuid, ip) in speakers {
+            self.seen_at.insert(uuid.to_string(), now_ms);
+            let old_ip = self.by_uuid.insert(uuid.to_string(), ip.to_string());
+            if let Some(old_ip) = old_ip.filter(|old_ip| old_ip != ip) {
+                moves.push(SpeakerMove {
+                    uuid: uuid.to_string(),
+                    old_ip,
+                    new_ip: ip.to_string(),
+                });
+            }
+        }
+        moves
+    }
+}
+
+/// Moves the entries of speakers that changed address to their new IP.
+///
+/// All entries are taken out before any is put back, so speakers swapping
+/// addresses don't overwrite each other.
+pub(crate) fn rekey_by_ip<V>(map: &DashMap<String, V>, moves: &[SpeakerMove]) {
+    let moved: Vec<(String, V)> = moves
+        .iter()
+        .filter_map(|m| {
+            map.remove(&m.old_ip)
+                .map(|(_, value)| (m.new_ip.clone(), value))
+        })
+        .collect();
+    for (ip, value) in moved {
+        map.insert(ip, value);
+    }
+}
+
 /// Runtime state for discovered Sonos groups and their statuses.
 ///
 /// # Concurrency design
@@ -134,6 +478,15 @@ pub struct SonosState {
     /// Map of coordinator IP to their fixed volume status.
     /// True indicates volume cannot be adjusted (line-level output).
     pub group_volume_fixed: DashMap<String, bool>,
+    /// Map of speaker IP to its Wi-Fi signal strength (RSSI), for speakers
+    /// whose firmware reports it.
+    pub signal_strengths: DashMap<String, u8>,
+    /// Speakers kept out of `groups` and discovery results.
+    pub ignored_speakers: IgnoredSpeakers,
+    /// Last known address of every discovered speaker, by UUID.
+    pub speaker_addresses: SpeakerAddresses,
+    /// Speakers running S1 software, whose streams are limited.
+    pub s1_speakers: S1Speakers,
 }
 
 impl SonosState {
@@ -173,6 +526,19 @@ impl SonosState {
             .retain(|ip, _| valid_speaker_ips.contains(ip));
         self.group_volume_fixed
             .retain(|ip, _| valid_speaker_ips.contains(ip));
+        self.signal_strengths
+            .retain(|ip, _| valid_speaker_ips.contains(ip));
+    }
+
+    /// Moves the state of speakers that changed address to their new IP.
+    ///
+    /// Entries at a new address that the move doesn't replace are kept.
+    pub fn rekey_speakers(&self, moves: &[SpeakerMove]) {
+        rekey_by_ip(&self.transport_states, moves);
+        rekey_by_ip(&self.group_volumes, moves);
+        rekey_by_ip(&self.group_mutes, moves);
+        rekey_by_ip(&self.group_volume_fixed, moves);
+        rekey_by_ip(&self.signal_strengths, moves);
     }
 
     /// Looks up a coordinator's UUID by their IP address.
@@ -201,6 +567,40 @@ impl SonosState {
             .map(|m| m.uuid.clone())
     }
 
+    /// Looks up a speaker by its IP address.
+    ///
+    /// Returns None if the IP isn't a member of any known group.
+    #[must_use]
+    pub fn get_member_by_ip(&self, ip: &str) -> Option<ZoneGroupMember> {
+        self.groups
+            .read()
+            .iter()
+            .flat_map(|g| g.members.iter())
+            .find(|m| m.ip == ip)
+            .cloned()
+    }
+
+    /// Returns true if the speaker at `ip` runs S1 software.
+    #[must_use]
+    pub fn is_s1_speaker(&self, ip: &str) -> bool {
+        self.get_member_by_ip(ip)
+            .map(|member| member.uuid)
+            .or_else(|| self.speaker_addresses.uuid_at(ip))
+            .is_some_and(|uuid| self.s1_speakers.contains(&uuid))
+    }
+
+    /// Returns the IP of the primary speaker a non-playable member is bonded
+    /// to (the other half of a stereo pair, or a home theater's soundbar).
+    #[must_use]
+    pub fn get_bond_primary_ip(&self, ip: &str) -> Option<String> {
+        let groups = self.groups.read();
+        let members = || groups.iter().flat_map(|g| g.members.iter());
+        let bond = members().find(|m| m.ip == ip)?.bond.as_ref()?;
+        members()
+            .find(|m| m.uuid == bond.primary_uuid && m.role.is_playable())
+            .map(|m| m.ip.clone())
+    }
+
     /// Returns the coordinator UUID if the speaker is a slave in an existing group.
     ///
     /// This is used to capture original group membership before joining a streaming group,
@@ -237,6 +637,193 @@ where
         .collect()
 }
 
+// ─────────────────────────────────────────────────────────────────────────────
+// Ignored Speakers (persisted)
+// ─────────────────────────────────────────────────────────────────────────────
+
+const IGNORED_SPEAKERS_FILE: &str = "ignored_speakers.json";
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct IgnoredSpeakersFile {
+    speakers: Vec<String>,
+}
+
+/// Speakers left out of discovery, topology and events, by UUID or IP.
+///
+/// For shared buildings, where the neighbours' speakers answer discovery
+/// too. Entries come from [`Config::ignored_speakers`] and from the API; the
+/// latter are saved to `ignored_speakers.json` in the data directory.
+#[derive(Debug, Default)]
+pub struct IgnoredSpeakers {
+    /// Entries from the configuration, which the API can't remove.
+    configured: RwLock<Vec<String>>,
+    /// Entries added through the API.
+    added: RwLock<Vec<String>>,
+    data_dir: RwLock<Option<PathBuf>>,
+}
+
+impl IgnoredSpeakers {
+    /// Sets the entries from the configuration, skipping invalid ones.
+    pub fn set_configured(&self, entries: &[String]) {
+        *self.configured.write() = entries
+            .iter()
+            .filter_map(|entry| match normalize_ignored_speaker(entry) {
+                Ok(entry) => Some(entry),
+                Err(e) => {
+                    log::warn!("[IgnoredSpeakers] Skipping {:?}: {}", entry, e);
+                    None
+                }
+            })
+            .collect();
+    }
+
+    /// Sets the directory entries added through the API are persisted to,
+    /// and loads them.
+    pub fn set_data_dir(&self, dir: impl AsRef<Path>) {
+        let dir = dir.as_ref().to_path_buf();
+        let loaded = match std::fs::read_to_string(dir.join(IGNORED_SPEAKERS_FILE)) {
+            Ok(contents) => serde_json::from_str(&contents).unwrap_or_else(|e| {
+                log::warn!(
+                    "[IgnoredSpeakers] Ignoring unreadable {}: {}",
+                    IGNORED_SPEAKERS_FILE,
+                    e
+                );
+                IgnoredSpeakersFile::default()
+            }),
+            Err(_) => IgnoredSpeakersFile::default(),
+        };
+        *self.added.write() = loaded.speakers;
+        *self.data_dir.write() = Some(dir);
+    }
+
+    /// Returns every entry, configured ones first.
+    pub fn entries(&self) -> Vec<String> {
+        let mut entries = self.configured.read().clone();
+        for entry in self.added.read().iter() {
+            if !entries.contains(entry) {
+                entries.push(entry.clone());
+            }
+        }
+        entries
+    }
+
+    /// Returns whether a speaker is ignored by its UUID or IP.
+    pub fn is_ignored(&self, uuid: &str, ip: &str) -> bool {
+        let configured = self.configured.read();
+        let added = self.added.read();
+        if configured.is_empty() && added.is_empty() {
+            return false;
+        }
+        let uuid = normalize_uuid(uuid);
+        let matches = |entry: &String| entry == ip || entry.eq_ignore_ascii_case(&uuid);
+        configured.iter().any(matches) || added.iter().any(matches)
+    }
+
+    /// Ignores a speaker by UUID or IP, saving the list.
+    ///
+    /// Returns the entry as stored.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the entry is neither a Sonos UUID nor an IPv4
+    /// address, no data directory is set, or saving fails.
+    pub fn add(&self, entry: &str) -> ThaumicResult<String> {
+        let entry = normalize_ignored_speaker(entry).map_err(ThaumicError::InvalidRequest)?;
+        let dir = self.require_data_dir()?;
+        let mut added = self.added.write();
+        if !added.contains(&entry) {
+            added.push(entry.clone());
+            save_ignored_speakers(&dir, &added)
+                .map_err(|e| ThaumicError::SaveFailed(e.to_string()))?;
+            log::info!("[IgnoredSpeakers] Ignoring {}", entry);
+        }
+        Ok(entry)
+    }
+
+    /// Stops ignoring a speaker added through the API, saving the list.
+    ///
+    /// Returns whether the entry was in the list.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the entry comes from the configuration, no data
+    /// directory is set, or saving fails.
+    pub fn remove(&self, entry: &str) -> ThaumicResult<bool> {
+        // Unparseable entries are matched as typed, so stray ones can go
+        let entry = normalize_ignored_speaker(entry).unwrap_or_else(|_| entry.to_string());
+        if self.configured.read().contains(&entry) {
+            return Err(ThaumicError::InvalidRequest(format!(
+                "{} is ignored in the configuration file",
+                entry
+            )));
+        }
+        let dir = self.require_data_dir()?;
+        let mut added = self.added.write();
+        let len_before = added.len();
+        added.retain(|e| *e != entry);
+        if added.len() == len_before {
+            return Ok(false);
+        }
+        save_ignored_speakers(&dir, &added).map_err(|e| ThaumicError::SaveFailed(e.to_string()))?;
+        log::info!("[IgnoredSpeakers] No longer ignoring {}", entry);
+        Ok(true)
+    }
+
+    /// Drops groups coordinated by an ignored speaker and ignored members of
+    /// the others.
+    pub fn filter_groups(&self, groups: Vec<ZoneGroup>) -> Vec<ZoneGroup> {
+        groups
+            .into_iter()
+            .filter(|g| !self.is_ignored(&g.coordinator_uuid, &g.coordinator_ip))
+            .map(|mut group| {
+                let before = group.members.len();
+                group.members.retain(|m| !self.is_ignored(&m.uuid, &m.ip));
+                if group.members.len() != before {
+                    relabel_group(&mut group);
+                }
+                group
+            })
+            .collect()
+    }
+
+    fn require_data_dir(&self) -> ThaumicResult<PathBuf> {
+        self.data_dir.read().clone().ok_or_else(|| {
+            ThaumicError::DataDirNotConfigured("ignored speakers can't be saved".into())
+        })
+    }
+}
+
+/// Normalizes an ignore list entry: an IPv4 address, or a speaker UUID
+/// without its `uuid:` prefix and device suffixes.
+fn normalize_ignored_speaker(entry: &str) -> Result<String, String> {
+    let entry = entry.trim();
+    if let Ok(ip) = entry.parse::<Ipv4Addr>() {
+        return Ok(ip.to_string());
+    }
+    let uuid = normalize_uuid(entry).to_ascii_uppercase();
+    if uuid.len() > "RINCON_".len() && uuid.starts_with("RINCON_") {
+        Ok(uuid)
+    } else {
+        Err(format!(
+            "{:?} is neither a speaker UUID (RINCON_...) nor an IPv4 address",
+            entry
+        ))
+    }
+}
+
+/// Writes the ignore list atomically (temp file + rename), keeping it in
+/// memory only while the data directory is full or read-only.
+fn save_ignored_speakers(dir: &Path, speakers: &[String]) -> std::io::Result<()> {
+    let file = IgnoredSpeakersFile {
+        speakers: speakers.to_vec(),
+    };
+    storage::tolerate(storage::write_atomic(
+        dir,
+        IGNORED_SPEAKERS_FILE,
+        serde_json::to_string_pretty(&file)?,
+    ))
+}
+
 // ─────────────────────────────────────────────────────────────────────────────
 // Manual Speaker Configuration (persisted)
 // ─────────────────────────────────────────────────────────────────────────────
@@ -251,6 +838,14 @@ fn config_lock() -> &'static Mutex<()> {
     CONFIG_LOCK.get_or_init(|| Mutex::new(()))
 }
 
+/// Configs that couldn't be saved because their data directory is full or
+/// read-only, returned by [`ManualSpeakerConfig::load`] until a save works.
+static UNSAVED_CONFIGS: OnceLock<Mutex<HashMap<PathBuf, ManualSpeakerConfig>>> = OnceLock::new();
+
+fn unsaved_configs() -> &'static Mutex<HashMap<PathBuf, ManualSpeakerConfig>> {
+    UNSAVED_CONFIGS.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
 /// Persisted configuration for manually added speakers.
 ///
 /// Used when auto-discovery fails due to network configuration (VPN, firewall, etc.).
@@ -266,6 +861,9 @@ impl ManualSpeakerConfig {
     ///
     /// Returns default (empty) config if file doesn't exist or is invalid.
     pub fn load(app_data_dir: &std::path::Path) -> Self {
+        if let Some(unsaved) = unsaved_configs().lock().get(app_data_dir) {
+            return unsaved.clone();
+        }
         let path = app_data_dir.join(MANUAL_SPEAKERS_FILE);
         match std::fs::read_to_string(&path) {
             Ok(contents) => serde_json::from_str(&contents).unwrap_or_default(),
@@ -276,17 +874,23 @@ impl ManualSpeakerConfig {
     /// Saves manual speaker configuration to the app data directory.
     ///
     /// Uses atomic write (temp file + rename) to prevent corruption on crash.
-    /// Creates the directory if it doesn't exist.
+    /// Creates the directory if it doesn't exist. While the directory is full
+    /// or read-only, the config is kept in memory instead and `Ok` returned.
     pub fn save(&self, app_data_dir: &std::path::Path) -> std::io::Result<()> {
-        std::fs::create_dir_all(app_data_dir)?;
-        let path = app_data_dir.join(MANUAL_SPEAKERS_FILE);
-        let temp_path = app_data_dir.join("manual_speakers.json.tmp");
         let contents = serde_json::to_string_pretty(self)?;
-
-        // Write to temp file first
-        std::fs::write(&temp_path, contents)?;
-        // Atomic rename (on most filesystems)
-        std::fs::rename(&temp_path, &path)
+        match storage::write_atomic(app_data_dir, MANUAL_SPEAKERS_FILE, contents) {
+            Ok(()) => {
+                unsaved_configs().lock().remove(app_data_dir);
+                Ok(())
+            }
+            Err(e) if storage::issue_of(&e).is_some() => {
+                unsaved_configs()
+                    .lock()
+                    .insert(app_data_dir.to_path_buf(), self.clone());
+                Ok(())
+            }
+            Err(e) => Err(e),
+        }
     }
 
     /// Adds an IP address if not already present.
@@ -335,6 +939,105 @@ impl ManualSpeakerConfig {
         }
         Ok(())
     }
+
+    /// Atomically adds several addresses to the config file.
+    ///
+    /// Returns how many weren't present yet; saves only if any were added.
+    pub fn add_ips_atomic(
+        app_data_dir: &std::path::Path,
+        ips: impl IntoIterator<Item = String>,
+    ) -> std::io::Result<usize> {
+        let _guard = config_lock().lock();
+        let mut config = Self::load(app_data_dir);
+        let added = ips
+            .into_iter()
+            .filter(|ip| config.add_ip(ip.clone()))
+            .count();
+        if added > 0 {
+            config.save(app_data_dir)?;
+        }
+        Ok(added)
+    }
+}
+
+/// Manual and ignored speakers, exported as JSON from one installation and
+/// imported into another (e.g. from the desktop app to a headless server).
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SpeakerSetup {
+    /// Manual speaker IPs and hostnames.
+    #[serde(default)]
+    pub manual_speakers: Vec<String>,
+    /// Ignored speaker UUIDs and IPs.
+    #[serde(default)]
+    pub ignored_speakers: Vec<String>,
+}
+
+/// Outcome of importing a [`SpeakerSetup`].
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SpeakerSetupImport {
+    /// Manual speakers that weren't configured yet.
+    pub manual_speakers_added: usize,
+    /// Ignored speakers that weren't ignored yet.
+    pub ignored_speakers_added: usize,
+    /// Entries left out because they aren't valid addresses or UUIDs.
+    pub skipped: Vec<String>,
+}
+
+impl SpeakerSetup {
+    /// Collects the manual speakers in `app_data_dir` and every ignored
+    /// speaker, configured ones included.
+    pub fn export(app_data_dir: &Path, ignored: &IgnoredSpeakers) -> Self {
+        Self {
+            manual_speakers: ManualSpeakerConfig::load(app_data_dir).speaker_ips,
+            ignored_speakers: ignored.entries(),
+        }
+    }
+
+    /// Adds the entries to the existing ones; nothing is removed.
+    ///
+    /// Manual speakers are validated but not probed, since the speakers of
+    /// another installation may not be reachable until the next refresh.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if saving either list fails.
+    pub fn import(
+        self,
+        app_data_dir: &Path,
+        ignored: &IgnoredSpeakers,
+    ) -> ThaumicResult<SpeakerSetupImport> {
+        let mut skipped = Vec::new();
+        let hosts: Vec<String> = self
+            .manual_speakers
+            .into_iter()
+            .filter_map(|entry| match validate_speaker_host(entry.trim()) {
+                Ok(host) => Some(host),
+                Err(_) => {
+                    skipped.push(entry);
+                    None
+                }
+            })
+            .collect();
+        let manual_speakers_added = ManualSpeakerConfig::add_ips_atomic(app_data_dir, hosts)
+            .map_err(|e| ThaumicError::SaveFailed(e.to_string()))?;
+
+        let before = ignored.entries().len();
+        for entry in self.ignored_speakers {
+            match ignored.add(&entry) {
+                Ok(_) => {}
+                Err(ThaumicError::InvalidRequest(_)) => skipped.push(entry),
+                Err(e) => return Err(e),
+            }
+        }
+
+        Ok(SpeakerSetupImport {
+            manual_speakers_added,
+            ignored_speakers_added: ignored.entries().len() - before,
+            skipped,
+        })
+    }
 }
 
 #[cfg(test)]
@@ -354,6 +1057,42 @@ mod tests {
         assert!(StreamingConfig::new(10, 50, 0).is_err());
     }
 
+    #[test]
+    fn streaming_config_rejects_zero_socket_options() {
+        let mut config = StreamingConfig {
+            tcp_keepalive_secs: Some(0),
+            ..Default::default()
+        };
+        assert!(config.validate().is_err());
+
+        config.tcp_keepalive_secs = None;
+        assert!(config.validate().is_ok());
+
+        config.tcp_keepalive_interval_secs = 0;
+        assert!(config.validate().is_err());
+
+        config.tcp_keepalive_interval_secs = 10;
+        config.send_buffer_bytes = Some(0);
+        assert!(config.validate().is_err());
+
+        config.send_buffer_bytes = None;
+        config.max_jitter_buffer_ms = MAX_STREAMING_BUFFER_MS + 1;
+        assert!(config.validate().is_err());
+
+        config.max_jitter_buffer_ms = MIN_STREAMING_BUFFER_MS;
+        assert!(config.validate().is_ok());
+
+        config.takeover_resume_secs = Some(0);
+        assert!(config.validate().is_err());
+
+        config.takeover_resume_secs = None;
+        config.idle_listener_secs = Some(0);
+        assert!(config.validate().is_err());
+
+        config.idle_listener_secs = None;
+        assert!(config.validate().is_ok());
+    }
+
     #[test]
     fn config_default_is_sensible() {
         let config = Config::default();
@@ -370,7 +1109,9 @@ mod tests {
             let mut groups = state.groups.write();
             *groups = vec![ZoneGroup {
                 id: "group1".to_string(),
-                name: "Living Room".to_string(),
+                name: "Living Room, Kitchen".to_string(),
+                label: "Living Room + 1".to_string(),
+                room_count: 2,
                 coordinator_uuid: "RINCON_LIVING".to_string(),
                 coordinator_ip: "192.168.1.100".to_string(),
                 members: vec![
@@ -379,12 +1120,14 @@ mod tests {
                         ip: "192.168.1.100".to_string(),
                         zone_name: "Living Room".to_string(),
                         model: "One".to_string(),
+                        ..Default::default()
                     },
                     ZoneGroupMember {
                         uuid: "RINCON_KITCHEN".to_string(),
                         ip: "192.168.1.101".to_string(),
                         zone_name: "Kitchen".to_string(),
                         model: "One".to_string(),
+                        ..Default::default()
                     },
                 ],
             }];
@@ -407,6 +1150,8 @@ mod tests {
             *groups = vec![ZoneGroup {
                 id: "group1".to_string(),
                 name: "Living Room".to_string(),
+                label: "Living Room".to_string(),
+                room_count: 1,
                 coordinator_uuid: "RINCON_LIVING".to_string(),
                 coordinator_ip: "192.168.1.100".to_string(),
                 members: vec![ZoneGroupMember {
@@ -414,6 +1159,7 @@ mod tests {
                     ip: "192.168.1.100".to_string(),
                     zone_name: "Living Room".to_string(),
                     model: "One".to_string(),
+                    ..Default::default()
                 }],
             }];
         }
@@ -435,4 +1181,178 @@ mod tests {
             None
         );
     }
+
+    fn member(uuid: &str, ip: &str, zone_name: &str) -> ZoneGroupMember {
+        ZoneGroupMember {
+            uuid: uuid.to_string(),
+            ip: ip.to_string(),
+            zone_name: zone_name.to_string(),
+            model: "One".to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn ignored_speakers_are_filtered_from_groups() {
+        let ignored = IgnoredSpeakers::default();
+        ignored.set_configured(&[
+            "192.168.1.50".to_string(),
+            "uuid:rincon_next_door".to_string(),
+            "not a speaker".to_string(),
+        ]);
+        assert_eq!(ignored.entries(), ["192.168.1.50", "RINCON_NEXT_DOOR"]);
+
+        let groups = vec![
+            ZoneGroup {
+                id: "ours".to_string(),
+                name: "Kitchen, Next Door".to_string(),
+                label: "Kitchen + 1".to_string(),
+                room_count: 2,
+                coordinator_uuid: "RINCON_KITCHEN".to_string(),
+                coordinator_ip: "192.168.1.100".to_string(),
+                members: vec![
+                    member("RINCON_KITCHEN", "192.168.1.100", "Kitchen"),
+                    member("RINCON_NEXT_DOOR", "192.168.1.60", "Next Door"),
+                ],
+            },
+            ZoneGroup {
+                id: "theirs".to_string(),
+                name: "Bedroom".to_string(),
+                label: "Bedroom".to_string(),
+                room_count: 1,
+                coordinator_uuid: "RINCON_BEDROOM".to_string(),
+                coordinator_ip: "192.168.1.50".to_string(),
+                members: vec![member("RINCON_BEDROOM", "192.168.1.50", "Bedroom")],
+            },
+        ];
+
+        let groups = ignored.filter_groups(groups);
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].members.len(), 1);
+        assert_eq!(groups[0].label, "Kitchen");
+        assert_eq!(groups[0].room_count, 1);
+    }
+
+    #[test]
+    fn ignored_speakers_added_at_runtime_persist() {
+        let dir = tempfile::tempdir().unwrap();
+        let ignored = IgnoredSpeakers::default();
+        ignored.set_configured(&["192.168.1.50".to_string()]);
+        assert!(matches!(
+            ignored.add("RINCON_NEXT_DOOR"),
+            Err(ThaumicError::DataDirNotConfigured(_))
+        ));
+
+        ignored.set_data_dir(dir.path());
+        assert_eq!(
+            ignored.add(" rincon_next_door ").unwrap(),
+            "RINCON_NEXT_DOOR"
+        );
+        assert!(ignored.add("kitchen").is_err());
+        assert!(ignored.remove("192.168.1.50").is_err());
+
+        let reloaded = IgnoredSpeakers::default();
+        reloaded.set_data_dir(dir.path());
+        assert!(reloaded.is_ignored("uuid:RINCON_NEXT_DOOR", "192.168.1.60"));
+        assert!(reloaded.remove("RINCON_NEXT_DOOR").unwrap());
+        assert!(!reloaded.remove("RINCON_NEXT_DOOR").unwrap());
+        assert!(!reloaded.is_ignored("RINCON_NEXT_DOOR", "192.168.1.60"));
+    }
+
+    #[test]
+    fn speaker_setup_round_trips() {
+        let source = tempfile::tempdir().unwrap();
+        let source_ignored = IgnoredSpeakers::default();
+        source_ignored.set_data_dir(source.path());
+        source_ignored.add("RINCON_NEXT_DOOR").unwrap();
+        ManualSpeakerConfig::add_ip_atomic(source.path(), "10.8.0.20".to_string()).unwrap();
+
+        let mut setup = SpeakerSetup::export(source.path(), &source_ignored);
+        assert_eq!(setup.manual_speakers, vec!["10.8.0.20"]);
+        assert_eq!(setup.ignored_speakers, vec!["RINCON_NEXT_DOOR"]);
+        setup.manual_speakers.push("Kitchen.LAN".to_string());
+        setup.manual_speakers.push("not a host".to_string());
+
+        let target = tempfile::tempdir().unwrap();
+        let target_ignored = IgnoredSpeakers::default();
+        target_ignored.set_data_dir(target.path());
+        ManualSpeakerConfig::add_ip_atomic(target.path(), "10.8.0.20".to_string()).unwrap();
+
+        let summary = setup.import(target.path(), &target_ignored).unwrap();
+        assert_eq!(
+            summary,
+            SpeakerSetupImport {
+                manual_speakers_added: 1,
+                ignored_speakers_added: 1,
+                skipped: vec!["not a host".to_string()],
+            }
+        );
+        assert_eq!(
+            ManualSpeakerConfig::load(target.path()).speaker_ips,
+            vec!["10.8.0.20", "kitchen.lan"]
+        );
+        assert!(target_ignored.is_ignored("RINCON_NEXT_DOOR", "192.168.1.60"));
+    }
+
+    #[test]
+    fn speaker_addresses_report_moves() {
+        let addresses = SpeakerAddresses::default();
+        let first = [("RINCON_A", "192.168.1.10"), ("RINCON_B", "192.168.1.11")];
+        assert!(addresses.observe(first, 1_000).is_empty());
+        assert_eq!(addresses.last_seen("192.168.1.10"), Some(1_000));
+
+        let moves = addresses.observe([("RINCON_A", "192.168.1.20")], 2_000);
+        assert_eq!(
+            moves,
+            vec![SpeakerMove {
+                uuid: "RINCON_A".into(),
+                old_ip: "192.168.1.10".into(),
+                new_ip: "192.168.1.20".into(),
+            }]
+        );
+        assert_eq!(addresses.ip_of("RINCON_A").as_deref(), Some("192.168.1.20"));
+        assert_eq!(
+            addresses.uuid_at("192.168.1.11").as_deref(),
+            Some("RINCON_B")
+        );
+        assert_eq!(addresses.last_seen("192.168.1.20"), Some(2_000));
+        assert_eq!(addresses.last_seen("192.168.1.11"), Some(1_000));
+        assert_eq!(addresses.last_seen("192.168.1.10"), None);
+    }
+
+    #[test]
+    fn rekey_speakers_handles_swapped_addresses() {
+        let state = SonosState::default();
+        state
+            .transport_states
+            .insert("192.168.1.10".into(), TransportState::Playing);
+        state
+            .transport_states
+            .insert("192.168.1.11".into(), TransportState::Stopped);
+        state.group_volumes.insert("192.168.1.10".into(), 30);
+
+        let swap = |uuid: &str, old_ip: &str, new_ip: &str| SpeakerMove {
+            uuid: uuid.into(),
+            old_ip: old_ip.into(),
+            new_ip: new_ip.into(),
+        };
+        state.rekey_speakers(&[
+            swap("RINCON_A", "192.168.1.10", "192.168.1.11"),
+            swap("RINCON_B", "192.168.1.11", "192.168.1.10"),
+        ]);
+
+        assert_eq!(
+            state.transport_states.get("192.168.1.11").map(|s| *s),
+            Some(TransportState::Playing)
+        );
+        assert_eq!(
+            state.transport_states.get("192.168.1.10").map(|s| *s),
+            Some(TransportState::Stopped)
+        );
+        assert_eq!(
+            state.group_volumes.get("192.168.1.11").map(|v| *v),
+            Some(30)
+        );
+        assert!(!state.group_volumes.contains_key("192.168.1.10"));
+    }
 }
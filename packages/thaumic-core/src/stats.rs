@@ -0,0 +1,397 @@
+//! Persistent casting statistics.
+//!
+//! Counts how often each speaker is cast to, which stream formats are used,
+//! and how many hours are cast per day. Only counters are kept: no track
+//! metadata, tab URLs, or client details are recorded, and nothing leaves the
+//! machine. Counters are persisted to `stats.json` in the app data directory
+//! when one is configured, and kept in memory otherwise.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+
+use crate::storage;
+use crate::stream::{AudioCodec, AudioFormat};
+
+const STATS_FILE: &str = "stats.json";
+
+/// Days of per-day cast time kept on disk.
+const RETENTION_DAYS: u64 = 366;
+
+/// Days covered by the per-day breakdown in [`StatsSummary`].
+const SUMMARY_DAYS: u64 = 30;
+
+const MS_PER_DAY: u64 = 86_400_000;
+const MS_PER_HOUR: f64 = 3_600_000.0;
+
+/// Counters for one speaker.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+struct SpeakerCounters {
+    /// Last known room name.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    name: Option<String>,
+    /// Times playback was started on the speaker.
+    plays: u64,
+    /// Time spent playing a cast stream.
+    cast_ms: u64,
+}
+
+/// On-disk statistics.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+struct StatsData {
+    /// Keyed by speaker IP.
+    speakers: BTreeMap<String, SpeakerCounters>,
+    /// Streams started, keyed by format label.
+    presets: BTreeMap<String, u64>,
+    /// Stream time cast per UTC day, keyed by day number since the epoch.
+    days: BTreeMap<u64, u64>,
+}
+
+impl StatsData {
+    fn merge(&mut self, other: StatsData) {
+        for (ip, counters) in other.speakers {
+            let entry = self.speakers.entry(ip).or_default();
+            entry.plays += counters.plays;
+            entry.cast_ms += counters.cast_ms;
+            if counters.name.is_some() {
+                entry.name = counters.name;
+            }
+        }
+        for (preset, count) in other.presets {
+            *self.presets.entry(preset).or_default() += count;
+        }
+        for (day, ms) in other.days {
+            *self.days.entry(day).or_default() += ms;
+        }
+    }
+}
+
+/// Per-speaker totals.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SpeakerStats {
+    pub ip: String,
+    /// Last known room name.
+    pub name: Option<String>,
+    pub plays: u64,
+    pub hours: f64,
+}
+
+/// Streams started with one format.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PresetStats {
+    /// Format label, e.g. "flac 24-bit 48kHz".
+    pub preset: String,
+    pub streams: u64,
+}
+
+/// Cast time for one day.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DailyStats {
+    /// UTC date (YYYY-MM-DD).
+    pub date: String,
+    pub hours: f64,
+}
+
+/// Aggregated statistics for dashboards.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StatsSummary {
+    pub total_plays: u64,
+    pub total_hours: f64,
+    /// Speakers, most cast to first.
+    pub speakers: Vec<SpeakerStats>,
+    /// Formats, most used first.
+    pub presets: Vec<PresetStats>,
+    /// The last 30 days, oldest first (days without casting included).
+    pub daily: Vec<DailyStats>,
+}
+
+/// Persistent casting counters.
+#[derive(Default)]
+pub struct StatsStore {
+    data: Mutex<StatsData>,
+    data_dir: Mutex<Option<PathBuf>>,
+    /// Set when counters changed since the last flush.
+    dirty: AtomicBool,
+}
+
+impl StatsStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the directory stats are persisted to and loads existing counters.
+    ///
+    /// Anything counted before the directory was set is kept.
+    pub fn set_data_dir(&self, dir: impl AsRef<Path>) {
+        let dir = dir.as_ref().to_path_buf();
+        let mut loaded = load(&dir);
+        {
+            let mut data = self.data.lock();
+            loaded.merge(std::mem::take(&mut *data));
+            *data = loaded;
+        }
+        *self.data_dir.lock() = Some(dir);
+    }
+
+    /// Counts a stream started with the given format.
+    pub(crate) fn record_stream(&self, preset: &str) {
+        *self
+            .data
+            .lock()
+            .presets
+            .entry(preset.to_string())
+            .or_default() += 1;
+        self.dirty.store(true, Ordering::Relaxed);
+    }
+
+    /// Counts playback started on a speaker.
+    pub(crate) fn record_play(&self, speaker_ip: &str, name: Option<String>) {
+        let mut data = self.data.lock();
+        let speaker = data.speakers.entry(speaker_ip.to_string()).or_default();
+        speaker.plays += 1;
+        if name.is_some() {
+            speaker.name = name;
+        }
+        self.dirty.store(true, Ordering::Relaxed);
+    }
+
+    /// Adds time a speaker spent playing a cast stream.
+    pub(crate) fn add_speaker_time(&self, speaker_ip: &str, ms: u64) {
+        if ms == 0 {
+            return;
+        }
+        self.data
+            .lock()
+            .speakers
+            .entry(speaker_ip.to_string())
+            .or_default()
+            .cast_ms += ms;
+        self.dirty.store(true, Ordering::Relaxed);
+    }
+
+    /// Adds stream time to the day containing `now_ms` (Unix millis).
+    pub(crate) fn add_cast_time(&self, now_ms: u64, ms: u64) {
+        if ms == 0 {
+            return;
+        }
+        let today = now_ms / MS_PER_DAY;
+        let mut data = self.data.lock();
+        *data.days.entry(today).or_default() += ms;
+        let oldest = today.saturating_sub(RETENTION_DAYS);
+        data.days.retain(|day, _| *day > oldest);
+        self.dirty.store(true, Ordering::Relaxed);
+    }
+
+    /// Writes counters to disk if they changed and a data directory is set.
+    pub fn flush(&self) -> std::io::Result<()> {
+        let Some(dir) = self.data_dir.lock().clone() else {
+            return Ok(());
+        };
+        if !self.dirty.swap(false, Ordering::Relaxed) {
+            return Ok(());
+        }
+
+        let contents = serde_json::to_string_pretty(&*self.data.lock())?;
+        let result = save(&dir, &contents);
+        if result.is_err() {
+            self.dirty.store(true, Ordering::Relaxed);
+        }
+        result
+    }
+
+    /// Builds a summary as of `now_ms` (Unix millis).
+    pub fn summary(&self, now_ms: u64) -> StatsSummary {
+        let data = self.data.lock();
+
+        let mut speakers: Vec<_> = data
+            .speakers
+            .iter()
+            .map(|(ip, c)| SpeakerStats {
+                ip: ip.clone(),
+                name: c.name.clone(),
+                plays: c.plays,
+                hours: hours(c.cast_ms),
+            })
+            .collect();
+        speakers.sort_by(|a, b| {
+            b.plays
+                .cmp(&a.plays)
+                .then(b.hours.total_cmp(&a.hours))
+                .then_with(|| a.ip.cmp(&b.ip))
+        });
+
+        let mut presets: Vec<_> = data
+            .presets
+            .iter()
+            .map(|(preset, streams)| PresetStats {
+                preset: preset.clone(),
+                streams: *streams,
+            })
+            .collect();
+        presets.sort_by_key(|p| std::cmp::Reverse(p.streams));
+
+        let today = now_ms / MS_PER_DAY;
+        let daily = (today + 1 - SUMMARY_DAYS..=today)
+            .map(|day| DailyStats {
+                date: format_day(day),
+                hours: hours(data.days.get(&day).copied().unwrap_or_default()),
+            })
+            .collect();
+
+        StatsSummary {
+            total_plays: data.speakers.values().map(|c| c.plays).sum(),
+            total_hours: hours(data.days.values().sum()),
+            speakers,
+            presets,
+            daily,
+        }
+    }
+}
+
+/// Returns the label stats use for a stream's format.
+///
+/// Quality presets are chosen in the extension; the server sees the format
+/// they resolve to, so streams are grouped by that.
+pub(crate) fn preset_label(codec: AudioCodec, format: &AudioFormat) -> String {
+    let khz = format.sample_rate as f64 / 1000.0;
+    match codec {
+        AudioCodec::Pcm | AudioCodec::Flac => {
+            format!(
+                "{} {}-bit {}kHz",
+                codec.as_str(),
+                format.bits_per_sample,
+                khz
+            )
+        }
+        AudioCodec::Aac | AudioCodec::Mp3 => format!("{} {}kHz", codec.as_str(), khz),
+    }
+}
+
+fn hours(ms: u64) -> f64 {
+    (ms as f64 / MS_PER_HOUR * 100.0).round() / 100.0
+}
+
+/// Formats a Unix timestamp in milliseconds as a UTC date (`2024-03-01`).
+pub(crate) fn format_date(timestamp_ms: u64) -> String {
+    format_day(timestamp_ms / MS_PER_DAY)
+}
+
+/// Formats a day number since the Unix epoch as a UTC date.
+fn format_day(day: u64) -> String {
+    // Civil-from-days (Howard Hinnant), valid for all dates after 1970
+    let z = day + 719_468;
+    let era = z / 146_097;
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let d = doy - (153 * mp + 2) / 5 + 1;
+    let m = if mp < 10 { mp + 3 } else { mp - 9 };
+    let y = yoe + era * 400 + u64::from(m <= 2);
+    format!("{:04}-{:02}-{:02}", y, m, d)
+}
+
+/// Loads stats from the data directory, starting fresh if missing or invalid.
+fn load(dir: &Path) -> StatsData {
+    match std::fs::read_to_string(dir.join(STATS_FILE)) {
+        Ok(contents) => serde_json::from_str(&contents).unwrap_or_else(|e| {
+            log::warn!("[Stats] Ignoring unreadable {}: {}", STATS_FILE, e);
+            StatsData::default()
+        }),
+        Err(_) => StatsData::default(),
+    }
+}
+
+/// Writes stats atomically (temp file + rename), keeping them in memory only
+/// while the data directory is full or read-only.
+fn save(dir: &Path, contents: &str) -> std::io::Result<()> {
+    storage::tolerate(storage::write_atomic(dir, STATS_FILE, contents))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 2024-03-01T12:00:00Z
+    const NOW_MS: u64 = 1_709_294_400_000;
+
+    #[test]
+    fn formats_utc_dates() {
+        assert_eq!(format_day(0), "1970-01-01");
+        assert_eq!(format_day(NOW_MS / MS_PER_DAY), "2024-03-01");
+        assert_eq!(format_day(NOW_MS / MS_PER_DAY - 1), "2024-02-29");
+    }
+
+    #[test]
+    fn summarizes_most_cast_to_first() {
+        let store = StatsStore::new();
+        store.record_play("10.0.0.1", Some("Kitchen".to_string()));
+        store.record_play("10.0.0.2", Some("Office".to_string()));
+        store.record_play("10.0.0.2", None);
+        store.add_speaker_time("10.0.0.2", 5_400_000);
+        store.record_stream("flac 24-bit 48kHz");
+        store.record_stream("aac 48kHz");
+        store.record_stream("aac 48kHz");
+        store.add_cast_time(NOW_MS, 3_600_000);
+        store.add_cast_time(NOW_MS - MS_PER_DAY, 1_800_000);
+
+        let summary = store.summary(NOW_MS);
+        assert_eq!(summary.total_plays, 3);
+        assert_eq!(summary.total_hours, 1.5);
+        assert_eq!(summary.speakers[0].ip, "10.0.0.2");
+        assert_eq!(summary.speakers[0].name.as_deref(), Some("Office"));
+        assert_eq!(summary.speakers[0].hours, 1.5);
+        assert_eq!(summary.presets[0].preset, "aac 48kHz");
+        assert_eq!(summary.daily.len(), 30);
+        let last = &summary.daily[29];
+        assert_eq!((last.date.as_str(), last.hours), ("2024-03-01", 1.0));
+        assert_eq!(summary.daily[28].hours, 0.5);
+    }
+
+    #[test]
+    fn persists_and_merges_with_existing_file() {
+        let dir = tempfile::tempdir().unwrap();
+
+        let first = StatsStore::new();
+        first.set_data_dir(dir.path());
+        first.record_play("10.0.0.1", None);
+        first.flush().unwrap();
+
+        let second = StatsStore::new();
+        second.record_play("10.0.0.1", None);
+        second.set_data_dir(dir.path());
+        assert_eq!(second.summary(NOW_MS).total_plays, 2);
+    }
+
+    #[test]
+    fn flush_without_data_dir_keeps_counters_in_memory() {
+        let store = StatsStore::new();
+        store.record_stream("mp3 44.1kHz");
+        store.flush().unwrap();
+        assert_eq!(store.summary(NOW_MS).presets.len(), 1);
+    }
+
+    #[test]
+    fn drops_days_past_retention() {
+        let store = StatsStore::new();
+        store.add_cast_time(NOW_MS - (RETENTION_DAYS + 1) * MS_PER_DAY, 1_000);
+        store.add_cast_time(NOW_MS, 1_000);
+        assert_eq!(store.data.lock().days.len(), 1);
+    }
+
+    #[test]
+    fn labels_formats() {
+        let format = AudioFormat::new(48_000, 2, 24);
+        assert_eq!(preset_label(AudioCodec::Flac, &format), "flac 24-bit 48kHz");
+        let format = AudioFormat::new(44_100, 2, 16);
+        assert_eq!(preset_label(AudioCodec::Aac, &format), "aac 44.1kHz");
+    }
+}
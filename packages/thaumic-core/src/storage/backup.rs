@@ -0,0 +1,429 @@
+//! Backup and restore of the data directory.
+//!
+//! A backup is a zip archive of everything set up by hand: manual and
+//! ignored speakers, remote subnets, approved extensions, statistics, uploaded artwork and
+//! its presets, scripts, and the server's configuration file. Caches are left
+//! out; they refill on their own.
+//!
+//! Services keep their state in memory and save it as they go, so restoring
+//! files under them would be undone by the next save. An import is therefore
+//! staged as `pending_restore.zip` and applied by [`apply_pending`] on the
+//! next start, before anything is loaded (and before [`super::migrations`]
+//! upgrades files from older versions). The configuration file is only read
+//! at startup and is replaced right away.
+
+use std::io::{Cursor, Read, Write};
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+use super::{issue_of, write_atomic};
+use crate::error::{ThaumicError, ThaumicResult};
+use crate::utils::now_millis;
+
+/// Backup format written by this version.
+pub const BACKUP_FORMAT_VERSION: u32 = 1;
+
+/// Largest backup accepted for import.
+pub const MAX_BACKUP_BYTES: usize = 64 * 1024 * 1024;
+
+/// Largest total size of the files in an imported backup, so a small
+/// archive can't unpack into gigabytes.
+const MAX_UNPACKED_BYTES: u64 = 256 * 1024 * 1024;
+
+const HEADER_ENTRY: &str = "backup.json";
+const CONFIG_ENTRY: &str = "config.yaml";
+const DATA_PREFIX: &str = "data/";
+const PENDING_FILE: &str = "pending_restore.zip";
+
+/// Files backed up from the data directory.
+const DATA_FILES: &[&str] = &[
+    "manual_speakers.json",
+    "ignored_speakers.json",
+    "remote_subnets.json",
+    "trusted_origins.json",
+    "stats.json",
+    "data_versions.json",
+    "artwork/presets.json",
+];
+
+/// Directories whose files with the given extension are backed up.
+const DATA_DIRS: &[(&str, &str)] = &[("artwork", "jpg"), ("scripts", "rhai")];
+
+/// Describes a backup archive.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BackupHeader {
+    format: u32,
+    /// Version of the app that made the backup.
+    app_version: String,
+    /// Unix timestamp in milliseconds.
+    created_at: u64,
+}
+
+/// What an import restores.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportSummary {
+    /// Data files restored on the next start, relative to the data directory.
+    pub files: Vec<String>,
+    /// Whether the configuration file was replaced.
+    pub config_restored: bool,
+    /// Version of the app that made the backup.
+    pub app_version: String,
+    /// When the backup was made (Unix milliseconds).
+    pub created_at: u64,
+    /// Always true: the restore takes effect on the next start.
+    pub restart_required: bool,
+}
+
+/// Contents of a validated backup.
+struct Backup {
+    header: BackupHeader,
+    /// Data files, relative to the data directory, and their contents.
+    files: Vec<(String, Vec<u8>)>,
+    config: Option<Vec<u8>>,
+}
+
+/// Creates a backup of `data_dir` and the configuration file, if any.
+///
+/// # Errors
+///
+/// Returns an error if a file can't be read or the archive can't be written.
+pub fn export(data_dir: &Path, config_file: Option<&Path>) -> ThaumicResult<Vec<u8>> {
+    write_backup(data_dir, config_file)
+        .map_err(|e| ThaumicError::Internal(format!("Failed to create backup: {}", e)))
+}
+
+fn write_backup(data_dir: &Path, config_file: Option<&Path>) -> std::io::Result<Vec<u8>> {
+    let header = BackupHeader {
+        format: BACKUP_FORMAT_VERSION,
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        created_at: now_millis(),
+    };
+    let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+
+    zip.start_file(HEADER_ENTRY, options)?;
+    zip.write_all(&serde_json::to_vec_pretty(&header)?)?;
+    for file in data_files(data_dir) {
+        let contents = match std::fs::read(data_dir.join(&file)) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+            Err(e) => return Err(e),
+        };
+        zip.start_file(format!("{}{}", DATA_PREFIX, file), options)?;
+        zip.write_all(&contents)?;
+    }
+    if let Some(config_file) = config_file {
+        let contents = std::fs::read(config_file)?;
+        zip.start_file(CONFIG_ENTRY, options)?;
+        zip.write_all(&contents)?;
+    }
+    Ok(zip.finish()?.into_inner())
+}
+
+/// Validates a backup and stages it to be restored on the next start.
+///
+/// The configuration file is replaced right away (keeping the old one as
+/// `<file>.bak`) when `config_file` is given and the backup has one.
+///
+/// # Errors
+///
+/// Returns [`ThaumicError::InvalidRequest`] if the archive isn't a backup
+/// this version can restore, or an error if staging it fails.
+pub fn import(
+    data_dir: &Path,
+    config_file: Option<&Path>,
+    archive: &[u8],
+) -> ThaumicResult<ImportSummary> {
+    let backup = read_backup(archive).map_err(ThaumicError::InvalidRequest)?;
+
+    write_atomic(data_dir, PENDING_FILE, archive).map_err(save_error)?;
+
+    let config_restored = match (config_file, &backup.config) {
+        (Some(path), Some(config)) => {
+            let (Some(dir), Some(name)) = (path.parent(), path.file_name()) else {
+                return Err(ThaumicError::Internal(format!(
+                    "Invalid config file path: {}",
+                    path.display()
+                )));
+            };
+            let name = name.to_string_lossy();
+            if let Ok(current) = std::fs::read(path) {
+                write_atomic(dir, &format!("{}.bak", name), current).map_err(save_error)?;
+            }
+            write_atomic(dir, &name, config).map_err(save_error)?;
+            true
+        }
+        _ => false,
+    };
+
+    log::info!(
+        "[Backup] Staged restore of {} file(s) from a {} backup{}; restart to apply",
+        backup.files.len(),
+        backup.header.app_version,
+        if config_restored {
+            ", configuration replaced"
+        } else {
+            ""
+        }
+    );
+    Ok(ImportSummary {
+        files: backup.files.into_iter().map(|(name, _)| name).collect(),
+        config_restored,
+        app_version: backup.header.app_version,
+        created_at: backup.header.created_at,
+        restart_required: true,
+    })
+}
+
+/// Restores a backup staged by [`import`], if there is one.
+///
+/// Call on startup before services load the data directory. A staged backup
+/// that can't be restored is renamed to `pending_restore.zip.failed` so it
+/// isn't retried on every start.
+///
+/// Returns the restored files.
+pub fn apply_pending(data_dir: &Path) -> Vec<String> {
+    let path = data_dir.join(PENDING_FILE);
+    let Ok(archive) = std::fs::read(&path) else {
+        return Vec::new();
+    };
+
+    let restored = read_backup(&archive).and_then(|backup| {
+        let mut restored = Vec::new();
+        for (name, contents) in backup.files {
+            let target = data_dir.join(&name);
+            let (Some(dir), Some(file_name)) = (target.parent(), target.file_name()) else {
+                continue;
+            };
+            write_atomic(dir, &file_name.to_string_lossy(), contents)
+                .map_err(|e| format!("{}: {}", name, e))?;
+            restored.push(name);
+        }
+        Ok(restored)
+    });
+
+    match restored {
+        Ok(restored) => {
+            if let Err(e) = std::fs::remove_file(&path) {
+                log::warn!("[Backup] Failed to remove {}: {}", PENDING_FILE, e);
+            }
+            log::info!("[Backup] Restored {} file(s) from backup", restored.len());
+            restored
+        }
+        Err(e) => {
+            log::error!("[Backup] Failed to restore backup: {}", e);
+            let _ = std::fs::rename(&path, data_dir.join(format!("{}.failed", PENDING_FILE)));
+            Vec::new()
+        }
+    }
+}
+
+/// Lists the backed-up files present in `data_dir`.
+fn data_files(data_dir: &Path) -> Vec<String> {
+    let mut files: Vec<String> = DATA_FILES.iter().map(|file| file.to_string()).collect();
+    for (dir, extension) in DATA_DIRS {
+        let Ok(entries) = std::fs::read_dir(data_dir.join(dir)) else {
+            continue;
+        };
+        let mut names: Vec<String> = entries
+            .filter_map(|entry| entry.ok()?.file_name().into_string().ok())
+            .filter(|name| valid_file_name(name, extension))
+            .map(|name| format!("{}/{}", dir, name))
+            .collect();
+        names.sort();
+        files.extend(names);
+    }
+    files
+}
+
+/// Whether a backup entry may be restored to `name` in the data directory.
+fn restorable(name: &str) -> bool {
+    DATA_FILES.contains(&name)
+        || DATA_DIRS.iter().any(|(dir, extension)| {
+            name.strip_prefix(dir)
+                .and_then(|rest| rest.strip_prefix('/'))
+                .is_some_and(|file| valid_file_name(file, extension))
+        })
+}
+
+/// Whether `name` is a plain file name with the given extension.
+fn valid_file_name(name: &str, extension: &str) -> bool {
+    name.strip_suffix(extension)
+        .and_then(|stem| stem.strip_suffix('.'))
+        .is_some_and(|stem| {
+            (1..=64).contains(&stem.len())
+                && stem
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        })
+}
+
+/// Reads and validates a backup archive.
+///
+/// Entries this version doesn't restore are skipped, so backups from newer
+/// versions with extra files still restore what they can.
+fn read_backup(archive: &[u8]) -> Result<Backup, String> {
+    let mut zip = ZipArchive::new(Cursor::new(archive))
+        .map_err(|e| format!("Not a backup archive: {}", e))?;
+
+    let mut header = None;
+    let mut files = Vec::new();
+    let mut config = None;
+    let mut unpacked = 0u64;
+    for index in 0..zip.len() {
+        let entry = zip
+            .by_index(index)
+            .map_err(|e| format!("Damaged backup archive: {}", e))?;
+        if entry.is_dir() {
+            continue;
+        }
+        let name = entry.name().to_string();
+        let data_file = name
+            .strip_prefix(DATA_PREFIX)
+            .filter(|file| restorable(file));
+        if name != HEADER_ENTRY && name != CONFIG_ENTRY && data_file.is_none() {
+            log::debug!("[Backup] Skipping unknown entry {}", name);
+            continue;
+        }
+
+        unpacked += entry.size();
+        if unpacked > MAX_UNPACKED_BYTES {
+            return Err("Backup archive unpacks to more than 256 MiB".to_string());
+        }
+        let mut contents = Vec::new();
+        entry
+            .take(MAX_UNPACKED_BYTES)
+            .read_to_end(&mut contents)
+            .map_err(|e| format!("Damaged backup archive: {}", e))?;
+
+        match (name.as_str(), data_file) {
+            (HEADER_ENTRY, _) => {
+                header = Some(
+                    serde_json::from_slice::<BackupHeader>(&contents)
+                        .map_err(|e| format!("Invalid {}: {}", HEADER_ENTRY, e))?,
+                );
+            }
+            (CONFIG_ENTRY, _) => config = Some(contents),
+            (_, Some(file)) => files.push((file.to_string(), contents)),
+            _ => {}
+        }
+    }
+
+    let header = header.ok_or("Not a Thaumic Cast backup (no backup.json)")?;
+    if header.format > BACKUP_FORMAT_VERSION {
+        return Err(format!(
+            "Backup from {} uses format {}, newer than this version supports",
+            header.app_version, header.format
+        ));
+    }
+    Ok(Backup {
+        header,
+        files,
+        config,
+    })
+}
+
+fn save_error(e: std::io::Error) -> ThaumicError {
+    match issue_of(&e) {
+        Some(issue) => ThaumicError::StorageUnavailable(format!("data directory is {}", issue)),
+        None => ThaumicError::SaveFailed(e.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write(dir: &Path, name: &str, contents: &str) {
+        let path = dir.join(name);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, contents).unwrap();
+    }
+
+    #[test]
+    fn restores_a_backup_on_the_next_start() {
+        let old = tempfile::tempdir().unwrap();
+        write(
+            old.path(),
+            "manual_speakers.json",
+            r#"{"speaker_ips":["192.168.1.20"]}"#,
+        );
+        write(old.path(), "artwork/default.jpg", "jpeg");
+        write(old.path(), "scripts/night.rhai", "fn on_event(event) {}");
+        write(old.path(), "speakers.json", "cache");
+        let config = old.path().join("server.yaml");
+        std::fs::write(&config, "bind_port: 49400\n").unwrap();
+
+        let archive = export(old.path(), Some(&config)).unwrap();
+
+        let new = tempfile::tempdir().unwrap();
+        let new_config = new.path().join("server.yaml");
+        std::fs::write(&new_config, "bind_port: 8080\n").unwrap();
+        let summary = import(new.path(), Some(&new_config), &archive).unwrap();
+        assert_eq!(
+            summary.files,
+            vec![
+                "manual_speakers.json",
+                "artwork/default.jpg",
+                "scripts/night.rhai"
+            ]
+        );
+        assert!(summary.config_restored);
+        assert_eq!(
+            std::fs::read_to_string(&new_config).unwrap(),
+            "bind_port: 49400\n"
+        );
+        assert_eq!(
+            std::fs::read_to_string(new.path().join("server.yaml.bak")).unwrap(),
+            "bind_port: 8080\n"
+        );
+        // Data files wait for the restart
+        assert!(!new.path().join("manual_speakers.json").exists());
+
+        assert_eq!(apply_pending(new.path()).len(), 3);
+        assert_eq!(
+            std::fs::read_to_string(new.path().join("artwork/default.jpg")).unwrap(),
+            "jpeg"
+        );
+        assert!(!new.path().join("speakers.json").exists());
+        assert!(!new.path().join(PENDING_FILE).exists());
+        assert!(apply_pending(new.path()).is_empty());
+    }
+
+    #[test]
+    fn rejects_archives_that_arent_backups() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(matches!(
+            import(dir.path(), None, b"not a zip"),
+            Err(ThaumicError::InvalidRequest(_))
+        ));
+
+        let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+        zip.start_file("data/manual_speakers.json", SimpleFileOptions::default())
+            .unwrap();
+        zip.write_all(b"{}").unwrap();
+        let archive = zip.finish().unwrap().into_inner();
+        assert!(matches!(
+            import(dir.path(), None, &archive),
+            Err(ThaumicError::InvalidRequest(_))
+        ));
+        assert!(!dir.path().join(PENDING_FILE).exists());
+    }
+
+    #[test]
+    fn only_known_files_are_restorable() {
+        assert!(restorable("stats.json"));
+        assert!(restorable("artwork/AAC_48k.jpg"));
+        assert!(restorable("scripts/night-mode.rhai"));
+        assert!(!restorable("../outside.json"));
+        assert!(!restorable("artwork/../../etc/passwd.jpg"));
+        assert!(!restorable("scripts/nested/x.rhai"));
+        assert!(!restorable("scripts/.rhai"));
+        assert!(!restorable("speakers.json"));
+    }
+}
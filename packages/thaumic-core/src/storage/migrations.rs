@@ -0,0 +1,343 @@
+//! Format migrations of persisted files.
+//!
+//! Every file in the data directory has a format version, recorded in
+//! `data_versions.json`. Files from before versioning (or without an entry)
+//! are version 1. When a file's format changes, its version in [`FORMATS`]
+//! is bumped and a [`Migration`] from the previous version added to
+//! [`MIGRATIONS`]; on startup [`run`] upgrades older files step by step,
+//! after copying the original to `<file>.v<N>.bak`.
+//!
+//! Missing files are recorded at their current version, since they will be
+//! written in the current format. Files recorded at a version newer than
+//! this build knows (after a downgrade) are left alone.
+
+use std::collections::BTreeMap;
+use std::io;
+use std::path::Path;
+
+use serde_json::Value;
+
+use super::{tolerate, write_atomic};
+
+/// Records the format version of each file.
+const MANIFEST_FILE: &str = "data_versions.json";
+
+/// Persisted files, relative to the data directory, and their current
+/// format versions.
+pub const FORMATS: &[(&str, u32)] = &[
+    ("manual_speakers.json", 1),
+    ("ignored_speakers.json", 1),
+    ("remote_subnets.json", 1),
+    ("trusted_origins.json", 1),
+    ("stats.json", 1),
+    ("speakers.json", 1),
+    ("device_descriptions.json", 1),
+    ("artwork/presets.json", 1),
+];
+
+/// Upgrades of persisted files, each by one version.
+pub const MIGRATIONS: &[Migration] = &[];
+
+/// Upgrade of one file from one format version to the next.
+pub struct Migration {
+    /// File the migration applies to, as listed in [`FORMATS`].
+    pub file: &'static str,
+    /// Version upgraded from; the result is version `from + 1`.
+    pub from: u32,
+    /// What changes, for the log.
+    pub description: &'static str,
+    /// Converts the file's JSON to the next version.
+    pub migrate: fn(Value) -> Result<Value, String>,
+}
+
+/// A file upgraded by [`run`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Upgrade {
+    pub file: &'static str,
+    pub from: u32,
+    pub to: u32,
+}
+
+/// Upgrades the files in `dir` to their current format versions.
+///
+/// A file whose migration fails is left as it was (and recorded at its old
+/// version, so the next start tries again); other files are still upgraded.
+///
+/// # Errors
+///
+/// Fails only if the version manifest can't be written. Writes skipped
+/// because the directory is full or read-only aren't errors: files stay in
+/// their old format until a later start.
+pub fn run(dir: &Path) -> io::Result<Vec<Upgrade>> {
+    run_with(dir, FORMATS, MIGRATIONS)
+}
+
+fn run_with(
+    dir: &Path,
+    formats: &[(&'static str, u32)],
+    migrations: &[Migration],
+) -> io::Result<Vec<Upgrade>> {
+    let mut manifest = load_manifest(dir);
+    let mut upgrades = Vec::new();
+
+    for &(file, current) in formats {
+        let path = dir.join(file);
+        let recorded = manifest.get(file).copied();
+        let Ok(contents) = std::fs::read_to_string(&path) else {
+            // Nothing to upgrade; it will be written in the current format
+            if recorded.map_or(true, |version| version < current) {
+                manifest.insert(file.to_string(), current);
+            }
+            continue;
+        };
+
+        let from = recorded.unwrap_or(1);
+        if from > current {
+            log::warn!(
+                "[Migrations] {} is version {}, newer than this build's {}; leaving it as is",
+                file,
+                from,
+                current
+            );
+            continue;
+        }
+        if from == current {
+            manifest.insert(file.to_string(), current);
+            continue;
+        }
+
+        match upgrade(dir, file, &contents, from, current, migrations) {
+            Ok(()) => {
+                log::info!(
+                    "[Migrations] Upgraded {} from version {} to {}",
+                    file,
+                    from,
+                    current
+                );
+                manifest.insert(file.to_string(), current);
+                upgrades.push(Upgrade {
+                    file,
+                    from,
+                    to: current,
+                });
+            }
+            Err(e) => {
+                log::error!(
+                    "[Migrations] Couldn't upgrade {} from version {}: {}; left unchanged",
+                    file,
+                    from,
+                    e
+                );
+                manifest.insert(file.to_string(), from);
+            }
+        }
+    }
+
+    let contents = serde_json::to_string_pretty(&manifest)?;
+    tolerate(write_atomic(dir, MANIFEST_FILE, contents))?;
+    Ok(upgrades)
+}
+
+/// Backs up a file and runs its migrations from `from` to `to`.
+fn upgrade(
+    dir: &Path,
+    file: &str,
+    contents: &str,
+    from: u32,
+    to: u32,
+    migrations: &[Migration],
+) -> Result<(), String> {
+    let mut value: Value = serde_json::from_str(contents).map_err(|e| e.to_string())?;
+    for version in from..to {
+        let migration = migrations
+            .iter()
+            .find(|m| m.file == file && m.from == version)
+            .ok_or_else(|| format!("no migration from version {}", version))?;
+        log::info!(
+            "[Migrations] {} v{} -> v{}: {}",
+            file,
+            version,
+            version + 1,
+            migration.description
+        );
+        value = (migration.migrate)(value)?;
+    }
+    let upgraded = serde_json::to_string_pretty(&value).map_err(|e| e.to_string())?;
+
+    // The file may live in a subdirectory (artwork/presets.json)
+    let path = dir.join(file);
+    let (parent, name) = match (path.parent(), path.file_name()) {
+        (Some(parent), Some(name)) => (parent, name.to_string_lossy().into_owned()),
+        _ => return Err("invalid file name".to_string()),
+    };
+    write_atomic(parent, &backup_name(&name, from), contents)
+        .map_err(|e| format!("backup failed: {}", e))?;
+    write_atomic(parent, &name, upgraded).map_err(|e| e.to_string())
+}
+
+fn backup_name(file: &str, version: u32) -> String {
+    format!("{}.v{}.bak", file, version)
+}
+
+fn load_manifest(dir: &Path) -> BTreeMap<String, u32> {
+    match std::fs::read_to_string(dir.join(MANIFEST_FILE)) {
+        Ok(contents) => serde_json::from_str(&contents).unwrap_or_else(|e| {
+            log::warn!("[Migrations] Ignoring unreadable {}: {}", MANIFEST_FILE, e);
+            BTreeMap::new()
+        }),
+        Err(_) => BTreeMap::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const FILE: &str = "manual_speakers.json";
+
+    /// v1 `{"speaker_ips": [...]}` -> v2 `{"speakers": [{"ip": ...}]}`.
+    fn to_objects(mut value: Value) -> Result<Value, String> {
+        let ips = value
+            .get_mut("speaker_ips")
+            .map(Value::take)
+            .ok_or("missing speaker_ips")?;
+        let speakers: Vec<Value> = ips
+            .as_array()
+            .ok_or("speaker_ips isn't a list")?
+            .iter()
+            .map(|ip| json!({ "ip": ip }))
+            .collect();
+        Ok(json!({ "speakers": speakers }))
+    }
+
+    /// v2 -> v3: adds a `label` to every speaker.
+    fn add_labels(mut value: Value) -> Result<Value, String> {
+        for speaker in value["speakers"].as_array_mut().ok_or("no speakers")? {
+            speaker["label"] = Value::Null;
+        }
+        Ok(value)
+    }
+
+    const STEPS: &[Migration] = &[
+        Migration {
+            file: FILE,
+            from: 1,
+            description: "IPs become objects",
+            migrate: to_objects,
+        },
+        Migration {
+            file: FILE,
+            from: 2,
+            description: "speakers get labels",
+            migrate: add_labels,
+        },
+    ];
+
+    fn read_json(path: &Path) -> Value {
+        serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn upgrades_unversioned_files_with_a_backup() {
+        let dir = tempfile::tempdir().unwrap();
+        let original = r#"{"speaker_ips": ["192.168.1.20"]}"#;
+        std::fs::write(dir.path().join(FILE), original).unwrap();
+
+        let upgrades = run_with(dir.path(), &[(FILE, 3)], STEPS).unwrap();
+        assert_eq!(
+            upgrades,
+            vec![Upgrade {
+                file: FILE,
+                from: 1,
+                to: 3
+            }]
+        );
+        assert_eq!(
+            read_json(&dir.path().join(FILE)),
+            json!({ "speakers": [{ "ip": "192.168.1.20", "label": null }] })
+        );
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join("manual_speakers.json.v1.bak")).unwrap(),
+            original
+        );
+        assert_eq!(
+            read_json(&dir.path().join(MANIFEST_FILE)),
+            json!({ FILE: 3 })
+        );
+
+        // Already current: nothing to do
+        assert!(run_with(dir.path(), &[(FILE, 3)], STEPS)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn missing_files_are_recorded_as_current() {
+        let dir = tempfile::tempdir().unwrap();
+        run_with(dir.path(), &[(FILE, 3)], STEPS).unwrap();
+
+        // Written later in the current format, it must not be migrated again
+        let current = r#"{"speakers": []}"#;
+        std::fs::write(dir.path().join(FILE), current).unwrap();
+        assert!(run_with(dir.path(), &[(FILE, 3)], STEPS)
+            .unwrap()
+            .is_empty());
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join(FILE)).unwrap(),
+            current
+        );
+    }
+
+    #[test]
+    fn failed_migrations_leave_the_file_alone() {
+        let dir = tempfile::tempdir().unwrap();
+        let original = r#"{"unexpected": true}"#;
+        std::fs::write(dir.path().join(FILE), original).unwrap();
+
+        assert!(run_with(dir.path(), &[(FILE, 3)], STEPS)
+            .unwrap()
+            .is_empty());
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join(FILE)).unwrap(),
+            original
+        );
+        assert_eq!(
+            read_json(&dir.path().join(MANIFEST_FILE)),
+            json!({ FILE: 1 })
+        );
+    }
+
+    #[test]
+    fn leaves_files_from_newer_versions_alone() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join(MANIFEST_FILE),
+            json!({ FILE: 5 }).to_string(),
+        )
+        .unwrap();
+        std::fs::write(dir.path().join(FILE), "{}").unwrap();
+
+        assert!(run_with(dir.path(), &[(FILE, 3)], STEPS)
+            .unwrap()
+            .is_empty());
+        assert_eq!(
+            read_json(&dir.path().join(MANIFEST_FILE)),
+            json!({ FILE: 5 })
+        );
+    }
+
+    #[test]
+    fn every_format_has_its_migrations() {
+        for &(file, current) in FORMATS {
+            for from in 1..current {
+                assert!(
+                    MIGRATIONS.iter().any(|m| m.file == file && m.from == from),
+                    "{} has no migration from version {}",
+                    file,
+                    from
+                );
+            }
+        }
+    }
+}
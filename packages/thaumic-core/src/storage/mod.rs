@@ -0,0 +1,236 @@
+//! Data directory write health.
+//!
+//! Persisted files (manual and ignored speakers, approvals, statistics,
+//! artwork presets, caches) are written through [`write_atomic`], which
+//! notices when a directory fills up or turns read-only. Such a directory is
+//! marked degraded: later writes to it are skipped, so services keep their
+//! changes in memory instead of failing on every update, until
+//! [`StorageMonitor`](crate::services::StorageMonitor) finds it writable
+//! again.
+//!
+//! File format upgrades between releases are handled by [`migrations`],
+//! backups of the whole directory by [`backup`].
+
+pub mod backup;
+pub mod migrations;
+
+use std::collections::HashMap;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use parking_lot::Mutex;
+use serde::Serialize;
+
+/// Size of the probe file written to check a directory, large enough that a
+/// nearly full disk fails it.
+const PROBE_BYTES: usize = 64 * 1024;
+
+/// Name of the probe file, removed right after writing.
+const PROBE_FILE: &str = ".write-probe";
+
+/// Why a data directory can't be written to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum StorageIssue {
+    /// The disk (or the user's quota) is full.
+    Full,
+    /// The file system is mounted read-only, or the directory isn't ours to
+    /// write.
+    ReadOnly,
+}
+
+impl std::fmt::Display for StorageIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Full => write!(f, "full"),
+            Self::ReadOnly => write!(f, "read-only"),
+        }
+    }
+}
+
+/// Error payload of writes skipped because the directory is degraded.
+#[derive(Debug)]
+struct Degraded(StorageIssue);
+
+impl std::fmt::Display for Degraded {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "data directory is {}; keeping changes in memory", self.0)
+    }
+}
+
+impl std::error::Error for Degraded {}
+
+/// Degraded data directories and why.
+static DEGRADED: OnceLock<Mutex<HashMap<PathBuf, StorageIssue>>> = OnceLock::new();
+
+fn degraded() -> &'static Mutex<HashMap<PathBuf, StorageIssue>> {
+    DEGRADED.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// Returns why `dir` is degraded, if it is.
+pub fn issue(dir: &Path) -> Option<StorageIssue> {
+    degraded().lock().get(dir).copied()
+}
+
+/// Returns the storage issue behind a failed write, if it was one: the
+/// disk being full or read-only, or the write skipped because it is.
+pub fn issue_of(error: &io::Error) -> Option<StorageIssue> {
+    if let Some(Degraded(issue)) = error.get_ref().and_then(|e| e.downcast_ref()) {
+        return Some(*issue);
+    }
+    classify(error)
+}
+
+/// Writes `file_name` in `dir` atomically (temp file + rename), creating the
+/// directory if needed.
+///
+/// # Errors
+///
+/// Fails without touching the disk while `dir` is degraded. A write failing
+/// because the disk is full or read-only marks `dir` degraded.
+pub fn write_atomic(dir: &Path, file_name: &str, contents: impl AsRef<[u8]>) -> io::Result<()> {
+    if let Some(issue) = issue(dir) {
+        return Err(io::Error::other(Degraded(issue)));
+    }
+
+    let temp_path = dir.join(format!("{}.tmp", file_name));
+    let result = std::fs::create_dir_all(dir)
+        .and_then(|()| std::fs::write(&temp_path, contents))
+        .and_then(|()| std::fs::rename(&temp_path, dir.join(file_name)));
+    if let Err(e) = &result {
+        if let Some(issue) = classify(e) {
+            let _ = std::fs::remove_file(&temp_path);
+            mark(dir, issue, e);
+        }
+    }
+    result
+}
+
+/// Treats a write that failed on a storage issue as done, for callers that
+/// keep the change in memory. The issue was logged when the directory was
+/// marked degraded.
+pub fn tolerate(result: io::Result<()>) -> io::Result<()> {
+    match result {
+        Err(e) if issue_of(&e).is_some() => Ok(()),
+        other => other,
+    }
+}
+
+/// Checks that `dir` can be written to by writing and removing a probe
+/// file, marking it degraded or healthy accordingly.
+///
+/// # Errors
+///
+/// Returns the failed write. Failures that aren't storage issues (a
+/// missing parent directory on another mount, say) leave the mark alone.
+pub fn probe(dir: &Path) -> io::Result<()> {
+    let path = dir.join(PROBE_FILE);
+    let result = std::fs::create_dir_all(dir)
+        .and_then(|()| std::fs::write(&path, vec![0u8; PROBE_BYTES]))
+        .and_then(|()| std::fs::remove_file(&path));
+    match &result {
+        Ok(()) => {
+            if degraded().lock().remove(dir).is_some() {
+                log::info!("[Storage] {} is writable again", dir.display());
+            }
+        }
+        Err(e) => {
+            let _ = std::fs::remove_file(&path);
+            if let Some(issue) = classify(e) {
+                mark(dir, issue, e);
+            }
+        }
+    }
+    result
+}
+
+fn mark(dir: &Path, issue: StorageIssue, error: &io::Error) {
+    if degraded().lock().insert(dir.to_path_buf(), issue).is_none() {
+        log::error!(
+            "[Storage] {} is {} ({}); keeping changes in memory until it is writable",
+            dir.display(),
+            issue,
+            error
+        );
+    }
+}
+
+/// Maps the OS errors of full and read-only file systems.
+fn classify(error: &io::Error) -> Option<StorageIssue> {
+    if error.kind() == io::ErrorKind::PermissionDenied {
+        return Some(StorageIssue::ReadOnly);
+    }
+    let code = error.raw_os_error()?;
+
+    #[cfg(unix)]
+    {
+        if code == libc::ENOSPC || code == libc::EDQUOT {
+            return Some(StorageIssue::Full);
+        }
+        if code == libc::EROFS {
+            return Some(StorageIssue::ReadOnly);
+        }
+    }
+
+    #[cfg(windows)]
+    {
+        use windows_sys::Win32::Foundation::{
+            ERROR_DISK_FULL, ERROR_HANDLE_DISK_FULL, ERROR_WRITE_PROTECT,
+        };
+        let code = code as u32;
+        if code == ERROR_DISK_FULL || code == ERROR_HANDLE_DISK_FULL {
+            return Some(StorageIssue::Full);
+        }
+        if code == ERROR_WRITE_PROTECT {
+            return Some(StorageIssue::ReadOnly);
+        }
+    }
+
+    #[cfg(not(any(unix, windows)))]
+    let _ = code;
+
+    None
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn degraded_directories_skip_writes_until_probed() {
+        let dir = tempfile::tempdir().unwrap();
+        write_atomic(dir.path(), "a.json", "{}").unwrap();
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join("a.json")).unwrap(),
+            "{}"
+        );
+
+        let full = io::Error::from_raw_os_error(libc::ENOSPC);
+        mark(dir.path(), StorageIssue::Full, &full);
+        let skipped = write_atomic(dir.path(), "a.json", "[]").unwrap_err();
+        assert_eq!(issue_of(&skipped), Some(StorageIssue::Full));
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join("a.json")).unwrap(),
+            "{}"
+        );
+
+        probe(dir.path()).unwrap();
+        assert_eq!(issue(dir.path()), None);
+        assert!(!dir.path().join(PROBE_FILE).exists());
+        write_atomic(dir.path(), "a.json", "[]").unwrap();
+    }
+
+    #[test]
+    fn classifies_full_and_read_only_errors() {
+        assert_eq!(
+            issue_of(&io::Error::from_raw_os_error(libc::ENOSPC)),
+            Some(StorageIssue::Full)
+        );
+        assert_eq!(
+            issue_of(&io::Error::from_raw_os_error(libc::EROFS)),
+            Some(StorageIssue::ReadOnly)
+        );
+        assert_eq!(issue_of(&io::Error::other("bad json")), None);
+    }
+}
@@ -6,20 +6,25 @@
 
 use std::collections::VecDeque;
 use std::net::IpAddr;
+use std::pin::Pin;
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::{Arc, OnceLock};
+use std::task::{Context, Poll};
 use std::time::{Duration, Instant};
 
 use async_stream::stream;
 use bytes::Bytes;
+use futures::task::AtomicWaker;
 use futures::Stream;
 use tokio::sync::broadcast;
 use tokio::time::{interval, Instant as TokioInstant, MissedTickBehavior};
 
+use super::jitter::{AdaptiveQueue, QueueAdaptation};
 use super::{
     apply_fade_in, create_fade_out_frame, crossfade_samples, extract_last_sample_pair,
-    is_crossfade_compatible, AudioFormat, StreamState,
+    is_crossfade_compatible, AudioFormat, StartupStage, StreamState,
 };
+use crate::streaming_runtime::raise_thread_priority;
 
 /// Threshold for counting delivery gaps (100ms).
 /// PCM at 48kHz stereo 16-bit = 192KB/s, so 100ms = ~19KB of audio.
@@ -57,6 +62,12 @@ pub(crate) struct CadenceStats {
     pub silence_frames: u64,
     /// Frames dropped due to cadence queue overflow.
     pub frames_dropped: u64,
+    /// Highest smoothed ingest jitter observed (ms).
+    pub max_jitter_ms: f64,
+    /// Number of times the queue bound adapted to jitter.
+    pub queue_adaptations: u64,
+    /// Queue bound (frames) when the stream ended.
+    pub final_queue_size: usize,
 }
 
 /// Wrapper that logs HTTP audio stream lifecycle and tracks delivery timing.
@@ -181,10 +192,21 @@ impl Drop for LoggingStreamGuard {
             .map(|s| format!(", frames_dropped={}", s.frames_dropped))
             .unwrap_or_default();
 
+        // Build jitter string if the queue had to adapt
+        let jitter_info = cadence
+            .filter(|s| s.queue_adaptations > 0)
+            .map(|s| {
+                format!(
+                    ", max_jitter={:.1}ms, queue_adaptations={}, final_queue={}",
+                    s.max_jitter_ms, s.queue_adaptations, s.final_queue_size
+                )
+            })
+            .unwrap_or_default();
+
         if let Some(ref err) = *first_error {
             log::warn!(
                 "[Stream] HTTP stream ended with error{}: stream={}, client={}, frames_sent={}, \
-                 max_gap={}ms, gaps_over_{}ms={}, final_gap={}ms{}{}, error={}",
+                 max_gap={}ms, gaps_over_{}ms={}, final_gap={}ms{}{}{}, error={}",
                 stalled_suffix,
                 self.stream_id,
                 self.client_ip,
@@ -195,12 +217,13 @@ impl Drop for LoggingStreamGuard {
                 final_gap_ms,
                 silence_info,
                 dropped_info,
+                jitter_info,
                 err
             );
         } else {
             log::info!(
                 "[Stream] HTTP stream ended normally{}: stream={}, client={}, frames_sent={}, \
-                 max_gap={}ms, gaps_over_{}ms={}, final_gap={}ms{}{}",
+                 max_gap={}ms, gaps_over_{}ms={}, final_gap={}ms{}{}{}",
                 stalled_suffix,
                 self.stream_id,
                 self.client_ip,
@@ -210,7 +233,8 @@ impl Drop for LoggingStreamGuard {
                 gaps_over_threshold,
                 final_gap_ms,
                 silence_info,
-                dropped_info
+                dropped_info,
+                jitter_info
             );
         }
     }
@@ -288,20 +312,221 @@ impl CrossfadeState {
 pub struct CadenceConfig {
     /// Silence frame emitted when no audio is queued.
     pub silence_frame: Bytes,
-    /// Maximum number of frames to buffer.
+    /// Initial (and minimum) number of frames to buffer.
     pub queue_size: usize,
+    /// Upper bound the queue may grow to when ingest jitter rises.
+    /// Set equal to `queue_size` to disable adaptation.
+    pub max_queue_size: usize,
     /// Duration of each output frame in milliseconds.
     pub frame_duration_ms: u32,
     /// Audio format (sample rate, channels, bit depth).
     pub audio_format: AudioFormat,
     /// Initial frames pre-populated in the queue to eliminate handoff gap.
     pub prefill_frames: Vec<Bytes>,
+    /// Called whenever the queue bound adapts to measured jitter.
+    pub on_adapt: Option<Box<dyn Fn(QueueAdaptation) + Send + Sync>>,
+}
+
+/// One-shot hook that starts a playback epoch on the first real audio frame.
+pub type EpochHook = (Arc<StreamState>, Option<Instant>, Instant, IpAddr);
+
+/// Pushes a received frame, dropping the oldest frames beyond `bound`.
+///
+/// Returns the number of frames dropped. More than one frame may be dropped
+/// right after the bound shrinks.
+fn push_bounded(queue: &mut VecDeque<Bytes>, frame: Bytes, bound: usize) -> u64 {
+    let mut dropped = 0;
+    while queue.len() >= bound {
+        queue.pop_front();
+        dropped += 1;
+    }
+    queue.push_back(frame);
+    dropped
+}
+
+/// Cadence state machine shared by the async and dedicated-thread pipelines.
+///
+/// The driver calls [`receive`](Self::receive) as frames arrive and
+/// [`tick`](Self::tick) once per `frame_duration_ms`; the engine decides
+/// whether each tick carries queued audio, a crossfade, or silence.
+struct CadenceEngine {
+    queue: VecDeque<Bytes>,
+    silence_frame: Bytes,
+    crossfade: CrossfadeState,
+    adaptive: AdaptiveQueue,
+    on_adapt: Option<Box<dyn Fn(QueueAdaptation) + Send + Sync>>,
+    epoch_hook: Option<EpochHook>,
+    rx_closed: bool,
+    in_silence: bool,
+    silence_start: Option<TokioInstant>,
+    // Cadence-specific counters (written to guard at stream end)
+    silence_events: u64,
+    silence_frames: u64,
+    frames_dropped: u64,
+    // Rate-limit lagged warnings (max once per second)
+    last_lagged_log: Option<TokioInstant>,
+}
+
+impl CadenceEngine {
+    fn new(config: CadenceConfig, epoch_hook: Option<EpochHook>) -> Self {
+        let CadenceConfig {
+            silence_frame,
+            queue_size,
+            max_queue_size,
+            frame_duration_ms,
+            audio_format,
+            prefill_frames,
+            on_adapt,
+        } = config;
+
+        // Pre-populate queue with prefill frames to eliminate handoff gap.
+        // This ensures the first tick immediately yields audio.
+        let mut queue = VecDeque::with_capacity(max_queue_size.max(prefill_frames.len()));
+        queue.extend(prefill_frames);
+
+        Self {
+            queue,
+            silence_frame,
+            crossfade: CrossfadeState::new(&audio_format, frame_duration_ms),
+            // Jitter-driven queue bound (starts at the configured streaming buffer)
+            adaptive: AdaptiveQueue::new(frame_duration_ms, queue_size, max_queue_size),
+            on_adapt,
+            epoch_hook,
+            rx_closed: false,
+            in_silence: false,
+            silence_start: None,
+            silence_events: 0,
+            silence_frames: 0,
+            frames_dropped: 0,
+            last_lagged_log: None,
+        }
+    }
+
+    /// Whether the channel is closed and every queued frame has been emitted.
+    fn is_finished(&self) -> bool {
+        self.rx_closed && self.queue.is_empty()
+    }
+
+    /// Queues a frame received at `now`, updating the jitter-driven bound.
+    fn receive(&mut self, frame: Bytes, now: TokioInstant) {
+        if let Some(adaptation) = self.adaptive.record_arrival(now) {
+            log::info!(
+                "[Stream] Cadence queue {} -> {} frames (jitter {:.1}ms)",
+                adaptation.previous_size,
+                adaptation.queue_size,
+                adaptation.jitter_ms
+            );
+            if let Some(ref on_adapt) = self.on_adapt {
+                on_adapt(adaptation);
+            }
+        }
+
+        // Queue full - drop oldest to maintain bounded latency
+        let dropped = push_bounded(&mut self.queue, frame, self.adaptive.size());
+        if dropped > 0 {
+            self.frames_dropped += dropped;
+            log::trace!("[Stream] Queue full, dropped {} oldest frame(s)", dropped);
+        }
+    }
+
+    /// Records a lagged broadcast receiver.
+    fn lagged(&mut self, n: u64, context: &str) {
+        log_lagged(n, &mut self.last_lagged_log, context);
+    }
+
+    /// Marks the input channel closed; remaining queued frames still drain.
+    fn close(&mut self) {
+        if !self.rx_closed {
+            self.rx_closed = true;
+            log::debug!(
+                "[Stream] Channel closed, draining {} queued frames",
+                self.queue.len()
+            );
+        }
+    }
+
+    /// Drains every frame currently waiting in `rx` without blocking.
+    fn drain(&mut self, rx: &mut broadcast::Receiver<Bytes>, context: &str) {
+        while !self.rx_closed {
+            match rx.try_recv() {
+                Ok(frame) => self.receive(frame, TokioInstant::now()),
+                Err(broadcast::error::TryRecvError::Empty) => break,
+                Err(broadcast::error::TryRecvError::Lagged(n)) => self.lagged(n, context),
+                Err(broadcast::error::TryRecvError::Closed) => self.close(),
+            }
+        }
+    }
+
+    /// Produces the frame for one cadence tick.
+    ///
+    /// Returns `None` only once the channel is closed and the queue is drained.
+    fn tick(&mut self) -> Option<Bytes> {
+        if let Some(frame) = self.queue.pop_front() {
+            // Real audio available
+            let was_in_silence = self.in_silence;
+            if self.in_silence {
+                if let Some(start) = self.silence_start.take() {
+                    log::info!(
+                        "[Stream] Exiting silence (cadence) after {:.1}s",
+                        start.elapsed().as_secs_f32()
+                    );
+                }
+                self.in_silence = false;
+            }
+
+            self.crossfade.track_frame(&frame);
+
+            // Fire epoch hook on first real audio frame
+            if let Some((stream_state, epoch_candidate, connected_at, remote_ip)) =
+                self.epoch_hook.take()
+            {
+                stream_state
+                    .timing
+                    .start_new_epoch(epoch_candidate, connected_at, remote_ip);
+                stream_state.record_startup(remote_ip, StartupStage::FirstAudio);
+            }
+
+            if was_in_silence {
+                Some(self.crossfade.maybe_fade_in(frame))
+            } else {
+                Some(frame)
+            }
+        } else if !self.rx_closed {
+            // No frame available, emit silence
+            self.silence_frames += 1;
+            if !self.in_silence {
+                log::info!("[Stream] Entering silence (cadence) - queue empty");
+                self.in_silence = true;
+                self.silence_start = Some(TokioInstant::now());
+                self.silence_events += 1;
+                Some(self.crossfade.enter_silence(&self.silence_frame))
+            } else {
+                Some(self.silence_frame.clone())
+            }
+        } else {
+            // Channel closed and queue empty - stream is done
+            None
+        }
+    }
+
+    /// Writes final statistics to the guard.
+    fn finish(&self, guard: &LoggingStreamGuard) {
+        guard.set_cadence_stats(CadenceStats {
+            silence_events: self.silence_events,
+            silence_frames: self.silence_frames,
+            frames_dropped: self.frames_dropped,
+            max_jitter_ms: self.adaptive.max_jitter_ms(),
+            queue_adaptations: self.adaptive.adaptations(),
+            final_queue_size: self.adaptive.size(),
+        });
+    }
 }
 
 /// Creates a WAV audio stream with fixed-cadence output and crossfade on silence transitions.
 ///
 /// Maintains real-time cadence regardless of input timing:
-/// - Incoming frames are queued (bounded to `queue_size`)
+/// - Incoming frames are queued (bounded to `queue_size`, growing up to
+///   `max_queue_size` while ingest jitter is high)
 /// - Metronome ticks every `frame_duration_ms`
 /// - On each tick: send queued frame if available, else send silence
 ///
@@ -317,54 +542,27 @@ pub struct CadenceConfig {
 ///
 /// This ensures Sonos always receives continuous data with smooth transitions,
 /// eliminating pops from abrupt audio/silence boundaries.
+///
+/// The metronome runs on the calling Tokio runtime. Prefer
+/// [`spawn_cadence_thread`], which keeps ticks on time when the executor stalls.
 pub fn create_wav_stream_with_cadence(
     mut rx: broadcast::Receiver<Bytes>,
     guard: Arc<LoggingStreamGuard>,
     config: CadenceConfig,
-    epoch_hook: Option<(Arc<StreamState>, Option<Instant>, Instant, IpAddr)>,
+    epoch_hook: Option<EpochHook>,
 ) -> impl Stream<Item = Result<Bytes, std::io::Error>> {
     stream! {
-        let CadenceConfig {
-            silence_frame,
-            queue_size,
-            frame_duration_ms,
-            audio_format,
-            prefill_frames,
-        } = config;
-        let cadence_duration = Duration::from_millis(frame_duration_ms as u64);
-
-        // Pre-populate queue with prefill frames to eliminate handoff gap.
-        // This ensures the first tick immediately yields audio.
-        let mut queue: VecDeque<Bytes> = VecDeque::with_capacity(queue_size.max(prefill_frames.len()));
-        for frame in prefill_frames {
-            queue.push_back(frame);
-        }
+        let cadence_duration = Duration::from_millis(config.frame_duration_ms as u64);
+        let mut engine = CadenceEngine::new(config, epoch_hook);
 
         // Fire first tick immediately to get audio flowing before Sonos times out.
         // On resume, Sonos closes the connection within milliseconds if no audio arrives.
         let mut metronome = interval(cadence_duration);
         metronome.set_missed_tick_behavior(MissedTickBehavior::Burst);
 
-        let mut rx_closed = false;
-        let mut in_silence = false;
-        let mut silence_start: Option<TokioInstant> = None;
-
-        // Cadence-specific counters (written to guard at stream end)
-        let mut silence_events: u64 = 0;
-        let mut silence_frames: u64 = 0;
-        let mut frames_dropped: u64 = 0;
-
-        let mut crossfade = CrossfadeState::new(&audio_format, frame_duration_ms);
-
-        // Rate-limit lagged warnings (max once per second)
-        let mut last_lagged_log: Option<TokioInstant> = None;
-
-        // One-shot epoch hook: fires on the first real audio frame, then consumed
-        let mut epoch_hook = epoch_hook;
-
         loop {
             // Exit when channel closed AND queue drained
-            if rx_closed && queue.is_empty() {
+            if engine.is_finished() {
                 break;
             }
 
@@ -373,111 +571,155 @@ pub fn create_wav_stream_with_cadence(
 
                 // PRIORITY 1: Metronome tick - MUST emit something every frame_duration_ms
                 _ = metronome.tick() => {
-                    if let Some(frame) = queue.pop_front() {
-                        // Real audio available
-                        let was_in_silence = in_silence;
-                        if in_silence {
-                            if let Some(start) = silence_start.take() {
-                                log::info!(
-                                    "[Stream] Exiting silence (cadence) after {:.1}s",
-                                    start.elapsed().as_secs_f32()
-                                );
-                            }
-                            in_silence = false;
-                        }
-
-                        crossfade.track_frame(&frame);
-
-                        // Fire epoch hook on first real audio frame
-                        if let Some((stream_state, epoch_candidate, connected_at, remote_ip)) = epoch_hook.take() {
-                            stream_state.timing.start_new_epoch(
-                                epoch_candidate,
-                                connected_at,
-                                remote_ip,
-                            );
-                        }
-
-                        if was_in_silence {
-                            yield Ok(crossfade.maybe_fade_in(frame));
-                        } else {
-                            yield Ok(frame);
-                        }
-                    } else if !rx_closed {
-                        // No frame available, emit silence
-                        if !in_silence {
-                            log::info!("[Stream] Entering silence (cadence) - queue empty");
-                            in_silence = true;
-                            silence_start = Some(TokioInstant::now());
-                            silence_events += 1;
-                            silence_frames += 1;
-                            yield Ok(crossfade.enter_silence(&silence_frame));
-                        } else {
-                            silence_frames += 1;
-                            yield Ok(silence_frame.clone());
-                        }
-                    }
                     // If rx_closed and queue empty, don't yield - loop will break
+                    if let Some(frame) = engine.tick() {
+                        yield Ok(frame);
+                    }
 
                     // Drain any pending frames from rx into queue after emitting.
                     // This prevents starvation: with biased select, ticks always win,
                     // so without this drain, frames could pile up in rx while we
                     // emit silence (especially during recovery from underflow).
-                    if !rx_closed {
-                        loop {
-                            match rx.try_recv() {
-                                Ok(frame) => {
-                                    if queue.len() >= queue_size {
-                                        queue.pop_front();
-                                        frames_dropped += 1;
-                                    }
-                                    queue.push_back(frame);
-                                }
-                                Err(broadcast::error::TryRecvError::Empty) => break,
-                                Err(broadcast::error::TryRecvError::Lagged(n)) => {
-                                    log_lagged(n, &mut last_lagged_log, " (during drain)");
-                                }
-                                Err(broadcast::error::TryRecvError::Closed) => {
-                                    rx_closed = true;
-                                    log::debug!("[Stream] Channel closed, draining {} queued frames", queue.len());
-                                    break;
-                                }
-                            }
-                        }
-                    }
+                    engine.drain(&mut rx, " (during drain)");
                 }
 
                 // PRIORITY 2: Receive frames into queue (when channel open and tick not ready)
-                result = rx.recv(), if !rx_closed => {
+                result = rx.recv(), if !engine.rx_closed => {
                     match result {
-                        Ok(frame) => {
-                            if queue.len() >= queue_size {
-                                // Queue full - drop oldest to maintain bounded latency
-                                queue.pop_front();
-                                frames_dropped += 1;
-                                log::trace!("[Stream] Queue full, dropped oldest frame");
-                            }
-                            queue.push_back(frame);
-                        }
-                        Err(broadcast::error::RecvError::Lagged(n)) => {
-                            log_lagged(n, &mut last_lagged_log, "");
-                        }
-                        Err(broadcast::error::RecvError::Closed) => {
-                            rx_closed = true;
-                            log::debug!("[Stream] Channel closed, draining {} queued frames", queue.len());
-                        }
+                        Ok(frame) => engine.receive(frame, TokioInstant::now()),
+                        Err(broadcast::error::RecvError::Lagged(n)) => engine.lagged(n, ""),
+                        Err(broadcast::error::RecvError::Closed) => engine.close(),
                     }
                 }
             }
         }
 
-        guard.set_cadence_stats(CadenceStats {
-            silence_events,
-            silence_frames,
-            frames_dropped,
-        });
+        engine.finish(&guard);
     }
 }
 
+// ─────────────────────────────────────────────────────────────────────────────
+// Dedicated Cadence Thread
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Capacity of the ring between the cadence thread and the HTTP writer (frames).
+///
+/// Only fills when the socket applies backpressure; the cadence thread then
+/// holds its ticks and bursts once space frees up, like `MissedTickBehavior::Burst`.
+const CADENCE_RING_CAPACITY: usize = 64;
+
+/// How often the cadence thread checks for new frames between ticks.
+///
+/// Short enough that arrival timestamps stay accurate for jitter tracking.
+const CADENCE_POLL_INTERVAL: Duration = Duration::from_millis(2);
+
+/// Consumer half of the cadence thread's output ring.
+///
+/// Dropping it tells the cadence thread to exit (via ring abandonment).
+pub struct CadenceThreadStream {
+    consumer: rtrb::Consumer<Bytes>,
+    waker: Arc<AtomicWaker>,
+}
+
+impl Stream for CadenceThreadStream {
+    type Item = Result<Bytes, std::io::Error>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        if let Ok(frame) = self.consumer.pop() {
+            return Poll::Ready(Some(Ok(frame)));
+        }
+
+        self.waker.register(cx.waker());
+
+        // Re-check after registering so a push racing with registration isn't missed
+        match self.consumer.pop() {
+            Ok(frame) => Poll::Ready(Some(Ok(frame))),
+            Err(_) if self.consumer.is_abandoned() => {
+                // Producer may have pushed its last frames right before exiting
+                match self.consumer.pop() {
+                    Ok(frame) => Poll::Ready(Some(Ok(frame))),
+                    Err(_) => Poll::Ready(None),
+                }
+            }
+            Err(_) => Poll::Pending,
+        }
+    }
+}
+
+/// Runs the cadence pipeline on a dedicated high-priority OS thread.
+///
+/// Same output as [`create_wav_stream_with_cadence`], but the metronome is a
+/// plain thread with deadline-based sleeps instead of a Tokio timer, so an
+/// overloaded or stalled executor can't delay ticks. Frames are handed to the
+/// HTTP writer through a lock-free SPSC ring; the writer only has to keep the
+/// socket fed.
+///
+/// The thread exits when the returned stream is dropped (client disconnected)
+/// or when the channel closes and the queue drains.
+///
+/// # Errors
+///
+/// Returns an error if the OS refuses to spawn the thread.
+pub fn spawn_cadence_thread(
+    mut rx: broadcast::Receiver<Bytes>,
+    guard: Arc<LoggingStreamGuard>,
+    config: CadenceConfig,
+    epoch_hook: Option<EpochHook>,
+) -> std::io::Result<CadenceThreadStream> {
+    let (mut producer, consumer) = rtrb::RingBuffer::new(CADENCE_RING_CAPACITY);
+    let waker = Arc::new(AtomicWaker::new());
+    let thread_waker = Arc::clone(&waker);
+    let cadence_duration = Duration::from_millis(config.frame_duration_ms as u64);
+
+    std::thread::Builder::new()
+        .name("cadence".into())
+        .spawn(move || {
+            raise_thread_priority();
+            let mut engine = CadenceEngine::new(config, epoch_hook);
+
+            // First tick fires immediately (see create_wav_stream_with_cadence)
+            let mut next_tick = Instant::now();
+
+            loop {
+                if producer.is_abandoned() {
+                    log::debug!("[Stream] Cadence thread stopping: writer dropped");
+                    break;
+                }
+
+                engine.drain(&mut rx, "");
+
+                if Instant::now() >= next_tick && !producer.is_full() {
+                    match engine.tick() {
+                        Some(frame) => {
+                            // Cannot fail: checked is_full() and we are the only producer
+                            let _ = producer.push(frame);
+                            thread_waker.wake();
+                            next_tick += cadence_duration;
+                        }
+                        None => break,
+                    }
+                    continue;
+                }
+
+                // Sleep until the next tick, waking early to timestamp arrivals.
+                // A due tick here means the ring is full: wait for the writer.
+                let until_tick = next_tick.saturating_duration_since(Instant::now());
+                if until_tick.is_zero() {
+                    std::thread::sleep(CADENCE_POLL_INTERVAL);
+                } else {
+                    std::thread::sleep(until_tick.min(CADENCE_POLL_INTERVAL));
+                }
+            }
+
+            engine.finish(&guard);
+            // Dropping the producer marks the ring abandoned; wake the writer to notice
+            drop(producer);
+            thread_waker.wake();
+        })?;
+
+    Ok(CadenceThreadStream { consumer, waker })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -517,9 +759,11 @@ mod tests {
         CadenceConfig {
             silence_frame: test_silence_frame(),
             queue_size: TEST_QUEUE_SIZE,
+            max_queue_size: TEST_QUEUE_SIZE,
             frame_duration_ms: SILENCE_FRAME_DURATION_MS,
             audio_format: test_audio_format(),
             prefill_frames: vec![],
+            on_adapt: None,
         }
     }
 
@@ -568,6 +812,64 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn cadence_thread_emits_queued_frames_then_ends() {
+        let (tx, rx) = broadcast::channel::<Bytes>(16);
+        let guard = test_guard();
+        let guard_for_check = Arc::clone(&guard);
+
+        let mut stream = spawn_cadence_thread(rx, guard, test_config(), None)
+            .expect("cadence thread should spawn");
+
+        for i in 1..=3u8 {
+            tx.send(Bytes::from(vec![i; 64]))
+                .expect("send should succeed");
+        }
+        drop(tx);
+
+        // Queued frames come out in order; the thread may emit silence first
+        // if it ticked before the frames arrived (then frame 1 is faded in).
+        let mut audio = Vec::new();
+        while let Some(frame) = time::timeout(Duration::from_secs(2), stream.next())
+            .await
+            .expect("cadence thread should keep ticking")
+        {
+            let frame = frame.expect("should be Ok");
+            if frame.iter().any(|&b| b != 0) {
+                audio.push(frame);
+            }
+        }
+        assert_eq!(audio.len(), 3);
+        assert_eq!(audio[1], Bytes::from(vec![2u8; 64]));
+        assert_eq!(audio[2], Bytes::from(vec![3u8; 64]));
+        assert!(guard_for_check.cadence_stats.get().is_some());
+    }
+
+    #[tokio::test]
+    async fn cadence_thread_stops_when_stream_dropped() {
+        let (tx, rx) = broadcast::channel::<Bytes>(16);
+        let mut stream = spawn_cadence_thread(rx, test_guard(), test_config(), None)
+            .expect("cadence thread should spawn");
+
+        // Silence keeps flowing while the channel is open
+        let frame = time::timeout(Duration::from_secs(2), stream.next())
+            .await
+            .expect("cadence thread should tick")
+            .expect("stream should yield");
+        assert!(frame.unwrap().iter().all(|&b| b == 0));
+
+        // Dropping the stream abandons the ring; the thread exits and drops rx
+        drop(stream);
+        let deadline = std::time::Instant::now() + Duration::from_secs(2);
+        while tx.receiver_count() > 0 {
+            assert!(
+                std::time::Instant::now() < deadline,
+                "cadence thread did not exit"
+            );
+            std::thread::sleep(Duration::from_millis(5));
+        }
+    }
+
     #[tokio::test(start_paused = true)]
     async fn emits_frames_at_cadence() {
         let (tx, rx) = broadcast::channel::<Bytes>(16);
@@ -901,4 +1203,232 @@ mod tests {
 
         drop(tx);
     }
+
+    /// Randomized frame arrival patterns against the cadence state machine.
+    mod properties {
+        use super::*;
+        use crate::stream::{crossfade_samples, PCM_16BIT_BYTES_PER_SAMPLE};
+        use proptest::prelude::*;
+
+        const FORMAT_SAMPLE_RATE: u32 = 48_000;
+        const FORMAT_CHANNELS: u16 = 2;
+
+        /// Broadcast capacity large enough that no schedule lags the receiver.
+        const CHANNEL_CAPACITY: usize = 1024;
+
+        fn frame_duration() -> Duration {
+            Duration::from_millis(SILENCE_FRAME_DURATION_MS as u64)
+        }
+
+        fn format() -> AudioFormat {
+            AudioFormat::new(FORMAT_SAMPLE_RATE, FORMAT_CHANNELS, 16)
+        }
+
+        /// One frame of constant-amplitude stereo audio.
+        fn constant_frame(amplitude: i16) -> Bytes {
+            let samples = format().frame_samples(SILENCE_FRAME_DURATION_MS) * 2;
+            let bytes: Vec<u8> = std::iter::repeat(amplitude.to_le_bytes())
+                .take(samples)
+                .flatten()
+                .collect();
+            Bytes::from(bytes)
+        }
+
+        fn samples(frame: &[u8]) -> impl Iterator<Item = i16> + '_ {
+            frame
+                .chunks_exact(PCM_16BIT_BYTES_PER_SAMPLE)
+                .map(|b| i16::from_le_bytes([b[0], b[1]]))
+        }
+
+        /// What the stream produced for one schedule.
+        struct Run {
+            /// Frames emitted at each step (one step per cadence tick).
+            ticks: Vec<Vec<Bytes>>,
+            /// Step at which the stream ended, if it did.
+            ended_at: Option<usize>,
+            stats: Option<(u64, u64)>,
+        }
+
+        /// Feeds `arrivals[i]` frames just before tick `i`, closes the channel
+        /// after the schedule, and collects output until the stream ends or
+        /// `max_steps` ticks have passed.
+        fn run(
+            arrivals: &[usize],
+            amplitude: i16,
+            queue_size: usize,
+            max_queue_size: usize,
+            max_steps: usize,
+        ) -> Run {
+            let runtime = tokio::runtime::Builder::new_current_thread()
+                .enable_time()
+                .start_paused(true)
+                .build()
+                .expect("runtime should build");
+
+            runtime.block_on(async {
+                let (tx, rx) = broadcast::channel::<Bytes>(CHANNEL_CAPACITY);
+                let guard = test_guard();
+                let config = CadenceConfig {
+                    silence_frame: format().silence_frame(SILENCE_FRAME_DURATION_MS),
+                    queue_size,
+                    max_queue_size,
+                    frame_duration_ms: SILENCE_FRAME_DURATION_MS,
+                    audio_format: format(),
+                    prefill_frames: vec![],
+                    on_adapt: None,
+                };
+                let mut stream = Box::pin(create_wav_stream_with_cadence(
+                    rx,
+                    Arc::clone(&guard),
+                    config,
+                    None,
+                ));
+
+                let frame = constant_frame(amplitude);
+                let mut tx = Some(tx);
+                let mut ticks = Vec::new();
+                let mut ended_at = None;
+
+                for step in 0..max_steps {
+                    match (arrivals.get(step), &tx) {
+                        (Some(&count), Some(sender)) => {
+                            for _ in 0..count {
+                                sender.send(frame.clone()).expect("send should succeed");
+                            }
+                        }
+                        _ => tx = None,
+                    }
+
+                    let mut out = Vec::new();
+                    let finished = loop {
+                        match poll_fn(|cx| Poll::Ready(stream.as_mut().poll_next(cx))).await {
+                            Poll::Ready(Some(frame)) => out.push(frame.expect("should be Ok")),
+                            Poll::Ready(None) => break true,
+                            Poll::Pending => break false,
+                        }
+                    };
+                    ticks.push(out);
+                    if finished {
+                        ended_at = Some(step);
+                        break;
+                    }
+                    time::advance(frame_duration()).await;
+                }
+
+                let stats = guard
+                    .cadence_stats
+                    .get()
+                    .map(|s| (s.frames_dropped, s.silence_frames));
+                Run {
+                    ticks,
+                    ended_at,
+                    stats,
+                }
+            })
+        }
+
+        /// Steady arrivals mixed with gaps and bursts.
+        fn arrival_pattern() -> impl Strategy<Value = Vec<usize>> {
+            prop::collection::vec(
+                prop_oneof![4 => Just(1usize), 2 => Just(0usize), 1 => 2..=5usize],
+                1..60,
+            )
+        }
+
+        fn amplitude() -> impl Strategy<Value = i16> {
+            prop_oneof![1000i16..=30000, -30000i16..=-1000]
+        }
+
+        proptest! {
+            #![proptest_config(ProptestConfig::with_cases(128))]
+
+            #[test]
+            fn emits_one_full_frame_per_tick_until_drained(
+                arrivals in arrival_pattern(),
+                amplitude in amplitude(),
+                queue_size in 1usize..=6,
+                extra in 0usize..=4,
+            ) {
+                let max_queue_size = queue_size + extra;
+                let max_steps = arrivals.len() + max_queue_size + 3;
+                let run = run(&arrivals, amplitude, queue_size, max_queue_size, max_steps);
+
+                // Termination: the queue drains within its bound after closure
+                let ended_at = run.ended_at.expect("stream should end after closure");
+                prop_assert!(ended_at <= arrivals.len() + max_queue_size + 1);
+
+                // Timing: exactly one frame per tick, each a full frame duration
+                let frame_bytes = format().frame_bytes(SILENCE_FRAME_DURATION_MS);
+                for (step, out) in run.ticks.iter().enumerate() {
+                    if step < ended_at {
+                        prop_assert_eq!(out.len(), 1, "tick {} emitted {} frames", step, out.len());
+                    } else {
+                        prop_assert!(out.len() <= 1);
+                    }
+                    for frame in out {
+                        prop_assert_eq!(frame.len(), frame_bytes);
+                    }
+                }
+            }
+
+            #[test]
+            fn silence_transitions_are_faded(
+                arrivals in arrival_pattern(),
+                amplitude in amplitude(),
+                queue_size in 1usize..=6,
+            ) {
+                let max_steps = arrivals.len() + queue_size + 3;
+                let run = run(&arrivals, amplitude, queue_size, queue_size, max_steps);
+
+                // A linear ramp over the crossfade moves at most this far per sample
+                let fade = crossfade_samples(FORMAT_SAMPLE_RATE);
+                let max_step = i32::from(amplitude).abs() / (fade as i32 - 1) + 1;
+
+                let output: Vec<i16> = run
+                    .ticks
+                    .iter()
+                    .flatten()
+                    .flat_map(|frame| samples(frame).collect::<Vec<_>>())
+                    .collect();
+                let channels = FORMAT_CHANNELS as usize;
+                for (i, pair) in output.windows(channels + 1).enumerate() {
+                    let jump = (i32::from(pair[channels]) - i32::from(pair[0])).abs();
+                    prop_assert!(
+                        jump <= max_step,
+                        "discontinuity of {} at sample {} (limit {})",
+                        jump,
+                        i,
+                        max_step
+                    );
+                }
+            }
+
+            #[test]
+            fn every_frame_is_either_played_or_dropped(
+                arrivals in arrival_pattern(),
+                amplitude in amplitude(),
+                queue_size in 1usize..=6,
+                extra in 0usize..=4,
+            ) {
+                let max_queue_size = queue_size + extra;
+                let max_steps = arrivals.len() + max_queue_size + 3;
+                let run = run(&arrivals, amplitude, queue_size, max_queue_size, max_steps);
+
+                // Audio frames end on the input amplitude; fades and silence end on zero
+                let played = run
+                    .ticks
+                    .iter()
+                    .flatten()
+                    .filter(|frame| samples(frame).last() == Some(amplitude))
+                    .count() as u64;
+                let (dropped, silence_frames) = run.stats.expect("stats written at stream end");
+
+                let sent: usize = arrivals.iter().sum();
+                prop_assert_eq!(played + dropped, sent as u64);
+
+                let emitted = run.ticks.iter().flatten().count() as u64;
+                prop_assert_eq!(played + silence_frames, emitted);
+            }
+        }
+    }
 }
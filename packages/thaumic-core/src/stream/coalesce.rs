@@ -0,0 +1,166 @@
+//! Write coalescing for HTTP audio responses.
+//!
+//! Each audio frame is a separate body chunk, and hyper flushes the socket
+//! whenever the body stream returns `Pending`. In steady state that's one
+//! write per frame, which is what we want for latency. But prefill and
+//! post-stall catch-up deliver dozens of frames at once. Without coalescing,
+//! each of those frames costs its own chunk bookkeeping and, beyond hyper's
+//! vectored-write queue depth, its own syscall, multiplied by every listener.
+//!
+//! [`Coalesce`] merges frames that are *already ready* into one chunk, up to a
+//! byte limit. It never waits for more data, so it adds no latency.
+
+use std::io;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use bytes::{Bytes, BytesMut};
+use futures::Stream;
+
+/// Default upper bound for a coalesced chunk (bytes).
+///
+/// 64 KiB matches typical socket send buffer increments and holds ~340ms of
+/// 48kHz/16-bit stereo PCM, so a full prefill goes out in a few writes.
+pub const DEFAULT_COALESCE_MAX_BYTES: usize = 64 * 1024;
+
+/// Stream adapter that merges immediately-available chunks.
+pub struct Coalesce<S> {
+    inner: S,
+    max_bytes: usize,
+    /// Error encountered while gathering; yielded after the gathered data.
+    pending_error: Option<io::Error>,
+    /// Inner stream ended while gathering; end after the gathered data.
+    inner_done: bool,
+}
+
+impl<S> Coalesce<S> {
+    /// Wraps `inner`, merging ready chunks up to `max_bytes` per item.
+    pub fn new(inner: S, max_bytes: usize) -> Self {
+        Self {
+            inner,
+            max_bytes: max_bytes.max(1),
+            pending_error: None,
+            inner_done: false,
+        }
+    }
+}
+
+impl<S> Stream for Coalesce<S>
+where
+    S: Stream<Item = Result<Bytes, io::Error>> + Unpin,
+{
+    type Item = Result<Bytes, io::Error>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = &mut *self;
+
+        if let Some(err) = this.pending_error.take() {
+            return Poll::Ready(Some(Err(err)));
+        }
+        if this.inner_done {
+            return Poll::Ready(None);
+        }
+
+        let first = match Pin::new(&mut this.inner).poll_next(cx) {
+            Poll::Ready(Some(Ok(chunk))) => chunk,
+            other => return other,
+        };
+
+        // Only copy once a second chunk is actually ready
+        let mut merged: Option<BytesMut> = None;
+        let mut len = first.len();
+
+        while len < this.max_bytes {
+            match Pin::new(&mut this.inner).poll_next(cx) {
+                Poll::Ready(Some(Ok(chunk))) => {
+                    let buf = merged.get_or_insert_with(|| {
+                        let mut buf = BytesMut::with_capacity(this.max_bytes.max(len));
+                        buf.extend_from_slice(&first);
+                        buf
+                    });
+                    buf.extend_from_slice(&chunk);
+                    len += chunk.len();
+                }
+                Poll::Ready(Some(Err(e))) => {
+                    this.pending_error = Some(e);
+                    break;
+                }
+                Poll::Ready(None) => {
+                    this.inner_done = true;
+                    break;
+                }
+                Poll::Pending => break,
+            }
+        }
+
+        Poll::Ready(Some(Ok(match merged {
+            Some(buf) => buf.freeze(),
+            None => first,
+        })))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::StreamExt;
+
+    fn chunks(sizes: &[usize]) -> Vec<Result<Bytes, io::Error>> {
+        sizes
+            .iter()
+            .enumerate()
+            .map(|(i, &n)| Ok(Bytes::from(vec![i as u8; n])))
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn merges_ready_chunks_up_to_limit() {
+        let inner = futures::stream::iter(chunks(&[10, 10, 10, 10, 10]));
+        let out: Vec<_> = Coalesce::new(inner, 25)
+            .map(|r| r.unwrap().len())
+            .collect()
+            .await;
+        // 10+10+10 crosses the limit, then the remaining 10+10
+        assert_eq!(out, vec![30, 20]);
+    }
+
+    #[tokio::test]
+    async fn preserves_byte_order() {
+        let inner = futures::stream::iter(chunks(&[2, 2, 2]));
+        let out: Vec<_> = Coalesce::new(inner, 1024)
+            .map(|r| r.unwrap())
+            .collect()
+            .await;
+        assert_eq!(out, vec![Bytes::from_static(&[0, 0, 1, 1, 2, 2])]);
+    }
+
+    #[tokio::test]
+    async fn yields_error_after_gathered_data() {
+        let mut items = chunks(&[4, 4]);
+        items.push(Err(io::Error::other("boom")));
+        let mut stream = Coalesce::new(futures::stream::iter(items), 1024);
+
+        assert_eq!(stream.next().await.unwrap().unwrap().len(), 8);
+        assert!(stream.next().await.unwrap().is_err());
+        assert!(stream.next().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn does_not_wait_for_pending_chunks() {
+        let (tx, rx) = futures::channel::mpsc::unbounded::<Result<Bytes, io::Error>>();
+        let mut stream = Coalesce::new(rx, 1024);
+
+        tx.unbounded_send(Ok(Bytes::from_static(b"abc"))).unwrap();
+        assert_eq!(
+            stream.next().await.unwrap().unwrap(),
+            Bytes::from_static(b"abc")
+        );
+
+        tx.unbounded_send(Ok(Bytes::from_static(b"de"))).unwrap();
+        tx.unbounded_send(Ok(Bytes::from_static(b"f"))).unwrap();
+        assert_eq!(
+            stream.next().await.unwrap().unwrap(),
+            Bytes::from_static(b"def")
+        );
+    }
+}
@@ -0,0 +1,326 @@
+//! Feedback loop detection for ingest streams.
+//!
+//! If the user captures a tab that is itself playing one of our streams,
+//! ingest becomes a delayed copy of its own output and the loop repeats
+//! forever. Two signals catch this:
+//!
+//! - **Audio:** the loudness envelope of PCM ingest closely matches itself
+//!   at one fixed delay, check after check. Ordinary music rarely stays that
+//!   self-similar at one lag for several seconds.
+//! - **Metadata:** the captured tab's title or source points at this server
+//!   (e.g. a tab titled `live.wav`).
+//!
+//! Only PCM ingest is fingerprinted; compressed frames can't be inspected
+//! without decoding.
+
+use std::collections::VecDeque;
+
+use serde::Serialize;
+
+/// Length of the envelope window compared against its past (ms).
+const WINDOW_MS: u32 = 3_000;
+
+/// Shortest loop delay considered (ms). Below this is ordinary sustain.
+const MIN_LAG_MS: u32 = 200;
+
+/// Longest loop delay considered (ms); covers max streaming buffer + Sonos.
+const MAX_LAG_MS: u32 = 5_000;
+
+/// How often the envelope is checked (ms).
+const CHECK_INTERVAL_MS: u32 = 1_000;
+
+/// Correlation at which a lag counts as a loop match.
+const MATCH_CORRELATION: f32 = 0.95;
+
+/// Correlation below which a check counts as clean.
+const CLEAN_CORRELATION: f32 = 0.8;
+
+/// Correlation margin within which a shorter lag is preferred over the best.
+const LAG_TIE_TOLERANCE: f32 = 0.02;
+
+/// Consecutive matching checks (at the same lag) before warning.
+const MATCH_CHECKS: u32 = 5;
+
+/// Consecutive clean checks before a new warning may fire.
+const REARM_CHECKS: u32 = 10;
+
+/// Mean absolute sample level below which a window is treated as silence.
+const SILENCE_LEVEL: f32 = 64.0;
+
+/// What triggered a feedback loop warning.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum FeedbackCause {
+    /// Ingest audio repeats itself at a fixed delay.
+    Audio,
+    /// Tab metadata references this server's stream.
+    Metadata,
+}
+
+/// Detects self-similar PCM ingest (a delayed copy of itself).
+#[derive(Debug)]
+pub struct FeedbackDetector {
+    frame_ms: u32,
+    window: usize,
+    min_lag: usize,
+    max_lag: usize,
+    check_every: usize,
+    /// Per-frame mean absolute level, oldest first.
+    envelope: VecDeque<f32>,
+    frames_since_check: usize,
+    /// Lag (frames) of the current run of matching checks.
+    streak_lag: Option<usize>,
+    streak: u32,
+    clean_checks: u32,
+    /// A warning fired and hasn't been re-armed yet.
+    latched: bool,
+}
+
+impl FeedbackDetector {
+    /// Creates a detector for frames of the given duration.
+    pub fn new(frame_duration_ms: u32) -> Self {
+        let frame_ms = frame_duration_ms.max(1);
+        let frames = |ms: u32| (ms / frame_ms).max(1) as usize;
+        let window = frames(WINDOW_MS);
+        let max_lag = frames(MAX_LAG_MS);
+        Self {
+            frame_ms,
+            window,
+            min_lag: frames(MIN_LAG_MS).max(2),
+            max_lag,
+            check_every: frames(CHECK_INTERVAL_MS),
+            envelope: VecDeque::with_capacity(window + max_lag),
+            frames_since_check: 0,
+            streak_lag: None,
+            streak: 0,
+            clean_checks: 0,
+            latched: false,
+        }
+    }
+
+    /// Records one frame of interleaved 16-bit little-endian PCM.
+    ///
+    /// Returns the loop delay in milliseconds when a loop is first detected.
+    /// Fires once per loop; re-arms after the audio stops repeating.
+    pub fn observe_pcm16(&mut self, frame: &[u8]) -> Option<u64> {
+        let samples = frame.len() / 2;
+        if samples == 0 {
+            return None;
+        }
+        let sum: u64 = frame
+            .chunks_exact(2)
+            .map(|b| u64::from(i16::from_le_bytes([b[0], b[1]]).unsigned_abs()))
+            .sum();
+        self.record_level(sum as f32 / samples as f32)
+    }
+
+    fn record_level(&mut self, level: f32) -> Option<u64> {
+        if self.envelope.len() == self.window + self.max_lag {
+            self.envelope.pop_front();
+        }
+        self.envelope.push_back(level);
+
+        self.frames_since_check += 1;
+        if self.frames_since_check < self.check_every
+            || self.envelope.len() < self.window + self.min_lag
+        {
+            return None;
+        }
+        self.frames_since_check = 0;
+        self.check()
+    }
+
+    fn check(&mut self) -> Option<u64> {
+        let best = self.best_lag();
+
+        match best {
+            Some((lag, corr)) if corr >= MATCH_CORRELATION => {
+                self.clean_checks = 0;
+                let same_lag = self.streak_lag.is_some_and(|l| l.abs_diff(lag) <= 1);
+                self.streak = if same_lag { self.streak + 1 } else { 1 };
+                self.streak_lag = Some(lag);
+
+                if self.streak >= MATCH_CHECKS && !self.latched {
+                    self.latched = true;
+                    return Some(lag as u64 * u64::from(self.frame_ms));
+                }
+            }
+            other => {
+                self.streak = 0;
+                self.streak_lag = None;
+                // Silence or middling similarity neither confirms nor clears
+                if other.is_some_and(|(_, corr)| corr < CLEAN_CORRELATION) {
+                    self.clean_checks += 1;
+                    if self.clean_checks >= REARM_CHECKS {
+                        self.latched = false;
+                    }
+                }
+            }
+        }
+        None
+    }
+
+    /// Returns the lag (frames) whose past window best matches the latest
+    /// window, with its correlation. `None` if the latest window is silent.
+    ///
+    /// A loop also matches at multiples of its delay, so the shortest lag
+    /// within [`LAG_TIE_TOLERANCE`] of the best correlation wins.
+    fn best_lag(&self) -> Option<(usize, f32)> {
+        let len = self.envelope.len();
+        let (a, b) = self.envelope.as_slices();
+        let at = |i: usize| if i < a.len() { a[i] } else { b[i - a.len()] };
+
+        let recent_start = len - self.window;
+        let (recent_mean, recent_dev) = stats((recent_start..len).map(at));
+        if recent_mean < SILENCE_LEVEL || recent_dev <= f32::EPSILON {
+            return None;
+        }
+
+        let max_lag = self.max_lag.min(recent_start);
+        let scores: Vec<(usize, f32)> = (self.min_lag..=max_lag)
+            .map(|lag| {
+                let past_start = recent_start - lag;
+                let (past_mean, past_dev) = stats((past_start..past_start + self.window).map(at));
+                if past_dev <= f32::EPSILON {
+                    return (lag, 0.0);
+                }
+                let cov: f32 = (0..self.window)
+                    .map(|i| {
+                        (at(recent_start + i) - recent_mean) * (at(past_start + i) - past_mean)
+                    })
+                    .sum::<f32>()
+                    / self.window as f32;
+                (lag, cov / (recent_dev * past_dev))
+            })
+            .collect();
+
+        let best = scores
+            .iter()
+            .map(|&(_, corr)| corr)
+            .fold(f32::MIN, f32::max);
+        scores
+            .into_iter()
+            .find(|&(_, corr)| corr >= best - LAG_TIE_TOLERANCE)
+    }
+}
+
+/// Mean and standard deviation of a series.
+fn stats(values: impl Iterator<Item = f32> + Clone) -> (f32, f32) {
+    let (n, sum) = values
+        .clone()
+        .fold((0usize, 0.0f32), |(n, s), v| (n + 1, s + v));
+    if n == 0 {
+        return (0.0, 0.0);
+    }
+    let mean = sum / n as f32;
+    let var = values.map(|v| (v - mean) * (v - mean)).sum::<f32>() / n as f32;
+    (mean, var.sqrt())
+}
+
+/// Returns whether tab metadata text points at one of our own streams.
+///
+/// Matches the server's `host:port`, a `/stream/` URL path, or a tab titled
+/// after the stream file (Chrome titles direct media tabs by file name).
+pub fn mentions_own_stream(text: &str, server_host: &str) -> bool {
+    let text = text.trim().to_ascii_lowercase();
+    if (!server_host.is_empty() && text.contains(&server_host.to_ascii_lowercase()))
+        || text.contains("/stream/")
+    {
+        return true;
+    }
+    let first_word = text.split_whitespace().next().unwrap_or_default();
+    matches!(first_word, "live.wav" | "live.flac" | "live.ogg")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FRAME_MS: u32 = 20;
+
+    /// Deterministic pseudo-random levels in a music-like range.
+    fn levels(count: usize, seed: u32) -> Vec<f32> {
+        let mut state = seed;
+        (0..count)
+            .map(|_| {
+                state = state.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+                1_000.0 + (state >> 16) as f32 % 8_000.0
+            })
+            .collect()
+    }
+
+    fn feed(detector: &mut FeedbackDetector, levels: &[f32]) -> Option<u64> {
+        levels
+            .iter()
+            .fold(None, |hit, &l| hit.or(detector.record_level(l)))
+    }
+
+    #[test]
+    fn detects_delayed_copy_of_itself() {
+        let mut detector = FeedbackDetector::new(FRAME_MS);
+        // 1.5s loop: after the first pass, ingest repeats what it sent earlier
+        let lag = 75;
+        let mut signal = levels(lag, 1);
+        for i in lag..lag * 12 {
+            signal.push(signal[i - lag]);
+        }
+
+        assert_eq!(feed(&mut detector, &signal), Some(lag as u64 * 20));
+    }
+
+    #[test]
+    fn warns_once_per_loop() {
+        let mut detector = FeedbackDetector::new(FRAME_MS);
+        let lag = 50;
+        let mut signal = levels(lag, 2);
+        for i in lag..lag * 30 {
+            signal.push(signal[i - lag]);
+        }
+
+        let hits = signal
+            .iter()
+            .filter(|&&l| detector.record_level(l).is_some())
+            .count();
+        assert_eq!(hits, 1);
+    }
+
+    #[test]
+    fn ignores_ordinary_audio() {
+        let mut detector = FeedbackDetector::new(FRAME_MS);
+        assert_eq!(feed(&mut detector, &levels(3_000, 3)), None);
+    }
+
+    #[test]
+    fn ignores_silence_and_constant_tones() {
+        let mut detector = FeedbackDetector::new(FRAME_MS);
+        assert_eq!(feed(&mut detector, &[0.0; 2_000]), None);
+        assert_eq!(feed(&mut detector, &[5_000.0; 2_000]), None);
+    }
+
+    #[test]
+    fn measures_pcm16_levels() {
+        let mut detector = FeedbackDetector::new(FRAME_MS);
+        let frame: Vec<u8> = [1000i16, -1000, 3000, -3000]
+            .iter()
+            .flat_map(|s| s.to_le_bytes())
+            .collect();
+        detector.observe_pcm16(&frame);
+        assert_eq!(detector.envelope.back(), Some(&2_000.0));
+    }
+
+    #[test]
+    fn matches_own_stream_metadata() {
+        assert!(mentions_own_stream("live.wav", "192.168.1.5:49400"));
+        assert!(mentions_own_stream("LIVE.FLAC (audio)", ""));
+        assert!(mentions_own_stream(
+            "http://192.168.1.5:49400/stream/abc/live.ogg",
+            "192.168.1.5:49400"
+        ));
+        assert!(mentions_own_stream(
+            "192.168.1.5:49400",
+            "192.168.1.5:49400"
+        ));
+        assert!(!mentions_own_stream("Live at Wembley", "192.168.1.5:49400"));
+        assert!(!mentions_own_stream("YouTube", "192.168.1.5:49400"));
+    }
+}
@@ -0,0 +1,269 @@
+//! FLAC stream header handling for live HTTP streams.
+//!
+//! The extension's WebCodecs encoder emits the FLAC stream header
+//! (`fLaC` + STREAMINFO) once, as the first WebSocket frame, then raw FLAC
+//! frames. Left in the rolling prefill buffer, that header falls out after a
+//! few seconds and later connections (second speakers, resumes) start with
+//! bare frames, which Sonos only plays after a long resync. Instead, the
+//! header is captured once, normalized for live streaming, and prepended to
+//! every connection, like the WAV header for PCM.
+
+use bytes::{BufMut, Bytes, BytesMut};
+
+use crate::stream::AudioFormat;
+
+/// FLAC stream marker.
+const FLAC_MARKER: &[u8; 4] = b"fLaC";
+
+/// STREAMINFO body length in bytes.
+const STREAMINFO_LEN: usize = 34;
+
+/// Metadata block header length in bytes.
+const BLOCK_HEADER_LEN: usize = 4;
+
+/// Metadata block header for a final STREAMINFO block (last-block flag, type 0, length 34).
+const LAST_STREAMINFO_BLOCK_HEADER: [u8; BLOCK_HEADER_LEN] = [0x80, 0x00, 0x00, 0x22];
+
+/// Smallest and largest block sizes permitted by the FLAC format.
+const MIN_BLOCK_SIZE: u16 = 16;
+const MAX_BLOCK_SIZE: u16 = 65535;
+
+/// Returns whether `frame` is a FLAC stream header rather than audio.
+///
+/// Accepts the forms WebCodecs implementations produce for the decoder
+/// description: full `fLaC` stream header, or a bare STREAMINFO block with
+/// or without its block header.
+pub fn is_stream_header(frame: &[u8]) -> bool {
+    frame.starts_with(FLAC_MARKER)
+        || (!is_frame_start(frame)
+            && (frame.len() == STREAMINFO_LEN || frame.len() == BLOCK_HEADER_LEN + STREAMINFO_LEN))
+}
+
+/// Returns whether `frame` begins with a FLAC frame sync code.
+///
+/// Frame headers start with the 14-bit sync `0b11111111_111110`, a reserved
+/// zero bit, and the blocking-strategy bit.
+pub fn is_frame_start(frame: &[u8]) -> bool {
+    matches!(frame, [0xFF, b, ..] if b & 0xFE == 0xF8)
+}
+
+/// Returns the number of samples (per channel) in a FLAC frame.
+///
+/// Reads the block size from the frame header, which may be stored after
+/// the UTF-8 coded frame number. Returns `None` for non-frames.
+pub fn frame_block_size(frame: &[u8]) -> Option<u32> {
+    if !is_frame_start(frame) {
+        return None;
+    }
+    let code = frame.get(2)? >> 4;
+    match code {
+        1 => Some(192),
+        2..=5 => Some(576 << (code - 2)),
+        8..=15 => Some(256 << (code - 8)),
+        6 | 7 => {
+            // Explicit size follows the UTF-8 coded frame/sample number
+            let lead = *frame.get(4)?;
+            let number_len = match lead.leading_ones() {
+                0 => 1,
+                n @ 2..=7 => n as usize,
+                _ => return None,
+            };
+            let at = 4 + number_len;
+            if code == 6 {
+                Some(u32::from(*frame.get(at)?) + 1)
+            } else {
+                let bytes = frame.get(at..at + 2)?;
+                Some(u32::from(u16::from_be_bytes([bytes[0], bytes[1]])) + 1)
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Rewrites an encoder-provided header into a live-stream header.
+///
+/// The result is `fLaC` + a single final STREAMINFO block with the total
+/// sample count, frame size bounds, and MD5 zeroed ("unknown"). A nonzero
+/// total tells the decoder the stream has a fixed length it can seek in and
+/// buffer against, which a live stream doesn't. Any other metadata blocks
+/// (padding, Vorbis comments) are dropped.
+///
+/// Returns `None` if no STREAMINFO block can be found.
+pub fn normalize_stream_header(header: &[u8]) -> Option<Bytes> {
+    let streaminfo = if let Some(rest) = header.strip_prefix(FLAC_MARKER) {
+        // First metadata block must be STREAMINFO (type 0)
+        let block = rest.get(..BLOCK_HEADER_LEN + STREAMINFO_LEN)?;
+        if block[0] & 0x7F != 0 {
+            return None;
+        }
+        &block[BLOCK_HEADER_LEN..]
+    } else if header.len() == BLOCK_HEADER_LEN + STREAMINFO_LEN {
+        &header[BLOCK_HEADER_LEN..]
+    } else if header.len() == STREAMINFO_LEN {
+        header
+    } else {
+        return None;
+    };
+
+    let mut info = [0u8; STREAMINFO_LEN];
+    info.copy_from_slice(streaminfo);
+    // Min/max frame size (bytes 4-9): unknown
+    info[4..10].fill(0);
+    // Total samples: low 4 bits of byte 13 + bytes 14-17
+    info[13] &= 0xF0;
+    info[14..18].fill(0);
+    // MD5 signature: unknown
+    info[18..34].fill(0);
+
+    Some(assemble(&info))
+}
+
+/// Builds a live-stream header from the stream's audio format.
+///
+/// Fallback for when the encoder's own header never arrived. Block sizes are
+/// set to the format's full permitted range since the encoder's are unknown.
+pub fn synthesize_stream_header(format: AudioFormat) -> Bytes {
+    let mut info = BytesMut::with_capacity(STREAMINFO_LEN);
+    info.put_u16(MIN_BLOCK_SIZE);
+    info.put_u16(MAX_BLOCK_SIZE);
+    // Min/max frame size: unknown
+    info.put_slice(&[0; 6]);
+    // 20-bit sample rate | 3-bit channels-1 | 5-bit bps-1 | 36-bit total samples (unknown)
+    let packed = (u64::from(format.sample_rate & 0xF_FFFF) << 44)
+        | (u64::from(format.channels.saturating_sub(1) & 0x7) << 41)
+        | (u64::from(format.bits_per_sample.saturating_sub(1) & 0x1F) << 36);
+    info.put_u64(packed);
+    // MD5 signature: unknown
+    info.put_slice(&[0; 16]);
+
+    assemble(&info)
+}
+
+fn assemble(streaminfo: &[u8]) -> Bytes {
+    let mut header = BytesMut::with_capacity(FLAC_MARKER.len() + BLOCK_HEADER_LEN + STREAMINFO_LEN);
+    header.put_slice(FLAC_MARKER);
+    header.put_slice(&LAST_STREAMINFO_BLOCK_HEADER);
+    header.put_slice(streaminfo);
+    header.freeze()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// STREAMINFO for 48kHz stereo 24-bit, 4096-sample blocks, with encoder totals set.
+    fn encoder_streaminfo() -> Vec<u8> {
+        let mut info = vec![0x10, 0x00, 0x10, 0x00]; // block size 4096/4096
+        info.extend_from_slice(&[0x00, 0x00, 0x0E, 0x00, 0x40, 0x00]); // frame sizes
+        let packed: u64 = (48_000 << 44) | (1 << 41) | (23 << 36) | 123_456;
+        info.extend_from_slice(&packed.to_be_bytes());
+        info.extend_from_slice(&[0xAB; 16]); // MD5
+        assert_eq!(info.len(), STREAMINFO_LEN);
+        info
+    }
+
+    fn full_header(extra_block: bool) -> Vec<u8> {
+        let mut header = FLAC_MARKER.to_vec();
+        header.push(if extra_block { 0x00 } else { 0x80 });
+        header.extend_from_slice(&[0x00, 0x00, 0x22]);
+        header.extend_from_slice(&encoder_streaminfo());
+        if extra_block {
+            // Padding block
+            header.extend_from_slice(&[0x81, 0x00, 0x00, 0x04, 0, 0, 0, 0]);
+        }
+        header
+    }
+
+    #[test]
+    fn normalizes_all_header_forms_identically() {
+        let info = encoder_streaminfo();
+        let mut block = LAST_STREAMINFO_BLOCK_HEADER.to_vec();
+        block.extend_from_slice(&info);
+
+        let expected = normalize_stream_header(&full_header(false)).unwrap();
+        assert_eq!(
+            normalize_stream_header(&full_header(true)).unwrap(),
+            expected
+        );
+        assert_eq!(normalize_stream_header(&block).unwrap(), expected);
+        assert_eq!(normalize_stream_header(&info).unwrap(), expected);
+        assert_eq!(expected.len(), 42);
+    }
+
+    #[test]
+    fn normalized_header_has_unknown_length() {
+        let header = normalize_stream_header(&full_header(true)).unwrap();
+        let info = &header[8..];
+
+        assert_eq!(&header[..8], b"fLaC\x80\x00\x00\x22");
+        assert_eq!(&info[..4], &[0x10, 0x00, 0x10, 0x00], "block sizes kept");
+        assert!(info[4..10].iter().all(|&b| b == 0), "frame sizes unknown");
+        assert_eq!(info[13] & 0x0F, 0, "total samples unknown");
+        assert!(
+            info[14..].iter().all(|&b| b == 0),
+            "total samples and MD5 unknown"
+        );
+        assert_eq!(
+            &info[10..13],
+            &encoder_streaminfo()[10..13],
+            "format bits kept"
+        );
+    }
+
+    #[test]
+    fn synthesized_header_encodes_format() {
+        let header = synthesize_stream_header(AudioFormat::new(48_000, 2, 24));
+        let expected = normalize_stream_header(&full_header(false)).unwrap();
+
+        // Same format fields as the encoder's header (bytes 10-13 of STREAMINFO)
+        assert_eq!(&header[8 + 10..8 + 14], &expected[8 + 10..8 + 14]);
+        assert_eq!(header.len(), expected.len());
+    }
+
+    #[test]
+    fn distinguishes_headers_from_frames() {
+        assert!(is_stream_header(&full_header(false)));
+        assert!(is_stream_header(&encoder_streaminfo()));
+        assert!(!is_stream_header(&[0xFF, 0xF8, 0x69, 0x18, 0x00]));
+        assert!(!is_stream_header(b"not flac at all"));
+
+        assert!(is_frame_start(&[0xFF, 0xF8, 0x00]));
+        assert!(is_frame_start(&[0xFF, 0xF9, 0x00]));
+        assert!(!is_frame_start(&[0xFF, 0xFA]));
+        assert!(!is_frame_start(b"fLaC"));
+        assert!(!is_frame_start(&[0xFF]));
+    }
+
+    #[test]
+    fn reads_frame_block_sizes() {
+        // Coded sizes
+        assert_eq!(frame_block_size(&[0xFF, 0xF8, 0x19, 0x18, 0x00]), Some(192));
+        assert_eq!(
+            frame_block_size(&[0xFF, 0xF8, 0xC9, 0x18, 0x00]),
+            Some(4096)
+        );
+        // 8-bit explicit size after a 1-byte frame number: 0x7F + 1
+        assert_eq!(
+            frame_block_size(&[0xFF, 0xF8, 0x69, 0x18, 0x05, 0x7F]),
+            Some(128)
+        );
+        // 16-bit explicit size after a 2-byte frame number: 0x0FFF + 1
+        assert_eq!(
+            frame_block_size(&[0xFF, 0xF8, 0x79, 0x18, 0xC2, 0x80, 0x0F, 0xFF]),
+            Some(4096)
+        );
+        assert_eq!(frame_block_size(&[0xFF, 0xF8, 0x09, 0x18]), None);
+        assert_eq!(frame_block_size(&[0xFF, 0xF8, 0x79, 0x18, 0x05]), None);
+        assert_eq!(frame_block_size(b"fLaC"), None);
+    }
+
+    #[test]
+    fn rejects_malformed_headers() {
+        assert_eq!(normalize_stream_header(b"fLaC\x80\x00"), None);
+        // First block is not STREAMINFO
+        let mut header = full_header(false);
+        header[4] = 0x84;
+        assert_eq!(normalize_stream_header(&header), None);
+        assert_eq!(normalize_stream_header(&[0u8; 20]), None);
+    }
+}
@@ -203,6 +203,7 @@ mod tests {
             title: Some("Test Song".to_string()),
             artist: None,
             source: None,
+            ..Default::default()
         };
         let result = IcyFormatter::format_metadata(&metadata);
         assert_eq!(result[0], 2); // Two 16-byte blocks for "StreamTitle='Test Song';"
@@ -215,6 +216,7 @@ mod tests {
             title: Some("Song".to_string()),
             artist: Some("Artist".to_string()),
             source: None,
+            ..Default::default()
         };
         let result = IcyFormatter::format_metadata(&metadata);
         let content = String::from_utf8_lossy(&result[1..]);
@@ -227,6 +229,7 @@ mod tests {
             title: Some("It's a Test".to_string()), // ASCII apostrophe U+0027
             artist: None,
             source: None,
+            ..Default::default()
         };
         let result = IcyFormatter::format_metadata(&metadata);
         let content = String::from_utf8_lossy(&result[1..]);
@@ -286,6 +289,7 @@ mod tests {
             title: Some("Song A".to_string()),
             artist: Some("Artist".to_string()),
             source: None,
+            ..Default::default()
         };
 
         // First injection with metadata1
@@ -306,6 +310,7 @@ mod tests {
             title: Some("Song B".to_string()),
             artist: Some("Artist".to_string()),
             source: None,
+            ..Default::default()
         };
 
         let result3 = injector.inject(&chunk, &metadata2);
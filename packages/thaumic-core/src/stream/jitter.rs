@@ -0,0 +1,278 @@
+//! Ingest jitter tracking and adaptive cadence queue sizing.
+//!
+//! Frames from the browser arrive over WebSocket with irregular spacing
+//! (tab throttling, GC pauses, Wi-Fi retransmits). The cadence queue absorbs
+//! that irregularity, but a fixed bound either wastes latency on clean
+//! networks or drops bursts on noisy ones. [`AdaptiveQueue`] measures
+//! inter-arrival jitter and moves the bound between configured limits:
+//! it grows promptly when jitter rises and shrinks back only after jitter
+//! has stayed low for a while.
+
+use std::time::Duration;
+
+use tokio::time::Instant;
+
+/// Smoothing divisor for the jitter estimate (RFC 3550 uses 16).
+const JITTER_SMOOTHING: f64 = 16.0;
+
+/// Queue headroom as a multiple of the jitter estimate.
+const JITTER_HEADROOM_FACTOR: f64 = 3.0;
+
+/// Minimum time between two growth steps (limits event churn during bursts).
+const GROW_COOLDOWN: Duration = Duration::from_secs(1);
+
+/// How long the target must stay below the current bound before shrinking.
+const SHRINK_HOLD: Duration = Duration::from_secs(10);
+
+/// Smoothed inter-arrival jitter estimator (RFC 3550 §6.4.1 style).
+///
+/// Jitter is the mean deviation of the gap between consecutive arrivals from
+/// the nominal frame duration.
+#[derive(Debug)]
+pub struct JitterEstimator {
+    frame_duration: Duration,
+    last_arrival: Option<Instant>,
+    jitter_ms: f64,
+    max_jitter_ms: f64,
+}
+
+impl JitterEstimator {
+    /// Creates an estimator for frames of the given nominal duration.
+    pub fn new(frame_duration_ms: u32) -> Self {
+        Self {
+            frame_duration: Duration::from_millis(u64::from(frame_duration_ms)),
+            last_arrival: None,
+            jitter_ms: 0.0,
+            max_jitter_ms: 0.0,
+        }
+    }
+
+    /// Records a frame arrival and returns the updated jitter estimate (ms).
+    pub fn record_arrival(&mut self, now: Instant) -> f64 {
+        if let Some(last) = self.last_arrival.replace(now) {
+            let gap_ms = now.saturating_duration_since(last).as_secs_f64() * 1000.0;
+            let deviation = (gap_ms - self.frame_duration.as_secs_f64() * 1000.0).abs();
+            self.jitter_ms += (deviation - self.jitter_ms) / JITTER_SMOOTHING;
+            self.max_jitter_ms = self.max_jitter_ms.max(self.jitter_ms);
+        }
+        self.jitter_ms
+    }
+
+    /// Current smoothed jitter in milliseconds.
+    pub fn jitter_ms(&self) -> f64 {
+        self.jitter_ms
+    }
+
+    /// Highest smoothed jitter seen so far, in milliseconds.
+    pub fn max_jitter_ms(&self) -> f64 {
+        self.max_jitter_ms
+    }
+}
+
+/// A change of the cadence queue bound.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct QueueAdaptation {
+    /// Previous queue bound (frames).
+    pub previous_size: usize,
+    /// New queue bound (frames).
+    pub queue_size: usize,
+    /// Smoothed jitter that triggered the change (ms).
+    pub jitter_ms: f64,
+}
+
+/// Jitter-driven cadence queue bound, kept within `[min_size, max_size]`.
+#[derive(Debug)]
+pub struct AdaptiveQueue {
+    estimator: JitterEstimator,
+    frame_duration_ms: u32,
+    min_size: usize,
+    max_size: usize,
+    size: usize,
+    last_grow: Option<Instant>,
+    /// When the target first dropped below the current size (for shrink hold).
+    below_since: Option<Instant>,
+    adaptations: u64,
+}
+
+impl AdaptiveQueue {
+    /// Creates an adaptive bound starting at `min_size`.
+    ///
+    /// `min_size` is the configured streaming buffer in frames; `max_size`
+    /// caps how much extra latency jitter may add.
+    pub fn new(frame_duration_ms: u32, min_size: usize, max_size: usize) -> Self {
+        let min_size = min_size.max(1);
+        Self {
+            estimator: JitterEstimator::new(frame_duration_ms),
+            frame_duration_ms: frame_duration_ms.max(1),
+            min_size,
+            max_size: max_size.max(min_size),
+            size: min_size,
+            last_grow: None,
+            below_since: None,
+            adaptations: 0,
+        }
+    }
+
+    /// Current queue bound in frames.
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Number of times the bound has changed.
+    pub fn adaptations(&self) -> u64 {
+        self.adaptations
+    }
+
+    /// Highest smoothed jitter observed, in milliseconds.
+    pub fn max_jitter_ms(&self) -> f64 {
+        self.estimator.max_jitter_ms()
+    }
+
+    /// Records a frame arrival and returns the adaptation, if the bound changed.
+    pub fn record_arrival(&mut self, now: Instant) -> Option<QueueAdaptation> {
+        let jitter_ms = self.estimator.record_arrival(now);
+        let target = self.target_size(jitter_ms);
+
+        let new_size = if target > self.size {
+            self.below_since = None;
+            let cooled_down = self
+                .last_grow
+                .map_or(true, |t| now.saturating_duration_since(t) >= GROW_COOLDOWN);
+            if !cooled_down {
+                return None;
+            }
+            self.last_grow = Some(now);
+            target
+        } else if target < self.size {
+            let since = *self.below_since.get_or_insert(now);
+            if now.saturating_duration_since(since) < SHRINK_HOLD {
+                return None;
+            }
+            self.below_since = None;
+            target
+        } else {
+            self.below_since = None;
+            return None;
+        };
+
+        let previous_size = std::mem::replace(&mut self.size, new_size);
+        self.adaptations += 1;
+        Some(QueueAdaptation {
+            previous_size,
+            queue_size: new_size,
+            jitter_ms,
+        })
+    }
+
+    /// Queue size needed to absorb the given jitter.
+    ///
+    /// Rounds rather than ceils so sub-frame timestamp noise (scheduler
+    /// wakeups, the cadence thread's poll interval) doesn't add a frame.
+    fn target_size(&self, jitter_ms: f64) -> usize {
+        let extra =
+            (JITTER_HEADROOM_FACTOR * jitter_ms / f64::from(self.frame_duration_ms)).round();
+        (self.min_size + extra as usize).clamp(self.min_size, self.max_size)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FRAME_MS: u32 = 20;
+
+    /// Feeds `count` arrivals spaced `gap_ms` apart, returning the last adaptation.
+    fn feed(
+        queue: &mut AdaptiveQueue,
+        start: &mut Instant,
+        gap_ms: u64,
+        count: usize,
+    ) -> Option<QueueAdaptation> {
+        let mut last = None;
+        for _ in 0..count {
+            *start += Duration::from_millis(gap_ms);
+            if let Some(a) = queue.record_arrival(*start) {
+                last = Some(a);
+            }
+        }
+        last
+    }
+
+    #[test]
+    fn steady_arrivals_have_no_jitter() {
+        let mut estimator = JitterEstimator::new(FRAME_MS);
+        let mut t = Instant::now();
+        for _ in 0..50 {
+            t += Duration::from_millis(u64::from(FRAME_MS));
+            estimator.record_arrival(t);
+        }
+        assert!(estimator.jitter_ms() < 0.01);
+    }
+
+    #[test]
+    fn alternating_gaps_converge_to_deviation() {
+        let mut estimator = JitterEstimator::new(FRAME_MS);
+        let mut t = Instant::now();
+        for i in 0..500 {
+            // Alternating 10ms/30ms gaps: every gap deviates 10ms from nominal
+            t += Duration::from_millis(if i % 2 == 0 { 10 } else { 30 });
+            estimator.record_arrival(t);
+        }
+        assert!((estimator.jitter_ms() - 10.0).abs() < 0.5);
+    }
+
+    #[test]
+    fn grows_under_jitter_within_limits() {
+        let mut queue = AdaptiveQueue::new(FRAME_MS, 10, 15);
+        let mut t = Instant::now();
+        queue.record_arrival(t);
+
+        // Bursty arrivals: 100ms silence then back-to-back frames
+        let mut grew = None;
+        for _ in 0..20 {
+            t += Duration::from_millis(100);
+            grew = queue.record_arrival(t).or(grew);
+            grew = feed(&mut queue, &mut t, 0, 4).or(grew);
+        }
+
+        let adaptation = grew.expect("queue should grow under jitter");
+        assert!(adaptation.queue_size > adaptation.previous_size);
+        assert_eq!(queue.size(), 15, "bound must not exceed max_size");
+    }
+
+    #[test]
+    fn shrinks_only_after_hold() {
+        let mut queue = AdaptiveQueue::new(FRAME_MS, 10, 50);
+        let mut t = Instant::now();
+        queue.record_arrival(t);
+        for _ in 0..20 {
+            t += Duration::from_millis(100);
+            queue.record_arrival(t);
+            feed(&mut queue, &mut t, 0, 4);
+        }
+        let grown = queue.size();
+        assert!(grown > 10);
+
+        // Calm arrivals: jitter decays but the bound holds for SHRINK_HOLD
+        let calm_frames = (SHRINK_HOLD.as_millis() as u64 / u64::from(FRAME_MS)) as usize;
+        assert_eq!(
+            feed(&mut queue, &mut t, u64::from(FRAME_MS), calm_frames / 2),
+            None
+        );
+        assert_eq!(queue.size(), grown);
+
+        let shrink = feed(&mut queue, &mut t, u64::from(FRAME_MS), calm_frames * 2)
+            .expect("queue should shrink once jitter stays low");
+        assert!(shrink.queue_size < grown);
+        assert!(queue.size() >= 10, "bound must not drop below min_size");
+    }
+
+    #[test]
+    fn steady_stream_never_adapts() {
+        let mut queue = AdaptiveQueue::new(FRAME_MS, 10, 50);
+        let mut t = Instant::now();
+        assert_eq!(feed(&mut queue, &mut t, u64::from(FRAME_MS), 1000), None);
+        assert_eq!(queue.size(), 10);
+        assert_eq!(queue.adaptations(), 0);
+    }
+}
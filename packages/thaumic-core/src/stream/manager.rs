@@ -5,19 +5,48 @@ use std::collections::{HashMap, VecDeque};
 use std::net::IpAddr;
 use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::{Arc, OnceLock};
-use std::time::Instant;
+use std::time::{Duration, Instant};
 use tokio::sync::broadcast;
 use uuid::Uuid;
 
 use crate::state::StreamingConfig;
-use crate::stream::AudioFormat;
+use crate::stream::{flac, AudioFormat, FeedbackDetector, StartupLog, StartupReport, StartupStage};
+use crate::utils::constant_time_eq;
+
+/// Wrong PINs a locked stream takes before it refuses attempts for a while.
+const PIN_ATTEMPTS_BEFORE_BACKOFF: u32 = 5;
+
+/// How long attempts are refused once the limit is reached; doubles with
+/// each further wrong PIN.
+const PIN_BACKOFF_BASE: Duration = Duration::from_secs(30);
+
+/// Longest a stream refuses PIN attempts for.
+const PIN_BACKOFF_MAX: Duration = Duration::from_secs(15 * 60);
+
+/// Outcome of checking a PIN against a stream.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PinCheck {
+    /// The PIN matches, or the stream isn't locked.
+    Accepted,
+    /// The PIN is missing or wrong.
+    Rejected,
+    /// Too many wrong PINs were tried; attempts are refused for this long.
+    Backoff(Duration),
+}
+
+/// Wrong PINs tried against a locked stream.
+#[derive(Debug, Default)]
+struct PinFailures {
+    count: u32,
+    refused_until: Option<Instant>,
+}
 
 /// Supported audio codecs for the stream.
 ///
 /// Note: `Pcm` outputs as WAV container (PCM + RIFF headers) for Sonos compatibility.
 /// The MIME type and file extensions remain `audio/wav` and `.wav` because that's
 /// what Sonos expects, but the actual codec is uncompressed PCM.
-#[derive(Debug, Clone, Copy, Serialize, PartialEq)]
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq, Hash)]
 #[serde(rename_all = "lowercase")]
 pub enum AudioCodec {
     Pcm,
@@ -88,9 +117,37 @@ impl AudioCodec {
 pub struct StreamMetadata {
     pub title: Option<String>,
     pub artist: Option<String>,
+    /// Album title, filled in by metadata enrichment.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub album: Option<String>,
+    /// Cover art URL, filled in by metadata enrichment.
+    #[serde(
+        rename = "artworkUrl",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub artwork_url: Option<String>,
     /// Source name derived from tab URL (e.g., "YouTube", "Spotify").
     /// Used to format album as "{source} • {APP_NAME}" in DIDL-Lite.
     pub source: Option<String>,
+    /// URL of the page being cast. Shown in activity views, never sent to
+    /// speakers.
+    #[serde(rename = "sourceUrl", default, skip_serializing_if = "Option::is_none")]
+    pub source_url: Option<String>,
+    /// Title of the page being cast (e.g. the tab title).
+    #[serde(
+        rename = "sourceTitle",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub source_title: Option<String>,
+    /// Favicon URL of the page being cast.
+    #[serde(
+        rename = "faviconUrl",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub favicon_url: Option<String>,
 }
 
 /// A playback epoch represents one Sonos streaming session per speaker.
@@ -130,6 +187,9 @@ pub struct StreamTiming {
 
     /// Monotonic epoch counter (shared across all IPs).
     epoch_counter: AtomicU64,
+
+    /// Cold-start timeline per speaker, relative to stream creation.
+    pub startup: StartupLog,
 }
 
 impl StreamTiming {
@@ -139,6 +199,7 @@ impl StreamTiming {
             first_frame_at: OnceLock::new(),
             current_epoch_by_ip: parking_lot::Mutex::new(HashMap::new()),
             epoch_counter: AtomicU64::new(0),
+            startup: StartupLog::new(Instant::now()),
         }
     }
 
@@ -234,6 +295,13 @@ pub struct StreamState {
     /// Used for WAV header generation and silence frame creation.
     pub audio_format: AudioFormat,
     pub metadata: Arc<parking_lot::RwLock<StreamMetadata>>,
+    /// User-given name for the stream (e.g. "Vinyl rip"). Unlike the
+    /// metadata, it isn't replaced by track updates.
+    label: parking_lot::RwLock<Option<String>>,
+    /// PIN required to control the stream's speakers through the API.
+    pin: parking_lot::RwLock<Option<String>>,
+    /// Wrong PINs tried since the last correct one.
+    pin_failures: parking_lot::Mutex<PinFailures>,
     /// Broadcast channel for distributing audio frames to HTTP clients
     pub tx: broadcast::Sender<Bytes>,
     /// Recent frames buffer with timestamps for epoch calculation.
@@ -250,6 +318,11 @@ pub struct StreamState {
     /// Frame duration in milliseconds for cadence timing.
     /// Determines silence frame duration and cadence tick interval.
     pub frame_duration_ms: u32,
+    /// Normalized FLAC stream header, captured from the encoder's first frame.
+    /// Kept out of the frame buffer so every connection can be given it.
+    flac_header: OnceLock<Bytes>,
+    /// Watches PCM ingest for the stream being captured back into itself.
+    feedback: parking_lot::Mutex<FeedbackDetector>,
 }
 
 impl StreamState {
@@ -286,6 +359,9 @@ impl StreamState {
             codec,
             audio_format,
             metadata: Arc::new(parking_lot::RwLock::new(StreamMetadata::default())),
+            label: parking_lot::RwLock::new(None),
+            pin: parking_lot::RwLock::new(None),
+            pin_failures: parking_lot::Mutex::new(PinFailures::default()),
             tx,
             buffer: Arc::new(parking_lot::RwLock::new(VecDeque::with_capacity(
                 buffer_frames,
@@ -295,9 +371,36 @@ impl StreamState {
             timing: StreamTiming::new(),
             streaming_buffer_ms,
             frame_duration_ms,
+            flac_header: OnceLock::new(),
+            feedback: parking_lot::Mutex::new(FeedbackDetector::new(frame_duration_ms)),
         }
     }
 
+    /// Records a cold-start stage for a speaker, now.
+    ///
+    /// Logs the speaker's whole timeline once its first audio is sent.
+    pub fn record_startup(&self, ip: IpAddr, stage: StartupStage) {
+        if !self.timing.startup.record(ip, stage, Instant::now()) {
+            return;
+        }
+        if stage == StartupStage::FirstAudio {
+            if let Some(report) =
+                self.timing
+                    .startup
+                    .report(&self.id, ip, self.timing.first_frame_at())
+            {
+                log::info!("[Startup] {}", report);
+            }
+        }
+    }
+
+    /// Returns the cold-start timeline of every speaker of this stream.
+    pub fn startup_reports(&self) -> Vec<StartupReport> {
+        self.timing
+            .startup
+            .reports(&self.id, self.timing.first_frame_at())
+    }
+
     /// Pushes a new audio frame into the stream.
     ///
     /// The frame is timestamped, added to the buffer, and broadcast to HTTP clients.
@@ -305,6 +408,22 @@ impl StreamState {
     /// Returns `true` if this was the first frame (stream just became ready),
     /// `false` otherwise.
     pub fn push_frame(&self, frame: Bytes) -> bool {
+        // FLAC stream header is per-connection data, not audio: stash it
+        if self.codec == AudioCodec::Flac && flac::is_stream_header(&frame) {
+            match flac::normalize_stream_header(&frame) {
+                Some(header) => {
+                    log::debug!("[Stream] {} captured FLAC stream header", self.id);
+                    let _ = self.flac_header.set(header);
+                }
+                None => log::warn!(
+                    "[Stream] {} dropped malformed FLAC header ({} bytes)",
+                    self.id,
+                    frame.len()
+                ),
+            }
+            return false;
+        }
+
         let captured_at = Instant::now();
 
         // Add to recent buffer (ring buffer behavior)
@@ -339,11 +458,111 @@ impl StreamState {
         is_first_frame
     }
 
+    /// Checks an ingest frame for a feedback loop.
+    ///
+    /// Returns the loop delay in milliseconds when a loop is first detected.
+    /// Only 16-bit PCM is inspected; other codecs always return `None`.
+    pub fn check_feedback(&self, frame: &[u8]) -> Option<u64> {
+        if self.codec != AudioCodec::Pcm || self.audio_format.bits_per_sample != 16 {
+            return None;
+        }
+        self.feedback.lock().observe_pcm16(frame)
+    }
+
+    /// Returns the FLAC stream header to send at the start of each connection.
+    ///
+    /// Uses the encoder's header when it has arrived, otherwise one
+    /// synthesized from the stream's audio format.
+    #[must_use]
+    pub fn flac_stream_header(&self) -> Bytes {
+        self.flac_header.get().cloned().unwrap_or_else(|| {
+            log::warn!(
+                "[Stream] {} has no FLAC header from encoder, synthesizing one",
+                self.id
+            );
+            flac::synthesize_stream_header(self.audio_format)
+        })
+    }
+
     /// Updates the metadata for the stream.
     pub fn update_metadata(&self, metadata: StreamMetadata) {
         *self.metadata.write() = metadata;
     }
 
+    /// Returns the stream's user-given label.
+    #[must_use]
+    pub fn label(&self) -> Option<String> {
+        self.label.read().clone()
+    }
+
+    /// Sets the stream's label, returning whether it changed.
+    pub fn set_label(&self, label: Option<String>) -> bool {
+        let mut current = self.label.write();
+        if *current == label {
+            return false;
+        }
+        *current = label;
+        true
+    }
+
+    /// Returns whether the stream is locked with a PIN.
+    #[must_use]
+    pub fn is_locked(&self) -> bool {
+        self.pin.read().is_some()
+    }
+
+    /// Locks the stream with `pin`, or unlocks it with `None`.
+    pub fn set_pin(&self, pin: Option<String>) {
+        *self.pin.write() = pin;
+        *self.pin_failures.lock() = PinFailures::default();
+    }
+
+    /// Checks whether `pin` unlocks the stream. Unlocked streams accept any.
+    ///
+    /// After [`PIN_ATTEMPTS_BEFORE_BACKOFF`] wrong PINs every attempt,
+    /// right or wrong, is refused for a backoff that doubles with each
+    /// further wrong PIN. A missing PIN isn't a guess and isn't counted; a
+    /// correct one resets the count.
+    #[must_use]
+    pub fn check_pin(&self, pin: Option<&str>) -> PinCheck {
+        let expected = self.pin.read();
+        let Some(expected) = expected.as_deref() else {
+            return PinCheck::Accepted;
+        };
+        let mut failures = self.pin_failures.lock();
+        let now = Instant::now();
+        if let Some(until) = failures.refused_until.filter(|until| *until > now) {
+            return PinCheck::Backoff(until - now);
+        }
+        let Some(pin) = pin else {
+            return PinCheck::Rejected;
+        };
+        if constant_time_eq(pin.as_bytes(), expected.as_bytes()) {
+            *failures = PinFailures::default();
+            return PinCheck::Accepted;
+        }
+        failures.count += 1;
+        if let Some(excess) = failures.count.checked_sub(PIN_ATTEMPTS_BEFORE_BACKOFF) {
+            let backoff = PIN_BACKOFF_BASE
+                .saturating_mul(1 << excess.min(10))
+                .min(PIN_BACKOFF_MAX);
+            failures.refused_until = Some(now + backoff);
+        }
+        PinCheck::Rejected
+    }
+
+    /// Returns the number of HTTP connections currently pulling this stream.
+    #[must_use]
+    pub fn listener_count(&self) -> usize {
+        self.tx.receiver_count()
+    }
+
+    /// Returns when the newest buffered frame was received.
+    #[must_use]
+    pub fn last_frame_at(&self) -> Option<Instant> {
+        self.buffer.read().back().map(|frame| frame.captured_at)
+    }
+
     /// Returns the number of frames currently in the buffer.
     #[must_use]
     pub fn buffer_len(&self) -> usize {
@@ -356,12 +575,18 @@ impl StreamState {
     /// a broadcast receiver, ensuring late-joining clients receive prefill data
     /// without duplicates or gaps.
     ///
+    /// # Arguments
+    /// * `max_prefill` - Most recent frames to return; older ones are skipped
+    ///
     /// # Returns
     /// A tuple of (epoch_candidate, prefill_frames, live_receiver) where:
-    /// - `epoch_candidate`: Timestamp of oldest buffered frame (None if buffer empty)
+    /// - `epoch_candidate`: Timestamp of oldest returned frame (None if buffer empty)
     /// - `prefill_frames`: A `Vec<Bytes>` containing buffered frames to send immediately
     /// - `live_receiver`: A `broadcast::Receiver<Bytes>` for subsequent live frames
-    pub fn subscribe(&self) -> (Option<Instant>, Vec<Bytes>, broadcast::Receiver<Bytes>) {
+    pub fn subscribe(
+        &self,
+        max_prefill: usize,
+    ) -> (Option<Instant>, Vec<Bytes>, broadcast::Receiver<Bytes>) {
         // Hold the buffer lock while subscribing to ensure atomicity.
         // This prevents races where a frame could appear in both prefill and rx:
         // - Any push_frame() that completes before we lock will have its frame in buffer
@@ -372,8 +597,9 @@ impl StreamState {
         let rx = self.tx.subscribe();
 
         // Epoch candidate = timestamp of oldest frame we'll serve (T0 for this connection)
-        let epoch_candidate = buffer.front().map(|f| f.captured_at);
-        let prefill: Vec<Bytes> = buffer.iter().map(|f| f.data.clone()).collect();
+        let recent = buffer.range(buffer.len().saturating_sub(max_prefill)..);
+        let epoch_candidate = recent.clone().next().map(|f| f.captured_at);
+        let prefill: Vec<Bytes> = recent.map(|f| f.data.clone()).collect();
 
         (epoch_candidate, prefill, rx)
     }
@@ -1,16 +1,28 @@
 pub mod cadence;
+pub mod coalesce;
+pub mod feedback;
+pub mod flac;
 pub mod icy;
+pub mod jitter;
 pub mod manager;
+pub mod ogg;
+pub mod startup;
 pub mod wav;
 
 pub use cadence::{
-    create_wav_stream_with_cadence, lagged_error, CadenceConfig, LoggingStreamGuard,
+    create_wav_stream_with_cadence, lagged_error, spawn_cadence_thread, CadenceConfig,
+    CadenceThreadStream, EpochHook, LoggingStreamGuard,
 };
+pub use coalesce::{Coalesce, DEFAULT_COALESCE_MAX_BYTES};
+pub use feedback::{FeedbackCause, FeedbackDetector};
 pub use icy::{IcyMetadataInjector, ICY_METAINT};
+pub use jitter::{AdaptiveQueue, JitterEstimator, QueueAdaptation};
 pub use manager::{
-    AudioCodec, CleanupOrder, PlaybackEpoch, StreamMetadata, StreamRegistry, StreamState,
+    AudioCodec, CleanupOrder, PinCheck, PlaybackEpoch, StreamMetadata, StreamRegistry, StreamState,
     StreamTiming,
 };
+pub use ogg::{OggFlacMuxer, OGG_MIME_TYPE};
+pub use startup::{StartupLog, StartupReport, StartupStage};
 pub use wav::create_wav_header;
 
 use std::collections::HashMap;
@@ -39,9 +51,16 @@ pub const CROSSFADE_MS: u32 = 2;
 ///
 /// Silence frames are keyed by their byte length since different audio formats
 /// with the same duration may produce the same byte count. The cache is lazily
-/// initialized and never cleared (silence frames are small and finite).
+/// initialized and never cleared; once it holds [`MAX_SILENCE_CACHE_ENTRIES`]
+/// sizes, further sizes are allocated per call instead.
 static SILENCE_CACHE: OnceLock<RwLock<HashMap<usize, Bytes>>> = OnceLock::new();
 
+/// Most distinct silence frame sizes kept in the cache.
+///
+/// Each stream needs one size (its format at its frame duration), so this
+/// covers far more format/duration combinations than are live at once.
+const MAX_SILENCE_CACHE_ENTRIES: usize = 64;
+
 /// Gets a cached silence frame of the given byte length, or creates and caches one.
 ///
 /// This avoids ~200KB/s of allocations during delivery gaps by reusing
@@ -62,7 +81,9 @@ fn get_or_create_silence(byte_len: usize) -> Bytes {
     }
 
     let silence = Bytes::from(vec![0u8; byte_len]);
-    cache_write.insert(byte_len, silence.clone());
+    if cache_write.len() < MAX_SILENCE_CACHE_ENTRIES {
+        cache_write.insert(byte_len, silence.clone());
+    }
     silence
 }
 
@@ -71,7 +92,7 @@ fn get_or_create_silence(byte_len: usize) -> Bytes {
 /// Describes the PCM audio format being streamed, used for:
 /// - WAV header generation (sample rate, channels, bit depth)
 /// - Silence frame generation (keepalive during delivery gaps)
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct AudioFormat {
     pub sample_rate: u32,
     pub channels: u16,
@@ -385,6 +406,17 @@ mod tests {
             assert_eq!(silence.len(), 3840);
         }
 
+        #[test]
+        fn silence_frames_follow_frame_duration() {
+            let format = AudioFormat::new(48000, 2, 16);
+            for duration_ms in [5, 10, 20, 50] {
+                let silence = format.silence_frame(duration_ms);
+                assert_eq!(silence.len(), format.frame_bytes(duration_ms));
+                // Repeated requests share the cached buffer
+                assert_eq!(format.silence_frame(duration_ms).as_ptr(), silence.as_ptr());
+            }
+        }
+
         #[test]
         fn silence_frame_is_all_zeros() {
             let format = AudioFormat::new(48000, 2, 16);
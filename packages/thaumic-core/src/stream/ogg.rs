@@ -0,0 +1,273 @@
+//! Ogg container output for FLAC streams.
+//!
+//! Native FLAC has no packet framing, so generic players (and some DLNA
+//! renderers) can't find their place in a live stream without scanning for
+//! sync codes. Ogg wraps each FLAC frame in a checksummed page with a
+//! granule position, which those clients handle well. Sonos doesn't need it
+//! and keeps getting native FLAC.
+//!
+//! Implements the Ogg FLAC mapping (<https://xiph.org/flac/ogg_mapping.html>):
+//! one identification packet carrying STREAMINFO on the BOS page, one
+//! VORBIS_COMMENT header packet, then one audio packet per FLAC frame. Each
+//! audio frame gets its own page so pages never wait for more audio.
+
+use bytes::{BufMut, Bytes, BytesMut};
+
+use crate::protocol_constants::APP_NAME;
+use crate::stream::flac;
+
+/// MIME type for Ogg audio.
+pub const OGG_MIME_TYPE: &str = "audio/ogg";
+
+/// Page header flag: page continues a packet from the previous page.
+const FLAG_CONTINUED: u8 = 0x01;
+/// Page header flag: first page of the logical stream.
+const FLAG_BOS: u8 = 0x02;
+
+/// Maximum lacing segments per page.
+const MAX_SEGMENTS: usize = 255;
+
+/// Granule position meaning "no packet finishes on this page".
+const GRANULE_NONE: i64 = -1;
+
+/// Offset of the STREAMINFO metadata block header in a native stream header.
+const STREAMINFO_BLOCK_OFFSET: usize = 4;
+
+/// CRC-32 lookup table for Ogg (polynomial 0x04C11DB7, unreflected).
+const CRC_TABLE: [u32; 256] = {
+    let mut table = [0u32; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = (i as u32) << 24;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 0x8000_0000 != 0 {
+                (crc << 1) ^ 0x04C1_1DB7
+            } else {
+                crc << 1
+            };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+};
+
+fn crc32(data: &[u8]) -> u32 {
+    data.iter().fold(0u32, |crc, &byte| {
+        (crc << 8) ^ CRC_TABLE[((crc >> 24) as u8 ^ byte) as usize]
+    })
+}
+
+/// Wraps native FLAC frames into an Ogg FLAC stream for one connection.
+pub struct OggFlacMuxer {
+    serial: u32,
+    sequence: u32,
+    /// Samples (per channel) written so far; the Ogg granule position.
+    granule: i64,
+}
+
+impl OggFlacMuxer {
+    /// Creates a muxer for a new logical stream with the given serial number.
+    pub fn new(serial: u32) -> Self {
+        Self {
+            serial,
+            sequence: 0,
+            granule: 0,
+        }
+    }
+
+    /// Returns the Ogg header pages built from a native FLAC stream header.
+    ///
+    /// `flac_header` is `fLaC` followed by the STREAMINFO block, as produced
+    /// by [`flac::normalize_stream_header`]. Must be sent before any frames.
+    pub fn header_pages(&mut self, flac_header: &[u8]) -> Bytes {
+        let streaminfo_block = &flac_header[STREAMINFO_BLOCK_OFFSET..];
+
+        // Identification packet: 0x7F "FLAC" v1.0, one header packet follows
+        let mut ident = BytesMut::with_capacity(13 + streaminfo_block.len());
+        ident.put_u8(0x7F);
+        ident.put_slice(b"FLAC");
+        ident.put_slice(&[1, 0]);
+        ident.put_u16(1);
+        ident.put_slice(b"fLaC");
+        ident.put_slice(streaminfo_block);
+        // STREAMINFO is no longer the last metadata block
+        ident[13] &= 0x7F;
+
+        // VORBIS_COMMENT block (type 4, last): vendor string, no comments
+        let vendor = APP_NAME.as_bytes();
+        let comment_len = 4 + vendor.len() + 4;
+        let mut comment = BytesMut::with_capacity(4 + comment_len);
+        comment.put_u8(0x80 | 4);
+        comment.put_uint(comment_len as u64, 3);
+        comment.put_u32_le(vendor.len() as u32);
+        comment.put_slice(vendor);
+        comment.put_u32_le(0);
+
+        let mut out = BytesMut::new();
+        self.write_packet(&mut out, &ident, FLAG_BOS, 0);
+        self.write_packet(&mut out, &comment, 0, 0);
+        out.freeze()
+    }
+
+    /// Wraps one native FLAC frame into Ogg page(s).
+    ///
+    /// Returns `None` for data that isn't a FLAC frame, which can't be given
+    /// a granule position and would corrupt the Ogg stream.
+    pub fn frame_pages(&mut self, frame: &[u8]) -> Option<Bytes> {
+        let samples = flac::frame_block_size(frame)?;
+        self.granule += i64::from(samples);
+
+        let mut out = BytesMut::with_capacity(frame.len() + 27 + frame.len() / 255 + 1);
+        self.write_packet(&mut out, frame, 0, self.granule);
+        Some(out.freeze())
+    }
+
+    /// Writes one packet as one or more pages, ending on a page boundary.
+    fn write_packet(&mut self, out: &mut BytesMut, packet: &[u8], flags: u8, granule: i64) {
+        // Lacing: 255-byte segments, then a final segment < 255 (possibly 0)
+        let full_segments = packet.len() / 255;
+        let mut lacing = vec![255u8; full_segments];
+        lacing.push((packet.len() % 255) as u8);
+
+        let mut offset = 0;
+        let mut first = true;
+        for segments in lacing.chunks(MAX_SEGMENTS) {
+            let len: usize = segments.iter().map(|&s| usize::from(s)).sum();
+            // A packet ends on the page whose last lacing value is < 255
+            let completes = segments.last() != Some(&255);
+            let page_flags = if first { flags } else { FLAG_CONTINUED };
+            let page_granule = if completes { granule } else { GRANULE_NONE };
+
+            self.write_page(
+                out,
+                page_flags,
+                page_granule,
+                segments,
+                &packet[offset..offset + len],
+            );
+            offset += len;
+            first = false;
+        }
+    }
+
+    fn write_page(
+        &mut self,
+        out: &mut BytesMut,
+        flags: u8,
+        granule: i64,
+        segments: &[u8],
+        body: &[u8],
+    ) {
+        let start = out.len();
+        out.put_slice(b"OggS");
+        out.put_u8(0); // Version
+        out.put_u8(flags);
+        out.put_i64_le(granule);
+        out.put_u32_le(self.serial);
+        out.put_u32_le(self.sequence);
+        out.put_u32_le(0); // CRC placeholder
+        out.put_u8(segments.len() as u8);
+        out.put_slice(segments);
+        out.put_slice(body);
+
+        let crc = crc32(&out[start..]);
+        out[start + 22..start + 26].copy_from_slice(&crc.to_le_bytes());
+        self.sequence = self.sequence.wrapping_add(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stream::AudioFormat;
+
+    /// Parsed page: (flags, granule, sequence, body).
+    fn parse_pages(mut data: &[u8]) -> Vec<(u8, i64, u32, Vec<u8>)> {
+        let mut pages = Vec::new();
+        while !data.is_empty() {
+            assert_eq!(&data[..4], b"OggS");
+            let flags = data[5];
+            let granule = i64::from_le_bytes(data[6..14].try_into().unwrap());
+            let sequence = u32::from_le_bytes(data[18..22].try_into().unwrap());
+            let stored_crc = u32::from_le_bytes(data[22..26].try_into().unwrap());
+            let n = usize::from(data[26]);
+            let body_len: usize = data[27..27 + n].iter().map(|&s| usize::from(s)).sum();
+            let page_len = 27 + n + body_len;
+
+            let mut copy = data[..page_len].to_vec();
+            copy[22..26].fill(0);
+            assert_eq!(crc32(&copy), stored_crc, "page CRC");
+
+            pages.push((flags, granule, sequence, data[27 + n..page_len].to_vec()));
+            data = &data[page_len..];
+        }
+        pages
+    }
+
+    #[test]
+    fn crc_matches_ogg_reference() {
+        // CRC-32/CKSUM check value without the final XOR
+        assert_eq!(crc32(b""), 0);
+        assert_eq!(crc32(b"123456789"), 0x89A1_897F);
+    }
+
+    #[test]
+    fn header_pages_follow_flac_mapping() {
+        let native = flac::synthesize_stream_header(AudioFormat::default());
+        let mut muxer = OggFlacMuxer::new(7);
+        let pages = parse_pages(&muxer.header_pages(&native));
+
+        assert_eq!(pages.len(), 2);
+        let (flags, granule, sequence, ident) = &pages[0];
+        assert_eq!(*flags, FLAG_BOS);
+        assert_eq!((*granule, *sequence), (0, 0));
+        assert_eq!(&ident[..9], b"\x7FFLAC\x01\x00\x00\x01");
+        assert_eq!(&ident[9..13], b"fLaC");
+        assert_eq!(ident[13], 0x00, "STREAMINFO not last");
+        assert_eq!(&ident[17..], &native[8..]);
+        assert_eq!(ident.len(), 51);
+
+        let (_, _, sequence, comment) = &pages[1];
+        assert_eq!(*sequence, 1);
+        assert_eq!(comment[0], 0x84, "last block, VORBIS_COMMENT");
+    }
+
+    #[test]
+    fn frames_get_one_page_with_running_granule() {
+        let mut muxer = OggFlacMuxer::new(7);
+        let frame = [0xFF, 0xF8, 0xC9, 0x18, 0x00, 0xAA, 0xBB];
+
+        let first = parse_pages(&muxer.frame_pages(&frame).unwrap());
+        let second = parse_pages(&muxer.frame_pages(&frame).unwrap());
+
+        assert_eq!(first.len(), 1);
+        assert_eq!(first[0].1, 4096);
+        assert_eq!(first[0].3, frame);
+        assert_eq!(second[0].1, 8192);
+        assert_eq!(second[0].2, first[0].2 + 1);
+    }
+
+    #[test]
+    fn large_frames_span_continued_pages() {
+        let mut muxer = OggFlacMuxer::new(1);
+        let mut frame = vec![0xFF, 0xF8, 0xC9, 0x18];
+        frame.resize(70_000, 0x55);
+
+        let pages = parse_pages(&muxer.frame_pages(&frame).unwrap());
+        assert_eq!(pages.len(), 2);
+        assert_eq!(pages[0].1, GRANULE_NONE, "packet not finished on page 1");
+        assert_eq!(pages[1].0, FLAG_CONTINUED);
+        assert_eq!(pages[1].1, 4096);
+        let joined: Vec<u8> = pages.iter().flat_map(|p| p.3.clone()).collect();
+        assert_eq!(joined, frame);
+    }
+
+    #[test]
+    fn skips_non_frames() {
+        let mut muxer = OggFlacMuxer::new(1);
+        assert!(muxer.frame_pages(b"not a frame").is_none());
+    }
+}
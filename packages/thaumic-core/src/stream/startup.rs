@@ -0,0 +1,214 @@
+//! Cold-start timeline per speaker.
+//!
+//! Records when each step between a stream being created and a speaker
+//! receiving its first audio happened, so a slow start can be traced to the
+//! stage that took the time: SOAP calls, the speaker connecting back, or the
+//! audio arriving.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::net::IpAddr;
+use std::time::Instant;
+
+use serde::Serialize;
+
+/// Steps of a speaker's cold start, in the order they normally happen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StartupStage {
+    /// Playback was requested for the speaker.
+    PlaybackRequested,
+    /// `SetAVTransportURI` succeeded.
+    TransportUriSet,
+    /// `Play` succeeded.
+    PlayAcknowledged,
+    /// The speaker's first HTTP request for the stream arrived.
+    FirstRequest,
+    /// The first real audio frame was sent to the speaker.
+    FirstAudio,
+}
+
+impl StartupStage {
+    const COUNT: usize = 5;
+
+    fn index(self) -> usize {
+        self as usize
+    }
+}
+
+/// Maximum speakers tracked per stream (prevents unbounded growth from
+/// stray clients).
+const MAX_SPEAKERS: usize = 20;
+
+/// Cold-start timelines for every speaker of one stream.
+pub struct StartupLog {
+    /// When the stream was created; all stages are reported relative to it.
+    created_at: Instant,
+    speakers: parking_lot::Mutex<HashMap<IpAddr, [Option<Instant>; StartupStage::COUNT]>>,
+}
+
+impl StartupLog {
+    /// Creates an empty log for a stream created at `created_at`.
+    pub fn new(created_at: Instant) -> Self {
+        Self {
+            created_at,
+            speakers: parking_lot::Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Records that `stage` happened for a speaker at `at`.
+    ///
+    /// A playback request starts a fresh timeline; every other stage keeps
+    /// its first time, so reconnects don't hide the original start.
+    /// Returns `true` if the stage was recorded.
+    pub fn record(&self, ip: IpAddr, stage: StartupStage, at: Instant) -> bool {
+        let mut speakers = self.speakers.lock();
+        if !speakers.contains_key(&ip) && speakers.len() >= MAX_SPEAKERS {
+            return false;
+        }
+        let timeline = speakers.entry(ip).or_default();
+        if stage == StartupStage::PlaybackRequested {
+            *timeline = Default::default();
+        } else if timeline[stage.index()].is_some() {
+            return false;
+        }
+        timeline[stage.index()] = Some(at);
+        true
+    }
+
+    /// Returns the timeline for one speaker.
+    pub fn report(
+        &self,
+        stream_id: &str,
+        ip: IpAddr,
+        first_frame_at: Option<Instant>,
+    ) -> Option<StartupReport> {
+        let timeline = *self.speakers.lock().get(&ip)?;
+        Some(self.build_report(stream_id, ip, first_frame_at, &timeline))
+    }
+
+    /// Returns the timelines of every speaker, ordered by IP.
+    pub fn reports(&self, stream_id: &str, first_frame_at: Option<Instant>) -> Vec<StartupReport> {
+        let mut reports: Vec<_> = self
+            .speakers
+            .lock()
+            .iter()
+            .map(|(ip, timeline)| self.build_report(stream_id, *ip, first_frame_at, timeline))
+            .collect();
+        reports.sort_by(|a, b| a.speaker_ip.cmp(&b.speaker_ip));
+        reports
+    }
+
+    fn build_report(
+        &self,
+        stream_id: &str,
+        ip: IpAddr,
+        first_frame_at: Option<Instant>,
+        timeline: &[Option<Instant>; StartupStage::COUNT],
+    ) -> StartupReport {
+        let offset = |at: Option<Instant>| {
+            at.map(|at| at.saturating_duration_since(self.created_at).as_millis() as u64)
+        };
+        let stage = |stage: StartupStage| offset(timeline[stage.index()]);
+        StartupReport {
+            stream_id: stream_id.to_string(),
+            speaker_ip: ip.to_string(),
+            first_frame_ms: offset(first_frame_at),
+            playback_requested_ms: stage(StartupStage::PlaybackRequested),
+            transport_uri_set_ms: stage(StartupStage::TransportUriSet),
+            play_acknowledged_ms: stage(StartupStage::PlayAcknowledged),
+            first_request_ms: stage(StartupStage::FirstRequest),
+            first_audio_ms: stage(StartupStage::FirstAudio),
+        }
+    }
+}
+
+/// A speaker's cold start, as milliseconds since its stream was created.
+///
+/// Stages that haven't happened (or were skipped, like SOAP for a speaker
+/// that connected on its own) are `None`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StartupReport {
+    pub stream_id: String,
+    pub speaker_ip: String,
+    /// First audio frame received from the client.
+    pub first_frame_ms: Option<u64>,
+    pub playback_requested_ms: Option<u64>,
+    pub transport_uri_set_ms: Option<u64>,
+    pub play_acknowledged_ms: Option<u64>,
+    /// The speaker's first HTTP request for the stream.
+    pub first_request_ms: Option<u64>,
+    /// First real audio frame sent to the speaker.
+    pub first_audio_ms: Option<u64>,
+}
+
+impl fmt::Display for StartupReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} -> {}:", self.stream_id, self.speaker_ip)?;
+        let stages = [
+            ("first frame", self.first_frame_ms),
+            ("requested", self.playback_requested_ms),
+            ("URI set", self.transport_uri_set_ms),
+            ("playing", self.play_acknowledged_ms),
+            ("GET", self.first_request_ms),
+            ("audio", self.first_audio_ms),
+        ];
+        for (name, ms) in stages {
+            match ms {
+                Some(ms) => write!(f, " {} +{}ms", name, ms)?,
+                None => write!(f, " {} -", name)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    const SPEAKER: IpAddr = IpAddr::V4(std::net::Ipv4Addr::new(192, 168, 1, 50));
+
+    #[test]
+    fn stages_are_reported_relative_to_stream_creation() {
+        let created = Instant::now();
+        let log = StartupLog::new(created);
+        let at = |ms| created + Duration::from_millis(ms);
+
+        log.record(SPEAKER, StartupStage::PlaybackRequested, at(100));
+        log.record(SPEAKER, StartupStage::TransportUriSet, at(250));
+        log.record(SPEAKER, StartupStage::PlayAcknowledged, at(400));
+        log.record(SPEAKER, StartupStage::FirstRequest, at(900));
+        log.record(SPEAKER, StartupStage::FirstAudio, at(1200));
+
+        let report = log.report("s1", SPEAKER, Some(at(40))).unwrap();
+        assert_eq!(report.first_frame_ms, Some(40));
+        assert_eq!(report.transport_uri_set_ms, Some(250));
+        assert_eq!(report.first_audio_ms, Some(1200));
+        assert_eq!(
+            report.to_string(),
+            "s1 -> 192.168.1.50: first frame +40ms requested +100ms URI set +250ms \
+             playing +400ms GET +900ms audio +1200ms"
+        );
+    }
+
+    #[test]
+    fn reconnects_keep_first_times_until_playback_is_requested_again() {
+        let created = Instant::now();
+        let log = StartupLog::new(created);
+        let at = |ms| created + Duration::from_millis(ms);
+
+        assert!(log.record(SPEAKER, StartupStage::FirstRequest, at(500)));
+        assert!(!log.record(SPEAKER, StartupStage::FirstRequest, at(9000)));
+        assert_eq!(
+            log.report("s1", SPEAKER, None).unwrap().first_request_ms,
+            Some(500)
+        );
+
+        log.record(SPEAKER, StartupStage::PlaybackRequested, at(10_000));
+        let report = log.report("s1", SPEAKER, None).unwrap();
+        assert_eq!(report.playback_requested_ms, Some(10_000));
+        assert_eq!(report.first_request_ms, None);
+    }
+}
@@ -34,10 +34,10 @@
 //! pauses, or hardware issues). But it significantly reduces the >300ms gaps caused
 //! by application-level scheduler starvation.
 
+use std::sync::mpsc;
 use std::thread::{self, JoinHandle};
 
 use tokio::runtime::{Builder, Handle};
-use tokio::sync::oneshot;
 use tokio_util::sync::CancellationToken;
 
 /// Number of worker threads for the streaming runtime.
@@ -67,7 +67,9 @@ impl StreamingRuntime {
     /// Returns an error if the runtime thread fails to spawn or initialize.
     /// Priority elevation failures are logged but don't cause errors.
     pub fn new() -> std::io::Result<Self> {
-        let (tx, rx) = oneshot::channel();
+        // A std channel, not a Tokio one: callers may already be inside a
+        // runtime (e.g. `#[tokio::main]`), where blocking on Tokio panics
+        let (tx, rx) = mpsc::sync_channel(1);
         let cancel = CancellationToken::new();
         let cancel_clone = cancel.clone();
 
@@ -105,7 +107,7 @@ impl StreamingRuntime {
 
         // Wait for runtime to be ready
         let handle = rx
-            .blocking_recv()
+            .recv()
             .map_err(|_| std::io::Error::other("Failed to receive streaming runtime handle"))?;
 
         log::info!(
@@ -160,8 +162,9 @@ impl Drop for StreamingRuntime {
 
 /// Elevates the current thread's priority for audio streaming.
 ///
-/// Called by each worker thread on startup via `on_thread_start`.
-fn raise_thread_priority() {
+/// Called by each worker thread on startup via `on_thread_start`, and by
+/// per-stream cadence threads.
+pub(crate) fn raise_thread_priority() {
     #[cfg(target_os = "windows")]
     raise_thread_priority_windows();
 
@@ -0,0 +1,35 @@
+//! Server fixtures shared by the crate's HTTP tests and the loopback
+//! end-to-end test (`tests/loopback.rs`, behind the `e2e` feature).
+
+use std::sync::Arc;
+
+use parking_lot::RwLock;
+
+use crate::api::AppState;
+use crate::artwork::ArtworkConfig;
+use crate::context::NetworkContext;
+use crate::state::Config;
+use crate::{bootstrap_services_with_network, BootstrappedServices};
+
+/// Bootstraps every service for `config` on `network` and returns them with
+/// the app state built on top.
+///
+/// The streaming runtime blocks while starting and stopping, so it's created
+/// via `block_in_place` (tests need the multi-thread runtime) and the
+/// services are leaked rather than dropped in async context.
+pub fn bootstrap_app(
+    config: Config,
+    network: NetworkContext,
+) -> (&'static BootstrappedServices, AppState) {
+    let services = tokio::task::block_in_place(|| {
+        bootstrap_services_with_network(&config, network, tokio::runtime::Handle::current())
+            .expect("bootstrap")
+    });
+    let services: &'static BootstrappedServices = Box::leak(Box::new(services));
+    let state = AppState::new(
+        services,
+        Arc::new(RwLock::new(config)),
+        ArtworkConfig::default(),
+    );
+    (services, state)
+}
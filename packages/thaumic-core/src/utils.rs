@@ -17,13 +17,23 @@ pub fn now_millis() -> u64 {
         .unwrap_or(0)
 }
 
+// ─────────────────────────────────────────────────────────────────────────────
+// Secrets
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Compares secrets without exiting early on the first difference.
+#[must_use]
+pub fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
 // ─────────────────────────────────────────────────────────────────────────────
 // IP Address Validation
 // ─────────────────────────────────────────────────────────────────────────────
 
 use std::net::{IpAddr, Ipv4Addr};
 
-use crate::error::ErrorCode;
+use crate::error::{ErrorCode, Remediation};
 
 /// Error returned when an IP address is not valid for a Sonos speaker.
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -40,6 +50,8 @@ pub enum IpValidationError {
     Multicast,
     /// Link-local address (169.254.x.x).
     LinkLocal,
+    /// Neither an IP address nor a valid hostname.
+    InvalidHostname,
 }
 
 impl ErrorCode for IpValidationError {
@@ -49,6 +61,14 @@ impl ErrorCode for IpValidationError {
     fn code(&self) -> &'static str {
         "invalid_ip"
     }
+
+    fn remediation(&self) -> Option<Remediation> {
+        Some(Remediation::new(
+            "verify_ip_address",
+            "Enter the speaker's IPv4 address as shown in the Sonos app",
+            false,
+        ))
+    }
 }
 
 impl IpValidationError {
@@ -62,6 +82,7 @@ impl IpValidationError {
             Self::Broadcast => "Broadcast addresses cannot be Sonos speakers",
             Self::Multicast => "Multicast addresses cannot be Sonos speakers",
             Self::LinkLocal => "Link-local addresses (169.254.x.x) cannot be Sonos speakers",
+            Self::InvalidHostname => "Invalid IP address or hostname",
         }
     }
 }
@@ -122,10 +143,57 @@ pub fn validate_speaker_ip(ip: &IpAddr) -> Result<Ipv4Addr, IpValidationError> {
     Ok(ipv4)
 }
 
+/// Validates a manual speaker address: an IPv4 address or a hostname
+/// (e.g. `sonos-kitchen.lan`) to be resolved through DNS.
+///
+/// Returns the canonical form for storage: the IP as returned by
+/// [`validate_speaker_ip`], or the hostname in lowercase without a
+/// trailing dot.
+pub fn validate_speaker_host(input: &str) -> Result<String, IpValidationError> {
+    if let Ok(ip) = input.parse::<IpAddr>() {
+        return validate_speaker_ip(&ip).map(|ip| ip.to_string());
+    }
+
+    let host = input
+        .strip_suffix('.')
+        .unwrap_or(input)
+        .to_ascii_lowercase();
+    let valid_label = |label: &str| {
+        (1..=63).contains(&label.len())
+            && !label.starts_with('-')
+            && !label.ends_with('-')
+            && label
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    };
+    // An all-numeric last label would be a malformed IP, not a hostname
+    let numeric_tld = host
+        .rsplit('.')
+        .next()
+        .is_some_and(|tld| tld.chars().all(|c| c.is_ascii_digit()));
+    if host.len() > 253 || numeric_tld || !host.split('.').all(valid_label) {
+        return Err(IpValidationError::InvalidHostname);
+    }
+    Ok(host)
+}
+
+/// Whether a manual speaker address is a hostname rather than an IP.
+#[must_use]
+pub fn is_hostname(address: &str) -> bool {
+    address.parse::<IpAddr>().is_err()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn compares_secrets_exactly() {
+        assert!(constant_time_eq(b"s3cret", b"s3cret"));
+        assert!(!constant_time_eq(b"s3cret", b"s3creT"));
+        assert!(!constant_time_eq(b"s3cret", b"s3cret2"));
+    }
+
     #[test]
     fn test_validate_speaker_ip_valid_private() {
         let ip: IpAddr = "192.168.1.100".parse().unwrap();
@@ -224,4 +292,41 @@ mod tests {
         assert!(IpValidationError::Loopback.message().contains("Loopback"));
         assert!(IpValidationError::LinkLocal.message().contains("169.254"));
     }
+
+    #[test]
+    fn test_validate_speaker_host_accepts_ips_and_hostnames() {
+        assert_eq!(
+            validate_speaker_host("192.168.1.100").unwrap(),
+            "192.168.1.100"
+        );
+        assert_eq!(
+            validate_speaker_host("Sonos-Kitchen.lan.").unwrap(),
+            "sonos-kitchen.lan"
+        );
+        assert_eq!(validate_speaker_host("sonos").unwrap(), "sonos");
+        assert_eq!(
+            validate_speaker_host("127.0.0.1"),
+            Err(IpValidationError::Loopback)
+        );
+    }
+
+    #[test]
+    fn test_validate_speaker_host_rejects_malformed_input() {
+        for input in [
+            "",
+            "192.168.1",
+            "a..b",
+            "-sonos.lan",
+            "sonos kitchen",
+            "sonos/x",
+        ] {
+            assert_eq!(
+                validate_speaker_host(input),
+                Err(IpValidationError::InvalidHostname),
+                "{input}"
+            );
+        }
+        assert!(is_hostname("sonos-kitchen.lan"));
+        assert!(!is_hostname("192.168.1.100"));
+    }
 }
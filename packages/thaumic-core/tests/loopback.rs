@@ -0,0 +1,494 @@
+//! Loopback end-to-end test.
+//!
+//! Boots the full server on loopback, registers a simulated speaker, casts
+//! generated audio through the WebSocket ingest exactly like the extension
+//! does, and checks what the speaker receives over HTTP byte for byte.
+//!
+//! Needs the `e2e` feature and binds speakers to `127.0.0.2:1400` and up:
+//!
+//! ```sh
+//! cargo test -p thaumic-core --features e2e --test loopback
+//! ```
+
+mod sonos_sim;
+
+use std::collections::HashMap;
+use std::f64::consts::TAU;
+use std::net::Ipv4Addr;
+use std::time::Duration;
+
+use futures::{SinkExt, StreamExt};
+use serde_json::{json, Value};
+use thaumic_core::test_support::bootstrap_app;
+use thaumic_core::{start_server, BootstrappedServices, Config, NetworkContext};
+use tokio::net::TcpStream;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::{MaybeTlsStream, WebSocketStream};
+
+use sonos_sim::SonosSim;
+
+/// Interval at which the fake extension sends frames.
+const FRAME_MS: u64 = 20;
+
+/// How long to wait for any single server reply.
+const REPLY_TIMEOUT: Duration = Duration::from_secs(10);
+
+const SAMPLE_RATE: u32 = 48_000;
+const CHANNELS: usize = 2;
+const WAV_HEADER_LEN: usize = 44;
+
+/// Test tone frequency. 997 Hz shares no factor with common sample rates,
+/// so the tone only repeats every second and a dropped or repeated frame
+/// cannot line up with the reference by accident.
+const TONE_HZ: f64 = 997.0;
+
+type WsStream = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Harness
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// A server running on loopback with all background services started.
+struct Server {
+    services: &'static BootstrappedServices,
+    port: u16,
+    task: JoinHandle<()>,
+}
+
+impl Server {
+    async fn boot() -> Self {
+        // Pick a free port up front so the URLs handed to speakers are known
+        let port = std::net::TcpListener::bind("127.0.0.1:0")
+            .and_then(|listener| listener.local_addr())
+            .expect("free port")
+            .port();
+        let config = Config {
+            preferred_port: port,
+            ..Default::default()
+        };
+        let network = NetworkContext::explicit(port, Ipv4Addr::LOCALHOST.into());
+        let (services, state) = bootstrap_app(config, network);
+        services.start_background_tasks();
+
+        let task = tokio::spawn(async move {
+            start_server(state).await.expect("server");
+        });
+
+        let server = Self {
+            services,
+            port,
+            task,
+        };
+        server.wait_until_healthy().await;
+        server
+    }
+
+    async fn wait_until_healthy(&self) {
+        let url = format!("http://127.0.0.1:{}/health", self.port);
+        for _ in 0..100 {
+            if let Ok(response) = reqwest::get(&url).await {
+                if response.status().is_success() {
+                    return;
+                }
+            }
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+        panic!("server did not come up on port {}", self.port);
+    }
+
+    /// Makes a speaker part of the topology, as discovery would.
+    fn register(&self, speaker: &SonosSim) {
+        self.services
+            .sonos_state
+            .groups
+            .write()
+            .push(speaker.zone_group());
+    }
+
+    async fn connect(&self) -> Extension {
+        let url = format!("ws://127.0.0.1:{}/ws", self.port);
+        let (ws, _) = tokio_tungstenite::connect_async(url)
+            .await
+            .expect("WebSocket connect");
+        Extension::new(ws)
+    }
+
+    async fn shutdown(self) {
+        self.services.shutdown().await;
+        self.task.abort();
+    }
+}
+
+/// Plays the part of the browser extension on one WebSocket connection.
+///
+/// Once audio starts, frames are sent at real-time pace from a separate
+/// task; control messages are queued to the same socket in between.
+struct Extension {
+    sink: Option<futures::stream::SplitSink<WsStream, Message>>,
+    control: Option<mpsc::UnboundedSender<Message>>,
+    incoming: futures::stream::SplitStream<WsStream>,
+    pacer: Option<JoinHandle<()>>,
+}
+
+impl Extension {
+    fn new(ws: WsStream) -> Self {
+        let (sink, incoming) = ws.split();
+        Self {
+            sink: Some(sink),
+            control: None,
+            incoming,
+            pacer: None,
+        }
+    }
+
+    async fn send(&mut self, message: Value) {
+        let message = Message::text(message.to_string());
+        match (&mut self.sink, &self.control) {
+            (Some(sink), _) => sink.send(message).await.expect("send"),
+            (None, Some(control)) => control.send(message).expect("send"),
+            (None, None) => unreachable!("socket is always held by one side"),
+        }
+    }
+
+    /// Waits for the next server message of `kind`, skipping broadcasts.
+    async fn expect(&mut self, kind: &str) -> Value {
+        let wait = async {
+            while let Some(message) = self.incoming.next().await {
+                let Ok(Message::Text(text)) = message else {
+                    continue;
+                };
+                let value: Value = serde_json::from_str(&text).expect("JSON message");
+                match value["type"].as_str() {
+                    Some(t) if t == kind => return value,
+                    Some("ERROR" | "PLAYBACK_ERROR") => panic!("server error: {}", value),
+                    _ => {}
+                }
+            }
+            panic!("connection closed while waiting for {}", kind);
+        };
+        tokio::time::timeout(REPLY_TIMEOUT, wait)
+            .await
+            .unwrap_or_else(|_| panic!("no {} within {:?}", kind, REPLY_TIMEOUT))
+    }
+
+    /// Sends the handshake and returns the new stream's ID.
+    async fn handshake(&mut self, encoder_config: Value) -> String {
+        self.send(json!({
+            "type": "HANDSHAKE",
+            "payload": { "encoderConfig": encoder_config },
+        }))
+        .await;
+        let ack = self.expect("HANDSHAKE_ACK").await;
+        ack["payload"]["streamId"]
+            .as_str()
+            .expect("stream ID")
+            .to_string()
+    }
+
+    /// Starts sending `frames`, one every `FRAME_MS`.
+    fn stream(&mut self, frames: Vec<Vec<u8>>) {
+        let mut sink = self.sink.take().expect("already streaming");
+        let (control, mut control_rx) = mpsc::unbounded_channel();
+        self.control = Some(control);
+        self.pacer = Some(tokio::spawn(async move {
+            let mut frames = frames.into_iter();
+            let mut interval = tokio::time::interval(Duration::from_millis(FRAME_MS));
+            loop {
+                tokio::select! {
+                    message = control_rx.recv() => match message {
+                        Some(message) => {
+                            if sink.send(message).await.is_err() {
+                                return;
+                            }
+                        }
+                        None => break,
+                    },
+                    _ = interval.tick() => {
+                        // Past the last frame, keep the stream open until the
+                        // test is done with it
+                        let Some(frame) = frames.next() else {
+                            continue;
+                        };
+                        if sink.send(Message::binary(frame)).await.is_err() {
+                            return;
+                        }
+                    }
+                }
+            }
+            let _ = sink.close().await;
+        }));
+    }
+
+    /// Casts the stream to `speaker` and returns its playback result.
+    async fn start_playback(&mut self, speaker: &SonosSim, metadata: Value) -> Value {
+        self.send(json!({
+            "type": "START_PLAYBACK",
+            "payload": { "speakerIps": [speaker.ip()], "metadata": metadata },
+        }))
+        .await;
+        let results = self.expect("PLAYBACK_RESULTS").await;
+        results["payload"]["results"][0].clone()
+    }
+
+    async fn disconnect(mut self) {
+        self.control = None;
+        if let Some(pacer) = self.pacer.take() {
+            let _ = pacer.await;
+        }
+    }
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Signals
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Interleaved stereo tone: the left channel carries the sine, the right its
+/// inverse, so swapped channels are caught as well.
+fn tone(frames: usize, samples_per_frame: usize) -> Vec<i16> {
+    (0..frames * samples_per_frame)
+        .flat_map(|n| {
+            let t = n as f64 / f64::from(SAMPLE_RATE);
+            let sample = ((TAU * TONE_HZ * t).sin() * 16_000.0).round() as i16;
+            [sample, -sample]
+        })
+        .collect()
+}
+
+/// Deterministic bytes that never repeat within a test run.
+fn noise(len: usize) -> Vec<u8> {
+    let mut state: u32 = 0x9E37_79B9;
+    (0..len)
+        .map(|_| {
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            (state >> 24) as u8
+        })
+        .collect()
+}
+
+fn pcm16_bytes(samples: &[i16]) -> Vec<u8> {
+    samples.iter().flat_map(|s| s.to_le_bytes()).collect()
+}
+
+/// Finds where `window` starts in `haystack`.
+fn find<T: PartialEq>(haystack: &[T], window: &[T]) -> Option<usize> {
+    haystack.windows(window.len()).position(|w| w == window)
+}
+
+/// Finds the first sample-frame position in `received` where a full frame of
+/// `reference` begins, and where in `reference` it comes from.
+fn align(reference: &[i16], received: &[i16], frame_samples: usize) -> Option<(usize, usize)> {
+    // Index the reference by short runs so every candidate is a lookup,
+    // not a scan
+    const KEY: usize = 8;
+    let mut index: HashMap<&[i16], Vec<usize>> = HashMap::new();
+    for at in (0..reference.len() - frame_samples).step_by(CHANNELS) {
+        index.entry(&reference[at..at + KEY]).or_default().push(at);
+    }
+    (0..received.len().saturating_sub(frame_samples))
+        .step_by(CHANNELS)
+        .find_map(|at| {
+            let frame = &received[at..at + frame_samples];
+            index
+                .get(&frame[..KEY])?
+                .iter()
+                .find(|&&offset| &reference[offset..offset + frame_samples] == frame)
+                .map(|&offset| (at, offset))
+        })
+}
+
+/// Splits an ICY stream into audio and metadata blocks.
+///
+/// Returns the audio bytes and every metadata string, with padding removed.
+/// A trailing partial interval is returned as audio.
+fn split_icy(body: &[u8], metaint: usize) -> (Vec<u8>, Vec<String>) {
+    let mut audio = Vec::with_capacity(body.len());
+    let mut metadata = Vec::new();
+    let mut rest = body;
+    while rest.len() > metaint {
+        audio.extend_from_slice(&rest[..metaint]);
+        let meta_len = rest[metaint] as usize * 16;
+        let block = &rest[metaint + 1..];
+        assert!(block.len() >= meta_len, "truncated metadata block");
+        let text = String::from_utf8(block[..meta_len].to_vec()).expect("UTF-8 metadata");
+        metadata.push(text.trim_end_matches('\0').to_string());
+        rest = &block[meta_len..];
+    }
+    audio.extend_from_slice(rest);
+    (audio, metadata)
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Tests
+// ─────────────────────────────────────────────────────────────────────────────
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn pcm_reaches_the_speaker_sample_accurate() {
+    let samples_per_frame = (SAMPLE_RATE as u64 * FRAME_MS / 1000) as usize;
+    let frame_bytes = samples_per_frame * CHANNELS * 2;
+    // Five seconds sent; two seconds read, after up to a second of setup
+    let reference = tone(250, samples_per_frame);
+    let captured_samples = 2 * SAMPLE_RATE as usize * CHANNELS;
+
+    let server = Server::boot().await;
+    let mut speaker = SonosSim::start(Ipv4Addr::new(127, 0, 0, 2), {
+        WAV_HEADER_LEN + captured_samples * 2
+    })
+    .await
+    .expect("bind 127.0.0.2:1400");
+    server.register(&speaker);
+
+    let mut extension = server.connect().await;
+    let stream_id = extension
+        .handshake(json!({
+            "codec": "pcm",
+            "sampleRate": SAMPLE_RATE,
+            "channels": CHANNELS,
+            "bitsPerSample": 16,
+            "frameSizeSamples": samples_per_frame,
+            "streamingBufferMs": 500,
+        }))
+        .await;
+    extension.stream(
+        pcm16_bytes(&reference)
+            .chunks(frame_bytes)
+            .map(<[u8]>::to_vec)
+            .collect(),
+    );
+    extension.expect("STREAM_READY").await;
+
+    let result = extension
+        .start_playback(&speaker, json!({ "title": "Tone" }))
+        .await;
+    assert_eq!(result["success"], true, "playback failed: {}", result);
+
+    let fetched = speaker.fetched().await.expect("speaker fetch");
+    assert!(
+        fetched
+            .transport_uri
+            .ends_with(&format!("/stream/{}/live.wav", stream_id)),
+        "unexpected transport URI {}",
+        fetched.transport_uri
+    );
+    assert_eq!(fetched.headers["content-type"], "audio/wav");
+    assert!(
+        fetched.headers.get("icy-metaint").is_none(),
+        "ICY metadata must not be injected into PCM"
+    );
+
+    // WAV header describes the stream as sent
+    let header = &fetched.body[..WAV_HEADER_LEN];
+    assert_eq!(&header[..4], b"RIFF");
+    assert_eq!(&header[8..16], b"WAVEfmt ");
+    assert_eq!(
+        u16::from_le_bytes([header[22], header[23]]),
+        CHANNELS as u16
+    );
+    assert_eq!(
+        u32::from_le_bytes([header[24], header[25], header[26], header[27]]),
+        SAMPLE_RATE
+    );
+    assert_eq!(u16::from_le_bytes([header[34], header[35]]), 16);
+    assert_eq!(&header[36..40], b"data");
+
+    // Playback may open with silence or a fade while the queue fills; from
+    // the first exact frame on, every sample must follow the reference
+    let received: Vec<i16> = fetched.body[WAV_HEADER_LEN..]
+        .chunks_exact(2)
+        .map(|b| i16::from_le_bytes([b[0], b[1]]))
+        .collect();
+    let (start, offset) = align(&reference, &received, samples_per_frame * CHANNELS)
+        .expect("tone never appeared in the stream");
+    assert!(
+        start <= SAMPLE_RATE as usize / 2 * CHANNELS,
+        "tone started {}ms into the stream",
+        start / CHANNELS * 1000 / SAMPLE_RATE as usize
+    );
+    let matched = received[start..]
+        .iter()
+        .zip(&reference[offset..])
+        .take_while(|(got, want)| got == want)
+        .count();
+    assert_eq!(
+        matched,
+        received.len() - start,
+        "stream diverged from the tone {}ms after it started",
+        matched / CHANNELS * 1000 / SAMPLE_RATE as usize
+    );
+
+    let actions = speaker.actions();
+    assert!(actions.starts_with(&["SetAVTransportURI".into(), "Play".into()]));
+
+    extension.disconnect().await;
+    server.shutdown().await;
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn compressed_streams_carry_icy_metadata() {
+    const CHUNK: usize = 1_000;
+    const METAINT: usize = 8_192;
+    let reference = noise(CHUNK * 250);
+    let title = "StreamTitle='Thaumic - Loopback';";
+    // Enough for four metadata blocks
+    let block_len = 1 + title.len().div_ceil(16) * 16;
+    let fetch_bytes = 4 * (METAINT + block_len);
+
+    let server = Server::boot().await;
+    let mut speaker = SonosSim::start(Ipv4Addr::new(127, 0, 0, 3), fetch_bytes)
+        .await
+        .expect("bind 127.0.0.3:1400");
+    server.register(&speaker);
+
+    let mut extension = server.connect().await;
+    let stream_id = extension
+        .handshake(json!({ "codec": "mp3", "bitrate": 128 }))
+        .await;
+    extension.stream(reference.chunks(CHUNK).map(<[u8]>::to_vec).collect());
+    extension.expect("STREAM_READY").await;
+
+    let result = extension
+        .start_playback(
+            &speaker,
+            json!({ "title": "Loopback", "artist": "Thaumic" }),
+        )
+        .await;
+    assert_eq!(result["success"], true, "playback failed: {}", result);
+
+    let fetched = speaker.fetched().await.expect("speaker fetch");
+    assert!(
+        fetched
+            .transport_uri
+            .starts_with("x-rincon-mp3radio://127.0.0.1:"),
+        "unexpected transport URI {}",
+        fetched.transport_uri
+    );
+    assert!(fetched
+        .transport_uri
+        .ends_with(&format!("/stream/{}/live", stream_id)));
+    assert_eq!(fetched.headers["content-type"], "audio/mpeg");
+    assert_eq!(fetched.headers["icy-metaint"], METAINT.to_string());
+
+    let (audio, metadata) = split_icy(&fetched.body, METAINT);
+    assert!(
+        metadata.len() >= 4,
+        "only {} metadata blocks",
+        metadata.len()
+    );
+    assert!(
+        metadata.iter().all(|m| m == title),
+        "metadata: {:?}",
+        metadata
+    );
+
+    // Audio between the blocks is the ingested bytes, untouched and in
+    // order, starting on a frame boundary
+    let offset = find(&reference, &audio[..64]).expect("audio not from the ingest");
+    assert_eq!(offset % CHUNK, 0, "audio started mid-frame");
+    assert_eq!(audio, reference[offset..offset + audio.len()]);
+
+    extension.disconnect().await;
+    server.shutdown().await;
+}
@@ -0,0 +1,252 @@
+//! Minimal simulated Sonos speaker for end-to-end tests.
+//!
+//! Answers the UPnP requests the server sends while casting (AVTransport
+//! SOAP actions and GENA subscriptions) and, once told to play, fetches the
+//! stream URI the way a speaker does, asking for ICY metadata like real
+//! speakers always do. The fetched bytes are handed back to the test for
+//! verification.
+//!
+//! The server always talks to speakers on port 1400, so each simulated
+//! speaker binds its own loopback address (`127.0.0.2`, `127.0.0.3`, ...).
+//! Linux routes all of `127.0.0.0/8` to loopback; macOS needs an alias per
+//! address (`sudo ifconfig lo0 alias 127.0.0.2`).
+
+use std::net::{Ipv4Addr, SocketAddr};
+use std::sync::Arc;
+use std::time::Duration;
+
+use axum::body::Bytes;
+use axum::extract::State;
+use axum::http::{HeaderMap, Method, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::Router;
+use parking_lot::Mutex;
+use thaumic_core::sonos::types::{DeviceRole, ZoneGroupMember};
+use thaumic_core::ZoneGroup;
+use tokio::sync::oneshot;
+use tokio_util::sync::CancellationToken;
+
+/// Port every Sonos speaker listens on.
+const SONOS_PORT: u16 = 1400;
+
+/// How long a fetch may take before the speaker gives up.
+const FETCH_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// A stream fetched by the simulated speaker.
+#[derive(Debug)]
+pub struct Fetched {
+    /// URI the speaker was told to play, as sent in `SetAVTransportURI`.
+    pub transport_uri: String,
+    /// Response headers of the stream request.
+    pub headers: HeaderMap,
+    /// Raw response body, exactly as received.
+    pub body: Vec<u8>,
+}
+
+/// Shared state of one simulated speaker.
+struct Speaker {
+    /// Body bytes to read before hanging up.
+    fetch_bytes: usize,
+    /// SOAP actions received, in order.
+    actions: Mutex<Vec<String>>,
+    /// Last URI set through `SetAVTransportURI`.
+    transport_uri: Mutex<Option<String>>,
+    /// Resolved once the first fetch after `Play` completes.
+    fetched: Mutex<Option<oneshot::Sender<Result<Fetched, String>>>>,
+}
+
+/// A simulated speaker serving on `<ip>:1400` until dropped.
+pub struct SonosSim {
+    ip: Ipv4Addr,
+    speaker: Arc<Speaker>,
+    fetched: Option<oneshot::Receiver<Result<Fetched, String>>>,
+    cancel: CancellationToken,
+}
+
+impl SonosSim {
+    /// Starts a speaker on `ip:1400` that reads `fetch_bytes` of the stream
+    /// it is told to play.
+    pub async fn start(ip: Ipv4Addr, fetch_bytes: usize) -> std::io::Result<Self> {
+        let listener = tokio::net::TcpListener::bind(SocketAddr::from((ip, SONOS_PORT))).await?;
+        let (fetched_tx, fetched_rx) = oneshot::channel();
+        let speaker = Arc::new(Speaker {
+            fetch_bytes,
+            actions: Mutex::new(Vec::new()),
+            transport_uri: Mutex::new(None),
+            fetched: Mutex::new(Some(fetched_tx)),
+        });
+
+        let cancel = CancellationToken::new();
+        let app = Router::new()
+            .fallback(handle_request)
+            .with_state(Arc::clone(&speaker));
+        let shutdown = cancel.clone();
+        tokio::spawn(async move {
+            let _ = axum::serve(listener, app)
+                .with_graceful_shutdown(shutdown.cancelled_owned())
+                .await;
+        });
+
+        Ok(Self {
+            ip,
+            speaker,
+            fetched: Some(fetched_rx),
+            cancel,
+        })
+    }
+
+    /// Returns the speaker's IP address as the server knows it.
+    pub fn ip(&self) -> String {
+        self.ip.to_string()
+    }
+
+    /// Returns the speaker's UUID.
+    pub fn uuid(&self) -> String {
+        format!("RINCON_SIM{:08X}01400", u32::from(self.ip))
+    }
+
+    /// Returns a single-speaker zone group for registering the speaker.
+    pub fn zone_group(&self) -> ZoneGroup {
+        let member = ZoneGroupMember {
+            uuid: self.uuid(),
+            ip: self.ip(),
+            zone_name: format!("Sim {}", self.ip),
+            model: "one".to_string(),
+            role: DeviceRole::Player,
+            bond: None,
+            wired: None,
+        };
+        ZoneGroup {
+            id: format!("{}:1", member.uuid),
+            name: member.zone_name.clone(),
+            label: member.zone_name.clone(),
+            room_count: 1,
+            coordinator_uuid: member.uuid.clone(),
+            coordinator_ip: member.ip.clone(),
+            members: vec![member],
+        }
+    }
+
+    /// Returns the SOAP actions received so far, in order.
+    pub fn actions(&self) -> Vec<String> {
+        self.speaker.actions.lock().clone()
+    }
+
+    /// Waits for the speaker to finish fetching the stream it was told to play.
+    ///
+    /// # Panics
+    /// Panics if called twice.
+    pub async fn fetched(&mut self) -> Result<Fetched, String> {
+        let rx = self.fetched.take().expect("stream already taken");
+        rx.await
+            .map_err(|_| "speaker was never told to play".to_string())?
+    }
+}
+
+impl Drop for SonosSim {
+    fn drop(&mut self) {
+        self.cancel.cancel();
+    }
+}
+
+/// Answers every request the speaker receives.
+async fn handle_request(
+    State(speaker): State<Arc<Speaker>>,
+    method: Method,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Response {
+    // GENA: accept any subscription, never notify
+    if method.as_str() == "SUBSCRIBE" {
+        return (
+            StatusCode::OK,
+            [("SID", "uuid:sim-subscription"), ("TIMEOUT", "Second-1800")],
+        )
+            .into_response();
+    }
+    if method.as_str() == "UNSUBSCRIBE" {
+        return StatusCode::OK.into_response();
+    }
+
+    let Some((service, action)) = headers
+        .get("soapaction")
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.trim_matches('"').split_once('#'))
+        .map(|(service, action)| (service.to_string(), action.to_string()))
+    else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+    speaker.actions.lock().push(action.clone());
+
+    let body = String::from_utf8_lossy(&body);
+    match action.as_str() {
+        "SetAVTransportURI" => {
+            let uri = element_text(&body, "CurrentURI")
+                .map(|uri| html_escape::decode_html_entities(uri).into_owned());
+            *speaker.transport_uri.lock() = uri;
+        }
+        "Play" => {
+            let uri = speaker.transport_uri.lock().clone();
+            let done = speaker.fetched.lock().take();
+            if let (Some(uri), Some(done)) = (uri, done) {
+                let fetch_bytes = speaker.fetch_bytes;
+                tokio::spawn(async move {
+                    let _ = done.send(fetch(uri, fetch_bytes).await);
+                });
+            }
+        }
+        _ => {}
+    }
+
+    soap_response(&service, &action)
+}
+
+/// Fetches a stream the way a speaker does, after undoing the Sonos scheme.
+async fn fetch(transport_uri: String, fetch_bytes: usize) -> Result<Fetched, String> {
+    let url = transport_uri.replace("x-rincon-mp3radio://", "http://");
+    let request = reqwest::Client::new().get(&url).header("Icy-MetaData", "1");
+
+    let read = async {
+        let mut response = request.send().await.map_err(|e| e.to_string())?;
+        if !response.status().is_success() {
+            return Err(format!("stream request failed: {}", response.status()));
+        }
+        let headers = response.headers().clone();
+        let mut body = Vec::with_capacity(fetch_bytes);
+        while body.len() < fetch_bytes {
+            match response.chunk().await.map_err(|e| e.to_string())? {
+                Some(chunk) => body.extend_from_slice(&chunk),
+                None => return Err(format!("stream ended after {} bytes", body.len())),
+            }
+        }
+        Ok(Fetched {
+            transport_uri: transport_uri.clone(),
+            headers,
+            body,
+        })
+    };
+
+    tokio::time::timeout(FETCH_TIMEOUT, read)
+        .await
+        .map_err(|_| format!("timed out fetching {}", url))?
+}
+
+/// Returns the text of the first `<name>` element in a SOAP body.
+fn element_text<'a>(xml: &'a str, name: &str) -> Option<&'a str> {
+    let start = xml.find(&format!("<{}>", name))? + name.len() + 2;
+    let end = start + xml[start..].find(&format!("</{}>", name))?;
+    Some(&xml[start..end])
+}
+
+/// Builds an empty success response for a SOAP action.
+fn soap_response(service: &str, action: &str) -> Response {
+    let body = format!(
+        r#"<?xml version="1.0"?><s:Envelope xmlns:s="http://schemas.xmlsoap.org/soap/envelope/" s:encodingStyle="http://schemas.xmlsoap.org/soap/encoding/"><s:Body><u:{action}Response xmlns:u="{service}"></u:{action}Response></s:Body></s:Envelope>"#
+    );
+    (
+        StatusCode::OK,
+        [("Content-Type", "text/xml; charset=\"utf-8\"")],
+        body,
+    )
+        .into_response()
+}